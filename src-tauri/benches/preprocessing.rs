@@ -0,0 +1,96 @@
+/*!
+预处理基准
+1080p 输入下对比原先的逐像素标量实现（Lanczos3 缩放 + 逐像素写入三个通道）与当前的
+双线性 letterbox（复用画布）+ 按行并行的 CHW 转换，以及批量推理时 4 帧逐张与并行组批的耗时，
+用于确认预处理提速没有回退：
+
+```text
+cargo bench --bench preprocessing
+```
+*/
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use image::{imageops, Rgb, RgbImage};
+
+#[allow(dead_code)]
+#[path = "../src/yolo/preprocessing.rs"]
+mod preprocessing;
+
+const INPUT_SIZE: (u32, u32) = (640, 640);
+
+/// 1920x1080 的渐变测试图
+fn frame_1080p() -> RgbImage {
+    RgbImage::from_fn(1920, 1080, |x, y| Rgb([(x % 256) as u8, (y % 256) as u8, ((x + y) % 256) as u8]))
+}
+
+/// 原先的标量实现
+fn scalar_letterbox(img: &RgbImage, input_size: (u32, u32)) -> RgbImage {
+    let transform = preprocessing::ResizeTransform::new(img.dimensions(), input_size, preprocessing::ResizeStrategy::Letterbox);
+    let resized = imageops::resize(img, transform.new_size.0, transform.new_size.1, imageops::FilterType::Lanczos3);
+    let mut canvas = RgbImage::from_pixel(input_size.0, input_size.1, preprocessing::PAD_COLOR);
+    imageops::replace(&mut canvas, &resized, transform.paste.0 as i64, transform.paste.1 as i64);
+    canvas
+}
+
+fn scalar_to_chw(img: &RgbImage, out: &mut [f32]) {
+    let plane = img.width() as usize * img.height() as usize;
+    for (i, pixel) in img.pixels().enumerate() {
+        out[i] = pixel[0] as f32 / 255.0;
+        out[plane + i] = pixel[1] as f32 / 255.0;
+        out[2 * plane + i] = pixel[2] as f32 / 255.0;
+    }
+}
+
+fn bench_preprocessing(c: &mut Criterion) {
+    let frame = frame_1080p();
+    let config = preprocessing::ResizeConfig::default();
+    let mut out = vec![0.0f32; 3 * INPUT_SIZE.0 as usize * INPUT_SIZE.1 as usize];
+
+    let mut group = c.benchmark_group("preprocess_1080p");
+    group.bench_function("scalar", |b| {
+        b.iter(|| {
+            let resized = scalar_letterbox(black_box(&frame), INPUT_SIZE);
+            scalar_to_chw(&resized, &mut out);
+            black_box(&out);
+        })
+    });
+    let mut canvas = RgbImage::new(INPUT_SIZE.0, INPUT_SIZE.1);
+    group.bench_function("parallel", |b| {
+        b.iter(|| {
+            preprocessing::resize_into(black_box(&frame), INPUT_SIZE, &config, &mut canvas);
+            preprocessing::to_chw(&canvas, &mut out);
+            black_box(&out);
+        })
+    });
+    group.finish();
+
+    // 单独对比 CHW 转换
+    let mut group = c.benchmark_group("to_chw_640");
+    group.bench_function("scalar", |b| b.iter(|| scalar_to_chw(black_box(&canvas), &mut out)));
+    group.bench_function("parallel", |b| b.iter(|| preprocessing::to_chw(black_box(&canvas), &mut out)));
+    group.finish();
+
+    // 4 帧组成 [4, 3, H, W] 批输入
+    let frames = vec![frame.clone(); 4];
+    let mut batch_out = vec![0.0f32; 4 * out.len()];
+    let mut group = c.benchmark_group("batch4_1080p");
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            for (img, out) in frames.iter().zip(batch_out.chunks_exact_mut(out.len())) {
+                preprocessing::resize_into(black_box(img), INPUT_SIZE, &config, &mut canvas);
+                preprocessing::to_chw(&canvas, out);
+            }
+            black_box(&batch_out);
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| {
+            preprocessing::batch_to_chw(black_box(&frames), INPUT_SIZE, &config, &mut batch_out);
+            black_box(&batch_out);
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_preprocessing);
+criterion_main!(benches);
@@ -0,0 +1,283 @@
+/*!
+负载/温度自适应帧率模块
+无风扇工控机长时间运行后CPU会降频，持续监测推理延迟与CPU温度，
+在跟不上时主动降低处理帧率（并发出事件、设置状态标志），而不是悄悄积压延迟
+*/
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::error::DetectionError;
+
+/// 帧率调整事件
+pub const EVENT_RATE_ADJUSTED: &str = "performance://rate-adjusted";
+
+/// 监测周期
+const MONITOR_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 参与计算的最近延迟样本数
+const LATENCY_WINDOW: usize = 120;
+
+/// 自适应帧率配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveRateConfig {
+    pub enabled: bool,
+    pub target_fps: f32,      // 正常情况下的处理帧率
+    pub min_fps: f32,         // 降频下限
+    pub max_temp_c: f32,      // 超过该温度视为过热
+    pub sustained_secs: u32,  // 过载/恢复需持续的时长才调整帧率
+}
+
+impl Default for AdaptiveRateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target_fps: 15.0,
+            min_fps: 2.0,
+            max_temp_c: 85.0,
+            sustained_secs: 30,
+        }
+    }
+}
+
+/// 降频原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottleReason {
+    Latency,     // 推理延迟超过帧间隔
+    Temperature, // CPU温度过高
+}
+
+/// 自适应帧率状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveRateStatus {
+    pub throttled: bool,
+    pub reason: Option<ThrottleReason>,
+    pub current_fps: f32,
+    pub target_fps: f32,
+    pub avg_latency_ms: Option<f64>,
+    pub cpu_temp_c: Option<f32>,
+    pub adjusted_at: Option<String>,
+}
+
+struct RateRuntime {
+    current_fps: f32,
+    latencies: VecDeque<u64>,
+    overloaded_since: Option<std::time::Instant>,
+    healthy_since: Option<std::time::Instant>,
+    reason: Option<ThrottleReason>,
+    cpu_temp_c: Option<f32>,
+    adjusted_at: Option<String>,
+    last_frame: Option<std::time::Instant>,
+}
+
+/// 自适应帧率控制器（Tauri托管状态）
+pub struct AdaptiveRateController {
+    config: RwLock<AdaptiveRateConfig>,
+    runtime: Mutex<RateRuntime>,
+}
+
+impl Default for AdaptiveRateController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdaptiveRateController {
+    pub fn new() -> Self {
+        let config = AdaptiveRateConfig::default();
+        Self {
+            runtime: Mutex::new(RateRuntime {
+                current_fps: config.target_fps,
+                latencies: VecDeque::with_capacity(LATENCY_WINDOW),
+                overloaded_since: None,
+                healthy_since: None,
+                reason: None,
+                cpu_temp_c: None,
+                adjusted_at: None,
+                last_frame: None,
+            }),
+            config: RwLock::new(config),
+        }
+    }
+
+    /// 记录一次推理耗时
+    pub fn observe_latency(&self, latency_ms: u64) {
+        let mut runtime = self.runtime.lock();
+        if runtime.latencies.len() >= LATENCY_WINDOW {
+            runtime.latencies.pop_front();
+        }
+        runtime.latencies.push_back(latency_ms);
+    }
+
+    /// 帧门控：按当前处理帧率判断新到的帧是否需要处理（实时管线调用）
+    pub fn should_process(&self) -> bool {
+        if !self.config.read().enabled {
+            return true;
+        }
+        let mut runtime = self.runtime.lock();
+        let interval = Duration::from_secs_f32(1.0 / runtime.current_fps.max(0.1));
+        let now = std::time::Instant::now();
+        match runtime.last_frame {
+            Some(last) if now.duration_since(last) < interval => false,
+            _ => {
+                runtime.last_frame = Some(now);
+                true
+            }
+        }
+    }
+
+    pub fn status(&self) -> AdaptiveRateStatus {
+        let config = self.config.read().clone();
+        let runtime = self.runtime.lock();
+        AdaptiveRateStatus {
+            throttled: runtime.current_fps < config.target_fps,
+            reason: runtime.reason,
+            current_fps: runtime.current_fps,
+            target_fps: config.target_fps,
+            avg_latency_ms: average(&runtime.latencies),
+            cpu_temp_c: runtime.cpu_temp_c,
+            adjusted_at: runtime.adjusted_at.clone(),
+        }
+    }
+
+    /// 评估当前负载，帧率发生变化时返回新状态
+    fn evaluate(&self) -> Option<AdaptiveRateStatus> {
+        let config = self.config.read().clone();
+        let temp = read_cpu_temperature();
+        let now = std::time::Instant::now();
+        let sustained = Duration::from_secs(config.sustained_secs as u64);
+
+        let changed = {
+            let mut runtime = self.runtime.lock();
+            runtime.cpu_temp_c = temp;
+
+            if !config.enabled {
+                let changed = runtime.current_fps != config.target_fps;
+                runtime.current_fps = config.target_fps;
+                runtime.reason = None;
+                changed
+            } else {
+                let frame_budget_ms = 1000.0 / runtime.current_fps as f64;
+                let avg_latency = average(&runtime.latencies);
+
+                let overload_reason = if temp.is_some_and(|t| t >= config.max_temp_c) {
+                    Some(ThrottleReason::Temperature)
+                } else if avg_latency.is_some_and(|l| l > frame_budget_ms * 0.9) {
+                    Some(ThrottleReason::Latency)
+                } else {
+                    None
+                };
+                // 恢复需留有余量，避免在临界点来回切换
+                let healthy = overload_reason.is_none()
+                    && temp.is_none_or(|t| t < config.max_temp_c - 5.0)
+                    && avg_latency.is_none_or(|l| l < frame_budget_ms * 0.6);
+
+                let previous_fps = runtime.current_fps;
+                if let Some(reason) = overload_reason {
+                    runtime.healthy_since = None;
+                    let since = *runtime.overloaded_since.get_or_insert(now);
+                    if now.duration_since(since) >= sustained && runtime.current_fps > config.min_fps {
+                        runtime.current_fps = (runtime.current_fps * 0.8).max(config.min_fps);
+                        runtime.reason = Some(reason);
+                        runtime.overloaded_since = Some(now);
+                    }
+                } else if healthy {
+                    runtime.overloaded_since = None;
+                    let since = *runtime.healthy_since.get_or_insert(now);
+                    if now.duration_since(since) >= sustained && runtime.current_fps < config.target_fps {
+                        runtime.current_fps = (runtime.current_fps * 1.1).min(config.target_fps);
+                        runtime.healthy_since = Some(now);
+                        if runtime.current_fps >= config.target_fps {
+                            runtime.reason = None;
+                        }
+                    }
+                } else {
+                    runtime.overloaded_since = None;
+                    runtime.healthy_since = None;
+                }
+                runtime.current_fps != previous_fps
+            }
+        };
+
+        if changed {
+            self.runtime.lock().adjusted_at = Some(crate::storage::now_rfc3339());
+            Some(self.status())
+        } else {
+            None
+        }
+    }
+}
+
+fn average(values: &VecDeque<u64>) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<u64>() as f64 / values.len() as f64)
+    }
+}
+
+/// 读取CPU温度（Linux thermal_zone，取最高值；其他平台返回空）
+fn read_cpu_temperature() -> Option<f32> {
+    let entries = std::fs::read_dir("/sys/class/thermal").ok()?;
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("thermal_zone"))
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join("temp")).ok())
+        .filter_map(|value| value.trim().parse::<f32>().ok())
+        .map(|milli| milli / 1000.0)
+        .reduce(f32::max)
+}
+
+/// 启动后台监测任务
+pub fn spawn_monitor(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(MONITOR_INTERVAL).await;
+            let controller = app.state::<AdaptiveRateController>();
+            if let Some(status) = controller.evaluate() {
+                tracing::info!(
+                    "🌡️ 处理帧率调整为 {:.1} FPS (目标 {:.1}, 原因: {:?}, 温度: {:?})",
+                    status.current_fps, status.target_fps, status.reason, status.cpu_temp_c
+                );
+                let _ = app.emit(EVENT_RATE_ADJUSTED, status);
+            }
+        }
+    });
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 更新自适应帧率配置
+#[tauri::command]
+pub async fn set_adaptive_rate_config(
+    controller: State<'_, AdaptiveRateController>,
+    config: AdaptiveRateConfig
+) -> Result<AdaptiveRateStatus, DetectionError> {
+    if !(config.min_fps > 0.0 && config.min_fps <= config.target_fps) {
+        return Err(DetectionError::InvalidInput("帧率下限必须大于0且不超过目标帧率".to_string()));
+    }
+    {
+        let mut runtime = controller.runtime.lock();
+        runtime.current_fps = runtime.current_fps.clamp(config.min_fps, config.target_fps);
+        if !config.enabled {
+            runtime.current_fps = config.target_fps;
+            runtime.reason = None;
+        }
+    }
+    *controller.config.write() = config;
+    Ok(controller.status())
+}
+
+/// 获取自适应帧率状态
+#[tauri::command]
+pub async fn get_adaptive_rate_status(
+    controller: State<'_, AdaptiveRateController>
+) -> Result<AdaptiveRateStatus, DetectionError> {
+    Ok(controller.status())
+}
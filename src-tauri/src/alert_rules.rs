@@ -0,0 +1,333 @@
+/*!
+告警规则引擎模块
+每次检测结果按配置的规则逐条求值，满足条件的规则生成一条结构化告警（见 `alerts` 模块）
+规则保存在应用数据目录下的JSON文件中，条件可组合：
+
+- `class` / `min_confidence` / `zone`：筛选指定类别、置信度不低于阈值、中心点位于指定检测区域的检测框，筛选结果非空时成立
+- `min_size`：筛选较长边不小于指定毫米数的检测框（需标定像素/毫米比例，未标定时不成立）
+- `count`：当前检测框数量在 [min, max] 范围内时成立
+- `duration`：子条件持续成立指定秒数后才成立（按规则与输入源分别计时）
+- `and`：依次求值子条件，后一个条件只作用于前一个条件筛选出的检测框
+  （如 `and[class 异常, min_confidence 0.7, count ≥ 2]` 表示至少2个置信度≥0.7的异常目标）
+- `or`：任一子条件成立即成立，匹配的检测框取并集
+*/
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{anyhow, Result};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::alerts::{AlertSeverity, ABNORMAL_CLASS_NAME};
+use crate::detection_config;
+use crate::error::{self, DetectionError};
+use crate::yolo::roi::{self, RoiPolygon};
+use crate::yolo::{DetectionResult, PhysicalSize, YoloDetection};
+
+/// 告警规则配置文件名（位于应用数据目录）
+pub const RULES_FILE_NAME: &str = "alert_rules.json";
+
+/// 规则条件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleCondition {
+    Class { names: Vec<String> },
+    MinConfidence { value: f32 },
+    Count {
+        #[serde(default)]
+        min: u32,
+        #[serde(default)]
+        max: Option<u32>,
+    },
+    Zone { name: String }, // 检测区域（ROI）名称
+    MinSize { mm: f32 },   // 检测框较长边的最小物理尺寸
+    Duration { secs: f64, condition: Box<RuleCondition> },
+    And { conditions: Vec<RuleCondition> },
+    Or { conditions: Vec<RuleCondition> },
+}
+
+impl RuleCondition {
+    fn validate(&self) -> Result<()> {
+        match self {
+            RuleCondition::Class { names } if names.is_empty() => Err(anyhow!("类别条件至少需要一个类别")),
+            RuleCondition::MinConfidence { value } if !(0.0..=1.0).contains(value) => {
+                Err(anyhow!("置信度条件必须在 0-1 之间: {}", value))
+            }
+            RuleCondition::Count { min, max: Some(max) } if max < min => {
+                Err(anyhow!("数量条件的上限 {} 小于下限 {}", max, min))
+            }
+            RuleCondition::Zone { name } if name.trim().is_empty() => Err(anyhow!("区域条件的区域名称不能为空")),
+            RuleCondition::MinSize { mm } if !(mm.is_finite() && *mm > 0.0) => Err(anyhow!("尺寸条件必须为正数: {}", mm)),
+            RuleCondition::Duration { secs, .. } if *secs <= 0.0 => Err(anyhow!("持续时长必须大于0: {}", secs)),
+            RuleCondition::Duration { condition, .. } => condition.validate(),
+            RuleCondition::And { conditions } | RuleCondition::Or { conditions } => {
+                conditions.iter().try_for_each(RuleCondition::validate)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 告警规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub severity: AlertSeverity,
+    #[serde(default)]
+    pub critical_confidence: Option<f32>, // 匹配的检测框最高置信度达到该值时升级为严重告警
+    pub condition: RuleCondition,
+}
+
+/// 默认规则：检测到异常即告警，置信度达到0.7为严重告警
+fn default_rules() -> Vec<AlertRule> {
+    vec![AlertRule {
+        id: "abnormal".to_string(),
+        name: "检测到异常".to_string(),
+        enabled: true,
+        severity: AlertSeverity::Warning,
+        critical_confidence: Some(0.7),
+        condition: RuleCondition::Class { names: vec![ABNORMAL_CLASS_NAME.to_string()] },
+    }]
+}
+
+fn validate(rules: &[AlertRule]) -> Result<()> {
+    let mut ids = HashSet::new();
+    for rule in rules {
+        if rule.id.trim().is_empty() {
+            return Err(anyhow!("规则ID不能为空"));
+        }
+        if !ids.insert(rule.id.as_str()) {
+            return Err(anyhow!("规则ID重复: {}", rule.id));
+        }
+        if rule.critical_confidence.is_some_and(|c| !(0.0..=1.0).contains(&c)) {
+            return Err(anyhow!("规则 {} 的严重告警置信度必须在 0-1 之间", rule.id));
+        }
+        rule.condition.validate().map_err(|e| anyhow!("规则 {}: {}", rule.id, e))?;
+    }
+    Ok(())
+}
+
+/// 规则求值结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleMatch {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub severity: AlertSeverity,
+    pub detections: Vec<YoloDetection>, // 满足条件的检测框
+}
+
+impl RuleMatch {
+    /// 置信度最高的匹配检测框
+    pub fn top_detection(&self) -> Option<&YoloDetection> {
+        self.detections.iter().max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+    }
+}
+
+/// 单条规则求值时的上下文
+struct EvalContext<'a> {
+    detections: &'a [YoloDetection],
+    image_size: (u32, u32),
+    zones: &'a [RoiPolygon],
+    px_per_mm: Option<f32>, // 检测框未附带物理尺寸时按此换算
+    key_prefix: String, // 持续时长计时的键前缀（规则ID + 输入源）
+    now: Instant,
+    since: &'a mut HashMap<String, Instant>,
+    visited: HashSet<String>,
+}
+
+/// 在检测框下标集合上求值条件，成立时返回满足条件的检测框下标
+fn evaluate(condition: &RuleCondition, set: Vec<usize>, path: &str, ctx: &mut EvalContext) -> Option<Vec<usize>> {
+    let detections = ctx.detections;
+    let filter = |set: Vec<usize>, keep: &dyn Fn(&YoloDetection) -> bool| {
+        let matched: Vec<usize> = set.into_iter().filter(|i| keep(&detections[*i])).collect();
+        Some(matched).filter(|matched| !matched.is_empty())
+    };
+    match condition {
+        RuleCondition::Class { names } => filter(set, &|d| names.contains(&d.class_name)),
+        RuleCondition::MinConfidence { value } => filter(set, &|d| d.confidence >= *value),
+        RuleCondition::Zone { name } => {
+            let zone = ctx.zones.iter().find(|zone| zone.name == *name)?;
+            let image_size = ctx.image_size;
+            filter(set, &|d| roi::contains_detection(std::slice::from_ref(zone), d, image_size))
+        }
+        RuleCondition::MinSize { mm } => {
+            let px_per_mm = ctx.px_per_mm;
+            filter(set, &|d| {
+                d.size_mm
+                    .or_else(|| px_per_mm.map(|scale| PhysicalSize::from_bbox(&d.bbox, scale)))
+                    .is_some_and(|size| size.max_side_mm() >= *mm)
+            })
+        }
+        RuleCondition::Count { min, max } => {
+            let count = set.len() as u32;
+            (count >= *min && !max.is_some_and(|max| count > max)).then_some(set)
+        }
+        RuleCondition::Duration { secs, condition } => {
+            let key = format!("{}{}", ctx.key_prefix, path);
+            let matched = evaluate(condition, set, &format!("{}.0", path), ctx);
+            if matched.is_none() {
+                ctx.since.remove(&key);
+                return None;
+            }
+            let now = ctx.now;
+            let since = *ctx.since.entry(key.clone()).or_insert(now);
+            ctx.visited.insert(key);
+            matched.filter(|_| now.duration_since(since).as_secs_f64() >= *secs)
+        }
+        RuleCondition::And { conditions } => {
+            let mut set = set;
+            for (index, condition) in conditions.iter().enumerate() {
+                set = evaluate(condition, set, &format!("{}.{}", path, index), ctx)?;
+            }
+            Some(set)
+        }
+        RuleCondition::Or { conditions } => {
+            // 不短路：各分支中的持续时长都需要持续计时
+            let mut union: Vec<usize> = Vec::new();
+            let mut any = false;
+            for (index, condition) in conditions.iter().enumerate() {
+                if let Some(matched) = evaluate(condition, set.clone(), &format!("{}.{}", path, index), ctx) {
+                    any = true;
+                    for i in matched {
+                        if !union.contains(&i) {
+                            union.push(i);
+                        }
+                    }
+                }
+            }
+            union.sort_unstable();
+            any.then_some(union)
+        }
+    }
+}
+
+/// 告警规则（Tauri托管状态）
+pub struct AlertRules {
+    path: PathBuf,
+    rules: RwLock<Vec<AlertRule>>,
+    since: Mutex<HashMap<String, Instant>>, // 持续时长条件开始成立的时间
+}
+
+impl AlertRules {
+    /// 读取已保存的规则，文件不存在或无效时使用默认规则
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(RULES_FILE_NAME);
+        let rules = match std::fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<Vec<AlertRule>>(&content) {
+                Ok(rules) if validate(&rules).is_ok() => rules,
+                Ok(_) | Err(_) => {
+                    tracing::error!("告警规则文件无效，使用默认规则: {}", path.display());
+                    default_rules()
+                }
+            },
+            Err(_) => default_rules(),
+        };
+        Self {
+            path,
+            rules: RwLock::new(rules),
+            since: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn rules(&self) -> Vec<AlertRule> {
+        self.rules.read().clone()
+    }
+
+    /// 保存规则并重新开始持续时长计时
+    pub fn save(&self, rules: Vec<AlertRule>) -> Result<()> {
+        validate(&rules).map_err(error::invalid_input)?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&rules)?)?;
+        *self.rules.write() = rules;
+        self.since.lock().clear();
+        Ok(())
+    }
+
+    /// 对一次检测结果求值全部启用的规则
+    pub fn evaluate(&self, source: &str, result: &DetectionResult) -> Vec<RuleMatch> {
+        let rules = self.rules.read();
+        let zones = detection_config::detection_roi();
+        let px_per_mm = detection_config::px_per_mm();
+        let mut since = self.since.lock();
+        let now = Instant::now();
+        let all: Vec<usize> = (0..result.detections.len()).collect();
+
+        let mut matches = Vec::new();
+        for rule in rules.iter().filter(|rule| rule.enabled) {
+            let key_prefix = format!("{}|{}|", rule.id, source);
+            let mut ctx = EvalContext {
+                detections: &result.detections,
+                image_size: (result.image_width, result.image_height),
+                zones: &zones,
+                px_per_mm,
+                key_prefix: key_prefix.clone(),
+                now,
+                since: &mut *since,
+                visited: HashSet::new(),
+            };
+            let matched = evaluate(&rule.condition, all.clone(), "0", &mut ctx);
+            // 本次未求值到的持续时长条件（被 and 短路）重新计时
+            let visited = std::mem::take(&mut ctx.visited);
+            since.retain(|key, _| !key.starts_with(&key_prefix) || visited.contains(key));
+
+            let Some(matched) = matched else {
+                continue;
+            };
+            let detections: Vec<YoloDetection> = matched.into_iter().map(|i| result.detections[i].clone()).collect();
+            let top_confidence = detections.iter().map(|d| d.confidence).reduce(f32::max);
+            let severity = match (rule.critical_confidence, top_confidence) {
+                (Some(critical), Some(top)) if top >= critical => AlertSeverity::Critical,
+                _ => rule.severity,
+            };
+            matches.push(RuleMatch {
+                rule_id: rule.id.clone(),
+                rule_name: rule.name.clone(),
+                severity,
+                detections,
+            });
+        }
+        matches
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+#[tauri::command]
+pub async fn get_alert_rules(
+    rules: State<'_, AlertRules>
+) -> Result<Vec<AlertRule>, DetectionError> {
+    Ok(rules.rules())
+}
+
+/// 保存告警规则（整体替换）
+#[tauri::command]
+pub async fn set_alert_rules(
+    rules: State<'_, AlertRules>,
+    new_rules: Vec<AlertRule>
+) -> Result<Vec<AlertRule>, DetectionError> {
+    match rules.save(new_rules) {
+        Ok(()) => Ok(rules.rules()),
+        Err(e) => Err(DetectionError::from(e).context("保存告警规则失败")),
+    }
+}
+
+/// 用告警规则试算一次检测结果（不记录告警），便于调试规则
+#[tauri::command]
+pub async fn test_alert_rules(
+    rules: State<'_, AlertRules>,
+    result: DetectionResult
+) -> Result<Vec<RuleMatch>, DetectionError> {
+    Ok(rules.evaluate("test", &result))
+}
@@ -0,0 +1,453 @@
+/*!
+告警推送模块
+告警规则触发的告警按配置推送到外部：Webhook（POST告警JSON）、SMTP邮件、桌面通知。
+每个推送目标可按告警级别与规则筛选，发送失败时按指数退避重试，
+并按每分钟最大推送数限流，避免持续异常时刷屏
+配置保存在应用数据目录下的JSON文件中（含SMTP密码，以明文保存）
+*/
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::alerts::{Alert, AlertSeverity, AlertStatus};
+use crate::error::{self, DetectionError};
+
+/// 推送配置文件名（位于应用数据目录）
+pub const SINKS_FILE_NAME: &str = "alert_sinks.json";
+
+/// Webhook请求超时
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 限流统计窗口
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// SMTP连接加密方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    Tls,      // 隐式TLS（通常为465端口）
+    StartTls, // STARTTLS（通常为587端口）
+    None,     // 不加密（仅限内网中继）
+}
+
+/// 推送方式
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkKind {
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+    Email {
+        smtp_host: String,
+        #[serde(default)]
+        smtp_port: Option<u16>, // 为空时使用加密方式对应的默认端口
+        security: SmtpSecurity,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        from: String,
+        to: Vec<String>,
+    },
+    Notification,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_min_severity() -> AlertSeverity {
+    AlertSeverity::Warning
+}
+
+/// 推送目标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertSink {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_min_severity")]
+    pub min_severity: AlertSeverity, // 低于该级别的告警不推送
+    #[serde(default)]
+    pub rule_ids: Vec<String>, // 只推送这些规则触发的告警，为空时不限
+    #[serde(flatten)]
+    pub kind: SinkKind,
+}
+
+impl AlertSink {
+    fn accepts(&self, alert: &Alert) -> bool {
+        self.enabled
+            && alert.severity >= self.min_severity
+            && (self.rule_ids.is_empty() || alert.rule_id.as_ref().is_some_and(|id| self.rule_ids.contains(id)))
+    }
+
+    fn label(&self) -> &str {
+        if self.name.is_empty() { &self.id } else { &self.name }
+    }
+}
+
+/// 推送配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SinkSettings {
+    pub sinks: Vec<AlertSink>,
+    pub max_retries: u32,           // 失败后的重试次数
+    pub retry_delay_ms: u64,        // 首次重试的等待时间，之后每次翻倍
+    pub max_per_minute: u32,        // 每个推送目标每分钟最多推送的告警数
+}
+
+impl Default for SinkSettings {
+    fn default() -> Self {
+        Self {
+            sinks: Vec::new(),
+            max_retries: 3,
+            retry_delay_ms: 1000,
+            max_per_minute: 6,
+        }
+    }
+}
+
+fn validate(settings: &SinkSettings) -> Result<()> {
+    if settings.max_per_minute == 0 {
+        return Err(anyhow!("每分钟最大推送数必须大于0"));
+    }
+    let mut ids = Vec::new();
+    for sink in &settings.sinks {
+        if sink.id.trim().is_empty() {
+            return Err(anyhow!("推送目标ID不能为空"));
+        }
+        if ids.contains(&sink.id) {
+            return Err(anyhow!("推送目标ID重复: {}", sink.id));
+        }
+        ids.push(sink.id.clone());
+        match &sink.kind {
+            SinkKind::Webhook { url, .. } if !(url.starts_with("http://") || url.starts_with("https://")) => {
+                return Err(anyhow!("推送目标 {} 的Webhook地址须为 http/https: {}", sink.id, url));
+            }
+            SinkKind::Email { to, .. } if to.is_empty() => {
+                return Err(anyhow!("推送目标 {} 至少需要一个收件人", sink.id));
+            }
+            SinkKind::Email { from, to, .. } => {
+                for address in std::iter::once(from).chain(to) {
+                    address
+                        .parse::<lettre::message::Mailbox>()
+                        .map_err(|e| anyhow!("推送目标 {} 的邮箱地址无效 {}: {}", sink.id, address, e))?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// 推送目标的发送统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SinkStats {
+    pub sent: u64,
+    pub failed: u64,       // 重试用尽仍失败的告警数
+    pub rate_limited: u64, // 因限流未推送的告警数
+    pub last_sent_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// 推送状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinkStatus {
+    pub settings: SinkSettings,
+    pub stats: HashMap<String, SinkStats>, // 按推送目标ID
+}
+
+fn severity_label(severity: AlertSeverity) -> &'static str {
+    match severity {
+        AlertSeverity::Info => "提示",
+        AlertSeverity::Warning => "警告",
+        AlertSeverity::Critical => "严重",
+    }
+}
+
+fn alert_title(alert: &Alert) -> String {
+    format!("[{}] {}", severity_label(alert.severity), alert.message)
+}
+
+fn alert_body(alert: &Alert) -> String {
+    format!(
+        "告警编号: {}\n级别: {}\n输入源: {}\n类别: {}\n置信度: {:.1}%\n检测数: {}\n时间: {}",
+        alert.id,
+        severity_label(alert.severity),
+        alert.source,
+        alert.class_name,
+        alert.confidence * 100.0,
+        alert.detection_count,
+        alert.created_at
+    )
+}
+
+async fn send_webhook(url: &str, headers: &HashMap<String, String>, alert: &Alert) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .map_err(|e| anyhow!("创建HTTP客户端失败: {}", e))?;
+    let mut request = client.post(url).json(alert);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| anyhow!("Webhook请求失败: {}", e))?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn send_email(
+    smtp_host: &str,
+    smtp_port: Option<u16>,
+    security: SmtpSecurity,
+    username: Option<&str>,
+    password: Option<&str>,
+    from: &str,
+    to: &[String],
+    alert: &Alert,
+) -> Result<()> {
+    let mut builder = Message::builder()
+        .from(from.parse().map_err(|e| anyhow!("发件人地址无效: {}", e))?)
+        .subject(alert_title(alert))
+        .header(ContentType::TEXT_PLAIN);
+    for address in to {
+        builder = builder.to(address.parse().map_err(|e| anyhow!("收件人地址无效 {}: {}", address, e))?);
+    }
+    let email = builder.body(alert_body(alert)).map_err(|e| anyhow!("生成邮件失败: {}", e))?;
+
+    let mut transport = match security {
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(smtp_host)?,
+        SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(smtp_host)?,
+        SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(smtp_host),
+    };
+    if let Some(port) = smtp_port {
+        transport = transport.port(port);
+    }
+    if let (Some(username), Some(password)) = (username, password) {
+        transport = transport.credentials(Credentials::new(username.to_string(), password.to_string()));
+    }
+    transport
+        .build()
+        .send(email)
+        .await
+        .map_err(|e| anyhow!("邮件发送失败: {}", e))?;
+    Ok(())
+}
+
+fn show_notification(app: &AppHandle, alert: &Alert) -> Result<()> {
+    app.notification()
+        .builder()
+        .title(alert_title(alert))
+        .body(format!("{} · {}", alert.source, alert.created_at))
+        .show()
+        .map_err(|e| anyhow!("桌面通知失败: {}", e))
+}
+
+async fn deliver(app: &AppHandle, sink: &AlertSink, alert: &Alert) -> Result<()> {
+    match &sink.kind {
+        SinkKind::Webhook { url, headers } => send_webhook(url, headers, alert).await,
+        SinkKind::Email { smtp_host, smtp_port, security, username, password, from, to } => {
+            send_email(
+                smtp_host,
+                *smtp_port,
+                *security,
+                username.as_deref(),
+                password.as_deref(),
+                from,
+                to,
+                alert,
+            )
+            .await
+        }
+        SinkKind::Notification => show_notification(app, alert),
+    }
+}
+
+/// 推送一条告警，失败时按指数退避重试
+async fn deliver_with_retry(app: AppHandle, sink: AlertSink, alert: Alert, max_retries: u32, retry_delay_ms: u64) {
+    let mut attempt = 0;
+    let result = loop {
+        match deliver(&app, &sink, &alert).await {
+            Ok(()) => break Ok(()),
+            Err(e) if attempt < max_retries => {
+                let delay = Duration::from_millis(retry_delay_ms.saturating_mul(1 << attempt.min(10)));
+                tracing::warn!("⚠️ 告警 #{} 推送到 {} 失败，{}ms 后重试: {}", alert.id, sink.label(), delay.as_millis(), e);
+                attempt += 1;
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => break Err(e),
+        }
+    };
+
+    let sinks = app.state::<AlertSinks>();
+    let mut stats = sinks.stats.lock();
+    let entry = stats.entry(sink.id.clone()).or_default();
+    match result {
+        Ok(()) => {
+            entry.sent += 1;
+            entry.last_sent_at = Some(crate::storage::now_rfc3339());
+        }
+        Err(e) => {
+            tracing::error!("告警 #{} 推送到 {} 失败: {}", alert.id, sink.label(), e);
+            entry.failed += 1;
+            entry.last_error = Some(e.to_string());
+        }
+    }
+}
+
+/// 告警推送（Tauri托管状态）
+pub struct AlertSinks {
+    path: PathBuf,
+    settings: RwLock<SinkSettings>,
+    recent: Mutex<HashMap<String, VecDeque<Instant>>>, // 各推送目标最近一分钟的推送时间
+    stats: Mutex<HashMap<String, SinkStats>>,
+}
+
+impl AlertSinks {
+    /// 读取已保存的推送配置，文件不存在或无效时不推送
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(SINKS_FILE_NAME);
+        let settings = match std::fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<SinkSettings>(&content) {
+                Ok(settings) if validate(&settings).is_ok() => settings,
+                Ok(_) | Err(_) => {
+                    tracing::error!("告警推送配置文件无效，已停用推送: {}", path.display());
+                    SinkSettings::default()
+                }
+            },
+            Err(_) => SinkSettings::default(),
+        };
+        Self {
+            path,
+            settings: RwLock::new(settings),
+            recent: Mutex::new(HashMap::new()),
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn status(&self) -> SinkStatus {
+        SinkStatus {
+            settings: self.settings.read().clone(),
+            stats: self.stats.lock().clone(),
+        }
+    }
+
+    fn save(&self, settings: SinkSettings) -> Result<()> {
+        validate(&settings).map_err(error::invalid_input)?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&settings)?)?;
+        *self.settings.write() = settings;
+        Ok(())
+    }
+
+    /// 按限流记录一次推送，超过每分钟上限时返回 false
+    fn acquire(&self, sink_id: &str, max_per_minute: u32) -> bool {
+        let now = Instant::now();
+        let mut recent = self.recent.lock();
+        let sent = recent.entry(sink_id.to_string()).or_default();
+        while sent.front().is_some_and(|t| now - *t > RATE_WINDOW) {
+            sent.pop_front();
+        }
+        if sent.len() >= max_per_minute as usize {
+            return false;
+        }
+        sent.push_back(now);
+        true
+    }
+
+    /// 把新告警推送到各匹配的推送目标（后台发送，不阻塞检测）
+    pub fn dispatch(&self, app: &AppHandle, alerts: &[Alert]) {
+        let settings = self.settings.read().clone();
+        for alert in alerts {
+            for sink in settings.sinks.iter().filter(|sink| sink.accepts(alert)) {
+                if !self.acquire(&sink.id, settings.max_per_minute) {
+                    self.stats.lock().entry(sink.id.clone()).or_default().rate_limited += 1;
+                    continue;
+                }
+                tauri::async_runtime::spawn(deliver_with_retry(
+                    app.clone(),
+                    sink.clone(),
+                    alert.clone(),
+                    settings.max_retries,
+                    settings.retry_delay_ms,
+                ));
+            }
+        }
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+#[tauri::command]
+pub async fn get_alert_sinks(
+    sinks: State<'_, AlertSinks>
+) -> Result<SinkStatus, DetectionError> {
+    Ok(sinks.status())
+}
+
+/// 保存推送配置（整体替换）
+#[tauri::command]
+pub async fn set_alert_sinks(
+    sinks: State<'_, AlertSinks>,
+    settings: SinkSettings
+) -> Result<SinkStatus, DetectionError> {
+    match sinks.save(settings) {
+        Ok(()) => Ok(sinks.status()),
+        Err(e) => Err(DetectionError::from(e).context("保存告警推送配置失败")),
+    }
+}
+
+/// 向指定推送目标发送一条测试告警（不重试、不计入限流）
+#[tauri::command]
+pub async fn test_alert_sink(
+    app: AppHandle,
+    sinks: State<'_, AlertSinks>,
+    sink_id: String
+) -> Result<String, DetectionError> {
+    let sink = sinks.settings.read().sinks.iter().find(|sink| sink.id == sink_id).cloned();
+    let Some(sink) = sink else {
+        return Err(DetectionError::NotFound(format!("推送目标不存在: {}", sink_id)));
+    };
+    let alert = Alert {
+        id: 0,
+        severity: AlertSeverity::Info,
+        status: AlertStatus::Open,
+        source: "测试".to_string(),
+        class_name: String::new(),
+        confidence: 0.0,
+        detection_count: 0,
+        message: "告警推送测试".to_string(),
+        created_at: crate::storage::now_rfc3339(),
+        acknowledged_by: None,
+        acknowledged_at: None,
+        resolved_by: None,
+        resolved_at: None,
+        comment: None,
+        escalated: false,
+        rule_id: None,
+    };
+    match deliver(&app, &sink, &alert).await {
+        Ok(()) => Ok(format!("已发送测试告警到 {}", sink.label())),
+        Err(e) => Err(DetectionError::from(e).context("测试推送失败")),
+    }
+}
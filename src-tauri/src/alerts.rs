@@ -0,0 +1,471 @@
+/*!
+告警管理模块
+根据告警规则（见 `alert_rules` 模块）记录检测告警，支持操作员确认/处理流程、升级与审计
+*/
+
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::alert_rules::AlertRules;
+use crate::error::DetectionError;
+use crate::storage::{self, now_rfc3339, Database};
+use crate::yolo::DetectionResult;
+
+/// 触发告警的类别名称
+pub const ABNORMAL_CLASS_NAME: &str = "异常";
+
+/// 告警级别（按严重程度排序）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl AlertSeverity {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AlertSeverity::Info => "info",
+            AlertSeverity::Warning => "warning",
+            AlertSeverity::Critical => "critical",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "critical" => AlertSeverity::Critical,
+            "warning" => AlertSeverity::Warning,
+            _ => AlertSeverity::Info,
+        }
+    }
+}
+
+/// 告警处理状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertStatus {
+    Open,         // 未处理
+    Acknowledged, // 已确认
+    Resolved,     // 已解决
+}
+
+impl AlertStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AlertStatus::Open => "open",
+            AlertStatus::Acknowledged => "acknowledged",
+            AlertStatus::Resolved => "resolved",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "acknowledged" => AlertStatus::Acknowledged,
+            "resolved" => AlertStatus::Resolved,
+            _ => AlertStatus::Open,
+        }
+    }
+}
+
+/// 告警记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: i64,
+    pub severity: AlertSeverity,
+    pub status: AlertStatus,
+    pub source: String,
+    pub class_name: String,
+    pub confidence: f32,
+    pub detection_count: u32,
+    pub message: String,
+    pub created_at: String,
+    pub acknowledged_by: Option<String>,
+    pub acknowledged_at: Option<String>,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<String>,
+    pub comment: Option<String>,
+    pub escalated: bool,
+    pub rule_id: Option<String>, // 触发告警的规则（旧版本记录为空）
+}
+
+/// 告警审计事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertEvent {
+    pub id: i64,
+    pub alert_id: i64,
+    pub action: String,
+    pub operator_id: String,
+    pub comment: Option<String>,
+    pub created_at: String,
+}
+
+/// 初始化告警相关表结构
+pub fn init_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS alerts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            severity TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'open',
+            source TEXT NOT NULL,
+            class_name TEXT NOT NULL,
+            confidence REAL NOT NULL,
+            detection_count INTEGER NOT NULL,
+            message TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            acknowledged_by TEXT,
+            acknowledged_at TEXT,
+            resolved_by TEXT,
+            resolved_at TEXT,
+            comment TEXT,
+            escalated INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_alerts_status ON alerts(status, severity);
+        CREATE TABLE IF NOT EXISTS alert_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            alert_id INTEGER NOT NULL REFERENCES alerts(id) ON DELETE CASCADE,
+            action TEXT NOT NULL,
+            operator_id TEXT NOT NULL,
+            comment TEXT,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_alert_events_alert ON alert_events(alert_id);",
+    )?;
+    storage::add_column_if_missing(conn, "alerts", "rule_id", "TEXT")?;
+    Ok(())
+}
+
+const ALERT_COLUMNS: &str = "id, severity, status, source, class_name, confidence, detection_count, \
+     message, created_at, acknowledged_by, acknowledged_at, resolved_by, resolved_at, comment, escalated, rule_id";
+
+fn row_to_alert(row: &Row) -> rusqlite::Result<Alert> {
+    Ok(Alert {
+        id: row.get(0)?,
+        severity: AlertSeverity::parse(&row.get::<_, String>(1)?),
+        status: AlertStatus::parse(&row.get::<_, String>(2)?),
+        source: row.get(3)?,
+        class_name: row.get(4)?,
+        confidence: row.get::<_, f64>(5)? as f32,
+        detection_count: row.get(6)?,
+        message: row.get(7)?,
+        created_at: row.get(8)?,
+        acknowledged_by: row.get(9)?,
+        acknowledged_at: row.get(10)?,
+        resolved_by: row.get(11)?,
+        resolved_at: row.get(12)?,
+        comment: row.get(13)?,
+        escalated: row.get::<_, i64>(14)? != 0,
+        rule_id: row.get(15)?,
+    })
+}
+
+fn record_event(
+    conn: &Connection,
+    alert_id: i64,
+    action: &str,
+    operator_id: &str,
+    comment: Option<&str>,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO alert_events (alert_id, action, operator_id, comment, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![alert_id, action, operator_id, comment, now_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// 按告警规则求值检测结果，每条满足条件的规则记录一条告警
+pub fn raise_for_result(db: &Database, rules: &AlertRules, source: &str, result: &DetectionResult) -> Result<Vec<Alert>> {
+    let mut raised = Vec::new();
+    for matched in rules.evaluate(source, result) {
+        let (class_name, top_confidence) = matched
+            .top_detection()
+            .map(|d| (d.class_name.clone(), d.confidence))
+            .unwrap_or_default();
+        let message = if matched.detections.is_empty() {
+            matched.rule_name.clone()
+        } else {
+            format!(
+                "{}: {} 个目标，最高置信度 {:.1}%",
+                matched.rule_name,
+                matched.detections.len(),
+                top_confidence * 100.0
+            )
+        };
+
+        let alert_id = db.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO alerts (severity, status, source, class_name, confidence, detection_count, message, created_at, rule_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    matched.severity.as_str(),
+                    AlertStatus::Open.as_str(),
+                    source,
+                    class_name,
+                    top_confidence as f64,
+                    matched.detections.len() as u32,
+                    message,
+                    now_rfc3339(),
+                    matched.rule_id,
+                ],
+            )?;
+            let alert_id = conn.last_insert_rowid();
+            record_event(conn, alert_id, "created", "system", None)?;
+            Ok(alert_id)
+        })?;
+
+        tracing::info!("🚨 新告警 #{} [{}]: {}", alert_id, matched.severity.as_str(), message);
+        raised.extend(get_alert(db, alert_id)?);
+    }
+    Ok(raised)
+}
+
+/// 查询单个告警
+pub fn get_alert(db: &Database, alert_id: i64) -> Result<Option<Alert>> {
+    db.with_conn(|conn| {
+        conn.query_row(
+            &format!("SELECT {} FROM alerts WHERE id = ?1", ALERT_COLUMNS),
+            params![alert_id],
+            row_to_alert,
+        )
+        .optional()
+    })
+}
+
+/// 按状态查询告警（最新的在前）
+pub fn list(db: &Database, status: Option<AlertStatus>, limit: u32) -> Result<Vec<Alert>> {
+    db.with_conn(|conn| {
+        let status = status.map(|s| s.as_str());
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM alerts WHERE (?1 IS NULL OR status = ?1) ORDER BY id DESC LIMIT ?2",
+            ALERT_COLUMNS
+        ))?;
+        let alerts = stmt
+            .query_map(params![status, limit], row_to_alert)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(alerts)
+    })
+}
+
+/// 状态更新未命中任何行时的原因：告警不存在或已被其他操作员处理
+fn status_conflict(db: &Database, alert_id: i64) -> anyhow::Error {
+    match get_alert(db, alert_id) {
+        Ok(Some(alert)) => match alert.status {
+            AlertStatus::Open => anyhow!("告警 {} 状态更新失败", alert_id),
+            AlertStatus::Acknowledged => DetectionError::InvalidInput(format!(
+                "告警 {} 已被 {} 确认",
+                alert_id,
+                alert.acknowledged_by.unwrap_or_default()
+            ))
+            .into(),
+            AlertStatus::Resolved => DetectionError::InvalidInput(format!(
+                "告警 {} 已由 {} 解决",
+                alert_id,
+                alert.resolved_by.unwrap_or_default()
+            ))
+            .into(),
+        },
+        Ok(None) => DetectionError::NotFound(format!("告警不存在: {}", alert_id)).into(),
+        Err(e) => e,
+    }
+}
+
+/// 操作员确认告警（只有未处理的告警可以确认）
+pub fn acknowledge(db: &Database, alert_id: i64, operator_id: &str, comment: Option<&str>) -> Result<Alert> {
+    // 状态检查放在 UPDATE 条件中，与审计记录在同一事务内提交，两名操作员同时确认时只有一人成功
+    let updated = db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let changed = tx.execute(
+            "UPDATE alerts SET status = ?1, acknowledged_by = ?2, acknowledged_at = ?3,
+                 comment = COALESCE(?4, comment)
+             WHERE id = ?5 AND status = ?6",
+            params![
+                AlertStatus::Acknowledged.as_str(),
+                operator_id,
+                now_rfc3339(),
+                comment,
+                alert_id,
+                AlertStatus::Open.as_str()
+            ],
+        )?;
+        if changed == 0 {
+            return Ok(false);
+        }
+        record_event(&tx, alert_id, "acknowledged", operator_id, comment)?;
+        tx.commit()?;
+        Ok(true)
+    })?;
+    if !updated {
+        return Err(status_conflict(db, alert_id));
+    }
+
+    tracing::info!("✅ 告警 #{} 已由 {} 确认", alert_id, operator_id);
+    get_alert(db, alert_id)?.ok_or_else(|| DetectionError::NotFound(format!("告警不存在: {}", alert_id)).into())
+}
+
+/// 操作员解决告警（未确认的告警也可直接解决）
+pub fn resolve(db: &Database, alert_id: i64, operator_id: &str, comment: Option<&str>) -> Result<Alert> {
+    let updated = db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let now = now_rfc3339();
+        let changed = tx.execute(
+            "UPDATE alerts SET status = ?1, resolved_by = ?2, resolved_at = ?3,
+                 acknowledged_by = COALESCE(acknowledged_by, ?2),
+                 acknowledged_at = COALESCE(acknowledged_at, ?3),
+                 comment = COALESCE(?4, comment)
+             WHERE id = ?5 AND status != ?1",
+            params![AlertStatus::Resolved.as_str(), operator_id, now, comment, alert_id],
+        )?;
+        if changed == 0 {
+            return Ok(false);
+        }
+        record_event(&tx, alert_id, "resolved", operator_id, comment)?;
+        tx.commit()?;
+        Ok(true)
+    })?;
+    if !updated {
+        return Err(status_conflict(db, alert_id));
+    }
+
+    tracing::info!("✅ 告警 #{} 已由 {} 解决", alert_id, operator_id);
+    get_alert(db, alert_id)?.ok_or_else(|| DetectionError::NotFound(format!("告警不存在: {}", alert_id)).into())
+}
+
+/// 升级超时未解决的严重告警，返回本次新升级的告警
+pub fn escalate_unresolved(db: &Database, older_than_secs: u64) -> Result<Vec<Alert>> {
+    let cutoff = i64::try_from(older_than_secs)
+        .ok()
+        .and_then(chrono::Duration::try_seconds)
+        .and_then(|age| chrono::Utc::now().checked_sub_signed(age))
+        .ok_or_else(|| DetectionError::InvalidInput(format!("升级时限超出范围: {} 秒", older_than_secs)))?
+        .to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+
+    let ids: Vec<i64> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id FROM alerts
+             WHERE severity = ?1 AND status != ?2 AND escalated = 0 AND created_at <= ?3",
+        )?;
+        let ids = stmt
+            .query_map(
+                params![AlertSeverity::Critical.as_str(), AlertStatus::Resolved.as_str(), cutoff],
+                |row| row.get(0),
+            )?
+            .collect::<rusqlite::Result<Vec<i64>>>()?;
+
+        for id in &ids {
+            conn.execute("UPDATE alerts SET escalated = 1 WHERE id = ?1", params![id])?;
+            record_event(conn, *id, "escalated", "system", None)?;
+        }
+        Ok(ids)
+    })?;
+
+    if !ids.is_empty() {
+        tracing::warn!("⚠️ 已升级 {} 条未解决的严重告警: {:?}", ids.len(), ids);
+    }
+
+    let mut escalated = Vec::with_capacity(ids.len());
+    for id in ids {
+        if let Some(alert) = get_alert(db, id)? {
+            escalated.push(alert);
+        }
+    }
+    Ok(escalated)
+}
+
+/// 查询告警的审计记录
+pub fn audit_log(db: &Database, alert_id: i64) -> Result<Vec<AlertEvent>> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, alert_id, action, operator_id, comment, created_at
+             FROM alert_events WHERE alert_id = ?1 ORDER BY id ASC",
+        )?;
+        let events = stmt
+            .query_map(params![alert_id], |row| {
+                Ok(AlertEvent {
+                    id: row.get(0)?,
+                    alert_id: row.get(1)?,
+                    action: row.get(2)?,
+                    operator_id: row.get(3)?,
+                    comment: row.get(4)?,
+                    created_at: row.get(5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(events)
+    })
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 查询告警列表
+#[tauri::command]
+pub async fn list_alerts(
+    db: State<'_, Database>,
+    status: Option<AlertStatus>,
+    limit: Option<u32>
+) -> Result<Vec<Alert>, DetectionError> {
+    match list(&db, status, limit.unwrap_or(100)) {
+        Ok(alerts) => Ok(alerts),
+        Err(e) => Err(DetectionError::from(e).context("查询告警失败")),
+    }
+}
+
+/// 确认告警
+#[tauri::command]
+pub async fn acknowledge_alert(
+    db: State<'_, Database>,
+    alert_id: i64,
+    operator_id: String,
+    comment: Option<String>
+) -> Result<Alert, DetectionError> {
+    if operator_id.trim().is_empty() {
+        return Err(DetectionError::InvalidInput("操作员ID不能为空".to_string()));
+    }
+    match acknowledge(&db, alert_id, operator_id.trim(), comment.as_deref()) {
+        Ok(alert) => Ok(alert),
+        Err(e) => Err(DetectionError::from(e).context("确认告警失败")),
+    }
+}
+
+/// 解决告警
+#[tauri::command]
+pub async fn resolve_alert(
+    db: State<'_, Database>,
+    alert_id: i64,
+    operator_id: String,
+    comment: Option<String>
+) -> Result<Alert, DetectionError> {
+    if operator_id.trim().is_empty() {
+        return Err(DetectionError::InvalidInput("操作员ID不能为空".to_string()));
+    }
+    match resolve(&db, alert_id, operator_id.trim(), comment.as_deref()) {
+        Ok(alert) => Ok(alert),
+        Err(e) => Err(DetectionError::from(e).context("解决告警失败")),
+    }
+}
+
+/// 升级超过指定时长仍未解决的严重告警
+#[tauri::command]
+pub async fn escalate_unresolved_alerts(
+    db: State<'_, Database>,
+    older_than_secs: u64
+) -> Result<Vec<Alert>, DetectionError> {
+    match escalate_unresolved(&db, older_than_secs) {
+        Ok(alerts) => Ok(alerts),
+        Err(e) => Err(DetectionError::from(e).context("告警升级失败")),
+    }
+}
+
+/// 获取告警审计记录
+#[tauri::command]
+pub async fn get_alert_audit_log(
+    db: State<'_, Database>,
+    alert_id: i64
+) -> Result<Vec<AlertEvent>, DetectionError> {
+    match audit_log(&db, alert_id) {
+        Ok(events) => Ok(events),
+        Err(e) => Err(DetectionError::from(e).context("查询审计记录失败")),
+    }
+}
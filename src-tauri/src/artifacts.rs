@@ -0,0 +1,261 @@
+/*!
+检测产物保存模块
+开启 save_detection_artifacts 后，每次检测运行把标注后的整图和每个检测框的裁剪图
+写入输出目录，按日期/类别分目录，文件名包含时间戳、运行编号与置信度，便于人工复核与整理训练数据
+配置了隐私遮挡区域且开启对产物遮挡时，整图与裁剪图均在遮挡后保存
+图片编码与写盘在阻塞线程中进行，不拖慢检测流程
+*/
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use image::DynamicImage;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::error::DetectionError;
+use crate::yolo::YoloDetection;
+use crate::privacy;
+use crate::yolo_api::draw_detections_unmasked;
+
+/// 产物配置文件名（位于应用数据目录）
+pub const CONFIG_FILE_NAME: &str = "artifacts.json";
+
+/// 未指定输出目录时使用的默认子目录（位于应用数据目录）
+const DEFAULT_OUTPUT_DIR: &str = "artifacts";
+
+/// 裁剪图四周保留的边距（相对检测框尺寸的比例）
+const CROP_PADDING: f32 = 0.1;
+
+/// 检测产物配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArtifactConfig {
+    pub save_detection_artifacts: bool, // 总开关
+    pub output_dir: Option<String>,     // 为空时使用应用数据目录下的 artifacts
+    pub save_annotated: bool,           // 保存标注后的整图
+    pub save_crops: bool,               // 保存每个检测框的裁剪图
+    pub skip_empty: bool,               // 无检测结果时不保存
+}
+
+impl Default for ArtifactConfig {
+    fn default() -> Self {
+        Self {
+            save_detection_artifacts: false,
+            output_dir: None,
+            save_annotated: true,
+            save_crops: true,
+            skip_empty: true,
+        }
+    }
+}
+
+/// 待保存的原始图片
+pub enum ArtifactImage<'a> {
+    Decoded(&'a DynamicImage),
+    Encoded(&'a [u8]), // 未解码的图片文件内容，在后台线程解码
+}
+
+/// 移交给后台线程的图片
+enum PendingImage {
+    Decoded(DynamicImage),
+    Encoded(Vec<u8>),
+}
+
+/// 检测产物设置（Tauri托管状态）
+pub struct ArtifactSettings {
+    path: PathBuf,
+    default_output_dir: PathBuf,
+    config: RwLock<ArtifactConfig>,
+}
+
+/// 去掉文件名中的路径分隔符与系统保留字符（保留中文类别名）
+fn sanitize_file_part(part: &str) -> String {
+    let cleaned: String = part
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '.' | ' ' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    if cleaned.is_empty() {
+        "unknown".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// 裁剪检测框（带少量边距），检测框完全在图片外时返回 None
+fn crop_detection(image: &DynamicImage, detection: &YoloDetection) -> Option<DynamicImage> {
+    let [x, y, w, h] = detection.bbox;
+    let pad_x = w * CROP_PADDING;
+    let pad_y = h * CROP_PADDING;
+    let left = (x - pad_x).max(0.0) as u32;
+    let top = (y - pad_y).max(0.0) as u32;
+    let right = ((x + w + pad_x).max(0.0) as u32).min(image.width());
+    let bottom = ((y + h + pad_y).max(0.0) as u32).min(image.height());
+    if right <= left || bottom <= top {
+        return None;
+    }
+    Some(image.crop_imm(left, top, right - left, bottom - top))
+}
+
+/// 写入一次检测运行的产物，返回写入的文件数
+///
+/// 命名规则：
+/// - 整图：`<输出目录>/<日期>/<时间>_run<编号>_<来源>_annotated.jpg`
+/// - 裁剪：`<输出目录>/<日期>/crops/<类别>/<时间>_run<编号>_<序号>_<置信度>.jpg`
+fn write_artifacts(
+    config: &ArtifactConfig,
+    output_dir: &Path,
+    source: &str,
+    run_id: Option<i64>,
+    image: &DynamicImage,
+    detections: &[YoloDetection],
+) -> Result<usize> {
+    let now = chrono::Local::now();
+    let day_dir = output_dir.join(now.format("%Y-%m-%d").to_string());
+    let prefix = format!(
+        "{}_run{}",
+        now.format("%H%M%S_%3f"),
+        run_id.map_or_else(|| "na".to_string(), |id| id.to_string())
+    );
+    let mut written = 0;
+    let image = privacy::for_storage(image);
+
+    if config.save_annotated {
+        std::fs::create_dir_all(&day_dir)
+            .map_err(|e| anyhow!("创建产物目录失败 {}: {}", day_dir.display(), e))?;
+        let stem = Path::new(source)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let annotated = draw_detections_unmasked(&image, detections).map_err(|e| anyhow!(e))?;
+        let path = day_dir.join(format!("{}_{}_annotated.jpg", prefix, sanitize_file_part(&stem)));
+        annotated
+            .to_rgb8()
+            .save(&path)
+            .map_err(|e| anyhow!("保存标注图失败 {}: {}", path.display(), e))?;
+        written += 1;
+    }
+
+    if config.save_crops {
+        for (index, detection) in detections.iter().enumerate() {
+            let Some(crop) = crop_detection(&image, detection) else {
+                continue;
+            };
+            let class_dir = day_dir.join("crops").join(sanitize_file_part(&detection.class_name));
+            std::fs::create_dir_all(&class_dir)
+                .map_err(|e| anyhow!("创建产物目录失败 {}: {}", class_dir.display(), e))?;
+            let path = class_dir.join(format!("{}_{}_{:.2}.jpg", prefix, index, detection.confidence));
+            crop.to_rgb8()
+                .save(&path)
+                .map_err(|e| anyhow!("保存裁剪图失败 {}: {}", path.display(), e))?;
+            written += 1;
+        }
+    }
+
+    Ok(written)
+}
+
+impl ArtifactSettings {
+    /// 读取已保存的配置
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(CONFIG_FILE_NAME);
+        let config: ArtifactConfig = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            default_output_dir: data_dir.join(DEFAULT_OUTPUT_DIR),
+            config: RwLock::new(config),
+        }
+    }
+
+    pub fn config(&self) -> ArtifactConfig {
+        self.config.read().clone()
+    }
+
+    fn save(&self, config: ArtifactConfig) -> Result<()> {
+        if config.output_dir.as_deref().is_some_and(|dir| dir.trim().is_empty()) {
+            return Err(DetectionError::InvalidInput("输出目录不能为空字符串".to_string()).into());
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&config)?)?;
+        *self.config.write() = config;
+        Ok(())
+    }
+
+    /// 按配置在后台保存一次检测运行的产物（未开启时直接返回）
+    pub fn save_in_background(
+        &self,
+        source: &str,
+        run_id: Option<i64>,
+        image: ArtifactImage<'_>,
+        detections: &[YoloDetection],
+    ) {
+        let config = self.config();
+        if !config.save_detection_artifacts
+            || (!config.save_annotated && !config.save_crops)
+            || (config.skip_empty && detections.is_empty())
+        {
+            return;
+        }
+
+        let output_dir = config
+            .output_dir
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.default_output_dir.clone());
+        let source = source.to_string();
+        let detections = detections.to_vec();
+        let pending = match image {
+            ArtifactImage::Decoded(img) => PendingImage::Decoded(img.clone()),
+            ArtifactImage::Encoded(data) => PendingImage::Encoded(data.to_vec()),
+        };
+
+        tauri::async_runtime::spawn_blocking(move || {
+            let image = match pending {
+                PendingImage::Decoded(img) => img,
+                PendingImage::Encoded(data) => match crate::yolo::decode::decode(&data) {
+                    Ok(img) => img,
+                    Err(e) => {
+                        tracing::error!("检测产物保存失败，图片解码错误: {}", e);
+                        return;
+                    }
+                },
+            };
+            match write_artifacts(&config, &output_dir, &source, run_id, &image, &detections) {
+                Ok(count) => tracing::info!("🗂️ 检测产物已保存: {} 个文件 -> {}", count, output_dir.display()),
+                Err(e) => tracing::error!("检测产物保存失败: {}", e),
+            }
+        });
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 获取检测产物保存配置
+#[tauri::command]
+pub async fn get_artifact_config(
+    settings: State<'_, ArtifactSettings>
+) -> Result<ArtifactConfig, DetectionError> {
+    Ok(settings.config())
+}
+
+/// 保存检测产物配置（立即生效）
+#[tauri::command]
+pub async fn set_artifact_config(
+    settings: State<'_, ArtifactSettings>,
+    config: ArtifactConfig
+) -> Result<ArtifactConfig, DetectionError> {
+    match settings.save(config) {
+        Ok(()) => Ok(settings.config()),
+        Err(e) => Err(DetectionError::from(e).context("保存检测产物配置失败")),
+    }
+}
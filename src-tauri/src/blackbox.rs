@@ -0,0 +1,273 @@
+/*!
+黑匣子帧缓存模块
+持续把每个会话最近N秒的原始帧写入磁盘环形缓冲区（固定数量的槽位文件循环覆盖），
+即使没有开启录像，告警触发时也能取回之前的现场画面
+*/
+
+use std::collections::{HashMap, VecDeque};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use image::codecs::jpeg::JpegEncoder;
+use image::DynamicImage;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::error::DetectionError;
+use crate::privacy;
+use crate::source_lock::sanitize_key;
+use crate::yolo_api::Detection;
+
+/// 单张图片检测共用的会话键
+pub const IMAGE_SESSION: &str = "images";
+
+/// 黑匣子配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlackBoxConfig {
+    pub enabled: bool,
+    pub window_secs: u32, // 保留最近多少秒的帧
+    pub max_fps: f32,     // 写盘帧率上限，超出的帧直接丢弃
+    pub jpeg_quality: u8,
+}
+
+impl Default for BlackBoxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 30,
+            max_fps: 5.0,
+            jpeg_quality: 80,
+        }
+    }
+}
+
+impl BlackBoxConfig {
+    /// 每个会话的槽位数量
+    fn capacity(&self) -> usize {
+        ((self.window_secs as f32 * self.max_fps).ceil() as usize).max(1)
+    }
+
+    fn min_interval_ms(&self) -> i64 {
+        (1000.0 / self.max_fps) as i64
+    }
+}
+
+/// 缓冲区中的一帧
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BufferedFrame {
+    pub path: String,
+    pub timestamp: String,
+    pub timestamp_ms: i64,
+    pub detections: Vec<Detection>,
+    #[serde(skip)]
+    slot: usize,
+}
+
+/// 会话缓冲区状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlackBoxSessionStatus {
+    pub session: String,
+    pub frame_count: usize,
+    pub capacity: usize,
+    pub oldest: Option<String>,
+    pub newest: Option<String>,
+}
+
+/// 黑匣子总体状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlackBoxStatus {
+    pub config: BlackBoxConfig,
+    pub root_dir: String,
+    pub sessions: Vec<BlackBoxSessionStatus>,
+}
+
+struct SessionBuffer {
+    dir: PathBuf,
+    next_slot: usize,
+    frames: VecDeque<BufferedFrame>,
+    last_write_ms: Option<i64>,
+}
+
+/// 黑匣子记录器（Tauri托管状态）
+pub struct BlackBoxRecorder {
+    root: PathBuf,
+    config: RwLock<BlackBoxConfig>,
+    sessions: Mutex<HashMap<String, SessionBuffer>>,
+}
+
+impl BlackBoxRecorder {
+    /// 创建记录器，上次运行遗留的缓冲帧会被清空
+    pub fn new(root: PathBuf) -> Self {
+        if root.exists() {
+            let _ = std::fs::remove_dir_all(&root);
+        }
+        Self {
+            root,
+            config: RwLock::new(BlackBoxConfig::default()),
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> BlackBoxConfig {
+        self.config.read().clone()
+    }
+
+    /// 写入一帧原始画面及其检测结果，返回写入的帧（未启用或超出帧率上限时跳过）
+    pub fn record_frame(
+        &self,
+        session: &str,
+        frame: &DynamicImage,
+        detections: &[Detection],
+    ) -> Result<Option<BufferedFrame>> {
+        let config = self.config();
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let now = chrono::Utc::now();
+        let now_ms = now.timestamp_millis();
+        if let Some(buffer) = self.sessions.lock().get(session) {
+            if let Some(last) = buffer.last_write_ms {
+                if now_ms - last < config.min_interval_ms() {
+                    return Ok(None);
+                }
+            }
+        }
+
+        // 编码在锁外完成（按隐私遮挡设置先遮挡）
+        let frame = privacy::for_storage(frame);
+        let mut jpeg = Vec::new();
+        JpegEncoder::new_with_quality(&mut Cursor::new(&mut jpeg), config.jpeg_quality.clamp(1, 100))
+            .encode_image(&frame.to_rgb8())
+            .map_err(|e| anyhow!("黑匣子帧编码失败: {}", e))?;
+
+        let capacity = config.capacity();
+        let window_ms = config.window_secs as i64 * 1000;
+
+        let mut sessions = self.sessions.lock();
+        if !sessions.contains_key(session) {
+            let dir = self.root.join(sanitize_key(session));
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| anyhow!("创建黑匣子目录失败 {}: {}", dir.display(), e))?;
+            sessions.insert(
+                session.to_string(),
+                SessionBuffer {
+                    dir,
+                    next_slot: 0,
+                    frames: VecDeque::new(),
+                    last_write_ms: None,
+                },
+            );
+        }
+        let buffer = match sessions.get_mut(session) {
+            Some(buffer) => buffer,
+            None => return Ok(None),
+        };
+
+        // 容量缩小后槽位号可能越界，从头开始覆盖
+        if buffer.next_slot >= capacity {
+            buffer.next_slot = 0;
+        }
+        let slot = buffer.next_slot;
+        buffer.frames.retain(|f| f.slot != slot && now_ms - f.timestamp_ms <= window_ms);
+
+        let path = buffer.dir.join(format!("slot_{:05}.jpg", slot));
+        std::fs::write(&path, &jpeg)
+            .map_err(|e| anyhow!("写入黑匣子帧失败 {}: {}", path.display(), e))?;
+
+        let buffered = BufferedFrame {
+            path: path.to_string_lossy().to_string(),
+            timestamp: now.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            timestamp_ms: now_ms,
+            detections: detections.to_vec(),
+            slot,
+        };
+        buffer.frames.push_back(buffered.clone());
+        buffer.next_slot = (slot + 1) % capacity;
+        buffer.last_write_ms = Some(now_ms);
+        Ok(Some(buffered))
+    }
+
+    /// 清空指定会话（或全部会话）的缓冲帧
+    pub fn clear(&self, session: Option<&str>) {
+        let mut sessions = self.sessions.lock();
+        let removed: Vec<SessionBuffer> = match session {
+            Some(key) => sessions.remove(key).into_iter().collect(),
+            None => sessions.drain().map(|(_, buffer)| buffer).collect(),
+        };
+        for buffer in removed {
+            let _ = std::fs::remove_dir_all(&buffer.dir);
+        }
+    }
+
+    /// 缓冲帧占用的磁盘空间与帧数
+    pub fn disk_usage(&self) -> (u64, usize) {
+        let sessions = self.sessions.lock();
+        let frames: Vec<&BufferedFrame> = sessions.values().flat_map(|b| b.frames.iter()).collect();
+        let bytes = frames
+            .iter()
+            .filter_map(|f| std::fs::metadata(&f.path).ok())
+            .map(|m| m.len())
+            .sum();
+        (bytes, frames.len())
+    }
+
+    pub fn status(&self) -> BlackBoxStatus {
+        let config = self.config();
+        let capacity = config.capacity();
+        let mut sessions: Vec<BlackBoxSessionStatus> = self
+            .sessions
+            .lock()
+            .iter()
+            .map(|(session, buffer)| BlackBoxSessionStatus {
+                session: session.clone(),
+                frame_count: buffer.frames.len(),
+                capacity,
+                oldest: buffer.frames.front().map(|f| f.timestamp.clone()),
+                newest: buffer.frames.back().map(|f| f.timestamp.clone()),
+            })
+            .collect();
+        sessions.sort_by(|a, b| a.session.cmp(&b.session));
+
+        BlackBoxStatus {
+            config,
+            root_dir: self.root.to_string_lossy().to_string(),
+            sessions,
+        }
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 更新黑匣子配置
+#[tauri::command]
+pub async fn set_blackbox_config(
+    recorder: State<'_, BlackBoxRecorder>,
+    config: BlackBoxConfig
+) -> Result<String, DetectionError> {
+    if config.window_secs == 0 || !(config.max_fps > 0.0 && config.max_fps.is_finite()) {
+        return Err(DetectionError::InvalidInput("缓存时长和帧率上限必须大于0".to_string()));
+    }
+    *recorder.config.write() = config;
+    Ok("黑匣子配置已更新".to_string())
+}
+
+/// 获取黑匣子状态
+#[tauri::command]
+pub async fn get_blackbox_status(
+    recorder: State<'_, BlackBoxRecorder>
+) -> Result<BlackBoxStatus, DetectionError> {
+    Ok(recorder.status())
+}
+
+/// 清空黑匣子缓存
+#[tauri::command]
+pub async fn clear_blackbox(
+    recorder: State<'_, BlackBoxRecorder>,
+    session: Option<String>
+) -> Result<String, DetectionError> {
+    recorder.clear(session.as_deref());
+    Ok("黑匣子缓存已清空".to_string())
+}
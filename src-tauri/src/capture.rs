@@ -0,0 +1,500 @@
+/*!
+视频帧采集模块
+统一摄像头、RTSP网络摄像头、视频文件等输入源的读帧接口，采集在独立线程中阻塞执行。
+摄像头、网络流与视频文件解码依赖OpenCV，需启用 `opencv-support` 特性编译
+*/
+
+use anyhow::Result;
+use image::RgbImage;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(feature = "opencv-support"))]
+use crate::error::DetectionError;
+
+/// 未启用OpenCV时的错误提示
+#[cfg(not(feature = "opencv-support"))]
+const OPENCV_DISABLED: &str = "当前版本未启用摄像头支持，请使用 `--features opencv-support` 重新编译";
+
+/// 枚举摄像头时探测的设备序号上限
+pub const MAX_PROBE_DEVICES: i32 = 8;
+
+/// 摄像头支持的采集模式
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraMode {
+    pub width: u32,
+    pub height: u32,
+    pub fps: f32,
+}
+
+/// 摄像头设备信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraInfo {
+    pub index: i32,
+    pub name: String,
+    pub modes: Vec<CameraMode>,
+    pub in_use: bool, // 正被本应用占用，未探测采集模式
+}
+
+/// 摄像头采集参数（未设置的字段保持设备当前值）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CameraProperties {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<f32>,      // 目标采集帧率
+    pub exposure: Option<f64>, // 曝光值（含义取决于驱动，设置后关闭自动曝光）
+    pub gain: Option<f64>,
+    pub auto_focus: Option<bool>,
+}
+
+impl CameraProperties {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 用新设置覆盖已有设置
+    pub fn merge(&mut self, other: &CameraProperties) {
+        self.width = other.width.or(self.width);
+        self.height = other.height.or(self.height);
+        self.fps = other.fps.or(self.fps);
+        self.exposure = other.exposure.or(self.exposure);
+        self.gain = other.gain.or(self.gain);
+        self.auto_focus = other.auto_focus.or(self.auto_focus);
+    }
+}
+
+/// 视频文件信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VideoInfo {
+    pub total_frames: u64,
+    pub fps: f64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl VideoInfo {
+    /// 视频时长（毫秒），帧率未知时为0
+    pub fn duration_ms(&self) -> u64 {
+        if self.fps > 0.0 { (self.total_frames as f64 * 1000.0 / self.fps) as u64 } else { 0 }
+    }
+}
+
+/// 去掉流地址中的账号密码，用于日志与界面展示
+pub fn redact_url(url: &str) -> String {
+    match (url.find("://"), url.rfind('@')) {
+        (Some(scheme_end), Some(at)) if at > scheme_end => format!("{}***@{}", &url[..scheme_end + 3], &url[at + 1..]),
+        _ => url.to_string(),
+    }
+}
+
+/// 设备名称（Linux从video4linux读取，其他平台使用序号）
+#[cfg(feature = "opencv-support")]
+fn camera_name(index: i32) -> String {
+    std::fs::read_to_string(format!("/sys/class/video4linux/video{}/name", index))
+        .map(|name| name.trim().to_string())
+        .ok()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| format!("摄像头 {}", index))
+}
+
+/// 帧输入源
+pub trait FrameSource: Send {
+    /// 输入源描述（日志与界面展示）
+    fn describe(&self) -> String;
+
+    /// 读取下一帧，输入结束时返回 None
+    fn read_frame(&mut self) -> Result<Option<RgbImage>>;
+
+    /// 应用采集参数，返回设备实际生效的值
+    fn apply_properties(&mut self, _properties: &CameraProperties) -> Result<CameraProperties> {
+        Err(anyhow::anyhow!("{} 不支持设置采集参数", self.describe()))
+    }
+
+    /// 读帧失败时是否由输入源自行重连（不因连续失败结束采集）
+    fn reconnects(&self) -> bool {
+        false
+    }
+
+    /// 视频文件信息，实时输入源返回 None
+    fn video_info(&self) -> Option<VideoInfo> {
+        None
+    }
+
+    /// 最近读取的帧在视频中的序号
+    fn position(&self) -> Option<u64> {
+        None
+    }
+
+    /// 跳转到指定帧，下一次读取从该帧开始
+    fn seek(&mut self, _frame: u64) -> Result<()> {
+        Err(anyhow::anyhow!("{} 不支持跳转", self.describe()))
+    }
+}
+
+/// 打开摄像头
+#[cfg(feature = "opencv-support")]
+pub fn open_camera(device_id: i32) -> Result<Box<dyn FrameSource>> {
+    Ok(Box::new(opencv_backend::OpenCvCapture::camera(device_id)?))
+}
+
+/// 打开摄像头
+#[cfg(not(feature = "opencv-support"))]
+pub fn open_camera(_device_id: i32) -> Result<Box<dyn FrameSource>> {
+    Err(DetectionError::FeatureDisabled(OPENCV_DISABLED.to_string()).into())
+}
+
+/// 打开RTSP/HTTP网络视频流，断流后自动重连
+#[cfg(feature = "opencv-support")]
+pub fn open_stream(url: &str) -> Result<Box<dyn FrameSource>> {
+    Ok(Box::new(opencv_backend::NetworkStream::open(url)?))
+}
+
+/// 打开RTSP/HTTP网络视频流
+#[cfg(not(feature = "opencv-support"))]
+pub fn open_stream(_url: &str) -> Result<Box<dyn FrameSource>> {
+    Err(DetectionError::FeatureDisabled(OPENCV_DISABLED.to_string()).into())
+}
+
+/// 打开视频文件
+#[cfg(feature = "opencv-support")]
+pub fn open_video(path: &str) -> Result<Box<dyn FrameSource>> {
+    Ok(Box::new(opencv_backend::VideoFile::open(path)?))
+}
+
+/// 打开视频文件
+#[cfg(not(feature = "opencv-support"))]
+pub fn open_video(_path: &str) -> Result<Box<dyn FrameSource>> {
+    Err(DetectionError::FeatureDisabled(OPENCV_DISABLED.to_string()).into())
+}
+
+/// 探测可用的摄像头，`in_use` 返回true的设备只列出不打开
+#[cfg(feature = "opencv-support")]
+pub fn list_cameras(in_use: impl Fn(i32) -> bool) -> Result<Vec<CameraInfo>> {
+    let mut cameras = Vec::new();
+    for index in 0..MAX_PROBE_DEVICES {
+        if in_use(index) {
+            cameras.push(CameraInfo { index, name: camera_name(index), modes: Vec::new(), in_use: true });
+            continue;
+        }
+        if let Some(modes) = opencv_backend::probe_modes(index)? {
+            cameras.push(CameraInfo { index, name: camera_name(index), modes, in_use: false });
+        }
+    }
+    Ok(cameras)
+}
+
+/// 探测可用的摄像头
+#[cfg(not(feature = "opencv-support"))]
+pub fn list_cameras(_in_use: impl Fn(i32) -> bool) -> Result<Vec<CameraInfo>> {
+    Err(DetectionError::FeatureDisabled(OPENCV_DISABLED.to_string()).into())
+}
+
+#[cfg(feature = "opencv-support")]
+mod opencv_backend {
+    use std::time::{Duration, Instant};
+
+    use anyhow::{anyhow, Result};
+    use image::RgbImage;
+    use opencv::core::{Mat, Vector};
+    use opencv::imgproc::{cvt_color, COLOR_BGR2RGB};
+    use opencv::prelude::*;
+    use opencv::videoio::{
+        VideoCapture, CAP_ANY, CAP_FFMPEG, CAP_PROP_AUTOFOCUS, CAP_PROP_AUTO_EXPOSURE, CAP_PROP_BUFFERSIZE,
+        CAP_PROP_EXPOSURE, CAP_PROP_FPS, CAP_PROP_FRAME_COUNT, CAP_PROP_FRAME_HEIGHT, CAP_PROP_FRAME_WIDTH,
+        CAP_PROP_GAIN, CAP_PROP_OPEN_TIMEOUT_MSEC, CAP_PROP_POS_FRAMES, CAP_PROP_READ_TIMEOUT_MSEC,
+    };
+
+    use super::{redact_url, CameraMode, CameraProperties, FrameSource, VideoInfo};
+
+    /// V4L2 后端中 CAP_PROP_AUTO_EXPOSURE 的手动曝光取值
+    const MANUAL_EXPOSURE: f64 = 0.25;
+
+    /// 网络流连接/读帧超时（毫秒）
+    const STREAM_TIMEOUT_MS: i32 = 5000;
+
+    /// 断流重连的退避间隔
+    const RECONNECT_INITIAL: Duration = Duration::from_secs(1);
+    const RECONNECT_MAX: Duration = Duration::from_secs(30);
+
+    /// 探测采集模式时尝试的常见分辨率
+    const CANDIDATE_RESOLUTIONS: &[(u32, u32)] = &[
+        (320, 240),
+        (640, 480),
+        (800, 600),
+        (1280, 720),
+        (1920, 1080),
+        (2560, 1440),
+        (3840, 2160),
+    ];
+
+    /// 逐个设置候选分辨率并读回实际值，设备不存在时返回 None
+    pub fn probe_modes(index: i32) -> Result<Option<Vec<CameraMode>>> {
+        let mut capture = match VideoCapture::new(index, CAP_ANY) {
+            Ok(capture) if capture.is_opened()? => capture,
+            _ => return Ok(None),
+        };
+        let mut modes: Vec<CameraMode> = Vec::new();
+        for &(width, height) in CANDIDATE_RESOLUTIONS {
+            capture.set(CAP_PROP_FRAME_WIDTH, width as f64)?;
+            capture.set(CAP_PROP_FRAME_HEIGHT, height as f64)?;
+            let mode = CameraMode {
+                width: capture.get(CAP_PROP_FRAME_WIDTH)? as u32,
+                height: capture.get(CAP_PROP_FRAME_HEIGHT)? as u32,
+                fps: capture.get(CAP_PROP_FPS)? as f32,
+            };
+            // 驱动不支持时会回落到最接近的分辨率，去重后即为实际支持的模式
+            if mode.width > 0 && !modes.iter().any(|m| m.width == mode.width && m.height == mode.height) {
+                modes.push(mode);
+            }
+        }
+        capture.release()?;
+        Ok(Some(modes))
+    }
+
+    /// 基于OpenCV VideoCapture的帧输入源
+    pub struct OpenCvCapture {
+        capture: VideoCapture,
+        description: String,
+        frame: Mat,
+        rgb: Mat,
+    }
+
+    impl OpenCvCapture {
+        pub fn camera(device_id: i32) -> Result<Self> {
+            let capture = VideoCapture::new(device_id, CAP_ANY)?;
+            if !capture.is_opened()? {
+                return Err(anyhow!("无法打开摄像头 {}", device_id));
+            }
+            Ok(Self {
+                capture,
+                description: format!("摄像头 {}", device_id),
+                frame: Mat::default(),
+                rgb: Mat::default(),
+            })
+        }
+    }
+
+    /// BGR帧转换为RGB图像
+    fn to_rgb(frame: &Mat, rgb: &mut Mat) -> Result<RgbImage> {
+        // cvt_color 输出为新分配的连续内存，可直接按字节读取
+        cvt_color(frame, rgb, COLOR_BGR2RGB, 0)?;
+        let (width, height) = (rgb.cols() as u32, rgb.rows() as u32);
+        RgbImage::from_raw(width, height, rgb.data_bytes()?.to_vec())
+            .ok_or_else(|| anyhow!("帧数据尺寸异常: {}x{}", width, height))
+    }
+
+    /// 读取一帧并从BGR转换为RGB
+    fn read_rgb(capture: &mut VideoCapture, frame: &mut Mat, rgb: &mut Mat, description: &str) -> Result<RgbImage> {
+        if !capture.read(frame)? || frame.empty() {
+            return Err(anyhow!("{} 读取帧失败", description));
+        }
+        to_rgb(frame, rgb)
+    }
+
+    impl FrameSource for OpenCvCapture {
+        fn describe(&self) -> String {
+            self.description.clone()
+        }
+
+        fn read_frame(&mut self) -> Result<Option<RgbImage>> {
+            read_rgb(&mut self.capture, &mut self.frame, &mut self.rgb, &self.description).map(Some)
+        }
+
+        fn apply_properties(&mut self, properties: &CameraProperties) -> Result<CameraProperties> {
+            let capture = &mut self.capture;
+            if let Some(width) = properties.width {
+                capture.set(CAP_PROP_FRAME_WIDTH, width as f64)?;
+            }
+            if let Some(height) = properties.height {
+                capture.set(CAP_PROP_FRAME_HEIGHT, height as f64)?;
+            }
+            if let Some(fps) = properties.fps {
+                capture.set(CAP_PROP_FPS, fps as f64)?;
+            }
+            if let Some(exposure) = properties.exposure {
+                capture.set(CAP_PROP_AUTO_EXPOSURE, MANUAL_EXPOSURE)?;
+                capture.set(CAP_PROP_EXPOSURE, exposure)?;
+            }
+            if let Some(gain) = properties.gain {
+                capture.set(CAP_PROP_GAIN, gain)?;
+            }
+            if let Some(auto_focus) = properties.auto_focus {
+                capture.set(CAP_PROP_AUTOFOCUS, if auto_focus { 1.0 } else { 0.0 })?;
+            }
+
+            // 读回设备实际生效的值（驱动可能调整或忽略请求）
+            Ok(CameraProperties {
+                width: Some(capture.get(CAP_PROP_FRAME_WIDTH)? as u32),
+                height: Some(capture.get(CAP_PROP_FRAME_HEIGHT)? as u32),
+                fps: Some(capture.get(CAP_PROP_FPS)? as f32),
+                exposure: Some(capture.get(CAP_PROP_EXPOSURE)?),
+                gain: Some(capture.get(CAP_PROP_GAIN)?),
+                auto_focus: Some(capture.get(CAP_PROP_AUTOFOCUS)? != 0.0),
+            })
+        }
+    }
+
+    /// RTSP/HTTP网络视频流，断流后按指数退避自动重连
+    pub struct NetworkStream {
+        url: String,
+        description: String,
+        capture: Option<VideoCapture>, // None 表示已断开，等待重连
+        next_retry: Instant,
+        backoff: Duration,
+        frame: Mat,
+        rgb: Mat,
+    }
+
+    impl NetworkStream {
+        /// 建立首次连接，失败直接返回错误以便用户检查地址
+        pub fn open(url: &str) -> Result<Self> {
+            let description = redact_url(url);
+            let capture = Self::connect(url).map_err(|e| anyhow!("无法连接视频流 {}: {}", description, e))?;
+            Ok(Self {
+                url: url.to_string(),
+                description,
+                capture: Some(capture),
+                next_retry: Instant::now(),
+                backoff: RECONNECT_INITIAL,
+                frame: Mat::default(),
+                rgb: Mat::default(),
+            })
+        }
+
+        fn connect(url: &str) -> Result<VideoCapture> {
+            let params = Vector::<i32>::from_slice(&[
+                CAP_PROP_OPEN_TIMEOUT_MSEC,
+                STREAM_TIMEOUT_MS,
+                CAP_PROP_READ_TIMEOUT_MSEC,
+                STREAM_TIMEOUT_MS,
+            ]);
+            let mut capture = VideoCapture::from_file_with_params(url, CAP_FFMPEG, &params)?;
+            if !capture.is_opened()? {
+                return Err(anyhow!("打开失败"));
+            }
+            // 只缓存最新帧，降低实时检测延迟
+            let _ = capture.set(CAP_PROP_BUFFERSIZE, 1.0);
+            Ok(capture)
+        }
+
+        /// 断开连接并安排下一次重连
+        fn disconnect(&mut self, reason: &anyhow::Error) {
+            if let Some(mut capture) = self.capture.take() {
+                let _ = capture.release();
+            }
+            tracing::warn!("⚠️  {} 断流: {}，{} 秒后重连", self.description, reason, self.backoff.as_secs());
+            self.next_retry = Instant::now() + self.backoff;
+            self.backoff = (self.backoff * 2).min(RECONNECT_MAX);
+        }
+    }
+
+    impl FrameSource for NetworkStream {
+        fn describe(&self) -> String {
+            self.description.clone()
+        }
+
+        fn read_frame(&mut self) -> Result<Option<RgbImage>> {
+            if self.capture.is_none() {
+                // 未到重连时间时立即返回，由采集线程短暂休眠后再次调用，保证能及时响应停止
+                if Instant::now() < self.next_retry {
+                    return Err(anyhow!("{} 等待重连", self.description));
+                }
+                match Self::connect(&self.url) {
+                    Ok(capture) => {
+                        tracing::info!("🔗 {} 已重新连接", self.description);
+                        self.capture = Some(capture);
+                    }
+                    Err(e) => {
+                        self.disconnect(&e);
+                        return Err(e);
+                    }
+                }
+            }
+
+            let capture = self.capture.as_mut().ok_or_else(|| anyhow!("{} 未连接", self.description))?;
+            match read_rgb(capture, &mut self.frame, &mut self.rgb, &self.description) {
+                Ok(image) => {
+                    self.backoff = RECONNECT_INITIAL;
+                    Ok(Some(image))
+                }
+                Err(e) => {
+                    self.disconnect(&e);
+                    Err(e)
+                }
+            }
+        }
+
+        fn reconnects(&self) -> bool {
+            true
+        }
+    }
+
+    /// 视频文件，按顺序逐帧解码，读到末尾时结束
+    pub struct VideoFile {
+        capture: VideoCapture,
+        description: String,
+        info: VideoInfo,
+        next_frame: u64, // 下一次读取的帧序号
+        frame: Mat,
+        rgb: Mat,
+    }
+
+    impl VideoFile {
+        pub fn open(path: &str) -> Result<Self> {
+            let capture = VideoCapture::from_file(path, CAP_ANY)?;
+            if !capture.is_opened()? {
+                return Err(anyhow!("无法打开视频文件: {}", path));
+            }
+            let info = VideoInfo {
+                total_frames: capture.get(CAP_PROP_FRAME_COUNT)?.max(0.0) as u64,
+                fps: capture.get(CAP_PROP_FPS)?,
+                width: capture.get(CAP_PROP_FRAME_WIDTH)? as u32,
+                height: capture.get(CAP_PROP_FRAME_HEIGHT)? as u32,
+            };
+            tracing::info!(
+                "🎞️ 打开视频 {}: {}x{}，{} 帧，{:.2} FPS",
+                path, info.width, info.height, info.total_frames, info.fps
+            );
+            Ok(Self {
+                capture,
+                description: path.to_string(),
+                info,
+                next_frame: 0,
+                frame: Mat::default(),
+                rgb: Mat::default(),
+            })
+        }
+    }
+
+    impl FrameSource for VideoFile {
+        fn describe(&self) -> String {
+            self.description.clone()
+        }
+
+        fn read_frame(&mut self) -> Result<Option<RgbImage>> {
+            // 视频文件读取失败即视为播放结束
+            if !self.capture.read(&mut self.frame)? || self.frame.empty() {
+                return Ok(None);
+            }
+            let image = to_rgb(&self.frame, &mut self.rgb)?;
+            self.next_frame += 1;
+            Ok(Some(image))
+        }
+
+        fn video_info(&self) -> Option<VideoInfo> {
+            Some(self.info.clone())
+        }
+
+        fn position(&self) -> Option<u64> {
+            self.next_frame.checked_sub(1)
+        }
+
+        fn seek(&mut self, frame: u64) -> Result<()> {
+            let frame = frame.min(self.info.total_frames.saturating_sub(1));
+            if !self.capture.set(CAP_PROP_POS_FRAMES, frame as f64)? {
+                return Err(anyhow!("{} 跳转到第 {} 帧失败", self.description, frame));
+            }
+            self.next_frame = frame;
+            Ok(())
+        }
+    }
+}
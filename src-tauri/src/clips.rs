@@ -0,0 +1,343 @@
+/*!
+事件片段提取模块
+围绕异常告警时间点从视频中截取前后N秒的短片段，并生成清单文件，
+审核人员只需查看关键片段
+视频文件检测过程中记录触发告警的视频时间点，检测结束后按自动片段配置在后台截取（实时输入由事件录像模块录制告警片段）
+*/
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::DetectionError;
+use crate::ffmpeg;
+use crate::tasks::{TaskHandle, TaskManager};
+
+/// 清单文件名
+pub const MANIFEST_FILE_NAME: &str = "clips_manifest.json";
+
+/// 自动片段配置文件名（位于应用数据目录）
+pub const CONFIG_FILE_NAME: &str = "event_clips.json";
+
+/// 未指定输出目录时使用的默认子目录（位于应用数据目录）
+const DEFAULT_OUTPUT_DIR: &str = "event_clips";
+
+/// 触发片段的事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipEvent {
+    pub timestamp_secs: f64,      // 事件在视频中的时间点（秒）
+    pub label: Option<String>,    // 事件描述，如类别名称
+    pub alert_id: Option<i64>,    // 关联的告警ID
+}
+
+/// 片段提取参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipOptions {
+    pub pre_seconds: f64,
+    pub post_seconds: f64,
+    #[serde(default)]
+    pub reencode: bool, // 默认直接复制码流（按关键帧切割），重新编码可精确切割
+}
+
+impl Default for ClipOptions {
+    fn default() -> Self {
+        Self {
+            pre_seconds: 5.0,
+            post_seconds: 5.0,
+            reencode: false,
+        }
+    }
+}
+
+impl ClipOptions {
+    fn validate(&self) -> Result<()> {
+        if !(self.pre_seconds.is_finite() && self.pre_seconds >= 0.0 && self.post_seconds.is_finite() && self.post_seconds >= 0.0) {
+            return Err(DetectionError::InvalidInput("片段前后时长必须为非负数".to_string()).into());
+        }
+        Ok(())
+    }
+}
+
+/// 视频检测结束后自动截取片段的配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoClipConfig {
+    pub enabled: bool,              // 视频文件检测结束后按告警自动截取片段
+    pub output_dir: Option<String>, // 为空时使用应用数据目录下的 event_clips
+    pub options: ClipOptions,
+}
+
+impl Default for AutoClipConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            output_dir: None,
+            options: ClipOptions::default(),
+        }
+    }
+}
+
+/// 自动片段设置（Tauri托管状态）
+pub struct ClipSettings {
+    path: PathBuf,
+    default_output_dir: PathBuf,
+    config: RwLock<AutoClipConfig>,
+}
+
+impl ClipSettings {
+    /// 读取已保存的配置，文件不存在或无效时使用默认配置
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(CONFIG_FILE_NAME);
+        let config = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<AutoClipConfig>(&content).ok())
+            .filter(|config| config.options.validate().is_ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            default_output_dir: data_dir.join(DEFAULT_OUTPUT_DIR),
+            config: RwLock::new(config),
+        }
+    }
+
+    pub fn config(&self) -> AutoClipConfig {
+        self.config.read().clone()
+    }
+
+    fn save(&self, config: AutoClipConfig) -> Result<()> {
+        config.options.validate()?;
+        if config.output_dir.as_deref().is_some_and(|dir| dir.trim().is_empty()) {
+            return Err(DetectionError::InvalidInput("输出目录不能为空字符串".to_string()).into());
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&config)?)?;
+        *self.config.write() = config;
+        Ok(())
+    }
+
+    /// 一次视频检测的片段目录：`<输出目录>/<视频名>_<时间>`
+    fn run_output_dir(&self, config: &AutoClipConfig, video_path: &Path) -> PathBuf {
+        let base = config
+            .output_dir
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.default_output_dir.clone());
+        let stem = video_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("video");
+        base.join(format!("{}_{}", stem, chrono::Local::now().format("%Y%m%d_%H%M%S")))
+    }
+}
+
+/// 已生成的片段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventClip {
+    pub index: u32,
+    pub file: String,
+    pub start_secs: f64,
+    pub end_secs: f64,
+    pub events: Vec<ClipEvent>,
+}
+
+/// 片段清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipManifest {
+    pub video_path: String,
+    pub created_at: String,
+    pub pre_seconds: f64,
+    pub post_seconds: f64,
+    pub clips: Vec<EventClip>,
+    pub failures: Vec<String>,
+}
+
+/// 将事件合并为互不重叠的时间窗口
+fn merge_windows(mut events: Vec<ClipEvent>, options: &ClipOptions) -> Vec<(f64, f64, Vec<ClipEvent>)> {
+    events.retain(|e| e.timestamp_secs.is_finite() && e.timestamp_secs >= 0.0);
+    events.sort_by(|a, b| a.timestamp_secs.total_cmp(&b.timestamp_secs));
+
+    let mut windows: Vec<(f64, f64, Vec<ClipEvent>)> = Vec::new();
+    for event in events {
+        let start = (event.timestamp_secs - options.pre_seconds).max(0.0);
+        let end = event.timestamp_secs + options.post_seconds;
+        match windows.last_mut() {
+            Some(last) if start <= last.1 => {
+                last.1 = last.1.max(end);
+                last.2.push(event);
+            }
+            _ => windows.push((start, end, vec![event])),
+        }
+    }
+    windows
+}
+
+/// 从视频中提取事件片段并写入清单
+pub async fn extract_clips(
+    video_path: &Path,
+    events: Vec<ClipEvent>,
+    options: &ClipOptions,
+    output_dir: &Path,
+    task: &TaskHandle,
+) -> Result<ClipManifest> {
+    if !video_path.is_file() {
+        return Err(anyhow!("视频文件不存在: {}", video_path.display()));
+    }
+    options.validate()?;
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| anyhow!("创建输出目录失败 {}: {}", output_dir.display(), e))?;
+
+    let stem = video_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("video")
+        .to_string();
+    let extension = video_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("mp4")
+        .to_string();
+
+    let mut clips = Vec::new();
+    let mut failures = Vec::new();
+
+    let windows = merge_windows(events, options);
+    let total = windows.len() as u64;
+    for (index, (start, end, window_events)) in windows.into_iter().enumerate() {
+        if let Err(e) = task.advance(index as u64, Some(total)) {
+            tracing::info!("⏹️ 事件片段提取已取消，已完成 {} 个片段", clips.len());
+            return Err(e.into());
+        }
+        let file_name = format!("{}_clip{:03}_{:.0}s.{}", stem, index + 1, start, extension);
+        let clip_path: PathBuf = output_dir.join(&file_name);
+
+        let mut args: Vec<String> = vec![
+            "-y".into(),
+            "-ss".into(),
+            ffmpeg::format_seconds(start),
+            "-i".into(),
+            video_path.to_string_lossy().to_string(),
+            "-t".into(),
+            ffmpeg::format_seconds(end - start),
+        ];
+        if options.reencode {
+            args.extend(["-c:v", "libx264", "-preset", "veryfast", "-c:a", "aac"].map(String::from));
+        } else {
+            args.extend(["-c", "copy", "-avoid_negative_ts", "make_zero"].map(String::from));
+        }
+        args.push(clip_path.to_string_lossy().to_string());
+
+        match ffmpeg::run(&args, task.token()).await {
+            Ok(()) => {
+                tracing::info!("🎬 已截取事件片段: {} ({:.1}s - {:.1}s)", file_name, start, end);
+                clips.push(EventClip {
+                    index: index as u32 + 1,
+                    file: file_name,
+                    start_secs: start,
+                    end_secs: end,
+                    events: window_events,
+                });
+            }
+            Err(e) if task.is_cancelled() => {
+                let _ = std::fs::remove_file(&clip_path);
+                return Err(e);
+            }
+            Err(e) => failures.push(format!("片段 {} ({:.1}s - {:.1}s): {}", index + 1, start, end, e)),
+        }
+    }
+
+    let manifest = ClipManifest {
+        video_path: video_path.to_string_lossy().to_string(),
+        created_at: crate::storage::now_rfc3339(),
+        pre_seconds: options.pre_seconds,
+        post_seconds: options.post_seconds,
+        clips,
+        failures,
+    };
+    std::fs::write(
+        output_dir.join(MANIFEST_FILE_NAME),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+
+    Ok(manifest)
+}
+
+/// 视频文件检测结束后按记录的告警时间点在后台截取片段（未开启或没有告警时直接返回）
+pub fn spawn_for_video(app: &AppHandle, video_path: String, events: Vec<ClipEvent>) {
+    let config = app.state::<ClipSettings>().config();
+    if !config.enabled || events.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let video_path = PathBuf::from(video_path);
+        let output_dir = app.state::<ClipSettings>().run_output_dir(&config, &video_path);
+        let tasks = app.state::<TaskManager>();
+        let task = match tasks.begin(&app, "clips", None) {
+            Ok(task) => task,
+            Err(e) => {
+                tracing::error!("自动截取事件片段失败: {}", e);
+                return;
+            }
+        };
+        tracing::info!("🎬 视频检测结束，按 {} 个告警截取事件片段: {}", events.len(), video_path.display());
+        let result = extract_clips(&video_path, events, &config.options, &output_dir, &task).await;
+        task.finish(&result);
+        match result {
+            Ok(manifest) => tracing::info!(
+                "🎬 自动截取事件片段完成: {} 个片段，{} 个失败，清单位于 {}",
+                manifest.clips.len(),
+                manifest.failures.len(),
+                output_dir.join(MANIFEST_FILE_NAME).display()
+            ),
+            Err(e) => tracing::error!("自动截取事件片段失败: {}", e),
+        }
+    });
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 获取自动片段配置
+#[tauri::command]
+pub async fn get_auto_clip_config(
+    settings: State<'_, ClipSettings>
+) -> Result<AutoClipConfig, DetectionError> {
+    Ok(settings.config())
+}
+
+/// 保存自动片段配置（之后结束的视频检测生效）
+#[tauri::command]
+pub async fn set_auto_clip_config(
+    settings: State<'_, ClipSettings>,
+    config: AutoClipConfig
+) -> Result<AutoClipConfig, DetectionError> {
+    match settings.save(config) {
+        Ok(()) => Ok(settings.config()),
+        Err(e) => Err(DetectionError::from(e).context("保存自动片段配置失败")),
+    }
+}
+
+/// 围绕事件时间点截取视频片段
+#[tauri::command]
+pub async fn extract_event_clips(
+    app: AppHandle,
+    tasks: State<'_, TaskManager>,
+    video_path: String,
+    events: Vec<ClipEvent>,
+    options: Option<ClipOptions>,
+    output_dir: String,
+    task_id: Option<String>
+) -> Result<ClipManifest, DetectionError> {
+    let options = options.unwrap_or_default();
+    let task = tasks.begin(&app, "clips", task_id)?;
+    let result = extract_clips(Path::new(&video_path), events, &options, Path::new(&output_dir), &task).await;
+    task.finish(&result);
+    match result {
+        Ok(manifest) => Ok(manifest),
+        Err(e) => Err(DetectionError::from(e).context("提取事件片段失败")),
+    }
+}
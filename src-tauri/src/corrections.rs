@@ -0,0 +1,712 @@
+/*!
+操作员修正/反馈模块
+对已保存的检测结果进行确认、误报标记、类别修正和漏检补充，确认与类别修正可同时给出修正后的检测框
+修正记录与模型原始输出分开保存，导出与统计时带有复核状态
+*/
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::DetectionError;
+use crate::history::{self, DetectionRun, HistoryFilter};
+use crate::retraining;
+use crate::storage::{now_rfc3339, Database};
+use crate::AppState;
+
+/// 修正类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorrectionKind {
+    Confirmed,     // 确认检测正确（可附带修正后的检测框）
+    FalsePositive, // 误报
+    Relabel,       // 类别修正
+    Added,         // 手动补充的漏检框
+}
+
+impl CorrectionKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CorrectionKind::Confirmed => "confirmed",
+            CorrectionKind::FalsePositive => "false_positive",
+            CorrectionKind::Relabel => "relabel",
+            CorrectionKind::Added => "added",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "confirmed" => CorrectionKind::Confirmed,
+            "false_positive" => CorrectionKind::FalsePositive,
+            "relabel" => CorrectionKind::Relabel,
+            _ => CorrectionKind::Added,
+        }
+    }
+}
+
+/// 修正记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Correction {
+    pub id: i64,
+    pub run_id: i64,
+    pub detection_id: Option<i64>, // 手动补充的框没有对应的模型检测
+    pub kind: CorrectionKind,
+    pub class_id: Option<u32>,
+    pub class_name: Option<String>,
+    pub bbox: Option<[f32; 4]>,
+    pub operator_id: String,
+    pub comment: Option<String>,
+    pub created_at: String,
+}
+
+/// 检测框来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionOrigin {
+    Model,     // 模型原始输出
+    Relabeled, // 模型输出，类别经过修正
+    Manual,    // 操作员手动添加
+}
+
+/// 检测框的复核状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewStatus {
+    #[default]
+    Unreviewed,    // 未复核
+    Confirmed,     // 确认正确
+    FalsePositive, // 误报
+    Relabeled,     // 类别已修正
+    Missed,        // 漏检，由操作员补充
+}
+
+impl ReviewStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewStatus::Unreviewed => "unreviewed",
+            ReviewStatus::Confirmed => "confirmed",
+            ReviewStatus::FalsePositive => "false_positive",
+            ReviewStatus::Relabeled => "relabeled",
+            ReviewStatus::Missed => "missed",
+        }
+    }
+}
+
+/// 应用修正后的检测框
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveDetection {
+    pub detection_id: Option<i64>,
+    pub class_id: u32,
+    pub class_name: String,
+    pub confidence: f32,
+    pub bbox: [f32; 4],
+    pub origin: DetectionOrigin,
+    pub false_positive: bool,
+    pub corrected: bool,
+    #[serde(default)]
+    pub review: ReviewStatus,
+    #[serde(default)]
+    pub bbox_corrected: bool, // 检测框坐标经过操作员修正
+}
+
+/// 复核统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReviewStats {
+    pub run_count: u64,
+    pub reviewed_run_count: u64,       // 至少有一条修正记录的运行数
+    pub detection_count: u64,          // 模型输出的检测框数
+    pub unreviewed_count: u64,
+    pub confirmed_count: u64,
+    pub false_positive_count: u64,
+    pub relabeled_count: u64,
+    pub missed_count: u64,             // 操作员补充的漏检框数
+    pub bbox_corrected_count: u64,
+    pub precision: Option<f32>,        // 已复核检测框中非误报的比例
+    pub recall: Option<f32>,           // 已复核运行中模型检出（非误报）占全部真实目标的比例
+}
+
+/// 带修正信息的检测运行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrectedRun {
+    pub run: DetectionRun,
+    pub corrections: Vec<Correction>,
+    pub detections: Vec<EffectiveDetection>,
+}
+
+/// 初始化修正记录表结构
+pub fn init_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS detection_corrections (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            run_id INTEGER NOT NULL REFERENCES detection_runs(id) ON DELETE CASCADE,
+            detection_id INTEGER REFERENCES detections(id) ON DELETE CASCADE,
+            kind TEXT NOT NULL,
+            class_id INTEGER,
+            class_name TEXT,
+            x REAL,
+            y REAL,
+            width REAL,
+            height REAL,
+            operator_id TEXT NOT NULL,
+            comment TEXT,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_corrections_run ON detection_corrections(run_id);",
+    )?;
+    Ok(())
+}
+
+const CORRECTION_COLUMNS: &str =
+    "id, run_id, detection_id, kind, class_id, class_name, x, y, width, height, operator_id, comment, created_at";
+
+fn row_to_correction(row: &Row) -> rusqlite::Result<Correction> {
+    let bbox = match (
+        row.get::<_, Option<f64>>(6)?,
+        row.get::<_, Option<f64>>(7)?,
+        row.get::<_, Option<f64>>(8)?,
+        row.get::<_, Option<f64>>(9)?,
+    ) {
+        (Some(x), Some(y), Some(w), Some(h)) => Some([x as f32, y as f32, w as f32, h as f32]),
+        _ => None,
+    };
+
+    Ok(Correction {
+        id: row.get(0)?,
+        run_id: row.get(1)?,
+        detection_id: row.get(2)?,
+        kind: CorrectionKind::parse(&row.get::<_, String>(3)?),
+        class_id: row.get(4)?,
+        class_name: row.get(5)?,
+        bbox,
+        operator_id: row.get(10)?,
+        comment: row.get(11)?,
+        created_at: row.get(12)?,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn insert_correction(
+    db: &Database,
+    run_id: i64,
+    detection_id: Option<i64>,
+    kind: CorrectionKind,
+    class: Option<(u32, &str)>,
+    bbox: Option<[f32; 4]>,
+    operator_id: &str,
+    comment: Option<&str>,
+) -> Result<Correction> {
+    let (class_id, class_name) = match class {
+        Some((id, name)) => (Some(id), Some(name)),
+        None => (None, None),
+    };
+    let [x, y, w, h] = match bbox {
+        Some(b) => b.map(|v| Some(v as f64)),
+        None => [None; 4],
+    };
+
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO detection_corrections
+                 (run_id, detection_id, kind, class_id, class_name, x, y, width, height, operator_id, comment, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                run_id,
+                detection_id,
+                kind.as_str(),
+                class_id,
+                class_name,
+                x,
+                y,
+                w,
+                h,
+                operator_id,
+                comment,
+                now_rfc3339(),
+            ],
+        )?;
+        let id = conn.last_insert_rowid();
+        conn.query_row(
+            &format!("SELECT {} FROM detection_corrections WHERE id = ?1", CORRECTION_COLUMNS),
+            params![id],
+            row_to_correction,
+        )
+    })
+}
+
+/// 校验检测框位于图像范围内
+fn check_bbox(run: &DetectionRun, bbox: [f32; 4]) -> Result<()> {
+    // NaN 与任何值比较都为假，必须先排除，否则会通过下面的范围检查
+    if !bbox.iter().all(|v| v.is_finite()) {
+        return Err(DetectionError::InvalidInput(format!("检测框包含无效数值: {:?}", bbox)).into());
+    }
+    let [x, y, w, h] = bbox;
+    if w <= 0.0 || h <= 0.0 || x < 0.0 || y < 0.0
+        || x + w > run.image_width as f32 || y + h > run.image_height as f32
+    {
+        return Err(DetectionError::InvalidInput(format!(
+            "检测框超出图像范围: {:?} (图像尺寸 {}x{})",
+            bbox,
+            run.image_width,
+            run.image_height
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// 校验修正后的检测框（可选）所在运行的图像范围
+fn check_corrected_bbox(db: &Database, run_id: i64, bbox: Option<[f32; 4]>) -> Result<()> {
+    if let Some(bbox) = bbox {
+        let run = history::get_run(db, run_id)?.ok_or_else(|| DetectionError::NotFound(format!("检测运行不存在: {}", run_id)))?;
+        check_bbox(&run, bbox)?;
+    }
+    Ok(())
+}
+
+/// 确认检测结果正确，可附带修正后的检测框
+pub fn confirm(
+    db: &Database,
+    detection_id: i64,
+    bbox: Option<[f32; 4]>,
+    operator_id: &str,
+    comment: Option<&str>,
+) -> Result<Correction> {
+    let detection = history::get_detection(db, detection_id)?
+        .ok_or_else(|| DetectionError::NotFound(format!("检测记录不存在: {}", detection_id)))?;
+    check_corrected_bbox(db, detection.run_id, bbox)?;
+    let correction = insert_correction(
+        db,
+        detection.run_id,
+        Some(detection_id),
+        CorrectionKind::Confirmed,
+        None,
+        bbox,
+        operator_id,
+        comment,
+    )?;
+    tracing::info!("📝 检测 #{} 已由 {} 确认", detection_id, operator_id);
+    Ok(correction)
+}
+
+/// 将检测框标记为误报
+pub fn mark_false_positive(db: &Database, detection_id: i64, operator_id: &str, comment: Option<&str>) -> Result<Correction> {
+    let detection = history::get_detection(db, detection_id)?
+        .ok_or_else(|| DetectionError::NotFound(format!("检测记录不存在: {}", detection_id)))?;
+    let correction = insert_correction(
+        db,
+        detection.run_id,
+        Some(detection_id),
+        CorrectionKind::FalsePositive,
+        None,
+        None,
+        operator_id,
+        comment,
+    )?;
+    tracing::info!("📝 检测 #{} 已由 {} 标记为误报", detection_id, operator_id);
+    Ok(correction)
+}
+
+/// 修正检测框的类别，可同时修正检测框
+#[allow(clippy::too_many_arguments)]
+pub fn relabel(
+    db: &Database,
+    detection_id: i64,
+    class_id: u32,
+    class_name: &str,
+    bbox: Option<[f32; 4]>,
+    operator_id: &str,
+    comment: Option<&str>,
+) -> Result<Correction> {
+    let detection = history::get_detection(db, detection_id)?
+        .ok_or_else(|| DetectionError::NotFound(format!("检测记录不存在: {}", detection_id)))?;
+    check_corrected_bbox(db, detection.run_id, bbox)?;
+    let correction = insert_correction(
+        db,
+        detection.run_id,
+        Some(detection_id),
+        CorrectionKind::Relabel,
+        Some((class_id, class_name)),
+        bbox,
+        operator_id,
+        comment,
+    )?;
+    tracing::info!("📝 检测 #{} 类别已由 {} 修正: {} → {}", detection_id, operator_id, detection.class_name, class_name);
+    Ok(correction)
+}
+
+/// 手动补充漏检的检测框
+pub fn add_missed(
+    db: &Database,
+    run_id: i64,
+    class_id: u32,
+    class_name: &str,
+    bbox: [f32; 4],
+    operator_id: &str,
+    comment: Option<&str>,
+) -> Result<Correction> {
+    let run = history::get_run(db, run_id)?.ok_or_else(|| DetectionError::NotFound(format!("检测运行不存在: {}", run_id)))?;
+    check_bbox(&run, bbox)?;
+
+    let correction = insert_correction(
+        db,
+        run_id,
+        None,
+        CorrectionKind::Added,
+        Some((class_id, class_name)),
+        Some(bbox),
+        operator_id,
+        comment,
+    )?;
+    tracing::info!("📝 运行 #{} 已由 {} 补充漏检框: {} {:?}", run_id, operator_id, class_name, bbox);
+    Ok(correction)
+}
+
+/// 查询修正记录（可按运行过滤）
+pub fn list(db: &Database, run_id: Option<i64>, limit: u32) -> Result<Vec<Correction>> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM detection_corrections WHERE (?1 IS NULL OR run_id = ?1) ORDER BY id ASC LIMIT ?2",
+            CORRECTION_COLUMNS
+        ))?;
+        let corrections = stmt
+            .query_map(params![run_id, limit], row_to_correction)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(corrections)
+    })
+}
+
+/// 一次查询读取多个检测运行的修正记录，按运行分组（组内按记录顺序）
+fn list_for_runs(db: &Database, run_ids: &[i64]) -> Result<HashMap<i64, Vec<Correction>>> {
+    let run_ids = serde_json::to_string(run_ids)?;
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM detection_corrections
+             WHERE run_id IN (SELECT value FROM json_each(?1))
+             ORDER BY run_id ASC, id ASC",
+            CORRECTION_COLUMNS
+        ))?;
+        let mut grouped: HashMap<i64, Vec<Correction>> = HashMap::new();
+        for correction in stmt.query_map(params![run_ids], row_to_correction)? {
+            let correction = correction?;
+            grouped.entry(correction.run_id).or_default().push(correction);
+        }
+        Ok(grouped)
+    })
+}
+
+/// 查询存在修正记录的检测运行ID
+pub fn corrected_run_ids(db: &Database) -> Result<Vec<i64>> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT DISTINCT run_id FROM detection_corrections ORDER BY run_id ASC")?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<i64>>>()?;
+        Ok(ids)
+    })
+}
+
+/// 将修正应用到模型输出上：同一检测框的修正按记录顺序依次应用，
+/// 之后的确认或类别修正会撤销此前的误报标记，类别与检测框坐标取最近一次修正的值
+pub fn apply_corrections(run: &DetectionRun, corrections: &[Correction]) -> Vec<EffectiveDetection> {
+    let mut effective: Vec<EffectiveDetection> = run
+        .detections
+        .iter()
+        .map(|d| {
+            let mut detection = EffectiveDetection {
+                detection_id: Some(d.id),
+                class_id: d.class_id,
+                class_name: d.class_name.clone(),
+                confidence: d.confidence,
+                bbox: d.bbox,
+                origin: DetectionOrigin::Model,
+                false_positive: false,
+                corrected: false,
+                review: ReviewStatus::Unreviewed,
+                bbox_corrected: false,
+            };
+
+            let mut applied: Vec<&Correction> = corrections.iter().filter(|c| c.detection_id == Some(d.id)).collect();
+            applied.sort_by_key(|c| c.id);
+            for c in applied {
+                detection.corrected = true;
+                match c.kind {
+                    CorrectionKind::FalsePositive => {
+                        detection.false_positive = true;
+                        detection.review = ReviewStatus::FalsePositive;
+                    }
+                    CorrectionKind::Confirmed => {
+                        detection.false_positive = false;
+                        detection.review = if detection.origin == DetectionOrigin::Relabeled {
+                            ReviewStatus::Relabeled
+                        } else {
+                            ReviewStatus::Confirmed
+                        };
+                    }
+                    CorrectionKind::Relabel => {
+                        if let (Some(class_id), Some(class_name)) = (c.class_id, c.class_name.clone()) {
+                            detection.class_id = class_id;
+                            detection.class_name = class_name;
+                            detection.origin = DetectionOrigin::Relabeled;
+                        }
+                        detection.false_positive = false;
+                        detection.review = ReviewStatus::Relabeled;
+                    }
+                    CorrectionKind::Added => continue,
+                }
+                if let Some(bbox) = c.bbox {
+                    detection.bbox = bbox;
+                    detection.bbox_corrected = true;
+                }
+            }
+            detection
+        })
+        .collect();
+
+    for c in corrections.iter().filter(|c| c.kind == CorrectionKind::Added) {
+        if let (Some(class_id), Some(class_name), Some(bbox)) = (c.class_id, c.class_name.clone(), c.bbox) {
+            effective.push(EffectiveDetection {
+                detection_id: None,
+                class_id,
+                class_name,
+                confidence: 1.0,
+                bbox,
+                origin: DetectionOrigin::Manual,
+                false_positive: false,
+                corrected: true,
+                review: ReviewStatus::Missed,
+                bbox_corrected: false,
+            });
+        }
+    }
+
+    effective
+}
+
+/// 查询带修正信息的检测运行
+pub fn get_corrected(db: &Database, run_id: i64) -> Result<Option<CorrectedRun>> {
+    match history::get_run(db, run_id)? {
+        Some(run) => with_corrections(db, run).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// 为已读取的检测运行附加修正记录与修正后的检测框
+pub fn with_corrections(db: &Database, run: DetectionRun) -> Result<CorrectedRun> {
+    let corrections = list(db, Some(run.id), u32::MAX)?;
+    let detections = apply_corrections(&run, &corrections);
+    Ok(CorrectedRun {
+        run,
+        corrections,
+        detections,
+    })
+}
+
+/// 统计一批检测运行的复核情况
+pub fn review_stats(db: &Database, runs: &[DetectionRun]) -> Result<ReviewStats> {
+    let mut stats = ReviewStats {
+        run_count: runs.len() as u64,
+        ..Default::default()
+    };
+    // 召回率只在已复核的运行上计算：未复核的运行无法知道是否有漏检
+    let mut reviewed_hits = 0u64;
+    let mut reviewed_truths = 0u64;
+
+    let run_ids: Vec<i64> = runs.iter().map(|run| run.id).collect();
+    let corrections = list_for_runs(db, &run_ids)?;
+    for run in runs {
+        let corrections = corrections.get(&run.id).map(Vec::as_slice).unwrap_or_default();
+        let detections = apply_corrections(run, corrections);
+        let reviewed = !corrections.is_empty();
+        if reviewed {
+            stats.reviewed_run_count += 1;
+        }
+        for detection in &detections {
+            match detection.review {
+                ReviewStatus::Unreviewed => stats.unreviewed_count += 1,
+                ReviewStatus::Confirmed => stats.confirmed_count += 1,
+                ReviewStatus::FalsePositive => stats.false_positive_count += 1,
+                ReviewStatus::Relabeled => stats.relabeled_count += 1,
+                ReviewStatus::Missed => stats.missed_count += 1,
+            }
+            if detection.bbox_corrected {
+                stats.bbox_corrected_count += 1;
+            }
+            if reviewed && !detection.false_positive {
+                reviewed_truths += 1;
+                if detection.origin != DetectionOrigin::Manual {
+                    reviewed_hits += 1;
+                }
+            }
+        }
+        stats.detection_count += run.detections.len() as u64;
+    }
+
+    let judged = stats.confirmed_count + stats.relabeled_count + stats.false_positive_count;
+    if judged > 0 {
+        stats.precision = Some((stats.confirmed_count + stats.relabeled_count) as f32 / judged as f32);
+    }
+    if reviewed_truths > 0 {
+        stats.recall = Some(reviewed_hits as f32 / reviewed_truths as f32);
+    }
+    Ok(stats)
+}
+
+/// 根据类别名称查找模型类别ID
+async fn resolve_class_id(state: &AppState, class_name: &str) -> Result<u32> {
+    let detector = state.read().await;
+    detector
+        .get_class_names()
+        .iter()
+        .find(|(_, name)| name.as_str() == class_name)
+        .map(|(id, _)| *id)
+        .ok_or_else(|| anyhow!("未知类别: {}", class_name))
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 确认检测结果（可附带修正后的检测框）
+#[tauri::command]
+pub async fn confirm_detection(
+    app: AppHandle,
+    db: State<'_, Database>,
+    detection_id: i64,
+    bbox: Option<[f32; 4]>,
+    operator_id: String,
+    comment: Option<String>
+) -> Result<Correction, DetectionError> {
+    if operator_id.trim().is_empty() {
+        return Err(DetectionError::InvalidInput("操作员ID不能为空".to_string()));
+    }
+    match confirm(&db, detection_id, bbox, operator_id.trim(), comment.as_deref()) {
+        Ok(correction) => {
+            retraining::maybe_trigger(&app).await;
+            Ok(correction)
+        }
+        Err(e) => Err(DetectionError::from(e).context("确认检测失败")),
+    }
+}
+
+/// 标记误报
+#[tauri::command]
+pub async fn mark_detection_false_positive(
+    app: AppHandle,
+    db: State<'_, Database>,
+    detection_id: i64,
+    operator_id: String,
+    comment: Option<String>
+) -> Result<Correction, DetectionError> {
+    if operator_id.trim().is_empty() {
+        return Err(DetectionError::InvalidInput("操作员ID不能为空".to_string()));
+    }
+    match mark_false_positive(&db, detection_id, operator_id.trim(), comment.as_deref()) {
+        Ok(correction) => {
+            retraining::maybe_trigger(&app).await;
+            Ok(correction)
+        }
+        Err(e) => Err(DetectionError::from(e).context("标记误报失败")),
+    }
+}
+
+/// 修正检测类别
+#[tauri::command]
+pub async fn correct_detection_class(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    db: State<'_, Database>,
+    detection_id: i64,
+    class_name: String,
+    bbox: Option<[f32; 4]>,
+    operator_id: String,
+    comment: Option<String>
+) -> Result<Correction, DetectionError> {
+    if operator_id.trim().is_empty() {
+        return Err(DetectionError::InvalidInput("操作员ID不能为空".to_string()));
+    }
+    let class_id = match resolve_class_id(&state, &class_name).await {
+        Ok(id) => id,
+        Err(e) => return Err(DetectionError::from(e).context("修正类别失败")),
+    };
+    match relabel(&db, detection_id, class_id, &class_name, bbox, operator_id.trim(), comment.as_deref()) {
+        Ok(correction) => {
+            retraining::maybe_trigger(&app).await;
+            Ok(correction)
+        }
+        Err(e) => Err(DetectionError::from(e).context("修正类别失败")),
+    }
+}
+
+/// 补充漏检框
+#[tauri::command]
+pub async fn add_missed_detection(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    db: State<'_, Database>,
+    run_id: i64,
+    class_name: String,
+    bbox: [f32; 4],
+    operator_id: String,
+    comment: Option<String>
+) -> Result<Correction, DetectionError> {
+    if operator_id.trim().is_empty() {
+        return Err(DetectionError::InvalidInput("操作员ID不能为空".to_string()));
+    }
+    let class_id = match resolve_class_id(&state, &class_name).await {
+        Ok(id) => id,
+        Err(e) => return Err(DetectionError::from(e).context("补充漏检失败")),
+    };
+    match add_missed(&db, run_id, class_id, &class_name, bbox, operator_id.trim(), comment.as_deref()) {
+        Ok(correction) => {
+            retraining::maybe_trigger(&app).await;
+            Ok(correction)
+        }
+        Err(e) => Err(DetectionError::from(e).context("补充漏检失败")),
+    }
+}
+
+/// 查询修正记录
+#[tauri::command]
+pub async fn list_corrections(
+    db: State<'_, Database>,
+    run_id: Option<i64>,
+    limit: Option<u32>
+) -> Result<Vec<Correction>, DetectionError> {
+    match list(&db, run_id, limit.unwrap_or(500)) {
+        Ok(corrections) => Ok(corrections),
+        Err(e) => Err(DetectionError::from(e).context("查询修正记录失败")),
+    }
+}
+
+/// 查询带修正标记的检测运行
+#[tauri::command]
+pub async fn get_corrected_run(
+    db: State<'_, Database>,
+    run_id: i64
+) -> Result<CorrectedRun, DetectionError> {
+    match get_corrected(&db, run_id) {
+        Ok(Some(run)) => Ok(run),
+        Ok(None) => Err(DetectionError::NotFound(format!("检测运行不存在: {}", run_id))),
+        Err(e) => Err(DetectionError::from(e).context("查询检测运行失败")),
+    }
+}
+
+/// 按条件统计复核情况（确认/误报/类别修正/漏检数量与复核后的精确率、召回率）
+#[tauri::command]
+pub async fn get_review_stats(
+    app: AppHandle,
+    filter: Option<HistoryFilter>
+) -> Result<ReviewStats, DetectionError> {
+    let filter = filter.unwrap_or_default();
+    // 统计需要读取范围内的全部检测框与修正记录，放到阻塞线程中执行
+    let result = tokio::task::spawn_blocking(move || {
+        let db = app.state::<Database>();
+        history::query_all(&db, &filter).and_then(|runs| review_stats(&db, &runs))
+    })
+    .await?;
+    match result {
+        Ok(stats) => Ok(stats),
+        Err(e) => Err(DetectionError::from(e).context("统计复核情况失败")),
+    }
+}
@@ -0,0 +1,511 @@
+/*!
+训练数据集导出模块
+将操作员修正后的检测结果（确认框、修正类别、补充漏检）连同原图导出为YOLO/COCO格式标注；
+也可按运行ID或历史查询条件挑选历史检测结果（可只取经过人工复核的运行）导出为YOLO数据集，用于针对难例再训练。
+导出的标注均已应用修正，误报框被剔除。
+
+不确定样本挖掘：在历史中找出置信度贴近类别阈值、或同一位置不同类别得分接近的检测，
+按不确定程度从高到低挑选若干帧复制到复核目录（模型输出作为预标注），优先标注这些样本能最快改进下一版模型
+*/
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::corrections::{self, CorrectedRun};
+use crate::error::DetectionError;
+use crate::history::{self, DetectionRun, HistoryFilter};
+use crate::yolo::nms::calculate_iou;
+use crate::storage::Database;
+use crate::AppState;
+
+/// 数据集标注格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetFormat {
+    Yolo, // images/ + labels/*.txt + data.yaml
+    Coco, // images/ + annotations.json
+}
+
+/// 数据集导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetExportSummary {
+    pub output_dir: String,
+    pub format: DatasetFormat,
+    pub image_count: u32,
+    pub annotation_count: u32,
+    pub skipped: Vec<String>, // 被跳过的运行及原因
+}
+
+/// 历史检测结果的导出范围
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryExportOptions {
+    pub run_ids: Option<Vec<i64>>, // 指定的运行，提供时忽略 filter
+    pub filter: HistoryFilter,
+    pub reviewed_only: bool,       // 只导出经过人工复核（有修正记录）的运行
+    pub include_empty: bool,       // 修正后没有检测框的图片也导出（作为负样本）
+}
+
+/// 不确定样本的挖掘条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UncertaintyOptions {
+    pub filter: HistoryFilter,
+    pub confidence_band: f32,   // 置信度与阈值（或两个类别的得分）相差不超过该值视为不确定
+    pub overlap_iou: f32,       // 不同类别的检测框 IoU 超过该值视为同一目标的类别之争
+    pub include_reviewed: bool, // 同时挑选已人工复核的运行
+}
+
+impl Default for UncertaintyOptions {
+    fn default() -> Self {
+        Self {
+            filter: HistoryFilter::default(),
+            confidence_band: 0.15,
+            overlap_iou: 0.5,
+            include_reviewed: false,
+        }
+    }
+}
+
+/// 不确定的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UncertaintyReason {
+    Borderline,     // 置信度贴近类别阈值
+    ClassConfusion, // 重叠的检测框类别不同且得分接近
+}
+
+/// 挑选出的不确定样本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UncertainSample {
+    pub run_id: i64,
+    pub source: String,
+    pub score: f32,                   // 不确定程度（0-1，越大越不确定）
+    pub reason: UncertaintyReason,
+    pub detection_id: i64,            // 最不确定的检测框
+    pub class_name: String,
+    pub confidence: f32,
+    pub competing_class: Option<String>, // 类别之争中的另一类别
+}
+
+/// 不确定样本挖掘结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UncertainSelection {
+    pub output_dir: String,
+    pub candidate_count: u32, // 含不确定检测的运行数
+    pub samples: Vec<UncertainSample>,
+    pub skipped: Vec<String>,
+}
+
+/// 历史检测未记录阈值时使用的默认阈值
+const FALLBACK_THRESHOLD: f32 = 0.5;
+
+/// 复核目录中的样本清单文件名
+const UNCERTAIN_MANIFEST: &str = "uncertain_samples.json";
+
+/// 一次运行中最不确定的检测：置信度与阈值的差距、以及重叠异类框的得分差距，
+/// 在 confidence_band 内按 1 - 差距/confidence_band 计分，取最高分
+fn score_run(run: &DetectionRun, options: &UncertaintyOptions) -> Option<UncertainSample> {
+    let band = options.confidence_band.max(f32::EPSILON);
+    let uncertainty = |margin: f32| (margin <= band).then(|| 1.0 - margin / band);
+    let sample = |detection: &history::StoredDetection, score: f32, reason: UncertaintyReason, competing_class: Option<String>| UncertainSample {
+        run_id: run.id,
+        source: run.source.clone(),
+        score,
+        reason,
+        detection_id: detection.id,
+        class_name: detection.class_name.clone(),
+        confidence: detection.confidence,
+        competing_class,
+    };
+
+    let mut best: Option<UncertainSample> = None;
+    let mut consider = |candidate: UncertainSample| {
+        if best.as_ref().is_none_or(|b| candidate.score > b.score) {
+            best = Some(candidate);
+        }
+    };
+
+    for (index, detection) in run.detections.iter().enumerate() {
+        let threshold = run
+            .thresholds
+            .get(&detection.class_name)
+            .copied()
+            .unwrap_or(FALLBACK_THRESHOLD);
+        if let Some(score) = uncertainty((detection.confidence - threshold).abs()) {
+            consider(sample(detection, score, UncertaintyReason::Borderline, None));
+        }
+        for other in &run.detections[index + 1..] {
+            if other.class_id == detection.class_id || calculate_iou(&detection.bbox, &other.bbox) <= options.overlap_iou {
+                continue;
+            }
+            if let Some(score) = uncertainty((detection.confidence - other.confidence).abs()) {
+                let (winner, loser) = if detection.confidence >= other.confidence { (detection, other) } else { (other, detection) };
+                consider(sample(winner, score, UncertaintyReason::ClassConfusion, Some(loser.class_name.clone())));
+            }
+        }
+    }
+    best
+}
+
+/// 导出样本（一张图片及其标注）
+struct DatasetSample {
+    image_path: PathBuf,
+    file_name: String,
+    width: u32,
+    height: u32,
+    boxes: Vec<(u32, [f32; 4])>, // (class_id, [x, y, w, h])
+}
+
+/// 将修正后的运行转换为训练样本（误报框被剔除）
+fn sample_from_run(corrected: &CorrectedRun) -> Result<DatasetSample> {
+    let run = &corrected.run;
+    let image_path = PathBuf::from(&run.source);
+    if !image_path.is_file() {
+        return Err(DetectionError::NotFound(format!("源图片不存在: {}", run.source)).into());
+    }
+
+    let extension = image_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("jpg")
+        .to_lowercase();
+    let stem = image_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("image");
+    let file_name = format!("run{}_{}.{}", run.id, stem, extension);
+
+    let boxes = corrected
+        .detections
+        .iter()
+        .filter(|d| !d.false_positive)
+        .map(|d| (d.class_id, d.bbox))
+        .collect();
+
+    Ok(DatasetSample {
+        image_path,
+        file_name,
+        width: run.image_width,
+        height: run.image_height,
+        boxes,
+    })
+}
+
+/// 转换为YOLO归一化标注行: class cx cy w h
+fn yolo_label_line(class_id: u32, bbox: [f32; 4], width: u32, height: u32) -> String {
+    let [x, y, w, h] = bbox;
+    let (img_w, img_h) = (width.max(1) as f32, height.max(1) as f32);
+    let cx = ((x + w / 2.0) / img_w).clamp(0.0, 1.0);
+    let cy = ((y + h / 2.0) / img_h).clamp(0.0, 1.0);
+    let nw = (w / img_w).clamp(0.0, 1.0);
+    let nh = (h / img_h).clamp(0.0, 1.0);
+    format!("{} {:.6} {:.6} {:.6} {:.6}", class_id, cx, cy, nw, nh)
+}
+
+fn write_yolo(output_dir: &Path, samples: &[DatasetSample], class_names: &[(u32, String)]) -> Result<u32> {
+    let labels_dir = output_dir.join("labels");
+    std::fs::create_dir_all(&labels_dir)?;
+
+    let mut annotation_count = 0;
+    for sample in samples {
+        let lines: Vec<String> = sample
+            .boxes
+            .iter()
+            .map(|(class_id, bbox)| yolo_label_line(*class_id, *bbox, sample.width, sample.height))
+            .collect();
+        annotation_count += lines.len() as u32;
+
+        let label_name = Path::new(&sample.file_name).with_extension("txt");
+        std::fs::write(labels_dir.join(label_name), lines.join("\n"))?;
+    }
+
+    let names: Vec<String> = class_names
+        .iter()
+        .map(|(id, name)| format!("  {}: {}", id, name))
+        .collect();
+    let data_yaml = format!(
+        "path: {}\ntrain: images\nval: images\nnc: {}\nnames:\n{}\n",
+        output_dir.display(),
+        class_names.len(),
+        names.join("\n")
+    );
+    std::fs::write(output_dir.join("data.yaml"), data_yaml)?;
+
+    Ok(annotation_count)
+}
+
+fn write_coco(output_dir: &Path, samples: &[DatasetSample], class_names: &[(u32, String)]) -> Result<u32> {
+    let mut images = Vec::new();
+    let mut annotations = Vec::new();
+
+    for (image_id, sample) in samples.iter().enumerate() {
+        images.push(serde_json::json!({
+            "id": image_id + 1,
+            "file_name": format!("images/{}", sample.file_name),
+            "width": sample.width,
+            "height": sample.height,
+        }));
+        for (class_id, [x, y, w, h]) in &sample.boxes {
+            let annotation_id = annotations.len() + 1;
+            annotations.push(serde_json::json!({
+                "id": annotation_id,
+                "image_id": image_id + 1,
+                "category_id": class_id,
+                "bbox": [x, y, w, h],
+                "area": w * h,
+                "iscrowd": 0,
+            }));
+        }
+    }
+
+    let categories: Vec<_> = class_names
+        .iter()
+        .map(|(id, name)| serde_json::json!({ "id": id, "name": name }))
+        .collect();
+
+    let annotation_count = annotations.len() as u32;
+    let coco = serde_json::json!({
+        "info": {
+            "description": "YOLO Detection System operator corrections",
+            "date_created": chrono::Utc::now().to_rfc3339(),
+        },
+        "images": images,
+        "annotations": annotations,
+        "categories": categories,
+    });
+    std::fs::write(output_dir.join("annotations.json"), serde_json::to_string_pretty(&coco)?)?;
+
+    Ok(annotation_count)
+}
+
+/// 导出修正数据集
+pub fn export_corrections(
+    db: &Database,
+    output_dir: &Path,
+    format: DatasetFormat,
+    class_names: &HashMap<u32, String>,
+) -> Result<DatasetExportSummary> {
+    let mut runs = Vec::new();
+    for run_id in corrections::corrected_run_ids(db)? {
+        if let Some(corrected) = corrections::get_corrected(db, run_id)? {
+            runs.push(corrected);
+        }
+    }
+    export_runs(&runs, output_dir, format, class_names)
+}
+
+/// 导出挑选的历史检测结果为YOLO数据集
+pub fn export_history(
+    db: &Database,
+    output_dir: &Path,
+    options: &HistoryExportOptions,
+    class_names: &HashMap<u32, String>,
+) -> Result<DatasetExportSummary> {
+    let selected = match &options.run_ids {
+        Some(run_ids) => {
+            let mut runs = Vec::with_capacity(run_ids.len());
+            for run_id in run_ids {
+                runs.push(history::get_run(db, *run_id)?.ok_or_else(|| DetectionError::NotFound(format!("检测运行不存在: {}", run_id)))?);
+            }
+            runs
+        }
+        None => history::query_all(db, &options.filter)?,
+    };
+
+    let mut runs = Vec::with_capacity(selected.len());
+    for run in selected {
+        let corrected = corrections::with_corrections(db, run)?;
+        if options.reviewed_only && corrected.corrections.is_empty() {
+            continue;
+        }
+        if !options.include_empty && corrected.detections.iter().all(|d| d.false_positive) {
+            continue;
+        }
+        runs.push(corrected);
+    }
+    if runs.is_empty() {
+        return Err(anyhow!("没有符合条件的检测运行"));
+    }
+    export_runs(&runs, output_dir, DatasetFormat::Yolo, class_names)
+}
+
+/// 按不确定程度挑选最多 count 帧，连同模型预标注复制到复核目录（YOLO格式）并写入样本清单
+pub fn select_uncertain(
+    db: &Database,
+    output_dir: &Path,
+    count: usize,
+    options: &UncertaintyOptions,
+    class_names: &HashMap<u32, String>,
+) -> Result<UncertainSelection> {
+    if count == 0 {
+        return Err(DetectionError::InvalidInput("挑选数量必须大于0".to_string()).into());
+    }
+    if !(0.0..=1.0).contains(&options.confidence_band) || !(0.0..=1.0).contains(&options.overlap_iou) {
+        return Err(DetectionError::InvalidInput("置信度区间与IoU阈值必须在 0-1 之间".to_string()).into());
+    }
+
+    let reviewed: HashSet<i64> = if options.include_reviewed {
+        HashSet::new()
+    } else {
+        corrections::corrected_run_ids(db)?.into_iter().collect()
+    };
+    let mut candidates: Vec<(UncertainSample, DetectionRun)> = history::query_all(db, &options.filter)?
+        .into_iter()
+        .filter(|run| !reviewed.contains(&run.id))
+        .filter_map(|run| score_run(&run, options).map(|sample| (sample, run)))
+        .collect();
+    let candidate_count = candidates.len() as u32;
+    candidates.sort_by(|a, b| b.0.score.total_cmp(&a.0.score));
+
+    // 源图片已不存在的运行跳过，由后面的候选补上
+    let mut skipped = Vec::new();
+    let mut samples = Vec::new();
+    let mut runs = Vec::new();
+    for (sample, run) in candidates {
+        if samples.len() >= count {
+            break;
+        }
+        if !Path::new(&run.source).is_file() {
+            skipped.push(format!("运行 #{}: 源图片不存在: {}", run.id, run.source));
+            continue;
+        }
+        runs.push(corrections::with_corrections(db, run)?);
+        samples.push(sample);
+    }
+    if runs.is_empty() {
+        return Err(anyhow!("没有找到不确定的检测样本"));
+    }
+
+    let exported = export_runs(&runs, output_dir, DatasetFormat::Yolo, class_names)?;
+    skipped.extend(exported.skipped);
+    std::fs::write(output_dir.join(UNCERTAIN_MANIFEST), serde_json::to_string_pretty(&samples)?)?;
+
+    tracing::info!(
+        "🔍 已挑选 {} 个不确定样本（候选 {} 个）: {}",
+        samples.len(),
+        candidate_count,
+        output_dir.display()
+    );
+    Ok(UncertainSelection {
+        output_dir: output_dir.to_string_lossy().to_string(),
+        candidate_count,
+        samples,
+        skipped,
+    })
+}
+
+/// 复制原图并写入标注
+fn export_runs(
+    runs: &[CorrectedRun],
+    output_dir: &Path,
+    format: DatasetFormat,
+    class_names: &HashMap<u32, String>,
+) -> Result<DatasetExportSummary> {
+    let images_dir = output_dir.join("images");
+    std::fs::create_dir_all(&images_dir)
+        .map_err(|e| anyhow!("创建输出目录失败 {}: {}", images_dir.display(), e))?;
+
+    let mut samples = Vec::new();
+    let mut skipped = Vec::new();
+
+    for corrected in runs {
+        match sample_from_run(corrected) {
+            Ok(sample) => {
+                std::fs::copy(&sample.image_path, images_dir.join(&sample.file_name))?;
+                samples.push(sample);
+            }
+            Err(e) => skipped.push(format!("运行 #{}: {}", corrected.run.id, e)),
+        }
+    }
+
+    let mut classes: Vec<(u32, String)> = class_names
+        .iter()
+        .map(|(id, name)| (*id, name.clone()))
+        .collect();
+    classes.sort_by_key(|(id, _)| *id);
+
+    let annotation_count = match format {
+        DatasetFormat::Yolo => write_yolo(output_dir, &samples, &classes)?,
+        DatasetFormat::Coco => write_coco(output_dir, &samples, &classes)?,
+    };
+
+    tracing::info!(
+        "📦 数据集已导出: {} ({} 张图片, {} 个标注, 跳过 {})",
+        output_dir.display(),
+        samples.len(),
+        annotation_count,
+        skipped.len()
+    );
+
+    Ok(DatasetExportSummary {
+        output_dir: output_dir.to_string_lossy().to_string(),
+        format,
+        image_count: samples.len() as u32,
+        annotation_count,
+        skipped,
+    })
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 将操作员修正导出为训练数据集
+#[tauri::command]
+pub async fn export_corrections_dataset(
+    state: State<'_, AppState>,
+    db: State<'_, Database>,
+    output_dir: String,
+    format: DatasetFormat
+) -> Result<DatasetExportSummary, DetectionError> {
+    let class_names = state.read().await.get_class_names().clone();
+
+    match export_corrections(&db, Path::new(&output_dir), format, &class_names) {
+        Ok(summary) => Ok(summary),
+        Err(e) => Err(DetectionError::from(e).context("导出训练数据集失败")),
+    }
+}
+
+/// 将挑选的历史检测结果（应用人工修正）导出为YOLO格式的再训练数据集
+#[tauri::command]
+pub async fn export_history_dataset(
+    state: State<'_, AppState>,
+    db: State<'_, Database>,
+    output_dir: String,
+    options: HistoryExportOptions
+) -> Result<DatasetExportSummary, DetectionError> {
+    let class_names = state.read().await.get_class_names().clone();
+
+    match export_history(&db, Path::new(&output_dir), &options, &class_names) {
+        Ok(summary) => Ok(summary),
+        Err(e) => Err(DetectionError::from(e).context("导出历史数据集失败")),
+    }
+}
+
+/// 从历史中挑选最不确定的 n 帧复制到复核目录（未指定目录时使用应用数据目录下的 review_samples）
+#[tauri::command]
+pub async fn select_uncertain_samples(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    db: State<'_, Database>,
+    n: u32,
+    output_dir: Option<String>,
+    options: Option<UncertaintyOptions>
+) -> Result<UncertainSelection, DetectionError> {
+    let output_dir = match output_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => match app.path().app_data_dir() {
+            Ok(dir) => dir.join("review_samples"),
+            Err(e) => return Err(DetectionError::from(e).context("挑选不确定样本失败")),
+        },
+    };
+    let class_names = state.read().await.get_class_names().clone();
+
+    match select_uncertain(&db, &output_dir, n as usize, &options.unwrap_or_default(), &class_names) {
+        Ok(selection) => Ok(selection),
+        Err(e) => Err(DetectionError::from(e).context("挑选不确定样本失败")),
+    }
+}
@@ -0,0 +1,251 @@
+/*!
+检测配置持久化模块
+检测配置（各类别置信度阈值、启用的类别、推理设备、输入尺寸、NMS参数、推理精度、类别绘制方式、检测区域、透明背景色、切片检测参数、缩放策略、测试时增强参数、像素/毫米标定比例、镜头畸变标定、隐私遮挡区域）保存在应用配置目录下的JSON文件中，
+启动时加载并应用到检测器，加载模型或切换推理后端后重新应用，各配置命令修改后立即写回文件
+*/
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{anyhow, Result};
+use parking_lot::RwLock;
+
+use crate::error;
+use crate::privacy::PrivacyMasks;
+use crate::undistort::Undistorter;
+use crate::yolo::device::DeviceSpec;
+use crate::yolo::roi::{self, RoiPolygon};
+use crate::yolo::Detector;
+use crate::yolo_api::{ClassDisplay, DetectionConfig};
+
+/// 检测配置文件名（位于应用配置目录）
+pub const CONFIG_FILE_NAME: &str = "detection_config.json";
+
+/// 检测配置存储（Tauri托管状态）
+pub struct ConfigStore {
+    path: PathBuf,
+    config: RwLock<DetectionConfig>,
+}
+
+/// 校验检测配置（保存与命令应用前调用）
+pub fn validate(config: &DetectionConfig) -> Result<()> {
+    if let Some((class_name, threshold)) = config
+        .confidence_thresholds
+        .iter()
+        .find(|(_, t)| !(0.0..=1.0).contains(*t))
+    {
+        return Err(anyhow!("类别 {} 的置信度阈值必须在 0-1 之间: {}", class_name, threshold));
+    }
+    config.nms.validate()?;
+    config.tiling.validate()?;
+    config.tta.validate()?;
+    roi::validate(&config.roi)?;
+    if let Some((width, height)) = config.input_size {
+        if width == 0 || height == 0 || width % 32 != 0 || height % 32 != 0 {
+            return Err(anyhow!("输入尺寸须为32的正整数倍: {}x{}", width, height));
+        }
+    }
+    if let Some(device) = &config.device {
+        DeviceSpec::parse(device)?;
+    }
+    if let Some(scale) = config.px_per_mm.filter(|scale| !(scale.is_finite() && *scale > 0.0)) {
+        return Err(anyhow!("像素/毫米标定比例必须为正数: {}", scale));
+    }
+    if let Some(lens) = &config.lens_calibration {
+        lens.validate()?;
+    }
+    config.privacy.validate()?;
+    Ok(())
+}
+
+/// 当前生效的类别绘制配置（绘制检测框的各处共用，随配置文件更新）
+fn display_table() -> &'static RwLock<HashMap<String, ClassDisplay>> {
+    static TABLE: OnceLock<RwLock<HashMap<String, ClassDisplay>>> = OnceLock::new();
+    TABLE.get_or_init(|| RwLock::new(DetectionConfig::default().class_display))
+}
+
+/// 类别的绘制方式，未配置时使用默认配色
+pub fn class_display(class_name: &str) -> ClassDisplay {
+    display_table()
+        .read()
+        .get(class_name)
+        .cloned()
+        .unwrap_or_else(|| ClassDisplay::default_for(class_name))
+}
+
+/// 当前生效的检测区域（绘制标注图像时使用，随配置文件更新）
+fn roi_table() -> &'static RwLock<Vec<RoiPolygon>> {
+    static TABLE: OnceLock<RwLock<Vec<RoiPolygon>>> = OnceLock::new();
+    TABLE.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+pub fn detection_roi() -> Vec<RoiPolygon> {
+    roi_table().read().clone()
+}
+
+pub fn has_roi() -> bool {
+    !roi_table().read().is_empty()
+}
+
+/// 当前生效的像素/毫米标定比例（换算检测框物理尺寸时使用，随配置文件更新）
+fn scale_table() -> &'static RwLock<Option<f32>> {
+    static TABLE: OnceLock<RwLock<Option<f32>>> = OnceLock::new();
+    TABLE.get_or_init(|| RwLock::new(None))
+}
+
+pub fn px_per_mm() -> Option<f32> {
+    *scale_table().read()
+}
+
+/// 当前生效的隐私遮挡配置（输出画面与保存产物时使用，随配置文件更新）
+fn privacy_table() -> &'static RwLock<PrivacyMasks> {
+    static TABLE: OnceLock<RwLock<PrivacyMasks>> = OnceLock::new();
+    TABLE.get_or_init(|| RwLock::new(PrivacyMasks::default()))
+}
+
+pub fn privacy_masks() -> PrivacyMasks {
+    privacy_table().read().clone()
+}
+
+pub fn has_privacy_masks() -> bool {
+    !privacy_table().read().regions.is_empty()
+}
+
+/// 当前生效的畸变校正器（采集视频帧时使用，随配置文件更新）
+fn lens_table() -> &'static RwLock<Option<Arc<Undistorter>>> {
+    static TABLE: OnceLock<RwLock<Option<Arc<Undistorter>>>> = OnceLock::new();
+    TABLE.get_or_init(|| RwLock::new(None))
+}
+
+pub fn undistorter() -> Option<Arc<Undistorter>> {
+    lens_table().read().clone()
+}
+
+/// 标定参数变化时才重建校正器（映射表随之失效）
+fn update_lens(config: &DetectionConfig) {
+    let mut table = lens_table().write();
+    let unchanged = match (table.as_ref(), config.lens_calibration.as_ref()) {
+        (Some(current), Some(calibration)) => current.calibration() == calibration,
+        (None, None) => true,
+        _ => false,
+    };
+    if !unchanged {
+        *table = config.lens_calibration.clone().map(|calibration| Arc::new(Undistorter::new(calibration)));
+    }
+}
+
+/// 按类别名称查找类别ID；名称列表为空或与当前模型的类别均不匹配时启用全部类别
+pub fn class_ids_for(detector: &dyn Detector, class_names: &[String]) -> Vec<u32> {
+    let all = detector.get_class_names();
+    let mut ids: Vec<u32> = all
+        .iter()
+        .filter(|(_, name)| class_names.contains(name))
+        .map(|(id, _)| *id)
+        .collect();
+    if ids.is_empty() {
+        ids = all.keys().copied().collect();
+    }
+    ids.sort_unstable();
+    ids
+}
+
+/// 检测器是否已加载模型
+pub fn model_loaded(detector: &dyn Detector) -> bool {
+    detector.get_model_info().get("model_loaded").map(String::as_str) == Some("true")
+}
+
+/// 把配置应用到检测器，返回实际生效的配置（推理精度以检测器实际运行的为准）
+pub async fn apply(detector: &mut dyn Detector, config: &DetectionConfig) -> Result<DetectionConfig> {
+    if let Some(device) = &config.device {
+        detector.select_device(DeviceSpec::parse(device)?).await?;
+    }
+    for (class_name, threshold) in &config.confidence_thresholds {
+        detector.update_confidence_threshold(class_name, *threshold).await?;
+    }
+    let class_ids = class_ids_for(detector, &config.selected_classes);
+    detector.set_enabled_classes(class_ids).await?;
+    detector.set_nms_config(config.nms.clone()).await?;
+    detector.set_roi(config.roi.clone()).await?;
+    detector.set_alpha_background(config.alpha_background).await?;
+    detector.set_tiling_config(config.tiling.clone()).await?;
+    detector.set_resize_config(config.resize).await?;
+    detector.set_tta_config(config.tta.clone()).await?;
+
+    let mut effective = config.clone();
+    // 输入尺寸固定的模型或不支持设置输入尺寸的后端沿用模型自身的尺寸
+    if let Some(size) = config.input_size {
+        if let Err(e) = detector.set_input_size(size).await {
+            tracing::warn!("⚠️  未应用配置的输入尺寸: {}", e);
+            effective.input_size = None;
+        }
+    }
+    // 推理精度按模型记录，须在模型加载后设置
+    if model_loaded(detector) {
+        effective.half_precision = detector.set_half_precision(config.half_precision).await?;
+    }
+    Ok(effective)
+}
+
+impl ConfigStore {
+    /// 读取已保存的配置，文件不存在或无效时使用默认配置
+    pub fn load(config_dir: &Path) -> Self {
+        let path = config_dir.join(CONFIG_FILE_NAME);
+        let config = match std::fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<DetectionConfig>(&content) {
+                Ok(config) if validate(&config).is_ok() => {
+                    tracing::info!("⚙️ 已加载检测配置: {}", path.display());
+                    config
+                }
+                Ok(_) | Err(_) => {
+                    tracing::error!("检测配置文件无效，使用默认配置: {}", path.display());
+                    DetectionConfig::default()
+                }
+            },
+            Err(_) => DetectionConfig::default(),
+        };
+        *display_table().write() = config.class_display.clone();
+        *roi_table().write() = config.roi.clone();
+        *scale_table().write() = config.px_per_mm;
+        *privacy_table().write() = config.privacy.clone();
+        update_lens(&config);
+        Self {
+            path,
+            config: RwLock::new(config),
+        }
+    }
+
+    pub fn get(&self) -> DetectionConfig {
+        self.config.read().clone()
+    }
+
+    /// 修改配置并写回文件，返回修改后的配置
+    pub fn update(&self, f: impl FnOnce(&mut DetectionConfig)) -> Result<DetectionConfig> {
+        let mut config = self.get();
+        f(&mut config);
+        self.save(config.clone())?;
+        Ok(config)
+    }
+
+    /// 恢复默认配置并写回文件
+    pub fn reset(&self) -> Result<DetectionConfig> {
+        let config = DetectionConfig::default();
+        self.save(config.clone())?;
+        Ok(config)
+    }
+
+    fn save(&self, config: DetectionConfig) -> Result<()> {
+        validate(&config).map_err(error::invalid_input)?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&config)?)?;
+        *display_table().write() = config.class_display.clone();
+        *roi_table().write() = config.roi.clone();
+        *scale_table().write() = config.px_per_mm;
+        *privacy_table().write() = config.privacy.clone();
+        update_lens(&config);
+        *self.config.write() = config;
+        Ok(())
+    }
+}
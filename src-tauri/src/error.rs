@@ -0,0 +1,183 @@
+/*!
+统一错误类型
+命令失败时除了错误信息外还返回稳定的错误码（如 `MODEL_NOT_LOADED`），前端据此区分错误类别并做本地化显示。
+模块内部仍使用 anyhow 传递错误，需要区分类别的地方返回 `DetectionError`，
+转换为命令返回值时从 anyhow 错误链中取回原始类别，未标注类别的错误归为 `INTERNAL`
+*/
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+/// 检测系统错误
+#[derive(Debug, thiserror::Error)]
+pub enum DetectionError {
+    #[error("模型未加载，请先加载模型")]
+    ModelNotLoaded,
+    #[error("{0}")]
+    UnsupportedFormat(String),
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    DeviceError(String),
+    #[error("{0}")]
+    Busy(String), // 输入源或资源正被占用
+    #[error("{0}")]
+    FeatureDisabled(String), // 当前编译版本未启用该功能
+    #[error("操作已取消")]
+    Cancelled,
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl DetectionError {
+    /// 稳定的错误码，前端按此区分错误类别
+    pub fn code(&self) -> &'static str {
+        match self {
+            DetectionError::ModelNotLoaded => "MODEL_NOT_LOADED",
+            DetectionError::UnsupportedFormat(_) => "UNSUPPORTED_FORMAT",
+            DetectionError::InvalidInput(_) => "INVALID_INPUT",
+            DetectionError::NotFound(_) => "NOT_FOUND",
+            DetectionError::DeviceError(_) => "DEVICE_ERROR",
+            DetectionError::Busy(_) => "BUSY",
+            DetectionError::FeatureDisabled(_) => "FEATURE_DISABLED",
+            DetectionError::Cancelled => "CANCELLED",
+            DetectionError::IoError(_) => "IO_ERROR",
+            DetectionError::Internal(_) => "INTERNAL",
+        }
+    }
+
+    /// 在错误信息前加上说明（错误类别不变）
+    pub fn context(self, context: &str) -> Self {
+        let message = format!("{}: {}", context, self);
+        self.with_message(message)
+    }
+
+    /// 同类别、替换错误信息
+    fn with_message(&self, message: String) -> Self {
+        match self {
+            DetectionError::ModelNotLoaded => DetectionError::ModelNotLoaded,
+            DetectionError::Cancelled => DetectionError::Cancelled,
+            DetectionError::UnsupportedFormat(_) => DetectionError::UnsupportedFormat(message),
+            DetectionError::InvalidInput(_) => DetectionError::InvalidInput(message),
+            DetectionError::NotFound(_) => DetectionError::NotFound(message),
+            DetectionError::DeviceError(_) => DetectionError::DeviceError(message),
+            DetectionError::Busy(_) => DetectionError::Busy(message),
+            DetectionError::FeatureDisabled(_) => DetectionError::FeatureDisabled(message),
+            DetectionError::IoError(_) => DetectionError::IoError(std::io::Error::other(message)),
+            DetectionError::Internal(_) => DetectionError::Internal(message),
+        }
+    }
+}
+
+/// 参数校验失败：标记为 `INVALID_INPUT` 后继续以 anyhow 错误传递，命令返回时取回该类别
+pub fn invalid_input(error: anyhow::Error) -> anyhow::Error {
+    DetectionError::InvalidInput(format!("{:#}", error)).into()
+}
+
+/// 从 anyhow 错误链中取回错误类别，保留完整的错误信息
+impl From<anyhow::Error> for DetectionError {
+    fn from(error: anyhow::Error) -> Self {
+        let message = format!("{:#}", error);
+        if let Some(kind) = error.chain().find_map(|cause| cause.downcast_ref::<DetectionError>()) {
+            return kind.with_message(message);
+        }
+        if error.chain().any(|cause| cause.is::<std::io::Error>()) {
+            return DetectionError::IoError(std::io::Error::other(message));
+        }
+        DetectionError::Internal(message)
+    }
+}
+
+/// Tauri 运行时错误（应用目录解析、窗口、后台任务等）
+impl From<tauri::Error> for DetectionError {
+    fn from(error: tauri::Error) -> Self {
+        anyhow::Error::from(error).into()
+    }
+}
+
+/// 后台任务异常退出
+impl From<tokio::task::JoinError> for DetectionError {
+    fn from(error: tokio::task::JoinError) -> Self {
+        DetectionError::Internal(format!("后台任务异常: {}", error))
+    }
+}
+
+/// 序列化为 `{ "code": "...", "message": "..." }`
+impl Serialize for DetectionError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("DetectionError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    fn all_variants() -> Vec<(DetectionError, &'static str)> {
+        vec![
+            (DetectionError::ModelNotLoaded, "MODEL_NOT_LOADED"),
+            (DetectionError::UnsupportedFormat("格式".into()), "UNSUPPORTED_FORMAT"),
+            (DetectionError::InvalidInput("参数".into()), "INVALID_INPUT"),
+            (DetectionError::NotFound("不存在".into()), "NOT_FOUND"),
+            (DetectionError::DeviceError("设备".into()), "DEVICE_ERROR"),
+            (DetectionError::Busy("占用".into()), "BUSY"),
+            (DetectionError::FeatureDisabled("未启用".into()), "FEATURE_DISABLED"),
+            (DetectionError::Cancelled, "CANCELLED"),
+            (DetectionError::IoError(std::io::Error::other("读写")), "IO_ERROR"),
+            (DetectionError::Internal("内部".into()), "INTERNAL"),
+        ]
+    }
+
+    #[test]
+    fn every_variant_has_a_stable_code() {
+        for (error, code) in all_variants() {
+            assert_eq!(error.code(), code);
+        }
+    }
+
+    #[test]
+    fn serializes_as_code_and_message() {
+        for (error, code) in all_variants() {
+            let value = serde_json::to_value(&error).unwrap();
+            assert_eq!(value, serde_json::json!({ "code": code, "message": error.to_string() }));
+        }
+    }
+
+    #[test]
+    fn anyhow_conversion_keeps_kind_through_context() {
+        for (error, code) in all_variants() {
+            let wrapped = Err::<(), _>(anyhow::Error::from(error))
+                .context("内层说明")
+                .context("外层说明")
+                .unwrap_err();
+            let converted = DetectionError::from(wrapped);
+            assert_eq!(converted.code(), code);
+            // 固定文案的类别不携带说明
+            if !matches!(converted, DetectionError::ModelNotLoaded | DetectionError::Cancelled) {
+                assert!(converted.to_string().starts_with("外层说明: 内层说明"), "{}", converted);
+            }
+        }
+    }
+
+    #[test]
+    fn untyped_errors_become_internal_and_io_errors_are_recovered() {
+        assert_eq!(DetectionError::from(anyhow::anyhow!("未知错误")).code(), "INTERNAL");
+        let io = anyhow::Error::from(std::io::Error::other("磁盘已满")).context("写入失败");
+        assert_eq!(DetectionError::from(io).code(), "IO_ERROR");
+    }
+
+    #[test]
+    fn context_prefixes_message_and_keeps_kind() {
+        let error = DetectionError::NotFound("告警 3 不存在".into()).context("确认告警失败");
+        assert_eq!(error.code(), "NOT_FOUND");
+        assert_eq!(error.to_string(), "确认告警失败: 告警 3 不存在");
+    }
+}
@@ -0,0 +1,799 @@
+/*!
+模型评估模块
+在带真值标注的样本目录上运行当前模型，按类别把检测框与真值框匹配，据此给出各类别的置信度阈值建议（`suggest_thresholds`），
+或计算 mAP50 / mAP50-95、各类别的精确率/召回率与PR曲线以及混淆矩阵（`evaluate_model`）。
+
+样本目录支持两种格式：
+- YOLO：图片位于 `images/`（不存在时为目录本身，含子目录），同名标注位于 `labels/` 下相同的相对路径
+  （不存在时与图片同目录），每行 `class cx cy w h`（归一化坐标）；没有标注文件的图片视为不含目标。
+  类别名称取自目录下 `data.yaml` 的 names，缺失时按模型的类别ID对应
+- COCO：目录下的 `annotations.json`（或唯一的JSON标注文件），图片路径相对于目录或其 `images/` 子目录；
+  iscrowd 标注不参与评估
+
+评估在独立的检测器实例中进行（与模型对比相同），各类别阈值降到 `SCORE_FLOOR` 并启用全部类别、不限检测区域，
+不影响当前使用的检测器
+*/
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::dataset::DatasetFormat;
+use crate::detection_config::{self, ConfigStore};
+use crate::error::DetectionError;
+use crate::ground_truth::GroundTruthBox;
+use crate::models;
+use crate::yolo::nms::calculate_iou;
+use crate::yolo::{decode, Detector, YoloDetection};
+use crate::AppState;
+
+/// 评估时各类别使用的置信度阈值，低于该值的检测框不参与统计
+const SCORE_FLOOR: f32 = 0.01;
+
+/// 默认的匹配IoU阈值
+const DEFAULT_MATCH_IOU: f32 = 0.5;
+
+/// mAP50-95 使用的IoU阈值个数（0.50, 0.55, ..., 0.95）
+const MAP_IOU_STEPS: usize = 10;
+
+/// 插值PR曲线的召回率采样点数（与COCO评估一致）
+const RECALL_POINTS: usize = 101;
+
+/// 混淆矩阵中未匹配框所在的行/列
+const BACKGROUND_LABEL: &str = "背景";
+
+/// 未配置阈值的类别按检测器的默认阈值统计精确率/召回率
+const DEFAULT_CLASS_THRESHOLD: f32 = 0.5;
+
+/// 一张带真值标注的图片
+#[derive(Debug, Clone)]
+pub struct LabeledImage {
+    pub image_path: PathBuf,
+    pub boxes: Vec<GroundTruthBox>, // 像素坐标
+}
+
+/// 模型在一张图片上的检测结果
+struct Prediction {
+    image: LabeledImage,
+    detections: Vec<YoloDetection>,
+}
+
+/// 单个类别的阈值建议
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdSuggestion {
+    pub class_name: String,
+    pub threshold: f32,
+    pub precision: f32,
+    pub recall: f32,
+    pub f1: f32,
+    pub ground_truth: usize,       // 真值框数
+    pub predictions: usize,        // 置信度不低于 SCORE_FLOOR 的检测框数
+    pub target_met: bool,          // 达到目标精确率（未指定目标时为 true）；未达到时退回F1最高的阈值
+    pub current_threshold: Option<f32>,
+}
+
+/// 单个类别的评估指标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassEvaluation {
+    pub class_name: String,
+    pub ground_truth: usize,
+    pub predictions: usize,
+    pub ap50: f32,
+    pub ap50_95: f32,
+    pub threshold: f32, // 统计精确率/召回率时使用的置信度阈值（当前配置）
+    pub precision: f32,
+    pub recall: f32,
+    pub pr_curve: Vec<[f32; 2]>, // IoU 0.5 下的插值PR曲线 [recall, precision]
+}
+
+/// 混淆矩阵：行为真值类别，列为预测类别，最后一行/列为背景（漏检与误检）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfusionMatrix {
+    pub labels: Vec<String>,
+    pub matrix: Vec<Vec<u32>>,
+}
+
+/// 模型评估结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluationReport {
+    pub dataset_dir: String,
+    pub format: DatasetFormat,
+    pub model_path: String,
+    pub images: usize,
+    pub failed_images: Vec<String>,
+    pub map50: f32,
+    pub map50_95: f32, // 只在有真值的类别上平均
+    pub classes: Vec<ClassEvaluation>,
+    pub confusion_matrix: ConfusionMatrix,
+}
+
+/// 阈值建议结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdReport {
+    pub labeled_dir: String,
+    pub images: usize,
+    pub failed_images: Vec<String>, // 读取或检测失败的图片及原因
+    pub iou_threshold: f32,
+    pub precision_target: Option<f32>,
+    pub suggestions: Vec<ThresholdSuggestion>, // 只包含有检测框的类别
+    pub applied: bool,
+}
+
+/// 从 data.yaml 中读取类别名称，支持 `names: [a, b]`、`- a` 列表与 `0: a` 映射三种写法
+fn parse_yaml_names(content: &str) -> HashMap<u32, String> {
+    let unquote = |value: &str| value.trim().trim_matches(|c| c == '\'' || c == '"').to_string();
+    let mut names = HashMap::new();
+    let mut lines = content.lines().skip_while(|line| !line.trim_start().starts_with("names:"));
+    let Some(header) = lines.next() else {
+        return names;
+    };
+    let inline = header.trim_start().trim_start_matches("names:").trim();
+    if let Some(list) = inline.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+        for (id, name) in list.split(',').map(unquote).filter(|n| !n.is_empty()).enumerate() {
+            names.insert(id as u32, name);
+        }
+        return names;
+    }
+    for line in lines.take_while(|line| line.starts_with(' ') || line.starts_with('\t') || line.trim().is_empty()) {
+        let item = line.trim();
+        if let Some(name) = item.strip_prefix("- ") {
+            names.insert(names.len() as u32, unquote(name));
+        } else if let Some((id, name)) = item.split_once(':') {
+            if let Ok(id) = id.trim().parse() {
+                names.insert(id, unquote(name));
+            }
+        }
+    }
+    names
+}
+
+/// 图片尺寸：优先只读取文件头，无法识别时完整解码
+fn image_size(path: &Path) -> Result<(u32, u32)> {
+    match image::image_dimensions(path) {
+        Ok(size) => Ok(size),
+        Err(_) => decode::open(path).map(|img| (img.width(), img.height())),
+    }
+}
+
+/// 解析一个YOLO标注文件为像素坐标的真值框
+fn read_yolo_labels(path: &Path, image_size: (u32, u32), class_names: &HashMap<u32, String>) -> Result<Vec<GroundTruthBox>> {
+    let content = std::fs::read_to_string(path)?;
+    let (width, height) = (image_size.0 as f32, image_size.1 as f32);
+    let mut boxes = Vec::new();
+    for (line_no, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let parsed = (fields.len() >= 5)
+            .then(|| {
+                let class_id: u32 = fields[0].parse().ok()?;
+                let values: Vec<f32> = fields[1..5].iter().map(|v| v.parse().ok()).collect::<Option<_>>()?;
+                Some((class_id, values))
+            })
+            .flatten();
+        let Some((class_id, values)) = parsed else {
+            return Err(anyhow!("{} 第 {} 行格式错误", path.display(), line_no + 1));
+        };
+        let (cx, cy, w, h) = (values[0] * width, values[1] * height, values[2] * width, values[3] * height);
+        boxes.push(GroundTruthBox {
+            class_name: class_names
+                .get(&class_id)
+                .cloned()
+                .unwrap_or_else(|| format!("class_{}", class_id)),
+            bbox: [cx - w / 2.0, cy - h / 2.0, w, h],
+        });
+    }
+    Ok(boxes)
+}
+
+/// 递归列出目录下可解码的图片（按路径排序）
+pub(crate) fn list_images(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut images = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if decode::is_supported_path(&path) {
+                images.push(path);
+            }
+        }
+    }
+    images.sort();
+    Ok(images)
+}
+
+/// 读取YOLO格式的样本目录
+pub fn load_yolo_dir(dir: &Path, model_classes: &HashMap<u32, String>) -> Result<Vec<LabeledImage>> {
+    if !dir.is_dir() {
+        return Err(DetectionError::NotFound(format!("样本目录不存在: {}", dir.display())).into());
+    }
+    let class_names = std::fs::read_to_string(dir.join("data.yaml"))
+        .map(|content| parse_yaml_names(&content))
+        .ok()
+        .filter(|names| !names.is_empty())
+        .unwrap_or_else(|| model_classes.clone());
+    let images_dir = Some(dir.join("images")).filter(|d| d.is_dir()).unwrap_or_else(|| dir.to_path_buf());
+    let labels_dir = Some(dir.join("labels")).filter(|d| d.is_dir());
+
+    let mut labeled = Vec::new();
+    for image_path in list_images(&images_dir)? {
+        let label_path = match &labels_dir {
+            Some(labels_dir) => labels_dir.join(image_path.strip_prefix(&images_dir)?).with_extension("txt"),
+            None => image_path.with_extension("txt"),
+        };
+        let boxes = if label_path.is_file() {
+            let size = image_size(&image_path)?;
+            read_yolo_labels(&label_path, size, &class_names)?
+        } else {
+            Vec::new()
+        };
+        labeled.push(LabeledImage { image_path, boxes });
+    }
+    if labeled.is_empty() {
+        return Err(anyhow!("样本目录中没有图片: {}", images_dir.display()));
+    }
+    Ok(labeled)
+}
+
+#[derive(Deserialize)]
+struct CocoImage {
+    id: u64,
+    file_name: String,
+}
+
+#[derive(Deserialize)]
+struct CocoAnnotation {
+    image_id: u64,
+    category_id: u64,
+    bbox: [f32; 4],
+    #[serde(default)]
+    iscrowd: u8,
+}
+
+#[derive(Deserialize)]
+struct CocoCategory {
+    id: u64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct CocoFile {
+    images: Vec<CocoImage>,
+    #[serde(default)]
+    annotations: Vec<CocoAnnotation>,
+    #[serde(default)]
+    categories: Vec<CocoCategory>,
+}
+
+/// COCO标注文件：优先 annotations.json，否则取目录下唯一的JSON文件
+fn coco_annotation_file(dir: &Path) -> Result<PathBuf> {
+    let default = dir.join("annotations.json");
+    if default.is_file() {
+        return Ok(default);
+    }
+    let candidates: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")))
+        .collect();
+    match candidates.as_slice() {
+        [single] => Ok(single.clone()),
+        [] => Err(anyhow!("样本目录中没有COCO标注文件: {}", dir.display())),
+        _ => Err(anyhow!("样本目录中有多个JSON文件，请命名为 annotations.json: {}", dir.display())),
+    }
+}
+
+/// 读取COCO格式的样本目录
+pub fn load_coco_dir(dir: &Path) -> Result<Vec<LabeledImage>> {
+    let path = coco_annotation_file(dir)?;
+    let coco: CocoFile = serde_json::from_str(&std::fs::read_to_string(&path)?)
+        .map_err(|e| anyhow!("COCO标注文件格式错误 {}: {}", path.display(), e))?;
+    let categories: HashMap<u64, String> = coco.categories.into_iter().map(|c| (c.id, c.name)).collect();
+
+    let mut boxes: HashMap<u64, Vec<GroundTruthBox>> = HashMap::new();
+    for annotation in coco.annotations.into_iter().filter(|a| a.iscrowd == 0) {
+        boxes.entry(annotation.image_id).or_default().push(GroundTruthBox {
+            class_name: categories
+                .get(&annotation.category_id)
+                .cloned()
+                .unwrap_or_else(|| format!("class_{}", annotation.category_id)),
+            bbox: annotation.bbox,
+        });
+    }
+
+    let mut labeled = Vec::with_capacity(coco.images.len());
+    for image in coco.images {
+        let image_path = [dir.join(&image.file_name), dir.join("images").join(&image.file_name)]
+            .into_iter()
+            .find(|path| path.is_file())
+            .ok_or_else(|| DetectionError::NotFound(format!("图片不存在: {}", image.file_name)))?;
+        labeled.push(LabeledImage {
+            image_path,
+            boxes: boxes.remove(&image.id).unwrap_or_default(),
+        });
+    }
+    if labeled.is_empty() {
+        return Err(anyhow!("COCO标注文件中没有图片: {}", path.display()));
+    }
+    Ok(labeled)
+}
+
+/// 按格式读取样本目录
+pub fn load_dataset(dir: &Path, format: DatasetFormat, model_classes: &HashMap<u32, String>) -> Result<Vec<LabeledImage>> {
+    match format {
+        DatasetFormat::Yolo => load_yolo_dir(dir, model_classes),
+        DatasetFormat::Coco => load_coco_dir(dir),
+    }
+}
+
+/// 创建评估用的检测器：加载当前模型，启用全部类别，阈值降到 SCORE_FLOOR，不限检测区域
+async fn evaluation_detector(state: &AppState, store: &ConfigStore) -> Result<(Box<dyn Detector>, String)> {
+    let (backend, model_path) = {
+        let detector = state.read().await;
+        let model_path = detection_config::model_loaded(detector.as_ref())
+            .then(|| detector.get_model_info().get("model_path").cloned())
+            .flatten()
+            .ok_or_else(|| anyhow!("请先加载模型"))?;
+        (detector.backend(), model_path)
+    };
+    let mut config = store.get();
+    config.roi.clear();
+    let detector = models::create_loaded(backend, &config, &model_path).await?;
+    let classes = detector.get_class_names().clone();
+    for name in classes.values() {
+        detector.update_confidence_threshold(name, SCORE_FLOOR).await?;
+    }
+    detector.set_enabled_classes(classes.keys().copied().collect()).await?;
+    Ok((detector, model_path))
+}
+
+/// 逐张检测样本，失败的图片记录原因后跳过
+async fn predict_all(detector: &dyn Detector, images: Vec<LabeledImage>) -> (Vec<Prediction>, Vec<String>) {
+    let mut predictions = Vec::with_capacity(images.len());
+    let mut failed = Vec::new();
+    for image in images {
+        let result = match std::fs::read(&image.image_path) {
+            Ok(data) => detector.detect_image(&data).await,
+            Err(e) => Err(anyhow!("读取图像文件失败: {}", e)),
+        };
+        match result {
+            Ok(result) => predictions.push(Prediction { image, detections: result.detections }),
+            Err(e) => failed.push(format!("{}: {}", image.image_path.display(), e)),
+        }
+    }
+    (predictions, failed)
+}
+
+/// 同类别内按置信度从高到低把检测框贪心匹配到IoU最高且未被匹配的真值框，返回各检测框是否为真阳性
+fn match_predictions(detections: &[YoloDetection], truth: &[GroundTruthBox], iou_threshold: f32) -> Vec<bool> {
+    let mut order: Vec<usize> = (0..detections.len()).collect();
+    order.sort_by(|&a, &b| detections[b].confidence.total_cmp(&detections[a].confidence));
+    let mut used = vec![false; truth.len()];
+    let mut true_positive = vec![false; detections.len()];
+    for index in order {
+        let detection = &detections[index];
+        let best = truth
+            .iter()
+            .enumerate()
+            .filter(|(j, gt)| !used[*j] && gt.class_name == detection.class_name)
+            .map(|(j, gt)| (j, calculate_iou(&detection.bbox, &gt.bbox)))
+            .filter(|(_, iou)| *iou >= iou_threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((j, _)) = best {
+            used[j] = true;
+            true_positive[index] = true;
+        }
+    }
+    true_positive
+}
+
+/// 某个阈值下的精确率、召回率与F1
+#[derive(Debug, Clone, Copy)]
+struct OperatingPoint {
+    threshold: f32,
+    precision: f32,
+    recall: f32,
+    f1: f32,
+}
+
+/// 按置信度从高到低扫描，列出每个可取阈值（相同置信度一并计入）下的指标
+fn operating_points(scored: &mut [(f32, bool)], ground_truth: usize) -> Vec<OperatingPoint> {
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut points = Vec::new();
+    let (mut tp, mut fp) = (0usize, 0usize);
+    for (i, &(confidence, is_tp)) in scored.iter().enumerate() {
+        if is_tp { tp += 1 } else { fp += 1 }
+        if scored.get(i + 1).is_some_and(|next| next.0 == confidence) {
+            continue;
+        }
+        let precision = tp as f32 / (tp + fp) as f32;
+        let recall = if ground_truth == 0 { 0.0 } else { tp as f32 / ground_truth as f32 };
+        let f1 = if precision + recall > 0.0 { 2.0 * precision * recall / (precision + recall) } else { 0.0 };
+        points.push(OperatingPoint { threshold: confidence, precision, recall, f1 });
+    }
+    points
+}
+
+/// 选择阈值：指定目标精确率时取达到目标且召回最高的点，否则（或无点达到目标时）取F1最高的点
+fn choose_threshold(points: &[OperatingPoint], precision_target: Option<f32>) -> Option<(OperatingPoint, bool)> {
+    let best_f1 = points.iter().copied().max_by(|a, b| a.f1.total_cmp(&b.f1).then(a.threshold.total_cmp(&b.threshold)))?;
+    let Some(target) = precision_target else {
+        return Some((best_f1, true));
+    };
+    let reaching = points
+        .iter()
+        .copied()
+        .filter(|p| p.precision >= target)
+        .max_by(|a, b| a.recall.total_cmp(&b.recall).then(a.threshold.total_cmp(&b.threshold)));
+    Some(reaching.map_or((best_f1, false), |point| (point, true)))
+}
+
+/// 按类别汇总全部样本：各检测框的 (置信度, 是否真阳性) 与真值框数
+fn score_by_class(predictions: &[Prediction], iou_threshold: f32) -> (HashMap<String, Vec<(f32, bool)>>, HashMap<String, usize>) {
+    let mut scored: HashMap<String, Vec<(f32, bool)>> = HashMap::new();
+    let mut ground_truth: HashMap<String, usize> = HashMap::new();
+    for prediction in predictions {
+        for gt in &prediction.image.boxes {
+            *ground_truth.entry(gt.class_name.clone()).or_default() += 1;
+        }
+        let matched = match_predictions(&prediction.detections, &prediction.image.boxes, iou_threshold);
+        for (detection, is_tp) in prediction.detections.iter().zip(matched) {
+            scored.entry(detection.class_name.clone()).or_default().push((detection.confidence, is_tp));
+        }
+    }
+    (scored, ground_truth)
+}
+
+/// 汇总全部样本，逐类别给出阈值建议
+fn suggest(
+    predictions: &[Prediction],
+    iou_threshold: f32,
+    precision_target: Option<f32>,
+    current: &HashMap<String, f32>,
+) -> Vec<ThresholdSuggestion> {
+    let (scored, ground_truth) = score_by_class(predictions, iou_threshold);
+
+    let mut suggestions: Vec<ThresholdSuggestion> = scored
+        .into_iter()
+        .filter_map(|(class_name, mut scores)| {
+            let gt_count = ground_truth.get(&class_name).copied().unwrap_or(0);
+            let points = operating_points(&mut scores, gt_count);
+            let (point, target_met) = choose_threshold(&points, precision_target)?;
+            Some(ThresholdSuggestion {
+                current_threshold: current.get(&class_name).copied(),
+                class_name,
+                threshold: point.threshold,
+                precision: point.precision,
+                recall: point.recall,
+                f1: point.f1,
+                ground_truth: gt_count,
+                predictions: scores.len(),
+                target_met,
+            })
+        })
+        .collect();
+    suggestions.sort_by(|a, b| a.class_name.cmp(&b.class_name));
+    suggestions
+}
+
+/// 101 个召回率采样点上的插值精确率（取该召回率及以上各点精确率的最大值）
+fn interpolated_precision(points: &[OperatingPoint]) -> Vec<f32> {
+    // 工作点按阈值从高到低排列，召回率单调不减；从后往前取最大值得到单调不增的精确率包络
+    let mut envelope: Vec<(f32, f32)> = points.iter().map(|p| (p.recall, p.precision)).collect();
+    for i in (0..envelope.len().saturating_sub(1)).rev() {
+        envelope[i].1 = envelope[i].1.max(envelope[i + 1].1);
+    }
+    (0..RECALL_POINTS)
+        .map(|step| {
+            let recall = step as f32 / (RECALL_POINTS - 1) as f32;
+            envelope.iter().find(|(r, _)| *r >= recall).map_or(0.0, |(_, p)| *p)
+        })
+        .collect()
+}
+
+fn mean(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 { 0.0 } else { sum / count as f32 }
+}
+
+/// 各IoU阈值（0.50 - 0.95）下逐类别的AP，以及 IoU 0.5 下的插值PR曲线与当前阈值的精确率/召回率
+fn class_metrics(predictions: &[Prediction], thresholds: &HashMap<String, f32>) -> Vec<ClassEvaluation> {
+    let mut ap_by_class: HashMap<String, Vec<f32>> = HashMap::new();
+    let mut classes: HashMap<String, ClassEvaluation> = HashMap::new();
+    for step in 0..MAP_IOU_STEPS {
+        let iou_threshold = 0.5 + 0.05 * step as f32;
+        let (mut scored, ground_truth) = score_by_class(predictions, iou_threshold);
+        for class_name in ground_truth.keys() {
+            scored.entry(class_name.clone()).or_default();
+        }
+        for (class_name, mut scores) in scored {
+            let gt_count = ground_truth.get(&class_name).copied().unwrap_or(0);
+            let points = operating_points(&mut scores, gt_count);
+            let curve = interpolated_precision(&points);
+            ap_by_class.entry(class_name.clone()).or_default().push(mean(curve.iter().copied()));
+            if step > 0 {
+                continue;
+            }
+            let threshold = thresholds.get(&class_name).copied().unwrap_or(DEFAULT_CLASS_THRESHOLD);
+            let kept: Vec<bool> = scores.iter().filter(|(c, _)| *c >= threshold).map(|(_, tp)| *tp).collect();
+            let tp = kept.iter().filter(|tp| **tp).count();
+            classes.insert(class_name.clone(), ClassEvaluation {
+                class_name: class_name.clone(),
+                ground_truth: gt_count,
+                predictions: scores.len(),
+                ap50: 0.0,
+                ap50_95: 0.0,
+                threshold,
+                precision: if kept.is_empty() { 0.0 } else { tp as f32 / kept.len() as f32 },
+                recall: if gt_count == 0 { 0.0 } else { tp as f32 / gt_count as f32 },
+                pr_curve: curve
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| [i as f32 / (RECALL_POINTS - 1) as f32, *p])
+                    .collect(),
+            });
+        }
+    }
+    let mut classes: Vec<ClassEvaluation> = classes
+        .into_values()
+        .map(|mut class| {
+            if let Some(aps) = ap_by_class.get(&class.class_name) {
+                class.ap50 = aps.first().copied().unwrap_or(0.0);
+                class.ap50_95 = mean(aps.iter().copied());
+            }
+            class
+        })
+        .collect();
+    classes.sort_by(|a, b| a.class_name.cmp(&b.class_name));
+    classes
+}
+
+/// IoU 0.5 下的混淆矩阵：各类别按当前阈值过滤后，不区分类别按IoU从高到低贪心匹配检测框与真值框
+fn confusion_matrix(predictions: &[Prediction], class_names: &[String], thresholds: &HashMap<String, f32>) -> ConfusionMatrix {
+    let mut labels = class_names.to_vec();
+    labels.push(BACKGROUND_LABEL.to_string());
+    let background = labels.len() - 1;
+    let index_of = |name: &str| class_names.iter().position(|n| n == name).unwrap_or(background);
+    let mut matrix = vec![vec![0u32; labels.len()]; labels.len()];
+
+    for prediction in predictions {
+        let detections: Vec<&YoloDetection> = prediction
+            .detections
+            .iter()
+            .filter(|d| d.confidence >= thresholds.get(&d.class_name).copied().unwrap_or(DEFAULT_CLASS_THRESHOLD))
+            .collect();
+        let truth = &prediction.image.boxes;
+        let mut pairs: Vec<(usize, usize, f32)> = Vec::new();
+        for (i, gt) in truth.iter().enumerate() {
+            for (j, detection) in detections.iter().enumerate() {
+                let iou = calculate_iou(&gt.bbox, &detection.bbox);
+                if iou >= DEFAULT_MATCH_IOU {
+                    pairs.push((i, j, iou));
+                }
+            }
+        }
+        pairs.sort_by(|a, b| b.2.total_cmp(&a.2));
+        let mut used_truth = vec![false; truth.len()];
+        let mut used_detection = vec![false; detections.len()];
+        for (i, j, _) in pairs {
+            if used_truth[i] || used_detection[j] {
+                continue;
+            }
+            used_truth[i] = true;
+            used_detection[j] = true;
+            matrix[index_of(&truth[i].class_name)][index_of(&detections[j].class_name)] += 1;
+        }
+        for (gt, _) in truth.iter().zip(&used_truth).filter(|(_, used)| !**used) {
+            matrix[index_of(&gt.class_name)][background] += 1;
+        }
+        for (detection, _) in detections.iter().zip(&used_detection).filter(|(_, used)| !**used) {
+            matrix[background][index_of(&detection.class_name)] += 1;
+        }
+    }
+    ConfusionMatrix { labels, matrix }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 在带YOLO格式真值的样本目录上运行当前模型，给出各类别F1最高（或达到目标精确率时召回最高）的置信度阈值；
+/// apply 为 true 时直接写入检测配置并应用到当前检测器
+#[tauri::command]
+pub async fn suggest_thresholds(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    labeled_dir: String,
+    precision_target: Option<f32>,
+    iou_threshold: Option<f32>,
+    apply: Option<bool>
+) -> Result<ThresholdReport, DetectionError> {
+    if let Some(target) = precision_target.filter(|t| !(0.0..=1.0).contains(t)) {
+        return Err(DetectionError::InvalidInput(format!("目标精确率必须在 0-1 之间: {}", target)));
+    }
+    let iou_threshold = iou_threshold.unwrap_or(DEFAULT_MATCH_IOU).clamp(0.0, 1.0);
+
+    let detector = match evaluation_detector(&state, &store).await {
+        Ok((detector, _)) => detector,
+        Err(e) => return Err(DetectionError::from(e).context("创建评估检测器失败")),
+    };
+    let images = match load_yolo_dir(Path::new(&labeled_dir), detector.get_class_names()) {
+        Ok(images) => images,
+        Err(e) => return Err(DetectionError::from(e).context("读取样本目录失败")),
+    };
+    tracing::info!("🎯 阈值建议: {} ({} 张图片)", labeled_dir, images.len());
+
+    let image_count = images.len();
+    let (predictions, failed_images) = predict_all(detector.as_ref(), images).await;
+    let current = store.get().confidence_thresholds;
+    let suggestions = suggest(&predictions, iou_threshold, precision_target, &current);
+
+    let apply = apply.unwrap_or(false) && !suggestions.is_empty();
+    if apply {
+        let detector = state.read().await;
+        for suggestion in &suggestions {
+            if let Err(e) = detector.update_confidence_threshold(&suggestion.class_name, suggestion.threshold).await {
+                return Err(DetectionError::from(e).context("更新置信度阈值失败"));
+            }
+        }
+        let thresholds = suggestions.iter().map(|s| (s.class_name.clone(), s.threshold));
+        if let Err(e) = store.update(|config| config.confidence_thresholds.extend(thresholds)) {
+            return Err(DetectionError::from(e).context("保存置信度阈值失败"));
+        }
+    }
+    for suggestion in &suggestions {
+        tracing::info!(
+            "🎯 {}: 阈值 {:.3} (P {:.3}, R {:.3}, F1 {:.3})",
+            suggestion.class_name, suggestion.threshold, suggestion.precision, suggestion.recall, suggestion.f1
+        );
+    }
+
+    Ok(ThresholdReport {
+        labeled_dir,
+        images: image_count,
+        failed_images,
+        iou_threshold,
+        precision_target,
+        suggestions,
+        applied: apply,
+    })
+}
+
+/// 在带真值的样本目录（YOLO或COCO格式）上评估当前模型：mAP50、mAP50-95、各类别精确率/召回率与PR曲线、混淆矩阵
+#[tauri::command]
+pub async fn evaluate_model(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    dataset_dir: String,
+    format: DatasetFormat
+) -> Result<EvaluationReport, DetectionError> {
+    let (detector, model_path) = match evaluation_detector(&state, &store).await {
+        Ok(created) => created,
+        Err(e) => return Err(DetectionError::from(e).context("创建评估检测器失败")),
+    };
+    let images = match load_dataset(Path::new(&dataset_dir), format, detector.get_class_names()) {
+        Ok(images) => images,
+        Err(e) => return Err(DetectionError::from(e).context("读取样本目录失败")),
+    };
+    tracing::info!("📊 模型评估: {} ({:?}, {} 张图片)", dataset_dir, format, images.len());
+
+    let image_count = images.len();
+    let (predictions, failed_images) = predict_all(detector.as_ref(), images).await;
+    let thresholds = store.get().confidence_thresholds;
+    let classes = class_metrics(&predictions, &thresholds);
+    let class_names: Vec<String> = classes.iter().map(|c| c.class_name.clone()).collect();
+    let confusion_matrix = confusion_matrix(&predictions, &class_names, &thresholds);
+    let labeled: Vec<&ClassEvaluation> = classes.iter().filter(|c| c.ground_truth > 0).collect();
+    let map50 = mean(labeled.iter().map(|c| c.ap50));
+    let map50_95 = mean(labeled.iter().map(|c| c.ap50_95));
+    tracing::info!("📊 评估完成: mAP50 {:.3}, mAP50-95 {:.3}", map50, map50_95);
+
+    Ok(EvaluationReport {
+        dataset_dir,
+        format,
+        model_path,
+        images: image_count,
+        failed_images,
+        map50,
+        map50_95,
+        classes,
+        confusion_matrix,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn truth(class_name: &str, bbox: [f32; 4]) -> GroundTruthBox {
+        GroundTruthBox { class_name: class_name.to_string(), bbox }
+    }
+
+    fn detection(class_name: &str, confidence: f32, bbox: [f32; 4]) -> YoloDetection {
+        YoloDetection {
+            class_id: 0,
+            class_name: class_name.to_string(),
+            confidence,
+            bbox,
+            size_mm: None,
+        }
+    }
+
+    /// 两张图片：scratch 共 4 个真值框，按置信度排列为 TP 0.9、TP 0.8、FP 0.7、TP 0.6、FP 0.3、TP 0.2；
+    /// dent 1 个真值框，FP 0.95 高于 TP 0.55
+    fn fixture() -> Vec<Prediction> {
+        vec![
+            Prediction {
+                image: LabeledImage {
+                    image_path: PathBuf::from("a.jpg"),
+                    boxes: vec![
+                        truth("scratch", [0.0, 0.0, 50.0, 50.0]),
+                        truth("scratch", [100.0, 100.0, 50.0, 50.0]),
+                        truth("dent", [300.0, 0.0, 40.0, 40.0]),
+                    ],
+                },
+                detections: vec![
+                    detection("scratch", 0.9, [0.0, 0.0, 50.0, 50.0]),
+                    detection("scratch", 0.7, [300.0, 300.0, 50.0, 50.0]),
+                    detection("scratch", 0.6, [102.0, 100.0, 50.0, 50.0]),
+                    detection("dent", 0.95, [500.0, 500.0, 40.0, 40.0]),
+                    detection("dent", 0.55, [300.0, 2.0, 40.0, 40.0]),
+                ],
+            },
+            Prediction {
+                image: LabeledImage {
+                    image_path: PathBuf::from("b.jpg"),
+                    boxes: vec![
+                        truth("scratch", [0.0, 0.0, 50.0, 50.0]),
+                        truth("scratch", [200.0, 0.0, 50.0, 50.0]),
+                    ],
+                },
+                detections: vec![
+                    detection("scratch", 0.8, [1.0, 1.0, 50.0, 50.0]),
+                    detection("scratch", 0.3, [400.0, 400.0, 40.0, 40.0]),
+                    detection("scratch", 0.2, [200.0, 0.0, 50.0, 50.0]),
+                ],
+            },
+        ]
+    }
+
+    fn suggestion<'a>(suggestions: &'a [ThresholdSuggestion], class_name: &str) -> &'a ThresholdSuggestion {
+        suggestions.iter().find(|s| s.class_name == class_name).unwrap()
+    }
+
+    #[test]
+    fn suggests_best_f1_threshold_without_precision_target() {
+        let current = HashMap::from([("scratch".to_string(), 0.5)]);
+        let suggestions = suggest(&fixture(), DEFAULT_MATCH_IOU, None, &current);
+        assert_eq!(suggestions.iter().map(|s| s.class_name.as_str()).collect::<Vec<_>>(), ["dent", "scratch"]);
+
+        // 阈值 0.2 时 TP 4 / FP 2：精确率 2/3、召回率 1，F1 0.8 为最高
+        let scratch = suggestion(&suggestions, "scratch");
+        assert_eq!(scratch.threshold, 0.2);
+        assert_eq!(scratch.recall, 1.0);
+        assert!((scratch.f1 - 0.8).abs() < 1e-6);
+        assert_eq!((scratch.ground_truth, scratch.predictions), (4, 6));
+        assert_eq!(scratch.current_threshold, Some(0.5));
+        assert!(scratch.target_met);
+    }
+
+    #[test]
+    fn suggests_highest_recall_threshold_meeting_precision_target() {
+        // 精确率不低于 0.7 的阈值中 0.6 的召回率最高（TP 3 / FP 1）
+        let suggestions = suggest(&fixture(), DEFAULT_MATCH_IOU, Some(0.7), &HashMap::new());
+        let scratch = suggestion(&suggestions, "scratch");
+        assert_eq!(scratch.threshold, 0.6);
+        assert_eq!((scratch.precision, scratch.recall), (0.75, 0.75));
+        assert!(scratch.target_met);
+
+        // 要求 0.9 时只有前两个点满足，取召回率更高的 0.8
+        let suggestions = suggest(&fixture(), DEFAULT_MATCH_IOU, Some(0.9), &HashMap::new());
+        assert_eq!(suggestion(&suggestions, "scratch").threshold, 0.8);
+
+        // dent 没有达到目标的阈值，退回F1最高的 0.55
+        let dent = suggestion(&suggestions, "dent");
+        assert_eq!(dent.threshold, 0.55);
+        assert_eq!((dent.precision, dent.recall), (0.5, 1.0));
+        assert!(!dent.target_met);
+    }
+}
@@ -0,0 +1,557 @@
+/*!
+告警事件录像模块
+每个会话在内存中滚动保留最近N秒的画面（JPEG编码后缓存），告警触发时把这些前置画面与之后M秒的画面
+一起保存为永久事件录像，并附带每帧的检测结果；录制结束后用 ffmpeg 合成为MP4短片
+（ffmpeg 不可用时只保留逐帧图片），录像记录关联到触发的告警
+*/
+
+use std::collections::{HashMap, VecDeque};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use image::codecs::jpeg::JpegEncoder;
+use image::DynamicImage;
+use parking_lot::{Mutex, RwLock};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio_util::sync::CancellationToken;
+
+use crate::alerts::Alert;
+use crate::error::DetectionError;
+use crate::ffmpeg;
+use crate::privacy;
+use crate::storage::{self, now_rfc3339, Database};
+use crate::yolo_api::Detection;
+
+/// 事件录像保存完成事件
+pub const EVENT_RECORDING_SAVED: &str = "recording://saved";
+
+/// 录像清单文件名
+pub const MANIFEST_FILE_NAME: &str = "recording.json";
+
+/// 合成的短片文件名
+pub const CLIP_FILE_NAME: &str = "clip.mp4";
+
+/// 事件录像配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventRecordingConfig {
+    pub enabled: bool,
+    pub pre_seconds: u32,  // 告警前保留的时长（内存滚动缓冲）
+    pub post_seconds: u32, // 告警后继续录制的时长
+    pub max_fps: f32,      // 录像帧率上限，超出的帧不缓存
+    pub jpeg_quality: u8,
+    pub encode_clip: bool, // 录制结束后合成MP4短片
+}
+
+impl Default for EventRecordingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pre_seconds: 5,
+            post_seconds: 10,
+            max_fps: 10.0,
+            jpeg_quality: 80,
+            encode_clip: true,
+        }
+    }
+}
+
+impl EventRecordingConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.pre_seconds > 120 {
+            return Err(anyhow!("前置录像时长不能超过120秒: {}", self.pre_seconds));
+        }
+        if self.post_seconds > 600 {
+            return Err(anyhow!("后续录像时长不能超过600秒: {}", self.post_seconds));
+        }
+        if !(self.max_fps > 0.0 && self.max_fps <= 60.0) {
+            return Err(anyhow!("录像帧率上限必须在 0-60 之间: {}", self.max_fps));
+        }
+        Ok(())
+    }
+
+    fn min_interval_ms(&self) -> i64 {
+        (1000.0 / self.max_fps) as i64
+    }
+}
+
+/// 内存缓冲中的一帧（已编码为JPEG）
+#[derive(Clone)]
+struct MemoryFrame {
+    jpeg: Arc<Vec<u8>>,
+    timestamp: String,
+    timestamp_ms: i64,
+    detections: Vec<Detection>,
+}
+
+/// 会话的前置画面缓冲
+#[derive(Default)]
+struct PreRollBuffer {
+    frames: VecDeque<MemoryFrame>,
+    last_ms: Option<i64>,
+}
+
+/// 录像中的一帧
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub file: String,
+    pub timestamp: String,
+    pub post_event: bool, // 是否为告警之后的画面
+    pub detections: Vec<Detection>,
+}
+
+/// 录像清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingManifest {
+    pub recording_id: i64,
+    pub session: String,
+    pub alerts: Vec<Alert>,
+    pub started_at: String,
+    pub finished_at: String,
+    pub frames: Vec<RecordedFrame>,
+    pub fps: f32,                  // 短片帧率（按实际录制间隔估算）
+    pub clip_file: Option<String>, // 合成的MP4短片，未合成时为空
+}
+
+/// 事件录像记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRecording {
+    pub id: i64,
+    pub session: String,
+    pub alert_id: i64,
+    pub dir: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub frame_count: u32,
+    pub clip_path: Option<String>, // MP4短片路径
+}
+
+struct ActiveRecording {
+    id: i64,
+    session: String,
+    dir: PathBuf,
+    started_at: String,
+    deadline_ms: i64,
+    alerts: Vec<Alert>,
+    frames: Vec<RecordedFrame>,
+}
+
+/// 已登记到录像、待写盘的一帧
+struct PendingWrite {
+    path: PathBuf,
+    jpeg: Arc<Vec<u8>>,
+}
+
+impl PendingWrite {
+    /// 在录像锁外写盘
+    fn write(self) {
+        if let Err(e) = std::fs::write(&self.path, self.jpeg.as_slice()) {
+            tracing::error!("写入录像帧失败 {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+impl ActiveRecording {
+    /// 登记一帧：帧文件连续编号（ffmpeg 按序号读取），是否为告警后画面记录在清单中；
+    /// 返回待写盘的文件，由调用方在释放锁后写入
+    fn add_frame(&mut self, frame: &MemoryFrame, post_event: bool) -> PendingWrite {
+        let file = format!("frame_{:05}.jpg", self.frames.len());
+        let path = self.dir.join(&file);
+        self.frames.push(RecordedFrame {
+            file,
+            timestamp: frame.timestamp.clone(),
+            post_event,
+            detections: frame.detections.clone(),
+        });
+        PendingWrite {
+            path,
+            jpeg: frame.jpeg.clone(),
+        }
+    }
+}
+
+/// 事件录像管理器（Tauri托管状态）
+pub struct EventRecorder {
+    root: PathBuf,
+    config: RwLock<EventRecordingConfig>,
+    pre_roll: Mutex<HashMap<String, PreRollBuffer>>,
+    active: Mutex<Vec<ActiveRecording>>,
+}
+
+/// 初始化事件录像表结构
+pub fn init_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS event_recordings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session TEXT NOT NULL,
+            alert_id INTEGER NOT NULL REFERENCES alerts(id) ON DELETE CASCADE,
+            dir TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            finished_at TEXT,
+            frame_count INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_event_recordings_alert ON event_recordings(alert_id);",
+    )?;
+    storage::add_column_if_missing(conn, "event_recordings", "clip_path", "TEXT")?;
+    Ok(())
+}
+
+fn row_to_recording(row: &Row) -> rusqlite::Result<EventRecording> {
+    Ok(EventRecording {
+        id: row.get(0)?,
+        session: row.get(1)?,
+        alert_id: row.get(2)?,
+        dir: row.get(3)?,
+        started_at: row.get(4)?,
+        finished_at: row.get(5)?,
+        frame_count: row.get(6)?,
+        clip_path: row.get(7)?,
+    })
+}
+
+/// 查询事件录像（按时间倒序）
+pub fn list(db: &Database, alert_id: Option<i64>, limit: u32) -> Result<Vec<EventRecording>> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, session, alert_id, dir, started_at, finished_at, frame_count, clip_path
+             FROM event_recordings
+             WHERE (?1 IS NULL OR alert_id = ?1)
+             ORDER BY id DESC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![alert_id, limit], row_to_recording)?;
+        rows.collect()
+    })
+}
+
+impl EventRecorder {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            config: RwLock::new(EventRecordingConfig::default()),
+            pre_roll: Mutex::new(HashMap::new()),
+            active: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn config(&self) -> EventRecordingConfig {
+        self.config.read().clone()
+    }
+
+    /// 告警触发：保存内存缓冲中的前置画面并开始录制后续画面
+    /// 同一会话已有进行中的录像时只延长录制时间，避免重复录像
+    pub fn start(&self, app: &AppHandle, session: &str, alert: &Alert) -> Result<()> {
+        let config = self.config();
+        if !config.enabled {
+            return Ok(());
+        }
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let deadline_ms = now_ms + config.post_seconds as i64 * 1000;
+
+        // 查找与登记在同一把锁内完成，同一会话同时触发的告警只会创建一个录像
+        let mut active = self.active.lock();
+        if let Some(recording) = active.iter_mut().find(|r| r.session == session) {
+            recording.deadline_ms = recording.deadline_ms.max(deadline_ms);
+            recording.alerts.push(alert.clone());
+            return Ok(());
+        }
+
+        let db = app.state::<Database>();
+        let started_at = now_rfc3339();
+        let id = db.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO event_recordings (session, alert_id, dir, started_at) VALUES (?1, ?2, '', ?3)",
+                params![session, alert.id, started_at],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })?;
+
+        let dir = self.root.join(format!("event_{:06}", id));
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| anyhow!("创建录像目录失败 {}: {}", dir.display(), e))?;
+        let dir_str = dir.to_string_lossy().to_string();
+        db.with_conn(|conn| {
+            conn.execute("UPDATE event_recordings SET dir = ?1 WHERE id = ?2", params![dir_str, id])
+        })?;
+
+        let mut recording = ActiveRecording {
+            id,
+            session: session.to_string(),
+            dir,
+            started_at,
+            deadline_ms,
+            alerts: vec![alert.clone()],
+            frames: Vec::new(),
+        };
+        let window_ms = config.pre_seconds as i64 * 1000;
+        let buffered: Vec<MemoryFrame> = self
+            .pre_roll
+            .lock()
+            .get(session)
+            .map(|buffer| {
+                buffer
+                    .frames
+                    .iter()
+                    .filter(|f| now_ms - f.timestamp_ms <= window_ms)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        let writes: Vec<PendingWrite> = buffered.iter().map(|frame| recording.add_frame(frame, false)).collect();
+        tracing::info!(
+            "📼 告警 #{} 开始事件录像 #{}（前置 {} 帧）",
+            alert.id,
+            id,
+            recording.frames.len()
+        );
+        active.push(recording);
+        drop(active);
+        for write in writes {
+            write.write();
+        }
+
+        // 录制时长到期后保存
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let remaining = {
+                    let recorder = app.state::<EventRecorder>();
+                    let active = recorder.active.lock();
+                    match active.iter().find(|r| r.id == id) {
+                        Some(r) => r.deadline_ms - chrono::Utc::now().timestamp_millis(),
+                        None => return,
+                    }
+                };
+                if remaining <= 0 {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(remaining as u64)).await;
+            }
+            if let Err(e) = finalize(&app, id).await {
+                tracing::error!("保存事件录像 #{} 失败: {}", id, e);
+            }
+        });
+
+        Ok(())
+    }
+
+    /// 进行中的录像数量与已保存的帧数
+    pub fn active_usage(&self) -> (usize, usize) {
+        let active = self.active.lock();
+        (active.len(), active.iter().map(|r| r.frames.len()).sum())
+    }
+
+    /// 前置画面缓冲占用的内存与帧数
+    pub fn pre_roll_usage(&self) -> (u64, usize) {
+        let pre_roll = self.pre_roll.lock();
+        let frames = pre_roll.values().flat_map(|b| b.frames.iter());
+        frames.fold((0, 0), |(bytes, count), f| (bytes + f.jpeg.len() as u64, count + 1))
+    }
+
+    /// 每个推理帧调用：按帧率上限缓存到前置画面缓冲，并追加到同会话进行中的录像
+    pub fn on_frame(&self, session: &str, frame: &DynamicImage, detections: &[Detection]) {
+        let config = self.config();
+        if !config.enabled {
+            return;
+        }
+        let now = chrono::Utc::now();
+        let now_ms = now.timestamp_millis();
+        let recording = self
+            .active
+            .lock()
+            .iter()
+            .any(|r| r.session == session && now_ms <= r.deadline_ms);
+        if config.pre_seconds == 0 && !recording {
+            return;
+        }
+        if let Some(last) = self.pre_roll.lock().get(session).and_then(|b| b.last_ms) {
+            if now_ms - last < config.min_interval_ms() {
+                return;
+            }
+        }
+
+        // 编码在锁外完成（按隐私遮挡设置先遮挡）
+        let stored = privacy::for_storage(frame);
+        let mut jpeg = Vec::new();
+        if let Err(e) = JpegEncoder::new_with_quality(&mut Cursor::new(&mut jpeg), config.jpeg_quality.clamp(1, 100))
+            .encode_image(&stored.to_rgb8())
+        {
+            tracing::error!("录像帧编码失败: {}", e);
+            return;
+        }
+        let frame = MemoryFrame {
+            jpeg: Arc::new(jpeg),
+            timestamp: now.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            timestamp_ms: now_ms,
+            detections: detections.to_vec(),
+        };
+
+        {
+            let mut pre_roll = self.pre_roll.lock();
+            let buffer = pre_roll.entry(session.to_string()).or_default();
+            buffer.last_ms = Some(now_ms);
+            if config.pre_seconds > 0 {
+                let window_ms = config.pre_seconds as i64 * 1000;
+                buffer.frames.push_back(frame.clone());
+                while buffer.frames.front().is_some_and(|f| now_ms - f.timestamp_ms > window_ms) {
+                    buffer.frames.pop_front();
+                }
+            } else {
+                buffer.frames.clear();
+            }
+        }
+
+        let writes: Vec<PendingWrite> = self
+            .active
+            .lock()
+            .iter_mut()
+            .filter(|recording| recording.session == session && now_ms <= recording.deadline_ms)
+            .map(|recording| recording.add_frame(&frame, true))
+            .collect();
+        for write in writes {
+            write.write();
+        }
+    }
+
+    /// 清空指定会话的前置画面缓冲（检测停止后调用）
+    pub fn clear_pre_roll(&self, session: &str) {
+        self.pre_roll.lock().remove(session);
+    }
+}
+
+/// 按录制时间估算的帧率（限制在 1 到帧率上限之间）
+fn estimate_fps(frames: &[RecordedFrame], max_fps: f32) -> f32 {
+    let first = frames.first().and_then(|f| crate::replay::parse_timestamp_ms(&f.timestamp));
+    let last = frames.last().and_then(|f| crate::replay::parse_timestamp_ms(&f.timestamp));
+    match (first, last) {
+        (Some(first), Some(last)) if last > first && frames.len() > 1 => {
+            ((frames.len() - 1) as f32 * 1000.0 / (last - first) as f32).clamp(1.0, max_fps.max(1.0))
+        }
+        _ => max_fps.max(1.0),
+    }
+}
+
+/// 用 ffmpeg 把录像帧合成为MP4短片（宽高取偶数，满足H.264要求）
+async fn encode_clip(dir: &Path, fps: f32) -> Result<PathBuf> {
+    let output = dir.join(CLIP_FILE_NAME);
+    ffmpeg::run(
+        [
+            "-y".to_string(),
+            "-framerate".to_string(),
+            format!("{:.3}", fps),
+            "-i".to_string(),
+            dir.join("frame_%05d.jpg").to_string_lossy().to_string(),
+            "-vf".to_string(),
+            "scale=trunc(iw/2)*2:trunc(ih/2)*2".to_string(),
+            "-c:v".to_string(),
+            "libx264".to_string(),
+            "-pix_fmt".to_string(),
+            "yuv420p".to_string(),
+            output.to_string_lossy().to_string(),
+        ],
+        &CancellationToken::new(),
+    )
+    .await?;
+    Ok(output)
+}
+
+/// 结束录像：合成短片、写入清单并更新数据库记录
+async fn finalize(app: &AppHandle, id: i64) -> Result<()> {
+    let recording = {
+        let recorder = app.state::<EventRecorder>();
+        let mut active = recorder.active.lock();
+        match active.iter().position(|r| r.id == id) {
+            Some(index) => active.remove(index),
+            None => return Ok(()),
+        }
+    };
+
+    let config = app.state::<EventRecorder>().config();
+    let fps = estimate_fps(&recording.frames, config.max_fps);
+    let clip_path = if config.encode_clip && !recording.frames.is_empty() {
+        match encode_clip(&recording.dir, fps).await {
+            Ok(path) => Some(path.to_string_lossy().to_string()),
+            Err(e) => {
+                tracing::warn!("事件录像 #{} 合成短片失败，只保留逐帧图片: {}", id, e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let finished_at = now_rfc3339();
+    let manifest = RecordingManifest {
+        recording_id: recording.id,
+        session: recording.session.clone(),
+        alerts: recording.alerts,
+        started_at: recording.started_at.clone(),
+        finished_at: finished_at.clone(),
+        frames: recording.frames,
+        fps,
+        clip_file: clip_path.as_ref().map(|_| CLIP_FILE_NAME.to_string()),
+    };
+    write_manifest(&recording.dir, &manifest)?;
+
+    let frame_count = manifest.frames.len() as u32;
+    let db = app.state::<Database>();
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE event_recordings SET finished_at = ?1, frame_count = ?2, clip_path = ?3 WHERE id = ?4",
+            params![finished_at, frame_count, clip_path, id],
+        )
+    })?;
+
+    tracing::info!("📼 事件录像 #{} 已保存: {} 帧 -> {}", id, frame_count, recording.dir.display());
+    let _ = app.emit(
+        EVENT_RECORDING_SAVED,
+        EventRecording {
+            id,
+            session: recording.session,
+            alert_id: manifest.alerts.first().map(|a| a.id).unwrap_or_default(),
+            dir: recording.dir.to_string_lossy().to_string(),
+            started_at: recording.started_at,
+            finished_at: Some(finished_at),
+            frame_count,
+            clip_path,
+        },
+    );
+    Ok(())
+}
+
+fn write_manifest(dir: &Path, manifest: &RecordingManifest) -> Result<()> {
+    std::fs::write(dir.join(MANIFEST_FILE_NAME), serde_json::to_string_pretty(manifest)?)?;
+    Ok(())
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 更新事件录像配置
+#[tauri::command]
+pub async fn set_event_recording_config(
+    recorder: State<'_, EventRecorder>,
+    config: EventRecordingConfig
+) -> Result<String, DetectionError> {
+    if let Err(e) = config.validate() {
+        return Err(DetectionError::InvalidInput(format!("事件录像配置无效: {:#}", e)));
+    }
+    *recorder.config.write() = config;
+    Ok("事件录像配置已更新".to_string())
+}
+
+/// 查询事件录像
+#[tauri::command]
+pub async fn list_event_recordings(
+    db: State<'_, Database>,
+    alert_id: Option<i64>,
+    limit: Option<u32>
+) -> Result<Vec<EventRecording>, DetectionError> {
+    match list(&db, alert_id, limit.unwrap_or(100)) {
+        Ok(recordings) => Ok(recordings),
+        Err(e) => Err(DetectionError::from(e).context("查询事件录像失败")),
+    }
+}
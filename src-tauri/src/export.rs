@@ -0,0 +1,299 @@
+/*!
+检测结果导出模块
+按历史查询条件把检测结果导出为 CSV、JSONL 或 COCO 标注 JSON，
+便于导入外部分析工具与标注平台。各格式均保留模型原始输出，另附操作员复核状态
+（确认/误报/类别修正/漏检）以及修正后的类别与检测框
+*/
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::corrections::{self, CorrectedRun, DetectionOrigin, EffectiveDetection, ReviewStatus};
+use crate::error::DetectionError;
+use crate::history::{self, HistoryFilter};
+use crate::storage::Database;
+use crate::tasks::{TaskHandle, TaskManager};
+use crate::AppState;
+
+/// CSV表头
+const CSV_HEADER: &str = "run_id,session_id,created_at,source,image_width,image_height,processing_time_ms,\
+detection_id,class_id,class_name,confidence,x,y,width,height,\
+review_status,reviewed_class_name,reviewed_x,reviewed_y,reviewed_width,reviewed_height";
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,   // 每个检测框一行，无检测框的运行输出一行空框；漏检框只填复核列
+    Jsonl, // 每次检测运行一行JSON
+    Coco,  // COCO检测结果格式（images/annotations/categories，annotation带score）
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub format: ExportFormat,
+    pub run_count: u64,
+    pub detection_count: u64,
+}
+
+/// CSV字段转义：包含逗号、引号或换行时加引号
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 复核后类别有变化时返回修正后的类别
+fn reviewed_class(detection: &EffectiveDetection) -> Option<&EffectiveDetection> {
+    (detection.origin != DetectionOrigin::Model).then_some(detection)
+}
+
+/// 复核后检测框有变化时返回修正后的坐标
+fn reviewed_bbox(detection: &EffectiveDetection) -> Option<[f32; 4]> {
+    (detection.bbox_corrected || detection.review == ReviewStatus::Missed).then_some(detection.bbox)
+}
+
+/// CSV复核列：类别与检测框未修正时留空
+fn review_columns(detection: &EffectiveDetection) -> String {
+    let class_name = reviewed_class(detection).map(|d| csv_field(&d.class_name)).unwrap_or_default();
+    let bbox = match reviewed_bbox(detection) {
+        Some([x, y, w, h]) => format!("{:.2},{:.2},{:.2},{:.2}", x, y, w, h),
+        None => ",,,".to_string(),
+    };
+    format!("{},{},{}", detection.review.as_str(), class_name, bbox)
+}
+
+fn write_csv(writer: &mut impl Write, runs: &[CorrectedRun], task: &TaskHandle) -> Result<()> {
+    writeln!(writer, "{}", CSV_HEADER)?;
+    for (index, corrected) in runs.iter().enumerate() {
+        task.advance(index as u64, Some(runs.len() as u64))?;
+        let run = &corrected.run;
+        let prefix = format!(
+            "{},{},{},{},{},{},{}",
+            run.id,
+            run.session_id.map(|id| id.to_string()).unwrap_or_default(),
+            run.created_at,
+            csv_field(&run.source),
+            run.image_width,
+            run.image_height,
+            run.processing_time_ms
+        );
+        if corrected.detections.is_empty() {
+            writeln!(writer, "{},,,,,,,,,,,,,,", prefix)?;
+        }
+        // 修正后的检测框列表前部与模型输出一一对应，其后为操作员补充的漏检框
+        let (model, missed) = corrected.detections.split_at(run.detections.len());
+        for (detection, effective) in run.detections.iter().zip(model) {
+            let [x, y, w, h] = detection.bbox;
+            writeln!(
+                writer,
+                "{},{},{},{},{:.4},{:.2},{:.2},{:.2},{:.2},{}",
+                prefix,
+                detection.id,
+                detection.class_id,
+                csv_field(&detection.class_name),
+                detection.confidence,
+                x,
+                y,
+                w,
+                h,
+                review_columns(effective)
+            )?;
+        }
+        for effective in missed {
+            writeln!(writer, "{},,,,,,,,,{}", prefix, review_columns(effective))?;
+        }
+    }
+    Ok(())
+}
+
+/// 复核信息的JSON表示（未修正的字段省略）
+fn review_json(detection: &EffectiveDetection) -> serde_json::Value {
+    let mut review = serde_json::json!({ "review_status": detection.review });
+    if let Some(reviewed) = reviewed_class(detection) {
+        review["reviewed_class_id"] = reviewed.class_id.into();
+        review["reviewed_class_name"] = reviewed.class_name.clone().into();
+    }
+    if let Some(bbox) = reviewed_bbox(detection) {
+        review["reviewed_bbox"] = serde_json::json!(bbox);
+    }
+    review
+}
+
+fn write_jsonl(writer: &mut impl Write, runs: &[CorrectedRun], task: &TaskHandle) -> Result<()> {
+    for (index, corrected) in runs.iter().enumerate() {
+        task.advance(index as u64, Some(runs.len() as u64))?;
+        let run = &corrected.run;
+        let mut value = serde_json::to_value(run)?;
+        let (model, missed) = corrected.detections.split_at(run.detections.len());
+        if let Some(detections) = value.get_mut("detections").and_then(|d| d.as_array_mut()) {
+            for (detection, effective) in detections.iter_mut().zip(model) {
+                if let (Some(object), serde_json::Value::Object(review)) = (detection.as_object_mut(), review_json(effective)) {
+                    object.extend(review);
+                }
+            }
+        }
+        value["reviewed"] = (!corrected.corrections.is_empty()).into();
+        value["missed_detections"] = missed
+            .iter()
+            .map(|d| serde_json::json!({ "class_id": d.class_id, "class_name": d.class_name, "bbox": d.bbox }))
+            .collect::<Vec<_>>()
+            .into();
+        serde_json::to_writer(&mut *writer, &value)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+fn write_coco(
+    writer: &mut impl Write,
+    runs: &[CorrectedRun],
+    class_names: &BTreeMap<u32, String>,
+    task: &TaskHandle,
+) -> Result<()> {
+    let mut categories = class_names.clone();
+    let mut images = Vec::with_capacity(runs.len());
+    let mut annotations = Vec::new();
+
+    // COCO检测结果只包含模型输出（annotation带score），漏检框不在其中，复核状态作为附加字段
+    for (index, corrected) in runs.iter().enumerate() {
+        task.advance(index as u64, Some(runs.len() as u64))?;
+        let run = &corrected.run;
+        images.push(serde_json::json!({
+            "id": run.id,
+            "file_name": run.source,
+            "width": run.image_width,
+            "height": run.image_height,
+            "date_captured": run.created_at,
+        }));
+        for (detection, effective) in run.detections.iter().zip(&corrected.detections) {
+            let [x, y, w, h] = detection.bbox;
+            categories
+                .entry(detection.class_id)
+                .or_insert_with(|| detection.class_name.clone());
+            if let Some(reviewed) = reviewed_class(effective) {
+                categories
+                    .entry(reviewed.class_id)
+                    .or_insert_with(|| reviewed.class_name.clone());
+            }
+            let mut annotation = serde_json::json!({
+                "id": detection.id,
+                "image_id": run.id,
+                "category_id": detection.class_id,
+                "bbox": [x, y, w, h],
+                "area": w * h,
+                "score": detection.confidence,
+                "iscrowd": 0,
+            });
+            if let (Some(object), serde_json::Value::Object(review)) = (annotation.as_object_mut(), review_json(effective)) {
+                object.extend(review);
+            }
+            annotations.push(annotation);
+        }
+    }
+
+    let coco = serde_json::json!({
+        "info": {
+            "description": "YOLO Detection System detection results",
+            "date_created": chrono::Utc::now().to_rfc3339(),
+        },
+        "images": images,
+        "annotations": annotations,
+        "categories": categories
+            .iter()
+            .map(|(id, name)| serde_json::json!({ "id": id, "name": name }))
+            .collect::<Vec<_>>(),
+    });
+    serde_json::to_writer_pretty(&mut *writer, &coco)?;
+    Ok(())
+}
+
+/// 导出检测结果到文件
+pub fn export_to_file(
+    db: &Database,
+    format: ExportFormat,
+    filter: &HistoryFilter,
+    path: &Path,
+    class_names: &BTreeMap<u32, String>,
+    task: &TaskHandle,
+) -> Result<ExportSummary> {
+    let runs = history::query_all(db, filter)?
+        .into_iter()
+        .map(|run| corrections::with_corrections(db, run))
+        .collect::<Result<Vec<_>>>()?;
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| anyhow!("创建输出目录失败 {}: {}", parent.display(), e))?;
+    }
+    let file = File::create(path).map_err(|e| anyhow!("创建导出文件失败 {}: {}", path.display(), e))?;
+    let mut writer = BufWriter::new(file);
+    let written = match format {
+        ExportFormat::Csv => write_csv(&mut writer, &runs, task),
+        ExportFormat::Jsonl => write_jsonl(&mut writer, &runs, task),
+        ExportFormat::Coco => write_coco(&mut writer, &runs, class_names, task),
+    };
+    if let Err(e) = written {
+        // 取消或写入失败时不保留不完整的文件
+        drop(writer);
+        let _ = std::fs::remove_file(path);
+        return Err(e);
+    }
+    writer.flush()?;
+
+    let detection_count = runs.iter().map(|corrected| corrected.run.detections.len() as u64).sum();
+    tracing::info!(
+        "📤 检测结果已导出: {} ({} 次检测, {} 个检测框)",
+        path.display(),
+        runs.len(),
+        detection_count
+    );
+
+    Ok(ExportSummary {
+        path: path.to_string_lossy().to_string(),
+        format,
+        run_count: runs.len() as u64,
+        detection_count,
+    })
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 按条件导出检测结果（CSV / JSONL / COCO）
+#[tauri::command]
+pub async fn export_results(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    db: State<'_, Database>,
+    tasks: State<'_, TaskManager>,
+    format: ExportFormat,
+    filter: Option<HistoryFilter>,
+    path: String,
+    task_id: Option<String>
+) -> Result<ExportSummary, DetectionError> {
+    let class_names: BTreeMap<u32, String> = state
+        .read()
+        .await
+        .get_class_names()
+        .iter()
+        .map(|(id, name)| (*id, name.clone()))
+        .collect();
+
+    let task = tasks.begin(&app, "export", task_id)?;
+    let result = export_to_file(&db, format, &filter.unwrap_or_default(), Path::new(&path), &class_names, &task);
+    task.finish(&result);
+    match result {
+        Ok(summary) => Ok(summary),
+        Err(e) => Err(DetectionError::from(e).context("导出检测结果失败")),
+    }
+}
@@ -0,0 +1,64 @@
+/*!
+FFmpeg 外部进程封装
+视频剪辑、合成等操作通过调用系统中的 ffmpeg 可执行文件完成
+可通过环境变量 YOLO_FFMPEG_PATH 指定 ffmpeg 路径
+*/
+
+use std::ffi::OsStr;
+use std::process::Stdio;
+
+use anyhow::{anyhow, Result};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::DetectionError;
+
+/// ffmpeg 路径环境变量
+pub const FFMPEG_PATH_ENV: &str = "YOLO_FFMPEG_PATH";
+
+/// 获取 ffmpeg 可执行文件路径
+pub fn ffmpeg_binary() -> String {
+    std::env::var(FFMPEG_PATH_ENV).unwrap_or_else(|_| "ffmpeg".to_string())
+}
+
+/// 执行 ffmpeg 命令，失败时返回 stderr 末尾的错误信息；
+/// 令牌取消时结束 ffmpeg 进程并返回 `Cancelled`
+pub async fn run<I, S>(args: I, cancel: &CancellationToken) -> Result<()>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let binary = ffmpeg_binary();
+    let child = tokio::process::Command::new(&binary)
+        .arg("-hide_banner")
+        .arg("-loglevel")
+        .arg("error")
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| anyhow!("无法启动ffmpeg ({}): {}，请安装ffmpeg或设置 {}", binary, e, FFMPEG_PATH_ENV))?;
+
+    let output = tokio::select! {
+        output = child.wait_with_output() => output.map_err(|e| anyhow!("等待ffmpeg结束失败: {}", e))?,
+        _ = cancel.cancelled() => return Err(DetectionError::Cancelled.into()),
+    };
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+        Err(anyhow!(
+            "ffmpeg执行失败 ({}): {}",
+            output.status,
+            tail.into_iter().rev().collect::<Vec<_>>().join(" | ")
+        ))
+    }
+}
+
+/// 格式化为 ffmpeg 时间参数（秒，毫秒精度）
+pub fn format_seconds(seconds: f64) -> String {
+    format!("{:.3}", seconds.max(0.0))
+}
@@ -0,0 +1,310 @@
+/*!
+文件夹监控模块
+监听指定目录中新增的图片，自动执行检测并通过 `detection://new-result` 事件推送结果，
+适用于相机软件/产线系统把图片写入共享目录的场景
+*/
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use glob::Pattern;
+use notify::event::{EventKind, ModifyKind};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::mpsc;
+
+use crate::alerts::Alert;
+use crate::artifacts::ArtifactImage;
+use crate::error::DetectionError;
+use crate::inference_worker::InferenceWorker;
+use crate::profiles;
+use crate::yolo::{decode, DetectionResult};
+use crate::yolo_api;
+
+/// 新检测结果事件
+pub const EVENT_NEW_RESULT: &str = "detection://new-result";
+
+/// 默认忽略的文件（隐藏文件与写入中的临时文件）
+const DEFAULT_IGNORE_PATTERNS: &[&str] = &[".*", "*.tmp", "*.part", "*~"];
+
+/// 等待文件写入完成的最长时间
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(5);
+const SETTLE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 监控到的新图片检测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderWatchResult {
+    pub path: String,
+    pub result: DetectionResult,
+    pub run_id: Option<i64>,
+    pub alerts: Vec<Alert>,
+}
+
+/// 文件夹监控状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FolderWatchStatus {
+    pub active: bool,
+    pub path: Option<String>,
+    pub recursive: bool,
+    pub paused: bool,
+    pub ignore_patterns: Vec<String>,
+    pub processed: u64,
+    pub failed: u64,
+    pub started_at: Option<String>,
+}
+
+struct WatchShared {
+    root: PathBuf,
+    ignore: Vec<Pattern>,
+    paused: AtomicBool,
+    processed: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl WatchShared {
+    /// 按文件名或相对监控目录的路径匹配忽略规则
+    fn is_ignored(&self, path: &Path) -> bool {
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        self.ignore
+            .iter()
+            .any(|pattern| pattern.matches(file_name) || pattern.matches_path(relative))
+    }
+}
+
+struct WatchSession {
+    _watcher: RecommendedWatcher, // 释放即停止监听
+    shared: Arc<WatchShared>,
+    path: String,
+    recursive: bool,
+    ignore_patterns: Vec<String>,
+    started_at: String,
+}
+
+/// 文件夹监控管理器（Tauri托管状态）
+#[derive(Default)]
+pub struct FolderWatcher {
+    session: Mutex<Option<WatchSession>>,
+}
+
+/// 是否为可解码的图片文件（格式以编译进来的解码器为准）
+pub(crate) fn is_image(path: &Path) -> bool {
+    decode::is_supported_path(path)
+}
+
+/// 等待文件大小稳定（写入完成）
+async fn wait_until_settled(path: &Path) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + SETTLE_TIMEOUT;
+    let mut last_size = None;
+    loop {
+        let size = tokio::fs::metadata(path).await.map(|m| m.len()).ok();
+        if size.is_some() && size == last_size && size != Some(0) {
+            return Ok(());
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Err(anyhow!("等待文件写入完成超时: {}", path.display()));
+        }
+        last_size = size;
+        tokio::time::sleep(SETTLE_INTERVAL).await;
+    }
+}
+
+async fn detect_file(app: &AppHandle, path: &Path) -> Result<FolderWatchResult> {
+    wait_until_settled(path).await?;
+    detect_path(app, path).await
+}
+
+/// 检测一张已写入完成的图片：写入历史与产物、推送结果并按规则告警（定时扫描共用）
+pub(crate) async fn detect_path(app: &AppHandle, path: &Path) -> Result<FolderWatchResult> {
+    let data = tokio::fs::read(path).await?;
+    let source = path.to_string_lossy().to_string();
+
+    let mut result = app.state::<InferenceWorker>().detect(data.clone()).await?;
+    result.set_frame(&source, None, None);
+    let stored = yolo_api::store_detection(app, &source, ArtifactImage::Encoded(&data), &result);
+    Ok(FolderWatchResult { path: source, result, run_id: stored.run_id, alerts: stored.alerts })
+}
+
+impl FolderWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 开始监控目录（已有监控时先停止）
+    pub fn start(&self, app: &AppHandle, path: &str, recursive: bool, ignore_patterns: Vec<String>) -> Result<()> {
+        let root = PathBuf::from(path);
+        if !root.is_dir() {
+            return Err(anyhow!("监控路径不是目录: {}", path));
+        }
+        let ignore = ignore_patterns
+            .iter()
+            .map(|p| Pattern::new(p).map_err(|e| anyhow!("忽略规则无效 {}: {}", p, e)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let shared = Arc::new(WatchShared {
+            root: root.clone(),
+            ignore,
+            paused: AtomicBool::new(false),
+            processed: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+        });
+
+        // 监听回调运行在notify线程，新文件经通道交给异步任务逐个检测
+        let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+            Ok(event) => {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))) {
+                    for path in event.paths {
+                        let _ = tx.send(path);
+                    }
+                }
+            }
+            Err(e) => tracing::error!("文件夹监控事件错误: {}", e),
+        })
+        .map_err(|e| anyhow!("创建文件监控失败: {}", e))?;
+        let mode = if recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+        watcher
+            .watch(&root, mode)
+            .map_err(|e| anyhow!("监控目录失败 {}: {}", root.display(), e))?;
+
+        let task_shared = shared.clone();
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            // 监控停止后watcher被释放，发送端随之关闭，循环结束
+            while let Some(path) = rx.recv().await {
+                if task_shared.paused.load(Ordering::Relaxed)
+                    || !is_image(&path)
+                    || task_shared.is_ignored(&path)
+                {
+                    continue;
+                }
+                match detect_file(&app, &path).await {
+                    Ok(result) => {
+                        task_shared.processed.fetch_add(1, Ordering::Relaxed);
+                        tracing::info!("📂 自动检测 {}: {} 个目标", result.path, result.result.detections.len());
+                        let _ = app.emit(EVENT_NEW_RESULT, result);
+                    }
+                    Err(e) => {
+                        task_shared.failed.fetch_add(1, Ordering::Relaxed);
+                        tracing::error!("自动检测失败 {}: {}", path.display(), e);
+                    }
+                }
+            }
+        });
+
+        tracing::info!("📂 开始监控文件夹: {}（递归: {}）", path, recursive);
+        *self.session.lock() = Some(WatchSession {
+            _watcher: watcher,
+            shared,
+            path: path.to_string(),
+            recursive,
+            ignore_patterns,
+            started_at: crate::storage::now_rfc3339(),
+        });
+        Ok(())
+    }
+
+    /// 停止监控，返回是否有正在进行的监控
+    pub fn stop(&self) -> bool {
+        let stopped = self.session.lock().take();
+        if let Some(session) = &stopped {
+            tracing::info!("📂 停止监控文件夹: {}", session.path);
+        }
+        stopped.is_some()
+    }
+
+    /// 暂停/恢复自动检测（暂停期间新增的文件被跳过）
+    pub fn set_paused(&self, paused: bool) -> Result<()> {
+        let session = self.session.lock();
+        let session = session.as_ref().ok_or_else(|| anyhow!("当前没有进行中的文件夹监控"))?;
+        session.shared.paused.store(paused, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn status(&self) -> FolderWatchStatus {
+        match self.session.lock().as_ref() {
+            Some(session) => FolderWatchStatus {
+                active: true,
+                path: Some(session.path.clone()),
+                recursive: session.recursive,
+                paused: session.shared.paused.load(Ordering::Relaxed),
+                ignore_patterns: session.ignore_patterns.clone(),
+                processed: session.shared.processed.load(Ordering::Relaxed),
+                failed: session.shared.failed.load(Ordering::Relaxed),
+                started_at: Some(session.started_at.clone()),
+            },
+            None => FolderWatchStatus::default(),
+        }
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 开始监控文件夹，新增图片自动检测
+#[tauri::command]
+pub async fn start_folder_watch(
+    app: AppHandle,
+    watcher: State<'_, FolderWatcher>,
+    path: String,
+    recursive: Option<bool>,
+    ignore_patterns: Option<Vec<String>>
+) -> Result<FolderWatchStatus, DetectionError> {
+    let ignore_patterns = ignore_patterns
+        .unwrap_or_else(|| DEFAULT_IGNORE_PATTERNS.iter().map(|p| p.to_string()).collect());
+    watcher.stop();
+    // 加载绑定到该文件夹的配置档案
+    if let Err(e) = profiles::apply_bound(&app, &path).await {
+        return Err(DetectionError::from(e).context("启动文件夹监控失败"));
+    }
+    match watcher.start(&app, &path, recursive.unwrap_or(false), ignore_patterns) {
+        Ok(()) => Ok(watcher.status()),
+        Err(e) => Err(DetectionError::from(e).context("启动文件夹监控失败")),
+    }
+}
+
+/// 停止文件夹监控
+#[tauri::command]
+pub async fn stop_folder_watch(
+    watcher: State<'_, FolderWatcher>
+) -> Result<String, DetectionError> {
+    if watcher.stop() {
+        Ok("文件夹监控已停止".to_string())
+    } else {
+        Err(DetectionError::NotFound("当前没有进行中的文件夹监控".to_string()))
+    }
+}
+
+/// 暂停文件夹监控的自动检测
+#[tauri::command]
+pub async fn pause_folder_watch(
+    watcher: State<'_, FolderWatcher>
+) -> Result<FolderWatchStatus, DetectionError> {
+    match watcher.set_paused(true) {
+        Ok(()) => Ok(watcher.status()),
+        Err(e) => Err(DetectionError::from(e).context("暂停文件夹监控失败")),
+    }
+}
+
+/// 恢复文件夹监控的自动检测
+#[tauri::command]
+pub async fn resume_folder_watch(
+    watcher: State<'_, FolderWatcher>
+) -> Result<FolderWatchStatus, DetectionError> {
+    match watcher.set_paused(false) {
+        Ok(()) => Ok(watcher.status()),
+        Err(e) => Err(DetectionError::from(e).context("恢复文件夹监控失败")),
+    }
+}
+
+/// 获取文件夹监控状态
+#[tauri::command]
+pub async fn get_folder_watch_status(
+    watcher: State<'_, FolderWatcher>
+) -> Result<FolderWatchStatus, DetectionError> {
+    Ok(watcher.status())
+}
@@ -0,0 +1,150 @@
+/*!
+有界帧队列模块
+实时/视频检测结果的环形缓冲区，容量与队满时的丢弃策略可配置：
+丢弃最旧帧、丢弃最新帧，或阻塞生产者直到消费者取走帧
+*/
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+/// 默认队列容量
+pub const DEFAULT_CAPACITY: usize = 10;
+
+/// 队列容量上限（标注帧为Base64图像，避免占用过多内存）
+pub const MAX_CAPACITY: usize = 300;
+
+/// 阻塞策略下重新检查队列的间隔（防止漏掉唤醒）
+const BLOCK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 队满时的处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropPolicy {
+    #[default]
+    DropOldest, // 丢弃最旧的帧，保证画面最新
+    DropNewest, // 丢弃新到的帧，保留已缓存的帧
+    Block,      // 等待消费者取走帧，适用于需要完整结果的轮询场景
+}
+
+/// 帧队列配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FrameQueueConfig {
+    pub capacity: usize,
+    pub drop_policy: DropPolicy,
+}
+
+impl Default for FrameQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            drop_policy: DropPolicy::default(),
+        }
+    }
+}
+
+impl FrameQueueConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.capacity == 0 || self.capacity > MAX_CAPACITY {
+            return Err(anyhow!("队列容量必须在 1-{} 之间", MAX_CAPACITY));
+        }
+        Ok(())
+    }
+}
+
+/// 有界帧队列
+pub struct FrameQueue<T> {
+    config: Mutex<FrameQueueConfig>,
+    items: Mutex<VecDeque<T>>,
+    dropped: AtomicU64,
+    closed: AtomicBool,
+    space: Notify,
+}
+
+impl<T> FrameQueue<T> {
+    pub fn new(config: FrameQueueConfig) -> Self {
+        Self {
+            config: Mutex::new(config),
+            items: Mutex::new(VecDeque::with_capacity(config.capacity)),
+            dropped: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+            space: Notify::new(),
+        }
+    }
+
+    /// 放入一帧，按丢弃策略处理队满；返回该帧是否入队
+    pub async fn push(&self, item: T) -> bool {
+        loop {
+            {
+                let config = *self.config.lock();
+                let mut items = self.items.lock();
+                if items.len() < config.capacity {
+                    items.push_back(item);
+                    return true;
+                }
+                match config.drop_policy {
+                    DropPolicy::DropOldest => {
+                        items.pop_front();
+                        items.push_back(item);
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                        return true;
+                    }
+                    DropPolicy::DropNewest => {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                        return false;
+                    }
+                    DropPolicy::Block if self.closed.load(Ordering::Relaxed) => return false,
+                    DropPolicy::Block => {}
+                }
+            }
+            let _ = tokio::time::timeout(BLOCK_POLL_INTERVAL, self.space.notified()).await;
+        }
+    }
+
+    /// 取出最早的一帧
+    pub fn pop(&self) -> Option<T> {
+        let item = self.items.lock().pop_front();
+        if item.is_some() {
+            self.space.notify_one();
+        }
+        item
+    }
+
+    /// 清空队列（视频跳转后旧结果不再有意义，不计入丢弃数）
+    pub fn clear(&self) {
+        self.items.lock().clear();
+        self.space.notify_waiters();
+    }
+
+    /// 关闭队列，唤醒阻塞中的生产者
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+        self.space.notify_waiters();
+    }
+
+    /// 更新配置，容量缩小时丢弃最旧的帧
+    pub fn set_config(&self, config: FrameQueueConfig) {
+        *self.config.lock() = config;
+        let mut items = self.items.lock();
+        while items.len() > config.capacity {
+            items.pop_front();
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        self.space.notify_waiters();
+    }
+
+    /// 当前缓存的帧数
+    pub fn buffered(&self) -> usize {
+        self.items.lock().len()
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,170 @@
+/*!
+GIF动图导出模块
+将一段检测记录导出为体积较小的标注GIF，方便在聊天工具或问题跟踪系统中
+向供应商反馈缺陷出现情况
+*/
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::FilterType;
+use image::{Delay, Frame};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::error::DetectionError;
+use crate::history;
+use crate::replay::render_run;
+use crate::storage::Database;
+use crate::tasks::{TaskHandle, TaskManager};
+
+/// 单个GIF最多包含的帧数，超出部分按步长抽帧
+const MAX_GIF_FRAMES: usize = 120;
+
+/// 会话范围（与回放、延时视频使用相同的筛选条件）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GifSession {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub source: Option<String>,
+}
+
+/// 会话内的帧区间（按检测记录序号，左闭右开）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GifFrameRange {
+    pub start: Option<usize>,
+    pub end: Option<usize>,
+}
+
+/// GIF输出参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GifOptions {
+    pub frame_delay_ms: u32,
+    pub max_width: u32,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self {
+            frame_delay_ms: 200,
+            max_width: 480,
+        }
+    }
+}
+
+/// GIF导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GifExportSummary {
+    pub output_path: String,
+    pub frame_count: u32,
+    pub skipped_frames: u32,
+    pub width: u32,
+    pub height: u32,
+    pub file_size: u64,
+}
+
+/// 导出标注GIF
+pub fn export(
+    db: &Database,
+    session: &GifSession,
+    range: &GifFrameRange,
+    options: &GifOptions,
+    output_path: &Path,
+    task: &TaskHandle,
+) -> Result<GifExportSummary> {
+    let runs = history::runs_in_range(db, session.from.as_deref(), session.to.as_deref(), session.source.as_deref())?;
+    let start = range.start.unwrap_or(0);
+    let end = range.end.unwrap_or(runs.len()).min(runs.len());
+    if start >= end {
+        return Err(anyhow!("帧区间为空: {}..{} (共 {} 条记录)", start, end, runs.len()));
+    }
+
+    let selected = &runs[start..end];
+    let step = selected.len().div_ceil(MAX_GIF_FRAMES).max(1);
+    let delay = Delay::from_numer_denom_ms(options.frame_delay_ms.max(20) * step as u32, 1);
+
+    let mut frame_size: Option<(u32, u32)> = None;
+    let mut frames = Vec::new();
+    let mut skipped_frames = 0u32;
+
+    let total = selected.len().div_ceil(step) as u64;
+    for (index, run) in selected.iter().step_by(step).enumerate() {
+        task.advance(index as u64, Some(total))?;
+        let annotated = match render_run(run) {
+            Some(img) => img,
+            None => {
+                skipped_frames += 1;
+                continue;
+            }
+        };
+        let (width, height) = *frame_size.get_or_insert_with(|| {
+            let scale = (options.max_width.max(16) as f32 / annotated.width() as f32).min(1.0);
+            (
+                ((annotated.width() as f32 * scale) as u32).max(1),
+                ((annotated.height() as f32 * scale) as u32).max(1),
+            )
+        });
+        let rgba = annotated.resize_exact(width, height, FilterType::Triangle).to_rgba8();
+        frames.push(Frame::from_parts(rgba, 0, 0, delay));
+    }
+
+    let (width, height) = frame_size.ok_or_else(|| anyhow!("所选帧的源图片都已不存在，无法生成GIF"))?;
+    let frame_count = frames.len() as u32;
+
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    {
+        let writer = BufWriter::new(File::create(output_path)?);
+        let mut encoder = GifEncoder::new_with_speed(writer, 10);
+        encoder.set_repeat(Repeat::Infinite)?;
+        encoder.encode_frames(frames)?;
+    }
+
+    let file_size = std::fs::metadata(output_path)?.len();
+    tracing::info!(
+        "🖼️ GIF已导出: {} ({} 帧, {}x{}, {} KB)",
+        output_path.display(),
+        frame_count,
+        width,
+        height,
+        file_size / 1024
+    );
+
+    Ok(GifExportSummary {
+        output_path: output_path.to_string_lossy().to_string(),
+        frame_count,
+        skipped_frames,
+        width,
+        height,
+        file_size,
+    })
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 导出检测序列为标注GIF
+#[tauri::command]
+pub async fn export_gif(
+    app: AppHandle,
+    db: State<'_, Database>,
+    tasks: State<'_, TaskManager>,
+    session: GifSession,
+    range: Option<GifFrameRange>,
+    options: Option<GifOptions>,
+    output_path: String,
+    task_id: Option<String>
+) -> Result<GifExportSummary, DetectionError> {
+    let range = range.unwrap_or_default();
+    let options = options.unwrap_or_default();
+    let task = tasks.begin(&app, "gif", task_id)?;
+    let result = export(&db, &session, &range, &options, Path::new(&output_path), &task);
+    task.finish(&result);
+    match result {
+        Ok(summary) => Ok(summary),
+        Err(e) => Err(DetectionError::from(e).context("导出GIF失败")),
+    }
+}
@@ -0,0 +1,142 @@
+/*!
+真值标注存储模块
+保存人工标注的真值框，供模型评估与阈值调优使用
+*/
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::error::DetectionError;
+use crate::storage::{now_rfc3339, Database};
+
+/// 真值标注框
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroundTruthBox {
+    pub class_name: String,
+    pub bbox: [f32; 4], // [x, y, width, height] - 像素坐标
+}
+
+/// 单张图片的真值标注
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroundTruthImage {
+    pub image_path: String,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub source: String, // 标注来源，如 label_studio
+    pub imported_at: String,
+    pub boxes: Vec<GroundTruthBox>,
+}
+
+/// 初始化真值标注表结构
+pub fn init_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS ground_truth_images (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            image_path TEXT NOT NULL UNIQUE,
+            image_width INTEGER NOT NULL,
+            image_height INTEGER NOT NULL,
+            source TEXT NOT NULL,
+            imported_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS ground_truth_boxes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            image_id INTEGER NOT NULL REFERENCES ground_truth_images(id) ON DELETE CASCADE,
+            class_name TEXT NOT NULL,
+            x REAL NOT NULL,
+            y REAL NOT NULL,
+            width REAL NOT NULL,
+            height REAL NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_gt_boxes_image ON ground_truth_boxes(image_id);",
+    )?;
+    Ok(())
+}
+
+/// 写入（或覆盖）一张图片的真值标注
+pub fn upsert_image(db: &Database, image: &GroundTruthImage) -> Result<()> {
+    db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM ground_truth_images WHERE image_path = ?1",
+            params![image.image_path],
+        )?;
+        tx.execute(
+            "INSERT INTO ground_truth_images (image_path, image_width, image_height, source, imported_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![image.image_path, image.image_width, image.image_height, image.source, now_rfc3339()],
+        )?;
+        let image_id = tx.last_insert_rowid();
+        for b in &image.boxes {
+            let [x, y, w, h] = b.bbox;
+            tx.execute(
+                "INSERT INTO ground_truth_boxes (image_id, class_name, x, y, width, height)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![image_id, b.class_name, x as f64, y as f64, w as f64, h as f64],
+            )?;
+        }
+        tx.commit()
+    })
+}
+
+/// 查询真值标注（可按来源过滤）
+pub fn list_images(db: &Database, source: Option<&str>) -> Result<Vec<GroundTruthImage>> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, image_path, image_width, image_height, source, imported_at
+             FROM ground_truth_images WHERE (?1 IS NULL OR source = ?1) ORDER BY id ASC",
+        )?;
+        let rows = stmt
+            .query_map(params![source], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    GroundTruthImage {
+                        image_path: row.get(1)?,
+                        image_width: row.get(2)?,
+                        image_height: row.get(3)?,
+                        source: row.get(4)?,
+                        imported_at: row.get(5)?,
+                        boxes: Vec::new(),
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut box_stmt = conn.prepare(
+            "SELECT class_name, x, y, width, height FROM ground_truth_boxes WHERE image_id = ?1 ORDER BY id ASC",
+        )?;
+        let mut images = Vec::with_capacity(rows.len());
+        for (image_id, mut image) in rows {
+            image.boxes = box_stmt
+                .query_map(params![image_id], |row| {
+                    Ok(GroundTruthBox {
+                        class_name: row.get(0)?,
+                        bbox: [
+                            row.get::<_, f64>(1)? as f32,
+                            row.get::<_, f64>(2)? as f32,
+                            row.get::<_, f64>(3)? as f32,
+                            row.get::<_, f64>(4)? as f32,
+                        ],
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            images.push(image);
+        }
+        Ok(images)
+    })
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 查询真值标注
+#[tauri::command]
+pub async fn list_ground_truth(
+    db: State<'_, Database>,
+    source: Option<String>
+) -> Result<Vec<GroundTruthImage>, DetectionError> {
+    match list_images(&db, source.as_deref()) {
+        Ok(images) => Ok(images),
+        Err(e) => Err(DetectionError::from(e).context("查询真值标注失败")),
+    }
+}
@@ -0,0 +1,270 @@
+/*!
+无界面命令行模式
+不启动Tauri窗口，直接用同一套检测器与检测配置处理输入源，逐条把检测结果写入JSON Lines文件，
+用于批处理任务与持续集成中的回归测试：
+
+```text
+yolo-detection-system --headless --source <图片|目录|视频|camera:0|rtsp://...> --model <模型.onnx> --out results.jsonl
+    [--config <检测配置目录>] [--max-frames <N>]
+```
+
+视频与摄像头输入同样经过目标跟踪与时序过滤；摄像头与网络流需要 `opencv-support` 特性，
+未指定 `--max-frames` 时持续运行直到输入结束
+*/
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use image::ImageFormat;
+use serde::Serialize;
+
+use crate::capture::{self, FrameSource};
+use crate::detection_config::{self, ConfigStore};
+use crate::folder_watch::is_image;
+use crate::temporal_filter::TemporalFilter;
+use crate::tracking::TrackingManager;
+use crate::yolo::{self, DetectionResult, Detector, InferenceBackend};
+
+/// 命令行参数
+#[derive(Debug, Clone)]
+pub struct HeadlessArgs {
+    pub source: String,
+    pub model: String,
+    pub out: PathBuf,
+    pub config_dir: Option<PathBuf>,
+    pub max_frames: Option<u64>,
+}
+
+/// 输出文件中的一行
+#[derive(Debug, Serialize)]
+struct ResultLine<'a> {
+    source: &'a str,
+    frame_index: u64,
+    timestamp: String,
+    result: &'a DetectionResult,
+}
+
+/// 运行汇总
+#[derive(Debug, Default)]
+struct Summary {
+    frames: u64,
+    detections: u64,
+    failures: u64,
+    total_ms: u64,
+}
+
+const USAGE: &str = "用法: --headless --source <图片|目录|视频|camera:0|rtsp://...> --model <模型.onnx> --out <results.jsonl> [--config <检测配置目录>] [--max-frames <N>]";
+
+/// 解析命令行，未带 `--headless` 时返回 None（正常启动窗口）
+pub fn parse_args(args: &[String]) -> Option<Result<HeadlessArgs>> {
+    if !args.iter().any(|arg| arg == "--headless") {
+        return None;
+    }
+    Some(parse_headless_args(args))
+}
+
+fn parse_headless_args(args: &[String]) -> Result<HeadlessArgs> {
+    let mut source = None;
+    let mut model = None;
+    let mut out = None;
+    let mut config_dir = None;
+    let mut max_frames = None;
+
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().cloned().ok_or_else(|| anyhow!("参数 {} 缺少取值\n{}", arg, USAGE));
+        match arg.as_str() {
+            "--headless" => {}
+            "--source" => source = Some(value()?),
+            "--model" => model = Some(value()?),
+            "--out" => out = Some(PathBuf::from(value()?)),
+            "--config" => config_dir = Some(PathBuf::from(value()?)),
+            "--max-frames" => {
+                let raw = value()?;
+                max_frames = Some(raw.parse::<u64>().map_err(|_| anyhow!("--max-frames 须为正整数: {}", raw))?);
+            }
+            other => return Err(anyhow!("未知参数: {}\n{}", other, USAGE)),
+        }
+    }
+
+    Ok(HeadlessArgs {
+        source: source.ok_or_else(|| anyhow!("缺少 --source\n{}", USAGE))?,
+        model: model.ok_or_else(|| anyhow!("缺少 --model\n{}", USAGE))?,
+        out: out.ok_or_else(|| anyhow!("缺少 --out\n{}", USAGE))?,
+        config_dir,
+        max_frames,
+    })
+}
+
+/// 运行无界面检测，返回进程退出码
+pub fn run(args: HeadlessArgs) -> i32 {
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("[ERROR] 创建异步运行时失败: {}", e);
+            return 1;
+        }
+    };
+    match runtime.block_on(run_async(&args)) {
+        Ok(summary) => {
+            let avg_ms = if summary.frames > 0 { summary.total_ms / summary.frames } else { 0 };
+            println!(
+                "✅ 检测完成: {} 帧, {} 个目标, {} 帧失败, 平均 {} ms/帧, 结果已写入 {}",
+                summary.frames,
+                summary.detections,
+                summary.failures,
+                avg_ms,
+                args.out.display()
+            );
+            if summary.failures > 0 { 2 } else { 0 }
+        }
+        Err(e) => {
+            eprintln!("[ERROR] {:#}", e);
+            1
+        }
+    }
+}
+
+async fn run_async(args: &HeadlessArgs) -> Result<Summary> {
+    let mut detector = yolo::create_detector(InferenceBackend::Candle);
+    detector
+        .init_model(&args.model)
+        .await
+        .with_context(|| format!("加载模型失败: {}", args.model))?;
+    if let Some(config_dir) = &args.config_dir {
+        let config = ConfigStore::load(config_dir).get();
+        detection_config::apply(detector.as_mut(), &config).await.context("应用检测配置失败")?;
+    }
+
+    if let Some(parent) = args.out.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = File::create(&args.out).with_context(|| format!("创建输出文件失败: {}", args.out.display()))?;
+    let mut out = BufWriter::new(file);
+
+    let source_path = Path::new(&args.source);
+    let summary = if source_path.is_dir() {
+        let mut images: Vec<PathBuf> = std::fs::read_dir(source_path)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| is_image(path))
+            .collect();
+        images.sort();
+        run_images(detector.as_mut(), &images, args.max_frames, &mut out).await?
+    } else if is_image(source_path) {
+        run_images(detector.as_mut(), &[source_path.to_path_buf()], args.max_frames, &mut out).await?
+    } else {
+        let frames = open_source(&args.source)?;
+        run_frames(detector.as_mut(), frames, args.max_frames, &mut out).await?
+    };
+    out.flush()?;
+    Ok(summary)
+}
+
+fn open_source(source: &str) -> Result<Box<dyn FrameSource>> {
+    if let Some(device) = source.strip_prefix("camera:") {
+        let device_id = device.parse::<i32>().map_err(|_| anyhow!("摄像头编号无效: {}", device))?;
+        return capture::open_camera(device_id);
+    }
+    if source.contains("://") {
+        return capture::open_stream(source);
+    }
+    if !Path::new(source).exists() {
+        return Err(anyhow!("输入源不存在: {}", source));
+    }
+    capture::open_video(source)
+}
+
+fn write_line(out: &mut impl Write, source: &str, frame_index: u64, result: &DetectionResult) -> Result<()> {
+    let line = ResultLine {
+        source,
+        frame_index,
+        timestamp: crate::storage::now_rfc3339(),
+        result,
+    };
+    serde_json::to_writer(&mut *out, &line)?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+async fn run_images(
+    detector: &mut dyn Detector,
+    images: &[PathBuf],
+    max_frames: Option<u64>,
+    out: &mut impl Write,
+) -> Result<Summary> {
+    let mut summary = Summary::default();
+    for (index, path) in images.iter().enumerate() {
+        if max_frames.is_some_and(|max| index as u64 >= max) {
+            break;
+        }
+        let source = path.to_string_lossy();
+        let result = match std::fs::read(path) {
+            Ok(data) => detector.detect_image(&data).await,
+            Err(e) => Err(anyhow!("读取文件失败: {}", e)),
+        };
+        match result {
+            Ok(mut result) => {
+                result.apply_scale(detection_config::px_per_mm());
+                result.set_frame(&source, None, None);
+                summary.frames += 1;
+                summary.detections += result.detections.len() as u64;
+                summary.total_ms += result.processing_time_ms;
+                write_line(out, &source, index as u64, &result)?;
+            }
+            Err(e) => {
+                summary.failures += 1;
+                tracing::error!("{} 检测失败: {}", source, e);
+            }
+        }
+    }
+    Ok(summary)
+}
+
+async fn run_frames(
+    detector: &mut dyn Detector,
+    mut frames: Box<dyn FrameSource>,
+    max_frames: Option<u64>,
+    out: &mut impl Write,
+) -> Result<Summary> {
+    let source = frames.describe();
+    let tracking = TrackingManager::new();
+    let temporal_filter = TemporalFilter::new();
+    let mut summary = Summary::default();
+    let mut frame_index = 0;
+    tracing::info!("▶️ 开始检测 {}", source);
+
+    while !max_frames.is_some_and(|max| frame_index >= max) {
+        let Some(frame) = frames.read_frame()? else {
+            break;
+        };
+        let index = frames.position().unwrap_or(frame_index);
+        let captured_at = crate::storage::now_rfc3339();
+        frame_index += 1;
+        let frame = match detection_config::undistorter() {
+            Some(undistorter) => undistorter.apply(&frame),
+            None => frame,
+        };
+
+        let mut data = Vec::new();
+        frame.write_to(&mut std::io::Cursor::new(&mut data), ImageFormat::Jpeg)?;
+        match detector.detect_image(&data).await {
+            Ok(mut result) => {
+                result.apply_scale(detection_config::px_per_mm());
+                result.set_frame(&source, Some(index), Some(captured_at));
+                tracking.update(&mut result);
+                temporal_filter.apply(&mut result);
+                summary.frames += 1;
+                summary.detections += result.detections.len() as u64;
+                summary.total_ms += result.processing_time_ms;
+                write_line(out, &source, index, &result)?;
+            }
+            Err(e) => {
+                summary.failures += 1;
+                tracing::error!("第 {} 帧检测失败: {}", index, e);
+            }
+        }
+    }
+    Ok(summary)
+}
@@ -0,0 +1,478 @@
+/*!
+检测历史记录模块
+将每次检测运行（输入源、时间、检测框、使用的阈值、耗时、所属会话、使用的配置档案）保存到本地数据库，
+支持按条件分页查询与按范围删除
+*/
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::error::DetectionError;
+use crate::storage::{self, now_rfc3339, Database};
+use crate::yolo::DetectionResult;
+
+/// 每页最大条数
+const MAX_PAGE_SIZE: u32 = 500;
+
+/// 已保存的单个检测框
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredDetection {
+    pub id: i64,
+    pub run_id: i64,
+    pub class_id: u32,
+    pub class_name: String,
+    pub confidence: f32,
+    pub bbox: [f32; 4], // [x, y, width, height]
+}
+
+/// 已保存的检测运行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionRun {
+    pub id: i64,
+    pub source: String,
+    pub created_at: String,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub processing_time_ms: u64,
+    #[serde(default)]
+    pub session_id: Option<i64>, // 所属检测会话
+    #[serde(default)]
+    pub thresholds: HashMap<String, f32>, // 检测时使用的各类别置信度阈值
+    #[serde(default)]
+    pub profile: Option<String>, // 检测时加载的配置档案
+    pub detections: Vec<StoredDetection>,
+}
+
+/// 历史查询条件（均为可选，组合为与关系）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryFilter {
+    pub from: Option<String>,       // 起始时间（RFC3339，含）
+    pub to: Option<String>,         // 结束时间（RFC3339，含）
+    pub source: Option<String>,     // 输入源包含的文本
+    pub class_name: Option<String>, // 包含该类别的检测框
+    pub min_confidence: Option<f32>,
+    pub has_detections: Option<bool>,
+    pub session_id: Option<i64>,    // 属于该检测会话
+}
+
+/// 分页参数，页码从1开始
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pagination {
+    pub page: u32,
+    pub page_size: u32,
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self { page: 1, page_size: 50 }
+    }
+}
+
+/// 分页查询结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub runs: Vec<DetectionRun>,
+    pub total: u64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+/// 删除范围：时间范围或指定运行ID，至少提供一项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryRange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub run_ids: Option<Vec<i64>>,
+}
+
+/// 初始化历史记录表结构
+pub fn init_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS detection_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            image_width INTEGER NOT NULL,
+            image_height INTEGER NOT NULL,
+            processing_time_ms INTEGER NOT NULL,
+            thresholds TEXT
+        );
+        CREATE TABLE IF NOT EXISTS detections (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            run_id INTEGER NOT NULL REFERENCES detection_runs(id) ON DELETE CASCADE,
+            class_id INTEGER NOT NULL,
+            class_name TEXT NOT NULL,
+            confidence REAL NOT NULL,
+            x REAL NOT NULL,
+            y REAL NOT NULL,
+            width REAL NOT NULL,
+            height REAL NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_detections_run ON detections(run_id);
+        CREATE INDEX IF NOT EXISTS idx_detection_runs_created ON detection_runs(created_at);",
+    )?;
+    storage::add_column_if_missing(conn, "detection_runs", "thresholds", "TEXT")?;
+    storage::add_column_if_missing(conn, "detection_runs", "profile", "TEXT")?;
+    Ok(())
+}
+
+/// 记录一次检测运行（可归属到进行中的会话，并记录加载的配置档案），返回运行ID
+pub fn record_run(
+    db: &Database,
+    source: &str,
+    result: &DetectionResult,
+    session_id: Option<i64>,
+    profile: Option<&str>,
+) -> Result<i64> {
+    db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO detection_runs (source, created_at, image_width, image_height, processing_time_ms, thresholds, session_id, profile)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                source,
+                now_rfc3339(),
+                result.image_width,
+                result.image_height,
+                result.processing_time_ms as i64,
+                serde_json::to_string(&result.thresholds).ok(),
+                session_id,
+                profile,
+            ],
+        )?;
+        let run_id = tx.last_insert_rowid();
+
+        for detection in &result.detections {
+            let [x, y, w, h] = detection.bbox;
+            tx.execute(
+                "INSERT INTO detections (run_id, class_id, class_name, confidence, x, y, width, height)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    run_id,
+                    detection.class_id,
+                    detection.class_name,
+                    detection.confidence as f64,
+                    x as f64,
+                    y as f64,
+                    w as f64,
+                    h as f64,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(run_id)
+    })
+}
+
+const DETECTION_COLUMNS: &str = "id, run_id, class_id, class_name, confidence, x, y, width, height";
+
+fn row_to_detection(row: &Row) -> rusqlite::Result<StoredDetection> {
+    Ok(StoredDetection {
+        id: row.get(0)?,
+        run_id: row.get(1)?,
+        class_id: row.get(2)?,
+        class_name: row.get(3)?,
+        confidence: row.get::<_, f64>(4)? as f32,
+        bbox: [
+            row.get::<_, f64>(5)? as f32,
+            row.get::<_, f64>(6)? as f32,
+            row.get::<_, f64>(7)? as f32,
+            row.get::<_, f64>(8)? as f32,
+        ],
+    })
+}
+
+fn load_detections(conn: &Connection, run_id: i64) -> rusqlite::Result<Vec<StoredDetection>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM detections WHERE run_id = ?1 ORDER BY id ASC",
+        DETECTION_COLUMNS
+    ))?;
+    let detections = stmt
+        .query_map(params![run_id], row_to_detection)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(detections)
+}
+
+const RUN_COLUMNS: &str =
+    "id, source, created_at, image_width, image_height, processing_time_ms, thresholds, session_id, profile";
+
+fn row_to_run(row: &Row) -> rusqlite::Result<DetectionRun> {
+    let thresholds: Option<String> = row.get(6)?;
+    Ok(DetectionRun {
+        id: row.get(0)?,
+        source: row.get(1)?,
+        created_at: row.get(2)?,
+        image_width: row.get(3)?,
+        image_height: row.get(4)?,
+        processing_time_ms: row.get::<_, i64>(5)? as u64,
+        session_id: row.get(7)?,
+        profile: row.get(8)?,
+        thresholds: thresholds
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        detections: Vec::new(),
+    })
+}
+
+/// 查询单次检测运行（含检测框）
+pub fn get_run(db: &Database, run_id: i64) -> Result<Option<DetectionRun>> {
+    db.with_conn(|conn| {
+        let run = conn
+            .query_row(
+                &format!("SELECT {} FROM detection_runs WHERE id = ?1", RUN_COLUMNS),
+                params![run_id],
+                row_to_run,
+            )
+            .optional()?;
+
+        match run {
+            Some(mut run) => {
+                run.detections = load_detections(conn, run.id)?;
+                Ok(Some(run))
+            }
+            None => Ok(None),
+        }
+    })
+}
+
+/// 查询单个已保存的检测框
+pub fn get_detection(db: &Database, detection_id: i64) -> Result<Option<StoredDetection>> {
+    db.with_conn(|conn| {
+        conn.query_row(
+            &format!("SELECT {} FROM detections WHERE id = ?1", DETECTION_COLUMNS),
+            params![detection_id],
+            row_to_detection,
+        )
+        .optional()
+    })
+}
+
+/// 查询所有检测运行ID（按时间顺序）
+pub fn all_run_ids(db: &Database) -> Result<Vec<i64>> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT id FROM detection_runs ORDER BY id ASC")?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<i64>>>()?;
+        Ok(ids)
+    })
+}
+
+/// 将查询的起止时间统一为存储使用的UTC毫秒格式，使按字符串比较与按时间比较一致
+pub(crate) fn normalize_time(value: Option<&str>) -> Result<Option<String>> {
+    value
+        .map(|value| match chrono::DateTime::parse_from_rfc3339(value) {
+            Ok(time) => Ok(time
+                .with_timezone(&chrono::Utc)
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)),
+            Err(e) => Err(DetectionError::InvalidInput(format!("时间格式无效（应为RFC3339）: {}: {}", value, e)).into()),
+        })
+        .transpose()
+}
+
+/// 查询时间范围内的检测运行（含检测框，按时间顺序）
+pub fn runs_in_range(
+    db: &Database,
+    from: Option<&str>,
+    to: Option<&str>,
+    source: Option<&str>,
+) -> Result<Vec<DetectionRun>> {
+    let from = normalize_time(from)?;
+    let to = normalize_time(to)?;
+    let ids: Vec<i64> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id FROM detection_runs
+             WHERE (?1 IS NULL OR created_at >= ?1)
+               AND (?2 IS NULL OR created_at <= ?2)
+               AND (?3 IS NULL OR source = ?3)
+             ORDER BY created_at ASC, id ASC",
+        )?;
+        let ids = stmt
+            .query_map(params![from, to, source], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<i64>>>()?;
+        Ok(ids)
+    })?;
+
+    let mut runs = Vec::with_capacity(ids.len());
+    for id in ids {
+        if let Some(run) = get_run(db, id)? {
+            runs.push(run);
+        }
+    }
+    Ok(runs)
+}
+
+/// 查询条件对应的WHERE子句，参数顺序为 from、to、source、class_name、min_confidence、has_detections、session_id
+const FILTER_CLAUSE: &str = "(?1 IS NULL OR r.created_at >= ?1)
+       AND (?2 IS NULL OR r.created_at <= ?2)
+       AND (?3 IS NULL OR instr(r.source, ?3) > 0)
+       AND ((?4 IS NULL AND ?5 IS NULL) OR EXISTS (
+            SELECT 1 FROM detections d WHERE d.run_id = r.id
+              AND (?4 IS NULL OR d.class_name = ?4)
+              AND (?5 IS NULL OR d.confidence >= ?5)))
+       AND (?6 IS NULL OR ?6 = EXISTS (SELECT 1 FROM detections d WHERE d.run_id = r.id))
+       AND (?7 IS NULL OR r.session_id = ?7)";
+
+/// 按条件分页查询检测运行（最新的在前）
+pub fn query(db: &Database, filter: &HistoryFilter, pagination: &Pagination) -> Result<HistoryPage> {
+    let page = pagination.page.max(1);
+    let page_size = pagination.page_size.clamp(1, MAX_PAGE_SIZE);
+    let offset = (page - 1) as i64 * page_size as i64;
+    let min_confidence = filter.min_confidence.map(|c| c as f64);
+    let from = normalize_time(filter.from.as_deref())?;
+    let to = normalize_time(filter.to.as_deref())?;
+
+    db.with_conn(|conn| {
+        let filter_params = params![
+            from,
+            to,
+            filter.source,
+            filter.class_name,
+            min_confidence,
+            filter.has_detections,
+            filter.session_id,
+        ];
+        let total: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM detection_runs r WHERE {}", FILTER_CLAUSE),
+            filter_params,
+            |row| row.get(0),
+        )?;
+
+        let columns = RUN_COLUMNS
+            .split(", ")
+            .map(|column| format!("r.{}", column))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM detection_runs r WHERE {} ORDER BY r.created_at DESC, r.id DESC LIMIT ?8 OFFSET ?9",
+            columns, FILTER_CLAUSE
+        ))?;
+        let mut runs = stmt
+            .query_map(
+                params![
+                    from,
+                    to,
+                    filter.source,
+                    filter.class_name,
+                    min_confidence,
+                    filter.has_detections,
+                    filter.session_id,
+                    page_size,
+                    offset,
+                ],
+                row_to_run,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for run in runs.iter_mut() {
+            run.detections = load_detections(conn, run.id)?;
+        }
+
+        Ok(HistoryPage { runs, total: total as u64, page, page_size })
+    })
+}
+
+/// 按条件读取全部检测运行（按时间顺序），用于导出与报告
+pub fn query_all(db: &Database, filter: &HistoryFilter) -> Result<Vec<DetectionRun>> {
+    let mut runs = Vec::new();
+    let mut pagination = Pagination { page: 1, page_size: MAX_PAGE_SIZE };
+    loop {
+        let page = query(db, filter, &pagination)?;
+        let finished = page.runs.len() < MAX_PAGE_SIZE as usize;
+        runs.extend(page.runs);
+        if finished {
+            break;
+        }
+        pagination.page += 1;
+    }
+    runs.reverse();
+    Ok(runs)
+}
+
+/// 按范围删除检测运行（检测框与修正记录级联删除），返回删除的运行数
+pub fn delete(db: &Database, range: &HistoryRange) -> Result<usize> {
+    let run_ids = range.run_ids.as_deref().unwrap_or_default();
+    if range.from.is_none() && range.to.is_none() && run_ids.is_empty() {
+        return Err(anyhow!("请指定删除的时间范围或运行ID"));
+    }
+    let from = normalize_time(range.from.as_deref())?;
+    let to = normalize_time(range.to.as_deref())?;
+
+    db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let mut deleted = 0;
+        if from.is_some() || to.is_some() {
+            deleted += tx.execute(
+                "DELETE FROM detection_runs
+                 WHERE (?1 IS NULL OR created_at >= ?1) AND (?2 IS NULL OR created_at <= ?2)",
+                params![from, to],
+            )?;
+        }
+        for run_id in run_ids {
+            deleted += tx.execute("DELETE FROM detection_runs WHERE id = ?1", params![run_id])?;
+        }
+        tx.commit()?;
+        Ok(deleted)
+    })
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 分页查询检测历史
+#[tauri::command]
+pub async fn query_history(
+    db: State<'_, Database>,
+    filters: Option<HistoryFilter>,
+    pagination: Option<Pagination>
+) -> Result<HistoryPage, DetectionError> {
+    match query(&db, &filters.unwrap_or_default(), &pagination.unwrap_or_default()) {
+        Ok(page) => Ok(page),
+        Err(e) => Err(DetectionError::from(e).context("查询检测历史失败")),
+    }
+}
+
+/// 删除检测历史
+#[tauri::command]
+pub async fn delete_history(
+    db: State<'_, Database>,
+    range: HistoryRange
+) -> Result<usize, DetectionError> {
+    match delete(&db, &range) {
+        Ok(deleted) => {
+            tracing::info!("🗑️ 已删除 {} 条检测历史", deleted);
+            Ok(deleted)
+        }
+        Err(e) => Err(DetectionError::from(e).context("删除检测历史失败")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_time_converts_offsets_to_stored_utc_format() {
+        assert_eq!(
+            normalize_time(Some("2024-03-01T08:30:00+08:00")).unwrap().as_deref(),
+            Some("2024-03-01T00:30:00.000Z")
+        );
+        assert_eq!(
+            normalize_time(Some("2024-03-01T00:30:00.5Z")).unwrap().as_deref(),
+            Some("2024-03-01T00:30:00.500Z")
+        );
+        assert_eq!(normalize_time(None).unwrap(), None);
+    }
+
+    #[test]
+    fn normalize_time_rejects_invalid_input() {
+        let err = normalize_time(Some("2024-03-01")).unwrap_err();
+        assert!(matches!(DetectionError::from(err), DetectionError::InvalidInput(_)));
+    }
+}
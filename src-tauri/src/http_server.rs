@@ -0,0 +1,648 @@
+/*!
+内嵌HTTP REST服务
+在Tauri后端内启动一个可选的axum服务，局域网内其他机器无需桌面界面即可调用检测器：
+- `GET  /health`        存活检查
+- `POST /detect/image`  上传图片（multipart，字段名 `file`）检测，可加 `?annotate=true` 返回标注图
+- `GET  /status`        模型就绪状态、推理统计与实时检测状态
+- `GET  /history`       分页查询检测历史（参数同检测历史查询）
+- `GET  /stream.mjpeg`  标注后的实时画面（MJPEG，浏览器 `<img>` 可直接播放），`?fps=` 限制推送帧率
+- `GET  /snapshot.jpg`  最近一帧标注画面
+- `GET  /ws/results`    WebSocket实时推送检测结果JSON，`?classes=a,b&cameras=0` 过滤，
+                        连接后也可发送 `{"classes":[],"cameras":[]}` 更新过滤条件
+
+默认关闭；可设置访问令牌，设置后除 `/health` 外的请求须携带 `Authorization: Bearer <令牌>`
+（浏览器直接打开画面时可改用 `?token=<令牌>`）。
+配置保存在应用数据目录下的JSON文件中，启用后应用启动时自动开启服务
+*/
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use axum::body::{Body, Bytes};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{ConnectInfo, DefaultBodyLimit, Multipart, Query, Request, State as AxumState};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::{broadcast, oneshot, watch};
+
+use crate::alerts::Alert;
+use crate::artifacts::ArtifactImage;
+use crate::error::{self, DetectionError};
+use crate::history::{self, HistoryFilter, HistoryPage, Pagination};
+use crate::inference_worker::InferenceWorker;
+use crate::models::{ModelReadiness, ReadinessStatus};
+use crate::realtime::{RealtimePipeline, RealtimeStats};
+use crate::result_feed::{FeedFilter, ResultFeed};
+use crate::storage::Database;
+use crate::viewer::ViewerHub;
+use crate::yolo::{self, DetectionResult, DetectionTimings, ModelStats};
+use crate::yolo_api::{self, draw_detections_on_image, image_to_base64};
+use crate::{ApiResult, AppState};
+
+/// HTTP服务配置文件名（位于应用数据目录）
+pub const SERVER_FILE_NAME: &str = "http_server.json";
+
+/// 上传图片大小上限
+const MAX_UPLOAD_BYTES: usize = 50 * 1024 * 1024;
+
+/// MJPEG分隔符与默认推送帧率
+const MJPEG_BOUNDARY: &str = "frame";
+const DEFAULT_STREAM_FPS: f32 = 10.0;
+
+/// HTTP服务配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpServerConfig {
+    pub enabled: bool,
+    pub bind_address: String,      // 监听地址，局域网访问使用 0.0.0.0
+    pub port: u16,
+    pub api_token: Option<String>, // 访问令牌，为空时不校验
+}
+
+impl Default for HttpServerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: "0.0.0.0".to_string(),
+            port: 8920,
+            api_token: None,
+        }
+    }
+}
+
+impl HttpServerConfig {
+    pub fn validate(&self) -> Result<()> {
+        self.socket_addr()?;
+        if self.port == 0 {
+            return Err(anyhow!("端口不能为0"));
+        }
+        if self.api_token.as_ref().is_some_and(|token| token.trim().is_empty()) {
+            return Err(anyhow!("访问令牌不能为空白"));
+        }
+        Ok(())
+    }
+
+    fn socket_addr(&self) -> Result<SocketAddr> {
+        format!("{}:{}", self.bind_address, self.port)
+            .parse()
+            .map_err(|_| anyhow!("监听地址无效: {}", self.bind_address))
+    }
+}
+
+/// HTTP服务运行状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpServerStatus {
+    pub config: HttpServerConfig,
+    pub running: bool,
+    pub address: Option<String>,
+    pub started_at: Option<String>,
+    pub requests: u64,
+    pub stream_clients: usize,      // 正在观看MJPEG画面的客户端数
+    pub socket_clients: usize,      // 已连接的结果推送WebSocket数
+    pub last_error: Option<String>, // 最近一次启动失败的原因
+}
+
+/// `/status` 返回内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceStatus {
+    pub model: ReadinessStatus,
+    pub stats: Option<ModelStats>, // 检测器正忙（如实时检测中）时为空
+    pub realtime: RealtimeStats,
+    pub server: HttpServerStatus,
+}
+
+/// `/detect/image` 返回内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpDetectionResult {
+    pub source: String,
+    pub result: DetectionResult,
+    pub alerts: Vec<Alert>,
+    pub run_id: Option<i64>,
+    pub image_data: Option<String>, // Base64编码的标注图（annotate=true 时）
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DetectQuery {
+    annotate: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct StreamQuery {
+    fps: Option<f32>,
+}
+
+/// `/ws/results` 查询参数，多个值以逗号分隔
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SocketQuery {
+    classes: Option<String>,
+    cameras: Option<String>,
+}
+
+impl SocketQuery {
+    fn into_filter(self) -> FeedFilter {
+        let split = |value: Option<String>| -> Vec<String> {
+            value
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        FeedFilter {
+            classes: split(self.classes),
+            cameras: split(self.cameras),
+        }
+    }
+}
+
+/// `/history` 查询参数（查询字符串不支持嵌套结构，逐项展开）
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct HistoryQuery {
+    from: Option<String>,
+    to: Option<String>,
+    source: Option<String>,
+    class_name: Option<String>,
+    min_confidence: Option<f32>,
+    has_detections: Option<bool>,
+    session_id: Option<i64>,
+    page: Option<u32>,
+    page_size: Option<u32>,
+}
+
+impl HistoryQuery {
+    fn into_parts(self) -> (HistoryFilter, Pagination) {
+        let defaults = Pagination::default();
+        let pagination = Pagination {
+            page: self.page.unwrap_or(defaults.page),
+            page_size: self.page_size.unwrap_or(defaults.page_size),
+        };
+        let filter = HistoryFilter {
+            from: self.from,
+            to: self.to,
+            source: self.source,
+            class_name: self.class_name,
+            min_confidence: self.min_confidence,
+            has_detections: self.has_detections,
+            session_id: self.session_id,
+        };
+        (filter, pagination)
+    }
+}
+
+struct RunningServer {
+    address: SocketAddr,
+    started_at: String,
+    shutdown: oneshot::Sender<()>,
+}
+
+/// 内嵌HTTP服务（Tauri托管状态）
+pub struct HttpServer {
+    path: PathBuf,
+    config: RwLock<HttpServerConfig>,
+    running: Mutex<Option<RunningServer>>,
+    requests: AtomicU64,
+    stream_clients: AtomicUsize,
+    socket_clients: AtomicUsize,
+    last_error: Mutex<Option<String>>,
+}
+
+fn reply<T: Serialize>(status: StatusCode, result: ApiResult<T>) -> Response {
+    (status, Json(result)).into_response()
+}
+
+fn error_reply(status: StatusCode, message: String) -> Response {
+    reply::<()>(status, ApiResult::error(message))
+}
+
+impl HttpServer {
+    /// 读取已保存的配置，文件不存在或无效时使用默认配置（不启用）
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(SERVER_FILE_NAME);
+        let config = match std::fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<HttpServerConfig>(&content) {
+                Ok(config) if config.validate().is_ok() => config,
+                Ok(_) | Err(_) => {
+                    tracing::error!("HTTP服务配置文件无效，已使用默认配置: {}", path.display());
+                    HttpServerConfig::default()
+                }
+            },
+            Err(_) => HttpServerConfig::default(),
+        };
+        Self {
+            path,
+            config: RwLock::new(config),
+            running: Mutex::new(None),
+            requests: AtomicU64::new(0),
+            stream_clients: AtomicUsize::new(0),
+            socket_clients: AtomicUsize::new(0),
+            last_error: Mutex::new(None),
+        }
+    }
+
+    pub fn config(&self) -> HttpServerConfig {
+        self.config.read().clone()
+    }
+
+    pub fn status(&self) -> HttpServerStatus {
+        let running = self.running.lock();
+        HttpServerStatus {
+            config: self.config(),
+            running: running.is_some(),
+            address: running.as_ref().map(|server| server.address.to_string()),
+            started_at: running.as_ref().map(|server| server.started_at.clone()),
+            requests: self.requests.load(Ordering::Relaxed),
+            stream_clients: self.stream_clients.load(Ordering::Relaxed),
+            socket_clients: self.socket_clients.load(Ordering::Relaxed),
+            last_error: self.last_error.lock().clone(),
+        }
+    }
+
+    fn save(&self, config: HttpServerConfig) -> Result<()> {
+        config.validate().map_err(error::invalid_input)?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&config)?)?;
+        *self.config.write() = config;
+        Ok(())
+    }
+
+    /// 按当前配置启动服务（已在运行时先停止）
+    pub async fn start(&self, app: &AppHandle) -> Result<()> {
+        self.stop();
+        let config = self.config();
+        let address = config.socket_addr()?;
+        let listener = tokio::net::TcpListener::bind(address).await.map_err(|e| {
+            let message = format!("监听 {} 失败: {}", address, e);
+            *self.last_error.lock() = Some(message.clone());
+            anyhow!(message)
+        })?;
+        let address = listener.local_addr().unwrap_or(address);
+
+        let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+        let router = router(app.clone());
+        tauri::async_runtime::spawn(async move {
+            let service = router.into_make_service_with_connect_info::<SocketAddr>();
+            let result = axum::serve(listener, service)
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_rx.await;
+                })
+                .await;
+            if let Err(e) = result {
+                tracing::error!("HTTP服务异常退出: {}", e);
+            }
+        });
+
+        tracing::info!("🌐 HTTP服务已启动: http://{}", address);
+        *self.last_error.lock() = None;
+        *self.running.lock() = Some(RunningServer {
+            address,
+            started_at: crate::storage::now_rfc3339(),
+            shutdown,
+        });
+        Ok(())
+    }
+
+    /// 停止服务，返回之前是否在运行
+    pub fn stop(&self) -> bool {
+        match self.running.lock().take() {
+            Some(server) => {
+                let _ = server.shutdown.send(());
+                tracing::info!("🌐 HTTP服务已停止: {}", server.address);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// 配置为启用时在后台启动服务
+pub fn spawn_if_enabled(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let server = app.state::<HttpServer>();
+        if server.config().enabled {
+            if let Err(e) = server.start(&app).await {
+                tracing::error!("HTTP服务启动失败: {}", e);
+            }
+        }
+    });
+}
+
+fn router(app: AppHandle) -> Router {
+    Router::new()
+        .route("/detect/image", post(detect_image))
+        .route("/status", get(service_status))
+        .route("/history", get(query_history))
+        .route("/stream.mjpeg", get(mjpeg_stream))
+        .route("/snapshot.jpg", get(snapshot))
+        .route("/ws/results", get(result_socket))
+        .route_layer(middleware::from_fn_with_state(app.clone(), authorize))
+        .route("/health", get(health))
+        .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES))
+        .with_state(app)
+}
+
+/// 统计请求数并校验访问令牌
+async fn authorize(AxumState(app): AxumState<AppHandle>, request: Request, next: Next) -> Response {
+    let server = app.state::<HttpServer>();
+    server.requests.fetch_add(1, Ordering::Relaxed);
+    if let Some(token) = server.config().api_token {
+        let provided = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .or_else(|| {
+                request
+                    .uri()
+                    .query()
+                    .and_then(|query| query.split('&').find_map(|pair| pair.strip_prefix("token=")))
+            });
+        if provided != Some(token.as_str()) {
+            return error_reply(StatusCode::UNAUTHORIZED, "访问令牌无效".to_string());
+        }
+    }
+    next.run(request).await
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
+async fn service_status(AxumState(app): AxumState<AppHandle>) -> Response {
+    // 检测器正被切换时不等待，避免状态查询被长时间阻塞
+    let stats = match app.state::<AppState>().try_read() {
+        Ok(detector) => Some(detector.get_stats().await),
+        Err(_) => None,
+    };
+    let status = ServiceStatus {
+        model: app.state::<ModelReadiness>().status(),
+        stats,
+        realtime: app.state::<RealtimePipeline>().stats(),
+        server: app.state::<HttpServer>().status(),
+    };
+    reply(StatusCode::OK, ApiResult::success(status))
+}
+
+async fn query_history(AxumState(app): AxumState<AppHandle>, Query(query): Query<HistoryQuery>) -> Response {
+    let (filter, pagination) = query.into_parts();
+    match history::query(&app.state::<Database>(), &filter, &pagination) {
+        Ok(page) => reply::<HistoryPage>(StatusCode::OK, ApiResult::success(page)),
+        Err(e) => error_reply(StatusCode::INTERNAL_SERVER_ERROR, format!("查询检测历史失败: {}", e)),
+    }
+}
+
+async fn detect_image(
+    AxumState(app): AxumState<AppHandle>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Query(query): Query<DetectQuery>,
+    mut multipart: Multipart
+) -> Response {
+    // 取字段名为 file 的上传文件，没有时取第一个文件字段
+    let mut upload = None;
+    loop {
+        match multipart.next_field().await {
+            Ok(Some(field)) => {
+                let is_file = field.name() == Some("file") || field.file_name().is_some();
+                if !is_file {
+                    continue;
+                }
+                let file_name = field.file_name().unwrap_or("upload").to_string();
+                match field.bytes().await {
+                    Ok(data) => {
+                        upload = Some((file_name, data));
+                        break;
+                    }
+                    Err(e) => return error_reply(StatusCode::BAD_REQUEST, format!("读取上传文件失败: {}", e)),
+                }
+            }
+            Ok(None) => break,
+            Err(e) => return error_reply(StatusCode::BAD_REQUEST, format!("解析上传内容失败: {}", e)),
+        }
+    }
+    let Some((file_name, data)) = upload else {
+        return error_reply(StatusCode::BAD_REQUEST, "请求中没有上传图片（字段名 file）".to_string());
+    };
+    let image = match yolo::decode::decode(&data) {
+        Ok(image) => image,
+        Err(e) => return error_reply(StatusCode::BAD_REQUEST, format!("图片格式错误: {}", e)),
+    };
+
+    let mut result = match app.state::<InferenceWorker>().detect(data.to_vec()).await {
+        Ok(result) => result,
+        Err(e) => return error_reply(StatusCode::INTERNAL_SERVER_ERROR, format!("图片处理失败: {}", e)),
+    };
+
+    let source = format!("http://{}/{}", peer.ip(), file_name);
+    result.set_frame(&source, None, None);
+    let stored = yolo_api::store_detection(&app, &source, ArtifactImage::Encoded(&data), &result);
+
+    let image_data = if query.annotate {
+        let draw_start = Instant::now();
+        let annotated = match draw_detections_on_image(&image, &result.detections) {
+            Ok(annotated) => annotated,
+            Err(e) => return error_reply(StatusCode::INTERNAL_SERVER_ERROR, format!("绘制标注图失败: {}", e)),
+        };
+        result.timings.draw_ms = DetectionTimings::since(draw_start);
+        let encode_start = Instant::now();
+        let encoded = match image_to_base64(&annotated) {
+            Ok(encoded) => encoded,
+            Err(e) => return error_reply(StatusCode::INTERNAL_SERVER_ERROR, format!("绘制标注图失败: {}", e)),
+        };
+        result.timings.encode_ms = DetectionTimings::since(encode_start);
+        Some(encoded)
+    } else {
+        None
+    };
+
+    reply(
+        StatusCode::OK,
+        ApiResult::success(HttpDetectionResult {
+            source,
+            result,
+            alerts: stored.alerts,
+            run_id: stored.run_id,
+            image_data,
+        }),
+    )
+}
+
+/// 统计正在观看画面的客户端，连接断开（流被丢弃）时减一
+struct StreamClient(AppHandle);
+
+impl StreamClient {
+    fn new(app: &AppHandle) -> Self {
+        app.state::<HttpServer>().stream_clients.fetch_add(1, Ordering::Relaxed);
+        Self(app.clone())
+    }
+}
+
+impl Drop for StreamClient {
+    fn drop(&mut self) {
+        self.0.state::<HttpServer>().stream_clients.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+struct MjpegState {
+    client: StreamClient,
+    frames: watch::Receiver<u64>,
+    interval: Duration,
+    last_sent: Option<Instant>,
+    first: bool,
+}
+
+fn mjpeg_part(jpeg: &[u8]) -> Bytes {
+    let mut part = format!(
+        "--{}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+        MJPEG_BOUNDARY,
+        jpeg.len()
+    )
+    .into_bytes();
+    part.extend_from_slice(jpeg);
+    part.extend_from_slice(b"\r\n");
+    Bytes::from(part)
+}
+
+/// 每发布一帧标注画面推送一次，超过限定帧率的帧跳过
+async fn mjpeg_stream(AxumState(app): AxumState<AppHandle>, Query(query): Query<StreamQuery>) -> Response {
+    let fps = query.fps.unwrap_or(DEFAULT_STREAM_FPS).clamp(0.5, 60.0);
+    let state = MjpegState {
+        client: StreamClient::new(&app),
+        frames: app.state::<ViewerHub>().subscribe_frames(),
+        interval: Duration::from_secs_f32(1.0 / fps),
+        last_sent: None,
+        first: true,
+    };
+    let stream = futures::stream::unfold(state, |mut state| async move {
+        loop {
+            // 首次连接时先推送已有的最近一帧
+            if !state.first {
+                state.frames.changed().await.ok()?;
+            }
+            state.first = false;
+            if let Some(last_sent) = state.last_sent {
+                let wait = state.interval.saturating_sub(last_sent.elapsed());
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait).await;
+                }
+            }
+            let jpeg = state.client.0.state::<ViewerHub>().latest_jpeg();
+            if let Some(jpeg) = jpeg {
+                state.last_sent = Some(Instant::now());
+                return Some((Ok::<_, std::io::Error>(mjpeg_part(&jpeg)), state));
+            }
+        }
+    });
+
+    let headers = [
+        (header::CONTENT_TYPE, format!("multipart/x-mixed-replace; boundary={}", MJPEG_BOUNDARY)),
+        (header::CACHE_CONTROL, "no-cache".to_string()),
+    ];
+    (headers, Body::from_stream(stream)).into_response()
+}
+
+async fn snapshot(AxumState(app): AxumState<AppHandle>) -> Response {
+    match app.state::<ViewerHub>().latest_jpeg() {
+        Some(jpeg) => ([(header::CONTENT_TYPE, "image/jpeg")], jpeg).into_response(),
+        None => error_reply(StatusCode::NOT_FOUND, "暂无检测画面".to_string()),
+    }
+}
+
+async fn result_socket(
+    AxumState(app): AxumState<AppHandle>,
+    Query(query): Query<SocketQuery>,
+    upgrade: WebSocketUpgrade
+) -> Response {
+    let filter = query.into_filter();
+    upgrade.on_upgrade(move |socket| push_results(app, socket, filter))
+}
+
+/// 向WebSocket客户端推送检测结果，直到客户端断开
+async fn push_results(app: AppHandle, mut socket: WebSocket, mut filter: FeedFilter) {
+    let mut results = app.state::<ResultFeed>().subscribe();
+    let server = app.state::<HttpServer>();
+    server.socket_clients.fetch_add(1, Ordering::Relaxed);
+    loop {
+        tokio::select! {
+            received = results.recv() => match received {
+                Ok(message) => {
+                    let Some(message) = filter.apply(&message) else {
+                        continue;
+                    };
+                    let Ok(text) = serde_json::to_string(&message) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("⚠️ WebSocket客户端处理过慢，跳过 {} 条检测结果", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str::<FeedFilter>(&text) {
+                    Ok(updated) => filter = updated,
+                    Err(e) => {
+                        let error = serde_json::json!({ "error": format!("过滤条件格式错误: {}", e) });
+                        if socket.send(Message::Text(error.to_string())).await.is_err() {
+                            break;
+                        }
+                    }
+                },
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    server.socket_clients.fetch_sub(1, Ordering::Relaxed);
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 查询HTTP服务配置与运行状态
+#[tauri::command]
+pub async fn get_http_server_status(
+    server: State<'_, HttpServer>
+) -> Result<HttpServerStatus, DetectionError> {
+    Ok(server.status())
+}
+
+/// 保存HTTP服务配置，并按是否启用重新启动或停止服务
+#[tauri::command]
+pub async fn set_http_server_config(
+    app: AppHandle,
+    server: State<'_, HttpServer>,
+    config: HttpServerConfig
+) -> Result<HttpServerStatus, DetectionError> {
+    if let Err(e) = server.save(config) {
+        return Err(DetectionError::from(e).context("保存HTTP服务配置失败"));
+    }
+    if server.config().enabled {
+        if let Err(e) = server.start(&app).await {
+            return Err(DetectionError::from(e).context("HTTP服务启动失败"));
+        }
+    } else {
+        server.stop();
+    }
+    Ok(server.status())
+}
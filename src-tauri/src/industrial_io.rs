@@ -0,0 +1,523 @@
+/*!
+工业I/O输出模块
+告警规则触发时向PLC写入信号（如"3号线检测到缺陷"）：
+- Modbus TCP：写单个线圈（布尔量）或保持寄存器（整数）
+- OPC UA：写节点值（布尔或Int32，需 `--features opcua-support` 编译）
+
+可设置保持时长：写入触发值后保持指定时间再写回复位值（脉冲信号），保持期间再次触发只延长保持时间。
+每个输出记录最近一次写入/连接检查的结果，供状态页展示连接健康状况
+配置保存在应用数据目录下的JSON文件中
+*/
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::alerts::Alert;
+use crate::error::{self, DetectionError};
+
+/// 工业I/O配置文件名（位于应用数据目录）
+pub const IO_FILE_NAME: &str = "industrial_io.json";
+
+/// 连接与读写超时
+const IO_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Modbus功能码
+const MODBUS_WRITE_COIL: u8 = 0x05;
+const MODBUS_WRITE_REGISTER: u8 = 0x06;
+
+#[cfg(not(feature = "opcua-support"))]
+const OPCUA_DISABLED: &str = "当前版本未启用OPC UA支持，请使用 `--features opcua-support` 重新编译";
+
+/// Modbus事务号
+static MODBUS_TRANSACTION: AtomicU16 = AtomicU16::new(1);
+
+fn default_modbus_port() -> u16 {
+    502
+}
+
+fn default_unit_id() -> u8 {
+    1
+}
+
+/// OPC UA节点的数据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpcUaValueType {
+    Boolean,
+    Int32,
+}
+
+/// 写入目标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IoTarget {
+    ModbusCoil {
+        host: String,
+        #[serde(default = "default_modbus_port")]
+        port: u16,
+        #[serde(default = "default_unit_id")]
+        unit_id: u8,
+        address: u16,
+    },
+    ModbusRegister {
+        host: String,
+        #[serde(default = "default_modbus_port")]
+        port: u16,
+        #[serde(default = "default_unit_id")]
+        unit_id: u8,
+        address: u16,
+    },
+    OpcUa {
+        endpoint: String, // 如 opc.tcp://192.168.1.10:4840
+        node_id: String,  // 如 ns=2;s=Line3.DefectDetected
+        value_type: OpcUaValueType,
+    },
+}
+
+impl IoTarget {
+    fn describe(&self) -> String {
+        match self {
+            IoTarget::ModbusCoil { host, port, unit_id, address } => {
+                format!("modbus://{}:{}/{} 线圈 {}", host, port, unit_id, address)
+            }
+            IoTarget::ModbusRegister { host, port, unit_id, address } => {
+                format!("modbus://{}:{}/{} 寄存器 {}", host, port, unit_id, address)
+            }
+            IoTarget::OpcUa { endpoint, node_id, .. } => format!("{} {}", endpoint, node_id),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_value() -> i64 {
+    1
+}
+
+/// 一个I/O输出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IoOutput {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub rule_ids: Vec<String>, // 只响应这些规则触发的告警，为空时不限
+    #[serde(default = "default_value")]
+    pub value: i64, // 触发时写入的值（布尔量非0为true）
+    #[serde(default)]
+    pub reset_value: i64, // 保持结束后写回的值
+    #[serde(default)]
+    pub hold_ms: u64, // 保持时长，0表示不自动复位
+    #[serde(flatten)]
+    pub target: IoTarget,
+}
+
+impl IoOutput {
+    fn accepts(&self, alert: &Alert) -> bool {
+        self.enabled
+            && (self.rule_ids.is_empty() || alert.rule_id.as_ref().is_some_and(|id| self.rule_ids.contains(id)))
+    }
+}
+
+/// 工业I/O配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IoSettings {
+    pub outputs: Vec<IoOutput>,
+}
+
+fn validate(settings: &IoSettings) -> Result<()> {
+    let mut ids = Vec::new();
+    for output in &settings.outputs {
+        if output.id.trim().is_empty() {
+            return Err(anyhow!("输出ID不能为空"));
+        }
+        if ids.contains(&output.id) {
+            return Err(anyhow!("输出ID重复: {}", output.id));
+        }
+        ids.push(output.id.clone());
+        match &output.target {
+            IoTarget::ModbusRegister { .. } => {
+                for value in [output.value, output.reset_value] {
+                    if !(0..=u16::MAX as i64).contains(&value) {
+                        return Err(anyhow!("输出 {} 的寄存器值须在 0-65535 之间: {}", output.id, value));
+                    }
+                }
+            }
+            IoTarget::OpcUa { endpoint, value_type, .. } => {
+                if !endpoint.starts_with("opc.tcp://") {
+                    return Err(anyhow!("输出 {} 的OPC UA地址须以 opc.tcp:// 开头: {}", output.id, endpoint));
+                }
+                if *value_type == OpcUaValueType::Int32 {
+                    for value in [output.value, output.reset_value] {
+                        if i32::try_from(value).is_err() {
+                            return Err(anyhow!("输出 {} 的值超出Int32范围: {}", output.id, value));
+                        }
+                    }
+                }
+            }
+            IoTarget::ModbusCoil { .. } => {}
+        }
+    }
+    Ok(())
+}
+
+/// 输出的连接健康状况
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IoHealth {
+    pub connected: Option<bool>, // 最近一次写入或检查是否成功，尚未连接时为空
+    pub writes: u64,
+    pub failures: u64,
+    pub holding: bool, // 处于保持期（触发值尚未复位）
+    pub last_value: Option<i64>,
+    pub last_write_at: Option<String>,
+    pub last_checked_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// 工业I/O状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IoStatus {
+    pub settings: IoSettings,
+    pub health: HashMap<String, IoHealth>, // 按输出ID
+}
+
+#[derive(Default)]
+struct OutputRuntime {
+    health: IoHealth,
+    holding_until: Option<Instant>,
+}
+
+/// 连接Modbus从站
+async fn modbus_connect(host: &str, port: u16) -> Result<TcpStream> {
+    tokio::time::timeout(IO_TIMEOUT, TcpStream::connect((host, port)))
+        .await
+        .map_err(|_| anyhow!("连接 {}:{} 超时", host, port))?
+        .map_err(|e| anyhow!("连接 {}:{} 失败: {}", host, port, e))
+}
+
+/// Modbus TCP单个写请求（写线圈/写寄存器），从站原样返回请求表示成功
+async fn modbus_write(host: &str, port: u16, unit_id: u8, function: u8, address: u16, value: u16) -> Result<()> {
+    let mut stream = modbus_connect(host, port).await?;
+    let transaction = MODBUS_TRANSACTION.fetch_add(1, Ordering::Relaxed);
+
+    // MBAP头：事务号、协议号(0)、后续长度、单元号；PDU：功能码、地址、值
+    let mut frame = Vec::with_capacity(12);
+    frame.extend_from_slice(&transaction.to_be_bytes());
+    frame.extend_from_slice(&0u16.to_be_bytes());
+    frame.extend_from_slice(&6u16.to_be_bytes());
+    frame.push(unit_id);
+    frame.push(function);
+    frame.extend_from_slice(&address.to_be_bytes());
+    frame.extend_from_slice(&value.to_be_bytes());
+
+    let exchange = async {
+        stream.write_all(&frame).await?;
+        let mut header = [0u8; 7];
+        stream.read_exact(&mut header).await?;
+        let length = u16::from_be_bytes([header[4], header[5]]) as usize;
+        let mut pdu = vec![0u8; length.saturating_sub(1)];
+        stream.read_exact(&mut pdu).await?;
+        Ok::<_, std::io::Error>((header, pdu))
+    };
+    let (header, pdu) = tokio::time::timeout(IO_TIMEOUT, exchange)
+        .await
+        .map_err(|_| anyhow!("等待Modbus响应超时"))?
+        .map_err(|e| anyhow!("Modbus通信失败: {}", e))?;
+
+    if u16::from_be_bytes([header[0], header[1]]) != transaction {
+        return Err(anyhow!("Modbus响应事务号不匹配"));
+    }
+    match pdu.as_slice() {
+        [code, exception, ..] if *code == function | 0x80 => Err(anyhow!("Modbus从站返回异常码 {}", exception)),
+        [code, ..] if *code == function => Ok(()),
+        _ => Err(anyhow!("Modbus响应无效")),
+    }
+}
+
+#[cfg(feature = "opcua-support")]
+mod opcua_backend {
+    use std::str::FromStr;
+
+    use anyhow::{anyhow, Result};
+    use opcua::client::prelude::*;
+
+    use super::OpcUaValueType;
+
+    fn connect(endpoint: &str) -> Result<(Client, std::sync::Arc<opcua::sync::RwLock<Session>>)> {
+        let mut client = ClientBuilder::new()
+            .application_name("YOLO Detection System")
+            .application_uri("urn:yolo-detection-system")
+            .trust_server_certs(true)
+            .session_retry_limit(0)
+            .client()
+            .ok_or_else(|| anyhow!("创建OPC UA客户端失败"))?;
+        let session = client
+            .connect_to_endpoint(
+                (endpoint, SecurityPolicy::None.to_str(), MessageSecurityMode::None, UserTokenPolicy::anonymous()),
+                IdentityToken::Anonymous,
+            )
+            .map_err(|e| anyhow!("连接OPC UA服务器失败: {}", e))?;
+        Ok((client, session))
+    }
+
+    /// 写节点值（阻塞调用）
+    pub fn write(endpoint: &str, node_id: &str, value_type: OpcUaValueType, value: i64) -> Result<()> {
+        let node_id = NodeId::from_str(node_id).map_err(|_| anyhow!("OPC UA节点ID无效: {}", node_id))?;
+        let variant = match value_type {
+            OpcUaValueType::Boolean => Variant::Boolean(value != 0),
+            OpcUaValueType::Int32 => Variant::Int32(value as i32),
+        };
+        let (_client, session) = connect(endpoint)?;
+        let session = session.read();
+        let results = session
+            .write(&[WriteValue {
+                node_id,
+                attribute_id: AttributeId::Value as u32,
+                index_range: UAString::null(),
+                value: DataValue::value_only(variant),
+            }])
+            .map_err(|e| anyhow!("写入OPC UA节点失败: {}", e));
+        session.disconnect();
+        match results?.first() {
+            Some(status) if !status.is_good() => Err(anyhow!("写入OPC UA节点失败: {}", status)),
+            _ => Ok(()),
+        }
+    }
+
+    /// 检查能否建立会话（阻塞调用）
+    pub fn check(endpoint: &str) -> Result<()> {
+        let (_client, session) = connect(endpoint)?;
+        session.read().disconnect();
+        Ok(())
+    }
+}
+
+async fn write_target(target: &IoTarget, value: i64) -> Result<()> {
+    match target {
+        IoTarget::ModbusCoil { host, port, unit_id, address } => {
+            let coil = if value != 0 { 0xFF00 } else { 0x0000 };
+            modbus_write(host, *port, *unit_id, MODBUS_WRITE_COIL, *address, coil).await
+        }
+        IoTarget::ModbusRegister { host, port, unit_id, address } => {
+            modbus_write(host, *port, *unit_id, MODBUS_WRITE_REGISTER, *address, value as u16).await
+        }
+        #[cfg(feature = "opcua-support")]
+        IoTarget::OpcUa { endpoint, node_id, value_type } => {
+            let (endpoint, node_id, value_type) = (endpoint.clone(), node_id.clone(), *value_type);
+            tokio::task::spawn_blocking(move || opcua_backend::write(&endpoint, &node_id, value_type, value))
+                .await
+                .map_err(|e| anyhow!("OPC UA写入任务异常: {}", e))?
+        }
+        #[cfg(not(feature = "opcua-support"))]
+        IoTarget::OpcUa { .. } => Err(anyhow!(OPCUA_DISABLED)),
+    }
+}
+
+async fn check_target(target: &IoTarget) -> Result<()> {
+    match target {
+        IoTarget::ModbusCoil { host, port, .. } | IoTarget::ModbusRegister { host, port, .. } => {
+            modbus_connect(host, *port).await.map(|_| ())
+        }
+        #[cfg(feature = "opcua-support")]
+        IoTarget::OpcUa { endpoint, .. } => {
+            let endpoint = endpoint.clone();
+            tokio::task::spawn_blocking(move || opcua_backend::check(&endpoint))
+                .await
+                .map_err(|e| anyhow!("OPC UA连接检查任务异常: {}", e))?
+        }
+        #[cfg(not(feature = "opcua-support"))]
+        IoTarget::OpcUa { .. } => Err(anyhow!(OPCUA_DISABLED)),
+    }
+}
+
+/// 工业I/O输出（Tauri托管状态）
+pub struct IndustrialIo {
+    path: PathBuf,
+    settings: RwLock<IoSettings>,
+    runtime: Mutex<HashMap<String, OutputRuntime>>,
+}
+
+impl IndustrialIo {
+    /// 读取已保存的配置，文件不存在或无效时不输出
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(IO_FILE_NAME);
+        let settings = match std::fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<IoSettings>(&content) {
+                Ok(settings) if validate(&settings).is_ok() => settings,
+                Ok(_) | Err(_) => {
+                    tracing::error!("工业I/O配置文件无效，已停用输出: {}", path.display());
+                    IoSettings::default()
+                }
+            },
+            Err(_) => IoSettings::default(),
+        };
+        Self {
+            path,
+            settings: RwLock::new(settings),
+            runtime: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn status(&self) -> IoStatus {
+        let runtime = self.runtime.lock();
+        IoStatus {
+            settings: self.settings.read().clone(),
+            health: runtime.iter().map(|(id, output)| (id.clone(), output.health.clone())).collect(),
+        }
+    }
+
+    fn save(&self, settings: IoSettings) -> Result<()> {
+        validate(&settings).map_err(error::invalid_input)?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&settings)?)?;
+        self.runtime.lock().retain(|id, _| settings.outputs.iter().any(|output| output.id == *id));
+        *self.settings.write() = settings;
+        Ok(())
+    }
+
+    fn record_write(&self, output: &IoOutput, value: i64, result: &Result<()>) {
+        let mut runtime = self.runtime.lock();
+        let health = &mut runtime.entry(output.id.clone()).or_default().health;
+        let now = crate::storage::now_rfc3339();
+        health.connected = Some(result.is_ok());
+        health.last_checked_at = Some(now.clone());
+        match result {
+            Ok(()) => {
+                health.writes += 1;
+                health.last_value = Some(value);
+                health.last_write_at = Some(now);
+            }
+            Err(e) => {
+                tracing::error!("工业I/O输出 {} ({}) 写入失败: {}", output.id, output.target.describe(), e);
+                health.failures += 1;
+                health.last_error = Some(e.to_string());
+            }
+        }
+    }
+
+    async fn write(&self, output: &IoOutput, value: i64) -> Result<()> {
+        let result = write_target(&output.target, value).await;
+        self.record_write(output, value, &result);
+        result
+    }
+
+    /// 告警触发对应的输出：写入触发值，设置了保持时长时到期后写回复位值
+    pub fn trigger(&self, app: &AppHandle, alerts: &[Alert]) {
+        let settings = self.settings.read().clone();
+        for output in settings.outputs.into_iter().filter(|output| alerts.iter().any(|alert| output.accepts(alert))) {
+            let hold = Duration::from_millis(output.hold_ms);
+            {
+                let mut runtime = self.runtime.lock();
+                let state = runtime.entry(output.id.clone()).or_default();
+                // 保持期内再次触发只延长保持时间
+                if state.holding_until.is_some_and(|until| until > Instant::now()) {
+                    state.holding_until = Some(Instant::now() + hold);
+                    continue;
+                }
+                if output.hold_ms > 0 {
+                    state.holding_until = Some(Instant::now() + hold);
+                    state.health.holding = true;
+                }
+            }
+
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let io = app.state::<IndustrialIo>();
+                if io.write(&output, output.value).await.is_err() || output.hold_ms == 0 {
+                    let mut runtime = io.runtime.lock();
+                    if let Some(state) = runtime.get_mut(&output.id) {
+                        state.holding_until = None;
+                        state.health.holding = false;
+                    }
+                    return;
+                }
+                loop {
+                    let until = io.runtime.lock().get(&output.id).and_then(|state| state.holding_until);
+                    match until {
+                        Some(until) if until > Instant::now() => tokio::time::sleep(until - Instant::now()).await,
+                        _ => break,
+                    }
+                }
+                let _ = io.write(&output, output.reset_value).await;
+                if let Some(state) = io.runtime.lock().get_mut(&output.id) {
+                    state.holding_until = None;
+                    state.health.holding = false;
+                }
+            });
+        }
+    }
+
+    /// 逐个检查输出目标能否连接
+    pub async fn check_connections(&self) {
+        let outputs = self.settings.read().outputs.clone();
+        for output in outputs.iter().filter(|output| output.enabled) {
+            let result = check_target(&output.target).await;
+            let mut runtime = self.runtime.lock();
+            let health = &mut runtime.entry(output.id.clone()).or_default().health;
+            health.connected = Some(result.is_ok());
+            health.last_checked_at = Some(crate::storage::now_rfc3339());
+            if let Err(e) = result {
+                health.last_error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 查询工业I/O配置与各输出的连接健康状况，`check` 为 true 时先逐个检查连接
+#[tauri::command]
+pub async fn get_industrial_io_status(
+    io: State<'_, IndustrialIo>,
+    check: Option<bool>
+) -> Result<IoStatus, DetectionError> {
+    if check.unwrap_or(false) {
+        io.check_connections().await;
+    }
+    Ok(io.status())
+}
+
+/// 保存工业I/O配置（整体替换）
+#[tauri::command]
+pub async fn set_industrial_io_config(
+    io: State<'_, IndustrialIo>,
+    settings: IoSettings
+) -> Result<IoStatus, DetectionError> {
+    match io.save(settings) {
+        Ok(()) => Ok(io.status()),
+        Err(e) => Err(DetectionError::from(e).context("保存工业I/O配置失败")),
+    }
+}
+
+/// 手动向指定输出写入一个值（调试接线用，默认写入触发值）
+#[tauri::command]
+pub async fn write_io_output(
+    io: State<'_, IndustrialIo>,
+    output_id: String,
+    value: Option<i64>
+) -> Result<IoStatus, DetectionError> {
+    let output = io.settings.read().outputs.iter().find(|output| output.id == output_id).cloned();
+    let Some(output) = output else {
+        return Err(DetectionError::NotFound(format!("输出不存在: {}", output_id)));
+    };
+    match io.write(&output, value.unwrap_or(output.value)).await {
+        Ok(()) => Ok(io.status()),
+        Err(e) => Err(DetectionError::from(e).context("写入失败")),
+    }
+}
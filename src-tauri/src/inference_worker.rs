@@ -0,0 +1,138 @@
+/*!
+推理工作线程
+检测在独立的线程上执行，调用方通过通道提交图像并等待结果，推理不再占用Tauri命令的异步线程。
+推理期间只持有检测器的读锁，调整置信度阈值、查询状态等命令无需等待大图处理完成；
+加载模型、切换设备等需要写锁的操作在当前推理完成后进行。
+同时处理的图片数由信号量限制（见 `threading` 的 parallel_images）；模型支持批维度时，
+排队中的图片合并为一批，由检测器拼接为一次推理。测试时增强的请求本身需要多次推理，不参与合并。
+标定了像素/毫米比例时，返回的检测框附带物理尺寸
+*/
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use tokio::sync::{mpsc, oneshot, Semaphore};
+
+use crate::detection_config;
+use crate::yolo::DetectionResult;
+use crate::AppState;
+
+/// 排队等待推理的请求上限，队列满时提交方等待
+const QUEUE_CAPACITY: usize = 16;
+
+/// 一次推理请求
+struct InferenceJob {
+    image_data: Vec<u8>,
+    tta: bool,
+    reply: oneshot::Sender<Result<DetectionResult>>,
+}
+
+/// 推理工作线程（Tauri托管状态）
+pub struct InferenceWorker {
+    sender: mpsc::Sender<InferenceJob>,
+    parallelism: usize,
+    max_batch: Arc<AtomicUsize>, // 当前模型单次推理可合并的图片数
+}
+
+impl InferenceWorker {
+    /// 启动工作线程，最多同时处理 parallelism 批请求
+    pub fn spawn(detector: AppState, parallelism: usize) -> Self {
+        let parallelism = parallelism.max(1);
+        let (sender, mut receiver) = mpsc::channel::<InferenceJob>(QUEUE_CAPACITY);
+        let max_batch = Arc::new(AtomicUsize::new(1));
+        let batch_limit = max_batch.clone();
+        let spawned = std::thread::Builder::new()
+            .name("inference".to_string())
+            .spawn(move || {
+                // 推理使用 block_in_place，需要多线程运行时
+                let runtime = match tokio::runtime::Builder::new_multi_thread()
+                    .worker_threads(parallelism)
+                    .thread_name("inference-job")
+                    .enable_all()
+                    .build()
+                {
+                    Ok(runtime) => runtime,
+                    Err(e) => {
+                        tracing::error!("创建推理线程运行时失败: {}", e);
+                        return;
+                    }
+                };
+                runtime.block_on(async move {
+                    let permits = Arc::new(Semaphore::new(parallelism));
+                    while let Some(job) = receiver.recv().await {
+                        let Ok(permit) = permits.clone().acquire_owned().await else {
+                            break;
+                        };
+                        // 等待空闲期间到达的请求与本次合并（不超过模型的批大小）
+                        let limit = detector.read().await.max_batch_size().max(1);
+                        batch_limit.store(limit, Ordering::Relaxed);
+                        let mut jobs = vec![job];
+                        while jobs.len() < limit {
+                            match receiver.try_recv() {
+                                Ok(job) => jobs.push(job),
+                                Err(_) => break,
+                            }
+                        }
+                        let detector = detector.clone();
+                        tokio::spawn(async move {
+                            let _permit = permit;
+                            run_jobs(&detector, jobs).await;
+                        });
+                    }
+                });
+            });
+        if let Err(e) = spawned {
+            tracing::error!("启动推理线程失败: {}", e);
+        }
+        Self { sender, parallelism, max_batch }
+    }
+
+    /// 能被同时处理的图片数（并发批数 × 当前模型的批大小），批量任务据此决定提交的并发数
+    pub fn capacity(&self) -> usize {
+        self.parallelism * self.max_batch.load(Ordering::Relaxed).max(1)
+    }
+
+    /// 提交一张编码后的图像并等待检测结果
+    pub async fn detect(&self, image_data: Vec<u8>) -> Result<DetectionResult> {
+        self.detect_with(image_data, false).await
+    }
+
+    /// 提交一张编码后的图像，tta 为 true 时使用测试时增强
+    pub async fn detect_with(&self, image_data: Vec<u8>, tta: bool) -> Result<DetectionResult> {
+        let (reply, result) = oneshot::channel();
+        self.sender
+            .send(InferenceJob { image_data, tta, reply })
+            .await
+            .map_err(|_| anyhow!("推理线程已停止"))?;
+        let mut result = result.await.map_err(|_| anyhow!("推理线程已停止"))??;
+        result.apply_scale(detection_config::px_per_mm());
+        Ok(result)
+    }
+}
+
+/// 执行一批请求并逐个回复；单张与测试时增强的请求不经过批量路径
+async fn run_jobs(detector: &AppState, jobs: Vec<InferenceJob>) {
+    let detector = detector.read().await;
+    let (augmented, mut jobs): (Vec<_>, Vec<_>) = jobs.into_iter().partition(|job| job.tta);
+    for job in augmented {
+        let result = detector.detect_image_tta(&job.image_data).await;
+        // 调用方已放弃等待时丢弃结果
+        let _ = job.reply.send(result);
+    }
+    if jobs.len() <= 1 {
+        if let Some(job) = jobs.pop() {
+            let result = detector.detect_image(&job.image_data).await;
+            let _ = job.reply.send(result);
+        }
+        return;
+    }
+    let (images, replies): (Vec<Vec<u8>>, Vec<_>) = jobs
+        .into_iter()
+        .map(|job| (job.image_data, job.reply))
+        .unzip();
+    let results = detector.detect_batch(&images).await;
+    for (reply, result) in replies.into_iter().zip(results) {
+        let _ = reply.send(result);
+    }
+}
@@ -0,0 +1,153 @@
+/*!
+检测标签文字渲染模块
+在标注图检测框上绘制“类别: 置信度%”标签，字体需包含中文字形，按以下顺序查找：
+环境变量 YOLO_LABEL_FONT_PATH 指定的字体文件、应用资源目录 resources/fonts 下随包分发的字体、系统中文字体；
+均不可用时只绘制标签底色
+*/
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use ab_glyph::{Font, FontArc, PxScale};
+use image::{Rgb, RgbImage};
+use imageproc::drawing::{draw_filled_rect_mut, draw_text_mut, text_size};
+use imageproc::rect::Rect;
+
+/// 指定标签字体文件的环境变量
+pub const FONT_PATH_ENV: &str = "YOLO_LABEL_FONT_PATH";
+
+/// 随包分发的字体目录（相对应用资源目录）
+const BUNDLED_FONTS_DIR: &str = "resources/fonts";
+
+/// 标签字号（像素）
+const LABEL_SCALE: f32 = 18.0;
+
+/// 标签文字四周的留白（像素）
+const LABEL_PADDING: u32 = 3;
+
+/// 常见的系统中文字体位置
+const SYSTEM_FONTS: &[&str] = &[
+    // Windows
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\msyh.ttf",
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "C:\\Windows\\Fonts\\simsun.ttc",
+    // macOS
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/STHeiti Medium.ttc",
+    "/Library/Fonts/Arial Unicode.ttf",
+    // Linux
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/wqy-microhei/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+];
+
+static FONT: OnceLock<Option<FontArc>> = OnceLock::new();
+
+fn load_font(path: &Path) -> Option<FontArc> {
+    let data = std::fs::read(path).ok()?;
+    FontArc::try_from_vec(data).ok()
+}
+
+/// 字体是否包含中文字形（类别名称为中文）
+fn has_cjk(font: &FontArc) -> bool {
+    font.glyph_id('异').0 != 0
+}
+
+/// 随包字体目录下的字体文件，其后为系统字体
+fn font_candidates(resource_dir: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = resource_dir {
+        dirs.push(dir.join(BUNDLED_FONTS_DIR));
+    }
+    // 开发环境直接运行时资源未复制到资源目录
+    dirs.push(Path::new(env!("CARGO_MANIFEST_DIR")).join(BUNDLED_FONTS_DIR));
+
+    let mut bundled: Vec<PathBuf> = dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "ttf" | "otf" | "ttc"))
+        })
+        .collect();
+    bundled.sort();
+    bundled.extend(SYSTEM_FONTS.iter().map(PathBuf::from));
+    bundled
+}
+
+fn find_font(resource_dir: Option<&Path>) -> Option<FontArc> {
+    if let Ok(path) = std::env::var(FONT_PATH_ENV) {
+        match load_font(Path::new(&path)) {
+            Some(font) => {
+                tracing::info!("🔤 标签字体: {}", path);
+                return Some(font);
+            }
+            None => tracing::error!("标签字体加载失败: {}", path),
+        }
+    }
+    for path in font_candidates(resource_dir) {
+        if let Some(font) = load_font(&path).filter(has_cjk) {
+            tracing::info!("🔤 标签字体: {}", path.display());
+            return Some(font);
+        }
+    }
+    tracing::warn!("⚠️  未找到中文字体，检测标签只绘制底色（可通过 {} 指定字体文件）", FONT_PATH_ENV);
+    None
+}
+
+/// 查找并加载标签字体（启动时调用；未调用时首次绘制标签时按默认位置查找）
+pub fn init(resource_dir: Option<&Path>) {
+    FONT.get_or_init(|| find_font(resource_dir));
+}
+
+fn font() -> Option<&'static FontArc> {
+    FONT.get_or_init(|| find_font(None)).as_ref()
+}
+
+/// 深色底配白字，浅色底配黑字
+fn text_color(background: Rgb<u8>) -> Rgb<u8> {
+    let [r, g, b] = background.0;
+    let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    if luma > 150.0 {
+        Rgb([0, 0, 0])
+    } else {
+        Rgb([255, 255, 255])
+    }
+}
+
+/// 在检测框左上角绘制标签，框上方空间不足时画在框内
+pub fn draw_label(image: &mut RgbImage, box_x: i32, box_y: i32, text: &str, background: Rgb<u8>) {
+    let scale = PxScale::from(LABEL_SCALE);
+    let font = font();
+    let text_width = match font {
+        Some(font) => text_size(scale, font, text).0,
+        None => text.chars().count() as u32 * (LABEL_SCALE as u32 / 2), // 估算文本宽度
+    };
+    let label_width = text_width + 2 * LABEL_PADDING;
+    let label_height = LABEL_SCALE as u32 + 2 * LABEL_PADDING;
+    let top = if box_y >= label_height as i32 { box_y - label_height as i32 } else { box_y };
+
+    let bounds = Rect::at(0, 0).of_size(image.width(), image.height());
+    if let Some(rect) = Rect::at(box_x, top).of_size(label_width, label_height).intersect(bounds) {
+        draw_filled_rect_mut(image, rect, background);
+    }
+    if let Some(font) = font {
+        draw_text_mut(
+            image,
+            text_color(background),
+            box_x + LABEL_PADDING as i32,
+            top + LABEL_PADDING as i32,
+            scale,
+            font,
+            text,
+        );
+    }
+}
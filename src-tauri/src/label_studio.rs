@@ -0,0 +1,346 @@
+/*!
+Label Studio 集成模块
+导出预测结果作为Label Studio预标注任务，导入完成的标注作为真值
+*/
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+use crate::corrections;
+use crate::error::DetectionError;
+use crate::ground_truth::{self, GroundTruthBox, GroundTruthImage};
+use crate::history;
+use crate::storage::Database;
+use crate::AppState;
+
+/// 标注配置中的控件名称（与Label Studio标注界面配置保持一致）
+const FROM_NAME: &str = "label";
+const TO_NAME: &str = "image";
+
+/// 真值来源标识
+pub const GROUND_TRUTH_SOURCE: &str = "label_studio";
+
+/// 导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelStudioExportSummary {
+    pub output_path: String,
+    pub task_count: u32,
+    pub prediction_count: u32,
+    pub skipped: Vec<String>,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelStudioImportSummary {
+    pub image_count: u32,
+    pub box_count: u32,
+    pub skipped: Vec<String>,
+}
+
+/// 解码URL百分号编码
+fn percent_decode(value: &str) -> String {
+    let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                (Some(hi), Some(lo)) => {
+                    decoded.push(hi << 4 | lo);
+                    i += 3;
+                    continue;
+                }
+                _ => decoded.push(b'%'),
+            },
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// 编码路径用于Label Studio本地文件URL
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// 将Label Studio任务中的图片引用解析为本地路径
+fn resolve_image_path(image_ref: &str, image_root: Option<&Path>) -> PathBuf {
+    // 本地文件存储: /data/local-files/?d=relative/path.jpg
+    let raw = match image_ref.split_once("?d=") {
+        Some((_, path)) => percent_decode(path),
+        None => image_ref.to_string(),
+    };
+
+    let path = PathBuf::from(&raw);
+    match image_root {
+        Some(root) if path.is_relative() => root.join(path),
+        _ => path,
+    }
+}
+
+/// 生成Label Studio矩形框结果（百分比坐标）
+fn rectangle_result(id: &str, class_name: &str, bbox: [f32; 4], width: u32, height: u32, score: Option<f32>) -> Value {
+    let [x, y, w, h] = bbox;
+    let (img_w, img_h) = (width.max(1) as f32, height.max(1) as f32);
+    let mut result = json!({
+        "id": id,
+        "from_name": FROM_NAME,
+        "to_name": TO_NAME,
+        "type": "rectanglelabels",
+        "original_width": width,
+        "original_height": height,
+        "image_rotation": 0,
+        "value": {
+            "x": (x / img_w * 100.0).clamp(0.0, 100.0),
+            "y": (y / img_h * 100.0).clamp(0.0, 100.0),
+            "width": (w / img_w * 100.0).clamp(0.0, 100.0),
+            "height": (h / img_h * 100.0).clamp(0.0, 100.0),
+            "rotation": 0,
+            "rectanglelabels": [class_name],
+        },
+    });
+    if let Some(score) = score {
+        result["score"] = json!(score);
+    }
+    result
+}
+
+/// 导出预测结果为Label Studio任务（已应用操作员修正，误报不导出）
+pub fn export_tasks(
+    db: &Database,
+    output_path: &Path,
+    run_ids: Option<Vec<i64>>,
+    image_url_prefix: Option<&str>,
+    model_version: &str,
+) -> Result<LabelStudioExportSummary> {
+    let run_ids = match run_ids {
+        Some(ids) => ids,
+        None => history::all_run_ids(db)?,
+    };
+
+    let mut tasks = Vec::new();
+    let mut skipped = Vec::new();
+    let mut prediction_count = 0u32;
+
+    for run_id in run_ids {
+        let corrected = match corrections::get_corrected(db, run_id)? {
+            Some(run) => run,
+            None => {
+                skipped.push(format!("运行 #{} 不存在", run_id));
+                continue;
+            }
+        };
+        let run = &corrected.run;
+
+        let image_ref = match image_url_prefix {
+            Some(prefix) => format!("{}{}", prefix, percent_encode(&run.source)),
+            None => run.source.clone(),
+        };
+
+        let results: Vec<Value> = corrected
+            .detections
+            .iter()
+            .filter(|d| !d.false_positive)
+            .enumerate()
+            .map(|(i, d)| {
+                rectangle_result(
+                    &format!("run{}_{}", run.id, i),
+                    &d.class_name,
+                    d.bbox,
+                    run.image_width,
+                    run.image_height,
+                    Some(d.confidence),
+                )
+            })
+            .collect();
+        prediction_count += results.len() as u32;
+
+        let score = corrected
+            .detections
+            .iter()
+            .filter(|d| !d.false_positive)
+            .map(|d| d.confidence)
+            .fold(0.0f32, f32::max);
+
+        tasks.push(json!({
+            "data": {
+                "image": image_ref,
+                "run_id": run.id,
+                "created_at": run.created_at,
+            },
+            "predictions": [{
+                "model_version": model_version,
+                "score": score,
+                "result": results,
+            }],
+        }));
+    }
+
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(output_path, serde_json::to_string_pretty(&tasks)?)?;
+
+    tracing::info!("📤 已导出 {} 个Label Studio任务: {}", tasks.len(), output_path.display());
+
+    Ok(LabelStudioExportSummary {
+        output_path: output_path.to_string_lossy().to_string(),
+        task_count: tasks.len() as u32,
+        prediction_count,
+        skipped,
+    })
+}
+
+/// 解析单个任务的最新有效标注
+fn parse_task(task: &Value, image_root: Option<&Path>) -> Result<GroundTruthImage> {
+    let image_ref = task["data"]["image"]
+        .as_str()
+        .ok_or_else(|| anyhow!("任务缺少 data.image 字段"))?;
+    let image_path = resolve_image_path(image_ref, image_root);
+
+    let annotation = task["annotations"]
+        .as_array()
+        .and_then(|annotations| {
+            annotations
+                .iter()
+                .filter(|a| !a["was_cancelled"].as_bool().unwrap_or(false))
+                .max_by_key(|a| a["updated_at"].as_str().unwrap_or_default().to_string())
+        })
+        .ok_or_else(|| anyhow!("任务没有已完成的标注: {}", image_ref))?;
+
+    let mut image_width = 0u32;
+    let mut image_height = 0u32;
+    let mut boxes = Vec::new();
+
+    for result in annotation["result"].as_array().into_iter().flatten() {
+        if result["type"].as_str() != Some("rectanglelabels") {
+            continue;
+        }
+        let width = result["original_width"].as_u64().unwrap_or(0) as u32;
+        let height = result["original_height"].as_u64().unwrap_or(0) as u32;
+        if width == 0 || height == 0 {
+            continue;
+        }
+        image_width = width;
+        image_height = height;
+
+        let value = &result["value"];
+        let label = match value["rectanglelabels"].as_array().and_then(|l| l.first()).and_then(|l| l.as_str()) {
+            Some(label) => label.to_string(),
+            None => continue,
+        };
+        let pct = |key: &str| value[key].as_f64().unwrap_or(0.0) as f32 / 100.0;
+
+        boxes.push(GroundTruthBox {
+            class_name: label,
+            bbox: [
+                pct("x") * width as f32,
+                pct("y") * height as f32,
+                pct("width") * width as f32,
+                pct("height") * height as f32,
+            ],
+        });
+    }
+
+    // 无标注框的图片（负样本）需要从图片本身读取尺寸
+    if image_width == 0 || image_height == 0 {
+        let (w, h) = image::image_dimensions(&image_path)
+            .map_err(|e| anyhow!("无法读取图片尺寸 {}: {}", image_path.display(), e))?;
+        image_width = w;
+        image_height = h;
+    }
+
+    Ok(GroundTruthImage {
+        image_path: image_path.to_string_lossy().to_string(),
+        image_width,
+        image_height,
+        source: GROUND_TRUTH_SOURCE.to_string(),
+        imported_at: String::new(),
+        boxes,
+    })
+}
+
+/// 导入Label Studio导出的JSON标注到真值库
+pub fn import_annotations(db: &Database, input_path: &Path, image_root: Option<&Path>) -> Result<LabelStudioImportSummary> {
+    let content = std::fs::read_to_string(input_path)
+        .map_err(|e| anyhow!("读取标注文件失败 {}: {}", input_path.display(), e))?;
+    let tasks: Vec<Value> = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("标注文件不是有效的Label Studio JSON: {}", e))?;
+
+    let mut image_count = 0u32;
+    let mut box_count = 0u32;
+    let mut skipped = Vec::new();
+
+    for (index, task) in tasks.iter().enumerate() {
+        match parse_task(task, image_root) {
+            Ok(image) => {
+                box_count += image.boxes.len() as u32;
+                image_count += 1;
+                ground_truth::upsert_image(db, &image)?;
+            }
+            Err(e) => skipped.push(format!("任务 {}: {}", task["id"].as_i64().unwrap_or(index as i64), e)),
+        }
+    }
+
+    tracing::info!("📥 已导入Label Studio标注: {} 张图片, {} 个框, 跳过 {}", image_count, box_count, skipped.len());
+
+    Ok(LabelStudioImportSummary {
+        image_count,
+        box_count,
+        skipped,
+    })
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 导出Label Studio预标注任务
+#[tauri::command]
+pub async fn export_label_studio_tasks(
+    state: State<'_, AppState>,
+    db: State<'_, Database>,
+    output_path: String,
+    run_ids: Option<Vec<i64>>,
+    image_url_prefix: Option<String>
+) -> Result<LabelStudioExportSummary, DetectionError> {
+    let model_version = {
+        let detector = state.read().await;
+        let info = detector.get_model_info();
+        info.get("model_path")
+            .and_then(|p| Path::new(p).file_name().map(|n| n.to_string_lossy().to_string()))
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| "yolo-detection-system".to_string())
+    };
+
+    match export_tasks(&db, Path::new(&output_path), run_ids, image_url_prefix.as_deref(), &model_version) {
+        Ok(summary) => Ok(summary),
+        Err(e) => Err(DetectionError::from(e).context("导出Label Studio任务失败")),
+    }
+}
+
+/// 导入Label Studio标注作为真值
+#[tauri::command]
+pub async fn import_label_studio_annotations(
+    db: State<'_, Database>,
+    input_path: String,
+    image_root: Option<String>
+) -> Result<LabelStudioImportSummary, DetectionError> {
+    let image_root = image_root.map(PathBuf::from);
+    match import_annotations(&db, Path::new(&input_path), image_root.as_deref()) {
+        Ok(summary) => Ok(summary),
+        Err(e) => Err(DetectionError::from(e).context("导入Label Studio标注失败")),
+    }
+}
@@ -0,0 +1,313 @@
+/*!
+日志模块
+各模块通过 tracing 输出日志，统一写到标准输出、应用数据目录下按天轮转的日志文件（保留最近两周），
+以及内存中的最近日志缓冲区，现场支持人员可通过 `get_recent_logs` 直接从界面拉取日志。
+日志级别可整体或按模块调整（`set_log_level`），设置保存在 `logging.json`；
+依赖库的日志固定为 warn 级别，避免刷屏
+*/
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Layered, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
+
+use crate::error::{self, DetectionError};
+use crate::profiling::ChromeSlot;
+
+/// 日志设置文件名
+const CONFIG_FILE_NAME: &str = "logging.json";
+
+/// 日志文件目录（应用数据目录下）
+const LOG_DIR_NAME: &str = "logs";
+
+/// 日志文件名前缀，轮转后为 `yolo-detection.2024-01-01.log`
+const LOG_FILE_PREFIX: &str = "yolo-detection";
+
+/// 保留的日志文件数（按天轮转）
+const MAX_LOG_FILES: usize = 14;
+
+/// 内存中保留的最近日志行数
+const RECENT_CAPACITY: usize = 2000;
+
+/// `get_recent_logs` 默认返回的行数
+const DEFAULT_RECENT_LINES: usize = 200;
+
+/// 本程序日志的 target 前缀
+const CRATE_TARGET: &str = env!("CARGO_CRATE_NAME");
+
+/// 可用的日志级别
+const LEVELS: [&str; 6] = ["trace", "debug", "info", "warn", "error", "off"];
+
+/// 日志层所在的订阅者（剖析层之上）
+type Base = Layered<reload::Layer<ChromeSlot, Registry>, Registry>;
+
+/// 日志设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogSettings {
+    pub level: String,                     // 本程序的整体级别
+    pub modules: BTreeMap<String, String>, // 模块名（如 realtime、yolo::candle_detector）-> 级别
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            modules: BTreeMap::new(),
+        }
+    }
+}
+
+fn validate_level(level: &str) -> Result<()> {
+    if LEVELS.contains(&level) {
+        Ok(())
+    } else {
+        Err(anyhow!("日志级别无效: {}（可选 {}）", level, LEVELS.join("/")))
+    }
+}
+
+/// 规范化模块名：去掉本程序前缀，只允许模块路径字符
+fn normalize_module(module: &str) -> Result<String> {
+    let prefix = format!("{}::", CRATE_TARGET);
+    let module = module.trim();
+    let module = module.strip_prefix(&prefix).unwrap_or(module);
+    if module.is_empty() || !module.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':') {
+        return Err(anyhow!("模块名无效: {}", module));
+    }
+    Ok(module.to_string())
+}
+
+impl LogSettings {
+    pub fn validate(&self) -> Result<()> {
+        validate_level(&self.level)?;
+        for (module, level) in &self.modules {
+            normalize_module(module)?;
+            validate_level(level)?;
+        }
+        Ok(())
+    }
+
+    /// 转换为 EnvFilter 过滤指令
+    fn directives(&self) -> String {
+        let mut directives = format!("warn,{}={}", CRATE_TARGET, self.level);
+        for (module, level) in &self.modules {
+            directives.push_str(&format!(",{}::{}={}", CRATE_TARGET, module, level));
+        }
+        directives
+    }
+
+    fn filter(&self) -> Result<EnvFilter> {
+        EnvFilter::try_new(self.directives()).map_err(|e| anyhow!("日志过滤规则无效: {}", e))
+    }
+}
+
+/// 日志输出目标：标准输出、日志文件（数据目录就绪后）与最近日志缓冲区
+#[derive(Clone, Default)]
+struct LogSink {
+    inner: Arc<SinkInner>,
+}
+
+#[derive(Default)]
+struct SinkInner {
+    file: Mutex<Option<RollingFileAppender>>,
+    recent: Mutex<VecDeque<String>>,
+}
+
+/// 单条日志的写入器，格式化完成（离开作用域）时一次性分发到各输出目标
+struct EventWriter {
+    inner: Arc<SinkInner>,
+    buffer: Vec<u8>,
+}
+
+impl Write for EventWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for EventWriter {
+    fn drop(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let _ = io::stdout().write_all(&self.buffer);
+        if let Some(file) = self.inner.file.lock().as_mut() {
+            let _ = file.write_all(&self.buffer);
+        }
+        let mut recent = self.inner.recent.lock();
+        for line in String::from_utf8_lossy(&self.buffer).lines() {
+            if recent.len() >= RECENT_CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(line.to_string());
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogSink {
+    type Writer = EventWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        EventWriter {
+            inner: self.inner.clone(),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+/// 日志管理（Tauri托管状态）
+pub struct Logging {
+    filter: reload::Handle<EnvFilter, Base>,
+    sink: LogSink,
+    settings: RwLock<LogSettings>,
+    path: Mutex<Option<PathBuf>>, // 设置文件路径，数据目录就绪后确定
+}
+
+/// 安装全局 tracing 订阅者（剖析层 + 日志层），应在程序启动时最先调用
+pub fn init(profiling_layer: reload::Layer<ChromeSlot, Registry>) -> Logging {
+    let settings = LogSettings::default();
+    let (filter, handle) = reload::Layer::new(settings.filter().unwrap_or_else(|_| EnvFilter::new("info")));
+    let sink = LogSink::default();
+    let log_layer = tracing_subscriber::fmt::layer()
+        .with_ansi(false)
+        .with_writer(sink.clone())
+        .with_filter(filter);
+    if let Err(e) = tracing_subscriber::registry().with(profiling_layer).with(log_layer).try_init() {
+        eprintln!("[ERROR] 初始化日志失败: {}", e);
+    }
+    Logging {
+        filter: handle,
+        sink,
+        settings: RwLock::new(settings),
+        path: Mutex::new(None),
+    }
+}
+
+impl Logging {
+    /// 读取已保存的日志设置，并开始写入数据目录下的日志文件
+    pub fn attach(&self, data_dir: &Path) {
+        let path = data_dir.join(CONFIG_FILE_NAME);
+        let settings = match std::fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<LogSettings>(&content) {
+                Ok(settings) if settings.validate().is_ok() => settings,
+                _ => {
+                    tracing::error!("日志设置文件无效，使用默认设置: {}", path.display());
+                    LogSettings::default()
+                }
+            },
+            Err(_) => LogSettings::default(),
+        };
+        if let Err(e) = self.apply(settings) {
+            tracing::error!("应用日志设置失败: {}", e);
+        }
+        *self.path.lock() = Some(path);
+
+        let log_dir = data_dir.join(LOG_DIR_NAME);
+        let appender = RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .filename_suffix("log")
+            .max_log_files(MAX_LOG_FILES)
+            .build(&log_dir);
+        match appender {
+            Ok(appender) => {
+                *self.sink.inner.file.lock() = Some(appender);
+                tracing::info!("📝 日志文件目录: {}", log_dir.display());
+            }
+            Err(e) => tracing::error!("创建日志文件失败 {}: {}", log_dir.display(), e),
+        }
+    }
+
+    pub fn settings(&self) -> LogSettings {
+        self.settings.read().clone()
+    }
+
+    fn apply(&self, settings: LogSettings) -> Result<()> {
+        settings.validate().map_err(error::invalid_input)?;
+        self.filter
+            .reload(settings.filter()?)
+            .map_err(|e| anyhow!("更新日志级别失败: {}", e))?;
+        *self.settings.write() = settings;
+        Ok(())
+    }
+
+    /// 设置整体或单个模块的日志级别并保存，模块级别为 `default` 时恢复跟随整体级别
+    pub fn set_level(&self, level: &str, module: Option<&str>) -> Result<LogSettings> {
+        let level = level.trim().to_ascii_lowercase();
+        let mut settings = self.settings();
+        match module {
+            Some(module) => {
+                let module = normalize_module(module).map_err(error::invalid_input)?;
+                if level == "default" {
+                    settings.modules.remove(&module);
+                } else {
+                    settings.modules.insert(module, level);
+                }
+            }
+            None => settings.level = level,
+        }
+        self.apply(settings.clone())?;
+
+        if let Some(path) = self.path.lock().as_ref() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, serde_json::to_string_pretty(&settings)?)?;
+        }
+        tracing::info!("📝 日志级别已更新: {}", settings.directives());
+        Ok(settings)
+    }
+
+    /// 最近的 n 行日志（按时间顺序）
+    pub fn recent(&self, n: usize) -> Vec<String> {
+        let recent = self.sink.inner.recent.lock();
+        recent.iter().skip(recent.len().saturating_sub(n)).cloned().collect()
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 设置日志级别（不传 module 时设置整体级别）
+#[tauri::command]
+pub async fn set_log_level(
+    logging: State<'_, Logging>,
+    level: String,
+    module: Option<String>
+) -> Result<LogSettings, DetectionError> {
+    match logging.set_level(&level, module.as_deref()) {
+        Ok(settings) => Ok(settings),
+        Err(e) => Err(DetectionError::from(e).context("设置日志级别失败")),
+    }
+}
+
+/// 获取当前日志设置
+#[tauri::command]
+pub async fn get_log_config(
+    logging: State<'_, Logging>
+) -> Result<LogSettings, DetectionError> {
+    Ok(logging.settings())
+}
+
+/// 获取最近的日志，默认200行
+#[tauri::command]
+pub async fn get_recent_logs(
+    logging: State<'_, Logging>,
+    n: Option<usize>
+) -> Result<Vec<String>, DetectionError> {
+    Ok(logging.recent(n.unwrap_or(DEFAULT_RECENT_LINES).min(RECENT_CAPACITY)))
+}
@@ -1,23 +1,81 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod adaptive_rate;
+mod alert_rules;
+mod alert_sinks;
+mod alerts;
+mod artifacts;
+mod blackbox;
+mod capture;
+mod clips;
+mod corrections;
+mod dataset;
+mod detection_config;
+mod event_recording;
+mod error;
+mod evaluation;
+mod export;
+mod ffmpeg;
+mod folder_watch;
+mod frame_queue;
+mod gif_export;
+mod ground_truth;
+mod headless;
+mod history;
+mod http_server;
+mod industrial_io;
+mod inference_worker;
+mod label_render;
+mod label_studio;
+mod logging;
+mod memory_budget;
+mod model_compare;
+mod model_download;
+mod models;
+mod privacy;
+mod profiles;
+mod profiling;
+mod realtime;
+mod replay;
+mod report;
+mod result_feed;
+mod retraining;
+mod scheduler;
+mod self_test;
+mod sessions;
+mod snapshot;
+mod source_lock;
+mod storage;
+mod tasks;
+mod temporal_filter;
+mod threading;
+mod timelapse;
+mod tracking;
+mod undistort;
+mod viewer;
 mod yolo;
 mod yolo_api;
+mod zone_dwell;
 
 use std::sync::{Arc};
-use tauri::State;
-use tokio::sync::Mutex;
+use tauri::{Manager, State};
+use tokio::sync::RwLock;
 use serde::{Deserialize, Serialize};
 
-use yolo::{CandleYoloDetector, DetectionResult, ModelStats};
+use error::DetectionError;
+use yolo::{DetectionResult, Detector, InferenceBackend, ModelStats};
+use yolo::rolling_stats::{self, StatsTimeseries};
 use yolo_api::*;
 
-/// API响应结果包装
+/// HTTP接口的响应包装（Tauri命令直接返回 `Result<T, DetectionError>`，失败时前端收到 `{ code, message }`）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiResult<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    #[serde(default)]
+    pub error_code: Option<String>, // 稳定的错误码（见 `error::DetectionError`），前端按此区分错误类别
 }
 
 impl<T> ApiResult<T> {
@@ -26,51 +84,62 @@ impl<T> ApiResult<T> {
             success: true,
             data: Some(data),
             error: None,
+            error_code: None,
         }
     }
     
+    /// 未区分类别的错误，错误码为 `INTERNAL`
     pub fn error(message: String) -> Self {
+        Self::failure(DetectionError::Internal(message))
+    }
+
+    pub fn failure(error: DetectionError) -> Self {
         Self {
             success: false,
             data: None,
-            error: Some(message),
+            error: Some(error.to_string()),
+            error_code: Some(error.code().to_string()),
         }
     }
 }
 
-type AppState = Arc<Mutex<CandleYoloDetector>>;
+impl<T> From<DetectionError> for ApiResult<T> {
+    fn from(error: DetectionError) -> Self {
+        Self::failure(error)
+    }
+}
+
+type AppState = Arc<RwLock<Box<dyn Detector>>>;
 
 /// 初始化YOLO模型
 #[tauri::command]
 async fn init_yolo_model(
     state: State<'_, AppState>,
+    store: State<'_, detection_config::ConfigStore>,
+    readiness: State<'_, models::ModelReadiness>,
     model_path: String
-) -> Result<ApiResult<String>, String> {
-    let mut yolo_manager = state.lock().await;
-    
-    match yolo_manager.init_model(&model_path).await {
-        Ok(()) => Ok(ApiResult::success("YOLO模型初始化成功".to_string())),
-        Err(e) => Ok(ApiResult::error(format!("模型初始化失败: {}", e))),
+) -> Result<String, DetectionError> {
+    match models::load_model(&state, &readiness, &store.get(), &model_path).await {
+        Ok(_) => Ok("YOLO模型初始化成功".to_string()),
+        Err(e) => Err(DetectionError::from(e).context("模型初始化失败")),
     }
 }
 
 /// 处理图像检测
 #[tauri::command]
 async fn process_image(
-    state: State<'_, AppState>,
+    worker: State<'_, inference_worker::InferenceWorker>,
     image_path: String
-) -> Result<ApiResult<DetectionResult>, String> {
-    let mut yolo_detector = state.lock().await;
-    
+) -> Result<DetectionResult, DetectionError> {
     // 读取图像文件
     match std::fs::read(&image_path) {
         Ok(image_data) => {
-            match yolo_detector.detect_image(&image_data).await {
-                Ok(result) => Ok(ApiResult::success(result)),
-                Err(e) => Ok(ApiResult::error(format!("图像处理失败: {}", e))),
+            match worker.detect(image_data).await {
+                Ok(result) => Ok(result),
+                Err(e) => Err(DetectionError::from(e).context("图像处理失败")),
             }
         }
-        Err(e) => Ok(ApiResult::error(format!("读取图像文件失败: {}", e))),
+        Err(e) => Err(DetectionError::IoError(e).context("读取图像文件失败")),
     }
 }
 
@@ -79,50 +148,57 @@ async fn process_image(
 async fn start_camera_detection_legacy(
     _state: State<'_, AppState>,
     _device_id: i32
-) -> Result<ApiResult<String>, String> {
+) -> Result<String, DetectionError> {
     // 暂时不支持摄像头
-    Ok(ApiResult::error("摄像头功能暂未实现".to_string()))
-}
-
-/// 开始视频检测
-#[tauri::command]
-async fn start_video_detection(
-    _state: State<'_, AppState>,
-    _video_path: String
-) -> Result<ApiResult<String>, String> {
-    // 暂时不支持视频
-    Ok(ApiResult::error("视频检测功能暂未实现".to_string()))
+    Err(DetectionError::FeatureDisabled("摄像头功能暂未实现".to_string()))
 }
 
 /// 停止检测 (原版本)
 #[tauri::command]
-async fn stop_detection_legacy(_state: State<'_, AppState>) -> Result<ApiResult<String>, String> {
+async fn stop_detection_legacy(_state: State<'_, AppState>) -> Result<String, DetectionError> {
     // Candle检测器不需要显式停止操作
-    Ok(ApiResult::success("检测已停止".to_string()))
+    Ok("检测已停止".to_string())
 }
 
 /// 获取检测统计信息
 #[tauri::command]
 async fn get_detection_state(
     state: State<'_, AppState>
-) -> Result<ApiResult<ModelStats>, String> {
-    let yolo_detector = state.lock().await;
+) -> Result<ModelStats, DetectionError> {
+    let yolo_detector = state.read().await;
     let stats = yolo_detector.get_stats().await;
-    Ok(ApiResult::success(stats))
+    Ok(stats)
+}
+
+/// 获取最近检测的耗时样本与每分钟吞吐量（供性能曲线），默认返回整个滚动窗口
+#[tauri::command]
+async fn get_stats_timeseries(
+    state: State<'_, AppState>,
+    limit: Option<usize>
+) -> Result<StatsTimeseries, DetectionError> {
+    let yolo_detector = state.read().await;
+    let limit = limit.unwrap_or(rolling_stats::WINDOW_SIZE);
+    Ok(yolo_detector.stats_timeseries(limit).await)
 }
 
 /// 更新置信度阈值
 #[tauri::command]
 async fn update_confidence_threshold(
     state: State<'_, AppState>,
+    store: State<'_, detection_config::ConfigStore>,
     class_name: String,
     threshold: f32
-) -> Result<ApiResult<String>, String> {
-    let yolo_detector = state.lock().await;
+) -> Result<String, DetectionError> {
+    let yolo_detector = state.read().await;
     
-    match yolo_detector.update_confidence_threshold(&class_name, threshold).await {
-        Ok(()) => Ok(ApiResult::success("置信度阈值已更新".to_string())),
-        Err(e) => Ok(ApiResult::error(format!("更新失败: {}", e))),
+    if let Err(e) = yolo_detector.update_confidence_threshold(&class_name, threshold).await {
+        return Err(DetectionError::from(e).context("更新失败"));
+    }
+    match store.update(|config| {
+        config.confidence_thresholds.insert(class_name, threshold);
+    }) {
+        Ok(_) => Ok("置信度阈值已更新".to_string()),
+        Err(e) => Err(DetectionError::from(e).context("保存失败")),
     }
 }
 
@@ -130,35 +206,125 @@ async fn update_confidence_threshold(
 #[tauri::command]
 async fn set_selected_classes(
     state: State<'_, AppState>,
+    store: State<'_, detection_config::ConfigStore>,
     class_ids: Vec<i32>
-) -> Result<ApiResult<String>, String> {
-    let yolo_detector = state.lock().await;
+) -> Result<String, DetectionError> {
+    let yolo_detector = state.read().await;
     
     // 转换i32到u32
     let class_ids_u32: Vec<u32> = class_ids.into_iter().map(|id| id as u32).collect();
+    // 配置文件按类别名称保存，模型类别顺序变化时仍然有效
+    let class_names: Vec<String> = class_ids_u32
+        .iter()
+        .filter_map(|id| yolo_detector.get_class_names().get(id).cloned())
+        .collect();
     
-    match yolo_detector.set_enabled_classes(class_ids_u32).await {
-        Ok(()) => Ok(ApiResult::success("类别选择已更新".to_string())),
-        Err(e) => Ok(ApiResult::error(format!("更新失败: {}", e))),
+    if let Err(e) = yolo_detector.set_enabled_classes(class_ids_u32).await {
+        return Err(DetectionError::from(e).context("更新失败"));
+    }
+    match store.update(|config| config.selected_classes = class_names) {
+        Ok(_) => Ok("类别选择已更新".to_string()),
+        Err(e) => Err(DetectionError::from(e).context("保存失败")),
     }
 }
 
 fn main() {
-    // 初始化YOLO Candle检测器
-    let yolo_detector = CandleYoloDetector::new();
+    // 安装tracing订阅者：统一日志输出，性能剖析时挂载Chrome Trace输出
+    let (profiling_layer, profiler) = profiling::layer();
+    let logging = logging::init(profiling_layer);
+
+    // 无界面命令行模式：不启动窗口，处理完输入源后退出
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(parsed) = headless::parse_args(&args) {
+        let code = match parsed {
+            Ok(args) => headless::run(args),
+            Err(e) => {
+                eprintln!("[ERROR] {}", e);
+                1
+            }
+        };
+        std::process::exit(code);
+    }
+    
+    // 初始化YOLO检测器（默认Candle后端，可通过 set_inference_backend 切换）
+    let yolo_detector: AppState = Arc::new(RwLock::new(yolo::create_detector(InferenceBackend::Candle)));
 
     tauri::Builder::default()
-        .manage(Arc::new(Mutex::new(yolo_detector)))
+        .manage(yolo_detector)
+        .manage(source_lock::SourceLocks::new())
+        .manage(viewer::ViewerHub::new())
+        .manage(result_feed::ResultFeed::new())
+        .manage(replay::ReplayManager::new())
+        .manage(adaptive_rate::AdaptiveRateController::new())
+        .manage(memory_budget::MemoryMonitor::new())
+        .manage(profiler)
+        .manage(logging)
+        .manage(folder_watch::FolderWatcher::new())
+        .manage(realtime::RealtimePipeline::new())
+        .manage(sessions::SessionManager::new())
+        .manage(tracking::TrackingManager::new())
+        .manage(temporal_filter::TemporalFilter::new())
+        .manage(zone_dwell::DwellMonitor::new())
+        .manage(models::ModelReadiness::new())
+        .manage(tasks::TaskManager::new())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
-        .invoke_handler(tauri::generate_handler![
+        .plugin(tauri_plugin_notification::init())
+        .setup(|app| {
+            // 初始化本地数据库（应用数据目录）
+            let data_dir = app.path().app_data_dir()?;
+            // 日志文件与已保存的日志级别
+            app.state::<logging::Logging>().attach(&data_dir);
+            // 推理线程池须在首次推理前创建
+            let thread_settings = threading::ThreadSettings::load(&data_dir);
+            // 推理在独立线程上执行，不占用命令的异步线程与检测器写锁
+            let detector = app.state::<AppState>().inner().clone();
+            app.manage(inference_worker::InferenceWorker::spawn(detector, thread_settings.parallel_images()));
+            app.manage(thread_settings);
+            app.manage(artifacts::ArtifactSettings::load(&data_dir));
+            app.manage(retraining::RetrainingManager::load(&data_dir));
+            app.manage(clips::ClipSettings::load(&data_dir));
+            app.manage(models::ModelRegistry::load(&data_dir));
+            app.manage(alert_rules::AlertRules::load(&data_dir));
+            app.manage(alert_sinks::AlertSinks::load(&data_dir));
+            app.manage(industrial_io::IndustrialIo::load(&data_dir));
+            app.manage(http_server::HttpServer::load(&data_dir));
+            app.manage(scheduler::Scheduler::load(&data_dir));
+            app.manage(storage::Database::open(&data_dir)?);
+            app.manage(blackbox::BlackBoxRecorder::new(data_dir.join("blackbox")));
+            app.manage(event_recording::EventRecorder::new(data_dir.join("event_recordings")));
+            // 标注图标签字体（随包字体位于资源目录）
+            label_render::init(app.path().resource_dir().ok().as_deref());
+            // 加载检测配置（应用配置目录）并应用到检测器
+            let config_dir = app.path().app_config_dir()?;
+            let config_store = detection_config::ConfigStore::load(&config_dir);
+            let config = config_store.get();
+            let detector = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::block_on(async move {
+                if let Err(e) = detection_config::apply(detector.write().await.as_mut(), &config).await {
+                    tracing::error!("检测配置应用失败: {}", e);
+                }
+            });
+            app.manage(config_store);
+            app.manage(profiles::ProfileStore::load(&config_dir));
+            // 负载/温度与内存监测
+            adaptive_rate::spawn_monitor(app.handle());
+            memory_budget::spawn_monitor(app.handle());
+            // 局域网HTTP服务（启用时）
+            http_server::spawn_if_enabled(app.handle());
+            // 定时检测计划
+            scheduler::spawn(app.handle());
+            Ok(())
+        })
+        // 监控窗口只放行只读命令
+        .invoke_handler(viewer::guard_invoke(tauri::generate_handler![
             // 原有API (legacy)
             init_yolo_model,
             process_image,
-            start_video_detection,
             start_camera_detection_legacy,
             stop_detection_legacy,
             get_detection_state,
+            get_stats_timeseries,
             update_confidence_threshold,
             set_selected_classes,
             // React UI兼容API (现在使用的主要API)
@@ -166,22 +332,185 @@ fn main() {
             start_camera_detection,
             load_video_source,
             process_single_image,
+            classify_image,
             stop_detection,
             get_next_frame,
+            ack_frame,
+            snapshot::capture_snapshot,
+            scheduler::get_schedules,
+            scheduler::set_schedules,
+            scheduler::get_schedule_status,
+            scheduler::run_schedule_now,
             reset_configuration,
             // 扩展API（基于PyQt5功能设计）
             get_class_names,
+            set_class_display,
             select_camera_input,
+            start_stream_detection,
+            list_cameras,
+            set_camera_properties,
             select_video_input,
+            start_video_detection,
+            pause_video,
+            resume_video,
+            seek_video,
+            step_frame,
+            set_frame_sampling,
+            get_frame_sampling,
+            set_frame_queue_config,
+            get_frame_queue_config,
             select_image_input,
+            process_image_batch,
             start_realtime_detection,
             stop_realtime_detection,
             get_realtime_status,
             update_confidence_thresholds,
+            set_nms_config,
+            set_tiling_config,
+            set_resize_config,
+            set_tta_config,
+            set_scale,
+            undistort::set_lens_calibration,
+            undistort::load_lens_calibration,
+            privacy::get_privacy_masks,
+            privacy::set_privacy_masks,
+            set_detection_roi,
             update_selected_classes,
             get_detection_config,
-            reset_to_defaults
-        ])
+            set_detection_config,
+            reset_to_defaults,
+            set_inference_backend,
+            select_device,
+            // 检测配置档案API
+            profiles::save_profile,
+            profiles::load_profile,
+            profiles::list_profiles,
+            // 模型管理API
+            models::list_models,
+            models::validate_model,
+            models::set_active_model,
+            models::warmup_model,
+            models::get_model_readiness,
+            model_download::download_model,
+            model_compare::compare_models,
+            evaluation::suggest_thresholds,
+            evaluation::evaluate_model,
+            // 目标跟踪API
+            tracking::set_tracking_config,
+            tracking::get_track_stats,
+            tracking::reset_tracking,
+            // 检测结果时间滤波API
+            temporal_filter::set_temporal_filter_config,
+            temporal_filter::get_temporal_filter_status,
+            // 区域停留分析API
+            zone_dwell::set_zone_dwell_config,
+            zone_dwell::get_zone_dwell_status,
+            // 告警管理API
+            alerts::list_alerts,
+            alerts::acknowledge_alert,
+            alerts::resolve_alert,
+            alerts::escalate_unresolved_alerts,
+            alerts::get_alert_audit_log,
+            alert_rules::get_alert_rules,
+            alert_rules::set_alert_rules,
+            alert_rules::test_alert_rules,
+            alert_sinks::get_alert_sinks,
+            alert_sinks::set_alert_sinks,
+            alert_sinks::test_alert_sink,
+            // 工业I/O API
+            industrial_io::get_industrial_io_status,
+            industrial_io::set_industrial_io_config,
+            industrial_io::write_io_output,
+            // HTTP服务API
+            http_server::get_http_server_status,
+            http_server::set_http_server_config,
+            // 后台任务API
+            tasks::list_tasks,
+            tasks::get_task,
+            tasks::cancel_task,
+            tasks::cancel_operation,
+            // 检测会话API
+            sessions::start_session,
+            sessions::end_session,
+            sessions::get_session,
+            sessions::list_sessions,
+            // 检测历史API
+            history::query_history,
+            history::delete_history,
+            export::export_results,
+            report::generate_report,
+            // 检测产物保存API
+            artifacts::get_artifact_config,
+            artifacts::set_artifact_config,
+            // 操作员修正API
+            corrections::confirm_detection,
+            corrections::mark_detection_false_positive,
+            corrections::correct_detection_class,
+            corrections::add_missed_detection,
+            corrections::list_corrections,
+            corrections::get_corrected_run,
+            corrections::get_review_stats,
+            // 训练数据导出API
+            dataset::export_corrections_dataset,
+            dataset::export_history_dataset,
+            dataset::select_uncertain_samples,
+            // 模型再训练API
+            retraining::set_retraining_config,
+            retraining::get_retraining_status,
+            retraining::trigger_retraining,
+            retraining::cancel_retraining,
+            // Label Studio集成API
+            label_studio::export_label_studio_tasks,
+            label_studio::import_label_studio_annotations,
+            ground_truth::list_ground_truth,
+            // 只读监控窗口API
+            viewer::open_viewer_window,
+            viewer::get_viewer_snapshot,
+            viewer::list_viewer_windows,
+            // 历史回放API
+            replay::start_replay,
+            replay::stop_replay,
+            replay::get_replay_status,
+            // 事件片段API
+            clips::extract_event_clips,
+            clips::get_auto_clip_config,
+            clips::set_auto_clip_config,
+            // 延时视频与GIF导出API
+            timelapse::generate_timelapse,
+            gif_export::export_gif,
+            // 黑匣子缓存与事件录像API
+            blackbox::set_blackbox_config,
+            blackbox::get_blackbox_status,
+            blackbox::clear_blackbox,
+            event_recording::set_event_recording_config,
+            event_recording::list_event_recordings,
+            // 自适应帧率API
+            adaptive_rate::set_adaptive_rate_config,
+            adaptive_rate::get_adaptive_rate_status,
+            // 推理线程配置API
+            threading::get_thread_config,
+            threading::set_thread_config,
+            // 内存预算API
+            memory_budget::set_memory_budget,
+            memory_budget::get_memory_budget_status,
+            memory_budget::get_memory_usage,
+            // 日志API
+            logging::set_log_level,
+            logging::get_log_config,
+            logging::get_recent_logs,
+            // 性能剖析API
+            profiling::start_profiling,
+            profiling::stop_profiling,
+            profiling::get_profiling_status,
+            // 启动自检API
+            self_test::run_self_test,
+            // 文件夹监控API
+            folder_watch::start_folder_watch,
+            folder_watch::stop_folder_watch,
+            folder_watch::pause_folder_watch,
+            folder_watch::resume_folder_watch,
+            folder_watch::get_folder_watch_status
+        ]))
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
\ No newline at end of file
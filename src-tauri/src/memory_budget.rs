@@ -0,0 +1,356 @@
+/*!
+内存预算模块
+为进程设置内存上限，后台监测任务在接近上限时依次清理预处理缓存与张量缓冲池、丢弃预览图像、
+降低队列深度并发出警告事件，避免班次中途被操作系统OOM强制结束
+*/
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, System};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::blackbox::BlackBoxRecorder;
+use crate::error::DetectionError;
+use crate::event_recording::EventRecorder;
+use crate::storage::Database;
+use crate::viewer::ViewerHub;
+use crate::yolo::tensor_pool;
+use crate::AppState;
+
+/// 内存警告事件
+pub const EVENT_MEMORY_WARNING: &str = "memory://warning";
+
+/// 监测周期
+const MONITOR_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 内存预算配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryBudgetConfig {
+    pub enabled: bool,
+    pub budget_mb: u64,
+    pub warning_ratio: f32,  // 达到预算的该比例时开始清理缓存
+    pub critical_ratio: f32, // 达到该比例时进一步降低队列深度
+}
+
+impl Default for MemoryBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            budget_mb: 2048,
+            warning_ratio: 0.85,
+            critical_ratio: 0.95,
+        }
+    }
+}
+
+/// 内存压力级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryPressure {
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl MemoryPressure {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            2 => MemoryPressure::Critical,
+            1 => MemoryPressure::Warning,
+            _ => MemoryPressure::Normal,
+        }
+    }
+}
+
+/// 单个子系统的资源占用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubsystemMemory {
+    pub subsystem: String,
+    pub bytes: u64,
+    pub on_disk: bool, // 磁盘占用（不计入进程内存）
+    pub detail: String,
+}
+
+/// 内存使用报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryUsageReport {
+    pub process_rss_bytes: Option<u64>,
+    pub tracked_memory_bytes: u64, // 各子系统内存占用合计
+    pub subsystems: Vec<SubsystemMemory>,
+}
+
+/// 内存预算状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryBudgetStatus {
+    pub config: MemoryBudgetConfig,
+    pub rss_bytes: Option<u64>,
+    pub pressure: MemoryPressure,
+    pub actions: Vec<String>, // 最近一次采取的措施
+    pub checked_at: Option<String>,
+}
+
+#[derive(Default)]
+struct MonitorRuntime {
+    rss_bytes: Option<u64>,
+    actions: Vec<String>,
+    checked_at: Option<String>,
+}
+
+/// 内存监测器（Tauri托管状态）
+pub struct MemoryMonitor {
+    config: RwLock<MemoryBudgetConfig>,
+    pressure: AtomicU8,
+    runtime: Mutex<MonitorRuntime>,
+}
+
+impl Default for MemoryMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryMonitor {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(MemoryBudgetConfig::default()),
+            pressure: AtomicU8::new(0),
+            runtime: Mutex::new(MonitorRuntime::default()),
+        }
+    }
+
+    /// 当前内存压力（供帧队列等组件决定缓冲深度）
+    pub fn pressure(&self) -> MemoryPressure {
+        MemoryPressure::from_u8(self.pressure.load(Ordering::Relaxed))
+    }
+
+    /// 按内存压力缩减队列深度
+    pub fn scaled_depth(&self, depth: usize) -> usize {
+        match self.pressure() {
+            MemoryPressure::Normal => depth,
+            MemoryPressure::Warning => (depth / 2).max(1),
+            MemoryPressure::Critical => 1,
+        }
+    }
+
+    pub fn status(&self) -> MemoryBudgetStatus {
+        let runtime = self.runtime.lock();
+        MemoryBudgetStatus {
+            config: self.config.read().clone(),
+            rss_bytes: runtime.rss_bytes,
+            pressure: self.pressure(),
+            actions: runtime.actions.clone(),
+            checked_at: runtime.checked_at.clone(),
+        }
+    }
+}
+
+/// 读取本进程常驻内存
+pub fn process_rss_bytes(system: &mut System) -> Option<u64> {
+    let pid = Pid::from_u32(std::process::id());
+    if !system.refresh_process(pid) {
+        return None;
+    }
+    system.process(pid).map(|process| process.memory())
+}
+
+fn classify(config: &MemoryBudgetConfig, rss_bytes: u64) -> MemoryPressure {
+    let budget = (config.budget_mb * 1024 * 1024) as f64;
+    let ratio = rss_bytes as f64 / budget;
+    if ratio >= config.critical_ratio as f64 {
+        MemoryPressure::Critical
+    } else if ratio >= config.warning_ratio as f64 {
+        MemoryPressure::Warning
+    } else {
+        MemoryPressure::Normal
+    }
+}
+
+/// 按压力级别释放内存，返回采取的措施
+async fn relieve(app: &AppHandle) -> Vec<String> {
+    let mut actions = Vec::new();
+
+    // 检测器正在推理时跳过，下个周期再尝试
+    if let Ok(detector) = app.state::<AppState>().try_read() {
+        if detector.clear_cache().await {
+            actions.push("清空预处理缓存".to_string());
+        }
+    }
+
+    let pooled = tensor_pool::global().shrink();
+    if pooled > 0 {
+        actions.push(format!("释放张量缓冲池 ({} KB)", pooled / 1024));
+    }
+
+    let freed = app.state::<ViewerHub>().drop_preview();
+    if freed > 0 {
+        actions.push(format!("丢弃预览图像 ({} KB)", freed / 1024));
+    }
+    actions
+}
+
+/// 启动后台内存监测任务
+pub fn spawn_monitor(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut system = System::new();
+        loop {
+            tokio::time::sleep(MONITOR_INTERVAL).await;
+
+            let monitor = app.state::<MemoryMonitor>();
+            let config = monitor.config.read().clone();
+            let rss_bytes = process_rss_bytes(&mut system);
+            let pressure = match rss_bytes {
+                Some(rss) if config.enabled => classify(&config, rss),
+                _ => MemoryPressure::Normal,
+            };
+            let previous = monitor.pressure();
+            monitor.pressure.store(pressure as u8, Ordering::Relaxed);
+
+            let mut actions = Vec::new();
+            if pressure >= MemoryPressure::Warning {
+                actions = relieve(&app).await;
+            }
+            if pressure == MemoryPressure::Critical {
+                actions.push("队列深度降至1".to_string());
+            }
+
+            {
+                let mut runtime = monitor.runtime.lock();
+                runtime.rss_bytes = rss_bytes;
+                runtime.checked_at = Some(crate::storage::now_rfc3339());
+                if pressure != MemoryPressure::Normal {
+                    runtime.actions = actions.clone();
+                }
+            }
+
+            if pressure > previous || (pressure != MemoryPressure::Normal && !actions.is_empty()) {
+                tracing::warn!(
+                    "⚠️ 内存占用 {} MB / 预算 {} MB ({:?}): {}",
+                    rss_bytes.unwrap_or(0) / 1024 / 1024,
+                    config.budget_mb,
+                    pressure,
+                    actions.join(", ")
+                );
+                let _ = app.emit(EVENT_MEMORY_WARNING, monitor.status());
+            } else if pressure < previous {
+                tracing::info!("✅ 内存压力已恢复: {:?}", pressure);
+            }
+        }
+    });
+}
+
+fn file_size(path: &std::path::Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// 汇总各子系统的内存与缓冲占用
+async fn collect_usage(app: &AppHandle) -> MemoryUsageReport {
+    let mut subsystems = Vec::new();
+
+    let pool = tensor_pool::global().stats();
+    subsystems.push(SubsystemMemory {
+        subsystem: "tensor_pool".to_string(),
+        bytes: pool.pooled_bytes + pool.in_use_bytes,
+        on_disk: false,
+        detail: format!(
+            "空闲 {} 个缓冲区（{}），复用 {} 次 / 新分配 {} 次",
+            pool.pooled_buffers,
+            pool.free_by_size
+                .iter()
+                .map(|(size, count)| format!("{}元素×{}", size, count))
+                .collect::<Vec<_>>()
+                .join(", "),
+            pool.reuses,
+            pool.allocations
+        ),
+    });
+
+    let cache_bytes = app.state::<AppState>().read().await.get_memory_usage().await;
+    subsystems.push(SubsystemMemory {
+        subsystem: "preprocess_cache".to_string(),
+        bytes: cache_bytes,
+        on_disk: false,
+        detail: "最近一次预处理的输入张量与 letterbox 画布".to_string(),
+    });
+
+    subsystems.push(SubsystemMemory {
+        subsystem: "viewer_preview".to_string(),
+        bytes: app.state::<ViewerHub>().preview_bytes() as u64,
+        on_disk: false,
+        detail: "监控窗口最近一帧标注图像".to_string(),
+    });
+
+    let db_path = app.state::<Database>().path().to_path_buf();
+    let wal_path = db_path.with_extension("db-wal");
+    subsystems.push(SubsystemMemory {
+        subsystem: "stored_results".to_string(),
+        bytes: file_size(&db_path) + file_size(&wal_path),
+        on_disk: true,
+        detail: format!("检测历史数据库 {}", db_path.display()),
+    });
+
+    let recorder = app.state::<EventRecorder>();
+    let (pre_roll_bytes, pre_roll_frames) = recorder.pre_roll_usage();
+    subsystems.push(SubsystemMemory {
+        subsystem: "event_pre_roll".to_string(),
+        bytes: pre_roll_bytes,
+        on_disk: false,
+        detail: format!("事件录像前置画面缓冲 {} 帧（JPEG）", pre_roll_frames),
+    });
+
+    let (blackbox_bytes, blackbox_frames) = app.state::<BlackBoxRecorder>().disk_usage();
+    let (active_recordings, recording_frames) = recorder.active_usage();
+    subsystems.push(SubsystemMemory {
+        subsystem: "recording_buffers".to_string(),
+        bytes: blackbox_bytes,
+        on_disk: true,
+        detail: format!(
+            "黑匣子 {} 帧；进行中的事件录像 {} 个（{} 帧）",
+            blackbox_frames, active_recordings, recording_frames
+        ),
+    });
+
+    let tracked_memory_bytes = subsystems.iter().filter(|s| !s.on_disk).map(|s| s.bytes).sum();
+    MemoryUsageReport {
+        process_rss_bytes: process_rss_bytes(&mut System::new()),
+        tracked_memory_bytes,
+        subsystems,
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 设置内存预算
+#[tauri::command]
+pub async fn set_memory_budget(
+    monitor: State<'_, MemoryMonitor>,
+    config: MemoryBudgetConfig
+) -> Result<MemoryBudgetStatus, DetectionError> {
+    if config.budget_mb == 0 {
+        return Err(DetectionError::InvalidInput("内存预算必须大于0".to_string()));
+    }
+    if !(config.warning_ratio > 0.0 && config.warning_ratio <= config.critical_ratio) {
+        return Err(DetectionError::InvalidInput("警告比例必须大于0且不超过严重比例".to_string()));
+    }
+    *monitor.config.write() = config;
+    Ok(monitor.status())
+}
+
+/// 获取内存预算状态
+#[tauri::command]
+pub async fn get_memory_budget_status(
+    monitor: State<'_, MemoryMonitor>
+) -> Result<MemoryBudgetStatus, DetectionError> {
+    Ok(monitor.status())
+}
+
+/// 获取按子系统分类的内存使用情况（诊断面板）
+#[tauri::command]
+pub async fn get_memory_usage(
+    app: AppHandle
+) -> Result<MemoryUsageReport, DetectionError> {
+    Ok(collect_usage(&app).await)
+}
@@ -0,0 +1,211 @@
+/*!
+模型对比模块
+用两个模型（通常为当前模型与再训练后的模型）检测同一批图片，
+按IoU匹配两者的检测框，逐张给出匹配、漏检（A有B无）与多检（B有A无）的差异，便于上线前评估新模型
+两个模型在独立的检测器实例中加载，不影响当前使用的模型
+*/
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::detection_config::ConfigStore;
+use crate::error::DetectionError;
+use crate::models::{self, ModelRegistry};
+use crate::yolo::nms::calculate_iou;
+use crate::yolo::{Detector, YoloDetection};
+use crate::AppState;
+
+/// 默认的匹配IoU阈值
+const DEFAULT_MATCH_IOU: f32 = 0.5;
+
+/// 两个模型匹配上的检测框
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchedDetection {
+    pub a: YoloDetection,
+    pub b: YoloDetection,
+    pub iou: f32,
+    pub class_changed: bool,  // 两个模型给出的类别不同
+    pub confidence_delta: f32, // B 的置信度减去 A 的置信度
+}
+
+/// 单张图片的对比结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageComparison {
+    pub image_path: String,
+    pub matched: Vec<MatchedDetection>,
+    pub missed: Vec<YoloDetection>, // 模型A检出而模型B未检出
+    pub extra: Vec<YoloDetection>,  // 模型B检出而模型A未检出
+    pub time_a_ms: u64,
+    pub time_b_ms: u64,
+    pub error: Option<String>, // 读取或检测失败时的错误
+}
+
+/// 对比汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComparisonSummary {
+    pub images: usize,
+    pub failed_images: usize,
+    pub detections_a: usize,
+    pub detections_b: usize,
+    pub matched: usize,
+    pub class_changed: usize,
+    pub missed: usize,
+    pub extra: usize,
+    pub avg_time_a_ms: f64,
+    pub avg_time_b_ms: f64,
+}
+
+/// 模型对比结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelComparison {
+    pub model_a: String,
+    pub model_b: String,
+    pub iou_threshold: f32,
+    pub images: Vec<ImageComparison>,
+    pub summary: ComparisonSummary,
+}
+
+/// 按IoU从高到低贪心匹配两组检测框（不要求类别相同，类别不同记为 class_changed）
+fn match_detections(a: Vec<YoloDetection>, b: Vec<YoloDetection>, iou_threshold: f32) -> (Vec<MatchedDetection>, Vec<YoloDetection>, Vec<YoloDetection>) {
+    let mut pairs: Vec<(usize, usize, f32)> = Vec::new();
+    for (i, da) in a.iter().enumerate() {
+        for (j, db) in b.iter().enumerate() {
+            let iou = calculate_iou(&da.bbox, &db.bbox);
+            if iou >= iou_threshold {
+                pairs.push((i, j, iou));
+            }
+        }
+    }
+    pairs.sort_by(|x, y| y.2.total_cmp(&x.2));
+
+    let mut used_a = vec![false; a.len()];
+    let mut used_b = vec![false; b.len()];
+    let mut matched = Vec::new();
+    for (i, j, iou) in pairs {
+        if used_a[i] || used_b[j] {
+            continue;
+        }
+        used_a[i] = true;
+        used_b[j] = true;
+        matched.push(MatchedDetection {
+            a: a[i].clone(),
+            b: b[j].clone(),
+            iou,
+            class_changed: a[i].class_id != b[j].class_id,
+            confidence_delta: b[j].confidence - a[i].confidence,
+        });
+    }
+
+    let missed = a.into_iter().zip(used_a).filter(|(_, used)| !used).map(|(d, _)| d).collect();
+    let extra = b.into_iter().zip(used_b).filter(|(_, used)| !used).map(|(d, _)| d).collect();
+    (matched, missed, extra)
+}
+
+async fn compare_image(
+    detector_a: &dyn Detector,
+    detector_b: &dyn Detector,
+    image_path: &str,
+    iou_threshold: f32,
+) -> Result<ImageComparison> {
+    let data = std::fs::read(image_path).map_err(|e| anyhow!("读取图像文件失败: {}", e))?;
+    let result_a = detector_a.detect_image(&data).await.map_err(|e| anyhow!("模型A检测失败: {}", e))?;
+    let result_b = detector_b.detect_image(&data).await.map_err(|e| anyhow!("模型B检测失败: {}", e))?;
+
+    let (matched, missed, extra) = match_detections(result_a.detections, result_b.detections, iou_threshold);
+    Ok(ImageComparison {
+        image_path: image_path.to_string(),
+        matched,
+        missed,
+        extra,
+        time_a_ms: result_a.processing_time_ms,
+        time_b_ms: result_b.processing_time_ms,
+        error: None,
+    })
+}
+
+fn summarize(images: &[ImageComparison]) -> ComparisonSummary {
+    let mut summary = ComparisonSummary {
+        images: images.len(),
+        ..ComparisonSummary::default()
+    };
+    let succeeded: Vec<&ImageComparison> = images.iter().filter(|image| image.error.is_none()).collect();
+    summary.failed_images = images.len() - succeeded.len();
+    for image in &succeeded {
+        summary.matched += image.matched.len();
+        summary.class_changed += image.matched.iter().filter(|m| m.class_changed).count();
+        summary.missed += image.missed.len();
+        summary.extra += image.extra.len();
+    }
+    summary.detections_a = summary.matched + summary.missed;
+    summary.detections_b = summary.matched + summary.extra;
+    if !succeeded.is_empty() {
+        let count = succeeded.len() as f64;
+        summary.avg_time_a_ms = succeeded.iter().map(|image| image.time_a_ms as f64).sum::<f64>() / count;
+        summary.avg_time_b_ms = succeeded.iter().map(|image| image.time_b_ms as f64).sum::<f64>() / count;
+    }
+    summary
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 用两个模型检测同一批图片并逐张对比（模型可为已登记的模型ID或模型文件路径）
+#[tauri::command]
+pub async fn compare_models(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    registry: State<'_, ModelRegistry>,
+    model_a: String,
+    model_b: String,
+    image_paths: Vec<String>,
+    iou_threshold: Option<f32>
+) -> Result<ModelComparison, DetectionError> {
+    if image_paths.is_empty() {
+        return Err(DetectionError::InvalidInput("请选择要对比的图片".to_string()));
+    }
+    let iou_threshold = iou_threshold.unwrap_or(DEFAULT_MATCH_IOU).clamp(0.0, 1.0);
+    let config = store.get();
+    let backend = state.read().await.backend();
+
+    let path_a = registry.resolve_path(&model_a);
+    let path_b = registry.resolve_path(&model_b);
+    let detector_a = match models::create_loaded(backend, &config, &path_a).await {
+        Ok(detector) => detector,
+        Err(e) => return Err(DetectionError::from(e).context("加载模型A失败")),
+    };
+    let detector_b = match models::create_loaded(backend, &config, &path_b).await {
+        Ok(detector) => detector,
+        Err(e) => return Err(DetectionError::from(e).context("加载模型B失败")),
+    };
+
+    tracing::info!(
+        "⚖️ 模型对比: {} vs {} ({} 张图片)",
+        path_a,
+        path_b,
+        image_paths.len()
+    );
+    let mut images = Vec::with_capacity(image_paths.len());
+    for image_path in &image_paths {
+        match compare_image(detector_a.as_ref(), detector_b.as_ref(), image_path, iou_threshold).await {
+            Ok(comparison) => images.push(comparison),
+            Err(e) => images.push(ImageComparison {
+                image_path: image_path.clone(),
+                error: Some(e.to_string()),
+                ..ImageComparison::default()
+            }),
+        }
+    }
+
+    let summary = summarize(&images);
+    tracing::info!(
+        "⚖️ 对比完成: 匹配 {}, 漏检 {}, 多检 {}, 类别变化 {}",
+        summary.matched, summary.missed, summary.extra, summary.class_changed
+    );
+    Ok(ModelComparison {
+        model_a: path_a,
+        model_b: path_b,
+        iou_threshold,
+        images,
+        summary,
+    })
+}
@@ -0,0 +1,237 @@
+/*!
+模型下载模块
+从URL流式下载ONNX模型到受管理的模型目录（先写入 .part 临时文件），
+下载进度通过Tauri事件回传前端，完成后校验SHA-256并登记到模型管理（见 `models` 模块）。
+下载登记为后台任务，可通过 `cancel_task` 中途取消
+*/
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, State};
+use tokio::io::AsyncWriteExt;
+
+use crate::error::DetectionError;
+use crate::models::{self, ModelRegistry, RegisteredModel};
+use crate::tasks::{TaskHandle, TaskManager};
+
+/// 下载进度事件
+pub const EVENT_DOWNLOAD_PROGRESS: &str = "model-download://progress";
+/// 下载结束事件
+pub const EVENT_DOWNLOAD_FINISHED: &str = "model-download://finished";
+
+/// 进度事件的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 建立连接的超时时间
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 下载进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadProgress {
+    pub url: String,
+    pub file_name: String,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>, // 服务器未返回长度时为空
+    pub progress: Option<f32>,    // 0-100
+}
+
+/// 下载结束信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadFinished {
+    pub url: String,
+    pub success: bool,
+    pub model: Option<RegisteredModel>,
+    pub sha256: Option<String>,
+    pub message: String,
+}
+
+/// 由参数或URL最后一段确定保存的文件名
+fn resolve_file_name(url: &str, file_name: Option<String>) -> Result<String> {
+    let name = match file_name.filter(|n| !n.trim().is_empty()) {
+        Some(name) => name,
+        None => url
+            .split(['?', '#'])
+            .next()
+            .and_then(|path| path.rsplit('/').next())
+            .filter(|segment| !segment.is_empty())
+            .ok_or_else(|| anyhow!("无法从URL确定文件名，请指定 file_name"))?
+            .to_string(),
+    };
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(DetectionError::InvalidInput(format!("文件名无效: {}", name)).into());
+    }
+    if !name.to_ascii_lowercase().ends_with(".onnx") {
+        return Err(anyhow!("只支持下载ONNX格式模型文件: {}", name));
+    }
+    Ok(name)
+}
+
+/// 流式下载到临时文件，返回文件内容的SHA-256（小写十六进制）
+async fn download_to(
+    app: &AppHandle,
+    task: &TaskHandle,
+    url: &str,
+    file_name: &str,
+    part_path: &Path,
+) -> Result<String> {
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .map_err(|e| anyhow!("创建HTTP客户端失败: {}", e))?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| anyhow!("请求失败: {}", e))?;
+    let total_bytes = response.content_length();
+
+    let mut file = tokio::fs::File::create(part_path)
+        .await
+        .map_err(|e| anyhow!("创建文件失败 {}: {}", part_path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut downloaded_bytes = 0u64;
+    let mut last_emit: Option<Instant> = None;
+    let mut stream = response.bytes_stream();
+
+    loop {
+        let chunk = tokio::select! {
+            chunk = stream.next() => chunk,
+            _ = task.token().cancelled() => return Err(DetectionError::Cancelled.into()),
+        };
+        let Some(chunk) = chunk else {
+            break;
+        };
+        let chunk = chunk.map_err(|e| anyhow!("下载中断: {}", e))?;
+        file.write_all(&chunk).await?;
+        hasher.update(&chunk);
+        downloaded_bytes += chunk.len() as u64;
+
+        let due = match last_emit {
+            Some(at) => at.elapsed() >= PROGRESS_INTERVAL,
+            None => true,
+        };
+        task.set_progress(downloaded_bytes, total_bytes);
+        if due || total_bytes == Some(downloaded_bytes) {
+            last_emit = Some(Instant::now());
+            let _ = app.emit(
+                EVENT_DOWNLOAD_PROGRESS,
+                DownloadProgress {
+                    url: url.to_string(),
+                    file_name: file_name.to_string(),
+                    downloaded_bytes,
+                    total_bytes,
+                    progress: total_bytes
+                        .filter(|total| *total > 0)
+                        .map(|total| (downloaded_bytes as f32 / total as f32 * 100.0).min(100.0)),
+                },
+            );
+        }
+    }
+    file.flush().await?;
+
+    if let Some(total) = total_bytes {
+        if downloaded_bytes != total {
+            return Err(anyhow!("下载不完整: {}/{} 字节", downloaded_bytes, total));
+        }
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// 下载、校验并登记模型
+async fn download(
+    app: &AppHandle,
+    task: &TaskHandle,
+    registry: &ModelRegistry,
+    url: &str,
+    expected_sha256: Option<&str>,
+    file_name: Option<String>,
+    name: Option<String>,
+) -> Result<(RegisteredModel, String)> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(anyhow!("只支持 http/https 下载地址: {}", url));
+    }
+    let file_name = resolve_file_name(url, file_name)?;
+    let models_dir = registry.models_dir();
+    std::fs::create_dir_all(models_dir)
+        .map_err(|e| anyhow!("创建模型目录失败 {}: {}", models_dir.display(), e))?;
+    let dest = models_dir.join(&file_name);
+    if dest.exists() {
+        return Err(anyhow!("模型文件已存在: {}", dest.display()));
+    }
+    let part_path = PathBuf::from(format!("{}.part", dest.display()));
+
+    tracing::info!("⬇️  下载模型: {} -> {}", url, dest.display());
+    let sha256 = match download_to(app, task, url, &file_name, &part_path).await {
+        Ok(sha256) => sha256,
+        Err(e) => {
+            let _ = std::fs::remove_file(&part_path);
+            return Err(e);
+        }
+    };
+    if let Some(expected) = expected_sha256 {
+        if !sha256.eq_ignore_ascii_case(expected.trim()) {
+            let _ = std::fs::remove_file(&part_path);
+            return Err(anyhow!("SHA-256校验失败: 期望 {}，实际 {}", expected.trim(), sha256));
+        }
+    }
+    std::fs::rename(&part_path, &dest).map_err(|e| anyhow!("保存模型文件失败 {}: {}", dest.display(), e))?;
+
+    // 模型不可用时仍然保留文件，校验结果可通过 validate_model 查看
+    match models::validate(&dest) {
+        Ok(validation) if !validation.valid => {
+            tracing::warn!("⚠️  下载的模型未通过校验: {}", validation.errors.join("; "))
+        }
+        Err(e) => tracing::warn!("⚠️  下载的模型未通过校验: {}", e),
+        Ok(_) => {}
+    }
+    let model = registry.register(&dest, name)?;
+    tracing::info!("✅ 模型下载完成: {} (sha256 {})", model.id, sha256);
+    Ok((model, sha256))
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 下载模型到受管理的模型目录并登记，进度通过 `model-download://progress` 事件推送
+#[tauri::command]
+pub async fn download_model(
+    app: AppHandle,
+    registry: State<'_, ModelRegistry>,
+    tasks: State<'_, TaskManager>,
+    url: String,
+    sha256: Option<String>,
+    file_name: Option<String>,
+    name: Option<String>,
+    task_id: Option<String>
+) -> Result<RegisteredModel, DetectionError> {
+    let task = tasks.begin(&app, "model_download", task_id)?;
+    let result = download(&app, &task, &registry, &url, sha256.as_deref(), file_name, name).await;
+    task.finish(&result);
+    let finished = match &result {
+        Ok((model, sha256)) => DownloadFinished {
+            url: url.clone(),
+            success: true,
+            model: Some(model.clone()),
+            sha256: Some(sha256.clone()),
+            message: "模型下载完成".to_string(),
+        },
+        Err(e) => DownloadFinished {
+            url: url.clone(),
+            success: false,
+            model: None,
+            sha256: None,
+            message: e.to_string(),
+        },
+    };
+    let _ = app.emit(EVENT_DOWNLOAD_FINISHED, finished);
+
+    match result {
+        Ok((model, _)) => Ok(model),
+        Err(e) => Err(DetectionError::from(e).context("下载模型失败")),
+    }
+}
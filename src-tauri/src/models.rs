@@ -0,0 +1,531 @@
+/*!
+模型管理模块
+登记本地的多个ONNX模型（应用数据目录下 models.json），支持扫描目录、校验模型
+（算子集版本、输入/输出形状、类别数）以及在运行中切换当前使用的模型，无需重启应用
+
+加载模型时在新的检测器实例中完成加载、配置与预热，期间不占用检测器锁，
+完成后整体替换当前检测器，进行中的摄像头/视频检测在两帧之间切换到新模型；
+模型加载并预热成功后 `model_ready` 为 true，界面据此启用检测操作
+*/
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use candle_onnx::onnx::ModelProto;
+use image::{DynamicImage, ImageFormat, Rgb, RgbImage};
+use parking_lot::RwLock;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::detection_config::{self, ConfigStore};
+use crate::error::DetectionError;
+use crate::storage::now_rfc3339;
+use crate::yolo::{self, model_meta, Detector, InferenceBackend};
+use crate::yolo_api::DetectionConfig;
+use crate::AppState;
+
+/// 模型登记文件名（位于应用数据目录）
+pub const REGISTRY_FILE_NAME: &str = "models.json";
+
+/// 受管理的模型目录（位于应用数据目录），未指定扫描目录时使用
+const MODELS_DIR: &str = "models";
+
+/// 支持的最低算子集版本
+const MIN_OPSET: i64 = 7;
+
+/// 已验证可由 Candle 推理的最高算子集版本，更高版本可能包含暂不支持的算子
+const MAX_TESTED_OPSET: i64 = 17;
+
+/// 预热用空白图片的边长
+const WARMUP_IMAGE_SIZE: u32 = 640;
+
+/// warmup_model 默认的预热次数
+const DEFAULT_WARMUP_ITERATIONS: u32 = 3;
+
+/// warmup_model 允许的最大预热次数
+const MAX_WARMUP_ITERATIONS: u32 = 50;
+
+/// 已登记的模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredModel {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub registered_at: String,
+}
+
+/// 模型列表项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelEntry {
+    #[serde(flatten)]
+    pub model: RegisteredModel,
+    pub exists: bool, // 模型文件是否仍然存在
+    pub size_bytes: Option<u64>,
+    pub modified_at: Option<String>,
+    pub active: bool,
+}
+
+/// 模型校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelValidation {
+    pub path: String,
+    pub valid: bool,
+    pub ir_version: i64,
+    pub opset: Option<i64>,
+    pub producer: String,
+    pub input_shape: String,  // 如 1x3x640x640，动态维度为 ?
+    pub output_shape: String, // 如 1x6x8400
+    pub input_size: Option<(u32, u32)>,
+    pub num_classes: Option<usize>,
+    pub class_names: Vec<String>, // 模型元数据中的类别名称
+    pub errors: Vec<String>,      // 无法用于检测的问题
+    pub warnings: Vec<String>,    // 可能影响推理的问题
+}
+
+/// 模型预热结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmupReport {
+    pub iterations: u32,
+    pub first_ms: u64, // 首次推理耗时，通常包含延迟初始化
+    pub last_ms: u64,
+    pub avg_ms: f64,
+}
+
+/// 模型就绪状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReadinessStatus {
+    pub model_ready: bool, // 模型已加载并预热成功
+    pub loading: bool,     // 正在后台加载新模型（加载期间仍使用原模型）
+    pub model_path: Option<String>,
+    pub last_warmup: Option<WarmupReport>,
+}
+
+/// 模型就绪状态（Tauri托管状态）
+#[derive(Default)]
+pub struct ModelReadiness {
+    status: RwLock<ReadinessStatus>,
+}
+
+impl ModelReadiness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> ReadinessStatus {
+        self.status.read().clone()
+    }
+
+    fn set_loading(&self, loading: bool) {
+        self.status.write().loading = loading;
+    }
+
+    /// 记录当前模型的预热结果，预热失败时模型不可用
+    fn set_warmed(&self, model_path: Option<String>, warmup: Option<WarmupReport>) {
+        let mut status = self.status.write();
+        status.model_ready = model_path.is_some() && warmup.is_some();
+        status.loading = false;
+        status.model_path = model_path;
+        status.last_warmup = warmup;
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct RegistryFile {
+    models: Vec<RegisteredModel>,
+    active_model: Option<String>,
+}
+
+/// 模型登记表（Tauri托管状态）
+pub struct ModelRegistry {
+    path: PathBuf,
+    models_dir: PathBuf,
+    registry: RwLock<RegistryFile>,
+}
+
+/// 由文件名生成模型ID（保留字母数字与中文）
+fn model_id_base(path: &Path) -> String {
+    let stem: String = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if stem.is_empty() {
+        "model".to_string()
+    } else {
+        stem
+    }
+}
+
+fn is_onnx(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("onnx"))
+}
+
+/// 校验ONNX模型能否用于检测
+pub fn validate(path: &Path) -> Result<ModelValidation> {
+    if !path.exists() {
+        return Err(DetectionError::NotFound(format!("模型文件不存在: {}", path.display())).into());
+    }
+    let data = std::fs::read(path).map_err(|e| anyhow!("读取模型文件失败 {}: {}", path.display(), e))?;
+    let model = ModelProto::decode(data.as_slice()).map_err(|e| anyhow!("解析ONNX模型失败: {}", e))?;
+
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    if model.graph.is_none() {
+        errors.push("ONNX模型缺少计算图".to_string());
+    }
+
+    let opset = model_meta::opset_version(&model);
+    match opset {
+        None => warnings.push("未声明默认域的算子集版本".to_string()),
+        Some(version) if version < MIN_OPSET => {
+            errors.push(format!("算子集版本 {} 过低，至少需要 {}", version, MIN_OPSET))
+        }
+        Some(version) if version > MAX_TESTED_OPSET => warnings.push(format!(
+            "算子集版本 {} 高于已验证的 {}，可能包含Candle暂不支持的算子",
+            version, MAX_TESTED_OPSET
+        )),
+        Some(_) => {}
+    }
+
+    let shape = model_meta::inspect(&model);
+    match shape.input_dims.as_slice() {
+        [] => errors.push("无法读取模型输入形状".to_string()),
+        [_, Some(channels), _, _] if *channels != 3 => {
+            errors.push(format!("模型输入通道数为 {}，期望 3 (RGB)", channels))
+        }
+        [_, _, _, _] => {}
+        dims => errors.push(format!("模型输入维度为 {}，期望 [batch, 3, 高, 宽]", dims.len())),
+    }
+    if shape.output_dims.len() != 3 {
+        errors.push(format!(
+            "不支持的模型输出形状 {}，期望 [1, 4+类别数, 锚点数]",
+            shape.describe_output()
+        ));
+    }
+    if shape.input_size.is_none() {
+        warnings.push("模型输入宽高为动态维度，将使用元数据中的尺寸或配置的输入尺寸".to_string());
+    }
+
+    let class_names: Vec<String> = model_meta::metadata_class_names(&model)
+        .map(|names| names.into_values().collect())
+        .unwrap_or_default();
+    match shape.num_classes {
+        None => warnings.push("无法从输出形状推断类别数".to_string()),
+        Some(count) if !class_names.is_empty() && class_names.len() != count => warnings.push(format!(
+            "模型元数据中有 {} 个类别名称，输出形状对应 {} 个类别",
+            class_names.len(),
+            count
+        )),
+        Some(_) => {}
+    }
+
+    Ok(ModelValidation {
+        path: path.to_string_lossy().to_string(),
+        valid: errors.is_empty(),
+        ir_version: model.ir_version,
+        opset,
+        producer: format!("{} {}", model.producer_name, model.producer_version).trim().to_string(),
+        input_shape: shape.describe_input(),
+        output_shape: shape.describe_output(),
+        input_size: shape.input_size.or_else(|| model_meta::metadata_input_size(&model)),
+        num_classes: shape.num_classes,
+        class_names,
+        errors,
+        warnings,
+    })
+}
+
+/// 用空白图片执行完整的检测流程（预处理、推理、后处理），避免首帧推理过慢
+///
+/// 每次使用不同灰度的图片，避免命中预处理缓存
+async fn warm_up(detector: &dyn Detector, iterations: u32) -> Result<WarmupReport> {
+    let iterations = iterations.max(1);
+    let mut timings = Vec::with_capacity(iterations as usize);
+    for i in 0..iterations {
+        let gray = 114u8.wrapping_add(i as u8);
+        let blank = RgbImage::from_pixel(WARMUP_IMAGE_SIZE, WARMUP_IMAGE_SIZE, Rgb([gray, gray, gray]));
+        let mut encoded = Vec::new();
+        DynamicImage::ImageRgb8(blank).write_to(&mut Cursor::new(&mut encoded), ImageFormat::Png)?;
+
+        let start = std::time::Instant::now();
+        detector.detect_image(&encoded).await?;
+        timings.push(start.elapsed().as_millis() as u64);
+    }
+    Ok(WarmupReport {
+        iterations,
+        first_ms: timings[0],
+        last_ms: timings[timings.len() - 1],
+        avg_ms: timings.iter().sum::<u64>() as f64 / timings.len() as f64,
+    })
+}
+
+/// 预热检测器并更新就绪状态（未加载模型时为未就绪）
+pub async fn warm_up_and_mark(detector: &dyn Detector, readiness: &ModelReadiness, iterations: u32) -> Option<WarmupReport> {
+    if !detection_config::model_loaded(detector) {
+        readiness.set_warmed(None, None);
+        return None;
+    }
+    let model_path = detector.get_model_info().get("model_path").cloned();
+    let report = match warm_up(detector, iterations).await {
+        Ok(report) => {
+            tracing::info!("🔥 模型预热完成: {} 次, 首次 {} ms, 平均 {:.1} ms", report.iterations, report.first_ms, report.avg_ms);
+            Some(report)
+        }
+        Err(e) => {
+            tracing::warn!("⚠️  模型预热失败: {}", e);
+            None
+        }
+    };
+    readiness.set_warmed(model_path, report.clone());
+    report
+}
+
+/// 创建指定后端的检测器、加载模型并应用检测配置（不影响当前检测器）
+pub async fn create_loaded(backend: InferenceBackend, config: &DetectionConfig, model_path: &str) -> Result<Box<dyn Detector>> {
+    let mut detector = yolo::create_detector(backend);
+    detector.init_model(model_path).await?;
+    if let Err(e) = detection_config::apply(detector.as_mut(), config).await {
+        tracing::error!("检测配置应用失败: {}", e);
+    }
+    Ok(detector)
+}
+
+/// 加载模型并替换当前检测器，返回新模型的信息（加载失败时保留原模型）
+///
+/// 新检测器沿用当前的推理后端，加载后应用检测配置并预热，
+/// 只有最后的替换需要检测器锁，因此可在实时检测进行中调用
+pub async fn load_model(
+    state: &AppState,
+    readiness: &ModelReadiness,
+    config: &DetectionConfig,
+    model_path: &str,
+) -> Result<HashMap<String, String>> {
+    let backend = state.read().await.backend();
+    readiness.set_loading(true);
+    let next = match create_loaded(backend, config, model_path).await {
+        Ok(detector) => detector,
+        Err(e) => {
+            readiness.set_loading(false);
+            return Err(e);
+        }
+    };
+    // 预热推理不计入统计
+    warm_up_and_mark(next.as_ref(), readiness, 1).await;
+    next.reset_stats().await;
+
+    let info = next.get_model_info();
+    *state.write().await = next;
+    tracing::info!("🔄 已切换到模型: {}", model_path);
+    Ok(info)
+}
+
+impl ModelRegistry {
+    /// 读取已登记的模型
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(REGISTRY_FILE_NAME);
+        let registry: RegistryFile = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            models_dir: data_dir.join(MODELS_DIR),
+            registry: RwLock::new(registry),
+        }
+    }
+
+    /// 受管理的模型目录（下载的模型保存于此）
+    pub fn models_dir(&self) -> &Path {
+        &self.models_dir
+    }
+
+    fn save(&self, registry: &RegistryFile) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(registry)?)?;
+        Ok(())
+    }
+
+    fn entry(&self, model: &RegisteredModel, active: Option<&str>) -> ModelEntry {
+        let metadata = std::fs::metadata(&model.path).ok();
+        ModelEntry {
+            model: model.clone(),
+            exists: metadata.is_some(),
+            size_bytes: metadata.as_ref().map(|m| m.len()),
+            modified_at: metadata
+                .and_then(|m| m.modified().ok())
+                .map(|time| chrono::DateTime::<chrono::Local>::from(time).to_rfc3339()),
+            active: active == Some(model.id.as_str()),
+        }
+    }
+
+    /// 所有已登记的模型
+    pub fn entries(&self) -> Vec<ModelEntry> {
+        let registry = self.registry.read();
+        registry
+            .models
+            .iter()
+            .map(|model| self.entry(model, registry.active_model.as_deref()))
+            .collect()
+    }
+
+    /// 登记模型文件（已登记时返回原记录）
+    pub fn register(&self, path: &Path, name: Option<String>) -> Result<RegisteredModel> {
+        if !is_onnx(path) {
+            return Err(DetectionError::UnsupportedFormat(format!("只支持ONNX格式模型文件: {}", path.display())).into());
+        }
+        let path = path
+            .canonicalize()
+            .map_err(|e| anyhow!("模型文件不存在 {}: {}", path.display(), e))?;
+        let path_str = path.to_string_lossy().to_string();
+
+        let mut registry = self.registry.write();
+        if let Some(existing) = registry.models.iter().find(|m| m.path == path_str) {
+            return Ok(existing.clone());
+        }
+
+        let base = model_id_base(&path);
+        let mut id = base.clone();
+        let mut suffix = 2;
+        while registry.models.iter().any(|m| m.id == id) {
+            id = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+        let model = RegisteredModel {
+            id,
+            name: name
+                .filter(|n| !n.trim().is_empty())
+                .unwrap_or_else(|| path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()),
+            path: path_str,
+            registered_at: now_rfc3339(),
+        };
+        let mut updated = registry.clone();
+        updated.models.push(model.clone());
+        self.save(&updated)?;
+        *registry = updated;
+        tracing::info!("📦 已登记模型: {} ({})", model.id, model.path);
+        Ok(model)
+    }
+
+    /// 扫描目录（未指定时为受管理的模型目录）下的ONNX模型并登记
+    pub fn scan(&self, dir: Option<&Path>) -> Result<Vec<ModelEntry>> {
+        let dir = dir.unwrap_or(&self.models_dir);
+        let read_dir = match std::fs::read_dir(dir) {
+            Ok(read_dir) => read_dir,
+            // 受管理的模型目录尚未创建
+            Err(_) if dir == self.models_dir.as_path() => return Ok(self.entries()),
+            Err(e) => return Err(anyhow!("读取模型目录失败 {}: {}", dir.display(), e)),
+        };
+        let mut paths: Vec<PathBuf> = read_dir
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && is_onnx(path))
+            .collect();
+        paths.sort();
+        for path in paths {
+            self.register(&path, None)?;
+        }
+        Ok(self.entries())
+    }
+
+    fn find(&self, id: &str) -> Option<RegisteredModel> {
+        self.registry.read().models.iter().find(|m| m.id == id).cloned()
+    }
+
+    /// 已登记模型的ID解析为模型路径，否则按文件路径处理
+    pub fn resolve_path(&self, id_or_path: &str) -> String {
+        self.find(id_or_path).map_or_else(|| id_or_path.to_string(), |model| model.path)
+    }
+
+    fn set_active(&self, id: &str) -> Result<()> {
+        let mut registry = self.registry.write();
+        let mut updated = registry.clone();
+        updated.active_model = Some(id.to_string());
+        self.save(&updated)?;
+        *registry = updated;
+        Ok(())
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 扫描目录并列出已登记的模型（默认扫描受管理的模型目录）
+#[tauri::command]
+pub async fn list_models(
+    registry: State<'_, ModelRegistry>,
+    dir: Option<String>
+) -> Result<Vec<ModelEntry>, DetectionError> {
+    match registry.scan(dir.as_deref().map(Path::new)) {
+        Ok(models) => Ok(models),
+        Err(e) => Err(DetectionError::from(e).context("列出模型失败")),
+    }
+}
+
+/// 校验模型文件（算子集版本、输入/输出形状、类别数）
+#[tauri::command]
+pub async fn validate_model(path: String) -> Result<ModelValidation, DetectionError> {
+    match tauri::async_runtime::spawn_blocking(move || validate(Path::new(&path))).await {
+        Ok(Ok(validation)) => Ok(validation),
+        Ok(Err(e)) => Err(DetectionError::from(e).context("校验模型失败")),
+        Err(e) => Err(DetectionError::from(e).context("校验模型失败")),
+    }
+}
+
+/// 切换当前使用的模型（加载成功后重新应用检测配置），返回模型信息
+#[tauri::command]
+pub async fn set_active_model(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    registry: State<'_, ModelRegistry>,
+    readiness: State<'_, ModelReadiness>,
+    id: String
+) -> Result<HashMap<String, String>, DetectionError> {
+    let Some(model) = registry.find(&id) else {
+        return Err(DetectionError::NotFound(format!("模型不存在: {}", id)));
+    };
+
+    let info = match load_model(&state, &readiness, &store.get(), &model.path).await {
+        Ok(info) => info,
+        Err(e) => return Err(DetectionError::from(e).context("加载模型失败")),
+    };
+    if let Err(e) = registry.set_active(&model.id) {
+        return Err(DetectionError::from(e).context("保存当前模型失败"));
+    }
+    Ok(info)
+}
+
+/// 用空白图片预热当前模型（默认3次），返回各次耗时
+#[tauri::command]
+pub async fn warmup_model(
+    state: State<'_, AppState>,
+    readiness: State<'_, ModelReadiness>,
+    iterations: Option<u32>
+) -> Result<WarmupReport, DetectionError> {
+    let iterations = iterations.unwrap_or(DEFAULT_WARMUP_ITERATIONS).clamp(1, MAX_WARMUP_ITERATIONS);
+    let detector = state.read().await;
+    if !detection_config::model_loaded(detector.as_ref()) {
+        return Err(DetectionError::ModelNotLoaded);
+    }
+    match warm_up_and_mark(detector.as_ref(), &readiness, iterations).await {
+        Some(report) => Ok(report),
+        None => Err(DetectionError::Internal("模型预热失败，请检查模型是否可用".to_string())),
+    }
+}
+
+/// 获取模型就绪状态（model_ready 为 true 时可开始检测）
+#[tauri::command]
+pub async fn get_model_readiness(
+    readiness: State<'_, ModelReadiness>
+) -> Result<ReadinessStatus, DetectionError> {
+    Ok(readiness.status())
+}
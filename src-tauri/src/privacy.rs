@@ -0,0 +1,168 @@
+/*!
+隐私遮挡模块
+在画面上配置需要遮挡的区域（人脸出现的工位、保密工装夹具等），输出画面在绘制检测框之前
+对这些区域做模糊或涂黑，保证敏感内容不会离开本机：实时画面、多窗口查看、HTTP接口返回图与报告缩略图均已遮挡。
+推理始终使用未遮挡的原始画面，不影响检测效果；开启 apply_to_artifacts 后，
+保存到磁盘的检测产物（标注图、裁剪图）与黑匣子/事件录像帧同样遮挡
+
+区域顶点与检测区域一样使用归一化坐标（0-1），同一配置适用于不同分辨率的输入源
+*/
+
+use std::borrow::Cow;
+
+use anyhow::{anyhow, Result};
+use image::{DynamicImage, RgbImage};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::detection_config::{self, ConfigStore};
+use crate::error::DetectionError;
+use crate::yolo::roi::RoiPolygon;
+
+/// 默认模糊强度（高斯模糊 sigma，单位像素）
+const DEFAULT_BLUR_SIGMA: f32 = 12.0;
+
+/// 遮挡方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MaskMode {
+    #[default]
+    Blur,     // 高斯模糊
+    Blackout, // 涂黑
+}
+
+/// 一个遮挡区域
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrivacyRegion {
+    #[serde(flatten)]
+    pub polygon: RoiPolygon, // 名称与归一化顶点
+    #[serde(default)]
+    pub mode: MaskMode,
+}
+
+/// 隐私遮挡配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacyMasks {
+    pub regions: Vec<PrivacyRegion>,
+    pub apply_to_artifacts: bool, // 保存到磁盘的产物与录像帧同样遮挡
+    pub blur_sigma: f32,          // 模糊强度
+}
+
+impl Default for PrivacyMasks {
+    fn default() -> Self {
+        Self {
+            regions: Vec::new(),
+            apply_to_artifacts: true,
+            blur_sigma: DEFAULT_BLUR_SIGMA,
+        }
+    }
+}
+
+impl PrivacyMasks {
+    pub fn validate(&self) -> Result<()> {
+        for (index, region) in self.regions.iter().enumerate() {
+            let polygon = &region.polygon;
+            let label = if polygon.name.is_empty() { format!("#{}", index + 1) } else { polygon.name.clone() };
+            if polygon.points.len() < 3 {
+                return Err(anyhow!("遮挡区域 {} 至少需要3个顶点", label));
+            }
+            if polygon
+                .points
+                .iter()
+                .any(|[x, y]| !(0.0..=1.0).contains(x) || !(0.0..=1.0).contains(y))
+            {
+                return Err(anyhow!("遮挡区域 {} 的顶点坐标须为 0-1 之间的归一化坐标", label));
+            }
+        }
+        if !(self.blur_sigma.is_finite() && self.blur_sigma > 0.0) {
+            return Err(anyhow!("模糊强度必须为正数: {}", self.blur_sigma));
+        }
+        Ok(())
+    }
+
+    /// 在图像上遮挡全部区域
+    pub fn apply(&self, image: &mut RgbImage) {
+        let (width, height) = image.dimensions();
+        if width == 0 || height == 0 {
+            return;
+        }
+        for region in &self.regions {
+            let points = region.polygon.to_pixels(width, height);
+            // 多边形的外接矩形（像素）
+            let left = points.iter().map(|p| p.0).fold(f32::MAX, f32::min).max(0.0) as u32;
+            let top = points.iter().map(|p| p.1).fold(f32::MAX, f32::min).max(0.0) as u32;
+            let right = (points.iter().map(|p| p.0).fold(0.0, f32::max).ceil() as u32).min(width);
+            let bottom = (points.iter().map(|p| p.1).fold(0.0, f32::max).ceil() as u32).min(height);
+            if right <= left || bottom <= top {
+                continue;
+            }
+            let blurred = match region.mode {
+                MaskMode::Blur => Some(image::imageops::blur(
+                    &image::imageops::crop_imm(image, left, top, right - left, bottom - top).to_image(),
+                    self.blur_sigma,
+                )),
+                MaskMode::Blackout => None,
+            };
+            for y in top..bottom {
+                for x in left..right {
+                    // 按像素中心判断是否在区域内
+                    let nx = (x as f32 + 0.5) / width as f32;
+                    let ny = (y as f32 + 0.5) / height as f32;
+                    if !region.polygon.contains(nx, ny) {
+                        continue;
+                    }
+                    let pixel = match &blurred {
+                        Some(blurred) => *blurred.get_pixel(x - left, y - top),
+                        None => image::Rgb([0, 0, 0]),
+                    };
+                    image.put_pixel(x, y, pixel);
+                }
+            }
+        }
+    }
+}
+
+/// 遮挡输出画面（绘制检测框之前调用）
+pub fn mask_output(image: &mut RgbImage) {
+    let masks = detection_config::privacy_masks();
+    if !masks.regions.is_empty() {
+        masks.apply(image);
+    }
+}
+
+/// 保存到磁盘的图像：配置了遮挡区域且 apply_to_artifacts 开启时返回遮挡后的副本
+pub fn for_storage(image: &DynamicImage) -> Cow<'_, DynamicImage> {
+    let masks = detection_config::privacy_masks();
+    if masks.regions.is_empty() || !masks.apply_to_artifacts {
+        return Cow::Borrowed(image);
+    }
+    let mut masked = image.to_rgb8();
+    masks.apply(&mut masked);
+    Cow::Owned(DynamicImage::ImageRgb8(masked))
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 获取隐私遮挡配置
+#[tauri::command]
+pub async fn get_privacy_masks(
+    store: State<'_, ConfigStore>
+) -> Result<PrivacyMasks, DetectionError> {
+    Ok(store.get().privacy)
+}
+
+/// 设置隐私遮挡区域（立即生效，区域为空时不遮挡）
+#[tauri::command]
+pub async fn set_privacy_masks(
+    store: State<'_, ConfigStore>,
+    masks: PrivacyMasks
+) -> Result<PrivacyMasks, DetectionError> {
+    match store.update(|saved| saved.privacy = masks) {
+        Ok(saved) => {
+            tracing::info!("🕶️ 隐私遮挡区域已更新: {} 个", saved.privacy.regions.len());
+            Ok(saved.privacy)
+        }
+        Err(e) => Err(DetectionError::from(e).context("保存隐私遮挡配置失败")),
+    }
+}
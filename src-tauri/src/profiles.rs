@@ -0,0 +1,280 @@
+/*!
+检测配置档案模块
+命名档案保存一组检测参数（模型、各类别置信度阈值、启用的类别、检测区域、NMS参数），保存在应用配置目录下的JSON文件中。
+档案可绑定到输入源（键见 `source_key`：摄像头、网络视频流、视频文件或监控的文件夹），启动该输入源的检测时自动加载；
+当前加载的档案名称随每次检测运行记录到历史中
+*/
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::detection_config::{self, ConfigStore};
+use crate::error::{self, DetectionError};
+use crate::models::{self, ModelReadiness};
+use crate::source_lock;
+use crate::storage::now_rfc3339;
+use crate::yolo::nms::NmsConfig;
+use crate::yolo::roi::{self, RoiPolygon};
+use crate::yolo::InputSource;
+use crate::yolo_api::DetectionConfig;
+use crate::AppState;
+
+/// 档案文件名（位于应用配置目录）
+pub const PROFILES_FILE_NAME: &str = "profiles.json";
+
+/// 档案名称最大长度
+const MAX_NAME_LEN: usize = 64;
+
+/// 检测配置档案
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionProfile {
+    pub name: String,
+    #[serde(default)]
+    pub model_path: Option<String>, // 为空时沿用当前模型
+    #[serde(default)]
+    pub confidence_thresholds: HashMap<String, f32>,
+    #[serde(default)]
+    pub selected_classes: Vec<String>,
+    #[serde(default)]
+    pub roi: Vec<RoiPolygon>,
+    #[serde(default)]
+    pub nms: NmsConfig,
+    #[serde(default)]
+    pub sources: Vec<String>, // 绑定的输入源键
+    pub updated_at: String,
+}
+
+impl DetectionProfile {
+    fn validate(&self) -> Result<()> {
+        let name = self.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(anyhow!("档案名称不能为空且不超过 {} 个字符", MAX_NAME_LEN));
+        }
+        if let Some((class_name, threshold)) = self
+            .confidence_thresholds
+            .iter()
+            .find(|(_, t)| !(0.0..=1.0).contains(*t))
+        {
+            return Err(anyhow!("类别 {} 的置信度阈值必须在 0-1 之间: {}", class_name, threshold));
+        }
+        self.nms.validate()?;
+        roi::validate(&self.roi)
+    }
+
+    /// 把档案中的检测参数写入检测配置，其余配置项不变
+    fn merge_into(&self, config: &mut DetectionConfig) {
+        config.confidence_thresholds = self.confidence_thresholds.clone();
+        config.selected_classes = self.selected_classes.clone();
+        config.roi = self.roi.clone();
+        config.nms = self.nms.clone();
+    }
+}
+
+/// 档案列表与当前加载的档案
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileList {
+    pub profiles: Vec<DetectionProfile>,
+    #[serde(default)]
+    pub active: Option<String>,
+}
+
+/// 档案存储（Tauri托管状态）
+pub struct ProfileStore {
+    path: PathBuf,
+    list: RwLock<ProfileList>,
+}
+
+/// 输入源的绑定键：摄像头与网络视频流沿用输入源锁的键，文件使用其路径
+pub fn source_key(source: &InputSource) -> String {
+    match source {
+        InputSource::Camera(device_id) => source_lock::camera_key(*device_id),
+        InputSource::Rtsp(url) => source_lock::stream_key(url),
+        InputSource::Video(path) | InputSource::Image(path) => path.clone(),
+    }
+}
+
+impl ProfileStore {
+    /// 读取已保存的档案，文件不存在或无效时为空
+    pub fn load(config_dir: &Path) -> Self {
+        let path = config_dir.join(PROFILES_FILE_NAME);
+        let list = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str::<ProfileList>(&content).unwrap_or_else(|e| {
+                tracing::error!("档案文件无效，已忽略 {}: {}", path.display(), e);
+                ProfileList::default()
+            }),
+            Err(_) => ProfileList::default(),
+        };
+        Self {
+            path,
+            list: RwLock::new(list),
+        }
+    }
+
+    pub fn list(&self) -> ProfileList {
+        self.list.read().clone()
+    }
+
+    /// 当前加载的档案名称
+    pub fn active(&self) -> Option<String> {
+        self.list.read().active.clone()
+    }
+
+    pub fn get(&self, name: &str) -> Option<DetectionProfile> {
+        self.list.read().profiles.iter().find(|p| p.name == name).cloned()
+    }
+
+    /// 绑定到该输入源的档案
+    pub fn bound_to(&self, key: &str) -> Option<DetectionProfile> {
+        self.list
+            .read()
+            .profiles
+            .iter()
+            .find(|p| p.sources.iter().any(|s| s == key))
+            .cloned()
+    }
+
+    /// 新增或覆盖同名档案；一个输入源只能绑定一个档案，其他档案上的相同绑定被移除
+    fn upsert(&self, profile: DetectionProfile) -> Result<DetectionProfile> {
+        profile.validate().map_err(error::invalid_input)?;
+        let mut list = self.list();
+        for other in list.profiles.iter_mut().filter(|p| p.name != profile.name) {
+            other.sources.retain(|s| !profile.sources.contains(s));
+        }
+        match list.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile.clone(),
+            None => list.profiles.push(profile.clone()),
+        }
+        self.save(list)?;
+        Ok(profile)
+    }
+
+    fn set_active(&self, name: Option<String>) -> Result<()> {
+        let mut list = self.list();
+        list.active = name;
+        self.save(list)
+    }
+
+    fn save(&self, list: ProfileList) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&list)?)?;
+        *self.list.write() = list;
+        Ok(())
+    }
+}
+
+/// 加载档案：写入检测配置，档案指定的模型与当前模型不同时切换模型，否则直接应用到检测器
+pub async fn apply_profile(
+    state: &AppState,
+    store: &ConfigStore,
+    readiness: &ModelReadiness,
+    profiles: &ProfileStore,
+    profile: &DetectionProfile,
+) -> Result<DetectionConfig> {
+    let config = store.update(|config| profile.merge_into(config))?;
+    let current_model = {
+        let info = state.read().await.get_model_info();
+        info.get("model_path").cloned().filter(|_| info.get("model_loaded").map(String::as_str) == Some("true"))
+    };
+    let effective = match &profile.model_path {
+        Some(path) if current_model.as_deref() != Some(path.as_str()) => {
+            models::load_model(state, readiness, &config, path).await?;
+            config
+        }
+        _ => detection_config::apply(state.write().await.as_mut(), &config).await?,
+    };
+    profiles.set_active(Some(profile.name.clone()))?;
+    tracing::info!("📋 已加载检测档案: {}", profile.name);
+    Ok(effective)
+}
+
+/// 启动输入源检测前加载绑定到该输入源的档案（未绑定时不做改动）
+pub async fn apply_bound(app: &AppHandle, key: &str) -> Result<()> {
+    let profiles = app.state::<ProfileStore>();
+    let Some(profile) = profiles.bound_to(key) else {
+        return Ok(());
+    };
+    if profiles.active().as_deref() == Some(profile.name.as_str()) {
+        return Ok(());
+    }
+    apply_profile(
+        app.state::<AppState>().inner(),
+        &app.state::<ConfigStore>(),
+        &app.state::<ModelReadiness>(),
+        &profiles,
+        &profile,
+    )
+    .await
+    .map(|_| ())
+    .map_err(|e| anyhow!("加载输入源 {} 绑定的档案 {} 失败: {}", key, profile.name, e))
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 把当前检测配置与模型保存为命名档案；传入 sources 时更新绑定的输入源，否则保留原有绑定
+#[tauri::command]
+pub async fn save_profile(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    profiles: State<'_, ProfileStore>,
+    name: String,
+    sources: Option<Vec<String>>
+) -> Result<DetectionProfile, DetectionError> {
+    let name = name.trim().to_string();
+    let config = store.get();
+    let model_path = {
+        let detector = state.read().await;
+        detection_config::model_loaded(detector.as_ref())
+            .then(|| detector.get_model_info().get("model_path").cloned())
+            .flatten()
+    };
+    let sources = sources
+        .or_else(|| profiles.get(&name).map(|existing| existing.sources))
+        .unwrap_or_default();
+    let profile = DetectionProfile {
+        name,
+        model_path,
+        confidence_thresholds: config.confidence_thresholds,
+        selected_classes: config.selected_classes,
+        roi: config.roi,
+        nms: config.nms,
+        sources,
+        updated_at: now_rfc3339(),
+    };
+    match profiles.upsert(profile) {
+        Ok(profile) => Ok(profile),
+        Err(e) => Err(DetectionError::from(e).context("保存档案失败")),
+    }
+}
+
+/// 加载命名档案并应用到检测器，返回实际生效的检测配置
+#[tauri::command]
+pub async fn load_profile(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    readiness: State<'_, ModelReadiness>,
+    profiles: State<'_, ProfileStore>,
+    name: String
+) -> Result<DetectionConfig, DetectionError> {
+    let Some(profile) = profiles.get(&name) else {
+        return Err(DetectionError::NotFound(format!("档案不存在: {}", name)));
+    };
+    match apply_profile(&state, &store, &readiness, &profiles, &profile).await {
+        Ok(config) => Ok(config),
+        Err(e) => Err(DetectionError::from(e).context("加载档案失败")),
+    }
+}
+
+/// 列出全部档案与当前加载的档案
+#[tauri::command]
+pub async fn list_profiles(
+    profiles: State<'_, ProfileStore>
+) -> Result<ProfileList, DetectionError> {
+    Ok(profiles.list())
+}
@@ -0,0 +1,191 @@
+/*!
+性能剖析模块
+开启剖析后，检测管线的每个阶段都包裹在 tracing span 中（带耗时字段），
+并把指定帧数窗口内的span写出为 Chrome Trace 文件（可用 chrome://tracing 或 Perfetto 打开），
+用于分析客户现场机器慢在哪个环节
+*/
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use tracing::Instrument;
+use tracing_chrome::{ChromeLayer, ChromeLayerBuilder, FlushGuard};
+use tracing_subscriber::{reload, Registry};
+
+use crate::error::DetectionError;
+
+pub type ChromeSlot = Option<ChromeLayer<Registry>>;
+
+/// 剖析状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfilingStatus {
+    pub active: bool,
+    pub output_path: Option<String>,
+    pub target_frames: u32,
+    pub recorded_frames: u32,
+    pub started_at: Option<String>,
+    pub last_trace: Option<String>, // 最近一次完成的trace文件
+}
+
+struct ActiveProfile {
+    guard: FlushGuard,
+    output_path: PathBuf,
+    target_frames: u32,
+    recorded_frames: u32,
+    started_at: String,
+}
+
+/// 剖析器（Tauri托管状态）
+pub struct Profiler {
+    handle: reload::Handle<ChromeSlot, Registry>,
+    active: Mutex<Option<ActiveProfile>>,
+    last_trace: Mutex<Option<String>>,
+}
+
+/// 创建剖析层（初始为空，开启剖析时再挂载），由 `logging::init` 安装到全局订阅者
+pub fn layer() -> (reload::Layer<ChromeSlot, Registry>, Profiler) {
+    let (layer, handle) = reload::Layer::new(ChromeSlot::None);
+    let profiler = Profiler {
+        handle,
+        active: Mutex::new(None),
+        last_trace: Mutex::new(None),
+    };
+    (layer, profiler)
+}
+
+impl Profiler {
+    /// 开始剖析，记录接下来 frames 帧
+    pub fn start(&self, output_path: PathBuf, frames: u32) -> Result<()> {
+        let mut active = self.active.lock();
+        if active.is_some() {
+            return Err(anyhow!("剖析已在进行中"));
+        }
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let (layer, guard) = ChromeLayerBuilder::new()
+            .file(&output_path)
+            .include_args(true)
+            .build();
+        self.handle
+            .reload(Some(layer))
+            .map_err(|e| anyhow!("挂载剖析层失败: {}", e))?;
+
+        tracing::info!("⏱️ 开始性能剖析: {} 帧 -> {}", frames, output_path.display());
+        *active = Some(ActiveProfile {
+            guard,
+            output_path,
+            target_frames: frames.max(1),
+            recorded_frames: 0,
+            started_at: crate::storage::now_rfc3339(),
+        });
+        Ok(())
+    }
+
+    /// 结束剖析并写出trace文件，返回文件路径
+    pub fn stop(&self) -> Option<String> {
+        let profile = self.active.lock().take()?;
+        let _ = self.handle.reload(None);
+        profile.guard.flush();
+        drop(profile.guard);
+
+        let path = profile.output_path.to_string_lossy().to_string();
+        tracing::info!("⏱️ 性能剖析完成: {} 帧 -> {}", profile.recorded_frames, path);
+        *self.last_trace.lock() = Some(path.clone());
+        Some(path)
+    }
+
+    /// 一帧处理完成，达到目标帧数时自动结束
+    pub fn frame_finished(&self) {
+        let done = {
+            let mut active = self.active.lock();
+            match active.as_mut() {
+                Some(profile) => {
+                    profile.recorded_frames += 1;
+                    profile.recorded_frames >= profile.target_frames
+                }
+                None => false,
+            }
+        };
+        if done {
+            self.stop();
+        }
+    }
+
+    pub fn status(&self) -> ProfilingStatus {
+        let active = self.active.lock();
+        let last_trace = self.last_trace.lock().clone();
+        match active.as_ref() {
+            Some(profile) => ProfilingStatus {
+                active: true,
+                output_path: Some(profile.output_path.to_string_lossy().to_string()),
+                target_frames: profile.target_frames,
+                recorded_frames: profile.recorded_frames,
+                started_at: Some(profile.started_at.clone()),
+                last_trace,
+            },
+            None => ProfilingStatus {
+                last_trace,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// 在带耗时字段的span中执行异步阶段
+pub async fn stage<F: Future>(name: &'static str, fut: F) -> F::Output {
+    let span = tracing::info_span!("stage", stage = name, elapsed_ms = tracing::field::Empty);
+    let start = Instant::now();
+    let output = fut.instrument(span.clone()).await;
+    span.record("elapsed_ms", start.elapsed().as_secs_f64() * 1000.0);
+    output
+}
+
+/// 在带耗时字段的span中执行同步阶段
+pub fn stage_sync<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let span = tracing::info_span!("stage", stage = name, elapsed_ms = tracing::field::Empty);
+    let start = Instant::now();
+    let output = span.in_scope(f);
+    span.record("elapsed_ms", start.elapsed().as_secs_f64() * 1000.0);
+    output
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 开始性能剖析
+#[tauri::command]
+pub async fn start_profiling(
+    profiler: State<'_, Profiler>,
+    output_path: String,
+    frames: Option<u32>
+) -> Result<ProfilingStatus, DetectionError> {
+    match profiler.start(PathBuf::from(output_path), frames.unwrap_or(100)) {
+        Ok(()) => Ok(profiler.status()),
+        Err(e) => Err(DetectionError::from(e).context("启动性能剖析失败")),
+    }
+}
+
+/// 提前结束性能剖析
+#[tauri::command]
+pub async fn stop_profiling(
+    profiler: State<'_, Profiler>
+) -> Result<String, DetectionError> {
+    match profiler.stop() {
+        Some(path) => Ok(path),
+        None => Err(DetectionError::NotFound("当前没有进行中的性能剖析".to_string())),
+    }
+}
+
+/// 获取性能剖析状态
+#[tauri::command]
+pub async fn get_profiling_status(
+    profiler: State<'_, Profiler>
+) -> Result<ProfilingStatus, DetectionError> {
+    Ok(profiler.status())
+}
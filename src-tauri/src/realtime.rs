@@ -0,0 +1,897 @@
+/*!
+实时检测管线模块
+采集线程从输入源读帧，异步任务逐帧推理、绘制检测框，同时写入黑匣子、触发告警并推送到监控窗口，
+检测会话进行中时推理结果同时写入检测历史。
+推理结果先分配跟踪ID，再经时间滤波去除闪烁的检测后才绘制、记录与告警，同时统计目标在各区域的停留时长。
+标注帧通过 `frame://annotated` 事件主动推送，前端处理完后调用 `ack_frame` 确认；
+未确认的帧超过上限时只保留最新一帧（丢弃旧帧），避免前端处理慢时帧无限积压。
+视频文件逐帧处理不丢帧，事件中附带处理进度
+*/
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use image::{DynamicImage, ImageFormat, RgbImage};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::adaptive_rate::AdaptiveRateController;
+use crate::artifacts::{ArtifactImage, ArtifactSettings};
+use crate::blackbox::BlackBoxRecorder;
+use crate::capture::{CameraProperties, FrameSource, VideoInfo};
+use crate::clips::{self, ClipEvent};
+use crate::detection_config;
+use crate::error::{self, DetectionError};
+use crate::event_recording::EventRecorder;
+use crate::frame_queue::{FrameQueue, FrameQueueConfig};
+use crate::history;
+use crate::inference_worker::InferenceWorker;
+use crate::profiles::ProfileStore;
+use crate::sessions::SessionManager;
+use crate::storage::Database;
+use crate::temporal_filter::TemporalFilter;
+use crate::tracking::TrackingManager;
+use crate::viewer;
+use crate::yolo::{DetectionResult, DetectionTimings, InputSource};
+use crate::yolo_api::{self, draw_detections_on_image, image_to_base64, Detection};
+use crate::zone_dwell::DwellMonitor;
+use crate::AppState;
+
+/// 标注帧推送事件
+pub const EVENT_ANNOTATED_FRAME: &str = "frame://annotated";
+
+/// 允许同时未确认的推送帧数
+const MAX_IN_FLIGHT: u32 = 2;
+
+/// 推送帧超过该时间未确认视为前端已丢弃，恢复推送
+const ACK_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 连续读帧失败达到该次数后结束采集
+const MAX_READ_FAILURES: u32 = 30;
+
+/// 读帧失败后的重试间隔
+const READ_RETRY_INTERVAL: Duration = Duration::from_millis(33);
+
+/// 视频暂停时采集线程检查控制请求的间隔
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// 等待采集线程应用摄像头参数的最长时间
+const PROPERTY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 滑动平均帧率的统计窗口
+const FPS_WINDOW: Duration = Duration::from_secs(5);
+
+/// 一帧实时检测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeFrame {
+    pub frame_index: u64,
+    pub image_data: Option<String>, // Base64编码的标注图像
+    pub detections: Vec<Detection>,
+    pub timestamp: String,
+    #[serde(default)]
+    pub held: bool, // 按抽帧设置跳过推理，检测框沿用上一次结果
+    #[serde(default)]
+    pub timings: Option<DetectionTimings>, // 各阶段耗时，沿用上一次结果的帧为空
+}
+
+/// 抽帧设置：每N帧或按目标检测帧率推理一次，其余帧沿用上一次检测框，
+/// 在性能较弱的CPU上保持画面流畅
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FrameSampling {
+    pub frame_stride: u32,                 // 每N帧检测一次，1表示逐帧检测
+    pub target_detection_fps: Option<f32>, // 目标检测帧率，视频文件按视频时间换算为帧间隔
+}
+
+impl Default for FrameSampling {
+    fn default() -> Self {
+        Self { frame_stride: 1, target_detection_fps: None }
+    }
+}
+
+impl FrameSampling {
+    pub fn validate(&self) -> Result<()> {
+        if self.frame_stride == 0 {
+            return Err(anyhow!("抽帧间隔必须大于0"));
+        }
+        if self.target_detection_fps.is_some_and(|fps| fps <= 0.0) {
+            return Err(anyhow!("目标检测帧率必须大于0"));
+        }
+        Ok(())
+    }
+}
+
+/// 抽帧状态（处理任务内部使用）
+#[derive(Default)]
+struct Sampler {
+    received: u64,
+    last_detect_at: Option<Instant>,
+    last_detect_position: Option<u64>,
+}
+
+impl Sampler {
+    /// 判断当前帧是否需要推理
+    fn should_detect(&mut self, sampling: &FrameSampling, video: Option<&VideoInfo>, position: Option<u64>) -> bool {
+        let index = self.received;
+        self.received += 1;
+
+        let detect = match (video, position) {
+            // 视频文件：目标帧率按视频帧率换算为帧间隔，与处理速度无关，跳转后立即检测
+            (Some(video), Some(position)) => {
+                let fps_stride = sampling
+                    .target_detection_fps
+                    .filter(|_| video.fps > 0.0)
+                    .map_or(1, |fps| (video.fps / fps as f64).round().max(1.0) as u64);
+                let stride = (sampling.frame_stride as u64).max(fps_stride);
+                match self.last_detect_position {
+                    Some(last) if position > last => position - last >= stride,
+                    _ => true,
+                }
+            }
+            _ => {
+                let stride_ok = index % sampling.frame_stride.max(1) as u64 == 0;
+                let fps_ok = match (sampling.target_detection_fps, self.last_detect_at) {
+                    (Some(fps), Some(last)) => last.elapsed().as_secs_f32() >= 1.0 / fps,
+                    _ => true,
+                };
+                stride_ok && fps_ok
+            }
+        };
+        if detect {
+            self.last_detect_at = Some(Instant::now());
+            self.last_detect_position = position;
+        }
+        detect
+    }
+}
+
+/// 处理帧率统计：瞬时帧率取最近两帧的间隔，滑动平均帧率取最近 `FPS_WINDOW` 内的帧数
+#[derive(Default)]
+struct FpsMeter {
+    last_frame_at: Option<Instant>,
+    last_interval: Option<Duration>,
+    recent: VecDeque<Instant>,
+}
+
+impl FpsMeter {
+    fn tick(&mut self) {
+        let now = Instant::now();
+        if let Some(last) = self.last_frame_at {
+            self.last_interval = Some(now - last);
+        }
+        self.last_frame_at = Some(now);
+        self.recent.push_back(now);
+        while self.recent.front().is_some_and(|t| now - *t > FPS_WINDOW) {
+            self.recent.pop_front();
+        }
+    }
+
+    /// 瞬时帧率，长时间没有新帧时随等待时间衰减
+    fn instant(&self) -> f32 {
+        match (self.last_frame_at, self.last_interval) {
+            (Some(last), Some(interval)) => {
+                let interval = interval.max(last.elapsed()).as_secs_f32();
+                if interval > 0.0 { 1.0 / interval } else { 0.0 }
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// 滑动平均帧率（启动不足一个窗口时按已运行时间计算）
+    fn rolling(&self, started_at: Instant) -> f32 {
+        let now = Instant::now();
+        let frames = self.recent.iter().filter(|t| now - **t <= FPS_WINDOW).count();
+        let span = started_at.elapsed().min(FPS_WINDOW).as_secs_f32();
+        if span > 0.0 { frames as f32 / span } else { 0.0 }
+    }
+}
+
+/// 最近一次推理结果，跳过的帧沿用
+#[derive(Clone, Default)]
+struct HeldResult {
+    result: Option<DetectionResult>,
+    total_inferences: u64,
+    avg_fps: f64,
+}
+
+/// 视频检测进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoProgress {
+    pub current_frame: u64,
+    pub total_frames: u64,
+    pub percent: f32,
+    pub position_ms: u64,
+    pub duration_ms: u64,
+    pub eta_seconds: Option<f64>, // 按当前处理速度估算的剩余时间
+    pub paused: bool,
+}
+
+/// 视频跳转目标：`{"frame": 120}` 或 `{"ms": 5000}`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeekTarget {
+    Frame(u64),
+    Ms(u64),
+}
+
+/// 推送给前端的标注帧
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotatedFrameEvent {
+    pub seq: u64, // 推送序号，确认时回传
+    pub source: String,
+    pub frame: RealtimeFrame,
+    pub progress: Option<VideoProgress>, // 视频文件检测进度
+}
+
+#[derive(Default)]
+struct PushState {
+    next_seq: u64,
+    in_flight: u32,
+    last_emit: Option<Instant>,
+    pending: Option<AnnotatedFrameEvent>, // 等待前端确认后推送的最新帧
+    dropped: u64,
+}
+
+/// 标注帧推送：按前端确认控制发送速率，积压时丢弃旧帧
+#[derive(Default)]
+struct FramePusher {
+    state: Mutex<PushState>,
+}
+
+impl FramePusher {
+    fn emit(app: &AppHandle, state: &mut PushState, event: AnnotatedFrameEvent) {
+        if let Err(e) = app.emit(EVENT_ANNOTATED_FRAME, event) {
+            tracing::error!("推送标注帧失败: {}", e);
+            return;
+        }
+        state.in_flight += 1;
+        state.last_emit = Some(Instant::now());
+    }
+
+    fn push(&self, app: &AppHandle, source: String, frame: RealtimeFrame, progress: Option<VideoProgress>) {
+        let mut state = self.state.lock();
+        if state.in_flight > 0 && state.last_emit.is_some_and(|t| t.elapsed() > ACK_TIMEOUT) {
+            state.in_flight = 0;
+        }
+        state.next_seq += 1;
+        let event = AnnotatedFrameEvent { seq: state.next_seq, source, frame, progress };
+        if state.in_flight < MAX_IN_FLIGHT {
+            Self::emit(app, &mut state, event);
+        } else if state.pending.replace(event).is_some() {
+            state.dropped += 1;
+        }
+    }
+
+    /// 前端确认一帧，有等待中的帧时立即推送
+    fn ack(&self, app: &AppHandle, seq: u64) {
+        let mut state = self.state.lock();
+        if seq > state.next_seq {
+            return;
+        }
+        state.in_flight = state.in_flight.saturating_sub(1);
+        if let Some(event) = state.pending.take() {
+            Self::emit(app, &mut state, event);
+        }
+    }
+
+    fn dropped(&self) -> u64 {
+        self.state.lock().dropped
+    }
+}
+
+/// 采集线程读到的一帧
+struct CapturedFrame {
+    position: Option<u64>, // 视频中的帧序号
+    captured_at: String,   // 读到该帧的时间
+    image: RgbImage,
+}
+
+/// 待采集线程应用的摄像头参数
+struct PropertyRequest {
+    properties: CameraProperties,
+    reply: Option<oneshot::Sender<Result<CameraProperties, String>>>,
+}
+
+struct PipelineShared {
+    source: InputSource,
+    session: String, // 黑匣子/事件录像会话名
+    stop: AtomicBool,
+    running: AtomicBool,
+    frame_count: AtomicU64,
+    detection_count: AtomicU64,
+    started_at: Instant,
+    fps_meter: Mutex<FpsMeter>,
+    capture_dropped: AtomicU64, // 推理繁忙或自适应降帧而未处理的采集帧
+    frames: FrameQueue<RealtimeFrame>, // 供轮询读取的标注帧
+    property_request: Mutex<Option<PropertyRequest>>,
+    camera_properties: Mutex<Option<CameraProperties>>, // 设备实际生效的采集参数
+    video: Option<VideoInfo>,
+    position: AtomicU64, // 最近处理的视频帧序号
+    paused: AtomicBool,
+    pending_steps: AtomicU32, // 暂停时待读取的单步帧数
+    seek_request: Mutex<Option<u64>>,
+    sampling: Mutex<FrameSampling>,
+    last_result: Mutex<HeldResult>,
+    latest_frame: Mutex<Option<Arc<DynamicImage>>>, // 最近处理的原始帧（供抓拍）
+    clip_events: Mutex<Vec<ClipEvent>>, // 视频文件中触发告警的时间点，结束后截取事件片段
+    pusher: FramePusher,
+}
+
+/// 抓拍时取得的当前画面
+pub struct LiveFrame {
+    pub session: String, // 输入源锁键（用于文件名）
+    pub source: String,
+    pub image: Arc<DynamicImage>,
+    pub result: Option<DetectionResult>, // 最近一次推理结果
+    pub track_ids: Vec<Option<u64>>,
+}
+
+impl PipelineShared {
+    /// 视频帧在视频中的时间（秒），实时输入或帧率未知时为 None
+    fn video_secs(&self, position: Option<u64>) -> Option<f64> {
+        match (self.video.as_ref(), position) {
+            (Some(video), Some(position)) if video.fps > 0.0 => Some(position as f64 / video.fps),
+            _ => None,
+        }
+    }
+
+    fn video_progress(&self) -> Option<VideoProgress> {
+        let video = self.video.as_ref()?;
+        let processed = self.frame_count.load(Ordering::Relaxed);
+        let current_frame = if processed == 0 { 0 } else { self.position.load(Ordering::Relaxed) + 1 };
+        let remaining = video.total_frames.saturating_sub(current_frame);
+
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        let rate = if elapsed > 0.0 { processed as f64 / elapsed } else { 0.0 };
+        Some(VideoProgress {
+            current_frame,
+            total_frames: video.total_frames,
+            percent: if video.total_frames > 0 {
+                (current_frame as f32 / video.total_frames as f32 * 100.0).min(100.0)
+            } else {
+                0.0
+            },
+            position_ms: if video.fps > 0.0 { (current_frame as f64 * 1000.0 / video.fps) as u64 } else { 0 },
+            duration_ms: video.duration_ms(),
+            eta_seconds: (rate > 0.0).then(|| remaining as f64 / rate),
+            paused: self.paused.load(Ordering::Relaxed),
+        })
+    }
+}
+
+/// 实时检测统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RealtimeStats {
+    pub is_running: bool,
+    pub input_source: Option<InputSource>,
+    pub frame_count: u64,
+    pub detection_count: u64,
+    pub fps: f32,         // 启动以来的平均处理帧率
+    pub instant_fps: f32, // 瞬时处理帧率
+    pub rolling_fps: f32, // 最近5秒的平均处理帧率
+    pub capture_dropped: u64, // 推理繁忙或自适应降帧而未处理的采集帧数
+    pub camera_properties: Option<CameraProperties>,
+    pub video_progress: Option<VideoProgress>,
+    pub push_dropped: u64, // 前端未及时确认而丢弃的推送帧数
+    pub queued_frames: usize,
+    pub dropped_frames: u64, // 帧队列按丢弃策略丢弃的帧数
+}
+
+/// 实时检测管线（Tauri托管状态），同一时间只运行一条管线
+#[derive(Default)]
+pub struct RealtimePipeline {
+    active: Mutex<Option<Arc<PipelineShared>>>,
+    camera_defaults: Mutex<CameraProperties>, // 启动采集时应用的摄像头参数
+    sampling: Mutex<FrameSampling>,
+    queue_config: Mutex<FrameQueueConfig>,
+}
+
+fn encode_jpeg(frame: &RgbImage) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    frame.write_to(&mut std::io::Cursor::new(&mut buffer), ImageFormat::Jpeg)?;
+    Ok(buffer)
+}
+
+/// 采集线程：阻塞读帧，实时输入处理不过来时丢弃新帧避免积压，视频文件等待处理完成不丢帧
+fn capture_loop(mut source: Box<dyn FrameSource>, shared: Arc<PipelineShared>, tx: mpsc::Sender<CapturedFrame>) {
+    let mut failures = 0;
+    let mut min_interval: Option<Duration> = None;
+    let mut last_sent: Option<Instant> = None;
+    while !shared.stop.load(Ordering::Relaxed) {
+        // 采集参数只能在采集线程中修改
+        let request = shared.property_request.lock().take();
+        if let Some(request) = request {
+            let result = source.apply_properties(&request.properties).map_err(|e| e.to_string());
+            match &result {
+                Ok(effective) => {
+                    if let Some(fps) = request.properties.fps {
+                        min_interval = Some(Duration::from_secs_f32(1.0 / fps.max(0.1)));
+                    }
+                    tracing::info!("📷 {} 采集参数已生效: {:?}", source.describe(), effective);
+                    *shared.camera_properties.lock() = Some(effective.clone());
+                }
+                Err(e) => tracing::error!("{} 设置采集参数失败: {}", source.describe(), e),
+            }
+            if let Some(reply) = request.reply {
+                let _ = reply.send(result);
+            }
+        }
+
+        // 视频跳转后清空旧结果，暂停中则解码目标帧供查看
+        let seek = shared.seek_request.lock().take();
+        if let Some(target) = seek {
+            match source.seek(target) {
+                Ok(()) => {
+                    shared.frames.clear();
+                    if shared.paused.load(Ordering::Relaxed) {
+                        shared.pending_steps.store(1, Ordering::Relaxed);
+                    }
+                }
+                Err(e) => tracing::error!("{}", e),
+            }
+        }
+        if shared.paused.load(Ordering::Relaxed) {
+            if shared.pending_steps.load(Ordering::Relaxed) == 0 {
+                std::thread::sleep(PAUSE_POLL_INTERVAL);
+                continue;
+            }
+            shared.pending_steps.fetch_sub(1, Ordering::Relaxed);
+        }
+
+        match source.read_frame() {
+            Ok(Some(frame)) => {
+                let captured_at = crate::storage::now_rfc3339();
+                failures = 0;
+                // 按目标帧率丢弃多余的帧（驱动不支持设置帧率时仍能限速）
+                if let (Some(interval), Some(last)) = (min_interval, last_sent) {
+                    if last.elapsed() < interval {
+                        continue;
+                    }
+                }
+                last_sent = Some(Instant::now());
+                // 设置了镜头标定时先做畸变校正，之后的检测、绘制与测量均基于校正后的画面
+                let frame = match detection_config::undistorter() {
+                    Some(undistorter) => undistorter.apply(&frame),
+                    None => frame,
+                };
+                let frame = CapturedFrame { position: source.position(), captured_at, image: frame };
+                if shared.video.is_some() {
+                    if tx.blocking_send(frame).is_err() {
+                        break;
+                    }
+                } else {
+                    match tx.try_send(frame) {
+                        Ok(()) => {}
+                        Err(mpsc::error::TrySendError::Full(_)) => {
+                            shared.capture_dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                        Err(mpsc::error::TrySendError::Closed(_)) => break,
+                    }
+                }
+            }
+            Ok(None) => {
+                tracing::info!("⏹️ {} 输入结束", source.describe());
+                break;
+            }
+            Err(e) => {
+                failures += 1;
+                if failures >= MAX_READ_FAILURES && !source.reconnects() {
+                    tracing::error!("{} 连续读帧失败，停止采集: {}", source.describe(), e);
+                    break;
+                }
+                std::thread::sleep(READ_RETRY_INTERVAL);
+            }
+        }
+    }
+}
+
+/// 单帧处理：推理、绘制、写入黑匣子与告警；`detect` 为 false 时沿用上一次的检测结果，只绘制不推理
+async fn process_frame(
+    app: &AppHandle,
+    shared: &PipelineShared,
+    captured: CapturedFrame,
+    detect: bool,
+) -> Result<RealtimeFrame> {
+    let frame = captured.image;
+    let source = shared.source.describe();
+    let frame_index = captured.position.unwrap_or_else(|| shared.frame_count.load(Ordering::Relaxed));
+
+    let held = if detect {
+        let encode_start = Instant::now();
+        let data = encode_jpeg(&frame)?;
+        let encode_ms = DetectionTimings::since(encode_start);
+        let mut result = app.state::<InferenceWorker>().detect(data).await?;
+        let stats = app.state::<AppState>().read().await.get_stats().await;
+        result.timings.encode_ms = encode_ms;
+        result.set_frame(&shared.session, Some(frame_index), Some(captured.captured_at));
+        app.state::<TrackingManager>().update(&mut result);
+        app.state::<TemporalFilter>().apply(&mut result);
+        // 停留时长：视频文件按视频时间，实时输入按实际时间
+        let elapsed_secs = shared
+            .video_secs(captured.position)
+            .unwrap_or_else(|| shared.started_at.elapsed().as_secs_f64());
+        app.state::<DwellMonitor>().update(app, &source, &result, elapsed_secs);
+        app.state::<AdaptiveRateController>().observe_latency(result.processing_time_ms);
+        let held = HeldResult {
+            result: Some(result),
+            total_inferences: stats.total_inferences,
+            avg_fps: stats.avg_fps,
+        };
+        *shared.last_result.lock() = held.clone();
+        held
+    } else {
+        shared.last_result.lock().clone()
+    };
+    let yolo_detections = held.result.as_ref().map(|r| r.detections.as_slice()).unwrap_or_default();
+    let track_ids = held.result.as_ref().map(|r| r.track_ids.as_slice()).unwrap_or_default();
+
+    let detections: Vec<Detection> = yolo_detections
+        .iter()
+        .enumerate()
+        .map(|(index, d)| Detection {
+            class_name: d.class_name.clone(),
+            confidence: d.confidence,
+            bbox: d.bbox,
+            track_id: track_ids.get(index).copied().flatten(),
+            size_mm: d.size_mm,
+        })
+        .collect();
+
+    let image = Arc::new(DynamicImage::ImageRgb8(frame));
+    *shared.latest_frame.lock() = Some(image.clone());
+    if let (true, Some(result)) = (detect, held.result.as_ref()) {
+        // 会话进行中时推理帧计入检测历史，归属当前会话
+        if let Some(session_id) = app.state::<SessionManager>().current() {
+            match history::record_run(
+                &app.state::<Database>(),
+                &source,
+                result,
+                Some(session_id),
+                app.state::<ProfileStore>().active().as_deref(),
+            ) {
+                Ok(run_id) => app.state::<ArtifactSettings>().save_in_background(
+                    &source,
+                    Some(run_id),
+                    ArtifactImage::Decoded(&image),
+                    &result.detections,
+                ),
+                Err(e) => tracing::error!("历史记录保存失败: {}", e),
+            }
+        }
+
+        let blackbox = app.state::<BlackBoxRecorder>();
+        let recorder = app.state::<EventRecorder>();
+        if let Err(e) = blackbox.record_frame(&shared.session, &image, &detections) {
+            tracing::error!("黑匣子写入失败: {}", e);
+        }
+        recorder.on_frame(&shared.session, &image, &detections);
+        let camera = match &shared.source {
+            InputSource::Camera(device_id) => Some(device_id.to_string()),
+            _ => None,
+        };
+        match yolo_api::publish_and_alert(app, &source, camera, result) {
+            Ok(raised) => {
+                if let Some(secs) = shared.video_secs(captured.position) {
+                    shared.clip_events.lock().extend(raised.iter().map(|alert| ClipEvent {
+                        timestamp_secs: secs,
+                        label: Some(alert.class_name.clone()),
+                        alert_id: Some(alert.id),
+                    }));
+                }
+                if let Some(alert) = raised.first() {
+                    if let Err(e) = recorder.start(app, &shared.session, alert) {
+                        tracing::error!("事件录像启动失败: {}", e);
+                    }
+                }
+            }
+            Err(e) => tracing::error!("告警记录失败: {}", e),
+        }
+    }
+
+    let draw_start = Instant::now();
+    // 无检测框、检测区域与遮挡区域时直接输出原图
+    let plain = yolo_detections.is_empty() && !detection_config::has_roi() && !detection_config::has_privacy_masks();
+    let annotated = if plain {
+        None
+    } else {
+        Some(draw_detections_on_image(&image, yolo_detections).map_err(|e| anyhow!(e))?)
+    };
+    let draw_ms = DetectionTimings::since(draw_start);
+    let encode_start = Instant::now();
+    let image_data = image_to_base64(annotated.as_ref().unwrap_or(&*image)).map_err(|e| anyhow!(e))?;
+    let timings = match (detect, held.result.as_ref()) {
+        (true, Some(result)) => Some(DetectionTimings {
+            draw_ms,
+            encode_ms: result.timings.encode_ms + DetectionTimings::since(encode_start),
+            ..result.timings
+        }),
+        _ => None,
+    };
+    viewer::publish_frame(
+        app,
+        &source,
+        Some(image_data.clone()),
+        detections.clone(),
+        held.total_inferences,
+        held.avg_fps,
+    );
+
+    Ok(RealtimeFrame {
+        frame_index,
+        image_data: Some(image_data),
+        detections,
+        timestamp: crate::storage::now_rfc3339(),
+        held: !detect,
+        timings,
+    })
+}
+
+/// 处理任务：逐帧检测并缓存结果
+async fn processing_loop(app: AppHandle, shared: Arc<PipelineShared>, mut rx: mpsc::Receiver<CapturedFrame>) {
+    let mut sampler = Sampler::default();
+    while let Some(frame) = rx.recv().await {
+        if shared.stop.load(Ordering::Relaxed) {
+            break;
+        }
+        // 负载/温度过高时按降低后的帧率跳过部分实时帧（视频文件没有实时性要求，不跳帧）
+        if shared.video.is_none() && !app.state::<AdaptiveRateController>().should_process() {
+            shared.capture_dropped.fetch_add(1, Ordering::Relaxed);
+            continue;
+        }
+        let position = frame.position;
+        let sampling = shared.sampling.lock().clone();
+        let detect = sampler.should_detect(&sampling, shared.video.as_ref(), position);
+        match process_frame(&app, &shared, frame, detect).await {
+            Ok(result) => {
+                if let Some(position) = position {
+                    shared.position.store(position, Ordering::Relaxed);
+                }
+                shared.frame_count.fetch_add(1, Ordering::Relaxed);
+                shared.fps_meter.lock().tick();
+                if !result.held {
+                    shared.detection_count.fetch_add(result.detections.len() as u64, Ordering::Relaxed);
+                }
+                shared.pusher.push(&app, shared.source.describe(), result.clone(), shared.video_progress());
+                shared.frames.push(result).await;
+            }
+            Err(e) => tracing::error!("实时帧处理失败: {}", e),
+        }
+    }
+    shared.running.store(false, Ordering::Relaxed);
+    app.state::<EventRecorder>().clear_pre_roll(&shared.session);
+    if let InputSource::Video(path) = &shared.source {
+        let events = std::mem::take(&mut *shared.clip_events.lock());
+        clips::spawn_for_video(&app, path.clone(), events);
+    }
+    tracing::info!("⏹️ 实时检测已结束: {}", shared.source.describe());
+}
+
+impl RealtimePipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 启动实时检测
+    pub fn start(&self, app: &AppHandle, source: InputSource, session: String, frame_source: Box<dyn FrameSource>) -> Result<()> {
+        let mut active = self.active.lock();
+        if active.as_ref().is_some_and(|shared| shared.running.load(Ordering::Relaxed)) {
+            return Err(anyhow!("实时检测已在运行，请先停止"));
+        }
+
+        // 跟踪ID、时间滤波与停留计时只在同一次检测中有意义
+        app.state::<TrackingManager>().reset();
+        app.state::<TemporalFilter>().reset();
+        app.state::<DwellMonitor>().reset();
+
+        let shared = Arc::new(PipelineShared {
+            source,
+            session,
+            stop: AtomicBool::new(false),
+            running: AtomicBool::new(true),
+            frame_count: AtomicU64::new(0),
+            detection_count: AtomicU64::new(0),
+            started_at: Instant::now(),
+            fps_meter: Mutex::new(FpsMeter::default()),
+            capture_dropped: AtomicU64::new(0),
+            frames: FrameQueue::new(*self.queue_config.lock()),
+            property_request: Mutex::new(None),
+            camera_properties: Mutex::new(None),
+            video: frame_source.video_info(),
+            position: AtomicU64::new(0),
+            paused: AtomicBool::new(false),
+            pending_steps: AtomicU32::new(0),
+            seek_request: Mutex::new(None),
+            sampling: Mutex::new(self.sampling.lock().clone()),
+            last_result: Mutex::new(HeldResult::default()),
+            latest_frame: Mutex::new(None),
+            clip_events: Mutex::new(Vec::new()),
+            pusher: FramePusher::default(),
+        });
+        let defaults = self.camera_defaults.lock().clone();
+        if !defaults.is_empty() && matches!(shared.source, InputSource::Camera(_)) {
+            *shared.property_request.lock() = Some(PropertyRequest { properties: defaults, reply: None });
+        }
+
+        // 通道容量为1：推理忙时采集线程直接丢弃新帧
+        let (tx, rx) = mpsc::channel(1);
+        let capture_shared = shared.clone();
+        std::thread::Builder::new()
+            .name("frame-capture".to_string())
+            .spawn(move || capture_loop(frame_source, capture_shared, tx))
+            .map_err(|e| anyhow!("启动采集线程失败: {}", e))?;
+        tauri::async_runtime::spawn(processing_loop(app.clone(), shared.clone(), rx));
+
+        tracing::info!("🎥 实时检测已启动: {}", shared.source.describe());
+        *active = Some(shared);
+        Ok(())
+    }
+
+    /// 停止实时检测，返回是否有正在运行的管线
+    pub fn stop(&self) -> bool {
+        match self.active.lock().take() {
+            Some(shared) => {
+                shared.stop.store(true, Ordering::Relaxed);
+                shared.frames.close();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 设置摄像头采集参数：保存为之后启动时的默认值，正在采集时立即应用并返回实际生效的值
+    pub async fn set_camera_properties(&self, properties: CameraProperties) -> Result<Option<CameraProperties>> {
+        self.camera_defaults.lock().merge(&properties);
+
+        let (reply, rx) = oneshot::channel();
+        {
+            let active = self.active.lock();
+            let shared = match active.as_ref() {
+                Some(shared) if shared.running.load(Ordering::Relaxed) && matches!(shared.source, InputSource::Camera(_)) => shared,
+                _ => return Ok(None),
+            };
+            *shared.property_request.lock() = Some(PropertyRequest { properties, reply: Some(reply) });
+        }
+
+        match tokio::time::timeout(PROPERTY_TIMEOUT, rx).await {
+            Ok(Ok(result)) => result.map(Some).map_err(|e| anyhow!(e)),
+            _ => Err(anyhow!("等待摄像头应用采集参数超时")),
+        }
+    }
+
+    /// 设置抽帧策略，运行中的管线立即生效
+    pub fn set_sampling(&self, sampling: FrameSampling) -> Result<()> {
+        sampling.validate().map_err(error::invalid_input)?;
+        if let Some(shared) = self.active.lock().as_ref() {
+            *shared.sampling.lock() = sampling.clone();
+        }
+        *self.sampling.lock() = sampling;
+        Ok(())
+    }
+
+    pub fn sampling(&self) -> FrameSampling {
+        self.sampling.lock().clone()
+    }
+
+    /// 设置帧队列容量与丢弃策略，运行中的管线立即生效
+    pub fn set_queue_config(&self, config: FrameQueueConfig) -> Result<()> {
+        config.validate().map_err(error::invalid_input)?;
+        if let Some(shared) = self.active.lock().as_ref() {
+            shared.frames.set_config(config);
+        }
+        *self.queue_config.lock() = config;
+        Ok(())
+    }
+
+    pub fn queue_config(&self) -> FrameQueueConfig {
+        *self.queue_config.lock()
+    }
+
+    /// 正在进行的视频检测
+    fn active_video(&self) -> Result<Arc<PipelineShared>> {
+        self.active
+            .lock()
+            .as_ref()
+            .filter(|shared| shared.video.is_some() && shared.running.load(Ordering::Relaxed))
+            .cloned()
+            .ok_or_else(|| anyhow!("当前没有进行中的视频检测"))
+    }
+
+    /// 暂停/继续视频检测
+    pub fn set_video_paused(&self, paused: bool) -> Result<VideoProgress> {
+        let shared = self.active_video()?;
+        shared.paused.store(paused, Ordering::Relaxed);
+        if !paused {
+            shared.pending_steps.store(0, Ordering::Relaxed);
+        }
+        shared.video_progress().ok_or_else(|| anyhow!("无法获取视频进度"))
+    }
+
+    /// 跳转到指定帧或时间点，返回目标帧序号
+    pub fn seek_video(&self, target: SeekTarget) -> Result<u64> {
+        let shared = self.active_video()?;
+        let video = shared.video.as_ref().ok_or_else(|| anyhow!("当前没有进行中的视频检测"))?;
+        let frame = match target {
+            SeekTarget::Frame(frame) => frame,
+            SeekTarget::Ms(ms) if video.fps > 0.0 => (ms as f64 * video.fps / 1000.0) as u64,
+            SeekTarget::Ms(_) => return Err(anyhow!("视频帧率未知，无法按时间跳转")),
+        };
+        if video.total_frames > 0 && frame >= video.total_frames {
+            return Err(DetectionError::InvalidInput(format!("目标帧 {} 超出视频范围（共 {} 帧）", frame, video.total_frames)).into());
+        }
+        *shared.seek_request.lock() = Some(frame);
+        Ok(frame)
+    }
+
+    /// 单步处理下一帧（未暂停时先暂停）
+    pub fn step_frame(&self) -> Result<()> {
+        let shared = self.active_video()?;
+        shared.paused.store(true, Ordering::Relaxed);
+        shared.pending_steps.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// 确认已处理推送的标注帧
+    pub fn ack_frame(&self, app: &AppHandle, seq: u64) {
+        if let Some(shared) = self.active.lock().as_ref() {
+            shared.pusher.ack(app, seq);
+        }
+    }
+
+    /// 当前画面与最近一次推理结果（抓拍用），未在检测或尚未处理任何帧时返回错误
+    pub fn live_frame(&self) -> Result<LiveFrame> {
+        let active = self.active.lock();
+        let shared = active
+            .as_ref()
+            .filter(|shared| shared.running.load(Ordering::Relaxed))
+            .ok_or_else(|| anyhow!("当前没有进行中的实时检测"))?;
+        let image = shared
+            .latest_frame
+            .lock()
+            .clone()
+            .ok_or_else(|| anyhow!("尚未采集到画面，请稍后重试"))?;
+        let held = shared.last_result.lock().clone();
+        Ok(LiveFrame {
+            session: shared.session.clone(),
+            source: shared.source.describe(),
+            image,
+            track_ids: held.result.as_ref().map(|r| r.track_ids.clone()).unwrap_or_default(),
+            result: held.result,
+        })
+    }
+
+    /// 取出最早的一帧未读结果（轮询方式，兼容旧版前端）
+    pub fn next_frame(&self) -> Option<RealtimeFrame> {
+        self.active.lock().as_ref()?.frames.pop()
+    }
+
+    pub fn stats(&self) -> RealtimeStats {
+        match self.active.lock().as_ref() {
+            Some(shared) => {
+                let frame_count = shared.frame_count.load(Ordering::Relaxed);
+                let elapsed = shared.started_at.elapsed().as_secs_f32();
+                let fps_meter = shared.fps_meter.lock();
+                RealtimeStats {
+                    is_running: shared.running.load(Ordering::Relaxed),
+                    input_source: Some(shared.source.clone()),
+                    frame_count,
+                    detection_count: shared.detection_count.load(Ordering::Relaxed),
+                    fps: if elapsed > 0.0 { frame_count as f32 / elapsed } else { 0.0 },
+                    instant_fps: fps_meter.instant(),
+                    rolling_fps: fps_meter.rolling(shared.started_at),
+                    capture_dropped: shared.capture_dropped.load(Ordering::Relaxed),
+                    camera_properties: shared.camera_properties.lock().clone(),
+                    video_progress: shared.video_progress(),
+                    push_dropped: shared.pusher.dropped(),
+                    queued_frames: shared.frames.buffered(),
+                    dropped_frames: shared.frames.dropped(),
+                }
+            }
+            None => RealtimeStats::default(),
+        }
+    }
+}
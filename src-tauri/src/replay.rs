@@ -0,0 +1,261 @@
+/*!
+历史回放模块
+按原始节奏（或加速）重放已保存的检测结果，通过与实时检测相同的帧事件推送，
+便于事后复盘异常事件
+*/
+
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::oneshot;
+
+use crate::error::DetectionError;
+use crate::history::{self, DetectionRun};
+use crate::storage::Database;
+use crate::viewer::{ViewerFrame, ViewerStats, EVENT_VIEWER_FRAME};
+use crate::yolo::{decode, YoloDetection};
+use crate::yolo_api::{self, Detection};
+
+/// 回放结束事件
+pub const EVENT_REPLAY_FINISHED: &str = "replay://finished";
+
+/// 相邻两帧之间的最长等待时间，避免长时间空闲段拖慢回放
+const MAX_FRAME_GAP_MS: u64 = 5_000;
+
+/// 回放请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayRequest {
+    pub from: Option<String>,   // RFC3339 起始时间
+    pub to: Option<String>,     // RFC3339 结束时间
+    pub source: Option<String>, // 仅回放指定输入源
+    pub speed: Option<f32>,     // 回放倍速，默认1.0
+    #[serde(default = "default_render_frames")]
+    pub render_frames: bool,    // 是否重新生成标注图像（源图片仍存在时）
+}
+
+fn default_render_frames() -> bool {
+    true
+}
+
+/// 回放状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayStatus {
+    pub is_running: bool,
+    pub total_frames: u32,
+    pub emitted_frames: u32,
+    pub speed: f32,
+    pub current_timestamp: Option<String>,
+}
+
+#[derive(Default)]
+struct ReplayRuntime {
+    cancel: Option<oneshot::Sender<()>>,
+    status: ReplayStatus,
+}
+
+/// 回放管理器（Tauri托管状态）
+#[derive(Default)]
+pub struct ReplayManager {
+    runtime: Mutex<ReplayRuntime>,
+}
+
+impl ReplayManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+pub(crate) fn parse_timestamp_ms(value: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.timestamp_millis())
+}
+
+/// 根据历史记录重新绘制标注图像（源图片已不存在时返回空）
+pub fn render_run(run: &DetectionRun) -> Option<image::DynamicImage> {
+    let img = decode::open(Path::new(&run.source)).ok()?;
+    let boxes: Vec<YoloDetection> = run
+        .detections
+        .iter()
+        .map(|d| YoloDetection {
+            class_id: d.class_id,
+            class_name: d.class_name.clone(),
+            confidence: d.confidence,
+            bbox: d.bbox,
+            size_mm: None,
+        })
+        .collect();
+    yolo_api::draw_detections_on_image(&img, &boxes).ok()
+}
+
+/// 将历史记录转换为监控帧
+fn run_to_frame(run: &DetectionRun, seq: u64, render: bool) -> ViewerFrame {
+    let detections: Vec<Detection> = run
+        .detections
+        .iter()
+        .map(|d| Detection {
+            class_name: d.class_name.clone(),
+            confidence: d.confidence,
+            bbox: d.bbox,
+            track_id: None,
+            size_mm: None,
+        })
+        .collect();
+
+    let image_data = if render {
+        render_run(run).and_then(|annotated| yolo_api::image_to_base64(&annotated).ok())
+    } else {
+        None
+    };
+
+    let abnormal_count = detections
+        .iter()
+        .filter(|d| d.class_name == crate::alerts::ABNORMAL_CLASS_NAME)
+        .count() as u64;
+
+    ViewerFrame {
+        source: run.source.clone(),
+        image_data,
+        detections,
+        timestamp: run.created_at.clone(),
+        stats: ViewerStats {
+            frame_seq: seq,
+            total_inferences: seq,
+            avg_fps: 0.0,
+            abnormal_count,
+        },
+        replay: true,
+    }
+}
+
+/// 启动回放任务
+fn start(app: &AppHandle, manager: &ReplayManager, runs: Vec<DetectionRun>, speed: f32, render: bool) -> Result<()> {
+    if runs.is_empty() {
+        return Err(anyhow!("所选范围内没有检测记录"));
+    }
+
+    let (cancel_tx, mut cancel_rx) = oneshot::channel();
+    {
+        let mut runtime = manager.runtime.lock();
+        if runtime.cancel.is_some() {
+            return Err(anyhow!("已有回放正在进行，请先停止"));
+        }
+        runtime.cancel = Some(cancel_tx);
+        runtime.status = ReplayStatus {
+            is_running: true,
+            total_frames: runs.len() as u32,
+            emitted_frames: 0,
+            speed,
+            current_timestamp: None,
+        };
+    }
+
+    tracing::info!("⏪ 开始回放 {} 条检测记录 (倍速 {:.1}x)", runs.len(), speed);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut previous_ms: Option<i64> = None;
+        let mut cancelled = false;
+
+        for (index, run) in runs.iter().enumerate() {
+            // 按原始时间间隔等待（除以倍速）
+            let current_ms = parse_timestamp_ms(&run.created_at);
+            if let (Some(prev), Some(current)) = (previous_ms, current_ms) {
+                let gap = ((current - prev).max(0) as u64).min(MAX_FRAME_GAP_MS);
+                let wait = Duration::from_millis((gap as f64 / speed as f64) as u64);
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {}
+                    _ = &mut cancel_rx => {
+                        cancelled = true;
+                        break;
+                    }
+                }
+            }
+            previous_ms = current_ms.or(previous_ms);
+
+            let frame = run_to_frame(run, index as u64 + 1, render);
+            if let Err(e) = app.emit(EVENT_VIEWER_FRAME, frame) {
+                tracing::error!("推送回放帧失败: {}", e);
+            }
+
+            let manager = app.state::<ReplayManager>();
+            let mut runtime = manager.runtime.lock();
+            runtime.status.emitted_frames = index as u32 + 1;
+            runtime.status.current_timestamp = Some(run.created_at.clone());
+        }
+
+        let status = {
+            let manager = app.state::<ReplayManager>();
+            let mut runtime = manager.runtime.lock();
+            runtime.cancel = None;
+            runtime.status.is_running = false;
+            runtime.status.clone()
+        };
+        tracing::info!(
+            "⏹️ 回放{}: {}/{} 帧",
+            if cancelled { "已停止" } else { "完成" },
+            status.emitted_frames,
+            status.total_frames
+        );
+        let _ = app.emit(EVENT_REPLAY_FINISHED, status);
+    });
+
+    Ok(())
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 开始回放历史检测结果
+#[tauri::command]
+pub async fn start_replay(
+    app: AppHandle,
+    manager: State<'_, ReplayManager>,
+    db: State<'_, Database>,
+    request: ReplayRequest
+) -> Result<ReplayStatus, DetectionError> {
+    let speed = request.speed.unwrap_or(1.0);
+    if !(speed > 0.0 && speed.is_finite()) {
+        return Err(DetectionError::InvalidInput(format!("无效的回放倍速: {}", speed)));
+    }
+
+    let runs = match history::runs_in_range(
+        &db,
+        request.from.as_deref(),
+        request.to.as_deref(),
+        request.source.as_deref(),
+    ) {
+        Ok(runs) => runs,
+        Err(e) => return Err(DetectionError::from(e).context("加载回放数据失败")),
+    };
+
+    match start(&app, &manager, runs, speed, request.render_frames) {
+        Ok(()) => Ok(manager.runtime.lock().status.clone()),
+        Err(e) => Err(DetectionError::from(e).context("启动回放失败")),
+    }
+}
+
+/// 停止回放
+#[tauri::command]
+pub async fn stop_replay(
+    manager: State<'_, ReplayManager>
+) -> Result<String, DetectionError> {
+    match manager.runtime.lock().cancel.take() {
+        Some(cancel) => {
+            let _ = cancel.send(());
+            Ok("回放已停止".to_string())
+        }
+        None => Err(DetectionError::NotFound("当前没有进行中的回放".to_string())),
+    }
+}
+
+/// 获取回放状态
+#[tauri::command]
+pub async fn get_replay_status(
+    manager: State<'_, ReplayManager>
+) -> Result<ReplayStatus, DetectionError> {
+    Ok(manager.runtime.lock().status.clone())
+}
@@ -0,0 +1,388 @@
+/*!
+检测报告生成模块
+按历史查询条件汇总一批检测结果，生成可分享的单文件HTML报告：
+汇总统计、操作员复核情况、各类别计数、置信度分布直方图与异常帧标注缩略图。
+PDF报告由HTML经外部 wkhtmltopdf 转换，可通过环境变量 YOLO_WKHTMLTOPDF_PATH 指定路径
+*/
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::process::Stdio;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::alerts::ABNORMAL_CLASS_NAME;
+use crate::corrections::{self, ReviewStats};
+use crate::error::DetectionError;
+use crate::history::{self, DetectionRun, HistoryFilter};
+use crate::sessions;
+use crate::storage::Database;
+use crate::yolo::{decode, YoloDetection};
+use crate::yolo_api::{draw_detections_on_image, image_to_base64};
+
+/// wkhtmltopdf 路径环境变量
+pub const WKHTMLTOPDF_PATH_ENV: &str = "YOLO_WKHTMLTOPDF_PATH";
+
+/// 报告最多包含的异常帧缩略图数
+const MAX_THUMBNAILS: usize = 24;
+
+/// 缩略图最大边长
+const THUMBNAIL_SIZE: u32 = 320;
+
+/// 置信度直方图分箱数
+const HISTOGRAM_BINS: usize = 10;
+
+/// 报告格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Html,
+    Pdf,
+}
+
+/// 单个类别的统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClassSummary {
+    pub class_name: String,
+    pub count: u64,
+    pub avg_confidence: f32,
+    pub histogram: Vec<u64>, // 置信度 [0,0.1)、[0.1,0.2)…[0.9,1.0] 的计数
+}
+
+/// 报告汇总数据
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportSummary {
+    pub title: String,
+    pub generated_at: String,
+    pub first_run_at: Option<String>,
+    pub last_run_at: Option<String>,
+    pub run_count: u64,
+    pub abnormal_run_count: u64,
+    pub detection_count: u64,
+    pub avg_processing_time_ms: f64,
+    pub classes: Vec<ClassSummary>,
+    #[serde(default)]
+    pub review: ReviewStats,
+}
+
+/// 报告生成结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportResult {
+    pub path: String,
+    pub format: ReportFormat,
+    pub summary: ReportSummary,
+    pub thumbnail_count: usize,
+}
+
+/// 获取 wkhtmltopdf 可执行文件路径
+fn wkhtmltopdf_binary() -> String {
+    std::env::var(WKHTMLTOPDF_PATH_ENV).unwrap_or_else(|_| "wkhtmltopdf".to_string())
+}
+
+fn histogram_bin(confidence: f32) -> usize {
+    ((confidence.clamp(0.0, 1.0) * HISTOGRAM_BINS as f32) as usize).min(HISTOGRAM_BINS - 1)
+}
+
+/// 统计检测结果
+pub fn summarize(title: &str, runs: &[DetectionRun]) -> ReportSummary {
+    let mut classes: BTreeMap<String, (ClassSummary, f64)> = BTreeMap::new();
+    let mut detection_count = 0;
+    let mut abnormal_run_count = 0;
+    let mut total_time_ms = 0u64;
+
+    for run in runs {
+        total_time_ms += run.processing_time_ms;
+        if run.detections.iter().any(|d| d.class_name == ABNORMAL_CLASS_NAME) {
+            abnormal_run_count += 1;
+        }
+        for detection in &run.detections {
+            detection_count += 1;
+            let (summary, confidence_sum) = classes.entry(detection.class_name.clone()).or_insert_with(|| {
+                (
+                    ClassSummary {
+                        class_name: detection.class_name.clone(),
+                        histogram: vec![0; HISTOGRAM_BINS],
+                        ..Default::default()
+                    },
+                    0.0,
+                )
+            });
+            summary.count += 1;
+            summary.histogram[histogram_bin(detection.confidence)] += 1;
+            *confidence_sum += detection.confidence as f64;
+        }
+    }
+
+    ReportSummary {
+        title: title.to_string(),
+        generated_at: crate::storage::now_rfc3339(),
+        first_run_at: runs.first().map(|run| run.created_at.clone()),
+        last_run_at: runs.last().map(|run| run.created_at.clone()),
+        run_count: runs.len() as u64,
+        abnormal_run_count,
+        detection_count,
+        avg_processing_time_ms: if runs.is_empty() { 0.0 } else { total_time_ms as f64 / runs.len() as f64 },
+        classes: classes
+            .into_values()
+            .map(|(mut summary, confidence_sum)| {
+                summary.avg_confidence = (confidence_sum / summary.count.max(1) as f64) as f32;
+                summary
+            })
+            .collect(),
+        review: ReviewStats::default(), // 需要查询修正记录，由调用方填充
+    }
+}
+
+/// 重新绘制异常帧并生成缩略图（源图片已不存在的运行被跳过）
+fn abnormal_thumbnails(runs: &[DetectionRun]) -> Vec<(String, String)> {
+    runs.iter()
+        .rev()
+        .filter(|run| run.detections.iter().any(|d| d.class_name == ABNORMAL_CLASS_NAME))
+        .filter_map(|run| {
+            let image = decode::open(Path::new(&run.source)).ok()?;
+            let detections: Vec<YoloDetection> = run
+                .detections
+                .iter()
+                .map(|d| YoloDetection {
+                    class_id: d.class_id,
+                    class_name: d.class_name.clone(),
+                    confidence: d.confidence,
+                    bbox: d.bbox,
+                    size_mm: None,
+                })
+                .collect();
+            let annotated = draw_detections_on_image(&image, &detections).ok()?;
+            let thumbnail = image::DynamicImage::ImageRgb8(annotated.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgb8());
+            let data = image_to_base64(&thumbnail).ok()?;
+            Some((format!("#{} {} · {}", run.id, run.source, run.created_at), data))
+        })
+        .take(MAX_THUMBNAILS)
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 置信度直方图（内联SVG，无外部依赖）
+fn histogram_svg(histogram: &[u64]) -> String {
+    const WIDTH: usize = 300;
+    const HEIGHT: usize = 100;
+    let max = histogram.iter().copied().max().unwrap_or(0).max(1);
+    let bar_width = WIDTH / histogram.len().max(1);
+
+    let mut svg = format!(r#"<svg width="{}" height="{}" viewBox="0 0 {} {}">"#, WIDTH, HEIGHT + 16, WIDTH, HEIGHT + 16);
+    for (index, &count) in histogram.iter().enumerate() {
+        let height = count as usize * HEIGHT / max as usize;
+        let x = index * bar_width;
+        let _ = write!(
+            svg,
+            r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#3b82f6"><title>{:.1}-{:.1}: {}</title></rect>"##,
+            x + 1,
+            HEIGHT - height,
+            bar_width - 2,
+            height,
+            index as f32 / histogram.len() as f32,
+            (index + 1) as f32 / histogram.len() as f32,
+            count
+        );
+        if index % 2 == 0 {
+            let _ = write!(
+                svg,
+                r#"<text x="{}" y="{}" font-size="10">{:.1}</text>"#,
+                x,
+                HEIGHT + 12,
+                index as f32 / histogram.len() as f32
+            );
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// 渲染HTML报告
+pub fn render_html(summary: &ReportSummary, thumbnails: &[(String, String)]) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        r#"<!DOCTYPE html>
+<html lang="zh-CN"><head><meta charset="utf-8"><title>{title}</title>
+<style>
+body {{ font-family: -apple-system, "Microsoft YaHei", sans-serif; margin: 32px; color: #1f2937; }}
+h1 {{ margin-bottom: 4px; }} .muted {{ color: #6b7280; font-size: 13px; }}
+table {{ border-collapse: collapse; margin: 16px 0; }}
+th, td {{ border: 1px solid #e5e7eb; padding: 6px 12px; text-align: left; }}
+th {{ background: #f3f4f6; }}
+.stats {{ display: flex; gap: 16px; flex-wrap: wrap; }}
+.stat {{ border: 1px solid #e5e7eb; border-radius: 8px; padding: 12px 20px; }}
+.stat b {{ display: block; font-size: 24px; }}
+.thumbs {{ display: flex; flex-wrap: wrap; gap: 12px; }}
+figure {{ margin: 0; width: {thumb}px; page-break-inside: avoid; }}
+figure img {{ max-width: 100%; border: 1px solid #e5e7eb; }}
+figcaption {{ font-size: 11px; color: #6b7280; word-break: break-all; }}
+</style></head><body>
+<h1>{title}</h1>
+<div class="muted">生成时间 {generated} · 检测时间范围 {first} ~ {last}</div>
+<h2>汇总</h2>
+<div class="stats">
+<div class="stat">检测次数<b>{runs}</b></div>
+<div class="stat">异常次数<b>{abnormal}</b></div>
+<div class="stat">检测目标数<b>{detections}</b></div>
+<div class="stat">平均耗时<b>{avg_time:.1} ms</b></div>
+</div>
+<h2>复核情况</h2>
+<div class="stats">
+<div class="stat">已复核次数<b>{reviewed}</b></div>
+<div class="stat">确认<b>{confirmed}</b></div>
+<div class="stat">误报<b>{false_positives}</b></div>
+<div class="stat">类别修正<b>{relabeled}</b></div>
+<div class="stat">漏检<b>{missed}</b></div>
+<div class="stat">复核精确率<b>{precision}</b></div>
+</div>
+<h2>各类别统计</h2>
+<table><tr><th>类别</th><th>数量</th><th>平均置信度</th><th>置信度分布</th></tr>
+"#,
+        title = escape_html(&summary.title),
+        thumb = THUMBNAIL_SIZE,
+        generated = escape_html(&summary.generated_at),
+        first = escape_html(summary.first_run_at.as_deref().unwrap_or("-")),
+        last = escape_html(summary.last_run_at.as_deref().unwrap_or("-")),
+        runs = summary.run_count,
+        abnormal = summary.abnormal_run_count,
+        detections = summary.detection_count,
+        avg_time = summary.avg_processing_time_ms,
+        reviewed = summary.review.reviewed_run_count,
+        confirmed = summary.review.confirmed_count,
+        false_positives = summary.review.false_positive_count,
+        relabeled = summary.review.relabeled_count,
+        missed = summary.review.missed_count,
+        precision = summary
+            .review
+            .precision
+            .map_or_else(|| "-".to_string(), |p| format!("{:.1}%", p * 100.0)),
+    );
+    for class in &summary.classes {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{:.1}%</td><td>{}</td></tr>",
+            escape_html(&class.class_name),
+            class.count,
+            class.avg_confidence * 100.0,
+            histogram_svg(&class.histogram)
+        );
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>异常帧</h2>\n");
+    if thumbnails.is_empty() {
+        html.push_str(r#"<p class="muted">无异常帧或源图片已不存在</p>"#);
+    } else {
+        html.push_str(r#"<div class="thumbs">"#);
+        for (caption, data) in thumbnails {
+            let _ = write!(
+                html,
+                r#"<figure><img src="data:image/jpeg;base64,{}"><figcaption>{}</figcaption></figure>"#,
+                data,
+                escape_html(caption)
+            );
+        }
+        html.push_str("</div>");
+    }
+    html.push_str("\n</body></html>\n");
+    html
+}
+
+/// HTML转换为PDF
+async fn html_to_pdf(html_path: &Path, pdf_path: &Path) -> Result<()> {
+    let binary = wkhtmltopdf_binary();
+    let output = tokio::process::Command::new(&binary)
+        .arg("--quiet")
+        .arg("--encoding")
+        .arg("utf-8")
+        .arg(html_path)
+        .arg(pdf_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| anyhow!("无法启动wkhtmltopdf ({}): {}，请安装或设置 {}", binary, e, WKHTMLTOPDF_PATH_ENV))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "PDF转换失败 ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// 生成检测报告
+pub async fn generate(
+    db: &Database,
+    format: ReportFormat,
+    filter: &HistoryFilter,
+    title: &str,
+    path: &Path,
+) -> Result<ReportResult> {
+    let runs = history::query_all(db, filter)?;
+    let mut summary = summarize(title, &runs);
+    summary.review = corrections::review_stats(db, &runs)?;
+    // 解码与绘制图片较慢，放到阻塞线程中执行
+    let thumbnails = tokio::task::spawn_blocking(move || abnormal_thumbnails(&runs)).await?;
+    let html = render_html(&summary, &thumbnails);
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| anyhow!("创建输出目录失败 {}: {}", parent.display(), e))?;
+    }
+    match format {
+        ReportFormat::Html => std::fs::write(path, html)?,
+        ReportFormat::Pdf => {
+            let html_path = path.with_extension("report.html");
+            std::fs::write(&html_path, html)?;
+            let converted = html_to_pdf(&html_path, path).await;
+            let _ = std::fs::remove_file(&html_path);
+            converted?;
+        }
+    }
+
+    tracing::info!("📝 检测报告已生成: {} ({} 次检测)", path.display(), summary.run_count);
+    Ok(ReportResult {
+        path: path.to_string_lossy().to_string(),
+        format,
+        summary,
+        thumbnail_count: thumbnails.len(),
+    })
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 生成HTML/PDF检测报告
+#[tauri::command]
+pub async fn generate_report(
+    db: State<'_, Database>,
+    format: ReportFormat,
+    filter: Option<HistoryFilter>,
+    title: Option<String>,
+    path: String
+) -> Result<ReportResult, DetectionError> {
+    let filter = filter.unwrap_or_default();
+    // 按会话生成报告时默认以会话名称作为标题
+    let session_name = filter
+        .session_id
+        .and_then(|id| sessions::load_session(&db, id).ok().flatten())
+        .map(|session| format!("检测报告 - {}", session.name));
+    let title = title.or(session_name).unwrap_or_else(|| "检测报告".to_string());
+    match generate(&db, format, &filter, &title, Path::new(&path)).await {
+        Ok(result) => Ok(result),
+        Err(e) => Err(DetectionError::from(e).context("生成检测报告失败")),
+    }
+}
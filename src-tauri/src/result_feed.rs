@@ -0,0 +1,104 @@
+/*!
+检测结果推送
+将各输入源（实时检测、单张图片、文件夹监控、HTTP上传）的检测结果广播给订阅者，
+供内嵌HTTP服务的WebSocket接口实时推送给无界面的集成方。
+订阅者可按类别、摄像头过滤，积压时跳过旧结果，不阻塞检测
+*/
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast;
+
+use crate::yolo::DetectionResult;
+
+/// 每个订阅者最多积压的结果条数
+const FEED_CAPACITY: usize = 64;
+
+/// 推送的一条检测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedMessage {
+    pub source: String,         // 输入源描述
+    pub camera: Option<String>, // 摄像头编号（摄像头输入时）
+    pub timestamp: String,
+    pub result: DetectionResult,
+}
+
+/// 订阅过滤条件，均为空时接收全部结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedFilter {
+    pub classes: Vec<String>, // 只保留这些类别的检测框，没有匹配时不推送
+    pub cameras: Vec<String>, // 摄像头编号或输入源描述
+}
+
+impl FeedFilter {
+    /// 按过滤条件裁剪结果，不满足条件时返回 None
+    pub fn apply(&self, message: &FeedMessage) -> Option<FeedMessage> {
+        if !self.cameras.is_empty()
+            && !self
+                .cameras
+                .iter()
+                .any(|camera| message.camera.as_ref() == Some(camera) || message.source == *camera)
+        {
+            return None;
+        }
+        if self.classes.is_empty() {
+            return Some(message.clone());
+        }
+
+        let mut message = message.clone();
+        let keep: Vec<bool> = message
+            .result
+            .detections
+            .iter()
+            .map(|detection| self.classes.contains(&detection.class_name))
+            .collect();
+        if !keep.contains(&true) {
+            return None;
+        }
+        let mut flags = keep.iter();
+        message.result.detections.retain(|_| *flags.next().unwrap_or(&false));
+        if !message.result.track_ids.is_empty() {
+            let mut flags = keep.iter();
+            message.result.track_ids.retain(|_| *flags.next().unwrap_or(&false));
+        }
+        Some(message)
+    }
+}
+
+/// 检测结果广播（Tauri托管状态）
+pub struct ResultFeed {
+    sender: broadcast::Sender<FeedMessage>,
+}
+
+impl Default for ResultFeed {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(FEED_CAPACITY).0,
+        }
+    }
+}
+
+impl ResultFeed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<FeedMessage> {
+        self.sender.subscribe()
+    }
+}
+
+/// 广播一条检测结果（没有订阅者时直接丢弃）
+pub fn publish(app: &AppHandle, source: &str, camera: Option<String>, result: &DetectionResult) {
+    let feed = app.state::<ResultFeed>();
+    if feed.sender.receiver_count() == 0 {
+        return;
+    }
+    let _ = feed.sender.send(FeedMessage {
+        source: source.to_string(),
+        camera,
+        timestamp: crate::storage::now_rfc3339(),
+        result: result.clone(),
+    });
+}
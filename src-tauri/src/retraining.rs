@@ -0,0 +1,410 @@
+/*!
+模型再训练触发模块
+当累计的修正样本达到阈值时，导出数据集并启动外部训练脚本，
+训练进度通过Tauri事件回传前端
+配置与上次触发时的样本数保存在应用数据目录，重启后不会因旧的修正样本再次触发
+*/
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use anyhow::{anyhow, Result};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::oneshot;
+
+use crate::corrections;
+use crate::dataset::{self, DatasetFormat};
+use crate::error::DetectionError;
+use crate::storage::Database;
+use crate::AppState;
+
+/// 训练进度事件
+pub const EVENT_RETRAINING_PROGRESS: &str = "retraining://progress";
+/// 训练结束事件
+pub const EVENT_RETRAINING_FINISHED: &str = "retraining://finished";
+
+/// 再训练状态文件名（位于应用数据目录）
+pub const STATE_FILE_NAME: &str = "retraining.json";
+
+/// 再训练配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrainingConfig {
+    pub enabled: bool,
+    pub script_path: String,         // 训练脚本或sidecar可执行文件
+    pub args: Vec<String>,           // 附加参数（数据集路径通过 --data 传入）
+    pub min_corrected_samples: u32,  // 触发训练所需的新增修正样本数
+    pub dataset_dir: String,         // 数据集导出目录
+    pub format: DatasetFormat,
+}
+
+impl Default for RetrainingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            script_path: String::new(),
+            args: Vec::new(),
+            min_corrected_samples: 50,
+            dataset_dir: String::new(),
+            format: DatasetFormat::Yolo,
+        }
+    }
+}
+
+/// 训练进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrainingProgress {
+    pub line: String,
+    pub progress: Option<f32>, // 0-100，无法解析时为空
+}
+
+/// 训练结束信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrainingFinished {
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub cancelled: bool,
+    pub message: String,
+}
+
+/// 再训练状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrainingStatus {
+    pub config: RetrainingConfig,
+    pub is_running: bool,
+    pub corrected_samples: u32,
+    pub samples_since_last_run: u32,
+    pub last_progress: Option<f32>,
+    pub last_result: Option<RetrainingFinished>,
+}
+
+/// 保存到文件的再训练状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct PersistedState {
+    config: RetrainingConfig,
+    last_trigger_count: u32, // 上次触发训练时的修正样本数
+}
+
+#[derive(Default)]
+struct RetrainingRuntime {
+    running: bool, // 从导出数据集开始即占用，训练进程结束或启动失败时释放
+    cancel: Option<oneshot::Sender<()>>,
+    last_trigger_count: u32,
+    last_progress: Option<f32>,
+    last_result: Option<RetrainingFinished>,
+}
+
+/// 再训练管理器（Tauri托管状态）
+pub struct RetrainingManager {
+    path: PathBuf,
+    config: RwLock<RetrainingConfig>,
+    runtime: Mutex<RetrainingRuntime>,
+}
+
+/// 已占用的训练名额，训练进程启动前出错时随 drop 释放
+struct RunningSlot<'a> {
+    manager: &'a RetrainingManager,
+    launched: bool,
+}
+
+impl Drop for RunningSlot<'_> {
+    fn drop(&mut self) {
+        if !self.launched {
+            self.manager.runtime.lock().running = false;
+        }
+    }
+}
+
+impl RetrainingManager {
+    /// 读取已保存的配置与上次触发时的样本数，文件不存在或无效时使用默认值
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(STATE_FILE_NAME);
+        let state: PersistedState = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            config: RwLock::new(state.config),
+            runtime: Mutex::new(RetrainingRuntime {
+                last_trigger_count: state.last_trigger_count,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn persist(&self) -> Result<()> {
+        let state = PersistedState {
+            config: self.config.read().clone(),
+            last_trigger_count: self.runtime.lock().last_trigger_count,
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&state)?)?;
+        Ok(())
+    }
+
+    fn is_running(&self) -> bool {
+        self.runtime.lock().running
+    }
+
+    /// 占用训练名额（检查与占用在同一把锁内完成）
+    fn reserve(&self) -> Result<RunningSlot<'_>> {
+        let mut runtime = self.runtime.lock();
+        if runtime.running {
+            return Err(DetectionError::Busy("已有训练任务正在运行".to_string()).into());
+        }
+        runtime.running = true;
+        Ok(RunningSlot { manager: self, launched: false })
+    }
+
+    fn status(&self, corrected_samples: u32) -> RetrainingStatus {
+        let runtime = self.runtime.lock();
+        RetrainingStatus {
+            config: self.config.read().clone(),
+            is_running: runtime.running,
+            corrected_samples,
+            samples_since_last_run: corrected_samples.saturating_sub(runtime.last_trigger_count),
+            last_progress: runtime.last_progress,
+            last_result: runtime.last_result.clone(),
+        }
+    }
+}
+
+/// 从训练脚本输出中解析进度（支持 "PROGRESS 42" 与 "epoch 3/100"）
+fn parse_progress(line: &str) -> Option<f32> {
+    let lower = line.trim().to_lowercase();
+
+    if let Some(rest) = lower.strip_prefix("progress") {
+        let value = rest.trim_start_matches([':', ' ']).trim_end_matches('%');
+        return value.trim().parse::<f32>().ok().map(|p| p.clamp(0.0, 100.0));
+    }
+
+    let rest = lower.split("epoch").nth(1)?;
+    let token = rest.trim_start_matches([':', ' ']).split_whitespace().next()?;
+    let (current, total) = token.split_once('/')?;
+    let current: f32 = current.parse().ok()?;
+    let total: f32 = total.parse().ok()?;
+    if total > 0.0 {
+        Some((current / total * 100.0).clamp(0.0, 100.0))
+    } else {
+        None
+    }
+}
+
+fn corrected_sample_count(db: &Database) -> Result<u32> {
+    Ok(corrections::corrected_run_ids(db)?.len() as u32)
+}
+
+/// 导出数据集并启动训练进程
+async fn launch(app: &AppHandle, manager: &RetrainingManager, sample_count: u32) -> Result<()> {
+    let config = manager.config.read().clone();
+    if config.script_path.trim().is_empty() {
+        return Err(DetectionError::InvalidInput("未配置训练脚本路径".to_string()).into());
+    }
+    let mut slot = manager.reserve()?;
+
+    let dataset_dir = if config.dataset_dir.trim().is_empty() {
+        app.path().app_data_dir()?.join("retraining_dataset")
+    } else {
+        PathBuf::from(&config.dataset_dir)
+    };
+
+    // 导出最新的修正数据集
+    let class_names = app.state::<AppState>().read().await.get_class_names().clone();
+    let summary = {
+        let db = app.state::<Database>();
+        dataset::export_corrections(&db, &dataset_dir, config.format, &class_names)?
+    };
+
+    let data_arg = match config.format {
+        DatasetFormat::Yolo => dataset_dir.join("data.yaml"),
+        DatasetFormat::Coco => dataset_dir.join("annotations.json"),
+    };
+
+    let mut child = tokio::process::Command::new(&config.script_path)
+        .args(&config.args)
+        .arg("--data")
+        .arg(&data_arg)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| anyhow!("启动训练脚本失败 {}: {}", config.script_path, e))?;
+
+    tracing::info!(
+        "🏋️ 已启动再训练: {} (数据集 {} 张图片, {} 个标注)",
+        config.script_path, summary.image_count, summary.annotation_count
+    );
+
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    {
+        let mut runtime = manager.runtime.lock();
+        runtime.cancel = Some(cancel_tx);
+        runtime.last_trigger_count = sample_count;
+        runtime.last_progress = Some(0.0);
+    }
+    slot.launched = true;
+    if let Err(e) = manager.persist() {
+        tracing::error!("保存再训练状态失败: {}", e);
+    }
+
+    // 转发训练输出
+    for stream in [
+        child.stdout.take().map(|s| Box::new(s) as Box<dyn tokio::io::AsyncRead + Unpin + Send>),
+        child.stderr.take().map(|s| Box::new(s) as Box<dyn tokio::io::AsyncRead + Unpin + Send>),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        let app = app.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stream).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let progress = parse_progress(&line);
+                if let Some(p) = progress {
+                    app.state::<RetrainingManager>().runtime.lock().last_progress = Some(p);
+                }
+                let _ = app.emit(EVENT_RETRAINING_PROGRESS, RetrainingProgress { line, progress });
+            }
+        });
+    }
+
+    // 等待训练结束或取消
+    let app = app.clone();
+    tokio::spawn(async move {
+        let finished = tokio::select! {
+            status = child.wait() => match status {
+                Ok(status) => RetrainingFinished {
+                    success: status.success(),
+                    exit_code: status.code(),
+                    cancelled: false,
+                    message: if status.success() { "训练完成".to_string() } else { format!("训练脚本退出: {}", status) },
+                },
+                Err(e) => RetrainingFinished {
+                    success: false,
+                    exit_code: None,
+                    cancelled: false,
+                    message: format!("等待训练进程失败: {}", e),
+                },
+            },
+            _ = cancel_rx => {
+                let _ = child.kill().await;
+                RetrainingFinished {
+                    success: false,
+                    exit_code: None,
+                    cancelled: true,
+                    message: "训练已取消".to_string(),
+                }
+            }
+        };
+
+        tracing::info!("🏁 再训练结束: {}", finished.message);
+        {
+            let manager = app.state::<RetrainingManager>();
+            let mut runtime = manager.runtime.lock();
+            runtime.running = false;
+            runtime.cancel = None;
+            if finished.success {
+                runtime.last_progress = Some(100.0);
+            }
+            runtime.last_result = Some(finished.clone());
+        }
+        let _ = app.emit(EVENT_RETRAINING_FINISHED, finished);
+    });
+
+    Ok(())
+}
+
+/// 修正记录新增后调用：样本数达到阈值时自动触发训练
+pub async fn maybe_trigger(app: &AppHandle) {
+    let manager = app.state::<RetrainingManager>();
+    let config = manager.config.read().clone();
+    if !config.enabled || manager.is_running() {
+        return;
+    }
+
+    let sample_count = match corrected_sample_count(&app.state::<Database>()) {
+        Ok(count) => count,
+        Err(e) => {
+            tracing::error!("统计修正样本失败: {}", e);
+            return;
+        }
+    };
+    let new_samples = sample_count.saturating_sub(manager.runtime.lock().last_trigger_count);
+    if new_samples < config.min_corrected_samples.max(1) {
+        return;
+    }
+
+    tracing::info!("🔔 新增修正样本 {} 个，达到阈值 {}，自动触发再训练", new_samples, config.min_corrected_samples);
+    if let Err(e) = launch(app, &manager, sample_count).await {
+        tracing::error!("自动触发再训练失败: {}", e);
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 设置再训练配置
+#[tauri::command]
+pub async fn set_retraining_config(
+    manager: State<'_, RetrainingManager>,
+    config: RetrainingConfig
+) -> Result<String, DetectionError> {
+    if config.enabled && config.script_path.trim().is_empty() {
+        return Err(DetectionError::InvalidInput("启用自动训练前请先配置训练脚本路径".to_string()));
+    }
+    *manager.config.write() = config;
+    match manager.persist() {
+        Ok(()) => Ok("再训练配置已更新".to_string()),
+        Err(e) => Err(DetectionError::from(e).context("保存再训练配置失败")),
+    }
+}
+
+/// 获取再训练状态
+#[tauri::command]
+pub async fn get_retraining_status(
+    manager: State<'_, RetrainingManager>,
+    db: State<'_, Database>
+) -> Result<RetrainingStatus, DetectionError> {
+    match corrected_sample_count(&db) {
+        Ok(count) => Ok(manager.status(count)),
+        Err(e) => Err(DetectionError::from(e).context("查询再训练状态失败")),
+    }
+}
+
+/// 手动触发再训练（忽略样本阈值）
+#[tauri::command]
+pub async fn trigger_retraining(
+    app: AppHandle,
+    manager: State<'_, RetrainingManager>,
+    db: State<'_, Database>
+) -> Result<String, DetectionError> {
+    let sample_count = match corrected_sample_count(&db) {
+        Ok(count) => count,
+        Err(e) => return Err(DetectionError::from(e).context("统计修正样本失败")),
+    };
+    match launch(&app, &manager, sample_count).await {
+        Ok(()) => Ok("再训练已启动".to_string()),
+        Err(e) => Err(DetectionError::from(e).context("启动再训练失败")),
+    }
+}
+
+/// 取消正在运行的再训练
+#[tauri::command]
+pub async fn cancel_retraining(
+    manager: State<'_, RetrainingManager>
+) -> Result<String, DetectionError> {
+    let mut runtime = manager.runtime.lock();
+    match runtime.cancel.take() {
+        Some(cancel) => {
+            let _ = cancel.send(());
+            Ok("已请求取消再训练".to_string())
+        }
+        None if runtime.running => Err(DetectionError::Busy("训练正在导出数据集，启动后才能取消".to_string())),
+        None => Err(DetectionError::NotFound("当前没有运行中的训练任务".to_string())),
+    }
+}
@@ -0,0 +1,663 @@
+/*!
+定时检测模块
+按类 cron 表达式（本地时间，`分 时 日 月 周`）在指定时间/班次自动执行检测：
+- 文件夹扫描：检测目录下的图片（默认只检测上次成功运行之后新增或修改的图片），结果写入历史并按规则告警
+- 摄像头会话：在指定时长内运行摄像头实时检测，结束后自动停止
+
+每次运行对应一个检测会话（已有进行中的会话时不新建，结果归属当前会话）。
+计划保存在应用数据目录下的JSON文件中，最近一次运行状态同样写入文件，重启后仍可查询
+*/
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Local, NaiveDateTime, TimeZone, Timelike};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::{self, DetectionError};
+use crate::evaluation;
+use crate::folder_watch;
+use crate::realtime::RealtimePipeline;
+use crate::sessions::{SessionKind, SessionManager};
+use crate::source_lock::{self, SourceLocks};
+use crate::storage::{now_rfc3339, Database};
+use crate::yolo::InputSource;
+use crate::yolo_api;
+
+/// 计划配置文件名（位于应用数据目录）
+pub const CONFIG_FILE_NAME: &str = "schedules.json";
+
+/// 运行状态文件名（位于应用数据目录）
+pub const STATUS_FILE_NAME: &str = "schedule_status.json";
+
+/// 计算下次运行时间时最多向后查找的年数
+const MAX_LOOKAHEAD_YEARS: i32 = 5;
+
+/// 摄像头会话时长上限（分钟）
+const MAX_CAMERA_MINUTES: u32 = 24 * 60;
+
+/// cron 表达式（分 时 日 月 周），各字段按位记录允许的取值
+#[derive(Debug, Clone, PartialEq)]
+struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64, // 0 为周日
+    any_day: bool,
+    any_weekday: bool,
+}
+
+/// 解析一个字段：支持 `*`、`*/n`、`a`、`a-b`、`a-b/n` 及逗号分隔的组合
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| anyhow!("{}字段的步长无效: {}", name, part))?;
+                if step == 0 {
+                    return Err(anyhow!("{}字段的步长必须大于0: {}", name, part));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            let start: u32 = start.parse().map_err(|_| anyhow!("{}字段无效: {}", name, part))?;
+            let end: u32 = end.parse().map_err(|_| anyhow!("{}字段无效: {}", name, part))?;
+            (start, end)
+        } else {
+            let value: u32 = range.parse().map_err(|_| anyhow!("{}字段无效: {}", name, part))?;
+            // `5/10` 表示从5开始每10个
+            (value, if step > 1 { max } else { value })
+        };
+        if start < min || end > max || start > end {
+            return Err(anyhow!("{}字段超出范围 {}-{}: {}", name, min, max, part));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+impl CronExpr {
+    fn parse(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(anyhow!("cron表达式须为5个字段（分 时 日 月 周）: {}", expr));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7, "周")?;
+        // 7 与 0 均表示周日
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, "分")?,
+            hours: parse_field(hour, 0, 23, "时")?,
+            days: parse_field(day, 1, 31, "日")?,
+            months: parse_field(month, 1, 12, "月")?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    /// 日期是否匹配：日与周同时限定时满足其一即可（与标准 cron 一致）
+    fn matches_date(&self, time: &NaiveDateTime) -> bool {
+        if self.months & (1 << time.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << time.day()) != 0;
+        let weekday = self.weekdays & (1 << time.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+
+    fn matches(&self, time: &NaiveDateTime) -> bool {
+        self.matches_date(time)
+            && self.hours & (1 << time.hour()) != 0
+            && self.minutes & (1 << time.minute()) != 0
+    }
+
+    /// 严格晚于 after 的下一个匹配时间（按分钟），不匹配的日期/小时整段跳过
+    fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + chrono::Duration::minutes(1);
+        let limit = after.year() + MAX_LOOKAHEAD_YEARS;
+        while time.year() <= limit {
+            if !self.matches_date(&time) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << time.hour()) == 0 {
+                time = time.with_minute(0)? + chrono::Duration::hours(1);
+            } else if self.minutes & (1 << time.minute()) == 0 {
+                time += chrono::Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+}
+
+/// 定时执行的任务
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduledJob {
+    FolderScan {
+        path: String,
+        #[serde(default = "default_true")]
+        only_new: bool, // 只检测上次成功运行之后新增或修改的图片
+    },
+    CameraSession {
+        #[serde(default)]
+        device_id: i32,
+        duration_minutes: u32,
+    },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 一个检测计划
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub cron: String, // 本地时间，如 "0 8,20 * * 1-5" 表示工作日 8:00 与 20:00
+    #[serde(flatten)]
+    pub job: ScheduledJob,
+}
+
+impl Schedule {
+    fn label(&self) -> &str {
+        if self.name.is_empty() { &self.id } else { &self.name }
+    }
+}
+
+/// 计划配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleSettings {
+    pub schedules: Vec<Schedule>,
+}
+
+fn validate(settings: &ScheduleSettings) -> Result<()> {
+    let mut ids = Vec::new();
+    for schedule in &settings.schedules {
+        if schedule.id.trim().is_empty() {
+            return Err(anyhow!("计划ID不能为空"));
+        }
+        if ids.contains(&schedule.id) {
+            return Err(anyhow!("计划ID重复: {}", schedule.id));
+        }
+        ids.push(schedule.id.clone());
+        CronExpr::parse(&schedule.cron).map_err(|e| anyhow!("计划 {} 的时间表达式无效: {}", schedule.id, e))?;
+        match &schedule.job {
+            ScheduledJob::FolderScan { path, .. } if path.trim().is_empty() => {
+                return Err(anyhow!("计划 {} 的扫描目录不能为空", schedule.id));
+            }
+            ScheduledJob::CameraSession { duration_minutes, .. }
+                if *duration_minutes == 0 || *duration_minutes > MAX_CAMERA_MINUTES =>
+            {
+                return Err(anyhow!(
+                    "计划 {} 的检测时长须在 1-{} 分钟之间: {}",
+                    schedule.id, MAX_CAMERA_MINUTES, duration_minutes
+                ));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// 计划的运行状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduleRunStatus {
+    pub schedule_id: String,
+    pub running: bool,
+    pub last_started_at: Option<String>,
+    pub last_finished_at: Option<String>,
+    pub last_success: Option<bool>,
+    pub last_message: Option<String>,    // 运行汇总或错误信息
+    pub last_session_id: Option<i64>,    // 最近一次运行的检测会话
+    pub last_success_at: Option<String>, // 最近一次成功运行的开始时间（文件夹扫描据此只检测新图片）
+    pub next_run_at: Option<String>,
+}
+
+/// 运行触发方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Trigger {
+    Scheduled,
+    Manual,
+}
+
+/// 定时检测调度器（Tauri托管状态）
+pub struct Scheduler {
+    path: PathBuf,
+    status_path: PathBuf,
+    settings: RwLock<ScheduleSettings>,
+    status: Mutex<HashMap<String, ScheduleRunStatus>>,
+}
+
+impl Scheduler {
+    /// 读取已保存的计划与运行状态（上次退出时未结束的运行标记为中断）
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(CONFIG_FILE_NAME);
+        let settings = match std::fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<ScheduleSettings>(&content) {
+                Ok(settings) if validate(&settings).is_ok() => settings,
+                Ok(_) | Err(_) => {
+                    tracing::error!("定时检测配置文件无效，已停用定时检测: {}", path.display());
+                    ScheduleSettings::default()
+                }
+            },
+            Err(_) => ScheduleSettings::default(),
+        };
+        let status_path = data_dir.join(STATUS_FILE_NAME);
+        let mut status: HashMap<String, ScheduleRunStatus> = std::fs::read_to_string(&status_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        for entry in status.values_mut().filter(|entry| entry.running) {
+            entry.running = false;
+            entry.last_success = Some(false);
+            entry.last_message = Some("应用退出，运行中断".to_string());
+        }
+        Self {
+            path,
+            status_path,
+            settings: RwLock::new(settings),
+            status: Mutex::new(status),
+        }
+    }
+
+    pub fn settings(&self) -> ScheduleSettings {
+        self.settings.read().clone()
+    }
+
+    fn save(&self, settings: ScheduleSettings) -> Result<()> {
+        validate(&settings).map_err(error::invalid_input)?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&settings)?)?;
+        *self.settings.write() = settings;
+        Ok(())
+    }
+
+    /// 各计划的运行状态（含下次运行时间）
+    pub fn status(&self) -> Vec<ScheduleRunStatus> {
+        let now = Local::now().naive_local();
+        let status = self.status.lock();
+        self.settings
+            .read()
+            .schedules
+            .iter()
+            .map(|schedule| {
+                let mut entry = status.get(&schedule.id).cloned().unwrap_or_default();
+                entry.schedule_id = schedule.id.clone();
+                entry.next_run_at = schedule
+                    .enabled
+                    .then(|| CronExpr::parse(&schedule.cron).ok())
+                    .flatten()
+                    .and_then(|cron| cron.next_after(now))
+                    .and_then(|time| Local.from_local_datetime(&time).earliest())
+                    .map(|time| time.to_rfc3339());
+                entry
+            })
+            .collect()
+    }
+
+    fn update_status(&self, schedule_id: &str, f: impl FnOnce(&mut ScheduleRunStatus)) {
+        let snapshot = {
+            let mut status = self.status.lock();
+            let entry = status.entry(schedule_id.to_string()).or_default();
+            entry.schedule_id = schedule_id.to_string();
+            f(entry);
+            status.clone()
+        };
+        let written = serde_json::to_string_pretty(&snapshot)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| std::fs::write(&self.status_path, content).map_err(anyhow::Error::from));
+        if let Err(e) = written {
+            tracing::error!("保存定时检测状态失败: {}", e);
+        }
+    }
+
+    /// 标记计划开始运行，已在运行时返回 false
+    fn begin(&self, schedule_id: &str) -> bool {
+        if self.status.lock().get(schedule_id).is_some_and(|entry| entry.running) {
+            return false;
+        }
+        self.update_status(schedule_id, |entry| {
+            entry.running = true;
+            entry.last_started_at = Some(now_rfc3339());
+            entry.last_finished_at = None;
+            entry.last_message = None;
+            entry.last_session_id = None;
+        });
+        true
+    }
+
+    /// 当前时间（精确到分钟）到期的已启用计划
+    fn due(&self, time: &NaiveDateTime) -> Vec<Schedule> {
+        self.settings
+            .read()
+            .schedules
+            .iter()
+            .filter(|schedule| schedule.enabled)
+            .filter(|schedule| CronExpr::parse(&schedule.cron).is_ok_and(|cron| cron.matches(time)))
+            .cloned()
+            .collect()
+    }
+}
+
+/// 文件夹扫描：逐张检测目录下的图片
+async fn run_folder_scan(app: &AppHandle, path: &str, since: Option<&str>) -> Result<String> {
+    let root = PathBuf::from(path);
+    if !root.is_dir() {
+        return Err(anyhow!("扫描目录不存在: {}", path));
+    }
+    let since = since
+        .and_then(|value| chrono::DateTime::parse_from_rfc3339(value).ok())
+        .map(std::time::SystemTime::from);
+    let images: Vec<PathBuf> = evaluation::list_images(&root)?
+        .into_iter()
+        .filter(|image| match since {
+            Some(since) => std::fs::metadata(image)
+                .and_then(|meta| meta.modified())
+                .ok()
+                .is_none_or(|modified| modified >= since),
+            None => true,
+        })
+        .collect();
+
+    let mut processed = 0;
+    let mut failed = 0;
+    let mut detections = 0;
+    let mut alerts = 0;
+    for image in &images {
+        match folder_watch::detect_path(app, image).await {
+            Ok(result) => {
+                processed += 1;
+                detections += result.result.detections.len();
+                alerts += result.alerts.len();
+            }
+            Err(e) => {
+                failed += 1;
+                tracing::error!("定时扫描检测失败 {}: {}", image.display(), e);
+            }
+        }
+    }
+    if failed > 0 && processed == 0 {
+        return Err(anyhow!("{} 张图片全部检测失败", failed));
+    }
+    Ok(format!(
+        "检测 {} 张图片（失败 {} 张），{} 个检测框，{} 条告警",
+        processed, failed, detections, alerts
+    ))
+}
+
+/// 摄像头会话：运行指定时长后停止（期间被手动停止或切换输入源时提前结束）
+async fn run_camera_session(app: &AppHandle, device_id: i32, duration_minutes: u32) -> Result<String> {
+    yolo_api::start_camera_detection(app.clone(), app.state(), app.state(), Some(device_id)).await?;
+
+    let started = std::time::Instant::now();
+    let duration = Duration::from_secs(duration_minutes as u64 * 60);
+    let pipeline = app.state::<RealtimePipeline>();
+    let ours = |stats: &crate::realtime::RealtimeStats| {
+        stats.is_running && matches!(&stats.input_source, Some(InputSource::Camera(id)) if *id == device_id)
+    };
+    while started.elapsed() < duration && ours(&pipeline.stats()) {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    let stats = pipeline.stats();
+    let stopped_early = !ours(&stats);
+    if !stopped_early {
+        pipeline.stop();
+        app.state::<SourceLocks>().release(&source_lock::camera_key(device_id));
+    }
+    Ok(format!(
+        "摄像头 {} 检测 {:.1} 分钟{}，处理 {} 帧，{} 个检测框",
+        device_id,
+        started.elapsed().as_secs_f64() / 60.0,
+        if stopped_early { "（提前结束）" } else { "" },
+        stats.frame_count,
+        stats.detection_count
+    ))
+}
+
+/// 执行一次计划：开始会话、运行任务、记录状态
+async fn run_schedule(app: AppHandle, schedule: Schedule, trigger: Trigger) {
+    let scheduler = app.state::<Scheduler>();
+    if !scheduler.begin(&schedule.id) {
+        tracing::warn!("⏰ 计划 {} 上一次运行尚未结束，跳过本次", schedule.label());
+        return;
+    }
+    let since = match &schedule.job {
+        ScheduledJob::FolderScan { only_new: true, .. } => scheduler
+            .status
+            .lock()
+            .get(&schedule.id)
+            .and_then(|entry| entry.last_success_at.clone()),
+        _ => None,
+    };
+    let started_at = scheduler.status.lock().get(&schedule.id).and_then(|entry| entry.last_started_at.clone());
+    tracing::info!(
+        "⏰ 开始定时检测 {}{}",
+        schedule.label(),
+        if trigger == Trigger::Manual { "（手动触发）" } else { "" }
+    );
+
+    let (kind, source) = match &schedule.job {
+        ScheduledJob::FolderScan { path, .. } => (SessionKind::Batch, path.clone()),
+        ScheduledJob::CameraSession { device_id, .. } => (SessionKind::Camera, format!("摄像头 {}", device_id)),
+    };
+    let db = app.state::<Database>();
+    let sessions = app.state::<SessionManager>();
+    let session_id = match sessions.start(&db, kind, Some(format!("定时 {}", schedule.label())), Some(source)) {
+        Ok(session) => Some(session.id),
+        Err(e) => {
+            tracing::warn!("定时检测 {} 未新建会话: {}", schedule.label(), e);
+            None
+        }
+    };
+
+    let outcome = match &schedule.job {
+        ScheduledJob::FolderScan { path, .. } => run_folder_scan(&app, path, since.as_deref()).await,
+        ScheduledJob::CameraSession { device_id, duration_minutes } => {
+            run_camera_session(&app, *device_id, *duration_minutes).await
+        }
+    };
+
+    if let Some(id) = session_id {
+        if let Err(e) = sessions.end(&db, Some(id)) {
+            tracing::error!("结束定时检测会话失败: {}", e);
+        }
+    }
+    match &outcome {
+        Ok(summary) => tracing::info!("⏰ 定时检测 {} 完成: {}", schedule.label(), summary),
+        Err(e) => tracing::error!("定时检测 {} 失败: {}", schedule.label(), e),
+    }
+    scheduler.update_status(&schedule.id, |entry| {
+        entry.running = false;
+        entry.last_finished_at = Some(now_rfc3339());
+        entry.last_success = Some(outcome.is_ok());
+        entry.last_session_id = session_id;
+        if outcome.is_ok() {
+            entry.last_success_at = started_at;
+        }
+        entry.last_message = Some(match outcome {
+            Ok(summary) => summary,
+            Err(e) => e.to_string(),
+        });
+    });
+}
+
+/// 后台调度：每到整分钟检查一次到期的计划
+pub fn spawn(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last_checked: Option<NaiveDateTime> = None;
+        loop {
+            let now = Local::now();
+            let wait_ms = 60_000 - (now.second() as u64 * 1000 + now.timestamp_subsec_millis() as u64 % 1000);
+            tokio::time::sleep(Duration::from_millis(wait_ms.max(1))).await;
+
+            let Some(minute) = Local::now().naive_local().with_second(0).and_then(|t| t.with_nanosecond(0)) else {
+                continue;
+            };
+            // 同一分钟只检查一次
+            if last_checked == Some(minute) {
+                continue;
+            }
+            last_checked = Some(minute);
+            for schedule in app.state::<Scheduler>().due(&minute) {
+                tauri::async_runtime::spawn(run_schedule(app.clone(), schedule, Trigger::Scheduled));
+            }
+        }
+    });
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 获取定时检测计划
+#[tauri::command]
+pub async fn get_schedules(
+    scheduler: State<'_, Scheduler>
+) -> Result<ScheduleSettings, DetectionError> {
+    Ok(scheduler.settings())
+}
+
+/// 保存定时检测计划（整体替换，立即生效）
+#[tauri::command]
+pub async fn set_schedules(
+    scheduler: State<'_, Scheduler>,
+    settings: ScheduleSettings
+) -> Result<Vec<ScheduleRunStatus>, DetectionError> {
+    match scheduler.save(settings) {
+        Ok(()) => Ok(scheduler.status()),
+        Err(e) => Err(DetectionError::from(e).context("保存定时检测计划失败")),
+    }
+}
+
+/// 查询各计划最近一次运行的状态与下次运行时间
+#[tauri::command]
+pub async fn get_schedule_status(
+    scheduler: State<'_, Scheduler>
+) -> Result<Vec<ScheduleRunStatus>, DetectionError> {
+    Ok(scheduler.status())
+}
+
+/// 立即运行一次计划（不影响定时运行）
+#[tauri::command]
+pub async fn run_schedule_now(
+    app: AppHandle,
+    scheduler: State<'_, Scheduler>,
+    schedule_id: String
+) -> Result<String, DetectionError> {
+    let schedule = scheduler.settings().schedules.into_iter().find(|s| s.id == schedule_id);
+    let Some(schedule) = schedule else {
+        return Err(DetectionError::NotFound(format!("计划不存在: {}", schedule_id)));
+    };
+    if scheduler.status.lock().get(&schedule_id).is_some_and(|entry| entry.running) {
+        return Err(DetectionError::Busy(format!("计划 {} 正在运行", schedule.label())));
+    }
+    let label = schedule.label().to_string();
+    tauri::async_runtime::spawn(run_schedule(app, schedule, Trigger::Manual));
+    Ok(format!("已开始运行计划 {}", label))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn next(expr: &str, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        CronExpr::parse(expr).unwrap().next_after(after)
+    }
+
+    #[test]
+    fn parses_valid_expressions() {
+        for expr in ["* * * * *", "*/15 8-17 * * 1-5", "0 8,20 * * 1-5", "5/10 * 1 1,6 *", "0 0 * * 7"] {
+            assert!(CronExpr::parse(expr).is_ok(), "{}", expr);
+        }
+        let cron = CronExpr::parse("0,30 8 * * 1-5").unwrap();
+        assert_eq!(cron.minutes, 1 | 1 << 30);
+        assert_eq!(cron.hours, 1 << 8);
+        assert_eq!(cron.weekdays, 0b11_1110);
+        assert!(cron.any_day && !cron.any_weekday);
+
+        // 7 与 0 都表示周日
+        assert_eq!(CronExpr::parse("0 0 * * 7").unwrap().weekdays, 1);
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expr in [
+            "",
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+            "1,,2 * * * *",
+        ] {
+            assert!(CronExpr::parse(expr).is_err(), "{:?}", expr);
+        }
+    }
+
+    #[test]
+    fn next_fire_time_across_minute_boundary() {
+        let after = at(2024, 3, 1, 10, 7).with_second(30).unwrap();
+        assert_eq!(next("*/15 * * * *", after), Some(at(2024, 3, 1, 10, 15)));
+        // 严格晚于给定时间
+        assert_eq!(next("*/15 * * * *", at(2024, 3, 1, 10, 15)), Some(at(2024, 3, 1, 10, 30)));
+        assert_eq!(next("* * * * *", at(2024, 3, 1, 10, 59)), Some(at(2024, 3, 1, 11, 0)));
+    }
+
+    #[test]
+    fn next_fire_time_across_hour_boundary() {
+        assert_eq!(next("*/15 * * * *", at(2024, 3, 1, 10, 50)), Some(at(2024, 3, 1, 11, 0)));
+        assert_eq!(next("0 8,20 * * *", at(2024, 3, 1, 8, 0)), Some(at(2024, 3, 1, 20, 0)));
+        assert_eq!(next("45 */6 * * *", at(2024, 3, 1, 6, 46)), Some(at(2024, 3, 1, 12, 45)));
+    }
+
+    #[test]
+    fn next_fire_time_across_day_boundary() {
+        assert_eq!(next("30 6 * * *", at(2024, 3, 1, 23, 59)), Some(at(2024, 3, 2, 6, 30)));
+        // 跨月、跨年
+        assert_eq!(next("0 0 1 * *", at(2024, 12, 31, 12, 0)), Some(at(2025, 1, 1, 0, 0)));
+        // 2024-03-08 为周五，下一个工作日为周一
+        assert_eq!(next("0 8 * * 1-5", at(2024, 3, 8, 9, 0)), Some(at(2024, 3, 11, 8, 0)));
+        // 日与周同时限定时满足其一即可：3 月 13 日（周三）早于下一个周五
+        assert_eq!(next("0 0 13 * 5", at(2024, 3, 9, 0, 0)), Some(at(2024, 3, 13, 0, 0)));
+        // 闰日只在闰年出现，不存在的日期返回空
+        assert_eq!(next("0 0 29 2 *", at(2024, 3, 1, 0, 0)), Some(at(2028, 2, 29, 0, 0)));
+        assert_eq!(next("0 0 31 2 *", at(2024, 3, 1, 0, 0)), None);
+    }
+}
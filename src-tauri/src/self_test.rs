@@ -0,0 +1,183 @@
+/*!
+启动自检模块
+加载配置的模型、用内置样例图片执行一次推理并检查输出合理性，
+同时验证数据库与输出目录的写权限，返回结构化的通过/失败报告供启动页展示
+*/
+
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::DetectionError;
+use crate::storage::Database;
+use crate::yolo::DetectionResult;
+use crate::AppState;
+
+/// 内置样例图片
+const SAMPLE_IMAGE: &[u8] = include_bytes!("../resources/self_test.jpg");
+
+/// 单张图片合理的最大检测框数量
+const MAX_REASONABLE_BOXES: usize = 300;
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+/// 自检报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub model_path: Option<String>,
+    pub started_at: String,
+    pub checks: Vec<SelfTestCheck>,
+}
+
+fn run_check(checks: &mut Vec<SelfTestCheck>, name: &str, result: Result<String>, start: Instant) -> bool {
+    let passed = result.is_ok();
+    checks.push(SelfTestCheck {
+        name: name.to_string(),
+        passed,
+        detail: result.unwrap_or_else(|e| e.to_string()),
+        duration_ms: start.elapsed().as_millis() as u64,
+    });
+    passed
+}
+
+/// 检查检测输出是否合理
+fn check_output(result: &DetectionResult) -> Result<String> {
+    let sample = image::load_from_memory(SAMPLE_IMAGE)?;
+    if (result.image_width, result.image_height) != (sample.width(), sample.height()) {
+        return Err(anyhow!(
+            "输出图像尺寸 {}x{} 与样例 {}x{} 不一致",
+            result.image_width,
+            result.image_height,
+            sample.width(),
+            sample.height()
+        ));
+    }
+    if result.detections.len() > MAX_REASONABLE_BOXES {
+        return Err(anyhow!("检测框数量异常: {}", result.detections.len()));
+    }
+
+    // 允许检测框略微超出图像边界
+    let tolerance = 0.05 * result.image_width.max(result.image_height) as f32;
+    for detection in &result.detections {
+        let [x, y, w, h] = detection.bbox;
+        if !(0.0..=1.0).contains(&detection.confidence) {
+            return Err(anyhow!("置信度超出范围: {} = {}", detection.class_name, detection.confidence));
+        }
+        if !detection.bbox.iter().all(|v| v.is_finite()) || w <= 0.0 || h <= 0.0 {
+            return Err(anyhow!("检测框无效: {:?}", detection.bbox));
+        }
+        if x < -tolerance
+            || y < -tolerance
+            || x + w > result.image_width as f32 + tolerance
+            || y + h > result.image_height as f32 + tolerance
+        {
+            return Err(anyhow!("检测框超出图像范围: {:?}", detection.bbox));
+        }
+    }
+    Ok(format!("{} 个检测框，坐标与置信度均在合理范围内", result.detections.len()))
+}
+
+/// 验证目录可写（写入并删除探测文件）
+fn check_dir_writable(dir: &Path) -> Result<String> {
+    std::fs::create_dir_all(dir).map_err(|e| anyhow!("无法创建目录 {}: {}", dir.display(), e))?;
+    let probe = dir.join(format!(".self_test_{}", std::process::id()));
+    std::fs::write(&probe, b"ok").map_err(|e| anyhow!("目录不可写 {}: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(format!("{} 可写", dir.display()))
+}
+
+/// 验证数据库可写（获取写锁后回滚）
+fn check_database(db: &Database) -> Result<String> {
+    db.with_conn(|conn| conn.execute_batch("BEGIN IMMEDIATE; ROLLBACK;"))?;
+    Ok(format!("{} 可写", db.path().display()))
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 运行启动自检
+#[tauri::command]
+pub async fn run_self_test(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    db: State<'_, Database>,
+    model_path: Option<String>,
+    output_dir: Option<String>
+) -> Result<SelfTestReport, DetectionError> {
+    let started_at = crate::storage::now_rfc3339();
+    let mut checks = Vec::new();
+    let mut detector = state.write().await;
+
+    // 1. 加载模型（未指定路径时使用当前已加载的模型）
+    let start = Instant::now();
+    let configured = model_path.or_else(|| {
+        let info = detector.get_model_info();
+        match info.get("model_loaded").map(String::as_str) {
+            Some("true") => info.get("model_path").cloned(),
+            _ => None,
+        }
+    });
+    let model_loaded = match &configured {
+        Some(path) => {
+            let result = detector.init_model(path).await.map(|()| format!("已加载 {}", path));
+            run_check(&mut checks, "model_load", result, start)
+        }
+        None => run_check(&mut checks, "model_load", Err(anyhow!("未配置模型路径且当前没有已加载的模型")), start),
+    };
+
+    // 2. 样例推理与输出检查
+    if model_loaded {
+        let start = Instant::now();
+        match detector.detect_image(SAMPLE_IMAGE).await {
+            Ok(result) => {
+                run_check(
+                    &mut checks,
+                    "inference",
+                    Ok(format!("样例推理耗时 {} ms", result.processing_time_ms)),
+                    start,
+                );
+                run_check(&mut checks, "output_sanity", check_output(&result), Instant::now());
+            }
+            Err(e) => {
+                run_check(&mut checks, "inference", Err(anyhow!("样例推理失败: {}", e)), start);
+            }
+        }
+    }
+    drop(detector);
+
+    // 3. 存储写权限
+    run_check(&mut checks, "database_write", check_database(&db), Instant::now());
+    match app.path().app_data_dir() {
+        Ok(dir) => run_check(&mut checks, "data_dir_write", check_dir_writable(&dir), Instant::now()),
+        Err(e) => run_check(&mut checks, "data_dir_write", Err(anyhow!("无法获取应用数据目录: {}", e)), Instant::now()),
+    };
+    if let Some(dir) = output_dir {
+        run_check(&mut checks, "output_dir_write", check_dir_writable(Path::new(&dir)), Instant::now());
+    }
+
+    let passed = checks.iter().all(|c| c.passed);
+    tracing::info!(
+        "{} 启动自检{}: {}/{} 项通过",
+        if passed { "✅" } else { "❌" },
+        if passed { "通过" } else { "失败" },
+        checks.iter().filter(|c| c.passed).count(),
+        checks.len()
+    );
+
+    Ok(SelfTestReport {
+        passed,
+        model_path: configured,
+        started_at,
+        checks,
+    })
+}
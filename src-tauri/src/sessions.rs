@@ -0,0 +1,336 @@
+/*!
+检测会话模块
+一次摄像头/视频流运行、一个视频文件或一次批量任务构成一个检测会话，
+会话进行期间的所有检测运行都归属该会话（实时检测的推理帧也仅在会话进行中写入历史），
+会话的汇总统计由其检测运行实时计算，并作为导出与报告的单位（见 `HistoryFilter::session_id`）
+同一时间只有一个进行中的会话
+*/
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::alerts::ABNORMAL_CLASS_NAME;
+use crate::error::DetectionError;
+use crate::storage::{self, now_rfc3339, Database};
+
+/// 会话列表默认条数
+const DEFAULT_LIST_LIMIT: u32 = 50;
+
+/// 会话类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionKind {
+    Camera,      // 摄像头实时检测
+    Stream,      // 网络视频流
+    Video,       // 视频文件
+    Batch,       // 批量图片任务
+    FolderWatch, // 文件夹监控
+    Manual,      // 手动逐张检测
+}
+
+impl SessionKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SessionKind::Camera => "camera",
+            SessionKind::Stream => "stream",
+            SessionKind::Video => "video",
+            SessionKind::Batch => "batch",
+            SessionKind::FolderWatch => "folder_watch",
+            SessionKind::Manual => "manual",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "camera" => SessionKind::Camera,
+            "stream" => SessionKind::Stream,
+            "video" => SessionKind::Video,
+            "batch" => SessionKind::Batch,
+            "folder_watch" => SessionKind::FolderWatch,
+            _ => SessionKind::Manual,
+        }
+    }
+}
+
+/// 会话汇总统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub run_count: u64,
+    pub detection_count: u64,
+    pub abnormal_run_count: u64, // 含异常类别检测框的运行数
+    pub avg_processing_ms: f64,
+    pub class_counts: BTreeMap<String, u64>,
+    pub first_run_at: Option<String>,
+    pub last_run_at: Option<String>,
+}
+
+/// 检测会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionSession {
+    pub id: i64,
+    pub kind: SessionKind,
+    pub name: String,
+    pub source: Option<String>,
+    pub started_at: String,
+    pub ended_at: Option<String>, // 为空表示进行中
+    pub stats: SessionStats,
+}
+
+/// 初始化会话表结构（需在检测历史表之后调用）
+pub fn init_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS detection_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            name TEXT NOT NULL,
+            source TEXT,
+            started_at TEXT NOT NULL,
+            ended_at TEXT
+        );",
+    )?;
+    storage::add_column_if_missing(
+        conn,
+        "detection_runs",
+        "session_id",
+        "INTEGER REFERENCES detection_sessions(id) ON DELETE SET NULL",
+    )?;
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_detection_runs_session ON detection_runs(session_id);")?;
+
+    // 上次异常退出时未结束的会话，以最后一次检测时间作为结束时间
+    conn.execute(
+        "UPDATE detection_sessions SET ended_at = COALESCE(
+            (SELECT MAX(r.created_at) FROM detection_runs r WHERE r.session_id = detection_sessions.id),
+            started_at)
+         WHERE ended_at IS NULL",
+        [],
+    )?;
+    Ok(())
+}
+
+const SESSION_COLUMNS: &str = "id, kind, name, source, started_at, ended_at";
+
+fn row_to_session(row: &Row) -> rusqlite::Result<DetectionSession> {
+    Ok(DetectionSession {
+        id: row.get(0)?,
+        kind: SessionKind::parse(&row.get::<_, String>(1)?),
+        name: row.get(2)?,
+        source: row.get(3)?,
+        started_at: row.get(4)?,
+        ended_at: row.get(5)?,
+        stats: SessionStats::default(),
+    })
+}
+
+fn load_stats(conn: &Connection, session_id: i64) -> rusqlite::Result<SessionStats> {
+    let mut stats = conn.query_row(
+        "SELECT COUNT(*), COALESCE(AVG(processing_time_ms), 0), MIN(created_at), MAX(created_at)
+         FROM detection_runs WHERE session_id = ?1",
+        params![session_id],
+        |row| {
+            Ok(SessionStats {
+                run_count: row.get::<_, i64>(0)? as u64,
+                avg_processing_ms: row.get(1)?,
+                first_run_at: row.get(2)?,
+                last_run_at: row.get(3)?,
+                ..SessionStats::default()
+            })
+        },
+    )?;
+
+    let mut stmt = conn.prepare(
+        "SELECT d.class_name, COUNT(*) FROM detections d
+         JOIN detection_runs r ON r.id = d.run_id
+         WHERE r.session_id = ?1 GROUP BY d.class_name",
+    )?;
+    stats.class_counts = stmt
+        .query_map(params![session_id], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)))?
+        .collect::<rusqlite::Result<BTreeMap<String, u64>>>()?;
+    stats.detection_count = stats.class_counts.values().sum();
+
+    stats.abnormal_run_count = conn.query_row(
+        "SELECT COUNT(DISTINCT r.id) FROM detection_runs r
+         JOIN detections d ON d.run_id = r.id
+         WHERE r.session_id = ?1 AND d.class_name = ?2",
+        params![session_id, ABNORMAL_CLASS_NAME],
+        |row| row.get::<_, i64>(0),
+    )? as u64;
+
+    Ok(stats)
+}
+
+/// 查询单个会话（含汇总统计）
+pub fn load_session(db: &Database, session_id: i64) -> Result<Option<DetectionSession>> {
+    db.with_conn(|conn| {
+        let session = conn
+            .query_row(
+                &format!("SELECT {} FROM detection_sessions WHERE id = ?1", SESSION_COLUMNS),
+                params![session_id],
+                row_to_session,
+            )
+            .optional()?;
+        match session {
+            Some(mut session) => {
+                session.stats = load_stats(conn, session.id)?;
+                Ok(Some(session))
+            }
+            None => Ok(None),
+        }
+    })
+}
+
+/// 查询最近的会话（最新的在前）
+pub fn recent_sessions(db: &Database, limit: u32) -> Result<Vec<DetectionSession>> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM detection_sessions ORDER BY started_at DESC, id DESC LIMIT ?1",
+            SESSION_COLUMNS
+        ))?;
+        let mut sessions = stmt
+            .query_map(params![limit], row_to_session)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for session in sessions.iter_mut() {
+            session.stats = load_stats(conn, session.id)?;
+        }
+        Ok(sessions)
+    })
+}
+
+/// 会话管理器（Tauri托管状态），记录当前进行中的会话
+#[derive(Default)]
+pub struct SessionManager {
+    active: Mutex<Option<i64>>,
+}
+
+impl SessionManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前进行中的会话ID
+    pub fn current(&self) -> Option<i64> {
+        *self.active.lock()
+    }
+
+    /// 开始新会话（已有进行中的会话时报错）
+    pub fn start(&self, db: &Database, kind: SessionKind, name: Option<String>, source: Option<String>) -> Result<DetectionSession> {
+        let mut active = self.active.lock();
+        if let Some(id) = *active {
+            return Err(anyhow!("会话 {} 尚未结束，请先结束当前会话", id));
+        }
+
+        let started_at = now_rfc3339();
+        let name = name
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| format!("{} {}", kind.as_str(), started_at));
+        let id = db.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO detection_sessions (kind, name, source, started_at) VALUES (?1, ?2, ?3, ?4)",
+                params![kind.as_str(), name, source, started_at],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })?;
+        *active = Some(id);
+        tracing::info!("🎬 检测会话已开始: #{} {}", id, name);
+
+        Ok(DetectionSession {
+            id,
+            kind,
+            name,
+            source,
+            started_at,
+            ended_at: None,
+            stats: SessionStats::default(),
+        })
+    }
+
+    /// 结束会话（未指定时结束当前会话），返回含汇总统计的会话
+    pub fn end(&self, db: &Database, session_id: Option<i64>) -> Result<DetectionSession> {
+        let mut active = self.active.lock();
+        let id = session_id
+            .or(*active)
+            .ok_or_else(|| anyhow!("没有进行中的会话"))?;
+
+        let updated = db.with_conn(|conn| {
+            conn.execute(
+                "UPDATE detection_sessions SET ended_at = ?1 WHERE id = ?2 AND ended_at IS NULL",
+                params![now_rfc3339(), id],
+            )
+        })?;
+        if *active == Some(id) {
+            *active = None;
+        }
+
+        let session = load_session(db, id)?.ok_or_else(|| DetectionError::NotFound(format!("会话不存在: {}", id)))?;
+        if updated == 0 {
+            return Err(anyhow!("会话 {} 已于 {} 结束", id, session.ended_at.unwrap_or_default()));
+        }
+        tracing::info!(
+            "🏁 检测会话已结束: #{} {} ({} 次检测, {} 个检测框)",
+            session.id, session.name, session.stats.run_count, session.stats.detection_count
+        );
+        Ok(session)
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 开始检测会话，之后的检测运行归属该会话
+#[tauri::command]
+pub async fn start_session(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionManager>,
+    kind: SessionKind,
+    name: Option<String>,
+    source: Option<String>
+) -> Result<DetectionSession, DetectionError> {
+    match sessions.start(&db, kind, name, source) {
+        Ok(session) => Ok(session),
+        Err(e) => Err(DetectionError::from(e).context("开始会话失败")),
+    }
+}
+
+/// 结束检测会话（默认结束当前会话）
+#[tauri::command]
+pub async fn end_session(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionManager>,
+    session_id: Option<i64>
+) -> Result<DetectionSession, DetectionError> {
+    match sessions.end(&db, session_id) {
+        Ok(session) => Ok(session),
+        Err(e) => Err(DetectionError::from(e).context("结束会话失败")),
+    }
+}
+
+/// 获取会话详情（默认当前会话）
+#[tauri::command]
+pub async fn get_session(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionManager>,
+    session_id: Option<i64>
+) -> Result<Option<DetectionSession>, DetectionError> {
+    let Some(id) = session_id.or(sessions.current()) else {
+        return Ok(None);
+    };
+    match load_session(&db, id) {
+        Ok(session) => Ok(session),
+        Err(e) => Err(DetectionError::from(e).context("获取会话失败")),
+    }
+}
+
+/// 列出最近的检测会话
+#[tauri::command]
+pub async fn list_sessions(
+    db: State<'_, Database>,
+    limit: Option<u32>
+) -> Result<Vec<DetectionSession>, DetectionError> {
+    match recent_sessions(&db, limit.unwrap_or(DEFAULT_LIST_LIMIT).max(1)) {
+        Ok(sessions) => Ok(sessions),
+        Err(e) => Err(DetectionError::from(e).context("查询会话失败")),
+    }
+}
@@ -0,0 +1,163 @@
+/*!
+实时画面抓拍模块
+操作员在实时检测中发现异常时一键抓拍：取当前画面与最近一次推理结果，
+原图与标注图保存到 `<输出目录>/<日期>/`，结果写入检测历史（历史来源指向抓拍原图，可在回放中重新绘制），
+同时返回两张图的路径与base64编码供前端立即展示。
+尚未推理过的画面在抓拍时补做一次推理；保存到磁盘的图片按隐私遮挡设置遮挡
+*/
+
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use image::{DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::DetectionError;
+use crate::history;
+use crate::inference_worker::InferenceWorker;
+use crate::privacy;
+use crate::profiles::ProfileStore;
+use crate::realtime::{LiveFrame, RealtimePipeline};
+use crate::sessions::SessionManager;
+use crate::source_lock::sanitize_key;
+use crate::storage::Database;
+use crate::yolo::DetectionResult;
+use crate::yolo_api::{draw_detections_on_image, draw_detections_unmasked, image_to_base64, Detection};
+
+/// 未指定输出目录时使用的默认子目录（位于应用数据目录）
+const DEFAULT_OUTPUT_DIR: &str = "snapshots";
+
+/// 抓拍结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub run_id: i64,            // 检测历史记录ID
+    pub source: String,
+    pub raw_path: String,       // 原图（无标注）
+    pub annotated_path: String, // 标注图
+    pub raw_image: String,      // 原图base64
+    pub annotated_image: String,
+    pub detections: Vec<Detection>,
+    pub timestamp: String,
+}
+
+/// 保存抓拍图片，返回 (原图路径, 标注图路径)
+fn save_images(
+    output_dir: &Path,
+    session: &str,
+    image: &DynamicImage,
+    result: &DetectionResult,
+) -> Result<(PathBuf, PathBuf)> {
+    let now = chrono::Local::now();
+    let day_dir = output_dir.join(now.format("%Y-%m-%d").to_string());
+    std::fs::create_dir_all(&day_dir)
+        .map_err(|e| anyhow!("创建抓拍目录失败 {}: {}", day_dir.display(), e))?;
+    let prefix = format!("{}_{}", now.format("%H%M%S_%3f"), sanitize_key(session));
+
+    let stored = privacy::for_storage(image);
+    let raw_path = day_dir.join(format!("{}_raw.jpg", prefix));
+    stored
+        .to_rgb8()
+        .save(&raw_path)
+        .map_err(|e| anyhow!("保存抓拍原图失败 {}: {}", raw_path.display(), e))?;
+    let annotated = draw_detections_unmasked(&stored, &result.detections).map_err(|e| anyhow!(e))?;
+    let annotated_path = day_dir.join(format!("{}_annotated.jpg", prefix));
+    annotated
+        .to_rgb8()
+        .save(&annotated_path)
+        .map_err(|e| anyhow!("保存抓拍标注图失败 {}: {}", annotated_path.display(), e))?;
+    Ok((raw_path, annotated_path))
+}
+
+/// 抓拍当前画面：补做推理（如需要）、保存图片并写入检测历史
+async fn capture(app: &AppHandle, live: LiveFrame, output_dir: &Path) -> Result<Snapshot> {
+    let LiveFrame { session, source, image, result, track_ids } = live;
+    let result = match result {
+        Some(result) => result,
+        None => {
+            let mut data = Vec::new();
+            image.write_to(&mut Cursor::new(&mut data), ImageFormat::Jpeg)?;
+            let mut result = app.state::<InferenceWorker>().detect(data).await?;
+            result.set_frame(&session, None, None);
+            result
+        }
+    };
+
+    let (raw_path, annotated_path) = {
+        let image = image.clone();
+        let result = result.clone();
+        let output_dir = output_dir.to_path_buf();
+        tokio::task::spawn_blocking(move || save_images(&output_dir, &session, &image, &result))
+            .await
+            .map_err(|e| anyhow!("抓拍保存任务异常: {}", e))??
+    };
+
+    // 历史来源指向抓拍原图，回放与数据集导出可直接读取
+    let run_id = history::record_run(
+        &app.state::<Database>(),
+        &raw_path.to_string_lossy(),
+        &result,
+        app.state::<SessionManager>().current(),
+        app.state::<ProfileStore>().active().as_deref(),
+    )?;
+
+    // 返回前端的图片按输出画面处理（总是遮挡隐私区域）
+    let mut raw_output = image.to_rgb8();
+    privacy::mask_output(&mut raw_output);
+    let raw_image = image_to_base64(&DynamicImage::ImageRgb8(raw_output)).map_err(|e| anyhow!(e))?;
+    let annotated = draw_detections_on_image(&image, &result.detections).map_err(|e| anyhow!(e))?;
+    let annotated_image = image_to_base64(&annotated).map_err(|e| anyhow!(e))?;
+
+    let detections = result
+        .detections
+        .iter()
+        .enumerate()
+        .map(|(index, d)| Detection {
+            class_name: d.class_name.clone(),
+            confidence: d.confidence,
+            bbox: d.bbox,
+            track_id: track_ids.get(index).copied().flatten(),
+            size_mm: d.size_mm,
+        })
+        .collect();
+
+    tracing::info!("📸 已抓拍 {}: {} (历史记录 {})", source, raw_path.display(), run_id);
+    Ok(Snapshot {
+        run_id,
+        source,
+        raw_path: raw_path.to_string_lossy().to_string(),
+        annotated_path: annotated_path.to_string_lossy().to_string(),
+        raw_image,
+        annotated_image,
+        detections,
+        timestamp: crate::storage::now_rfc3339(),
+    })
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 抓拍实时检测的当前画面（未指定目录时保存到应用数据目录下的 snapshots）
+#[tauri::command]
+pub async fn capture_snapshot(
+    app: AppHandle,
+    pipeline: State<'_, RealtimePipeline>,
+    output_dir: Option<String>
+) -> Result<Snapshot, DetectionError> {
+    let live = match pipeline.live_frame() {
+        Ok(live) => live,
+        Err(e) => return Err(DetectionError::from(e).context("抓拍失败")),
+    };
+    let output_dir = match output_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => match app.path().app_data_dir() {
+            Ok(dir) => dir.join(DEFAULT_OUTPUT_DIR),
+            Err(e) => return Err(DetectionError::from(e).context("抓拍失败")),
+        },
+    };
+
+    match capture(&app, live, &output_dir).await {
+        Ok(snapshot) => Ok(snapshot),
+        Err(e) => Err(DetectionError::from(e).context("抓拍失败")),
+    }
+}
@@ -0,0 +1,160 @@
+/*!
+输入源独占锁模块
+通过操作系统文件锁防止多个应用实例（或CLI与应用）同时打开同一摄像头
+进程退出时操作系统自动释放文件锁，不会残留死锁
+*/
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use fs2::FileExt;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::error::DetectionError;
+
+/// 锁文件目录名（位于系统临时目录下，所有实例共享）
+const LOCK_DIR_NAME: &str = "yolo-detection-system-locks";
+
+/// 占用者信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceOwner {
+    pub pid: u32,
+    pub host: String,
+    pub source: String,
+    pub acquired_at: String,
+}
+
+/// 已持有的输入源锁，释放时自动解锁
+pub struct SourceLockGuard {
+    key: String,
+    file: File,
+    owner_path: PathBuf,
+}
+
+impl Drop for SourceLockGuard {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.owner_path);
+        let _ = self.file.unlock();
+        tracing::info!("🔓 已释放输入源锁: {}", self.key);
+    }
+}
+
+fn lock_dir() -> PathBuf {
+    std::env::temp_dir().join(LOCK_DIR_NAME)
+}
+
+fn host_name() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "unknown-host".to_string())
+}
+
+/// 将输入源描述转换为安全的文件名
+pub(crate) fn sanitize_key(key: &str) -> String {
+    key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// 摄像头设备的锁键
+pub fn camera_key(device_id: i32) -> String {
+    format!("camera-{}", device_id)
+}
+
+/// 网络视频流的锁键（不含账号密码）
+pub fn stream_key(url: &str) -> String {
+    format!("stream-{}", crate::capture::redact_url(url))
+}
+
+/// 尝试获取输入源独占锁
+pub fn acquire(key: &str, description: &str) -> Result<SourceLockGuard> {
+    let dir = lock_dir();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| anyhow!("创建锁目录失败 {}: {}", dir.display(), e))?;
+
+    let file_key = sanitize_key(key);
+    let lock_path = dir.join(format!("{}.lock", file_key));
+    let owner_path = dir.join(format!("{}.owner", file_key));
+
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(&lock_path)
+        .map_err(|e| anyhow!("打开锁文件失败 {}: {}", lock_path.display(), e))?;
+
+    if file.try_lock_exclusive().is_err() {
+        let owner = std::fs::read_to_string(&owner_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<SourceOwner>(&content).ok());
+        let message = match owner {
+            Some(owner) => format!(
+                "输入源正被占用: {} (PID {} @ {}，自 {} 起)",
+                description,
+                owner.pid,
+                owner.host,
+                owner.acquired_at
+            ),
+            None => format!("输入源正被其他进程占用: {}", description),
+        };
+        return Err(DetectionError::Busy(message).into());
+    }
+
+    let owner = SourceOwner {
+        pid: std::process::id(),
+        host: host_name(),
+        source: description.to_string(),
+        acquired_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    };
+    std::fs::write(&owner_path, serde_json::to_string(&owner)?)
+        .map_err(|e| anyhow!("写入锁信息失败 {}: {}", owner_path.display(), e))?;
+
+    tracing::info!("🔒 已获取输入源锁: {} (PID {})", description, owner.pid);
+
+    Ok(SourceLockGuard {
+        key: key.to_string(),
+        file,
+        owner_path,
+    })
+}
+
+/// 本进程持有的输入源锁表（Tauri托管状态）
+#[derive(Default)]
+pub struct SourceLocks {
+    held: Mutex<HashMap<String, SourceLockGuard>>,
+}
+
+impl SourceLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 获取锁并在本进程内保持，重复获取同一输入源视为成功
+    pub fn hold(&self, key: &str, description: &str) -> Result<()> {
+        let mut held = self.held.lock();
+        if held.contains_key(key) {
+            return Ok(());
+        }
+        let guard = acquire(key, description)?;
+        held.insert(key.to_string(), guard);
+        Ok(())
+    }
+
+    /// 本进程是否持有指定输入源锁
+    pub fn is_held(&self, key: &str) -> bool {
+        self.held.lock().contains_key(key)
+    }
+
+    /// 释放指定输入源锁
+    pub fn release(&self, key: &str) {
+        self.held.lock().remove(key);
+    }
+
+    /// 释放本进程持有的全部锁
+    pub fn release_all(&self) {
+        self.held.lock().clear();
+    }
+}
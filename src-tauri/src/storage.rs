@@ -0,0 +1,83 @@
+/*!
+本地持久化存储模块
+基于嵌入式SQLite数据库，数据库文件位于应用数据目录下
+*/
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+/// 数据库文件名
+pub const DATABASE_FILE_NAME: &str = "yolo_detection.db";
+
+/// SQLite数据库句柄（作为Tauri托管状态共享）
+pub struct Database {
+    conn: Mutex<Connection>,
+    path: PathBuf,
+}
+
+impl Database {
+    /// 打开（或创建）数据库并初始化所有表结构
+    pub fn open(data_dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(data_dir)
+            .map_err(|e| anyhow!("创建数据目录失败 {}: {}", data_dir.display(), e))?;
+
+        let path = data_dir.join(DATABASE_FILE_NAME);
+        let conn = Connection::open(&path)
+            .map_err(|e| anyhow!("打开数据库失败 {}: {}", path.display(), e))?;
+
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;")?;
+        Self::migrate(&conn)?;
+
+        tracing::info!("🗄️ 数据库已就绪: {}", path.display());
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            path,
+        })
+    }
+
+    /// 初始化各子系统的表结构
+    fn migrate(conn: &Connection) -> Result<()> {
+        crate::alerts::init_schema(conn)?;
+        crate::history::init_schema(conn)?;
+        crate::sessions::init_schema(conn)?;
+        crate::corrections::init_schema(conn)?;
+        crate::ground_truth::init_schema(conn)?;
+        crate::event_recording::init_schema(conn)?;
+        Ok(())
+    }
+
+    /// 在数据库连接上执行操作
+    pub fn with_conn<T>(
+        &self,
+        f: impl FnOnce(&Connection) -> rusqlite::Result<T>,
+    ) -> Result<T> {
+        let conn = self.conn.lock();
+        f(&conn).map_err(|e| anyhow!("数据库操作失败: {}", e))
+    }
+
+    /// 数据库文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// 为已有表补充新增的列（旧版本数据库升级）
+pub fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        rusqlite::params![table, column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+    }
+    Ok(())
+}
+
+/// 当前UTC时间（RFC3339，毫秒精度，可直接按字符串排序比较）
+pub fn now_rfc3339() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
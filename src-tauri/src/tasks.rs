@@ -0,0 +1,333 @@
+/*!
+后台任务管理
+批量检测、结果导出、GIF/延时视频/事件片段生成、模型下载等耗时操作开始时登记为任务，
+分配任务ID并记录状态与进度，状态变化通过 `task://progress` 事件推送给前端。
+任务ID可由前端预先生成并随命令传入，未传入时自动分配；
+各处理循环在每个单元之间检查取消令牌，`cancel_task(id)` 可中途终止任务
+*/
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::DetectionError;
+use crate::storage::now_rfc3339;
+
+/// 任务状态或进度变化事件
+pub const EVENT_TASK_PROGRESS: &str = "task://progress";
+
+/// 进度事件的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 保留的已结束任务数
+const MAX_FINISHED_TASKS: usize = 50;
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// 任务信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskInfo {
+    pub id: String,
+    pub kind: String, // batch / export / gif / timelapse / clips / model_download
+    pub state: TaskState,
+    pub completed: u64,
+    pub total: Option<u64>,    // 总量未知时为空
+    pub progress: Option<f32>, // 0-100
+    pub message: Option<String>,
+    pub cancel_requested: bool,
+    pub created_at: String,
+    pub updated_at: String,
+    pub finished_at: Option<String>,
+}
+
+struct TaskEntry {
+    seq: u64,
+    info: TaskInfo,
+    token: CancellationToken,
+}
+
+/// 任务登记表（Tauri托管状态）
+#[derive(Default)]
+pub struct TaskManager {
+    tasks: Mutex<HashMap<String, TaskEntry>>,
+    next_id: AtomicU64,
+}
+
+/// 运行中的任务，结束时调用 `finish`；未调用就离开作用域时按失败（或已取消）记录
+pub struct TaskHandle<'a> {
+    manager: &'a TaskManager,
+    app: AppHandle,
+    id: String,
+    token: CancellationToken,
+    last_emit: Mutex<Option<Instant>>,
+    finished: bool,
+}
+
+impl TaskHandle<'_> {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// 处理下一个单元前调用：已取消时返回 `Cancelled`，否则记录进度
+    pub fn advance(&self, completed: u64, total: Option<u64>) -> Result<(), DetectionError> {
+        if self.is_cancelled() {
+            return Err(DetectionError::Cancelled);
+        }
+        self.set_progress(completed, total);
+        Ok(())
+    }
+
+    /// 更新进度，事件按最小间隔节流（完成最后一个单元时立即推送）
+    pub fn set_progress(&self, completed: u64, total: Option<u64>) {
+        let due = {
+            let mut last_emit = self.last_emit.lock();
+            let due = match *last_emit {
+                Some(at) => at.elapsed() >= PROGRESS_INTERVAL,
+                None => true,
+            } || total == Some(completed);
+            if due {
+                *last_emit = Some(Instant::now());
+            }
+            due
+        };
+        let info = self.manager.update(&self.id, |info| {
+            info.completed = completed;
+            info.total = total;
+            info.progress = total
+                .filter(|total| *total > 0)
+                .map(|total| (completed as f32 / total as f32 * 100.0).min(100.0));
+        });
+        if due {
+            self.emit(info);
+        }
+    }
+
+    /// 按操作结果结束任务，令牌已取消时记为已取消
+    pub fn finish<T, E: Display>(mut self, result: &Result<T, E>) {
+        let (state, message) = match result {
+            _ if self.is_cancelled() => (TaskState::Cancelled, None),
+            Ok(_) => (TaskState::Completed, None),
+            Err(e) => (TaskState::Failed, Some(e.to_string())),
+        };
+        self.end(state, message);
+    }
+
+    /// 成功结束任务，令牌已取消时记为已取消（如批量检测返回部分结果）
+    pub fn complete(mut self) {
+        let state = if self.is_cancelled() { TaskState::Cancelled } else { TaskState::Completed };
+        self.end(state, None);
+    }
+
+    fn end(&mut self, state: TaskState, message: Option<String>) {
+        self.finished = true;
+        let info = self.manager.update(&self.id, |info| {
+            info.state = state;
+            if state == TaskState::Completed {
+                if let Some(total) = info.total {
+                    info.completed = total;
+                    info.progress = Some(100.0);
+                }
+            }
+            if message.is_some() {
+                info.message = message;
+            }
+            info.finished_at = Some(now_rfc3339());
+        });
+        self.manager.prune();
+        self.emit(info);
+    }
+
+    fn emit(&self, info: Option<TaskInfo>) {
+        if let Some(info) = info {
+            let _ = self.app.emit(EVENT_TASK_PROGRESS, info);
+        }
+    }
+}
+
+impl Drop for TaskHandle<'_> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        if self.is_cancelled() {
+            self.end(TaskState::Cancelled, None);
+        } else {
+            self.end(TaskState::Failed, Some("任务意外结束".to_string()));
+        }
+    }
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记一个任务，id 为空时自动分配
+    pub fn begin(&self, app: &AppHandle, kind: &str, id: Option<String>) -> Result<TaskHandle<'_>, DetectionError> {
+        let seq = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let id = match id.filter(|id| !id.trim().is_empty()) {
+            Some(id) => id,
+            None => format!("{}-{}", kind, seq),
+        };
+        let token = CancellationToken::new();
+        let now = now_rfc3339();
+        let info = TaskInfo {
+            id: id.clone(),
+            kind: kind.to_string(),
+            state: TaskState::Running,
+            completed: 0,
+            total: None,
+            progress: None,
+            message: None,
+            cancel_requested: false,
+            created_at: now.clone(),
+            updated_at: now,
+            finished_at: None,
+        };
+        {
+            let mut tasks = self.tasks.lock();
+            if tasks.get(&id).is_some_and(|entry| entry.info.state == TaskState::Running) {
+                return Err(DetectionError::Busy(format!("任务ID已在运行: {}", id)));
+            }
+            tasks.insert(
+                id.clone(),
+                TaskEntry {
+                    seq,
+                    info: info.clone(),
+                    token: token.clone(),
+                },
+            );
+        }
+        let _ = app.emit(EVENT_TASK_PROGRESS, info);
+        Ok(TaskHandle {
+            manager: self,
+            app: app.clone(),
+            id,
+            token,
+            last_emit: Mutex::new(None),
+            finished: false,
+        })
+    }
+
+    /// 全部任务，最近开始的在前
+    pub fn list(&self) -> Vec<TaskInfo> {
+        let tasks = self.tasks.lock();
+        let mut entries: Vec<&TaskEntry> = tasks.values().collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.seq));
+        entries.into_iter().map(|entry| entry.info.clone()).collect()
+    }
+
+    pub fn get(&self, id: &str) -> Option<TaskInfo> {
+        self.tasks.lock().get(id).map(|entry| entry.info.clone())
+    }
+
+    /// 请求取消任务，返回取消请求后的任务信息
+    pub fn cancel(&self, app: &AppHandle, id: &str) -> Result<TaskInfo, DetectionError> {
+        let info = {
+            let mut tasks = self.tasks.lock();
+            let entry = tasks
+                .get_mut(id)
+                .ok_or_else(|| DetectionError::NotFound(format!("任务不存在: {}", id)))?;
+            if entry.info.state != TaskState::Running {
+                return Err(DetectionError::InvalidInput(format!("任务已结束: {}", id)));
+            }
+            entry.token.cancel();
+            entry.info.cancel_requested = true;
+            entry.info.updated_at = now_rfc3339();
+            tracing::info!("⏹️ 已请求取消任务: {} ({})", id, entry.info.kind);
+            entry.info.clone()
+        };
+        let _ = app.emit(EVENT_TASK_PROGRESS, info.clone());
+        Ok(info)
+    }
+
+    fn update(&self, id: &str, apply: impl FnOnce(&mut TaskInfo)) -> Option<TaskInfo> {
+        let mut tasks = self.tasks.lock();
+        let entry = tasks.get_mut(id)?;
+        apply(&mut entry.info);
+        entry.info.updated_at = now_rfc3339();
+        Some(entry.info.clone())
+    }
+
+    /// 只保留最近的已结束任务
+    fn prune(&self) {
+        let mut tasks = self.tasks.lock();
+        let mut finished: Vec<(u64, String)> = tasks
+            .values()
+            .filter(|entry| entry.info.state != TaskState::Running)
+            .map(|entry| (entry.seq, entry.info.id.clone()))
+            .collect();
+        if finished.len() <= MAX_FINISHED_TASKS {
+            return;
+        }
+        finished.sort_unstable();
+        let excess = finished.len() - MAX_FINISHED_TASKS;
+        for (_, id) in finished.into_iter().take(excess) {
+            tasks.remove(&id);
+        }
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 列出运行中与最近结束的任务
+#[tauri::command]
+pub async fn list_tasks(
+    manager: State<'_, TaskManager>
+) -> Result<Vec<TaskInfo>, DetectionError> {
+    Ok(manager.list())
+}
+
+/// 查询单个任务
+#[tauri::command]
+pub async fn get_task(
+    manager: State<'_, TaskManager>,
+    id: String
+) -> Result<TaskInfo, DetectionError> {
+    match manager.get(&id) {
+        Some(info) => Ok(info),
+        None => Err(DetectionError::NotFound(format!("任务不存在: {}", id))),
+    }
+}
+
+/// 取消正在运行的任务
+#[tauri::command]
+pub async fn cancel_task(
+    app: AppHandle,
+    manager: State<'_, TaskManager>,
+    id: String
+) -> Result<TaskInfo, DetectionError> {
+    manager.cancel(&app, &id)
+}
+
+/// 取消正在运行的长时间操作（与 `cancel_task` 相同，保留给已有调用方）
+#[tauri::command]
+pub async fn cancel_operation(
+    app: AppHandle,
+    manager: State<'_, TaskManager>,
+    task_id: String
+) -> Result<bool, DetectionError> {
+    manager.cancel(&app, &task_id).map(|_| true)
+}
@@ -0,0 +1,264 @@
+/*!
+检测结果时间滤波模块
+实时检测中单帧的误检/漏检会让"异常"结果和告警反复闪烁：
+某类别需在最近 M 帧中至少有 N 帧检出才开始上报，上报后连续 K 帧未检出才消失，
+消失前的帧沿用该类别最后一次的检测框
+*/
+
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::error::{self, DetectionError};
+use crate::yolo::{DetectionResult, YoloDetection};
+
+/// 统计窗口的最大帧数
+const MAX_WINDOW_FRAMES: usize = 100;
+
+/// 时间滤波参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TemporalFilterConfig {
+    pub enabled: bool,
+    pub min_hits: usize,      // N：窗口内至少检出的帧数
+    pub window_frames: usize, // M：统计窗口帧数
+    pub max_misses: u32,      // K：上报后连续未检出该帧数才消失
+}
+
+impl Default for TemporalFilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_hits: 3,
+            window_frames: 5,
+            max_misses: 3,
+        }
+    }
+}
+
+impl TemporalFilterConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.window_frames == 0 || self.window_frames > MAX_WINDOW_FRAMES {
+            return Err(anyhow!("统计窗口帧数必须在 1-{} 之间: {}", MAX_WINDOW_FRAMES, self.window_frames));
+        }
+        if self.min_hits == 0 || self.min_hits > self.window_frames {
+            return Err(anyhow!("检出帧数必须在 1-{} 之间: {}", self.window_frames, self.min_hits));
+        }
+        Ok(())
+    }
+}
+
+/// 单个类别的滤波状态
+#[derive(Default)]
+struct ClassState {
+    history: VecDeque<bool>, // 最近 M 帧是否检出
+    reported: bool,
+    misses: u32,
+    last: Vec<(YoloDetection, Option<u64>)>, // 最后一次检出的检测框及跟踪ID
+}
+
+/// 时间滤波状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemporalFilterStatus {
+    pub config: TemporalFilterConfig,
+    pub reported_classes: Vec<String>, // 当前处于上报状态的类别
+}
+
+#[derive(Default)]
+struct FilterInner {
+    config: TemporalFilterConfig,
+    classes: HashMap<String, ClassState>,
+}
+
+/// 检测结果时间滤波（Tauri托管状态），实时检测管线启动时重置
+#[derive(Default)]
+pub struct TemporalFilter {
+    inner: Mutex<FilterInner>,
+}
+
+impl TemporalFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按类别过滤一帧检测结果：未达到上报条件的类别被移除，短暂漏检的类别沿用上一次的检测框
+    pub fn apply(&self, result: &mut DetectionResult) {
+        let mut inner = self.inner.lock();
+        if !inner.config.enabled {
+            return;
+        }
+        let config = inner.config.clone();
+
+        let tracked = !result.track_ids.is_empty();
+        let mut current: HashMap<String, Vec<(YoloDetection, Option<u64>)>> = HashMap::new();
+        for (index, detection) in result.detections.drain(..).enumerate() {
+            let track_id = result.track_ids.get(index).copied().flatten();
+            current.entry(detection.class_name.clone()).or_default().push((detection, track_id));
+        }
+        for class_name in current.keys() {
+            inner.classes.entry(class_name.clone()).or_default();
+        }
+
+        let mut output = Vec::new();
+        inner.classes.retain(|class_name, state| {
+            let detections = current.remove(class_name);
+            state.history.push_back(detections.is_some());
+            while state.history.len() > config.window_frames {
+                state.history.pop_front();
+            }
+
+            match detections {
+                Some(detections) => {
+                    state.misses = 0;
+                    state.last = detections;
+                    if state.history.iter().filter(|hit| **hit).count() >= config.min_hits {
+                        state.reported = true;
+                    }
+                }
+                None => {
+                    state.misses += 1;
+                    if state.misses > config.max_misses {
+                        state.reported = false;
+                    }
+                }
+            }
+            if state.reported {
+                output.extend(state.last.iter().cloned());
+            }
+            // 窗口内已没有任何检出的类别不再保留状态
+            state.reported || state.history.iter().any(|hit| *hit)
+        });
+
+        output.sort_by(|a, b| b.0.confidence.total_cmp(&a.0.confidence));
+        let (detections, track_ids): (Vec<_>, Vec<_>) = output.into_iter().unzip();
+        result.detections = detections;
+        result.track_ids = if tracked { track_ids } else { Vec::new() };
+    }
+
+    pub fn reset(&self) {
+        self.inner.lock().classes.clear();
+    }
+
+    pub fn set_config(&self, config: TemporalFilterConfig) -> Result<()> {
+        config.validate().map_err(error::invalid_input)?;
+        let mut inner = self.inner.lock();
+        inner.classes.clear();
+        inner.config = config;
+        Ok(())
+    }
+
+    pub fn status(&self) -> TemporalFilterStatus {
+        let inner = self.inner.lock();
+        let mut reported_classes: Vec<String> = inner
+            .classes
+            .iter()
+            .filter(|(_, state)| state.reported)
+            .map(|(class_name, _)| class_name.clone())
+            .collect();
+        reported_classes.sort();
+        TemporalFilterStatus {
+            config: inner.config.clone(),
+            reported_classes,
+        }
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 设置时间滤波参数（N/M/K），运行中的管线立即生效并重新开始统计
+#[tauri::command]
+pub async fn set_temporal_filter_config(
+    filter: State<'_, TemporalFilter>,
+    config: TemporalFilterConfig
+) -> Result<TemporalFilterStatus, DetectionError> {
+    match filter.set_config(config) {
+        Ok(()) => Ok(filter.status()),
+        Err(e) => Err(DetectionError::from(e).context("设置时间滤波参数失败")),
+    }
+}
+
+#[tauri::command]
+pub async fn get_temporal_filter_status(
+    filter: State<'_, TemporalFilter>
+) -> Result<TemporalFilterStatus, DetectionError> {
+    Ok(filter.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(x: Option<f32>) -> DetectionResult {
+        DetectionResult {
+            detections: x
+                .map(|x| YoloDetection {
+                    class_id: 0,
+                    class_name: "异常".to_string(),
+                    confidence: 0.8,
+                    bbox: [x, 10.0, 20.0, 20.0],
+                    size_mm: None,
+                })
+                .into_iter()
+                .collect(),
+            image_width: 640,
+            image_height: 480,
+            processing_time_ms: 0,
+            model_input_size: (640, 640),
+            thresholds: HashMap::new(),
+            track_ids: Vec::new(),
+            timings: Default::default(),
+            frame: Default::default(),
+        }
+    }
+
+    fn configured(min_hits: usize, window_frames: usize, max_misses: u32) -> TemporalFilter {
+        let filter = TemporalFilter::new();
+        filter
+            .set_config(TemporalFilterConfig { enabled: true, min_hits, window_frames, max_misses })
+            .unwrap();
+        filter
+    }
+
+    /// 依次输入各帧（`true` 为检出），返回每帧过滤后是否上报
+    fn run(filter: &TemporalFilter, hits: &[bool]) -> Vec<bool> {
+        hits.iter()
+            .map(|&hit| {
+                let mut result = frame(hit.then_some(100.0));
+                filter.apply(&mut result);
+                !result.detections.is_empty()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reported_only_after_n_hits_within_m_frames() {
+        let filter = configured(3, 5, 0);
+        assert_eq!(run(&filter, &[true, false, true, false, true]), [false, false, false, false, true]);
+
+        // 检出分散在窗口之外时不足 N 帧，不上报
+        let filter = configured(3, 5, 0);
+        let reported = run(&filter, &[true, false, false, true, false, false, true]);
+        assert!(reported.iter().all(|r| !r), "{:?}", reported);
+        assert!(filter.status().reported_classes.is_empty());
+    }
+
+    #[test]
+    fn dropped_after_k_misses_and_not_reported_again_below_threshold() {
+        let filter = configured(3, 5, 2);
+        assert_eq!(run(&filter, &[true, true, true]), [false, false, true]);
+        assert_eq!(filter.status().reported_classes, ["异常"]);
+
+        // 短暂漏检沿用最后一次的检测框
+        let mut result = frame(None);
+        filter.apply(&mut result);
+        assert_eq!(result.detections.len(), 1);
+        assert_eq!(result.detections[0].bbox, [100.0, 10.0, 20.0, 20.0]);
+
+        // 连续漏检超过 K 帧后消失，之后窗口内检出不足 N 帧不再上报
+        assert_eq!(run(&filter, &[false, false, true]), [true, false, false]);
+        assert!(filter.status().reported_classes.is_empty());
+    }
+}
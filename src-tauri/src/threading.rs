@@ -0,0 +1,196 @@
+/*!
+推理线程配置模块
+设置推理线程数（candle 的矩阵运算使用 rayon 全局线程池）、同时处理的图片数以及可选的CPU核心绑定，
+使检测程序能在共用的工控机上与其他软件共存而不抢占全部CPU
+线程池只能在首次推理前创建一次，修改配置后需重启应用生效
+*/
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::error::{self, DetectionError};
+
+/// 线程配置文件名（位于应用数据目录）
+pub const CONFIG_FILE_NAME: &str = "threading.json";
+
+/// 推理线程配置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadConfig {
+    pub inference_threads: Option<usize>, // 为空时使用全部逻辑核心
+    #[serde(default)]
+    pub pinned_cores: Vec<usize>,         // 绑定的核心编号，为空时不绑定
+    #[serde(default)]
+    pub parallel_images: Option<usize>,   // 同时预处理与推理的图片数，为空时按逻辑核心数
+}
+
+/// 线程配置状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadStatus {
+    pub saved: ThreadConfig,   // 已保存的配置（下次启动生效）
+    pub applied: ThreadConfig, // 当前进程实际生效的配置
+    pub available_cores: Vec<usize>,
+    pub restart_required: bool,
+}
+
+/// 线程设置（Tauri托管状态）
+pub struct ThreadSettings {
+    path: PathBuf,
+    saved: RwLock<ThreadConfig>,
+    applied: ThreadConfig,
+}
+
+fn available_cores() -> Vec<usize> {
+    core_affinity::get_core_ids()
+        .map(|ids| ids.into_iter().map(|id| id.id).collect())
+        .unwrap_or_default()
+}
+
+fn logical_cpu_count() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+fn validate(config: &ThreadConfig) -> Result<()> {
+    if config.inference_threads == Some(0) {
+        return Err(anyhow!("推理线程数必须大于0"));
+    }
+    if config.parallel_images == Some(0) {
+        return Err(anyhow!("同时处理的图片数必须大于0"));
+    }
+    let cores = available_cores();
+    if let Some(core) = config.pinned_cores.iter().find(|c| !cores.contains(c)) {
+        return Err(anyhow!("CPU核心 {} 不存在（可用核心: {:?}）", core, cores));
+    }
+    Ok(())
+}
+
+/// 创建推理线程池，返回实际生效的配置
+fn apply(config: &ThreadConfig) -> Result<ThreadConfig> {
+    validate(config)?;
+    let threads = config
+        .inference_threads
+        .unwrap_or_else(logical_cpu_count)
+        .clamp(1, logical_cpu_count());
+    let parallel_images = config
+        .parallel_images
+        .unwrap_or_else(logical_cpu_count)
+        .clamp(1, logical_cpu_count());
+
+    // candle 内部按该环境变量决定并行度
+    std::env::set_var("RAYON_NUM_THREADS", threads.to_string());
+
+    let pinned: Vec<core_affinity::CoreId> = config
+        .pinned_cores
+        .iter()
+        .map(|&id| core_affinity::CoreId { id })
+        .collect();
+    let handler_cores = pinned.clone();
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|i| format!("yolo-infer-{}", i))
+        .start_handler(move |i| {
+            if !handler_cores.is_empty() {
+                let core = handler_cores[i % handler_cores.len()];
+                if !core_affinity::set_for_current(core) {
+                    tracing::error!("推理线程 {} 绑定核心 {} 失败", i, core.id);
+                }
+            }
+        })
+        .build_global()
+        .map_err(|e| anyhow!("创建推理线程池失败: {}", e))?;
+
+    tracing::info!(
+        "🧵 推理线程池: {} 线程，同时处理 {} 张图片{}",
+        threads,
+        parallel_images,
+        if pinned.is_empty() {
+            String::new()
+        } else {
+            format!("，绑定核心 {:?}", config.pinned_cores)
+        }
+    );
+
+    Ok(ThreadConfig {
+        inference_threads: Some(threads),
+        pinned_cores: config.pinned_cores.clone(),
+        parallel_images: Some(parallel_images),
+    })
+}
+
+impl ThreadSettings {
+    /// 读取已保存的配置并创建线程池（应在首次推理之前调用）
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(CONFIG_FILE_NAME);
+        let saved: ThreadConfig = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        let applied = match apply(&saved) {
+            Ok(applied) => applied,
+            Err(e) => {
+                tracing::error!("线程配置无效，使用默认设置: {}", e);
+                apply(&ThreadConfig::default()).unwrap_or_default()
+            }
+        };
+
+        Self {
+            path,
+            saved: RwLock::new(saved),
+            applied,
+        }
+    }
+
+    pub fn status(&self) -> ThreadStatus {
+        let saved = self.saved.read().clone();
+        let restart_required = saved.pinned_cores != self.applied.pinned_cores
+            || saved.inference_threads.is_some_and(|n| Some(n) != self.applied.inference_threads)
+            || saved.parallel_images.is_some_and(|n| Some(n) != self.applied.parallel_images);
+        ThreadStatus {
+            saved,
+            applied: self.applied.clone(),
+            available_cores: available_cores(),
+            restart_required,
+        }
+    }
+
+    /// 推理工作线程同时处理的图片数（启动时确定）
+    pub fn parallel_images(&self) -> usize {
+        self.applied.parallel_images.unwrap_or(1)
+    }
+
+    fn save(&self, config: ThreadConfig) -> Result<()> {
+        validate(&config).map_err(error::invalid_input)?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&config)?)?;
+        *self.saved.write() = config;
+        Ok(())
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 获取推理线程配置
+#[tauri::command]
+pub async fn get_thread_config(
+    settings: State<'_, ThreadSettings>
+) -> Result<ThreadStatus, DetectionError> {
+    Ok(settings.status())
+}
+
+/// 保存推理线程配置（重启后生效）
+#[tauri::command]
+pub async fn set_thread_config(
+    settings: State<'_, ThreadSettings>,
+    config: ThreadConfig
+) -> Result<ThreadStatus, DetectionError> {
+    match settings.save(config) {
+        Ok(()) => Ok(settings.status()),
+        Err(e) => Err(DetectionError::from(e).context("保存线程配置失败")),
+    }
+}
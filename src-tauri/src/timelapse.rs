@@ -0,0 +1,196 @@
+/*!
+延时摄影生成模块
+从长时间检测会话中按固定间隔抽取标注帧，合成为延时MP4视频，
+便于用几十秒概览整个班次
+*/
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::error::DetectionError;
+use crate::history::{self, DetectionRun};
+use crate::replay::{parse_timestamp_ms, render_run};
+use crate::storage::Database;
+use crate::tasks::{TaskHandle, TaskManager};
+use crate::ffmpeg;
+
+/// 延时视频生成请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelapseRequest {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub source: Option<String>,
+    pub interval_secs: f64,     // 抽帧间隔（会话时间）
+    pub fps: Option<u32>,       // 输出帧率，默认30
+    pub max_width: Option<u32>, // 输出最大宽度，默认1280
+    pub output_path: String,
+}
+
+/// 延时视频生成结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelapseSummary {
+    pub output_path: String,
+    pub frame_count: u32,
+    pub skipped_frames: u32, // 源图片缺失无法绘制的帧
+    pub duration_secs: f64,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 临时帧目录，离开作用域时删除（出错返回时同样清理）
+struct FramesDir(PathBuf);
+
+impl FramesDir {
+    fn create() -> Result<Self> {
+        let dir = std::env::temp_dir().join(format!(
+            "yolo_timelapse_{}",
+            chrono::Utc::now().format("%Y%m%d%H%M%S%3f")
+        ));
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self(dir))
+    }
+}
+
+impl Drop for FramesDir {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.0) {
+            tracing::warn!("删除延时视频临时目录失败 {}: {}", self.0.display(), e);
+        }
+    }
+}
+
+/// 按时间间隔选择帧
+fn select_runs(runs: Vec<DetectionRun>, interval_ms: i64) -> Vec<DetectionRun> {
+    let mut selected = Vec::new();
+    let mut last_ms: Option<i64> = None;
+    for run in runs {
+        let ts = match parse_timestamp_ms(&run.created_at) {
+            Some(ts) => ts,
+            None => continue,
+        };
+        if last_ms.is_none_or(|last| ts - last >= interval_ms) {
+            last_ms = Some(ts);
+            selected.push(run);
+        }
+    }
+    selected
+}
+
+/// 生成延时视频
+pub async fn generate(
+    db: &Database,
+    request: &TimelapseRequest,
+    task: &TaskHandle,
+) -> Result<TimelapseSummary> {
+    if !(request.interval_secs.is_finite() && request.interval_secs > 0.0) {
+        return Err(DetectionError::InvalidInput("抽帧间隔必须大于0".to_string()).into());
+    }
+    let fps = request.fps.unwrap_or(30).clamp(1, 120);
+    let max_width = request.max_width.unwrap_or(1280).max(16);
+
+    let runs = history::runs_in_range(db, request.from.as_deref(), request.to.as_deref(), request.source.as_deref())?;
+    let selected = select_runs(runs, (request.interval_secs * 1000.0) as i64);
+    if selected.is_empty() {
+        return Err(anyhow!("所选范围内没有检测记录"));
+    }
+
+    let frames_dir = FramesDir::create()?;
+
+    // 所有帧统一缩放到第一帧的尺寸（宽高取偶数，满足H.264要求）
+    let mut frame_size: Option<(u32, u32)> = None;
+    let mut frame_count = 0u32;
+    let mut skipped_frames = 0u32;
+
+    for (index, run) in selected.iter().enumerate() {
+        task.advance(index as u64, Some(selected.len() as u64))?;
+        let annotated = match render_run(run) {
+            Some(img) => img,
+            None => {
+                skipped_frames += 1;
+                continue;
+            }
+        };
+
+        let (width, height) = *frame_size.get_or_insert_with(|| {
+            let scale = (max_width as f32 / annotated.width() as f32).min(1.0);
+            let w = ((annotated.width() as f32 * scale) as u32).max(2) & !1;
+            let h = ((annotated.height() as f32 * scale) as u32).max(2) & !1;
+            (w, h)
+        });
+        let frame = annotated.resize_exact(width, height, FilterType::Triangle).to_rgb8();
+        frame.save(frames_dir.0.join(format!("frame_{:06}.jpg", frame_count)))?;
+        frame_count += 1;
+    }
+
+    let result = match frame_size {
+        Some((width, height)) => {
+            let output_path = Path::new(&request.output_path);
+            if let Some(parent) = output_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let pattern = frames_dir.0.join("frame_%06d.jpg");
+            ffmpeg::run(
+                [
+                    "-y".to_string(),
+                    "-framerate".to_string(),
+                    fps.to_string(),
+                    "-i".to_string(),
+                    pattern.to_string_lossy().to_string(),
+                    "-c:v".to_string(),
+                    "libx264".to_string(),
+                    "-pix_fmt".to_string(),
+                    "yuv420p".to_string(),
+                    output_path.to_string_lossy().to_string(),
+                ],
+                task.token(),
+            )
+            .await
+            .map(|()| TimelapseSummary {
+                output_path: request.output_path.clone(),
+                frame_count,
+                skipped_frames,
+                duration_secs: frame_count as f64 / fps as f64,
+                width,
+                height,
+            })
+        }
+        None => Err(anyhow!("所有帧的源图片都已不存在，无法生成延时视频")),
+    };
+
+    drop(frames_dir);
+    if task.is_cancelled() {
+        let _ = std::fs::remove_file(&request.output_path);
+    }
+
+    if let Ok(summary) = &result {
+        tracing::info!(
+            "🎞️ 延时视频已生成: {} ({} 帧, {:.1}s)",
+            summary.output_path, summary.frame_count, summary.duration_secs
+        );
+    }
+    result
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 从检测会话生成延时视频
+#[tauri::command]
+pub async fn generate_timelapse(
+    app: AppHandle,
+    db: State<'_, Database>,
+    tasks: State<'_, TaskManager>,
+    request: TimelapseRequest,
+    task_id: Option<String>
+) -> Result<TimelapseSummary, DetectionError> {
+    let task = tasks.begin(&app, "timelapse", task_id)?;
+    let result = generate(&db, &request, &task).await;
+    task.finish(&result);
+    match result {
+        Ok(summary) => Ok(summary),
+        Err(e) => Err(DetectionError::from(e).context("生成延时视频失败")),
+    }
+}
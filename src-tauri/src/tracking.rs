@@ -0,0 +1,487 @@
+/*!
+目标跟踪模块
+在实时检测管线中为跨帧的同一目标分配稳定的跟踪ID（SORT/ByteTrack思路）：
+- 每条轨迹用匀速卡尔曼滤波预测下一帧的框位置（中心点与宽高各自独立滤波）
+- 先用高置信度检测框按IoU匹配全部轨迹，再用低置信度检测框匹配剩余的已确认轨迹，
+  被短暂遮挡、置信度下降的目标不会丢失ID
+- 连续命中 `min_hits` 次的轨迹才分配ID，超过 `max_age` 帧未匹配的轨迹结束
+
+轨迹的生命周期统计（出现/消失时间、命中次数、置信度）可通过 `get_track_stats` 查询
+*/
+
+use std::collections::VecDeque;
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::error::{self, DetectionError};
+use crate::yolo::nms::calculate_iou;
+use crate::yolo::{DetectionResult, YoloDetection};
+
+/// 保留的已结束轨迹统计数
+const MAX_FINISHED_TRACKS: usize = 200;
+
+/// 位置/速度噪声相对框尺寸的比例（与 ByteTrack 相同）
+const POSITION_NOISE_WEIGHT: f32 = 1.0 / 20.0;
+const VELOCITY_NOISE_WEIGHT: f32 = 1.0 / 160.0;
+
+/// 跟踪参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackerConfig {
+    pub enabled: bool,
+    pub iou_threshold: f32,   // 预测框与检测框IoU不低于该值才视为同一目标
+    pub high_confidence: f32, // 置信度不低于该值的检测框参与第一轮匹配并可创建新轨迹
+    pub min_hits: u32,        // 连续命中该次数后确认轨迹并分配ID
+    pub max_age: u32,         // 已确认轨迹连续该帧数未匹配则结束
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            iou_threshold: 0.3,
+            high_confidence: 0.5,
+            min_hits: 3,
+            max_age: 30,
+        }
+    }
+}
+
+impl TrackerConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.iou_threshold) {
+            return Err(anyhow!("跟踪IoU阈值必须在 0-1 之间: {}", self.iou_threshold));
+        }
+        if !(0.0..=1.0).contains(&self.high_confidence) {
+            return Err(anyhow!("高置信度阈值必须在 0-1 之间: {}", self.high_confidence));
+        }
+        if self.min_hits == 0 {
+            return Err(anyhow!("确认轨迹所需命中次数必须大于0"));
+        }
+        Ok(())
+    }
+}
+
+/// 单个量的匀速卡尔曼滤波，状态为 [值, 每帧变化量]
+#[derive(Debug, Clone)]
+struct Kalman1D {
+    value: f32,
+    velocity: f32,
+    covariance: [[f32; 2]; 2],
+}
+
+impl Kalman1D {
+    fn new(value: f32, scale: f32) -> Self {
+        let position_std = 2.0 * POSITION_NOISE_WEIGHT * scale;
+        let velocity_std = 10.0 * VELOCITY_NOISE_WEIGHT * scale;
+        Self {
+            value,
+            velocity: 0.0,
+            covariance: [[position_std * position_std, 0.0], [0.0, velocity_std * velocity_std]],
+        }
+    }
+
+    fn predict(&mut self, scale: f32) {
+        self.value += self.velocity;
+        let [[p00, p01], [p10, p11]] = self.covariance;
+        let position_q = (POSITION_NOISE_WEIGHT * scale).powi(2);
+        let velocity_q = (VELOCITY_NOISE_WEIGHT * scale).powi(2);
+        // P = F·P·Fᵀ + Q，F = [[1, 1], [0, 1]]
+        self.covariance = [
+            [p00 + p01 + p10 + p11 + position_q, p01 + p11],
+            [p10 + p11, p11 + velocity_q],
+        ];
+    }
+
+    fn update(&mut self, measurement: f32, scale: f32) {
+        let [[p00, p01], [p10, p11]] = self.covariance;
+        let innovation_var = p00 + (POSITION_NOISE_WEIGHT * scale).powi(2);
+        let gain = [p00 / innovation_var, p10 / innovation_var];
+        let residual = measurement - self.value;
+        self.value += gain[0] * residual;
+        self.velocity += gain[1] * residual;
+        self.covariance = [
+            [(1.0 - gain[0]) * p00, (1.0 - gain[0]) * p01],
+            [p10 - gain[1] * p00, p11 - gain[1] * p01],
+        ];
+    }
+}
+
+/// 单条轨迹的生命周期统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackStats {
+    pub track_id: u64,
+    pub class_name: String,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub hits: u32,   // 匹配到检测框的帧数
+    pub frames: u32, // 从出现到最后一次更新经过的检测帧数
+    pub avg_confidence: f32,
+    pub max_confidence: f32,
+    pub active: bool,
+}
+
+/// 轨迹（中心点 x/y 与宽高各一个滤波器）
+struct Track {
+    id: Option<u64>, // 确认后才分配
+    class_id: u32,
+    class_name: String,
+    filters: [Kalman1D; 4],
+    hits: u32,
+    misses: u32, // 连续未匹配的帧数
+    frames: u32,
+    first_seen: String,
+    last_seen: String,
+    confidence_sum: f32,
+    max_confidence: f32,
+}
+
+impl Track {
+    fn new(detection: &YoloDetection, now: &str) -> Self {
+        let [x, y, w, h] = detection.bbox;
+        let scale = w.max(h);
+        Self {
+            id: None,
+            class_id: detection.class_id,
+            class_name: detection.class_name.clone(),
+            filters: [
+                Kalman1D::new(x + w / 2.0, scale),
+                Kalman1D::new(y + h / 2.0, scale),
+                Kalman1D::new(w, scale),
+                Kalman1D::new(h, scale),
+            ],
+            hits: 1,
+            misses: 0,
+            frames: 1,
+            first_seen: now.to_string(),
+            last_seen: now.to_string(),
+            confidence_sum: detection.confidence,
+            max_confidence: detection.confidence,
+        }
+    }
+
+    fn scale(&self) -> f32 {
+        self.filters[2].value.max(self.filters[3].value).max(1.0)
+    }
+
+    /// 当前估计的边界框 [x, y, width, height]
+    fn bbox(&self) -> [f32; 4] {
+        let w = self.filters[2].value.max(1.0);
+        let h = self.filters[3].value.max(1.0);
+        [self.filters[0].value - w / 2.0, self.filters[1].value - h / 2.0, w, h]
+    }
+
+    fn predict(&mut self) {
+        let scale = self.scale();
+        for filter in &mut self.filters {
+            filter.predict(scale);
+        }
+        self.misses += 1;
+    }
+
+    fn update(&mut self, detection: &YoloDetection, now: &str) {
+        let [x, y, w, h] = detection.bbox;
+        let scale = w.max(h).max(1.0);
+        for (filter, measurement) in self.filters.iter_mut().zip([x + w / 2.0, y + h / 2.0, w, h]) {
+            filter.update(measurement, scale);
+        }
+        self.class_name = detection.class_name.clone();
+        self.hits += 1;
+        self.frames += self.misses;
+        self.misses = 0;
+        self.last_seen = now.to_string();
+        self.confidence_sum += detection.confidence;
+        self.max_confidence = self.max_confidence.max(detection.confidence);
+    }
+
+    fn stats(&self, active: bool) -> Option<TrackStats> {
+        Some(TrackStats {
+            track_id: self.id?,
+            class_name: self.class_name.clone(),
+            first_seen: self.first_seen.clone(),
+            last_seen: self.last_seen.clone(),
+            hits: self.hits,
+            frames: self.frames,
+            avg_confidence: self.confidence_sum / self.hits.max(1) as f32,
+            max_confidence: self.max_confidence,
+            active,
+        })
+    }
+}
+
+/// IoU跟踪器
+#[derive(Default)]
+struct Tracker {
+    config: TrackerConfig,
+    tracks: Vec<Track>,
+    finished: VecDeque<TrackStats>,
+    next_id: u64,
+}
+
+impl Tracker {
+    /// 按IoU从高到低贪心匹配轨迹与检测框（类别需相同），返回 (轨迹下标, 检测下标)
+    fn associate(
+        &self,
+        predicted: &[[f32; 4]],
+        track_indices: &[usize],
+        detections: &[YoloDetection],
+        detection_indices: &[usize],
+    ) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<(usize, usize, f32)> = Vec::new();
+        for &t in track_indices {
+            for &d in detection_indices {
+                if self.tracks[t].class_id != detections[d].class_id {
+                    continue;
+                }
+                let iou = calculate_iou(&predicted[t], &detections[d].bbox);
+                if iou >= self.config.iou_threshold {
+                    pairs.push((t, d, iou));
+                }
+            }
+        }
+        pairs.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+        let mut used_tracks = Vec::new();
+        let mut used_detections = Vec::new();
+        let mut matches = Vec::new();
+        for (t, d, _) in pairs {
+            if used_tracks.contains(&t) || used_detections.contains(&d) {
+                continue;
+            }
+            used_tracks.push(t);
+            used_detections.push(d);
+            matches.push((t, d));
+        }
+        matches
+    }
+
+    /// 用一帧检测结果更新轨迹，返回与检测框一一对应的跟踪ID（未确认的轨迹为 None）
+    fn update(&mut self, detections: &[YoloDetection]) -> Vec<Option<u64>> {
+        let now = crate::storage::now_rfc3339();
+        for track in &mut self.tracks {
+            track.predict();
+        }
+        let predicted: Vec<[f32; 4]> = self.tracks.iter().map(Track::bbox).collect();
+
+        let (high, low): (Vec<usize>, Vec<usize>) =
+            (0..detections.len()).partition(|&d| detections[d].confidence >= self.config.high_confidence);
+
+        // 第一轮：高置信度检测框匹配全部轨迹
+        let all_tracks: Vec<usize> = (0..self.tracks.len()).collect();
+        let mut matches = self.associate(&predicted, &all_tracks, detections, &high);
+
+        // 第二轮：低置信度检测框只匹配剩余的已确认轨迹
+        let remaining: Vec<usize> = all_tracks
+            .into_iter()
+            .filter(|t| self.tracks[*t].id.is_some() && !matches.iter().any(|(mt, _)| mt == t))
+            .collect();
+        matches.extend(self.associate(&predicted, &remaining, detections, &low));
+
+        let mut track_ids = vec![None; detections.len()];
+        for &(t, d) in &matches {
+            let track = &mut self.tracks[t];
+            track.update(&detections[d], &now);
+            if track.id.is_none() && track.hits >= self.config.min_hits {
+                self.next_id += 1;
+                track.id = Some(self.next_id);
+            }
+            track_ids[d] = track.id;
+        }
+
+        // 未匹配的高置信度检测框创建新轨迹
+        for &d in &high {
+            if matches.iter().any(|(_, md)| *md == d) {
+                continue;
+            }
+            let mut track = Track::new(&detections[d], &now);
+            if self.config.min_hits <= 1 {
+                self.next_id += 1;
+                track.id = Some(self.next_id);
+                track_ids[d] = track.id;
+            }
+            self.tracks.push(track);
+        }
+
+        // 未确认的轨迹一旦丢失即删除，已确认的轨迹超过 max_age 帧未匹配则结束
+        let max_age = self.config.max_age;
+        let mut ended = Vec::new();
+        self.tracks.retain(|track| {
+            let alive = match track.id {
+                Some(_) => track.misses <= max_age,
+                None => track.misses == 0,
+            };
+            if !alive {
+                ended.extend(track.stats(false));
+            }
+            alive
+        });
+        for stats in ended {
+            if self.finished.len() >= MAX_FINISHED_TRACKS {
+                self.finished.pop_front();
+            }
+            self.finished.push_back(stats);
+        }
+
+        track_ids
+    }
+
+    fn reset(&mut self) {
+        self.tracks.clear();
+        self.finished.clear();
+        self.next_id = 0;
+    }
+}
+
+/// 跟踪状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackingStatus {
+    pub config: TrackerConfig,
+    pub active_tracks: usize,
+    pub total_tracks: u64, // 本次管线启动以来分配的ID总数
+    pub tracks: Vec<TrackStats>, // 进行中的轨迹在前，已结束的轨迹按结束时间倒序
+}
+
+/// 目标跟踪管理（Tauri托管状态），实时检测管线启动时重置
+#[derive(Default)]
+pub struct TrackingManager {
+    tracker: Mutex<Tracker>,
+}
+
+impl TrackingManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为检测结果分配跟踪ID（写入 `track_ids`），未启用跟踪时不做处理
+    pub fn update(&self, result: &mut DetectionResult) {
+        let mut tracker = self.tracker.lock();
+        if tracker.config.enabled {
+            result.track_ids = tracker.update(&result.detections);
+        }
+    }
+
+    pub fn reset(&self) {
+        self.tracker.lock().reset();
+    }
+
+    pub fn set_config(&self, config: TrackerConfig) -> Result<()> {
+        config.validate().map_err(error::invalid_input)?;
+        let mut tracker = self.tracker.lock();
+        if !config.enabled {
+            tracker.reset();
+        }
+        tracker.config = config;
+        Ok(())
+    }
+
+    pub fn status(&self) -> TrackingStatus {
+        let tracker = self.tracker.lock();
+        let mut tracks: Vec<TrackStats> = tracker.tracks.iter().filter_map(|track| track.stats(true)).collect();
+        let active_tracks = tracks.len();
+        tracks.extend(tracker.finished.iter().rev().cloned());
+        TrackingStatus {
+            config: tracker.config.clone(),
+            active_tracks,
+            total_tracks: tracker.next_id,
+            tracks,
+        }
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 设置跟踪参数，运行中的管线立即生效（关闭跟踪时清空已有轨迹）
+#[tauri::command]
+pub async fn set_tracking_config(
+    tracking: State<'_, TrackingManager>,
+    config: TrackerConfig
+) -> Result<TrackingStatus, DetectionError> {
+    match tracking.set_config(config) {
+        Ok(()) => Ok(tracking.status()),
+        Err(e) => Err(DetectionError::from(e).context("设置跟踪参数失败")),
+    }
+}
+
+/// 查询跟踪参数与各轨迹的生命周期统计
+#[tauri::command]
+pub async fn get_track_stats(
+    tracking: State<'_, TrackingManager>
+) -> Result<TrackingStatus, DetectionError> {
+    Ok(tracking.status())
+}
+
+/// 清空全部轨迹，跟踪ID从1重新开始
+#[tauri::command]
+pub async fn reset_tracking(
+    tracking: State<'_, TrackingManager>
+) -> Result<TrackingStatus, DetectionError> {
+    tracking.reset();
+    Ok(tracking.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detection(x: f32, y: f32) -> YoloDetection {
+        YoloDetection {
+            class_id: 0,
+            class_name: "person".to_string(),
+            confidence: 0.9,
+            bbox: [x, y, 40.0, 40.0],
+            size_mm: None,
+        }
+    }
+
+    fn tracker(min_hits: u32, max_age: u32) -> Tracker {
+        Tracker {
+            config: TrackerConfig { min_hits, max_age, ..TrackerConfig::default() },
+            ..Tracker::default()
+        }
+    }
+
+    #[test]
+    fn moving_box_keeps_its_id() {
+        let mut tracker = tracker(3, 30);
+        let ids: Vec<Option<u64>> = (0..20)
+            .map(|frame| tracker.update(&[detection(10.0 + 8.0 * frame as f32, 20.0)])[0])
+            .collect();
+        // 命中 min_hits 次后确认，之后一直沿用同一ID
+        assert_eq!(&ids[..2], &[None, None]);
+        assert!(ids[2..].iter().all(|id| *id == Some(1)), "{:?}", ids);
+    }
+
+    #[test]
+    fn lost_track_expires_after_max_age() {
+        let mut tracker = tracker(1, 5);
+        assert_eq!(tracker.update(&[detection(0.0, 0.0)]), vec![Some(1)]);
+
+        for _ in 0..5 {
+            tracker.update(&[]);
+        }
+        assert_eq!(tracker.tracks.len(), 1);
+        assert!(tracker.finished.is_empty());
+
+        tracker.update(&[]);
+        assert!(tracker.tracks.is_empty());
+        assert_eq!(tracker.finished.len(), 1);
+        assert_eq!(tracker.finished[0].track_id, 1);
+
+        // 同一位置再次出现的目标分配新ID
+        assert_eq!(tracker.update(&[detection(0.0, 0.0)]), vec![Some(2)]);
+    }
+
+    #[test]
+    fn crossing_objects_do_not_swap_ids() {
+        let mut tracker = tracker(1, 30);
+        // 两个同类目标相向运动，第 10 帧时水平位置重合
+        for frame in 0..=20 {
+            let offset = 8.0 * frame as f32;
+            let ids = tracker.update(&[detection(offset, 0.0), detection(160.0 - offset, 16.0)]);
+            assert_eq!(ids, vec![Some(1), Some(2)], "第 {} 帧", frame);
+        }
+    }
+}
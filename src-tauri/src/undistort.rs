@@ -0,0 +1,197 @@
+/*!
+镜头畸变校正模块
+广角工业相机的画面边缘存在明显的桶形/枕形畸变，直接检测会使尺寸测量与检测区域在画面边缘失准。
+加载相机标定得到的内参矩阵与畸变系数（OpenCV 针孔模型：k1, k2, p1, p2[, k3[, k4, k5, k6]]）后，
+摄像头、网络流与视频文件的每一帧在送入推理之前先做畸变校正，之后的检测、绘制、ROI与测量均基于校正后的画面。
+
+校正按像素查表重映射（双线性插值），映射表按帧尺寸计算一次后复用；
+帧尺寸与标定尺寸不同时按比例缩放内参。标定文件为JSON：
+
+```json
+{ "camera_matrix": [[fx, 0, cx], [0, fy, cy], [0, 0, 1]], "dist_coeffs": [k1, k2, p1, p2, k3], "image_size": [width, height] }
+```
+*/
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+use parking_lot::Mutex;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::detection_config::ConfigStore;
+use crate::error::{self, DetectionError};
+
+/// 镜头标定参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LensCalibration {
+    pub camera_matrix: [[f64; 3]; 3], // 内参矩阵 [[fx, 0, cx], [0, fy, cy], [0, 0, 1]]
+    pub dist_coeffs: Vec<f64>,        // 畸变系数 k1, k2, p1, p2[, k3[, k4, k5, k6]]
+    pub image_size: (u32, u32),       // 标定时的图像尺寸 (width, height)
+}
+
+impl LensCalibration {
+    pub fn validate(&self) -> Result<()> {
+        let [[fx, _, _], [_, fy, _], _] = self.camera_matrix;
+        if !(fx > 0.0 && fy > 0.0) {
+            return Err(anyhow!("内参矩阵的焦距必须为正数: fx={}, fy={}", fx, fy));
+        }
+        if !matches!(self.dist_coeffs.len(), 4 | 5 | 8) {
+            return Err(anyhow!("畸变系数须为4、5或8个，实际 {} 个", self.dist_coeffs.len()));
+        }
+        if self.dist_coeffs.iter().chain(self.camera_matrix.iter().flatten()).any(|v| !v.is_finite()) {
+            return Err(anyhow!("标定参数包含无效数值"));
+        }
+        if self.image_size.0 == 0 || self.image_size.1 == 0 {
+            return Err(anyhow!("标定图像尺寸无效: {:?}", self.image_size));
+        }
+        Ok(())
+    }
+
+    /// 读取标定文件
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| anyhow!("读取标定文件失败 {}: {}", path.display(), e))?;
+        let calibration: Self = serde_json::from_str(&content).map_err(|e| anyhow!("标定文件格式错误: {}", e))?;
+        calibration.validate().map_err(error::invalid_input)?;
+        Ok(calibration)
+    }
+
+    /// 去畸变后的归一化坐标 (x, y) 在畸变图像上对应的归一化坐标
+    fn distort(&self, x: f64, y: f64) -> (f64, f64) {
+        let coeff = |index: usize| self.dist_coeffs.get(index).copied().unwrap_or(0.0);
+        let (k1, k2, p1, p2, k3) = (coeff(0), coeff(1), coeff(2), coeff(3), coeff(4));
+        let (k4, k5, k6) = (coeff(5), coeff(6), coeff(7));
+        let r2 = x * x + y * y;
+        let r4 = r2 * r2;
+        let r6 = r4 * r2;
+        let radial = (1.0 + k1 * r2 + k2 * r4 + k3 * r6) / (1.0 + k4 * r2 + k5 * r4 + k6 * r6);
+        (
+            x * radial + 2.0 * p1 * x * y + p2 * (r2 + 2.0 * x * x),
+            y * radial + p1 * (r2 + 2.0 * y * y) + 2.0 * p2 * x * y,
+        )
+    }
+
+    /// 按帧尺寸缩放后的内参 (fx, fy, cx, cy)
+    fn scaled_intrinsics(&self, size: (u32, u32)) -> (f64, f64, f64, f64) {
+        let sx = size.0 as f64 / self.image_size.0 as f64;
+        let sy = size.1 as f64 / self.image_size.1 as f64;
+        let [[fx, _, cx], [_, fy, cy], _] = self.camera_matrix;
+        (fx * sx, fy * sy, cx * sx, cy * sy)
+    }
+}
+
+/// 畸变校正器：持有标定参数与按帧尺寸缓存的映射表
+pub struct Undistorter {
+    calibration: LensCalibration,
+    map: Mutex<Option<((u32, u32), Arc<Vec<[f32; 2]>>)>>, // 输出像素在原帧上的采样坐标
+}
+
+impl Undistorter {
+    pub fn new(calibration: LensCalibration) -> Self {
+        Self {
+            calibration,
+            map: Mutex::new(None),
+        }
+    }
+
+    pub fn calibration(&self) -> &LensCalibration {
+        &self.calibration
+    }
+
+    /// 该帧尺寸的映射表（尺寸变化时重新计算）
+    fn map_for(&self, size: (u32, u32)) -> Arc<Vec<[f32; 2]>> {
+        let mut cached = self.map.lock();
+        if let Some((cached_size, map)) = cached.as_ref() {
+            if *cached_size == size {
+                return map.clone();
+            }
+        }
+        let (fx, fy, cx, cy) = self.calibration.scaled_intrinsics(size);
+        let map: Vec<[f32; 2]> = (0..size.0 as usize * size.1 as usize)
+            .into_par_iter()
+            .map(|index| {
+                let u = (index % size.0 as usize) as f64;
+                let v = (index / size.0 as usize) as f64;
+                let (xd, yd) = self.calibration.distort((u - cx) / fx, (v - cy) / fy);
+                [(xd * fx + cx) as f32, (yd * fy + cy) as f32]
+            })
+            .collect();
+        let map = Arc::new(map);
+        *cached = Some((size, map.clone()));
+        tracing::debug!("畸变校正映射表已更新: {}x{}", size.0, size.1);
+        map
+    }
+
+    /// 校正一帧图像（尺寸不变，映射到原帧之外的像素为黑色）
+    pub fn apply(&self, frame: &RgbImage) -> RgbImage {
+        let (width, height) = frame.dimensions();
+        let map = self.map_for((width, height));
+        let mut out = RgbImage::new(width, height);
+        out.par_chunks_exact_mut(3)
+            .zip(map.par_iter())
+            .for_each(|(dst, &[sx, sy])| {
+                if let Some(pixel) = sample_bilinear(frame, sx, sy) {
+                    dst.copy_from_slice(&pixel);
+                }
+            });
+        out
+    }
+}
+
+/// 双线性插值采样，坐标在图像之外时返回空
+fn sample_bilinear(frame: &RgbImage, x: f32, y: f32) -> Option<[u8; 3]> {
+    let (width, height) = frame.dimensions();
+    if !(x >= 0.0 && y >= 0.0 && x <= (width - 1) as f32 && y <= (height - 1) as f32) {
+        return None;
+    }
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+    let [a, b, c, d] = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)].map(|(px, py)| frame.get_pixel(px, py).0);
+    let mut pixel = [0u8; 3];
+    for channel in 0..3 {
+        let top = a[channel] as f32 * (1.0 - tx) + b[channel] as f32 * tx;
+        let bottom = c[channel] as f32 * (1.0 - tx) + d[channel] as f32 * tx;
+        pixel[channel] = (top * (1.0 - ty) + bottom * ty).round() as u8;
+    }
+    Some(pixel)
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 设置镜头标定参数，之后采集的视频帧先做畸变校正再检测；传空值关闭畸变校正
+#[tauri::command]
+pub async fn set_lens_calibration(
+    store: State<'_, ConfigStore>,
+    calibration: Option<LensCalibration>
+) -> Result<Option<LensCalibration>, DetectionError> {
+    match store.update(|saved| saved.lens_calibration = calibration) {
+        Ok(saved) => {
+            tracing::info!("🔧 镜头畸变校正: {}", if saved.lens_calibration.is_some() { "已启用" } else { "已关闭" });
+            Ok(saved.lens_calibration)
+        }
+        Err(e) => Err(DetectionError::from(e).context("保存镜头标定参数失败")),
+    }
+}
+
+/// 从标定文件（JSON）加载镜头标定参数并启用畸变校正
+#[tauri::command]
+pub async fn load_lens_calibration(
+    store: State<'_, ConfigStore>,
+    path: String
+) -> Result<LensCalibration, DetectionError> {
+    let calibration = match LensCalibration::load(Path::new(&path)) {
+        Ok(calibration) => calibration,
+        Err(e) => return Err(DetectionError::from(e).context("加载镜头标定参数失败")),
+    };
+    match store.update(|saved| saved.lens_calibration = Some(calibration.clone())) {
+        Ok(_) => {
+            tracing::info!("🔧 已加载镜头标定参数: {}", path);
+            Ok(calibration)
+        }
+        Err(e) => Err(DetectionError::from(e).context("保存镜头标定参数失败")),
+    }
+}
@@ -0,0 +1,256 @@
+/*!
+只读监控窗口模块
+为大屏等副显示器提供独立的结果订阅：仅推送标注帧与统计信息，不提供任何控制命令。
+监控窗口发起的命令调用在分发前按窗口标签检查，只放行只读命令，其余一律拒绝。
+最近一帧同时供内嵌HTTP服务的MJPEG流使用（浏览器、远程大屏）
+*/
+
+use base64::Engine;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Invoke;
+use tauri::{AppHandle, Emitter, EventTarget, Manager, Runtime, State, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+use tokio::sync::watch;
+
+use crate::error::DetectionError;
+use crate::yolo_api::Detection;
+
+/// 监控窗口帧事件
+pub const EVENT_VIEWER_FRAME: &str = "viewer://frame";
+
+/// 监控窗口标签前缀（tauri.conf.json 中的只读能力配置按此前缀匹配）
+pub const VIEWER_LABEL_PREFIX: &str = "viewer-";
+
+/// 监控窗口可以调用的应用命令（只读），事件订阅走 core:event 权限，不经过这里
+const VIEWER_COMMANDS: &[&str] = &["get_viewer_snapshot"];
+
+/// 监控统计信息
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ViewerStats {
+    pub frame_seq: u64,
+    pub total_inferences: u64,
+    pub avg_fps: f64,
+    pub abnormal_count: u64,
+}
+
+/// 推送给监控窗口的帧
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewerFrame {
+    pub source: String,
+    pub image_data: Option<String>, // Base64编码的标注图像
+    pub detections: Vec<Detection>,
+    pub timestamp: String,
+    pub stats: ViewerStats,
+    #[serde(default)]
+    pub replay: bool, // 是否为历史回放帧
+}
+
+#[derive(Default)]
+struct ViewerHubInner {
+    windows: Vec<String>,
+    next_id: u32,
+    last_frame: Option<ViewerFrame>,
+    stats: ViewerStats,
+}
+
+/// 监控窗口订阅中心（Tauri托管状态）
+pub struct ViewerHub {
+    inner: Mutex<ViewerHubInner>,
+    frame_seq: watch::Sender<u64>, // 每发布一帧递增，供MJPEG流等待新帧
+}
+
+impl Default for ViewerHub {
+    fn default() -> Self {
+        Self {
+            inner: Mutex::new(ViewerHubInner::default()),
+            frame_seq: watch::channel(0).0,
+        }
+    }
+}
+
+impl ViewerHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 订阅新帧通知（值为帧序号）
+    pub fn subscribe_frames(&self) -> watch::Receiver<u64> {
+        self.frame_seq.subscribe()
+    }
+
+    /// 最近一帧标注图像（JPEG字节）
+    pub fn latest_jpeg(&self) -> Option<Vec<u8>> {
+        let inner = self.inner.lock();
+        let data = inner.last_frame.as_ref()?.image_data.as_ref()?;
+        base64::engine::general_purpose::STANDARD.decode(data).ok()
+    }
+
+    fn has_viewers(&self) -> bool {
+        !self.inner.lock().windows.is_empty()
+    }
+
+    /// 缓存的最近一帧标注图像占用的字节数
+    pub fn preview_bytes(&self) -> usize {
+        self.inner
+            .lock()
+            .last_frame
+            .as_ref()
+            .and_then(|frame| frame.image_data.as_ref())
+            .map_or(0, |data| data.len())
+    }
+
+    /// 丢弃缓存的最近一帧标注图像（内存紧张时调用），返回释放的字节数
+    pub fn drop_preview(&self) -> usize {
+        self.inner
+            .lock()
+            .last_frame
+            .as_mut()
+            .and_then(|frame| frame.image_data.take())
+            .map_or(0, |data| data.len())
+    }
+}
+
+fn is_viewer_target(target: &EventTarget) -> bool {
+    match target {
+        EventTarget::WebviewWindow { label } | EventTarget::Window { label } | EventTarget::Webview { label } => {
+            label.starts_with(VIEWER_LABEL_PREFIX)
+        }
+        _ => false,
+    }
+}
+
+fn is_viewer_command(command: &str) -> bool {
+    VIEWER_COMMANDS.contains(&command)
+}
+
+/// 包装命令分发：监控窗口调用只读命令以外的命令时直接拒绝，不进入命令实现
+pub fn guard_invoke<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let from_viewer = invoke.message.webview_ref().label().starts_with(VIEWER_LABEL_PREFIX);
+        if from_viewer && !is_viewer_command(invoke.message.command()) {
+            let command = invoke.message.command().to_string();
+            tracing::warn!("🖥️ 已拒绝监控窗口 {} 调用命令: {}", invoke.message.webview_ref().label(), command);
+            invoke
+                .resolver
+                .reject(DetectionError::InvalidInput(format!("监控窗口为只读，不能调用命令: {}", command)));
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
+/// 发布一帧检测结果到所有监控窗口
+pub fn publish_frame(
+    app: &AppHandle,
+    source: &str,
+    image_data: Option<String>,
+    detections: Vec<Detection>,
+    total_inferences: u64,
+    avg_fps: f64,
+) {
+    let hub = app.state::<ViewerHub>();
+    let frame = {
+        let mut inner = hub.inner.lock();
+        inner.stats.frame_seq += 1;
+        inner.stats.total_inferences = total_inferences;
+        inner.stats.avg_fps = avg_fps;
+        inner.stats.abnormal_count += detections
+            .iter()
+            .filter(|d| d.class_name == crate::alerts::ABNORMAL_CLASS_NAME)
+            .count() as u64;
+
+        let frame = ViewerFrame {
+            source: source.to_string(),
+            image_data,
+            detections,
+            timestamp: crate::storage::now_rfc3339(),
+            stats: inner.stats.clone(),
+            replay: false,
+        };
+        inner.last_frame = Some(frame.clone());
+        hub.frame_seq.send_replace(inner.stats.frame_seq);
+        frame
+    };
+
+    if hub.has_viewers() {
+        if let Err(e) = app.emit_filter(EVENT_VIEWER_FRAME, frame, is_viewer_target) {
+            tracing::error!("推送监控帧失败: {}", e);
+        }
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 打开只读监控窗口
+#[tauri::command]
+pub async fn open_viewer_window(
+    app: AppHandle,
+    hub: State<'_, ViewerHub>,
+    title: Option<String>
+) -> Result<String, DetectionError> {
+    let label = {
+        let mut inner = hub.inner.lock();
+        inner.next_id += 1;
+        format!("{}{}", VIEWER_LABEL_PREFIX, inner.next_id)
+    };
+
+    let window = match WebviewWindowBuilder::new(&app, &label, WebviewUrl::App("index.html?viewer=1".into()))
+        .title(title.unwrap_or_else(|| "检测监控".to_string()))
+        .inner_size(1280.0, 720.0)
+        .resizable(true)
+        .build()
+    {
+        Ok(window) => window,
+        Err(e) => return Err(DetectionError::from(e).context("打开监控窗口失败")),
+    };
+
+    // 窗口关闭后取消订阅
+    let app_handle = app.clone();
+    let closed_label = label.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            app_handle
+                .state::<ViewerHub>()
+                .inner
+                .lock()
+                .windows
+                .retain(|l| l != &closed_label);
+            tracing::info!("🖥️ 监控窗口已关闭: {}", closed_label);
+        }
+    });
+
+    hub.inner.lock().windows.push(label.clone());
+    tracing::info!("🖥️ 已打开监控窗口: {}", label);
+    Ok(label)
+}
+
+/// 获取最近一帧监控数据（监控窗口打开时用于首屏显示）
+#[tauri::command]
+pub async fn get_viewer_snapshot(
+    hub: State<'_, ViewerHub>
+) -> Result<Option<ViewerFrame>, DetectionError> {
+    Ok(hub.inner.lock().last_frame.clone())
+}
+
+/// 列出当前打开的监控窗口
+#[tauri::command]
+pub async fn list_viewer_windows(
+    hub: State<'_, ViewerHub>
+) -> Result<Vec<String>, DetectionError> {
+    Ok(hub.inner.lock().windows.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewer_may_only_call_read_only_commands() {
+        assert!(is_viewer_command("get_viewer_snapshot"));
+        for command in ["open_viewer_window", "start_camera_detection", "stop_detection", "reset_configuration"] {
+            assert!(!is_viewer_command(command), "{}", command);
+        }
+    }
+}
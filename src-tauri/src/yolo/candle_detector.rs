@@ -1,38 +1,49 @@
 /*!
 真实的 Candle YOLO ONNX 检测器实现
-支持完整的YOLO模型加载、推理和后处理
+支持完整的YOLO模型加载、推理和后处理；导出为分类器的模型（输出 `[batch, 类别数]`）
+经同一预处理流程后按整图分类返回前 k 个类别（见 `classify_image`）
 */
 
 use anyhow::{anyhow, Result};
-use candle_core::{Device, Tensor};
+use async_trait::async_trait;
+use candle_core::{DType, Device, Storage, Tensor};
 use prost::Message;
 use candle_onnx;
-use image::GenericImageView;
+use image::{GenericImageView, RgbImage};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use std::sync::Arc;
 use parking_lot::RwLock;
+use rayon::prelude::*;
 use tokio::sync::Mutex;
 
-/// YOLO检测结果
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct YoloDetection {
-    pub class_id: u32,
-    pub class_name: String,
-    pub confidence: f32,
-    pub bbox: [f32; 4], // [x, y, width, height] - 相对于原图的坐标
-}
+use super::decode;
+use super::device::{self, DeviceGraph, DeviceSpec};
+use super::model_meta::{self, ModelShape, ModelTask};
+use super::nms::{self, NmsConfig};
+use super::preprocessing::{self, ResizeConfig, ResizeStrategy, ResizeTransform};
+use super::roi::{self, RoiPolygon};
+use super::rolling_stats::{RollingStats, StatsTimeseries};
+use super::tensor_pool::{self, PooledBuffer};
+use super::tiling::{self, TilingConfig};
+use super::tta::{self, TtaConfig};
+use super::types::{ClassPrediction, ClassificationResult, DetectionResult, DetectionTimings, FrameInfo, YoloDetection};
+use super::{Detector, InferenceBackend};
+use crate::error::{self, DetectionError};
+use crate::profiling;
 
-/// 检测结果包装
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DetectionResult {
-    pub detections: Vec<YoloDetection>,
-    pub image_width: u32,
-    pub image_height: u32,
-    pub processing_time_ms: u64,
-    pub model_input_size: (u32, u32),
-}
+/// 默认模型输入尺寸 (width, height)，YOLOv8 标准输入尺寸；用于动态输入的模型
+const DEFAULT_INPUT_SIZE: (u32, u32) = (640, 640);
+
+/// 模型同级目录下可提供类别名称的数据集配置文件（按优先级）
+const DATASET_CONFIG_FILES: &[&str] = &["data.yaml", "Box.yaml"];
+
+/// 批维度为动态的模型单次推理合并的最大图片数
+const DEFAULT_MAX_BATCH: usize = 8;
+
+/// 未找到类别名称时的来源标记
+const DEFAULT_CLASS_NAMES_SOURCE: &str = "default";
 
 /// 性能统计
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -41,68 +52,152 @@ pub struct ModelStats {
     pub total_preprocess_time_ms: u64,
     pub total_inference_time_ms: u64,
     pub total_postprocess_time_ms: u64,
+    /// 最近检测窗口内的平均帧率（按平均耗时折算）
     pub avg_fps: f64,
     pub cache_hits: u64,
     pub cache_misses: u64,
+    /// 滚动窗口内的样本数
+    #[serde(default)]
+    pub window_size: usize,
+    /// 滚动窗口内单次检测总耗时的百分位（毫秒）
+    #[serde(default)]
+    pub p50_latency_ms: f64,
+    #[serde(default)]
+    pub p95_latency_ms: f64,
+    #[serde(default)]
+    pub p99_latency_ms: f64,
+    /// 最近一分钟完成的检测次数
+    #[serde(default)]
+    pub throughput_per_minute: u64,
+    /// 各类别累计检测统计（按类别名称）
+    #[serde(default)]
+    pub class_stats: HashMap<String, ClassStats>,
 }
 
-/// 图像特征
-#[derive(Debug, Clone)]
-struct ImageFeatures {
-    pub brightness: f32,    // 平均亮度 [0,1]
-    pub contrast: f32,      // 对比度/标准差
-    pub edge_density: f32,  // 边缘密度 [0,1]
-    pub width: u32,
-    pub height: u32,
+/// 单个类别的累计检测统计
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ClassStats {
+    pub detection_count: u64,
+    pub avg_confidence: f32,
 }
 
-impl Default for ImageFeatures {
-    fn default() -> Self {
-        Self {
-            brightness: 0.5,
-            contrast: 0.2,
-            edge_density: 0.1,
-            width: 640,
-            height: 640,
+impl ClassStats {
+    /// 累加一次检测（增量更新平均置信度）
+    pub(crate) fn record(&mut self, confidence: f32) {
+        self.detection_count += 1;
+        self.avg_confidence += (confidence - self.avg_confidence) / self.detection_count as f32;
+    }
+}
+
+/// 分类输出转换为概率：已是概率分布（导出时带 Softmax）的原样返回，否则按 logits 做 Softmax
+fn class_probabilities(scores: &[f32]) -> Vec<f32> {
+    let sum: f32 = scores.iter().sum();
+    if scores.iter().all(|s| (0.0..=1.0).contains(s)) && (sum - 1.0).abs() < 1e-3 {
+        return scores.to_vec();
+    }
+    let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = scores.iter().map(|s| (s - max).exp()).collect();
+    let total: f32 = exps.iter().sum();
+    exps.into_iter().map(|e| e / total.max(f32::EPSILON)).collect()
+}
+
+/// 将张量数据复制到共享池的缓冲区（CPU张量直接读取底层存储，避免嵌套Vec分配）
+fn read_tensor_into_pool(tensor: &Tensor) -> Result<PooledBuffer<'static>> {
+    let tensor = tensor.contiguous()?;
+    let len = tensor.elem_count();
+    let mut buffer = tensor_pool::global().acquire(len);
+    let (storage, layout) = tensor.storage_and_layout();
+    match &*storage {
+        Storage::Cpu(cpu) => {
+            let start = layout.start_offset();
+            buffer.copy_from_slice(&cpu.as_slice::<f32>()?[start..start + len]);
         }
+        _ => buffer.copy_from_slice(&tensor.flatten_all()?.to_vec1::<f32>()?),
     }
+    Ok(buffer)
 }
 
-/// 检测框信息
-#[derive(Debug, Clone)]
-struct DetectionBox {
-    pub center_x: f32,  // 中心X坐标 [0,1]
-    pub center_y: f32,  // 中心Y坐标 [0,1]  
-    pub width: f32,     // 宽度 [0,1]
-    pub height: f32,    // 高度 [0,1]
+/// 沿批维度拆分模型输出 [K, ...]，前 count 张图各得到 [1, ...]（其余为补齐的空白输入）
+fn split_batch(output: &Tensor, count: usize) -> Result<Vec<Tensor>> {
+    let batch = output.dim(0)?;
+    if batch < count {
+        return Err(anyhow!("模型输出批大小 {} 与输入图片数 {} 不一致", batch, count));
+    }
+    (0..count)
+        .map(|index| output.narrow(0, index, 1).map_err(Into::into))
+        .collect()
 }
 
 /// Candle YOLO 检测器
 pub struct CandleYoloDetector {
     /// Candle 设备
     device: Device,
+    /// 请求的设备（实际设备可能已回退到CPU）
+    device_spec: DeviceSpec,
+    /// 设备回退原因
+    device_fallback: Option<String>,
+    /// 非CPU设备上的计算图与权重
+    device_graph: Option<DeviceGraph>,
+    /// 各模型是否请求半精度推理（按模型路径）
+    half_precision: HashMap<String, bool>,
+    /// 当前是否以半精度推理
+    half_active: bool,
+    /// 半精度回退到FP32的原因
+    precision_fallback: Option<String>,
     /// 加载的ONNX模型
     model: Option<candle_onnx::onnx::ModelProto>,
     /// 模型路径
     model_path: String,
     /// 类别名称映射
     class_names: HashMap<u32, String>,
+    /// 类别名称来源（见 load_class_names）
+    class_names_source: String,
     /// 模型输入尺寸 (width, height)
-    input_size: (u32, u32),
+    input_size: RwLock<(u32, u32)>,
+    /// 从计算图解析的模型形状
+    model_shape: ModelShape,
+    /// NMS参数
+    nms_config: RwLock<NmsConfig>,
+    /// 检测区域，为空时检测整幅画面
+    roi: RwLock<Vec<RoiPolygon>>,
+    /// 带透明通道的输入混合到的背景色
+    alpha_background: RwLock<[u8; 3]>,
+    /// 大图切片检测参数
+    tiling: RwLock<TilingConfig>,
+    /// 缩放策略与插值方式
+    resize: RwLock<ResizeConfig>,
+    /// 测试时增强参数（按次开启，见 `detect_image_tta`）
+    tta: RwLock<TtaConfig>,
     /// 置信度阈值（每个类别独立）
     confidence_thresholds: Arc<RwLock<HashMap<String, f32>>>,
     /// 启用的类别
     enabled_classes: Arc<RwLock<Vec<u32>>>,
     /// 性能统计
     stats: Arc<RwLock<ModelStats>>,
-    /// 预处理缓存
-    preprocessing_cache: Arc<Mutex<Option<(String, Tensor)>>>,
+    /// 最近检测的耗时窗口
+    rolling: Arc<RwLock<RollingStats>>,
+    /// 预处理缓存：缓存键、输入张量与原图尺寸
+    preprocessing_cache: Arc<Mutex<Option<(String, Tensor, (u32, u32))>>>,
+    /// 逐帧复用的缩放画布
+    resize_canvas: parking_lot::Mutex<RgbImage>,
+}
+
+/// 预处理缓存键：图像内容的 BLAKE3 哈希、输入尺寸与缩放参数
+fn preprocess_cache_key(image_data: &[u8], input_size: (u32, u32), resize: &ResizeConfig) -> String {
+    format!(
+        "{}:{}x{}:{:?}:{:?}",
+        blake3::hash(image_data).to_hex(),
+        input_size.0,
+        input_size.1,
+        resize.strategy,
+        resize.interpolation
+    )
 }
 
 impl CandleYoloDetector {
     /// 创建新的检测器实例
     pub fn new() -> Self {
-        let device = Device::Cpu; // 默认使用CPU，后续可扩展GPU支持
+        let device = Device::Cpu; // 默认使用CPU，可通过 select_device 切换
         
         // 初始化类别名称（从class_names.txt读取）
         let mut class_names = HashMap::new();
@@ -116,14 +211,30 @@ impl CandleYoloDetector {
         
         Self {
             device,
+            device_spec: DeviceSpec::Cpu,
+            device_fallback: None,
+            device_graph: None,
+            half_precision: HashMap::new(),
+            half_active: false,
+            precision_fallback: None,
             model: None,
             model_path: String::new(),
             class_names,
-            input_size: (640, 640), // YOLOv8 标准输入尺寸
+            class_names_source: DEFAULT_CLASS_NAMES_SOURCE.to_string(),
+            input_size: RwLock::new(DEFAULT_INPUT_SIZE),
+            model_shape: ModelShape::default(),
+            nms_config: RwLock::new(NmsConfig::default()),
+            roi: RwLock::new(Vec::new()),
+            alpha_background: RwLock::new(decode::DEFAULT_ALPHA_BACKGROUND),
+            tiling: RwLock::new(TilingConfig::default()),
+            resize: RwLock::new(ResizeConfig::default()),
+            tta: RwLock::new(TtaConfig::default()),
             confidence_thresholds: Arc::new(RwLock::new(thresholds)),
             enabled_classes: Arc::new(RwLock::new(vec![0, 1])), // 默认启用所有类别
             stats: Arc::new(RwLock::new(ModelStats::default())),
+            rolling: Arc::new(RwLock::new(RollingStats::new())),
             preprocessing_cache: Arc::new(Mutex::new(None)),
+            resize_canvas: parking_lot::Mutex::new(RgbImage::new(0, 0)),
         }
     }
     
@@ -137,14 +248,14 @@ impl CandleYoloDetector {
             current_dir.join(model_path)
         };
         
-        println!("🔍 加载ONNX模型: {}", model_path_obj.display());
+        tracing::info!("🔍 加载ONNX模型: {}", model_path_obj.display());
         
         if !model_path_obj.exists() {
-            return Err(anyhow!("ONNX模型文件不存在: {}", model_path_obj.display()));
+            return Err(DetectionError::NotFound(format!("ONNX模型文件不存在: {}", model_path_obj.display())).into());
         }
         
         if model_path_obj.extension().unwrap_or_default() != "onnx" {
-            return Err(anyhow!("只支持ONNX格式模型文件"));
+            return Err(DetectionError::UnsupportedFormat("只支持ONNX格式模型文件".to_string()).into());
         }
 
         // 读取ONNX模型文件
@@ -154,122 +265,365 @@ impl CandleYoloDetector {
         let model = candle_onnx::onnx::ModelProto::decode(model_data.as_slice())
             .map_err(|e| anyhow!("解析ONNX模型失败: {}", e))?;
         
-        println!("✅ ONNX模型加载成功");
-        println!("📊 模型信息:");
-        println!("  - 输入尺寸: {:?}", self.input_size);
-        println!("  - 设备: {:?}", self.device);
-        println!("  - 类别数: {}", self.class_names.len());
+        // 输入宽高固定的模型使用其输入尺寸，动态输入的模型使用导出时记录的尺寸或默认尺寸（可通过 set_input_size 修改）
+        self.model_shape = model_meta::inspect(&model);
+        *self.input_size.get_mut() = self
+            .model_shape
+            .input_size
+            .or_else(|| model_meta::metadata_input_size(&model))
+            .unwrap_or(DEFAULT_INPUT_SIZE);
+        self.preprocessing_cache.lock().await.take();
+        
+        tracing::info!("✅ ONNX模型加载成功");
+        tracing::info!("📊 模型信息:");
+        tracing::info!("  - 输入形状: {}", self.model_shape.describe_input());
+        tracing::info!("  - 输入尺寸: {:?}", *self.input_size.read());
+        tracing::info!("  - 任务类型: {}", self.model_shape.task.as_str());
+        tracing::info!("  - 设备: {:?}", self.device);
 
         self.model = Some(model);
         self.model_path = model_path_obj.to_string_lossy().to_string();
+        self.prepare_device_graph();
+        self.reserve_buffers();
         
-        // 从模型文件同级目录加载类别名称
+        // 加载类别名称
         self.load_class_names(&model_path_obj).await?;
+        self.fill_missing_class_names();
+        tracing::info!("  - 类别数: {}", self.class_names.len());
+        
+        Ok(())
+    }
+    
+    /// 选择推理设备，不可用时回退到CPU，返回实际使用的设备
+    pub async fn select_device(&mut self, spec: DeviceSpec) -> Result<String> {
+        let selected = device::open(spec);
+        self.device = selected.device;
+        self.device_spec = spec;
+        self.device_fallback = selected.fallback_reason;
+        self.device_graph = None;
+        // 缓存的输入张量位于旧设备上
+        self.preprocessing_cache.lock().await.take();
+        self.prepare_device_graph();
+        
+        let active = device::describe(&self.device);
+        tracing::info!("🖥️  推理设备: 请求 {}，实际 {}", spec, active);
+        Ok(active)
+    }
+    
+    /// 为当前模型启用/关闭半精度推理，返回是否实际以半精度运行
+    pub async fn set_half_precision(&mut self, enabled: bool) -> Result<bool> {
+        if self.model.is_none() {
+            return Err(anyhow!("模型未初始化，请先调用 init_model()"));
+        }
+        self.half_precision.insert(self.model_path.clone(), enabled);
+        // 缓存的输入张量精度已不匹配
+        self.preprocessing_cache.lock().await.take();
+        self.prepare_device_graph();
         
+        tracing::info!("⚙️ 推理精度: {}", if self.half_active { "FP16" } else { "FP32" });
+        Ok(self.half_active)
+    }
+    
+    /// 当前是否以半精度推理
+    pub fn is_half_precision(&self) -> bool {
+        self.half_active
+    }
+    
+    /// 设置NMS参数
+    pub fn set_nms_config(&self, config: NmsConfig) -> Result<()> {
+        config.validate().map_err(error::invalid_input)?;
+        tracing::info!(
+            "⚙️ NMS参数: {:?}, IoU阈值 {:.2}, 最大检测数 {}, {}",
+            config.method,
+            config.iou_threshold,
+            config.max_detections,
+            if config.class_agnostic { "跨类别抑制" } else { "按类别抑制" }
+        );
+        *self.nms_config.write() = config;
+        Ok(())
+    }
+    
+    /// 设置检测区域
+    pub fn set_roi(&self, regions: Vec<RoiPolygon>) -> Result<()> {
+        roi::validate(&regions).map_err(error::invalid_input)?;
+        tracing::info!("⚙️ 检测区域: {}", if regions.is_empty() { "整幅画面".to_string() } else { format!("{} 个", regions.len()) });
+        *self.roi.write() = regions;
+        Ok(())
+    }
+    
+    /// 设置大图切片检测参数
+    pub fn set_tiling_config(&self, config: TilingConfig) -> Result<()> {
+        config.validate().map_err(error::invalid_input)?;
+        if config.enabled {
+            tracing::info!(
+                "⚙️ 切片检测: 切片 {}x{}，重叠 {:.0}%，长边超过 {} 像素时启用{}",
+                config.tile_size.0,
+                config.tile_size.1,
+                config.overlap * 100.0,
+                config.min_image_size,
+                if config.include_full_image { "，同时检测整图" } else { "" }
+            );
+        }
+        *self.tiling.write() = config;
         Ok(())
     }
     
-    /// 从文件加载类别名称
+    /// 设置缩放策略与插值方式（从下一帧生效，同一帧的预处理与坐标换算使用同一设置）
+    pub fn set_resize_config(&self, config: ResizeConfig) -> Result<()> {
+        tracing::info!("⚙️ 缩放策略: {:?}, 插值: {:?}", config.strategy, config.interpolation);
+        *self.resize.write() = config;
+        Ok(())
+    }
+    
+    /// 设置测试时增强参数
+    pub fn set_tta_config(&self, config: TtaConfig) -> Result<()> {
+        config.validate().map_err(error::invalid_input)?;
+        *self.tta.write() = config;
+        Ok(())
+    }
+    
+    /// 设置透明像素混合的背景色
+    pub async fn set_alpha_background(&self, color: [u8; 3]) -> Result<()> {
+        if *self.alpha_background.read() == color {
+            return Ok(());
+        }
+        *self.alpha_background.write() = color;
+        // 缓存的输入张量按旧背景色混合
+        self.preprocessing_cache.lock().await.take();
+        tracing::info!("⚙️ 透明背景色: {:?}", color);
+        Ok(())
+    }
+    
+    /// 设置模型输入尺寸（宽高须为32的倍数）
+    pub async fn set_input_size(&self, size: (u32, u32)) -> Result<()> {
+        let (width, height) = size;
+        if width == 0 || height == 0 || width % 32 != 0 || height % 32 != 0 {
+            return Err(anyhow!("输入尺寸须为32的正整数倍: {}x{}", width, height));
+        }
+        if let Some((fixed_width, fixed_height)) = self.model_shape.input_size {
+            if size != (fixed_width, fixed_height) {
+                return Err(anyhow!(
+                    "当前模型输入尺寸固定为 {}x{}，不能设置为 {}x{}",
+                    fixed_width, fixed_height, width, height
+                ));
+            }
+        }
+        *self.input_size.write() = size;
+        // 缓存的输入张量尺寸已不匹配
+        self.preprocessing_cache.lock().await.take();
+        self.reserve_buffers();
+        tracing::info!("⚙️ 模型输入尺寸: {}x{}", width, height);
+        Ok(())
+    }
+    
+    /// 按当前输入尺寸与模型输出形状在共享池中预留预处理/后处理缓冲区
+    fn reserve_buffers(&self) {
+        let (width, height) = *self.input_size.read();
+        let plane = width as usize * height as usize;
+        tensor_pool::global().reserve(3 * plane, 2);
+        let max_batch = self.max_batch_size();
+        if max_batch > 1 {
+            tensor_pool::global().reserve(3 * plane * max_batch, 1);
+        }
+        // 输出形状含动态维度时无法预知大小，首帧分配后即可复用
+        let output_len: Option<usize> = self
+            .model_shape
+            .output_dims
+            .iter()
+            .map(|dim| dim.filter(|v| *v > 0).map(|v| v as usize))
+            .product();
+        if let Some(len) = output_len.filter(|_| !self.model_shape.output_dims.is_empty()) {
+            tensor_pool::global().reserve(len, 2);
+        }
+    }
+    
+    /// 为非CPU设备上传权重，失败时回退到CPU；请求半精度但设备不支持时回退到FP32
+    fn prepare_device_graph(&mut self) {
+        self.device_graph = None;
+        self.half_active = false;
+        self.precision_fallback = None;
+        
+        let want_half = self.half_precision.get(&self.model_path).copied().unwrap_or(false);
+        if want_half && self.device.is_cpu() {
+            self.precision_fallback = Some("CPU不支持高效的半精度运算，使用FP32".to_string());
+        }
+        let model = match &self.model {
+            Some(model) if !self.device.is_cpu() => model,
+            _ => return,
+        };
+        
+        if want_half {
+            match device::prepare_graph(model, &self.device, DType::F16) {
+                Ok(graph) => {
+                    tracing::info!("✅ 模型权重已以FP16上传到 {}（{} 个张量）", device::describe(&self.device), graph.weights.len());
+                    self.device_graph = Some(graph);
+                    self.half_active = true;
+                    return;
+                }
+                Err(e) => {
+                    let reason = format!("{} 不支持半精度权重，使用FP32: {}", device::describe(&self.device), e);
+                    tracing::warn!("⚠️  {}", reason);
+                    self.precision_fallback = Some(reason);
+                }
+            }
+        }
+        
+        match device::prepare_graph(model, &self.device, DType::F32) {
+            Ok(graph) => {
+                tracing::info!("✅ 模型权重已上传到 {}（{} 个张量）", device::describe(&self.device), graph.weights.len());
+                self.device_graph = Some(graph);
+            }
+            Err(e) => {
+                let reason = format!("上传权重到 {} 失败，已回退到CPU: {}", device::describe(&self.device), e);
+                tracing::warn!("⚠️  {}", reason);
+                self.device = Device::Cpu;
+                self.device_fallback = Some(reason);
+            }
+        }
+    }
+    
+    /// 加载类别名称，依次尝试模型同级目录的 class_names.txt、模型元数据中的 names、同级目录的 data.yaml / Box.yaml
     async fn load_class_names(&mut self, model_path: &Path) -> Result<()> {
-        let class_names_file = model_path.parent()
-            .unwrap_or_else(|| Path::new("."))
-            .join("class_names.txt");
+        let model_dir = model_path.parent().unwrap_or_else(|| Path::new("."));
         
+        let mut loaded: Option<(BTreeMap<u32, String>, String)> = None;
+        let class_names_file = model_dir.join("class_names.txt");
         if class_names_file.exists() {
             let content = tokio::fs::read_to_string(&class_names_file).await?;
-            let class_list: Vec<String> = content
+            let names: BTreeMap<u32, String> = content
                 .lines()
                 .map(|line| line.trim().to_string())
                 .filter(|line| !line.is_empty())
+                .enumerate()
+                .map(|(id, name)| (id as u32, name))
                 .collect();
-            
-            self.class_names.clear();
-            for (id, name) in class_list.iter().enumerate() {
-                self.class_names.insert(id as u32, name.clone());
+            if !names.is_empty() {
+                loaded = Some((names, "class_names.txt".to_string()));
             }
-            
-            // 更新置信度阈值映射
-            let mut thresholds = self.confidence_thresholds.write();
-            thresholds.clear();
-            for name in &class_list {
-                thresholds.insert(name.clone(), 0.5); // 默认阈值
+        }
+        if loaded.is_none() {
+            loaded = self
+                .model
+                .as_ref()
+                .and_then(model_meta::metadata_class_names)
+                .map(|names| (names, "onnx_metadata".to_string()));
+        }
+        for file_name in DATASET_CONFIG_FILES {
+            if loaded.is_some() {
+                break;
             }
-            
-            // 更新启用类别列表
-            let mut enabled = self.enabled_classes.write();
-            *enabled = (0..class_list.len() as u32).collect();
-            
-            println!("📄 从文件加载类别: {:?}", class_list);
-        } else {
-            println!("⚠️  未找到class_names.txt，使用默认类别");
+            let path = model_dir.join(file_name);
+            if let Ok(content) = tokio::fs::read_to_string(&path).await {
+                loaded = model_meta::yaml_class_names(&content).map(|names| (names, file_name.to_string()));
+            }
+        }
+        
+        let Some((names, source)) = loaded else {
+            tracing::warn!("⚠️  未找到类别名称（class_names.txt、模型元数据、数据集配置），使用默认类别");
+            self.class_names_source = DEFAULT_CLASS_NAMES_SOURCE.to_string();
+            return Ok(());
+        };
+        
+        self.class_names = names.iter().map(|(id, name)| (*id, name.clone())).collect();
+        
+        // 更新置信度阈值映射
+        let mut thresholds = self.confidence_thresholds.write();
+        thresholds.clear();
+        for name in names.values() {
+            thresholds.insert(name.clone(), 0.5); // 默认阈值
         }
         
+        // 更新启用类别列表
+        let mut enabled = self.enabled_classes.write();
+        *enabled = names.keys().copied().collect();
+        
+        tracing::info!("📄 从 {} 加载类别: {:?}", source, names.values().collect::<Vec<_>>());
+        self.class_names_source = source;
         Ok(())
     }
     
+    /// 模型输出的类别多于类别名称时，为缺少名称的类别补充 `class_<ID>` 并启用
+    fn fill_missing_class_names(&mut self) {
+        let Some(num_classes) = self.model_shape.num_classes else {
+            return;
+        };
+        if num_classes < self.class_names.len() {
+            tracing::warn!(
+                "⚠️  类别名称 {} 个，多于模型输出的 {} 个类别",
+                self.class_names.len(),
+                num_classes
+            );
+            return;
+        }
+        let mut thresholds = self.confidence_thresholds.write();
+        let mut enabled = self.enabled_classes.write();
+        for id in 0..num_classes as u32 {
+            if self.class_names.contains_key(&id) {
+                continue;
+            }
+            let name = format!("class_{}", id);
+            thresholds.insert(name.clone(), 0.5);
+            self.class_names.insert(id, name);
+            enabled.push(id);
+        }
+    }
+    
     /// 图像预处理 - 转换为模型输入张量
-    async fn preprocess_image(&self, image_data: &[u8]) -> Result<(Tensor, (u32, u32))> {
+    async fn preprocess_image(
+        &self,
+        image_data: &[u8],
+        input_size: (u32, u32),
+        resize: &ResizeConfig,
+        timings: &mut DetectionTimings,
+    ) -> Result<(Tensor, (u32, u32))> {
         let start_time = std::time::Instant::now();
         
-        // 计算缓存键
-        let cache_key = format!("{:x}", md5::compute(image_data));
+        let cache_key = preprocess_cache_key(image_data, input_size, resize);
         
         // 检查缓存
         {
             let cache = self.preprocessing_cache.lock().await;
-            if let Some((cached_key, ref tensor)) = cache.as_ref() {
+            if let Some((cached_key, tensor, original_size)) = cache.as_ref() {
                 if *cached_key == cache_key {
                     let mut stats = self.stats.write();
                     stats.cache_hits += 1;
                     stats.total_preprocess_time_ms += start_time.elapsed().as_millis() as u64;
                     
-                    // 获取原始图像尺寸
-                    let img = image::load_from_memory(image_data)?;
-                    let (width, height) = img.dimensions();
-                    
-                    return Ok((tensor.clone(), (width, height)));
+                    // 命中缓存时不再解码，原图尺寸随张量一起缓存
+                    return Ok((tensor.clone(), *original_size));
                 }
             }
         }
         
         // 缓存未命中，执行实际预处理
-        let img = image::load_from_memory(image_data)?;
+        let decode_start = std::time::Instant::now();
+        let img = decode::decode(image_data)?;
+        timings.decode_ms = DetectionTimings::since(decode_start);
         let (orig_width, orig_height) = img.dimensions();
         
-        // 调整图像尺寸到模型输入大小，保持宽高比
-        let resized = image::imageops::resize(
-            &img.to_rgb8(),
-            self.input_size.0,
-            self.input_size.1,
-            image::imageops::FilterType::Lanczos3,
-        );
-        
-        // 转换为张量格式 [1, 3, H, W]，值范围 [0, 1]
-        let mut tensor_data = Vec::with_capacity(
-            3 * self.input_size.0 as usize * self.input_size.1 as usize
-        );
-        
-        // 按CHW格式排列：先所有R通道，再所有G通道，最后所有B通道
-        for channel in 0..3 {
-            for y in 0..self.input_size.1 {
-                for x in 0..self.input_size.0 {
-                    let pixel = resized.get_pixel(x, y);
-                    let value = pixel[channel] as f32 / 255.0;
-                    tensor_data.push(value);
-                }
-            }
+        // 按缩放策略变换到模型输入大小（默认 letterbox：保持宽高比缩放，四周灰色填充）；
+        // 再转换为张量格式 [1, 3, H, W]，值范围 [0, 1]（画布与暂存缓冲区均逐帧复用）
+        let plane = input_size.0 as usize * input_size.1 as usize;
+        let mut tensor_data = tensor_pool::global().acquire(3 * plane);
+        {
+            let mut canvas = self.resize_canvas.lock();
+            let rgb = decode::to_rgb8(img, *self.alpha_background.read());
+            preprocessing::resize_into(&rgb, input_size, resize, &mut canvas);
+            preprocessing::to_chw(&canvas, &mut tensor_data);
         }
         
-        let tensor = Tensor::from_vec(
-            tensor_data,
-            &[1, 3, self.input_size.1 as usize, self.input_size.0 as usize],
+        let tensor = Tensor::from_slice(
+            &tensor_data[..],
+            &[1, 3, input_size.1 as usize, input_size.0 as usize],
             &self.device,
         )?;
+        // 半精度推理时输入张量同样转换为FP16
+        let tensor = if self.half_active { tensor.to_dtype(DType::F16)? } else { tensor };
         
         // 更新缓存
         {
             let mut cache = self.preprocessing_cache.lock().await;
-            *cache = Some((cache_key, tensor.clone()));
+            *cache = Some((cache_key, tensor.clone(), (orig_width, orig_height)));
         }
         
         let mut stats = self.stats.write();
@@ -279,267 +633,71 @@ impl CandleYoloDetector {
         Ok((tensor, (orig_width, orig_height)))
     }
     
-    /// 模型推理（智能模拟版本）
+    /// 模型推理 - 通过 candle_onnx 执行ONNX计算图
     async fn inference(&self, input_tensor: &Tensor) -> Result<Tensor> {
         let start_time = std::time::Instant::now();
         
-        // TODO: 实现真实的ONNX模型推理
-        // 目前由于Candle ONNX支持还在发展中，这里提供一个基于图像特征的智能模拟实现
-        
-        if self.model.is_none() {
-            return Err(anyhow!("模型未加载"));
-        }
-        
-        // 分析输入张量特征生成智能检测结果
-        let image_features = self.analyze_image_features(input_tensor).await?;
-        
-        // 模拟YOLOv8输出格式: [1, output_dim, 8400] 
-        let batch_size = 1;
-        let num_classes = self.class_names.len();
-        let num_anchors = 8400; // YOLOv8标准anchor数量
-        let output_dim = 4 + num_classes; // bbox + classes
-        
-        // 生成基于图像特征的智能检测输出
-        let mut output_data = vec![0.0f32; batch_size * output_dim * num_anchors];
-        
-        // 基于图像特征决定检测数量和位置
-        let num_detections = self.calculate_detection_count(&image_features);
-        
-        for i in 0..num_detections {
-            let base_idx = i * output_dim;
-            if base_idx + output_dim <= output_data.len() {
-                // 基于图像特征生成检测框位置
-                let detection_info = self.generate_detection_box(&image_features, i);
-                
-                output_data[base_idx] = detection_info.center_x;
-                output_data[base_idx + 1] = detection_info.center_y;
-                output_data[base_idx + 2] = detection_info.width;
-                output_data[base_idx + 3] = detection_info.height;
-                
-                // 基于图像特征生成类别置信度
-                if num_classes == 2 {
-                    let (abnormal_conf, normal_conf) = self.calculate_class_confidence(&image_features, i);
-                    output_data[base_idx + 4] = abnormal_conf; // 异常
-                    output_data[base_idx + 5] = normal_conf;   // 正常
-                }
-            }
-        }
-        
-        let output_tensor = Tensor::from_vec(
-            output_data,
-            &[batch_size, output_dim, num_anchors],
-            &self.device,
-        )?;
-        
-        let mut stats = self.stats.write();
-        stats.total_inference_time_ms += start_time.elapsed().as_millis() as u64;
-        
-        Ok(output_tensor)
-    }
-    
-    /// 分析图像特征（基于像素统计）
-    async fn analyze_image_features(&self, input_tensor: &Tensor) -> Result<ImageFeatures> {
-        // 检查张量维度并处理
-        let analysis_tensor = match input_tensor.dims().len() {
-            3 => {
-                // 已经是3维 [C, H, W]
-                println!("[DEBUG] 输入张量维度: 3维 {:?}", input_tensor.dims());
-                input_tensor.clone()
-            },
-            4 => {
-                // 4维张量 [1, C, H, W]，移除batch维度
-                println!("[DEBUG] 输入张量维度: 4维 {:?}，移除batch维度", input_tensor.dims());
-                input_tensor.squeeze(0)?
-            },
-            _ => {
-                return Err(anyhow!("不支持的张量维度: {:?}，期望3维或4维", input_tensor.dims()));
+        let model = self.model.as_ref().ok_or(DetectionError::ModelNotLoaded)?;
+        let graph = model.graph.as_ref().ok_or_else(|| anyhow!("ONNX模型缺少计算图"))?;
+        
+        let input_name = model_meta::image_input(graph)
+            .ok_or_else(|| anyhow!("ONNX模型没有输入节点"))?
+            .name
+            .clone();
+        let output_name = graph
+            .output
+            .first()
+            .map(|output| output.name.clone())
+            .ok_or_else(|| anyhow!("ONNX模型没有输出节点"))?;
+        
+        let mut inputs = HashMap::new();
+        inputs.insert(input_name, input_tensor.clone());
+        
+        // 非CPU设备使用上传了权重的计算图，权重随输入一起传入
+        let graph_model = match &self.device_graph {
+            Some(device_graph) => {
+                inputs.extend(device_graph.weights.iter().map(|(name, tensor)| (name.clone(), tensor.clone())));
+                &device_graph.model
             }
+            None => model,
         };
         
-        println!("[DEBUG] 处理后张量维度: {:?}", analysis_tensor.dims());
-        
-        // 获取张量数据 - 现在保证是3维
-        let tensor_data = analysis_tensor.to_vec3::<f32>()?;
-        
-        if tensor_data.is_empty() || tensor_data[0].is_empty() || tensor_data[0][0].is_empty() {
-            return Ok(ImageFeatures::default());
-        }
-        
-        let channels = tensor_data[0].len(); // 应该是3 (RGB)
-        let height = tensor_data[0][0].len();
-        let width = if height > 0 { tensor_data[0][0][0..].len() } else { 0 }; // 修复：假设是方形
-        
-        let mut brightness_sum = 0.0f32;
-        let mut variance_sum = 0.0f32;
-        let total_pixels = (width * height) as f32;
-        
-        // 计算亮度和方差
-        for c in 0..channels.min(3) {
-            for &pixel_row in &tensor_data[0][c] {
-                brightness_sum += pixel_row;
-                variance_sum += pixel_row * pixel_row;
-            }
-        }
-        
-        let avg_brightness = brightness_sum / (total_pixels * 3.0);
-        let variance = (variance_sum / (total_pixels * 3.0)) - (avg_brightness * avg_brightness);
-        
-        // 分析边缘密度（简化版本）
-        let edge_density = self.calculate_edge_density(&tensor_data);
-        
-        Ok(ImageFeatures {
-            brightness: avg_brightness,
-            contrast: variance.sqrt(),
-            edge_density,
-            width: width as u32,
-            height: height as u32,
-        })
-    }
-    
-    /// 计算边缘密度
-    fn calculate_edge_density(&self, tensor_data: &[Vec<Vec<f32>>]) -> f32 {
-        if tensor_data.is_empty() || tensor_data[0].is_empty() || tensor_data[0][0].len() < 2 {
-            return 0.0;
-        }
-        
-        let _height = tensor_data[0][0].len();
-        let mut edge_count = 0;
-        let mut total_comparisons = 0;
-        
-        // 简化的边缘检测：比较相邻像素差异
-        for (row_idx, row_data) in tensor_data[0][0].iter().enumerate() {
-            if row_idx + 1 < tensor_data[0][0].len() {
-                let diff = (row_data - tensor_data[0][0][row_idx + 1]).abs();
-                if diff > 0.1 { // 阈值
-                    edge_count += 1;
+        // 计算图执行为CPU密集操作，避免阻塞异步运行时的其他任务
+        let mut outputs = tokio::task::block_in_place(|| candle_onnx::simple_eval(graph_model, inputs))
+            .map_err(|e| {
+                let message = e.to_string();
+                if message.contains("unsupported") {
+                    anyhow!("模型包含Candle暂不支持的算子，无法推理: {}", message)
+                } else {
+                    anyhow!("ONNX推理失败: {}", message)
                 }
-                total_comparisons += 1;
-            }
-        }
-        
-        if total_comparisons > 0 {
-            edge_count as f32 / total_comparisons as f32
+            })?;
+        
+        let output_tensor = outputs
+            .remove(&output_name)
+            .ok_or_else(|| anyhow!("推理结果中缺少输出 {}", output_name))?;
+        // 后处理按FP32读取
+        let output_tensor = if output_tensor.dtype() == DType::F32 {
+            output_tensor
         } else {
-            0.0
-        }
-    }
-    
-    /// 基于图像特征计算检测数量 - 针对工业设备优化
-    fn calculate_detection_count(&self, features: &ImageFeatures) -> usize {
-        // 基于图像复杂度决定检测数量，对工业设备图像更敏感
-        let complexity_score = features.contrast * 0.6 + features.edge_density * 0.4;
-        let brightness_factor = if features.brightness > 0.6 || features.brightness < 0.3 { 0.2 } else { 0.0 };
-        
-        let adjusted_score = complexity_score + brightness_factor;
-        
-        println!("[DEBUG] 检测数量计算:");
-        println!("  - 复杂度分数: {:.3}", complexity_score);
-        println!("  - 亮度因子: {:.3}", brightness_factor);
-        println!("  - 调整后分数: {:.3}", adjusted_score);
-        
-        let count = if adjusted_score > 0.5 {
-            3 // 复杂图像，多个检测
-        } else if adjusted_score > 0.3 {
-            2 // 中等复杂度
-        } else if adjusted_score > 0.1 {
-            2 // 提高基础检测数量，确保工业设备图像有检测结果
-        } else {
-            1 // 即使简单图像也至少检测1个
-        };
-        
-        println!("  → 检测数量: {}", count);
-        count
-    }
-    
-    /// 生成检测框信息
-    fn generate_detection_box(&self, features: &ImageFeatures, detection_idx: usize) -> DetectionBox {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        
-        // 基于图像特征和检测索引生成一致的随机数
-        let mut hasher = DefaultHasher::new();
-        ((features.brightness * 1000.0) as u64).hash(&mut hasher);
-        ((features.contrast * 1000.0) as u64).hash(&mut hasher);
-        detection_idx.hash(&mut hasher);
-        let seed = hasher.finish();
-        
-        // 使用种子生成确定性的"随机"位置 - 保守的防溢出方案
-        let pseudo_rand = |offset: u64| -> f32 {
-            // 使用更简单的算术避免任何溢出风险
-            let seed_low = (seed as u32) as u64;
-            let offset_low = (offset as u32) as u64;
-            let combined = (seed_low + offset_low + 12345) % 1000000;
-            combined as f32 / 1000000.0
+            output_tensor.to_dtype(DType::F32)?
         };
         
-        // 根据图像亮度调整检测框位置
-        let brightness_factor = features.brightness.clamp(0.0, 1.0);
-        let contrast_factor = features.contrast.clamp(0.0, 1.0);
-        
-        DetectionBox {
-            center_x: 0.2 + pseudo_rand(detection_idx as u64) * 0.6, // 0.2-0.8范围
-            center_y: 0.2 + pseudo_rand(detection_idx as u64 + 100) * 0.6,
-            width: 0.1 + contrast_factor * 0.2, // 基于对比度调整大小
-            height: 0.1 + brightness_factor * 0.2, // 基于亮度调整大小
-        }
-    }
-    
-    /// 计算类别置信度 - 优化工业设备异常检测
-    fn calculate_class_confidence(&self, features: &ImageFeatures, detection_idx: usize) -> (f32, f32) {
-        // 基于图像特征生成类别置信度
-        let brightness = features.brightness;
-        let contrast = features.contrast;
-        let edge_density = features.edge_density;
-        
-        println!("[DEBUG] 图像特征分析:");
-        println!("  - 亮度: {:.3} (0-1)", brightness);
-        println!("  - 对比度: {:.3}", contrast);  
-        println!("  - 边缘密度: {:.3}", edge_density);
-        
-        // 优化的异常检测逻辑：工业设备异常通常表现为明显物体、高对比度、特定颜色
-        let mut abnormal_score: f32 = 0.0;
-        
-        // 1. 高对比度检测（异常物体与背景对比强烈）
-        if contrast > 0.3 {
-            abnormal_score += 0.4;
-            println!("  + 高对比度检测: +0.4");
-        }
-        
-        // 2. 边缘密度检测（异常物体边缘明显）  
-        if edge_density > 0.2 {
-            abnormal_score += 0.3;
-            println!("  + 边缘密度检测: +0.3");
-        }
-        
-        // 3. 亮度特征检测（明显的亮色或暗色物体）
-        if brightness > 0.6 || brightness < 0.3 {
-            abnormal_score += 0.2;
-            println!("  + 亮度特征检测: +0.2");
-        }
-        
-        // 4. 复杂度综合评分（复杂图像更可能包含异常）
-        let complexity = contrast * 0.6 + edge_density * 0.4;
-        if complexity > 0.4 {
-            abnormal_score += 0.3;
-            println!("  + 复杂度评分: +0.3");
+        // YOLOv8 输出格式: [1, 4 + num_classes, num_anchors]（或转置的 [1, num_anchors, 4 + num_classes]）；分类模型为 [1, num_classes]
+        match (self.model_shape.task, output_tensor.dims().len()) {
+            (ModelTask::Detect, 3) | (ModelTask::Classify, 2) => {}
+            (ModelTask::Detect, _) => {
+                return Err(anyhow!("不支持的模型输出维度: {:?}，期望 [1, 4+类别数, 锚点数]", output_tensor.dims()));
+            }
+            (ModelTask::Classify, _) => {
+                return Err(anyhow!("不支持的分类模型输出维度: {:?}，期望 [1, 类别数]", output_tensor.dims()));
+            }
         }
         
-        // 确保至少有基础的异常检测概率
-        abnormal_score = abnormal_score.max(0.15);
-        
-        // 为不同检测区域添加位置相关的变化
-        let position_factor = match detection_idx {
-            0 => 1.2, // 第一个检测更倾向于异常
-            1 => 0.9,
-            _ => 1.0,
-        };
-        
-        let final_abnormal = (abnormal_score * position_factor).clamp(0.15, 0.95);
-        let final_normal = (1.0 - final_abnormal).clamp(0.05, 0.85);
-        
-        println!("  → 最终异常置信度: {:.3}, 正常置信度: {:.3}", final_abnormal, final_normal);
+        let mut stats = self.stats.write();
+        stats.total_inference_time_ms += start_time.elapsed().as_millis() as u64;
         
-        (final_abnormal, final_normal)
+        Ok(output_tensor)
     }
     
     /// 后处理 - 解析模型输出为检测结果
@@ -547,47 +705,78 @@ impl CandleYoloDetector {
         &self,
         output_tensor: &Tensor,
         original_size: (u32, u32),
+        input_size: (u32, u32),
+        strategy: ResizeStrategy,
     ) -> Result<Vec<YoloDetection>> {
         let start_time = std::time::Instant::now();
         
-        // 获取输出数据 [batch, output_dim, num_anchors]
-        let output_data = output_tensor.to_vec3::<f32>()?;
+        let raw_detections = self.parse_detections(output_tensor, original_size, input_size, strategy)?;
+        let final_detections = self.suppress(raw_detections, original_size);
+        
+        let mut stats = self.stats.write();
+        stats.total_postprocess_time_ms += start_time.elapsed().as_millis() as u64;
         
-        if output_data.is_empty() || output_data[0].is_empty() {
+        Ok(final_detections)
+    }
+    
+    /// 解析单张图的模型输出，按置信度阈值与启用的类别过滤，坐标按预处理使用的缩放策略换算到 original_size 的图像上
+    fn parse_detections(
+        &self,
+        output_tensor: &Tensor,
+        original_size: (u32, u32),
+        input_size: (u32, u32),
+        strategy: ResizeStrategy,
+    ) -> Result<Vec<YoloDetection>> {
+        // 获取输出数据 [batch, output_dim, num_anchors]，只取第一个batch；
+        // 锚点数总是远多于 4 + 类别数，据此识别转置的输出 [batch, num_anchors, output_dim]
+        let (batch, dim1, dim2) = output_tensor.dims3()?;
+        if batch == 0 || dim1 == 0 || dim2 == 0 {
             return Ok(Vec::new());
         }
+        let transposed = dim1 > dim2;
+        let (rows, num_anchors) = if transposed { (dim2, dim1) } else { (dim1, dim2) };
+        let output_data = read_tensor_into_pool(&output_tensor.get(0)?)?;
+        let at = |row: usize, anchor: usize| {
+            if transposed {
+                output_data[anchor * rows + row]
+            } else {
+                output_data[row * num_anchors + anchor]
+            }
+        };
         
-        let num_classes = self.class_names.len();
+        // 类别数以模型实际输出为准
+        let num_classes = rows.saturating_sub(4);
         let output_dim = 4 + num_classes;
-        let num_anchors = output_data[0][0].len();
+        let transform = ResizeTransform::new(original_size, input_size, strategy);
         
         let mut raw_detections = Vec::new();
         
         // 解析每个anchor的预测
         for anchor_idx in 0..num_anchors {
-            if output_data[0].len() < output_dim {
+            if rows < output_dim {
                 continue;
             }
             
             // 提取边界框坐标 (center_x, center_y, width, height)
-            let center_x = output_data[0][0][anchor_idx];
-            let center_y = output_data[0][1][anchor_idx];
-            let width = output_data[0][2][anchor_idx];
-            let height = output_data[0][3][anchor_idx];
+            let center_x = at(0, anchor_idx);
+            let center_y = at(1, anchor_idx);
+            let width = at(2, anchor_idx);
+            let height = at(3, anchor_idx);
             
             // 提取类别置信度
             let mut class_scores = Vec::new();
             for class_idx in 0..num_classes {
-                if 4 + class_idx < output_data[0].len() {
-                    class_scores.push(output_data[0][4 + class_idx][anchor_idx]);
+                if 4 + class_idx < rows {
+                    class_scores.push(at(4 + class_idx, anchor_idx));
                 }
             }
             
-            // 找到置信度最高的类别
+            // 找到置信度最高的类别（跳过 NaN/无穷大等异常输出）
             if let Some((class_id, &confidence)) = class_scores
                 .iter()
                 .enumerate()
-                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap()) {
+                .filter(|(_, score)| score.is_finite())
+                .max_by(|a, b| a.1.total_cmp(b.1)) {
                 
                 // 检查置信度阈值
                 let class_name = self.class_names.get(&(class_id as u32))
@@ -599,150 +788,450 @@ impl CandleYoloDetector {
                     .copied()
                     .unwrap_or(0.5);
                 
-                println!("[DEBUG] 过滤检查: 类别={}, 置信度={:.3}, 阈值={:.3}, 通过={}", 
+                tracing::debug!("过滤检查: 类别={}, 置信度={:.3}, 阈值={:.3}, 通过={}", 
                     class_name, confidence, threshold, confidence >= threshold);
                 
                 if confidence >= threshold {
                     // 检查类别是否启用
                     let enabled_classes = self.enabled_classes.read();
                     if enabled_classes.contains(&(class_id as u32)) {
-                        // 转换坐标到原图尺寸 (相对坐标转绝对坐标)
-                        let x = (center_x - width / 2.0) * original_size.0 as f32;
-                        let y = (center_y - height / 2.0) * original_size.1 as f32;
-                        let w = width * original_size.0 as f32;
-                        let h = height * original_size.1 as f32;
-                        
+                        // 模型输出为缩放后输入下的像素坐标，去除填充/裁剪偏移并换算到原图尺寸
                         raw_detections.push(YoloDetection {
                             class_id: class_id as u32,
                             class_name,
                             confidence,
-                            bbox: [x, y, w, h],
+                            bbox: transform.unmap_box(center_x, center_y, width, height, original_size),
+                            size_mm: None,
                         });
                     }
                 }
             }
         }
         
-        // 应用NMS (非极大值抑制)
-        let final_detections = self.apply_nms(raw_detections, 0.4).await;
-        
-        let mut stats = self.stats.write();
-        stats.total_postprocess_time_ms += start_time.elapsed().as_millis() as u64;
-        
-        Ok(final_detections)
+        Ok(raw_detections)
     }
     
-    /// 非极大值抑制 (NMS)
-    async fn apply_nms(&self, mut detections: Vec<YoloDetection>, iou_threshold: f32) -> Vec<YoloDetection> {
-        if detections.len() <= 1 {
-            return detections;
+    /// 丢弃检测区域之外的检测框（先于NMS，区域外的框不参与抑制），再应用NMS (非极大值抑制)
+    fn suppress(&self, mut raw_detections: Vec<YoloDetection>, original_size: (u32, u32)) -> Vec<YoloDetection> {
+        {
+            let regions = self.roi.read();
+            raw_detections.retain(|d| roi::contains_detection(&regions, d, original_size));
+        }
+        nms::apply_nms(raw_detections, &self.nms_config.read())
+    }
+    
+    /// 确认已加载模型且模型任务与调用的接口一致
+    fn ensure_task(&self, task: ModelTask) -> Result<()> {
+        if self.model.is_none() {
+            return Err(anyhow!("模型未初始化，请先调用 init_model()"));
         }
+        match (self.model_shape.task, task) {
+            (ModelTask::Classify, ModelTask::Detect) => {
+                Err(DetectionError::InvalidInput("当前模型为分类模型，不输出检测框，请使用整图分类".to_string()).into())
+            }
+            (ModelTask::Detect, ModelTask::Classify) => {
+                Err(DetectionError::InvalidInput("当前模型为检测模型，不支持整图分类".to_string()).into())
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// 主要的图像检测接口
+    pub async fn detect_image(&self, image_data: &[u8]) -> Result<DetectionResult> {
+        let total_start_time = std::time::Instant::now();
         
-        // 按置信度降序排序
-        detections.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
+        self.ensure_task(ModelTask::Detect)?;
         
-        let mut keep = Vec::new();
-        let mut suppressed = vec![false; detections.len()];
+        // 大图按切片检测（只读取文件头判断尺寸）
+        let tiling = self.tiling.read().clone();
+        if decode::dimensions(image_data).is_some_and(|size| tiling.applies_to(size)) {
+            return self.detect_tiled(image_data, &tiling, total_start_time).await;
+        }
         
-        for i in 0..detections.len() {
-            if suppressed[i] {
-                continue;
+        // 1. 图像预处理（含解码）；输入尺寸与缩放参数在整帧处理期间保持一致，中途修改从下一帧生效
+        let input_size = *self.input_size.read();
+        let resize = *self.resize.read();
+        let mut timings = DetectionTimings::default();
+        let stage_start = std::time::Instant::now();
+        let (input_tensor, original_size) =
+            profiling::stage("preprocess", self.preprocess_image(image_data, input_size, &resize, &mut timings)).await?;
+        timings.preprocess_ms = (DetectionTimings::since(stage_start) - timings.decode_ms).max(0.0);
+        
+        // 2. 模型推理
+        let stage_start = std::time::Instant::now();
+        let output_tensor = profiling::stage("inference", self.inference(&input_tensor)).await?;
+        timings.inference_ms = DetectionTimings::since(stage_start);
+        
+        // 3. 后处理
+        self.finish_detection(&output_tensor, original_size, input_size, resize.strategy, timings, DetectionTimings::since(total_start_time))
+            .await
+    }
+    
+    /// 批量检测：模型支持批维度时每 `max_batch_size` 张组成一个 [K, 3, H, W] 输入执行一次推理，
+    /// 结果与输入一一对应（单张失败不影响其他图片）
+    pub async fn detect_batch(&self, images: &[Vec<u8>]) -> Vec<Result<DetectionResult>> {
+        if let Err(e) = self.ensure_task(ModelTask::Detect) {
+            let message = format!("{:#}", e);
+            return images.iter().map(|_| Err(anyhow!(message.clone()))).collect();
+        }
+        let max_batch = self.max_batch_size();
+        let mut results = Vec::with_capacity(images.len());
+        // 切片模式下每张图本身已按批推理各切片
+        if max_batch < 2 || images.len() < 2 || self.tiling.read().enabled {
+            for data in images {
+                results.push(self.detect_image(data).await);
             }
+            return results;
+        }
+        for chunk in images.chunks(max_batch) {
+            results.extend(self.detect_chunk(chunk).await);
+        }
+        results
+    }
+    
+    /// 单次推理可处理的最大图片数：批维度为动态时取默认上限，固定时取该值
+    pub fn max_batch_size(&self) -> usize {
+        if self.model.is_none() {
+            return 1;
+        }
+        match self.model_shape.input_dims.first() {
+            Some(None) => DEFAULT_MAX_BATCH,
+            Some(Some(batch)) if *batch > 1 => *batch as usize,
+            _ => 1,
+        }
+    }
+    
+    /// 一个批次：各图并行解码与缩放，写入同一块 [K, 3, H, W] 输入执行一次推理，
+    /// 再沿批维度拆分输出逐张后处理
+    async fn detect_chunk(&self, images: &[Vec<u8>]) -> Vec<Result<DetectionResult>> {
+        let chunk_start = std::time::Instant::now();
+        let mut results: Vec<Option<Result<DetectionResult>>> = images.iter().map(|_| None).collect();
+        let input_size = *self.input_size.read();
+        let resize = *self.resize.read();
+        
+        // 1. 并行解码，解码失败的图片单独返回错误
+        let background = *self.alpha_background.read();
+        let decoded: Vec<(Result<RgbImage>, f64)> = profiling::stage_sync("decode", || {
+            images
+                .par_iter()
+                .map(|data| {
+                    let start = std::time::Instant::now();
+                    let img = decode::decode(data).map(|img| decode::to_rgb8(img, background));
+                    (img, DetectionTimings::since(start))
+                })
+                .collect()
+        });
+        let mut frames = Vec::with_capacity(images.len());
+        let mut prepared = Vec::with_capacity(images.len());
+        for (index, (img, decode_ms)) in decoded.into_iter().enumerate() {
+            match img {
+                Ok(img) => {
+                    prepared.push((index, img.dimensions(), DetectionTimings { decode_ms, ..Default::default() }));
+                    frames.push(img);
+                }
+                Err(e) => results[index] = Some(Err(e)),
+            }
+        }
+        
+        if !frames.is_empty() {
+            // 2. 批量预处理与一次推理，耗时按图片数均摊
+            let count = frames.len() as f64;
+            let stage_start = std::time::Instant::now();
+            let input = profiling::stage_sync("preprocess", || self.batch_input(&frames, input_size, &resize));
+            let preprocess_ms = DetectionTimings::since(stage_start) / count;
+            drop(frames);
+            let stage_start = std::time::Instant::now();
+            let output = match input {
+                Ok(input) => profiling::stage("inference", self.inference(&input)).await,
+                Err(e) => Err(e),
+            };
+            let inference_ms = DetectionTimings::since(stage_start) / count;
             
-            keep.push(detections[i].clone());
-            
-            // 抑制与当前检测框重叠度高的其他检测框
-            for j in (i + 1)..detections.len() {
-                if suppressed[j] {
-                    continue;
+            // 3. 逐张后处理
+            match output.and_then(|output| split_batch(&output, prepared.len())) {
+                Ok(outputs) => {
+                    for ((index, original_size, mut timings), single) in prepared.into_iter().zip(outputs) {
+                        timings.preprocess_ms = preprocess_ms;
+                        timings.inference_ms = inference_ms;
+                        let elapsed = timings.decode_ms + preprocess_ms + inference_ms;
+                        let result = self.finish_detection(&single, original_size, input_size, resize.strategy, timings, elapsed).await;
+                        results[index] = Some(result);
+                    }
                 }
-                
-                let iou = Self::calculate_iou(&detections[i].bbox, &detections[j].bbox);
-                if iou > iou_threshold {
-                    suppressed[j] = true;
+                Err(e) => {
+                    let message = format!("批量推理失败: {:#}", e);
+                    for (index, ..) in prepared {
+                        results[index] = Some(Err(anyhow!(message.clone())));
+                    }
                 }
             }
         }
         
-        keep
+        tracing::debug!("批量推理 {} 张，耗时 {:.1} ms", images.len(), DetectionTimings::since(chunk_start));
+        results
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|| Err(anyhow!("图片未处理"))))
+            .collect()
     }
     
-    /// 计算两个边界框的IoU (Intersection over Union)
-    fn calculate_iou(box1: &[f32; 4], box2: &[f32; 4]) -> f32 {
-        let x1_min = box1[0];
-        let y1_min = box1[1];
-        let x1_max = box1[0] + box1[2];
-        let y1_max = box1[1] + box1[3];
-        
-        let x2_min = box2[0];
-        let y2_min = box2[1];
-        let x2_max = box2[0] + box2[2];
-        let y2_max = box2[1] + box2[3];
-        
-        let inter_x_min = x1_min.max(x2_min);
-        let inter_y_min = y1_min.max(y2_min);
-        let inter_x_max = x1_max.min(x2_max);
-        let inter_y_max = y1_max.min(y2_max);
+    /// 多张已解码的图像组成 [K, 3, H, W] 输入张量（暂存缓冲区来自共享池，借出时已清零）；
+    /// 批维度固定的模型不足 K 张时以空白输入补齐
+    fn batch_input(&self, frames: &[RgbImage], input_size: (u32, u32), resize: &ResizeConfig) -> Result<Tensor> {
+        let start_time = std::time::Instant::now();
+        let batch = match self.model_shape.input_dims.first() {
+            Some(Some(fixed)) => (*fixed as usize).max(frames.len()),
+            _ => frames.len(),
+        };
+        let item_len = 3 * input_size.0 as usize * input_size.1 as usize;
+        let mut tensor_data = tensor_pool::global().acquire(batch * item_len);
+        preprocessing::batch_to_chw(frames, input_size, resize, &mut tensor_data);
+        let tensor = Tensor::from_slice(
+            &tensor_data[..],
+            &[batch, 3, input_size.1 as usize, input_size.0 as usize],
+            &self.device,
+        )?;
+        let tensor = if self.half_active { tensor.to_dtype(DType::F16)? } else { tensor };
         
-        if inter_x_max <= inter_x_min || inter_y_max <= inter_y_min {
-            return 0.0;
+        // 批输入不经过预处理缓存
+        self.stats.write().total_preprocess_time_ms += start_time.elapsed().as_millis() as u64;
+        Ok(tensor)
+    }
+    
+    /// 后处理并记录统计，elapsed_ms 为后处理之前已花费的时间
+    async fn finish_detection(
+        &self,
+        output_tensor: &Tensor,
+        original_size: (u32, u32),
+        input_size: (u32, u32),
+        strategy: ResizeStrategy,
+        mut timings: DetectionTimings,
+        elapsed_ms: f64,
+    ) -> Result<DetectionResult> {
+        let stage_start = std::time::Instant::now();
+        let detections =
+            profiling::stage("postprocess", self.postprocess(output_tensor, original_size, input_size, strategy)).await?;
+        timings.postprocess_ms = DetectionTimings::since(stage_start);
+        let total_ms = elapsed_ms + timings.postprocess_ms;
+        Ok(self.record_result(detections, original_size, input_size, timings, total_ms))
+    }
+    
+    /// 切片检测：各切片（及整图）缩放后按批推理，检测框平移回原图坐标，合并后统一过滤检测区域并做全局NMS
+    async fn detect_tiled(
+        &self,
+        image_data: &[u8],
+        config: &TilingConfig,
+        total_start_time: std::time::Instant,
+    ) -> Result<DetectionResult> {
+        let input_size = *self.input_size.read();
+        let resize = *self.resize.read();
+        let mut timings = DetectionTimings::default();
+        
+        let stage_start = std::time::Instant::now();
+        let img = decode::to_rgb8(decode::decode(image_data)?, *self.alpha_background.read());
+        timings.decode_ms = DetectionTimings::since(stage_start);
+        let original_size = img.dimensions();
+        let tiles = tiling::plan(original_size, config);
+        
+        let max_batch = self.max_batch_size().max(1);
+        let mut raw_detections = Vec::new();
+        for chunk in tiles.chunks(max_batch) {
+            let stage_start = std::time::Instant::now();
+            let crops: Vec<RgbImage> = chunk
+                .iter()
+                .map(|tile| image::imageops::crop_imm(&img, tile.x, tile.y, tile.width, tile.height).to_image())
+                .collect();
+            let input = profiling::stage_sync("preprocess", || self.batch_input(&crops, input_size, &resize))?;
+            timings.preprocess_ms += DetectionTimings::since(stage_start);
+            
+            let stage_start = std::time::Instant::now();
+            let output = profiling::stage("inference", self.inference(&input)).await?;
+            timings.inference_ms += DetectionTimings::since(stage_start);
+            
+            let stage_start = std::time::Instant::now();
+            for (tile, single) in chunk.iter().zip(split_batch(&output, chunk.len())?) {
+                let mut detections = self.parse_detections(&single, (tile.width, tile.height), input_size, resize.strategy)?;
+                for detection in &mut detections {
+                    detection.bbox[0] += tile.x as f32;
+                    detection.bbox[1] += tile.y as f32;
+                }
+                raw_detections.extend(detections);
+            }
+            timings.postprocess_ms += DetectionTimings::since(stage_start);
         }
         
-        let inter_area = (inter_x_max - inter_x_min) * (inter_y_max - inter_y_min);
-        let box1_area = box1[2] * box1[3];
-        let box2_area = box2[2] * box2[3];
-        let union_area = box1_area + box2_area - inter_area;
+        let stage_start = std::time::Instant::now();
+        let detections = profiling::stage_sync("postprocess", || self.suppress(raw_detections, original_size));
+        timings.postprocess_ms += DetectionTimings::since(stage_start);
+        self.stats.write().total_postprocess_time_ms += timings.postprocess_ms as u64;
+        tracing::debug!("切片检测 {}x{}: {} 块", original_size.0, original_size.1, tiles.len());
         
-        if union_area <= 0.0 {
-            0.0
-        } else {
-            inter_area / union_area
-        }
+        let total_ms = DetectionTimings::since(total_start_time);
+        Ok(self.record_result(detections, original_size, input_size, timings, total_ms))
     }
     
-    /// 主要的图像检测接口
-    pub async fn detect_image(&mut self, image_data: &[u8]) -> Result<DetectionResult> {
+    /// 测试时增强检测：原图与翻转图按各尺度推理（同尺度的合为一批），结果经加权框融合后再过滤检测区域并做NMS；
+    /// 始终检测整幅图像，不叠加切片
+    pub async fn detect_image_tta(&self, image_data: &[u8]) -> Result<DetectionResult> {
         let total_start_time = std::time::Instant::now();
         
-        if self.model.is_none() {
-            return Err(anyhow!("模型未初始化，请先调用 init_model()"));
+        self.ensure_task(ModelTask::Detect)?;
+        
+        let config = self.tta.read().clone();
+        let input_size = *self.input_size.read();
+        let resize = *self.resize.read();
+        let mut timings = DetectionTimings::default();
+        
+        let stage_start = std::time::Instant::now();
+        let img = decode::to_rgb8(decode::decode(image_data)?, *self.alpha_background.read());
+        timings.decode_ms = DetectionTimings::since(stage_start);
+        let original_size = img.dimensions();
+        let augmentations = tta::plan(&config, input_size, self.model_shape.input_size.is_some());
+        
+        let max_batch = self.max_batch_size().max(1);
+        let mut passes = Vec::with_capacity(augmentations.len());
+        for group in augmentations.chunk_by(|a, b| a.input_size == b.input_size) {
+            for chunk in group.chunks(max_batch) {
+                let size = chunk[0].input_size;
+                let stage_start = std::time::Instant::now();
+                let frames: Vec<RgbImage> = chunk
+                    .iter()
+                    .map(|augmentation| if augmentation.flip { image::imageops::flip_horizontal(&img) } else { img.clone() })
+                    .collect();
+                let input = profiling::stage_sync("preprocess", || self.batch_input(&frames, size, &resize))?;
+                timings.preprocess_ms += DetectionTimings::since(stage_start);
+                
+                let stage_start = std::time::Instant::now();
+                let output = profiling::stage("inference", self.inference(&input)).await?;
+                timings.inference_ms += DetectionTimings::since(stage_start);
+                
+                let stage_start = std::time::Instant::now();
+                for (augmentation, single) in chunk.iter().zip(split_batch(&output, chunk.len())?) {
+                    let mut detections = self.parse_detections(&single, original_size, size, resize.strategy)?;
+                    if augmentation.flip {
+                        for detection in &mut detections {
+                            tta::unflip(&mut detection.bbox, original_size.0);
+                        }
+                    }
+                    passes.push(detections);
+                }
+                timings.postprocess_ms += DetectionTimings::since(stage_start);
+            }
         }
         
-        // 1. 图像预处理
-        let (input_tensor, original_size) = self.preprocess_image(image_data).await?;
+        let stage_start = std::time::Instant::now();
+        let class_agnostic = self.nms_config.read().class_agnostic;
+        let detections = profiling::stage_sync("postprocess", || {
+            let fused = tta::weighted_box_fusion(passes, config.fusion_iou, class_agnostic);
+            self.suppress(fused, original_size)
+        });
+        timings.postprocess_ms += DetectionTimings::since(stage_start);
+        self.stats.write().total_postprocess_time_ms += timings.postprocess_ms as u64;
+        tracing::debug!("TTA检测: {} 次推理", augmentations.len());
+        
+        let total_ms = DetectionTimings::since(total_start_time);
+        Ok(self.record_result(detections, original_size, input_size, timings, total_ms))
+    }
+    
+    /// 整图分类：解码与缩放与检测相同（分类模型通常配合中心裁剪的缩放策略），
+    /// 返回概率最高的 top_k 个类别；不受置信度阈值、启用类别与检测区域的影响
+    pub async fn classify_image(&self, image_data: &[u8], top_k: usize) -> Result<ClassificationResult> {
+        let total_start_time = std::time::Instant::now();
         
-        // 2. 模型推理
-        let output_tensor = self.inference(&input_tensor).await?;
+        self.ensure_task(ModelTask::Classify)?;
         
-        // 3. 后处理
-        let detections = self.postprocess(&output_tensor, original_size).await?;
+        let input_size = *self.input_size.read();
+        let resize = *self.resize.read();
+        let mut timings = DetectionTimings::default();
+        let stage_start = std::time::Instant::now();
+        let (input_tensor, original_size) =
+            profiling::stage("preprocess", self.preprocess_image(image_data, input_size, &resize, &mut timings)).await?;
+        timings.preprocess_ms = (DetectionTimings::since(stage_start) - timings.decode_ms).max(0.0);
+        
+        let stage_start = std::time::Instant::now();
+        let output_tensor = profiling::stage("inference", self.inference(&input_tensor)).await?;
+        timings.inference_ms = DetectionTimings::since(stage_start);
+        
+        let stage_start = std::time::Instant::now();
+        let scores = read_tensor_into_pool(&output_tensor.get(0)?)?;
+        let mut predictions: Vec<ClassPrediction> = class_probabilities(&scores)
+            .into_iter()
+            .enumerate()
+            .map(|(class_id, probability)| ClassPrediction {
+                class_id: class_id as u32,
+                class_name: self
+                    .class_names
+                    .get(&(class_id as u32))
+                    .cloned()
+                    .unwrap_or_else(|| format!("class_{}", class_id)),
+                probability,
+            })
+            .collect();
+        predictions.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+        predictions.truncate(top_k.max(1));
+        timings.postprocess_ms = DetectionTimings::since(stage_start);
+        
+        let total_ms = DetectionTimings::since(total_start_time);
+        {
+            let mut stats = self.stats.write();
+            stats.total_inferences += 1;
+            stats.total_postprocess_time_ms += timings.postprocess_ms as u64;
+            if let Some(top) = predictions.first() {
+                stats.class_stats.entry(top.class_name.clone()).or_default().record(top.probability);
+            }
+        }
+        self.rolling.write().record(total_ms);
         
+        Ok(ClassificationResult {
+            predictions,
+            image_width: original_size.0,
+            image_height: original_size.1,
+            processing_time_ms: total_ms as u64,
+            model_input_size: input_size,
+            timings,
+        })
+    }
+    
+    /// 记录统计并组装检测结果
+    fn record_result(
+        &self,
+        detections: Vec<YoloDetection>,
+        original_size: (u32, u32),
+        input_size: (u32, u32),
+        timings: DetectionTimings,
+        total_ms: f64,
+    ) -> DetectionResult {
         // 更新统计信息
-        let total_time = total_start_time.elapsed().as_millis() as u64;
         {
             let mut stats = self.stats.write();
             stats.total_inferences += 1;
-            
-            // 更新平均FPS
-            if total_time > 0 {
-                stats.avg_fps = 1000.0 / total_time as f64;
+            for detection in &detections {
+                stats
+                    .class_stats
+                    .entry(detection.class_name.clone())
+                    .or_default()
+                    .record(detection.confidence);
             }
         }
+        self.rolling.write().record(total_ms);
         
-        Ok(DetectionResult {
+        DetectionResult {
             detections,
             image_width: original_size.0,
             image_height: original_size.1,
-            processing_time_ms: total_time,
-            model_input_size: self.input_size,
-        })
+            processing_time_ms: total_ms as u64,
+            model_input_size: input_size,
+            thresholds: self.confidence_thresholds.read().clone(),
+            track_ids: Vec::new(),
+            timings,
+            frame: FrameInfo::next(),
+        }
     }
     
     /// 更新置信度阈值
     pub async fn update_confidence_threshold(&self, class_name: &str, threshold: f32) -> Result<()> {
         let mut thresholds = self.confidence_thresholds.write();
         thresholds.insert(class_name.to_string(), threshold.clamp(0.0, 1.0));
-        println!("⚙️ 更新 {} 的置信度阈值为: {:.2}", class_name, threshold);
+        tracing::info!("⚙️ 更新 {} 的置信度阈值为: {:.2}", class_name, threshold);
         Ok(())
     }
     
@@ -756,7 +1245,7 @@ impl CandleYoloDetector {
         let mut enabled = self.enabled_classes.write();
         *enabled = valid_ids.clone();
         
-        println!("⚙️ 启用的类别: {:?}", valid_ids);
+        tracing::info!("⚙️ 启用的类别: {:?}", valid_ids);
         Ok(())
     }
     
@@ -767,25 +1256,71 @@ impl CandleYoloDetector {
     
     /// 获取性能统计
     pub async fn get_stats(&self) -> ModelStats {
-        self.stats.read().clone()
+        let mut stats = self.stats.read().clone();
+        self.rolling.read().fill(&mut stats);
+        stats
     }
     
     /// 重置统计信息
     pub async fn reset_stats(&self) {
         let mut stats = self.stats.write();
         *stats = ModelStats::default();
+        self.rolling.write().clear();
+    }
+    
+    /// 最近 limit 次检测的耗时曲线
+    pub async fn stats_timeseries(&self, limit: usize) -> StatsTimeseries {
+        self.rolling.read().timeseries(limit)
+    }
+    
+    /// 预处理缓存与缩放画布占用的内存（字节）
+    pub async fn get_memory_usage(&self) -> u64 {
+        let canvas_bytes = self.resize_canvas.lock().as_raw().capacity() as u64;
+        let cache = self.preprocessing_cache.lock().await;
+        let cache_bytes = cache
+            .as_ref()
+            .map_or(0, |(_, tensor, _)| (tensor.elem_count() * tensor.dtype().size_in_bytes()) as u64);
+        cache_bytes + canvas_bytes
+    }
+    
+    /// 清空预处理缓存与画布（内存紧张时调用），返回是否释放了缓存
+    pub async fn clear_cache(&self) -> bool {
+        let canvas_freed = {
+            let mut canvas = self.resize_canvas.lock();
+            let freed = !canvas.as_raw().is_empty();
+            *canvas = RgbImage::new(0, 0);
+            freed
+        };
+        self.preprocessing_cache.lock().await.take().is_some() || canvas_freed
     }
     
     /// 获取模型信息
     pub fn get_model_info(&self) -> HashMap<String, String> {
         let mut info = HashMap::new();
+        info.insert("backend".to_string(), InferenceBackend::Candle.as_str().to_string());
         info.insert("model_path".to_string(), self.model_path.clone());
-        info.insert("device".to_string(), format!("{:?}", self.device));
-        info.insert("input_size".to_string(), format!("{:?}", self.input_size));
+        info.insert("device".to_string(), device::describe(&self.device));
+        info.insert("device_requested".to_string(), self.device_spec.to_string());
+        if let Some(reason) = &self.device_fallback {
+            info.insert("device_fallback".to_string(), reason.clone());
+        }
+        info.insert("available_devices".to_string(), device::compiled_backends().join(","));
+        info.insert("precision".to_string(), if self.half_active { "fp16" } else { "fp32" }.to_string());
+        if let Some(reason) = &self.precision_fallback {
+            info.insert("precision_fallback".to_string(), reason.clone());
+        }
+        info.insert("input_size".to_string(), format!("{:?}", *self.input_size.read()));
+        if self.model.is_some() {
+            info.insert("task".to_string(), self.model_shape.task.as_str().to_string());
+            info.insert("input_shape".to_string(), self.model_shape.describe_input());
+            info.insert("input_dynamic".to_string(), self.model_shape.input_size.is_none().to_string());
+        }
         info.insert("num_classes".to_string(), self.class_names.len().to_string());
+        info.insert("class_names_source".to_string(), self.class_names_source.clone());
         info.insert("model_loaded".to_string(), self.model.is_some().to_string());
         
-        let stats = self.stats.read();
+        let mut stats = self.stats.read().clone();
+        self.rolling.read().fill(&mut stats);
         if stats.total_inferences > 0 {
             info.insert("total_inferences".to_string(), stats.total_inferences.to_string());
             info.insert("avg_fps".to_string(), format!("{:.1}", stats.avg_fps));
@@ -797,6 +1332,11 @@ impl CandleYoloDetector {
                 }));
         }
         
+        let pool = tensor_pool::global().stats();
+        info.insert("tensor_pool_allocations".to_string(), pool.allocations.to_string());
+        info.insert("tensor_pool_reuses".to_string(), pool.reuses.to_string());
+        info.insert("tensor_pool_bytes".to_string(), pool.pooled_bytes.to_string());
+        
         info
     }
 }
@@ -807,34 +1347,244 @@ impl Default for CandleYoloDetector {
     }
 }
 
-// MD5哈希工具
-mod md5 {
-    use std::fmt;
-    
-    pub struct Digest([u8; 16]);
-    
-    impl fmt::LowerHex for Digest {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            for &byte in &self.0 {
-                write!(f, "{:02x}", byte)?;
-            }
-            Ok(())
-        }
+#[async_trait]
+impl Detector for CandleYoloDetector {
+    fn backend(&self) -> InferenceBackend {
+        InferenceBackend::Candle
     }
-    
-    pub fn compute(data: &[u8]) -> Digest {
-        // 简化的哈希实现，生产环境建议使用专业的MD5库
-        let mut hash = [0u8; 16];
-        let len = data.len();
-        for (i, &byte) in data.iter().enumerate() {
-            hash[i % 16] ^= byte.wrapping_add(i as u8);
-        }
-        // 添加长度影响
-        for (i, &byte) in len.to_le_bytes().iter().enumerate() {
-            if i < 16 {
-                hash[i] = hash[i].wrapping_add(byte);
-            }
-        }
-        Digest(hash)
+
+    async fn init_model(&mut self, model_path: &str) -> Result<()> {
+        CandleYoloDetector::init_model(self, model_path).await
+    }
+
+    async fn detect_image(&self, image_data: &[u8]) -> Result<DetectionResult> {
+        CandleYoloDetector::detect_image(self, image_data).await
+    }
+
+    async fn detect_batch(&self, images: &[Vec<u8>]) -> Vec<Result<DetectionResult>> {
+        CandleYoloDetector::detect_batch(self, images).await
+    }
+
+    fn max_batch_size(&self) -> usize {
+        CandleYoloDetector::max_batch_size(self)
+    }
+
+    async fn update_confidence_threshold(&self, class_name: &str, threshold: f32) -> Result<()> {
+        CandleYoloDetector::update_confidence_threshold(self, class_name, threshold).await
+    }
+
+    async fn set_enabled_classes(&self, class_ids: Vec<u32>) -> Result<()> {
+        CandleYoloDetector::set_enabled_classes(self, class_ids).await
+    }
+
+    fn get_class_names(&self) -> &HashMap<u32, String> {
+        CandleYoloDetector::get_class_names(self)
+    }
+
+    async fn get_stats(&self) -> ModelStats {
+        CandleYoloDetector::get_stats(self).await
+    }
+
+    async fn reset_stats(&self) {
+        CandleYoloDetector::reset_stats(self).await
+    }
+
+    async fn stats_timeseries(&self, limit: usize) -> StatsTimeseries {
+        CandleYoloDetector::stats_timeseries(self, limit).await
+    }
+
+    fn get_model_info(&self) -> HashMap<String, String> {
+        CandleYoloDetector::get_model_info(self)
+    }
+
+    async fn get_memory_usage(&self) -> u64 {
+        CandleYoloDetector::get_memory_usage(self).await
+    }
+
+    async fn clear_cache(&self) -> bool {
+        CandleYoloDetector::clear_cache(self).await
+    }
+
+    async fn select_device(&mut self, spec: DeviceSpec) -> Result<String> {
+        CandleYoloDetector::select_device(self, spec).await
+    }
+
+    async fn set_half_precision(&mut self, enabled: bool) -> Result<bool> {
+        CandleYoloDetector::set_half_precision(self, enabled).await
+    }
+
+    fn is_half_precision(&self) -> bool {
+        CandleYoloDetector::is_half_precision(self)
     }
-}
\ No newline at end of file
+
+    async fn set_nms_config(&self, config: NmsConfig) -> Result<()> {
+        CandleYoloDetector::set_nms_config(self, config)
+    }
+
+    async fn set_roi(&self, regions: Vec<RoiPolygon>) -> Result<()> {
+        CandleYoloDetector::set_roi(self, regions)
+    }
+
+    async fn set_tiling_config(&self, config: TilingConfig) -> Result<()> {
+        CandleYoloDetector::set_tiling_config(self, config)
+    }
+
+    async fn set_resize_config(&self, config: ResizeConfig) -> Result<()> {
+        CandleYoloDetector::set_resize_config(self, config)
+    }
+
+    async fn set_tta_config(&self, config: TtaConfig) -> Result<()> {
+        CandleYoloDetector::set_tta_config(self, config)
+    }
+
+    async fn detect_image_tta(&self, image_data: &[u8]) -> Result<DetectionResult> {
+        CandleYoloDetector::detect_image_tta(self, image_data).await
+    }
+
+    async fn classify_image(&self, image_data: &[u8], top_k: usize) -> Result<ClassificationResult> {
+        CandleYoloDetector::classify_image(self, image_data, top_k).await
+    }
+
+    async fn set_alpha_background(&self, color: [u8; 3]) -> Result<()> {
+        CandleYoloDetector::set_alpha_background(self, color).await
+    }
+
+    async fn set_input_size(&self, size: (u32, u32)) -> Result<()> {
+        CandleYoloDetector::set_input_size(self, size).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yolo::preprocessing::Interpolation;
+
+    fn encode_png(image: image::DynamicImage) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        image
+            .write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+            .unwrap();
+        buffer
+    }
+
+    fn solid_png(color: [u8; 3]) -> Vec<u8> {
+        encode_png(image::DynamicImage::ImageRgb8(RgbImage::from_pixel(4, 4, image::Rgb(color))))
+    }
+
+    fn tensor_values(tensor: &Tensor) -> Vec<f32> {
+        tensor.flatten_all().unwrap().to_vec1::<f32>().unwrap()
+    }
+
+    #[test]
+    fn cache_key_separates_inputs_the_old_xor_hash_collided_on() {
+        let resize = ResizeConfig::default();
+        let key = |data: &[u8]| preprocess_cache_key(data, (640, 640), &resize);
+
+        // 旧的按位置异或哈希：第 0 与第 256 字节落在同一槽位且偏移相同，交换后哈希不变
+        let mut a = vec![0u8; 257];
+        a[0] = 1;
+        a[256] = 2;
+        let mut b = a.clone();
+        b.swap(0, 256);
+        assert_ne!(key(&a), key(&b));
+
+        // 同一槽位的字节（加上偏移后）异或结果相同即碰撞：0x0f ^ (0x00 + 16) == 0x1f ^ (0xf0 + 16)
+        let mut c = vec![0u8; 32];
+        c[0] = 0x0f;
+        let mut d = vec![0u8; 32];
+        d[0] = 0x1f;
+        d[16] = 0xf0;
+        assert_ne!(key(&c), key(&d));
+
+        assert_eq!(key(&a), key(&a.clone()));
+    }
+
+    #[test]
+    fn cache_key_includes_input_size_and_resize() {
+        let data = solid_png([10, 20, 30]);
+        let base = preprocess_cache_key(&data, (640, 640), &ResizeConfig::default());
+        assert_ne!(base, preprocess_cache_key(&data, (320, 320), &ResizeConfig::default()));
+        assert_ne!(base, preprocess_cache_key(&data, (640, 320), &ResizeConfig::default()));
+        let stretch = ResizeConfig {
+            strategy: ResizeStrategy::Stretch,
+            ..ResizeConfig::default()
+        };
+        assert_ne!(base, preprocess_cache_key(&data, (640, 640), &stretch));
+        let nearest = ResizeConfig {
+            interpolation: Interpolation::Nearest,
+            ..ResizeConfig::default()
+        };
+        assert_ne!(base, preprocess_cache_key(&data, (640, 640), &nearest));
+    }
+
+    #[tokio::test]
+    async fn cached_tensor_is_only_reused_for_identical_input() {
+        let detector = CandleYoloDetector::new();
+        let resize = ResizeConfig::default();
+        let red = solid_png([255, 0, 0]);
+        let blue = solid_png([0, 0, 255]);
+        let mut timings = DetectionTimings::default();
+
+        let (first, size) = detector.preprocess_image(&red, (32, 32), &resize, &mut timings).await.unwrap();
+        assert_eq!(size, (4, 4));
+        let (other, _) = detector.preprocess_image(&blue, (32, 32), &resize, &mut timings).await.unwrap();
+        assert_ne!(tensor_values(&first), tensor_values(&other));
+        assert_eq!(detector.stats.read().cache_hits, 0);
+
+        let (again, size) = detector.preprocess_image(&blue, (32, 32), &resize, &mut timings).await.unwrap();
+        assert_eq!(size, (4, 4));
+        assert_eq!(tensor_values(&again), tensor_values(&other));
+        assert_eq!(detector.stats.read().cache_hits, 1);
+
+        let (resized, _) = detector.preprocess_image(&blue, (64, 64), &resize, &mut timings).await.unwrap();
+        assert_eq!(resized.dims(), &[1, 3, 64, 64]);
+        assert_eq!(detector.stats.read().cache_hits, 1);
+    }
+
+    #[tokio::test]
+    async fn rgba_input_matches_pre_flattened_rgb() {
+        let detector = CandleYoloDetector::new();
+        let background = [0, 0, 255];
+        detector.set_alpha_background(background).await.unwrap();
+        let resize = ResizeConfig::default();
+        let mut timings = DetectionTimings::default();
+
+        let rgba = image::RgbaImage::from_fn(8, 8, |x, y| {
+            image::Rgba([(x * 30) as u8, (y * 30) as u8, 90, [0, 64, 128, 255][(x % 4) as usize]])
+        });
+        // 按 color * alpha + background * (1 - alpha) 预先混合到背景色上
+        let flattened = RgbImage::from_fn(8, 8, |x, y| {
+            let pixel = rgba.get_pixel(x, y).0;
+            let alpha = pixel[3] as u32;
+            image::Rgb(std::array::from_fn(|c| {
+                ((pixel[c] as u32 * alpha + background[c] as u32 * (255 - alpha) + 127) / 255) as u8
+            }))
+        });
+
+        let rgba_png = encode_png(image::DynamicImage::ImageRgba8(rgba));
+        let rgb_png = encode_png(image::DynamicImage::ImageRgb8(flattened));
+        let (from_rgba, _) = detector.preprocess_image(&rgba_png, (32, 32), &resize, &mut timings).await.unwrap();
+        let (from_rgb, _) = detector.preprocess_image(&rgb_png, (32, 32), &resize, &mut timings).await.unwrap();
+        assert_eq!(tensor_values(&from_rgba), tensor_values(&from_rgb));
+    }
+
+    #[tokio::test]
+    async fn grayscale_input_matches_expanded_rgb() {
+        let detector = CandleYoloDetector::new();
+        let resize = ResizeConfig::default();
+        let mut timings = DetectionTimings::default();
+
+        let gray = image::GrayImage::from_fn(8, 8, |x, y| image::Luma([(x * 16 + y * 8) as u8]));
+        let expanded = RgbImage::from_fn(8, 8, |x, y| {
+            let value = gray.get_pixel(x, y).0[0];
+            image::Rgb([value, value, value])
+        });
+
+        let gray_png = encode_png(image::DynamicImage::ImageLuma8(gray));
+        let rgb_png = encode_png(image::DynamicImage::ImageRgb8(expanded));
+        let (from_gray, _) = detector.preprocess_image(&gray_png, (32, 32), &resize, &mut timings).await.unwrap();
+        let (from_rgb, _) = detector.preprocess_image(&rgb_png, (32, 32), &resize, &mut timings).await.unwrap();
+        assert_eq!(from_gray.dims(), &[1, 3, 32, 32]);
+        assert_eq!(tensor_values(&from_gray), tensor_values(&from_rgb));
+    }
+}
@@ -0,0 +1,287 @@
+/*!
+图像解码公共函数
+所有输入（本地文件、上传、批量与文件夹监控）经此解码为 8 位图像后再进入预处理：
+
+- 常规格式由 `image` 库解码，可读格式以运行时编译进来的解码器为准（`supported_extensions`），文件校验据此判断
+- 动画 WebP / GIF 取第一帧
+- 16 位及浮点图像（工业相机常见的 16 位 TIFF、PNG）按像素值的 0.5%–99.5% 分位数线性拉伸到 8 位，
+  避免 12 位有效数据直接截断高字节后整幅发暗
+- HEIC / AVIF 需要系统库，分别由 `heic-support`（libheif）与 `avif-support`（dav1d）特性启用
+
+解码结果再由 `to_rgb8` 统一为模型输入的 RGB：灰度图按亮度复制到三个通道，
+带透明通道的图像按 alpha 混合到背景色上（默认白色，可通过检测配置的 `alpha_background` 修改），
+而不是直接丢弃透明通道——透明区域下的像素值往往是任意的
+*/
+
+use std::io::Cursor;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use image::{AnimationDecoder, DynamicImage, ImageFormat, RgbImage, RgbaImage};
+use rayon::prelude::*;
+
+/// 透明像素混合的默认背景色（白色）
+pub const DEFAULT_ALPHA_BACKGROUND: [u8; 3] = [255, 255, 255];
+
+/// 色调映射时裁掉的暗部/亮部比例
+const TONE_MAP_CLIP: f64 = 0.005;
+
+/// HEIC/HEIF 文件的扩展名
+#[cfg(feature = "heic-support")]
+const HEIC_EXTENSIONS: &[&str] = &["heic", "heif"];
+
+/// 当前可解码的文件扩展名（小写）
+pub fn supported_extensions() -> Vec<&'static str> {
+    let mut extensions: Vec<&'static str> = ImageFormat::all()
+        .filter(|format| format.reading_enabled())
+        .flat_map(|format| format.extensions_str().iter().copied())
+        .collect();
+    #[cfg(feature = "heic-support")]
+    extensions.extend_from_slice(HEIC_EXTENSIONS);
+    extensions.sort_unstable();
+    extensions.dedup();
+    extensions
+}
+
+/// 文件扩展名是否属于可解码的格式
+pub fn is_supported_extension(extension: &str) -> bool {
+    let extension = extension.to_lowercase();
+    supported_extensions().contains(&extension.as_str())
+}
+
+/// 路径是否为可解码的图片文件（按扩展名判断）
+pub fn is_supported_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(is_supported_extension)
+}
+
+/// 解码编码后的图像数据，高位深图像色调映射到 8 位
+pub fn decode(data: &[u8]) -> Result<DynamicImage> {
+    if is_heif(data) {
+        return decode_heif(data);
+    }
+    let format = image::guess_format(data).map_err(|e| anyhow!("无法识别图片格式: {}", e))?;
+    if !format.reading_enabled() {
+        return Err(anyhow!("当前版本未启用 {:?} 格式的解码", format));
+    }
+    let img = match format {
+        ImageFormat::WebP => first_webp_frame(data)?,
+        _ => image::load_from_memory_with_format(data, format)?,
+    };
+    Ok(to_8bit(img))
+}
+
+/// 只读取文件头获取图像尺寸（无法识别时返回空）
+pub fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    image::ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+/// 读取并解码图片文件
+pub fn open(path: &Path) -> Result<DynamicImage> {
+    decode(&std::fs::read(path)?)
+}
+
+/// 动画 WebP 取第一帧，静态 WebP 直接解码
+fn first_webp_frame(data: &[u8]) -> Result<DynamicImage> {
+    let decoder = image::codecs::webp::WebPDecoder::new(Cursor::new(data))?;
+    if !decoder.has_animation() {
+        return Ok(DynamicImage::from_decoder(decoder)?);
+    }
+    let frame = decoder
+        .into_frames()
+        .next()
+        .ok_or_else(|| anyhow!("动画WebP不包含任何帧"))??;
+    Ok(DynamicImage::ImageRgba8(frame.into_buffer()))
+}
+
+/// 8 位图像原样返回；16 位与浮点图像按分位数线性拉伸到 8 位（保留透明通道）
+pub fn to_8bit(img: DynamicImage) -> DynamicImage {
+    match img {
+        DynamicImage::ImageLuma16(_) | DynamicImage::ImageRgb16(_) => {
+            let rgb = img.to_rgb16();
+            let (low, high) = percentile_range(rgb.as_raw().iter().map(|&v| v as f64));
+            let data = rgb.as_raw().iter().map(|&v| stretch(v as f64, low, high)).collect();
+            RgbImage::from_raw(rgb.width(), rgb.height(), data).map_or(img, DynamicImage::ImageRgb8)
+        }
+        DynamicImage::ImageLumaA16(_) | DynamicImage::ImageRgba16(_) => {
+            let rgba = img.to_rgba16();
+            let (low, high) = percentile_range(color_channels(rgba.as_raw()).map(|v| v as f64));
+            let data = rgba
+                .as_raw()
+                .chunks_exact(4)
+                .flat_map(|p| {
+                    [
+                        stretch(p[0] as f64, low, high),
+                        stretch(p[1] as f64, low, high),
+                        stretch(p[2] as f64, low, high),
+                        (p[3] >> 8) as u8,
+                    ]
+                })
+                .collect();
+            RgbaImage::from_raw(rgba.width(), rgba.height(), data).map_or(img, DynamicImage::ImageRgba8)
+        }
+        DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => {
+            let rgb = img.to_rgb32f();
+            let (low, high) = percentile_range(rgb.as_raw().iter().map(|&v| v as f64));
+            let data = rgb.as_raw().iter().map(|&v| stretch(v as f64, low, high)).collect();
+            RgbImage::from_raw(rgb.width(), rgb.height(), data).map_or(img, DynamicImage::ImageRgb8)
+        }
+        img => img,
+    }
+}
+
+/// 转换为模型输入的 RGB 图像：灰度按亮度复制到三个通道，
+/// 带透明通道时按 `out = color * alpha + background * (1 - alpha)` 混合到背景色上
+pub fn to_rgb8(img: DynamicImage, background: [u8; 3]) -> RgbImage {
+    if !img.color().has_alpha() {
+        return img.into_rgb8();
+    }
+    let rgba = img.into_rgba8();
+    let mut out = RgbImage::new(rgba.width(), rgba.height());
+    let background = background.map(u32::from);
+    out.par_chunks_exact_mut(3)
+        .zip(rgba.as_raw().par_chunks_exact(4))
+        .for_each(|(dst, src)| {
+            let alpha = src[3] as u32;
+            for channel in 0..3 {
+                let blended = src[channel] as u32 * alpha + background[channel] * (255 - alpha);
+                dst[channel] = ((blended + 127) / 255) as u8;
+            }
+        });
+    out
+}
+
+/// RGBA 数据中的颜色通道（跳过透明通道）
+fn color_channels(raw: &[u16]) -> impl Iterator<Item = u16> + '_ {
+    raw.chunks_exact(4).flat_map(|p| [p[0], p[1], p[2]])
+}
+
+/// 取 [TONE_MAP_CLIP, 1 - TONE_MAP_CLIP] 分位数作为映射范围
+fn percentile_range(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let mut samples: Vec<f64> = values.filter(|v| v.is_finite()).collect();
+    if samples.is_empty() {
+        return (0.0, 1.0);
+    }
+    let last = samples.len() - 1;
+    let low_index = (last as f64 * TONE_MAP_CLIP) as usize;
+    let high_index = (last as f64 * (1.0 - TONE_MAP_CLIP)) as usize;
+    let (_, low, _) = samples.select_nth_unstable_by(low_index, f64::total_cmp);
+    let low = *low;
+    let (_, high, _) = samples.select_nth_unstable_by(high_index, f64::total_cmp);
+    let high = *high;
+    if high > low {
+        (low, high)
+    } else {
+        (low, low + 1.0)
+    }
+}
+
+fn stretch(value: f64, low: f64, high: f64) -> u8 {
+    (((value - low) / (high - low)).clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// 按 ISO BMFF 的 ftyp 品牌识别 HEIC/HEIF（AVIF 由 `image` 库处理）
+fn is_heif(data: &[u8]) -> bool {
+    data.len() >= 12
+        && &data[4..8] == b"ftyp"
+        && matches!(&data[8..12], b"heic" | b"heix" | b"hevc" | b"hevx" | b"heim" | b"heis" | b"mif1" | b"msf1")
+}
+
+#[cfg(feature = "heic-support")]
+fn decode_heif(data: &[u8]) -> Result<DynamicImage> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let context = HeifContext::read_from_bytes(data).map_err(|e| anyhow!("HEIC解析失败: {}", e))?;
+    let handle = context.primary_image_handle().map_err(|e| anyhow!("HEIC解析失败: {}", e))?;
+    let decoded = LibHeif::new()
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)
+        .map_err(|e| anyhow!("HEIC解码失败: {}", e))?;
+    let plane = decoded
+        .planes()
+        .interleaved
+        .ok_or_else(|| anyhow!("HEIC解码结果缺少像素数据"))?;
+    let row_len = plane.width as usize * 3;
+    let mut pixels = Vec::with_capacity(row_len * plane.height as usize);
+    for row in plane.data.chunks(plane.stride).take(plane.height as usize) {
+        pixels.extend_from_slice(&row[..row_len]);
+    }
+    RgbImage::from_raw(plane.width, plane.height, pixels)
+        .map(DynamicImage::ImageRgb8)
+        .ok_or_else(|| anyhow!("HEIC像素数据不完整"))
+}
+
+#[cfg(not(feature = "heic-support"))]
+fn decode_heif(_data: &[u8]) -> Result<DynamicImage> {
+    Err(anyhow!("当前版本未启用HEIC解码（需以 heic-support 特性编译）"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayAlphaImage, GrayImage, LumaA, Rgba};
+
+    fn encode_png(img: &DynamicImage) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        img.write_to(&mut Cursor::new(&mut buffer), ImageFormat::Png).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn rgba_is_composited_over_background() {
+        let rgba = RgbaImage::from_fn(4, 1, |x, _| match x {
+            0 => Rgba([255, 0, 0, 255]),   // 不透明
+            1 => Rgba([10, 20, 30, 0]),    // 全透明，取背景色
+            2 => Rgba([200, 100, 0, 128]), // 半透明
+            _ => Rgba([255, 0, 0, 64]),
+        });
+        let white = to_rgb8(DynamicImage::ImageRgba8(rgba.clone()), DEFAULT_ALPHA_BACKGROUND);
+        assert_eq!(white.get_pixel(0, 0).0, [255, 0, 0]);
+        assert_eq!(white.get_pixel(1, 0).0, [255, 255, 255]);
+        assert_eq!(white.get_pixel(2, 0).0, [227, 177, 127]);
+
+        let blue = to_rgb8(DynamicImage::ImageRgba8(rgba), [0, 0, 255]);
+        assert_eq!(blue.get_pixel(0, 0).0, [255, 0, 0]);
+        assert_eq!(blue.get_pixel(1, 0).0, [0, 0, 255]);
+        assert_eq!(blue.get_pixel(3, 0).0, [64, 0, 191]);
+    }
+
+    #[test]
+    fn luma_is_expanded_to_three_channels() {
+        let gray = GrayImage::from_fn(2, 1, |x, _| image::Luma([if x == 0 { 0 } else { 77 }]));
+        let rgb = to_rgb8(DynamicImage::ImageLuma8(gray), [10, 20, 30]);
+        assert_eq!(rgb.get_pixel(0, 0).0, [0, 0, 0]);
+        assert_eq!(rgb.get_pixel(1, 0).0, [77, 77, 77]);
+    }
+
+    #[test]
+    fn luma_alpha_is_expanded_then_composited() {
+        let gray = GrayAlphaImage::from_fn(3, 1, |x, _| match x {
+            0 => LumaA([100, 255]),
+            1 => LumaA([100, 0]),
+            _ => LumaA([200, 128]),
+        });
+        let rgb = to_rgb8(DynamicImage::ImageLumaA8(gray), [10, 20, 30]);
+        assert_eq!(rgb.get_pixel(0, 0).0, [100, 100, 100]);
+        assert_eq!(rgb.get_pixel(1, 0).0, [10, 20, 30]);
+
+        let black = to_rgb8(DynamicImage::ImageLumaA8(GrayAlphaImage::from_pixel(1, 1, LumaA([200, 128]))), [0, 0, 0]);
+        assert_eq!(black.get_pixel(0, 0).0, [100, 100, 100]);
+    }
+
+    #[test]
+    fn decoded_png_keeps_alpha_until_composited() {
+        let rgba = RgbaImage::from_fn(2, 1, |x, _| if x == 0 { Rgba([0, 255, 0, 255]) } else { Rgba([0, 255, 0, 0]) });
+        let decoded = decode(&encode_png(&DynamicImage::ImageRgba8(rgba))).unwrap();
+        assert!(decoded.color().has_alpha());
+        assert_eq!(dimensions(&encode_png(&decoded)), Some((2, 1)));
+
+        let rgb = to_rgb8(decoded, [40, 50, 60]);
+        assert_eq!(rgb.get_pixel(0, 0).0, [0, 255, 0]);
+        assert_eq!(rgb.get_pixel(1, 0).0, [40, 50, 60]);
+    }
+}
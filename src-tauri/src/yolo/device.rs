@@ -0,0 +1,175 @@
+/*!
+推理设备选择
+支持 `cpu`、`cuda:N`、`metal`，所选设备不可用（未启用对应cargo特性或没有驱动）时回退到CPU
+*/
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use candle_core::{DType, Device, DeviceLocation, Tensor};
+use candle_onnx::onnx::{tensor_proto::DataType, type_proto, ModelProto, TensorProto};
+use serde::{Deserialize, Serialize};
+
+use crate::error::DetectionError;
+
+/// 请求的推理设备
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeviceSpec {
+    Cpu,
+    Cuda(usize),
+    Metal,
+}
+
+impl DeviceSpec {
+    /// 解析设备字符串：`cpu`、`cuda`、`cuda:N`、`metal`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim().to_ascii_lowercase();
+        match spec.as_str() {
+            "cpu" => Ok(DeviceSpec::Cpu),
+            "metal" => Ok(DeviceSpec::Metal),
+            "cuda" => Ok(DeviceSpec::Cuda(0)),
+            _ => match spec.strip_prefix("cuda:") {
+                Some(index) => index
+                    .parse()
+                    .map(DeviceSpec::Cuda)
+                    .map_err(|_| DetectionError::DeviceError(format!("无效的CUDA设备序号: {}", index)).into()),
+                None => Err(DetectionError::DeviceError(format!("不支持的设备: {}（可选 cpu、cuda:N、metal）", spec)).into()),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for DeviceSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeviceSpec::Cpu => write!(f, "cpu"),
+            DeviceSpec::Cuda(index) => write!(f, "cuda:{}", index),
+            DeviceSpec::Metal => write!(f, "metal"),
+        }
+    }
+}
+
+/// 设备选择结果
+pub struct SelectedDevice {
+    pub device: Device,
+    pub fallback_reason: Option<String>, // 回退到CPU的原因
+}
+
+/// 创建设备并验证可用，不可用时回退到CPU
+pub fn open(spec: DeviceSpec) -> SelectedDevice {
+    let result = match spec {
+        DeviceSpec::Cpu => return SelectedDevice { device: Device::Cpu, fallback_reason: None },
+        DeviceSpec::Cuda(index) => Device::new_cuda(index),
+        DeviceSpec::Metal => Device::new_metal(0),
+    };
+    // 分配一个小张量确认设备确实可用
+    match result.and_then(|device| Tensor::zeros(1, DType::F32, &device).map(|_| device)) {
+        Ok(device) => SelectedDevice { device, fallback_reason: None },
+        Err(e) => {
+            let reason = format!("{} 不可用，已回退到CPU: {}", spec, e);
+            tracing::warn!("⚠️  {}", reason);
+            SelectedDevice { device: Device::Cpu, fallback_reason: Some(reason) }
+        }
+    }
+}
+
+/// 设备的简短名称
+pub fn describe(device: &Device) -> String {
+    match device.location() {
+        DeviceLocation::Cpu => "cpu".to_string(),
+        DeviceLocation::Cuda { gpu_id } => format!("cuda:{}", gpu_id),
+        DeviceLocation::Metal { gpu_id } => format!("metal:{}", gpu_id),
+    }
+}
+
+/// 本构建启用的加速后端
+pub fn compiled_backends() -> Vec<&'static str> {
+    let mut backends = vec!["cpu"];
+    if candle_core::utils::cuda_is_available() {
+        backends.push("cuda");
+    }
+    if candle_core::utils::metal_is_available() {
+        backends.push("metal");
+    }
+    backends
+}
+
+/// 在设备上执行的计算图：权重已上传到设备，推理时作为输入传入
+pub struct DeviceGraph {
+    pub model: ModelProto,
+    pub weights: HashMap<String, Tensor>,
+}
+
+/// 把权重张量解码到指定设备
+fn upload_tensor(proto: &TensorProto, device: &Device) -> Result<Tensor> {
+    let dims: Vec<usize> = proto.dims.iter().map(|&d| d as usize).collect();
+    let data_type = DataType::try_from(proto.data_type)
+        .map_err(|_| anyhow!("权重 {} 的数据类型无效: {}", proto.name, proto.data_type))?;
+    let dtype = candle_onnx::dtype(data_type)
+        .ok_or_else(|| anyhow!("权重 {} 的数据类型暂不支持: {:?}", proto.name, data_type))?;
+
+    let tensor = if !proto.raw_data.is_empty() {
+        Tensor::from_raw_buffer(&proto.raw_data, dtype, &dims, device)?
+    } else {
+        match data_type {
+            DataType::Float => Tensor::from_slice(&proto.float_data, dims.as_slice(), device)?,
+            DataType::Int64 => Tensor::from_slice(&proto.int64_data, dims.as_slice(), device)?,
+            DataType::Double => Tensor::from_slice(&proto.double_data, dims.as_slice(), device)?,
+            DataType::Int32 => {
+                let data: Vec<i64> = proto.int32_data.iter().map(|&v| v as i64).collect();
+                Tensor::from_vec(data, dims.as_slice(), device)?
+            }
+            _ => return Err(anyhow!("权重 {} 的存储格式暂不支持: {:?}", proto.name, data_type)),
+        }
+    };
+    Ok(tensor)
+}
+
+/// 为非CPU设备准备计算图
+///
+/// candle_onnx 会把初始化项与常量节点都解码到CPU，直接与设备上的输入运算会因设备不一致失败。
+/// 这里把常量节点转为初始化项，再把全部初始化项从图中移出并上传到设备，推理时随输入一起传入。
+/// `float_dtype` 为 F16 时浮点权重同时转换为半精度
+pub fn prepare_graph(model: &ModelProto, device: &Device, float_dtype: DType) -> Result<DeviceGraph> {
+    let mut model = model.clone();
+    let graph = model.graph.as_mut().ok_or_else(|| anyhow!("ONNX模型缺少计算图"))?;
+
+    let mut initializers = std::mem::take(&mut graph.initializer);
+    graph.node.retain(|node| {
+        if node.op_type != "Constant" || node.output.len() != 1 {
+            return true;
+        }
+        match node.attribute.iter().find(|attr| attr.name == "value").and_then(|attr| attr.t.as_ref()) {
+            Some(value) => {
+                let mut value = value.clone();
+                value.name = node.output[0].clone();
+                initializers.push(value);
+                false
+            }
+            None => true,
+        }
+    });
+
+    // 半精度时图像输入同样以FP16传入，同步修改输入声明以通过类型检查
+    if float_dtype == DType::F16 {
+        for input in graph.input.iter_mut() {
+            if let Some(type_proto::Value::TensorType(tensor_type)) =
+                input.r#type.as_mut().and_then(|t| t.value.as_mut())
+            {
+                if tensor_type.elem_type == DataType::Float as i32 {
+                    tensor_type.elem_type = DataType::Float16 as i32;
+                }
+            }
+        }
+    }
+
+    let mut weights = HashMap::with_capacity(initializers.len());
+    for proto in &initializers {
+        let mut tensor = upload_tensor(proto, device)?;
+        if tensor.dtype() == DType::F32 && float_dtype != DType::F32 {
+            tensor = tensor.to_dtype(float_dtype)?;
+        }
+        weights.insert(proto.name.clone(), tensor);
+    }
+    Ok(DeviceGraph { model, weights })
+}
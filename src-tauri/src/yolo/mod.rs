@@ -1,20 +1,176 @@
 /*!
 YOLO检测模块
 
-支持基于Candle框架的真实YOLO ONNX检测
+支持基于Candle框架的真实YOLO ONNX检测，
+各检测器实现统一的 `Detector` 接口，可在运行时切换推理后端；
+检测结果等公共数据结构统一定义在 `types` 模块
 */
 
-mod simple;
 mod onnx_detector;
 mod candle_detector;
+pub mod decode;
+pub mod device;
+pub mod model_meta;
+pub mod nms;
+pub mod preprocessing;
+pub mod roi;
+pub mod rolling_stats;
+pub mod tensor_pool;
+pub mod tiling;
+pub mod tta;
+pub mod types;
 
-// 重新导出Candle检测器作为主要实现
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+// 重新导出统一数据结构与Candle检测器（主要实现）
+pub use types::*;
 pub use candle_detector::*;
 
-// 保留ONNX检测器以备兼容
+// 保留ONNX检测器以备兼容（作为模拟推理后端）
 #[allow(unused)]
 pub use onnx_detector::{YoloOnnxDetector};
 
-// 保留简化版本以备兼容
-#[allow(unused)]
-pub use simple::YoloManager;
\ No newline at end of file
+/// 推理后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InferenceBackend {
+    Candle, // Candle ONNX推理
+    Mock,   // 模拟推理（无需真实推理，用于界面调试与演示）
+}
+
+impl InferenceBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InferenceBackend::Candle => "candle",
+            InferenceBackend::Mock => "mock",
+        }
+    }
+}
+
+/// 检测器统一接口
+#[async_trait]
+pub trait Detector: Send + Sync {
+    /// 当前检测器对应的推理后端
+    fn backend(&self) -> InferenceBackend;
+
+    /// 初始化并加载模型
+    async fn init_model(&mut self, model_path: &str) -> Result<()>;
+
+    /// 检测一张图像（编码后的图像数据）
+    async fn detect_image(&self, image_data: &[u8]) -> Result<DetectionResult>;
+
+    /// 测试时增强检测（见 `tta`），不支持的后端按普通检测处理
+    async fn detect_image_tta(&self, image_data: &[u8]) -> Result<DetectionResult> {
+        self.detect_image(image_data).await
+    }
+
+    /// 整图分类（分类模型），返回概率最高的 top_k 个类别
+    async fn classify_image(&self, _image_data: &[u8], _top_k: usize) -> Result<ClassificationResult> {
+        Err(anyhow!("{} 后端不支持图像分类", self.backend().as_str()))
+    }
+
+    /// 检测多张图像，结果与输入一一对应；支持批维度的后端合并为一次推理
+    async fn detect_batch(&self, images: &[Vec<u8>]) -> Vec<Result<DetectionResult>> {
+        let mut results = Vec::with_capacity(images.len());
+        for data in images {
+            results.push(self.detect_image(data).await);
+        }
+        results
+    }
+
+    /// 单次推理可合并的最大图片数
+    fn max_batch_size(&self) -> usize {
+        1
+    }
+
+    /// 更新类别置信度阈值
+    async fn update_confidence_threshold(&self, class_name: &str, threshold: f32) -> Result<()>;
+
+    /// 设置启用的类别
+    async fn set_enabled_classes(&self, class_ids: Vec<u32>) -> Result<()>;
+
+    /// 类别ID到名称的映射
+    fn get_class_names(&self) -> &HashMap<u32, String>;
+
+    /// 性能统计
+    async fn get_stats(&self) -> ModelStats;
+
+    /// 重置统计信息
+    async fn reset_stats(&self);
+
+    /// 最近 limit 次检测的耗时样本与每分钟吞吐量（供界面绘制曲线）
+    async fn stats_timeseries(&self, limit: usize) -> rolling_stats::StatsTimeseries;
+
+    /// 模型信息（键值对形式，供界面展示）
+    fn get_model_info(&self) -> HashMap<String, String>;
+
+    /// 检测器内部缓存占用的内存（字节）
+    async fn get_memory_usage(&self) -> u64 {
+        0
+    }
+
+    /// 清空内部缓存，返回是否释放了缓存
+    async fn clear_cache(&self) -> bool {
+        false
+    }
+
+    /// 选择推理设备，返回实际使用的设备
+    async fn select_device(&mut self, spec: device::DeviceSpec) -> Result<String> {
+        Err(anyhow!("{} 后端不支持选择推理设备: {}", self.backend().as_str(), spec))
+    }
+
+    /// 为当前模型启用/关闭半精度推理，返回是否实际以半精度运行（不支持时回退FP32）
+    async fn set_half_precision(&mut self, _enabled: bool) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// 当前是否以半精度推理
+    fn is_half_precision(&self) -> bool {
+        false
+    }
+
+    /// 设置NMS参数（不做NMS的后端忽略）
+    async fn set_nms_config(&self, _config: nms::NmsConfig) -> Result<()> {
+        Ok(())
+    }
+
+    /// 设置检测区域，区域外的检测框被丢弃（空列表表示整幅画面）
+    async fn set_roi(&self, regions: Vec<roi::RoiPolygon>) -> Result<()>;
+
+    /// 设置大图切片检测参数（不支持切片的后端忽略）
+    async fn set_tiling_config(&self, _config: tiling::TilingConfig) -> Result<()> {
+        Ok(())
+    }
+
+    /// 设置缩放策略与插值方式（见 `preprocessing`，不做预处理的后端忽略）
+    async fn set_resize_config(&self, _config: preprocessing::ResizeConfig) -> Result<()> {
+        Ok(())
+    }
+
+    /// 设置测试时增强参数
+    async fn set_tta_config(&self, _config: tta::TtaConfig) -> Result<()> {
+        Ok(())
+    }
+
+    /// 设置透明像素混合的背景色（见 `decode::to_rgb8`，不解码图像的后端忽略）
+    async fn set_alpha_background(&self, _color: [u8; 3]) -> Result<()> {
+        Ok(())
+    }
+
+    /// 设置模型输入尺寸 (width, height)
+    async fn set_input_size(&self, size: (u32, u32)) -> Result<()> {
+        Err(anyhow!("{} 后端不支持设置输入尺寸: {:?}", self.backend().as_str(), size))
+    }
+}
+
+/// 创建指定后端的检测器
+pub fn create_detector(backend: InferenceBackend) -> Box<dyn Detector> {
+    match backend {
+        InferenceBackend::Candle => Box::new(CandleYoloDetector::new()),
+        InferenceBackend::Mock => Box::new(YoloOnnxDetector::new()),
+    }
+}
@@ -1,577 +0,0 @@
-use anyhow::{Result, anyhow};
-use candle_core::{Device, Tensor};
-use image::{GenericImageView, DynamicImage};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
-use std::sync::Arc;
-use parking_lot::RwLock;
-use tokio::sync::Mutex;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct YoloDetection {
-    pub class_id: u32,
-    pub class_name: String,
-    pub confidence: f32,
-    pub bbox: [f32; 4], // [x, y, width, height]
-}
-
-#[derive(Debug, Default, Clone)]
-pub struct ModelStats {
-    pub total_inferences: u64,
-    pub total_preprocess_time_ms: u64,
-    pub total_inference_time_ms: u64,
-    pub total_postprocess_time_ms: u64,
-    pub cache_hits: u64,
-    pub cache_misses: u64,
-    pub avg_fps: f64,
-    pub memory_usage_mb: u64,
-}
-
-pub struct CandleYoloModel {
-    device: Device,
-    model_path: String,
-    class_names: HashMap<u32, String>,
-    input_size: (usize, usize),
-    // 性能优化：预分配内存池
-    tensor_buffer: Arc<Mutex<Vec<f32>>>,
-    // 图像处理缓存
-    image_cache: Arc<RwLock<Option<(Vec<u8>, (u32, u32), Vec<f32>)>>>, // (hash, size, tensor_data)
-    // 统计信息
-    stats: Arc<RwLock<ModelStats>>,
-    // 性能监控
-    last_inference_time: Arc<RwLock<std::time::Instant>>,
-}
-
-impl CandleYoloModel {
-    pub fn new(model_path: &str) -> Result<Self> {
-        // 检查模型文件是否存在（用于基准测试时可以跳过）
-        if !Path::new(model_path).exists() {
-            println!("⚠️ Model file not found: {} (using simulation mode for benchmarking)", model_path);
-        }
-
-        // 初始化设备 (目前使用CPU，GPU支持需要更多配置)
-        let device = Device::Cpu;
-        println!("💻 Using CPU for inference (GPU support available with additional configuration)");
-        
-        // 设置类别名称（从 Box.yaml 配置）
-        let mut class_names = HashMap::new();
-        class_names.insert(0, "异常".to_string());
-        class_names.insert(1, "正常".to_string());
-
-        // 预分配张量缓冲区 (640*640*3 = 1,228,800 floats ≈ 4.9MB)
-        let tensor_capacity = 640 * 640 * 3;
-        let tensor_buffer = Arc::new(Mutex::new(Vec::with_capacity(tensor_capacity)));
-
-        println!("🧠 YOLO Model initialized with device: {:?}", device);
-
-        Ok(Self {
-            device,
-            model_path: model_path.to_string(),
-            class_names,
-            input_size: (640, 640),
-            tensor_buffer,
-            image_cache: Arc::new(RwLock::new(None)),
-            stats: Arc::new(RwLock::new(ModelStats::default())),
-            last_inference_time: Arc::new(RwLock::new(std::time::Instant::now())),
-        })
-    }
-
-    pub fn get_class_names(&self) -> &HashMap<u32, String> {
-        &self.class_names
-    }
-
-    pub fn get_input_size(&self) -> (usize, usize) {
-        self.input_size
-    }
-
-    // 计算简单哈希用于缓存
-    fn compute_hash(&self, data: &[u8]) -> Vec<u8> {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        
-        let mut hasher = DefaultHasher::new();
-        data.hash(&mut hasher);
-        let hash = hasher.finish();
-        hash.to_le_bytes().to_vec()
-    }
-
-    // 优化的图像预处理
-    async fn preprocess_image_optimized(&self, image_data: &[u8]) -> Result<Tensor> {
-        let start_time = std::time::Instant::now();
-        
-        // 计算输入hash用于缓存
-        let image_hash = self.compute_hash(image_data);
-        
-        // 检查缓存
-        {
-            let cache = self.image_cache.read();
-            if let Some((cached_hash, _cached_size, ref tensor_data)) = cache.as_ref() {
-                if *cached_hash == image_hash {
-                    // 缓存命中，直接使用缓存数据
-                    let mut stats = self.stats.write();
-                    stats.cache_hits += 1;
-                    stats.total_preprocess_time_ms += start_time.elapsed().as_millis() as u64;
-                    
-                    let tensor = Tensor::from_vec(
-                        tensor_data.clone(),
-                        &[1, 3, self.input_size.1, self.input_size.0],
-                        &self.device,
-                    )?;
-                    return Ok(tensor);
-                }
-            }
-        }
-        
-        // 缓存未命中，执行实际处理
-        let result = self.preprocess_image_internal(image_data, image_hash).await;
-        
-        let mut stats = self.stats.write();
-        stats.cache_misses += 1;
-        stats.total_preprocess_time_ms += start_time.elapsed().as_millis() as u64;
-        
-        result
-    }
-
-    async fn preprocess_image_internal(&self, image_data: &[u8], image_hash: Vec<u8>) -> Result<Tensor> {
-        // 高效图像解码和调整大小
-        let img = image::load_from_memory(image_data)?;
-        let (orig_width, orig_height) = img.dimensions();
-        
-        // 使用更快的滤波器进行resize
-        let filter = if orig_width > self.input_size.0 as u32 * 2 {
-            image::imageops::FilterType::Triangle // 快速下采样
-        } else {
-            image::imageops::FilterType::Lanczos3 // 高质量
-        };
-        
-        let resized = image::imageops::resize(
-            &img.to_rgb8(),
-            self.input_size.0 as u32,
-            self.input_size.1 as u32,
-            filter,
-        );
-
-        // 使用预分配缓冲区进行高效张量转换
-        let mut tensor_buffer = self.tensor_buffer.lock().await;
-        tensor_buffer.clear();
-        tensor_buffer.reserve(3 * self.input_size.0 * self.input_size.1);
-        
-        // 优化的通道分离和归一化
-        let pixels = resized.as_raw();
-        let size = self.input_size.0 * self.input_size.1;
-        
-        // RGB -> CHW 格式转换，并行处理通道
-        for c in 0..3 {
-            for i in 0..size {
-                let pixel_idx = i * 3 + c;
-                let val = pixels[pixel_idx] as f32 * (1.0 / 255.0); // 快速归一化
-                tensor_buffer.push(val);
-            }
-        }
-        
-        let tensor_data = tensor_buffer.clone();
-
-        // 更新缓存
-        {
-            let mut cache = self.image_cache.write();
-            *cache = Some((image_hash, (orig_width, orig_height), tensor_data.clone()));
-        }
-        
-        let tensor = Tensor::from_vec(
-            tensor_data,
-            &[1, 3, self.input_size.1, self.input_size.0],
-            &self.device,
-        )?;
-
-        Ok(tensor)
-    }
-
-    // 优化的后处理检测结果
-    fn postprocess_detections_optimized(&self, output: &Tensor, confidence_threshold: f32) -> Result<Vec<YoloDetection>> {
-        let start_time = std::time::Instant::now();
-
-        // YOLOv8 输出格式通常是 [1, 84, 8400] 对于2个类别
-        // 其中 84 = 4 (bbox) + 2 (classes)
-        let output_data = output.to_vec2::<f32>()?;
-        let mut detections = Vec::new();
-
-        if output_data.is_empty() {
-            return Ok(detections);
-        }
-
-        // 优化的检测结果解析
-        let num_detections = output_data[0].len() / 6;
-        detections.reserve(num_detections.min(100)); // 预分配合理的容量
-        
-        for i in 0..num_detections {
-            if i * 6 + 5 >= output_data[0].len() {
-                break;
-            }
-
-            let x = output_data[0][i * 6];
-            let y = output_data[0][i * 6 + 1];
-            let w = output_data[0][i * 6 + 2];
-            let h = output_data[0][i * 6 + 3];
-            let conf_0 = output_data[0][i * 6 + 4]; // 异常
-            let conf_1 = output_data[0][i * 6 + 5]; // 正常
-
-            // 选择置信度最高的类别
-            let (class_id, confidence) = if conf_0 > conf_1 {
-                (0, conf_0)
-            } else {
-                (1, conf_1)
-            };
-
-            // 过滤低置信度检测
-            if confidence >= confidence_threshold {
-                let class_name = self.class_names.get(&class_id)
-                    .unwrap_or(&"未知".to_string())
-                    .clone();
-
-                detections.push(YoloDetection {
-                    class_id,
-                    class_name,
-                    confidence,
-                    bbox: [x, y, w, h],
-                });
-            }
-        }
-
-        // NMS (Non-Maximum Suppression) 优化
-        let filtered_detections = self.apply_nms(detections, 0.4);
-        
-        // 更新统计
-        let mut stats = self.stats.write();
-        stats.total_postprocess_time_ms += start_time.elapsed().as_millis() as u64;
-        
-        Ok(filtered_detections)
-    }
-    
-    // 简化的NMS实现
-    fn apply_nms(&self, mut detections: Vec<YoloDetection>, iou_threshold: f32) -> Vec<YoloDetection> {
-        if detections.len() <= 1 {
-            return detections;
-        }
-        
-        // 按置信度排序
-        detections.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
-        
-        let mut result = Vec::new();
-        let mut suppressed = vec![false; detections.len()];
-        
-        for i in 0..detections.len() {
-            if suppressed[i] {
-                continue;
-            }
-            
-            result.push(detections[i].clone());
-            
-            // 抑制重叠的检测框
-            for j in (i + 1)..detections.len() {
-                if suppressed[j] {
-                    continue;
-                }
-                
-                let iou = self.calculate_iou(&detections[i].bbox, &detections[j].bbox);
-                if iou > iou_threshold {
-                    suppressed[j] = true;
-                }
-            }
-        }
-        
-        result
-    }
-    
-    // 计算IoU (Intersection over Union)
-    fn calculate_iou(&self, box1: &[f32; 4], box2: &[f32; 4]) -> f32 {
-        let x1_min = box1[0];
-        let y1_min = box1[1];
-        let x1_max = box1[0] + box1[2];
-        let y1_max = box1[1] + box1[3];
-        
-        let x2_min = box2[0];
-        let y2_min = box2[1];
-        let x2_max = box2[0] + box2[2];
-        let y2_max = box2[1] + box2[3];
-        
-        let inter_x_min = x1_min.max(x2_min);
-        let inter_y_min = y1_min.max(y2_min);
-        let inter_x_max = x1_max.min(x2_max);
-        let inter_y_max = y1_max.min(y2_max);
-        
-        if inter_x_max <= inter_x_min || inter_y_max <= inter_y_min {
-            return 0.0;
-        }
-        
-        let inter_area = (inter_x_max - inter_x_min) * (inter_y_max - inter_y_min);
-        let box1_area = box1[2] * box1[3];
-        let box2_area = box2[2] * box2[3];
-        let union_area = box1_area + box2_area - inter_area;
-        
-        if union_area <= 0.0 {
-            0.0
-        } else {
-            inter_area / union_area
-        }
-    }
-
-    // 兼容原有接口
-    fn preprocess_image(&self, image_data: &[u8]) -> Result<Tensor> {
-        // 同步版本，直接调用异步版本并阻塞等待
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                self.preprocess_image_optimized(image_data).await
-            })
-        })
-    }
-
-    fn postprocess_detections(&self, output: &Tensor, confidence_threshold: f32) -> Result<Vec<YoloDetection>> {
-        self.postprocess_detections_optimized(output, confidence_threshold)
-    }
-
-    // 优化的图像检测方法
-    pub async fn detect_image(&self, image_data: &[u8]) -> Result<Vec<YoloDetection>> {
-        let total_start_time = std::time::Instant::now();
-        
-        // 更新统计计数
-        {
-            let mut stats = self.stats.write();
-            stats.total_inferences += 1;
-        }
-
-        // 注意：由于我们目前有 PyTorch 模型(.pt)，但 Candle 需要特定格式
-        // 这里先提供一个增强的模拟实现，带有真实的图像处理
-        
-        // 优化的图像预处理
-        let _tensor = self.preprocess_image_optimized(image_data).await?;
-        
-        // TODO: 当有 Candle 格式模型时，替换以下模拟逻辑
-        // let inference_start = std::time::Instant::now();
-        // let output = self.model.forward(&tensor)?;
-        // let inference_time = inference_start.elapsed();
-        // return self.postprocess_detections_optimized(&output, 0.5);
-
-        // 临时的增强模拟 - 基于真实图像特征的智能检测
-        let img = image::load_from_memory(image_data)?;
-        let (width, height) = img.dimensions();
-        
-        // 更智能的检测逻辑：分析图像特征
-        let mut detections = Vec::new();
-        
-        // 基于图像复杂度和尺寸的自适应检测
-        let brightness = self.calculate_average_brightness(&img);
-        let complexity_factor = (width * height) as f64 / (640.0 * 640.0);
-        
-        // 模拟不同场景的检测结果
-        let num_objects = if complexity_factor > 2.0 { 
-            3 // 大图像可能有更多目标
-        } else if brightness < 100.0 { 
-            1 // 暗图像检测难度高
-        } else { 
-            2 // 标准场景
-        };
-        
-        for i in 0..num_objects {
-            let class_id = if i % 2 == 0 { 1 } else { 0 }; // 交替正常/异常
-            
-            // 基于图像特征的置信度计算
-            let base_confidence = if brightness > 150.0 { 0.85 } else { 0.65 };
-            let confidence = base_confidence - (i as f32 * 0.05);
-            
-            // 更真实的边界框位置
-            let x = (width as f32 * 0.1) + (i as f32 * width as f32 * 0.25);
-            let y = (height as f32 * 0.15) + (i as f32 * height as f32 * 0.2);
-            let w = width as f32 * (0.15 + complexity_factor as f32 * 0.1);
-            let h = height as f32 * (0.2 + complexity_factor as f32 * 0.05);
-
-            let class_name = self.class_names.get(&class_id)
-                .unwrap_or(&"未知".to_string())
-                .clone();
-
-            detections.push(YoloDetection {
-                class_id,
-                class_name,
-                confidence,
-                bbox: [x, y, w, h],
-            });
-        }
-
-        // 更新总推理时间
-        {
-            let mut stats = self.stats.write();
-            stats.total_inference_time_ms += total_start_time.elapsed().as_millis() as u64;
-            
-            // 更新FPS统计
-            let current_time = std::time::Instant::now();
-            let mut last_time = self.last_inference_time.write();
-            let time_diff = current_time.duration_since(*last_time).as_secs_f64();
-            if time_diff > 0.0 {
-                stats.avg_fps = 1.0 / time_diff;
-            }
-            *last_time = current_time;
-        }
-        
-        Ok(detections)
-    }
-
-    // 计算图像平均亮度（用于智能检测）
-    fn calculate_average_brightness(&self, img: &DynamicImage) -> f32 {
-        let rgb_img = img.to_rgb8();
-        let pixels = rgb_img.as_raw();
-        
-        let mut total_brightness = 0u64;
-        let num_pixels = pixels.len() / 3;
-        
-        for i in 0..num_pixels {
-            let r = pixels[i * 3] as u64;
-            let g = pixels[i * 3 + 1] as u64;
-            let b = pixels[i * 3 + 2] as u64;
-            // 使用简化的亮度计算公式
-            total_brightness += (r + g + b) / 3;
-        }
-        
-        (total_brightness / num_pixels as u64) as f32
-    }
-
-    // 检查模型文件状态和性能统计
-    pub fn get_model_info(&self) -> HashMap<String, String> {
-        let mut info = HashMap::new();
-        info.insert("model_path".to_string(), self.model_path.clone());
-        info.insert("device".to_string(), format!("{:?}", self.device));
-        info.insert("input_size".to_string(), format!("{:?}", self.input_size));
-        info.insert("num_classes".to_string(), self.class_names.len().to_string());
-        
-        // 添加性能统计信息
-        let stats = self.stats.read();
-        if stats.total_inferences > 0 {
-            let avg_inference_time = stats.total_inference_time_ms / stats.total_inferences;
-            let avg_preprocess_time = stats.total_preprocess_time_ms / stats.total_inferences;
-            let avg_postprocess_time = stats.total_postprocess_time_ms / stats.total_inferences;
-            let cache_hit_rate = if stats.cache_hits + stats.cache_misses > 0 {
-                (stats.cache_hits as f64 / (stats.cache_hits + stats.cache_misses) as f64 * 100.0) as u64
-            } else {
-                0
-            };
-            
-            info.insert("total_inferences".to_string(), stats.total_inferences.to_string());
-            info.insert("avg_inference_time_ms".to_string(), avg_inference_time.to_string());
-            info.insert("avg_preprocess_time_ms".to_string(), avg_preprocess_time.to_string());
-            info.insert("avg_postprocess_time_ms".to_string(), avg_postprocess_time.to_string());
-            info.insert("cache_hit_rate_percent".to_string(), cache_hit_rate.to_string());
-            info.insert("avg_fps".to_string(), format!("{:.1}", stats.avg_fps));
-        }
-        
-        info
-    }
-    
-    // 获取性能统计
-    pub async fn get_performance_stats(&self) -> ModelStats {
-        self.stats.read().clone()
-    }
-    
-    // 重置统计信息
-    pub async fn reset_stats(&self) {
-        let mut stats = self.stats.write();
-        *stats = ModelStats::default();
-    }
-    
-    // 清除缓存
-    pub async fn clear_cache(&self) {
-        let mut cache = self.image_cache.write();
-        *cache = None;
-        
-        // 清除张量缓冲区
-        let mut buffer = self.tensor_buffer.lock().await;
-        buffer.clear();
-        buffer.shrink_to_fit();
-    }
-
-    // 内存使用监控
-    pub async fn get_memory_usage(&self) -> u64 {
-        let buffer = self.tensor_buffer.lock().await;
-        let cache = self.image_cache.read();
-        
-        let mut total_bytes = buffer.capacity() * std::mem::size_of::<f32>();
-        
-        if let Some((_, _, ref tensor_data)) = cache.as_ref() {
-            total_bytes += tensor_data.capacity() * std::mem::size_of::<f32>();
-        }
-        
-        (total_bytes / 1024 / 1024) as u64 // 转换为MB
-    }
-
-    // 性能分析报告
-    pub async fn generate_performance_report(&self) -> String {
-        let stats = self.stats.read();
-        let memory_usage = self.get_memory_usage().await;
-        
-        format!(
-            "🔥 YOLO Performance Report\n\
-            ================================\n\
-            📊 Inference Statistics:\n\
-            • Total Inferences: {}\n\
-            • Average FPS: {:.1}\n\
-            • Avg Inference Time: {}ms\n\
-            • Avg Preprocess Time: {}ms\n\
-            • Avg Postprocess Time: {}ms\n\
-            \n\
-            💾 Memory & Cache:\n\
-            • Memory Usage: {}MB\n\
-            • Cache Hit Rate: {:.1}%\n\
-            • Cache Hits: {}\n\
-            • Cache Misses: {}\n\
-            \n\
-            🚀 Device Info:\n\
-            • Device: {:?}\n\
-            • Input Size: {:?}\n\
-            • Model Path: {}",
-            stats.total_inferences,
-            stats.avg_fps,
-            if stats.total_inferences > 0 { stats.total_inference_time_ms / stats.total_inferences } else { 0 },
-            if stats.total_inferences > 0 { stats.total_preprocess_time_ms / stats.total_inferences } else { 0 },
-            if stats.total_inferences > 0 { stats.total_postprocess_time_ms / stats.total_inferences } else { 0 },
-            memory_usage,
-            if stats.cache_hits + stats.cache_misses > 0 { 
-                stats.cache_hits as f64 / (stats.cache_hits + stats.cache_misses) as f64 * 100.0 
-            } else { 0.0 },
-            stats.cache_hits,
-            stats.cache_misses,
-            self.device,
-            self.input_size,
-            self.model_path
-        )
-    }
-}
-
-// 置信度阈值管理
-pub struct ConfidenceThresholds {
-    thresholds: Arc<RwLock<HashMap<String, f32>>>,
-}
-
-impl ConfidenceThresholds {
-    pub fn new() -> Self {
-        let mut thresholds = HashMap::new();
-        thresholds.insert("异常".to_string(), 0.7); // 异常检测阈值稍高
-        thresholds.insert("正常".to_string(), 0.5);
-        
-        Self {
-            thresholds: Arc::new(RwLock::new(thresholds)),
-        }
-    }
-
-    pub async fn update_threshold(&self, class_name: &str, threshold: f32) {
-        let mut thresholds = self.thresholds.write();
-        thresholds.insert(class_name.to_string(), threshold);
-    }
-
-    pub async fn get_threshold(&self, class_name: &str) -> f32 {
-        let thresholds = self.thresholds.read();
-        thresholds.get(class_name).copied().unwrap_or(0.5)
-    }
-
-    pub async fn get_all_thresholds(&self) -> HashMap<String, f32> {
-        let thresholds = self.thresholds.read();
-        thresholds.clone()
-    }
-}
\ No newline at end of file
@@ -0,0 +1,267 @@
+/*!
+ONNX模型元信息解析
+从计算图的输入/输出形状推断模型输入尺寸、类别数与任务类型（检测或整图分类）；
+动态维度（dim_param 或非正数）视为未知，由模型元数据中的 imgsz 或配置的输入尺寸决定
+
+类别名称可来自 Ultralytics 导出时写入 metadata_props 的 `names`，
+或训练数据集配置 data.yaml / Box.yaml 中的 `names`
+*/
+
+use std::collections::BTreeMap;
+
+use candle_onnx::onnx::{tensor_shape_proto::dimension, type_proto, GraphProto, ModelProto, ValueInfoProto};
+use serde::{Deserialize, Serialize};
+
+/// 模型任务类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelTask {
+    #[default]
+    Detect,   // 目标检测，输出检测框
+    Classify, // 整图分类，输出各类别得分
+}
+
+impl ModelTask {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModelTask::Detect => "detect",
+            ModelTask::Classify => "classify",
+        }
+    }
+}
+
+/// 从计算图推断的模型形状
+#[derive(Debug, Clone, Default)]
+pub struct ModelShape {
+    pub input_dims: Vec<Option<i64>>,   // 图像输入各维度，None 表示动态维度
+    pub output_dims: Vec<Option<i64>>,  // 检测输出各维度
+    pub input_size: Option<(u32, u32)>, // 固定的输入尺寸 (width, height)，宽高为动态维度时为空
+    pub num_classes: Option<usize>,     // 由输出形状推断的类别数
+    pub task: ModelTask,
+}
+
+/// 形状描述，如 `1x3x640x640`，动态维度显示为 `?`
+fn describe_dims(dims: &[Option<i64>]) -> String {
+    dims.iter()
+        .map(|dim| dim.map_or_else(|| "?".to_string(), |v| v.to_string()))
+        .collect::<Vec<_>>()
+        .join("x")
+}
+
+impl ModelShape {
+    pub fn describe_input(&self) -> String {
+        describe_dims(&self.input_dims)
+    }
+
+    pub fn describe_output(&self) -> String {
+        describe_dims(&self.output_dims)
+    }
+}
+
+/// 模型使用的默认域（ai.onnx）算子集版本
+pub fn opset_version(model: &ModelProto) -> Option<i64> {
+    model
+        .opset_import
+        .iter()
+        .find(|opset| opset.domain.is_empty() || opset.domain == "ai.onnx")
+        .map(|opset| opset.version)
+}
+
+/// 计算图的图像输入（输入列表中包含权重初始化项，需排除）
+pub fn image_input(graph: &GraphProto) -> Option<&ValueInfoProto> {
+    graph
+        .input
+        .iter()
+        .find(|input| !graph.initializer.iter().any(|init| init.name == input.name))
+}
+
+/// 张量各维度，无形状信息时返回 None
+fn tensor_dims(info: &ValueInfoProto) -> Option<Vec<Option<i64>>> {
+    let type_proto::Value::TensorType(tensor) = info.r#type.as_ref()?.value.as_ref()? else {
+        return None;
+    };
+    let dims = tensor
+        .shape
+        .as_ref()?
+        .dim
+        .iter()
+        .map(|dim| match dim.value {
+            Some(dimension::Value::DimValue(value)) if value > 0 => Some(value),
+            _ => None,
+        })
+        .collect();
+    Some(dims)
+}
+
+/// 解析模型输入尺寸、类别数与任务类型
+///
+/// - 输入：`[batch, 3, height, width]`
+/// - 检测输出：`[batch, 4 + 类别数, 锚点数]`，部分导出为 `[batch, 锚点数, 4 + 类别数]`，取较小的维度
+/// - 分类输出：`[batch, 类别数]`；形状无法判断时以元数据中的 `task` 为准
+pub fn inspect(model: &ModelProto) -> ModelShape {
+    let Some(graph) = model.graph.as_ref() else {
+        return ModelShape::default();
+    };
+
+    let input_dims = image_input(graph).and_then(tensor_dims).unwrap_or_default();
+    let input_size = match input_dims.as_slice() {
+        [_, _, Some(height), Some(width)] => Some((*width as u32, *height as u32)),
+        _ => None,
+    };
+
+    let output_dims = graph.output.first().and_then(tensor_dims).unwrap_or_default();
+    let task = match output_dims.len() {
+        2 => ModelTask::Classify,
+        3 => ModelTask::Detect,
+        _ => metadata_task(model).unwrap_or_default(),
+    };
+    let num_classes = match (task, output_dims.as_slice()) {
+        (ModelTask::Classify, [_, Some(classes)]) => Some(*classes as usize),
+        (ModelTask::Detect, [_, Some(a), Some(b)]) => {
+            Some((*a).min(*b) as usize).filter(|channels| *channels > 4).map(|channels| channels - 4)
+        }
+        (ModelTask::Detect, [_, Some(channels), None]) if *channels > 4 => Some(*channels as usize - 4),
+        _ => None,
+    };
+
+    ModelShape {
+        input_dims,
+        output_dims,
+        input_size,
+        num_classes,
+        task,
+    }
+}
+
+/// 模型元数据中的字符串值
+fn metadata_value<'a>(model: &'a ModelProto, key: &str) -> Option<&'a str> {
+    model
+        .metadata_props
+        .iter()
+        .find(|prop| prop.key == key)
+        .map(|prop| prop.value.as_str())
+}
+
+/// Ultralytics 导出时记录的任务类型 `task`（detect / classify，其他任务不支持）
+pub fn metadata_task(model: &ModelProto) -> Option<ModelTask> {
+    match metadata_value(model, "task")? {
+        "detect" => Some(ModelTask::Detect),
+        "classify" => Some(ModelTask::Classify),
+        _ => None,
+    }
+}
+
+/// Ultralytics 导出时记录的训练输入尺寸 `imgsz`（`[height, width]`），返回 (width, height)
+pub fn metadata_input_size(model: &ModelProto) -> Option<(u32, u32)> {
+    let value = metadata_value(model, "imgsz")?;
+    let sizes: Vec<u32> = value
+        .trim_matches(|c| c == '[' || c == ']')
+        .split(',')
+        .map(|part| part.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    match sizes.as_slice() {
+        [height, width] if *height > 0 && *width > 0 => Some((*width, *height)),
+        [size] if *size > 0 => Some((*size, *size)),
+        _ => None,
+    }
+}
+
+/// 去掉名称两侧的引号
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    let quoted = value.len() >= 2
+        && ((value.starts_with('\'') && value.ends_with('\''))
+            || (value.starts_with('"') && value.ends_with('"')));
+    if quoted {
+        value[1..value.len() - 1].to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// 按逗号切分，忽略引号内的逗号
+fn split_items(content: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (index, c) in content.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, ',') => {
+                items.push(&content[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&content[start..]);
+    items.into_iter().filter(|item| !item.trim().is_empty()).collect()
+}
+
+/// 解析内联的类别名称：字典 `{0: 'a', 1: 'b'}` 或列表 `['a', 'b']`
+fn parse_inline_names(content: &str) -> Option<BTreeMap<u32, String>> {
+    let content = content.trim();
+    let names: BTreeMap<u32, String> = if let Some(body) = content.strip_prefix('{').and_then(|c| c.strip_suffix('}')) {
+        split_items(body)
+            .into_iter()
+            .map(|item| {
+                let (id, name) = item.split_once(':')?;
+                Some((id.trim().parse().ok()?, unquote(name)))
+            })
+            .collect::<Option<_>>()?
+    } else if let Some(body) = content.strip_prefix('[').and_then(|c| c.strip_suffix(']')) {
+        split_items(body)
+            .into_iter()
+            .enumerate()
+            .map(|(id, name)| (id as u32, unquote(name)))
+            .collect()
+    } else {
+        return None;
+    };
+    Some(names).filter(|names| !names.is_empty())
+}
+
+/// Ultralytics 导出时写入模型元数据的类别名称
+pub fn metadata_class_names(model: &ModelProto) -> Option<BTreeMap<u32, String>> {
+    parse_inline_names(metadata_value(model, "names")?)
+}
+
+/// 解析数据集配置（data.yaml / Box.yaml）中的 `names`，支持以下写法：
+///
+/// ```yaml
+/// names: ['异常', '正常']
+/// names:
+///   0: 异常
+///   1: 正常
+/// names:
+///   - 异常
+///   - 正常
+/// ```
+pub fn yaml_class_names(content: &str) -> Option<BTreeMap<u32, String>> {
+    let mut lines = content.lines();
+    let inline = lines.find_map(|line| line.strip_prefix("names:"))?;
+    // 去掉行尾注释
+    let inline = inline.split(" #").next().unwrap_or_default().trim();
+    if !inline.is_empty() {
+        return parse_inline_names(inline);
+    }
+
+    let mut names = BTreeMap::new();
+    for line in lines {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        // names 块在下一个顶层键处结束
+        if !line.starts_with(' ') && !line.starts_with('\t') && !line.starts_with('-') {
+            break;
+        }
+        let item = line.trim();
+        if let Some(name) = item.strip_prefix('-') {
+            names.insert(names.len() as u32, unquote(name));
+        } else if let Some((id, name)) = item.split_once(':') {
+            names.insert(id.trim().parse().ok()?, unquote(name));
+        }
+    }
+    Some(names).filter(|names| !names.is_empty())
+}
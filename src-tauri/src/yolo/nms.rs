@@ -0,0 +1,173 @@
+/*!
+非极大值抑制（NMS）
+按置信度从高到低保留检测框，抑制与已保留框重叠度超过阈值的框；
+可选择跨类别抑制（class_agnostic）或按类别分别抑制，并限制每张图片的最大检测数
+
+除标准NMS外支持：
+- Soft-NMS（线性/高斯衰减）：重叠框降低置信度而非直接删除，适合缺陷密集相邻的场景
+- DIoU-NMS：重叠度计入中心点距离，中心相距较远的相邻框不被抑制
+*/
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use super::YoloDetection;
+
+/// NMS算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NmsMethod {
+    Standard,     // 标准NMS，IoU超过阈值直接删除
+    SoftLinear,   // Soft-NMS线性衰减：IoU超过阈值时置信度乘以 (1 - IoU)
+    SoftGaussian, // Soft-NMS高斯衰减：置信度乘以 exp(-IoU² / sigma)
+    Diou,         // DIoU-NMS：DIoU超过阈值时删除
+}
+
+/// NMS参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NmsConfig {
+    pub method: NmsMethod,
+    pub iou_threshold: f32,        // IoU（DIoU-NMS为DIoU）超过该值的检测框被抑制或衰减
+    pub max_detections: usize,     // 每张图片最多保留的检测框数
+    pub class_agnostic: bool,      // true 时不同类别之间也相互抑制
+    pub soft_sigma: f32,           // 高斯衰减的sigma
+    pub soft_min_confidence: f32,  // Soft-NMS衰减后置信度低于该值的检测框被删除
+}
+
+impl Default for NmsConfig {
+    fn default() -> Self {
+        Self {
+            method: NmsMethod::Standard,
+            iou_threshold: 0.4,
+            max_detections: 300,
+            class_agnostic: true, // 同一区域只保留一个类别（正常/异常互斥）
+            soft_sigma: 0.5,
+            soft_min_confidence: 0.1,
+        }
+    }
+}
+
+impl NmsConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.iou_threshold) {
+            return Err(anyhow!("NMS IoU阈值必须在 0-1 之间: {}", self.iou_threshold));
+        }
+        if self.max_detections == 0 {
+            return Err(anyhow!("最大检测数必须大于0"));
+        }
+        if self.soft_sigma <= 0.0 {
+            return Err(anyhow!("Soft-NMS的sigma必须大于0: {}", self.soft_sigma));
+        }
+        if !(0.0..=1.0).contains(&self.soft_min_confidence) {
+            return Err(anyhow!("Soft-NMS最低置信度必须在 0-1 之间: {}", self.soft_min_confidence));
+        }
+        Ok(())
+    }
+}
+
+/// 计算两个边界框的IoU (Intersection over Union)，边界框格式为 [x, y, width, height]
+pub fn calculate_iou(box1: &[f32; 4], box2: &[f32; 4]) -> f32 {
+    let inter_x_min = box1[0].max(box2[0]);
+    let inter_y_min = box1[1].max(box2[1]);
+    let inter_x_max = (box1[0] + box1[2]).min(box2[0] + box2[2]);
+    let inter_y_max = (box1[1] + box1[3]).min(box2[1] + box2[3]);
+
+    if inter_x_max <= inter_x_min || inter_y_max <= inter_y_min {
+        return 0.0;
+    }
+
+    let inter_area = (inter_x_max - inter_x_min) * (inter_y_max - inter_y_min);
+    let union_area = box1[2] * box1[3] + box2[2] * box2[3] - inter_area;
+
+    if union_area <= 0.0 {
+        0.0
+    } else {
+        inter_area / union_area
+    }
+}
+
+/// 计算DIoU：IoU减去中心点距离平方与最小外接框对角线平方之比
+pub fn calculate_diou(box1: &[f32; 4], box2: &[f32; 4]) -> f32 {
+    let iou = calculate_iou(box1, box2);
+
+    let center_dx = (box1[0] + box1[2] / 2.0) - (box2[0] + box2[2] / 2.0);
+    let center_dy = (box1[1] + box1[3] / 2.0) - (box2[1] + box2[3] / 2.0);
+    let enclose_w = (box1[0] + box1[2]).max(box2[0] + box2[2]) - box1[0].min(box2[0]);
+    let enclose_h = (box1[1] + box1[3]).max(box2[1] + box2[3]) - box1[1].min(box2[1]);
+    let diagonal = enclose_w * enclose_w + enclose_h * enclose_h;
+
+    if diagonal <= 0.0 {
+        iou
+    } else {
+        iou - (center_dx * center_dx + center_dy * center_dy) / diagonal
+    }
+}
+
+/// 两个检测框是否参与相互抑制
+fn competes(config: &NmsConfig, a: &YoloDetection, b: &YoloDetection) -> bool {
+    config.class_agnostic || a.class_id == b.class_id
+}
+
+/// 非极大值抑制，结果按置信度降序排列
+pub fn apply_nms(detections: Vec<YoloDetection>, config: &NmsConfig) -> Vec<YoloDetection> {
+    match config.method {
+        NmsMethod::Standard => hard_nms(detections, config, calculate_iou),
+        NmsMethod::Diou => hard_nms(detections, config, calculate_diou),
+        NmsMethod::SoftLinear | NmsMethod::SoftGaussian => soft_nms(detections, config),
+    }
+}
+
+/// 硬抑制：重叠度超过阈值的检测框直接删除
+fn hard_nms(
+    mut detections: Vec<YoloDetection>,
+    config: &NmsConfig,
+    overlap: fn(&[f32; 4], &[f32; 4]) -> f32,
+) -> Vec<YoloDetection> {
+    // 按置信度降序排序
+    detections.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    let mut keep: Vec<YoloDetection> = Vec::new();
+    for detection in detections {
+        if keep.len() >= config.max_detections {
+            break;
+        }
+        // 抑制与已保留检测框重叠度高的检测框
+        let suppressed = keep.iter().any(|kept| {
+            competes(config, kept, &detection) && overlap(&kept.bbox, &detection.bbox) > config.iou_threshold
+        });
+        if !suppressed {
+            keep.push(detection);
+        }
+    }
+    keep
+}
+
+/// Soft-NMS：每轮保留置信度最高的检测框，并按重叠度衰减其余检测框的置信度
+fn soft_nms(mut detections: Vec<YoloDetection>, config: &NmsConfig) -> Vec<YoloDetection> {
+    let mut keep = Vec::new();
+    while keep.len() < config.max_detections {
+        let Some(best_index) = detections
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.confidence.total_cmp(&b.confidence))
+            .map(|(index, _)| index)
+        else {
+            break;
+        };
+        let best = detections.swap_remove(best_index);
+
+        for detection in detections.iter_mut().filter(|d| competes(config, &best, d)) {
+            let iou = calculate_iou(&best.bbox, &detection.bbox);
+            let decay = match config.method {
+                NmsMethod::SoftLinear if iou > config.iou_threshold => 1.0 - iou,
+                NmsMethod::SoftGaussian => (-(iou * iou) / config.soft_sigma).exp(),
+                _ => 1.0,
+            };
+            detection.confidence *= decay;
+        }
+        detections.retain(|d| d.confidence >= config.soft_min_confidence);
+        keep.push(best);
+    }
+    keep
+}
@@ -4,38 +4,17 @@ YOLO ONNX 检测器 - 基于yolo-rs和ONNX运行时的实现
 */
 
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use image::{GenericImageView};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::collections::HashMap;
 use tokio::sync::RwLock;
 
-/// YOLO检测结果
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DetectionResult {
-    pub detections: Vec<Detection>,
-    pub image_width: u32,
-    pub image_height: u32,
-    pub processing_time_ms: u64,
-}
-
-/// 单个检测框
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Detection {
-    pub class_id: u32,
-    pub class_name: String,
-    pub confidence: f32,
-    pub bbox: BoundingBox,
-}
-
-/// 边界框
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BoundingBox {
-    pub x: f32,
-    pub y: f32,
-    pub width: f32,
-    pub height: f32,
-}
+use crate::error;
+use super::roi::{self, RoiPolygon};
+use super::rolling_stats::{RollingStats, StatsTimeseries};
+use super::{DetectionResult, DetectionTimings, Detector, FrameInfo, InferenceBackend, ModelStats, YoloDetection};
 
 /// YOLO检测器状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,12 +33,20 @@ pub struct YoloOnnxDetector {
     model_path: Option<String>,
     /// 类别名称映射
     class_names: Vec<String>,
+    /// 类别ID到名称的映射（统一检测器接口使用）
+    class_map: HashMap<u32, String>,
     /// 置信度阈值设置
     confidence_thresholds: RwLock<HashMap<String, f32>>,
     /// 选中的检测类别
     selected_classes: RwLock<Vec<u32>>,
     /// 检测器状态
     state: RwLock<DetectionState>,
+    /// 性能统计
+    stats: RwLock<ModelStats>,
+    /// 最近检测的耗时窗口
+    rolling: RwLock<RollingStats>,
+    /// 检测区域
+    roi: RwLock<Vec<RoiPolygon>>,
 }
 
 impl YoloOnnxDetector {
@@ -68,6 +55,7 @@ impl YoloOnnxDetector {
         Self {
             model_path: None,
             class_names: Vec::new(),
+            class_map: HashMap::new(),
             confidence_thresholds: RwLock::new(HashMap::new()),
             selected_classes: RwLock::new(Vec::new()),
             state: RwLock::new(DetectionState {
@@ -78,6 +66,9 @@ impl YoloOnnxDetector {
                 selected_classes: Vec::new(),
                 is_running: false,
             }),
+            stats: RwLock::new(ModelStats::default()),
+            rolling: RwLock::new(RollingStats::new()),
+            roi: RwLock::new(Vec::new()),
         }
     }
 
@@ -93,7 +84,7 @@ impl YoloOnnxDetector {
             current_dir.join(model_path)
         };
         
-        println!("🔍 查找模型文件: {}", model_path_obj.display());
+        tracing::info!("🔍 查找模型文件: {}", model_path_obj.display());
         
         if !model_path_obj.exists() {
             return Err(anyhow!("模型文件不存在: {}", model_path_obj.display()));
@@ -103,7 +94,7 @@ impl YoloOnnxDetector {
             return Err(anyhow!("只支持ONNX格式模型"));
         }
 
-        println!("🔄 初始化YOLO模型: {}", model_path_obj.display());
+        tracing::info!("🔄 初始化YOLO模型: {}", model_path_obj.display());
 
         // 保存模型路径
         self.model_path = Some(model_path_obj.to_string_lossy().to_string());
@@ -120,8 +111,8 @@ impl YoloOnnxDetector {
         state.model_path = Some(model_path.to_string());
         state.class_names = self.class_names.clone();
 
-        println!("✅ YOLO模型初始化成功 (模拟)");
-        println!("📊 支持类别数量: {}", self.class_names.len());
+        tracing::info!("✅ YOLO模型初始化成功 (模拟)");
+        tracing::info!("📊 支持类别数量: {}", self.class_names.len());
 
         Ok(())
     }
@@ -138,12 +129,18 @@ impl YoloOnnxDetector {
                 .filter(|line| !line.is_empty())
                 .collect();
             
-            println!("📄 从文件加载类别名称: {:?}", self.class_names);
+            tracing::info!("📄 从文件加载类别名称: {:?}", self.class_names);
         } else {
             // 使用默认的二分类类别
             self.class_names = vec!["异常".to_string(), "正常".to_string()];
-            println!("⚠️  未找到类别文件，使用默认类别: {:?}", self.class_names);
+            tracing::warn!("⚠️  未找到类别文件，使用默认类别: {:?}", self.class_names);
         }
+        self.class_map = self
+            .class_names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (id as u32, name.clone()))
+            .collect();
 
         Ok(())
     }
@@ -161,30 +158,36 @@ impl YoloOnnxDetector {
         // 默认选择所有类别
         *selected = (0..self.class_names.len() as u32).collect();
 
-        println!("⚙️  默认配置已加载");
+        tracing::info!("⚙️  默认配置已加载");
         Ok(())
     }
 
     /// 处理单张图片
-    pub async fn process_image(&mut self, image_path: &str) -> Result<DetectionResult> {
+    pub async fn process_image(&self, image_path: &str) -> Result<DetectionResult> {
+        let image_data = tokio::fs::read(image_path).await?;
+        self.process_image_data(&image_data).await
+    }
+
+    /// 处理内存中的图片数据
+    pub async fn process_image_data(&self, image_data: &[u8]) -> Result<DetectionResult> {
         if self.model_path.is_none() {
             return Err(anyhow!("模型未初始化"));
         }
 
         let start_time = std::time::Instant::now();
 
-        // 加载图片
-        let img = image::open(image_path)?;
+        // 解码图片
+        let img = super::decode::decode(image_data)?;
         let (width, height) = img.dimensions();
 
-        println!("🖼️  处理图片: {}x{}", width, height);
+        tracing::info!("🖼️  处理图片: {}x{}", width, height);
 
         // TODO: 实际的ONNX推理 - 目前返回模拟结果
         let detections = self.create_mock_detections(width, height).await?;
 
         let processing_time = start_time.elapsed().as_millis() as u64;
 
-        println!("✅ 检测完成 (模拟)，用时: {}ms，检测到 {} 个目标", 
+        tracing::info!("✅ 检测完成 (模拟)，用时: {}ms，检测到 {} 个目标", 
                 processing_time, detections.len());
 
         Ok(DetectionResult {
@@ -192,11 +195,20 @@ impl YoloOnnxDetector {
             image_width: width,
             image_height: height,
             processing_time_ms: processing_time,
+            model_input_size: (width, height),
+            thresholds: self.confidence_thresholds.read().await.clone(),
+            track_ids: Vec::new(),
+            // 模拟推理不区分阶段，整体耗时计入推理
+            timings: DetectionTimings {
+                inference_ms: processing_time as f64,
+                ..Default::default()
+            },
+            frame: FrameInfo::next(),
         })
     }
 
     /// 创建模拟检测结果 (临时实现)
-    async fn create_mock_detections(&self, width: u32, height: u32) -> Result<Vec<Detection>> {
+    async fn create_mock_detections(&self, width: u32, height: u32) -> Result<Vec<YoloDetection>> {
         let confidence_thresholds = self.confidence_thresholds.read().await;
         let selected_classes = self.selected_classes.read().await;
 
@@ -218,16 +230,17 @@ impl YoloOnnxDetector {
             // 只在满足置信度阈值时添加模拟检测
             let mock_confidence = 0.85;
             if mock_confidence >= *threshold {
-                detections.push(Detection {
+                detections.push(YoloDetection {
                     class_id,
                     class_name,
                     confidence: mock_confidence,
-                    bbox: BoundingBox {
-                        x: width as f32 * 0.2,
-                        y: height as f32 * 0.2,
-                        width: width as f32 * 0.3,
-                        height: height as f32 * 0.4,
-                    },
+                    bbox: [
+                        width as f32 * 0.2,
+                        height as f32 * 0.2,
+                        width as f32 * 0.3,
+                        height as f32 * 0.4,
+                    ],
+                    size_mm: None,
                 });
             }
         }
@@ -244,7 +257,7 @@ impl YoloOnnxDetector {
         let mut state = self.state.write().await;
         state.confidence_thresholds = thresholds.clone();
         
-        println!("⚙️  更新 {} 的置信度阈值为: {:.2}", class_name, threshold);
+        tracing::info!("⚙️  更新 {} 的置信度阈值为: {:.2}", class_name, threshold);
         Ok(())
     }
 
@@ -262,7 +275,7 @@ impl YoloOnnxDetector {
         let mut state = self.state.write().await;
         state.selected_classes = valid_ids;
 
-        println!("⚙️  更新选中的类别: {:?}", *selected);
+        tracing::info!("⚙️  更新选中的类别: {:?}", *selected);
         Ok(())
     }
 
@@ -292,7 +305,7 @@ impl YoloOnnxDetector {
         let mut state = self.state.write().await;
         state.is_running = true;
         
-        println!("🎥 开始实时检测 (模拟)");
+        tracing::info!("🎥 开始实时检测 (模拟)");
         // TODO: 实现实时检测逻辑
         Ok(())
     }
@@ -302,7 +315,7 @@ impl YoloOnnxDetector {
         let mut state = self.state.write().await;
         state.is_running = false;
         
-        println!("⏹️  停止实时检测");
+        tracing::info!("⏹️  停止实时检测");
         Ok(())
     }
 }
@@ -311,4 +324,83 @@ impl Default for YoloOnnxDetector {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+/// 模拟推理后端：按检测区域过滤结果并累计统计
+#[async_trait]
+impl Detector for YoloOnnxDetector {
+    fn backend(&self) -> InferenceBackend {
+        InferenceBackend::Mock
+    }
+
+    async fn init_model(&mut self, model_path: &str) -> Result<()> {
+        YoloOnnxDetector::init_model(self, model_path).await
+    }
+
+    async fn detect_image(&self, image_data: &[u8]) -> Result<DetectionResult> {
+        let mut result = self.process_image_data(image_data).await?;
+        let size = (result.image_width, result.image_height);
+        let regions = self.roi.read().await;
+        result.detections.retain(|d| roi::contains_detection(&regions, d, size));
+        drop(regions);
+
+        {
+            let mut stats = self.stats.write().await;
+            stats.total_inferences += 1;
+            stats.total_inference_time_ms += result.processing_time_ms;
+            for detection in &result.detections {
+                stats
+                    .class_stats
+                    .entry(detection.class_name.clone())
+                    .or_default()
+                    .record(detection.confidence);
+            }
+        }
+        self.rolling.write().await.record(result.processing_time_ms as f64);
+
+        Ok(result)
+    }
+
+    async fn update_confidence_threshold(&self, class_name: &str, threshold: f32) -> Result<()> {
+        YoloOnnxDetector::update_confidence_threshold(self, class_name, threshold).await
+    }
+
+    async fn set_enabled_classes(&self, class_ids: Vec<u32>) -> Result<()> {
+        self.set_selected_classes(class_ids).await
+    }
+
+    async fn set_roi(&self, regions: Vec<RoiPolygon>) -> Result<()> {
+        roi::validate(&regions).map_err(error::invalid_input)?;
+        *self.roi.write().await = regions;
+        Ok(())
+    }
+
+    fn get_class_names(&self) -> &HashMap<u32, String> {
+        &self.class_map
+    }
+
+    async fn get_stats(&self) -> ModelStats {
+        let mut stats = self.stats.read().await.clone();
+        self.rolling.read().await.fill(&mut stats);
+        stats
+    }
+
+    async fn reset_stats(&self) {
+        *self.stats.write().await = ModelStats::default();
+        self.rolling.write().await.clear();
+    }
+
+    async fn stats_timeseries(&self, limit: usize) -> StatsTimeseries {
+        self.rolling.read().await.timeseries(limit)
+    }
+
+    fn get_model_info(&self) -> HashMap<String, String> {
+        let mut info = HashMap::new();
+        info.insert("backend".to_string(), InferenceBackend::Mock.as_str().to_string());
+        info.insert("model_path".to_string(), self.model_path.clone().unwrap_or_default());
+        info.insert("device".to_string(), "Mock".to_string());
+        info.insert("num_classes".to_string(), self.class_names.len().to_string());
+        info.insert("model_loaded".to_string(), self.model_path.is_some().to_string());
+        info
+    }
+}
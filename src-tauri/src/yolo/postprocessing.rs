@@ -1,6 +1,7 @@
 use anyhow::Result;
 use ort::value::Value;
 use std::collections::HashMap;
+use crate::yolo::nms::{apply_nms, NmsConfig};
 use crate::yolo::YoloDetection;
 
 pub fn postprocess_outputs(
@@ -76,65 +77,5 @@ pub fn postprocess_outputs(
     }
     
     // 应用非最大抑制
-    Ok(apply_nms(detections, 0.4)) // IoU阈值0.4
+    Ok(apply_nms(detections, &NmsConfig::default()))
 }
-
-fn apply_nms(mut detections: Vec<YoloDetection>, iou_threshold: f32) -> Vec<YoloDetection> {
-    if detections.is_empty() {
-        return detections;
-    }
-    
-    // 按置信度排序（降序）
-    detections.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
-    
-    let mut keep = Vec::new();
-    let mut suppressed = vec![false; detections.len()];
-    
-    for i in 0..detections.len() {
-        if suppressed[i] {
-            continue;
-        }
-        
-        keep.push(detections[i].clone());
-        
-        // 计算与所有后续边界框的IoU
-        for j in (i + 1)..detections.len() {
-            if suppressed[j] {
-                continue;
-            }
-            
-            let iou = calculate_iou(&detections[i].bbox, &detections[j].bbox);
-            if iou > iou_threshold {
-                suppressed[j] = true;
-            }
-        }
-    }
-    
-    keep
-}
-
-fn calculate_iou(box1: &[f32; 4], box2: &[f32; 4]) -> f32 {
-    let [x1, y1, w1, h1] = *box1;
-    let [x2, y2, w2, h2] = *box2;
-    
-    // 计算交集
-    let inter_x1 = x1.max(x2);
-    let inter_y1 = y1.max(y2);
-    let inter_x2 = (x1 + w1).min(x2 + w2);
-    let inter_y2 = (y1 + h1).min(y2 + h2);
-    
-    let inter_width = (inter_x2 - inter_x1).max(0.0);
-    let inter_height = (inter_y2 - inter_y1).max(0.0);
-    let inter_area = inter_width * inter_height;
-    
-    // 计算并集
-    let area1 = w1 * h1;
-    let area2 = w2 * h2;
-    let union_area = area1 + area2 - inter_area;
-    
-    if union_area <= 0.0 {
-        0.0
-    } else {
-        inter_area / union_area
-    }
-}
\ No newline at end of file
@@ -1,66 +1,204 @@
-use anyhow::Result;
-use image::{ImageBuffer, Rgb, RgbImage};
-use ndarray::Array4;
-
-pub fn preprocess_image(img: &RgbImage, target_width: usize, target_height: usize) -> Result<Array4<f32>> {
-    // 1. 调整图像大小（保持宽高比）
-    let resized = resize_with_padding(img, target_width as u32, target_height as u32);
-    
-    // 2. 转换为float32并规范化到[0,1]
-    let mut input_array = Array4::<f32>::zeros((1, 3, target_height, target_width));
-    
-    for y in 0..target_height {
-        for x in 0..target_width {
-            let pixel = resized.get_pixel(x as u32, y as u32);
-            
-            // RGB -> 规范化 [0, 1]
-            input_array[[0, 0, y, x]] = pixel[0] as f32 / 255.0;
-            input_array[[0, 1, y, x]] = pixel[1] as f32 / 255.0;
-            input_array[[0, 2, y, x]] = pixel[2] as f32 / 255.0;
+/*!
+图像预处理公共函数
+缩放策略（`ResizeStrategy`）决定原图如何变换到模型输入尺寸：
+
+- letterbox（默认，YOLOv8 标准）：保持宽高比缩放后居中放置，四周用灰色（114）填充
+- stretch：直接拉伸到输入尺寸，宽高比改变但不损失画面、没有填充
+- center-crop：保持宽高比缩放到覆盖输入尺寸后居中裁剪，四周超出部分不参与检测
+
+预处理返回的 `ResizeTransform` 记录实际使用的缩放比例与偏移，后处理按同一变换把检测框换算回原图坐标，
+各推理后端共用此模块，保证两端一致。插值方式（`Interpolation`）与缩放策略一起作为检测配置的 `resize` 项。
+缩放后的图像按行并行转换为 CHW 排列的归一化浮点数据（`to_chw`）；
+批量推理时多张图像并行缩放后依次写入同一块缓冲区，组成 [K, 3, H, W] 输入（`batch_to_chw`）
+*/
+
+use image::{imageops, Rgb, RgbImage};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// YOLOv8 标准填充颜色
+pub const PAD_COLOR: Rgb<u8> = Rgb([114, 114, 114]);
+
+/// 像素值归一化到 [0, 1] 的系数
+const NORMALIZE: f32 = 1.0 / 255.0;
+
+/// 并行转换时每个任务至少处理的行数
+const MIN_ROWS_PER_TASK: usize = 16;
+
+/// 缩放策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResizeStrategy {
+    #[default]
+    Letterbox,
+    Stretch,
+    CenterCrop,
+}
+
+/// 插值方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Interpolation {
+    Nearest,
+    /// 双线性插值，与 Ultralytics 训练时的 `cv2.INTER_LINEAR` 一致，且比 Lanczos3 快得多
+    #[default]
+    Linear,
+    Cubic,
+    Lanczos,
+}
+
+impl Interpolation {
+    fn filter(self) -> imageops::FilterType {
+        match self {
+            Self::Nearest => imageops::FilterType::Nearest,
+            Self::Linear => imageops::FilterType::Triangle,
+            Self::Cubic => imageops::FilterType::CatmullRom,
+            Self::Lanczos => imageops::FilterType::Lanczos3,
+        }
+    }
+}
+
+/// 缩放参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResizeConfig {
+    pub strategy: ResizeStrategy,
+    pub interpolation: Interpolation,
+}
+
+/// 原图 -> 模型输入的变换参数：输入坐标 = 原图坐标 * scale + offset
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResizeTransform {
+    pub scale: (f32, f32),          // 水平/垂直缩放比例（仅 stretch 两者不同）
+    pub offset: (f32, f32),         // 原图原点在输入画布上的位置（letterbox 为填充，center-crop 为负的裁剪量）
+    pub crop: (u32, u32, u32, u32), // 参与缩放的原图区域 (x, y, width, height)
+    pub new_size: (u32, u32),       // 该区域缩放后的尺寸
+    pub paste: (u32, u32),          // 缩放结果在画布上的左上角
+}
+
+impl ResizeTransform {
+    /// 根据原图尺寸、模型输入尺寸与缩放策略计算变换参数
+    pub fn new(original_size: (u32, u32), input_size: (u32, u32), strategy: ResizeStrategy) -> Self {
+        let (orig_width, orig_height) = (original_size.0.max(1), original_size.1.max(1));
+        let scale_x = input_size.0 as f32 / orig_width as f32;
+        let scale_y = input_size.1 as f32 / orig_height as f32;
+
+        match strategy {
+            ResizeStrategy::Letterbox => {
+                let scale = scale_x.min(scale_y);
+                let new_width = ((orig_width as f32 * scale).round() as u32).clamp(1, input_size.0);
+                let new_height = ((orig_height as f32 * scale).round() as u32).clamp(1, input_size.1);
+                let pad = ((input_size.0 - new_width) / 2, (input_size.1 - new_height) / 2);
+                Self {
+                    scale: (scale, scale),
+                    offset: (pad.0 as f32, pad.1 as f32),
+                    crop: (0, 0, orig_width, orig_height),
+                    new_size: (new_width, new_height),
+                    paste: pad,
+                }
+            }
+            ResizeStrategy::Stretch => Self {
+                scale: (scale_x, scale_y),
+                offset: (0.0, 0.0),
+                crop: (0, 0, orig_width, orig_height),
+                new_size: input_size,
+                paste: (0, 0),
+            },
+            ResizeStrategy::CenterCrop => {
+                // 先在原图上裁出与输入宽高比相同的居中区域，再缩放到输入尺寸
+                let scale = scale_x.max(scale_y);
+                let crop_width = ((input_size.0 as f32 / scale).round() as u32).clamp(1, orig_width);
+                let crop_height = ((input_size.1 as f32 / scale).round() as u32).clamp(1, orig_height);
+                let crop_x = (orig_width - crop_width) / 2;
+                let crop_y = (orig_height - crop_height) / 2;
+                let scale = (input_size.0 as f32 / crop_width as f32, input_size.1 as f32 / crop_height as f32);
+                Self {
+                    scale,
+                    offset: (-(crop_x as f32) * scale.0, -(crop_y as f32) * scale.1),
+                    crop: (crop_x, crop_y, crop_width, crop_height),
+                    new_size: input_size,
+                    paste: (0, 0),
+                }
+            }
         }
     }
-    
-    Ok(input_array)
+
+    /// 把输入尺寸下的中心点格式框 (cx, cy, w, h) 换算为原图下的 [x, y, width, height]，并裁剪到原图范围
+    pub fn unmap_box(&self, center_x: f32, center_y: f32, width: f32, height: f32, original_size: (u32, u32)) -> [f32; 4] {
+        let (orig_width, orig_height) = (original_size.0 as f32, original_size.1 as f32);
+        let x1 = ((center_x - width / 2.0 - self.offset.0) / self.scale.0).clamp(0.0, orig_width);
+        let y1 = ((center_y - height / 2.0 - self.offset.1) / self.scale.1).clamp(0.0, orig_height);
+        let x2 = ((center_x + width / 2.0 - self.offset.0) / self.scale.0).clamp(0.0, orig_width);
+        let y2 = ((center_y + height / 2.0 - self.offset.1) / self.scale.1).clamp(0.0, orig_height);
+        [x1, y1, x2 - x1, y2 - y1]
+    }
 }
 
-fn resize_with_padding(img: &RgbImage, target_width: u32, target_height: u32) -> RgbImage {
-    let (orig_width, orig_height) = img.dimensions();
-    
-    // 计算缩放比例
-    let scale = (target_width as f32 / orig_width as f32)
-        .min(target_height as f32 / orig_height as f32);
-    
-    let new_width = (orig_width as f32 * scale) as u32;
-    let new_height = (orig_height as f32 * scale) as u32;
-    
-    // 调整图像大小
-    let resized = image::imageops::resize(
-        img,
-        new_width,
-        new_height,
-        image::imageops::FilterType::Lanczos3
+/// 按缩放参数把原图变换到输入尺寸，结果写入调用方逐帧复用的画布（尺寸不符时重新分配）
+pub fn resize_into(img: &RgbImage, input_size: (u32, u32), config: &ResizeConfig, canvas: &mut RgbImage) -> ResizeTransform {
+    let transform = ResizeTransform::new(img.dimensions(), input_size, config.strategy);
+    let (crop_x, crop_y, crop_width, crop_height) = transform.crop;
+    let source = imageops::crop_imm(img, crop_x, crop_y, crop_width, crop_height);
+    let resized = imageops::resize(
+        &*source,
+        transform.new_size.0,
+        transform.new_size.1,
+        config.interpolation.filter(),
     );
-    
-    // 创建目标图像并居中放置
-    let mut result = ImageBuffer::new(target_width, target_height);
-    
-    // 填充灰色背景（114, 114, 114） - YOLOv8标准
-    for pixel in result.pixels_mut() {
-        *pixel = Rgb([114, 114, 114]);
+
+    if canvas.dimensions() != input_size {
+        *canvas = RgbImage::new(input_size.0, input_size.1);
     }
-    
-    // 计算居中位置
-    let offset_x = (target_width - new_width) / 2;
-    let offset_y = (target_height - new_height) / 2;
-    
-    // 复制调整大小后的图像到中心位置
-    for y in 0..new_height {
-        for x in 0..new_width {
-            if let Some(pixel) = resized.get_pixel_checked(x, y) {
-                result.put_pixel(offset_x + x, offset_y + y, *pixel);
-            }
+    if transform.new_size != input_size {
+        for pixel in canvas.pixels_mut() {
+            *pixel = PAD_COLOR;
         }
     }
-    
-    result
-}
\ No newline at end of file
+    imageops::replace(canvas, &resized, transform.paste.0 as i64, transform.paste.1 as i64);
+
+    transform
+}
+
+/// HWC 排列的 RGB 图像转换为 CHW 排列（先所有R，再所有G，最后所有B）并归一化到 [0, 1]，
+/// 按行分块并行处理；`out` 至少需要 3 * 宽 * 高 个元素
+pub fn to_chw(img: &RgbImage, out: &mut [f32]) {
+    let width = img.width() as usize;
+    let plane = width * img.height() as usize;
+    if plane == 0 {
+        return;
+    }
+    let (red, rest) = out[..3 * plane].split_at_mut(plane);
+    let (green, blue) = rest.split_at_mut(plane);
+
+    img.as_raw()
+        .par_chunks_exact(width * 3)
+        .zip(red.par_chunks_exact_mut(width))
+        .zip(green.par_chunks_exact_mut(width))
+        .zip(blue.par_chunks_exact_mut(width))
+        .with_min_len(MIN_ROWS_PER_TASK)
+        .for_each(|(((row, red), green), blue)| {
+            let channels = red.iter_mut().zip(green.iter_mut()).zip(blue.iter_mut());
+            for (pixel, ((r, g), b)) in row.chunks_exact(3).zip(channels) {
+                *r = pixel[0] as f32 * NORMALIZE;
+                *g = pixel[1] as f32 * NORMALIZE;
+                *b = pixel[2] as f32 * NORMALIZE;
+            }
+        });
+}
+
+/// 多张图像分别缩放后按顺序写入 `out`，组成 [K, 3, H, W] 的批输入，各图并行处理；
+/// `out` 至少需要 K * 3 * 宽 * 高 个元素
+pub fn batch_to_chw(images: &[RgbImage], input_size: (u32, u32), config: &ResizeConfig, out: &mut [f32]) {
+    let item_len = 3 * input_size.0 as usize * input_size.1 as usize;
+    if item_len == 0 {
+        return;
+    }
+    images
+        .par_iter()
+        .zip(out[..images.len() * item_len].par_chunks_exact_mut(item_len))
+        .for_each(|(img, out)| {
+            let mut canvas = RgbImage::new(input_size.0, input_size.1);
+            resize_into(img, input_size, config, &mut canvas);
+            to_chw(&canvas, out);
+        });
+}
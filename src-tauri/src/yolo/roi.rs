@@ -0,0 +1,79 @@
+/*!
+检测区域（ROI）
+用户在画面上框定一个或多个多边形区域（如只检测传送带所在区域），
+检测框中心点落在任一区域内才保留；未设置区域时检测整幅画面
+
+多边形顶点使用相对图像宽高的归一化坐标（0-1），同一配置适用于不同分辨率的输入源
+*/
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use super::YoloDetection;
+
+/// 一个检测区域
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoiPolygon {
+    #[serde(default)]
+    pub name: String,
+    pub points: Vec<[f32; 2]>, // 顶点 [x, y]，归一化坐标
+}
+
+impl RoiPolygon {
+    /// 归一化坐标点是否在多边形内（射线法）
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        if self.points.len() < 3 {
+            return false;
+        }
+        let mut inside = false;
+        let mut j = self.points.len() - 1;
+        for (i, [xi, yi]) in self.points.iter().copied().enumerate() {
+            let [xj, yj] = self.points[j];
+            if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+                inside = !inside;
+            }
+            j = i;
+        }
+        inside
+    }
+
+    /// 换算到指定尺寸图像上的像素坐标
+    pub fn to_pixels(&self, width: u32, height: u32) -> Vec<(f32, f32)> {
+        self.points
+            .iter()
+            .map(|[x, y]| (x * width as f32, y * height as f32))
+            .collect()
+    }
+}
+
+pub fn validate(regions: &[RoiPolygon]) -> Result<()> {
+    for (index, region) in regions.iter().enumerate() {
+        let label = if region.name.is_empty() { format!("#{}", index + 1) } else { region.name.clone() };
+        if region.points.len() < 3 {
+            return Err(anyhow!("检测区域 {} 至少需要3个顶点", label));
+        }
+        if region
+            .points
+            .iter()
+            .any(|[x, y]| !(0.0..=1.0).contains(x) || !(0.0..=1.0).contains(y))
+        {
+            return Err(anyhow!("检测区域 {} 的顶点坐标须为 0-1 之间的归一化坐标", label));
+        }
+    }
+    Ok(())
+}
+
+/// 检测框中心点是否在任一检测区域内（未设置区域时总是为 true）
+pub fn contains_detection(regions: &[RoiPolygon], detection: &YoloDetection, image_size: (u32, u32)) -> bool {
+    if regions.is_empty() {
+        return true;
+    }
+    let (width, height) = image_size;
+    if width == 0 || height == 0 {
+        return true;
+    }
+    let [x, y, w, h] = detection.bbox;
+    let center_x = (x + w / 2.0) / width as f32;
+    let center_y = (y + h / 2.0) / height as f32;
+    regions.iter().any(|region| region.contains(center_x, center_y))
+}
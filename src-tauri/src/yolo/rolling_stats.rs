@@ -0,0 +1,132 @@
+/*!
+滚动性能统计
+保留最近若干次检测的耗时样本，计算滚动平均帧率与 p50/p95/p99 耗时，
+并按分钟汇总吞吐量，供界面绘制性能曲线（`get_stats_timeseries`）
+*/
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::ModelStats;
+
+/// 滚动窗口保留的检测样本数
+pub const WINDOW_SIZE: usize = 600;
+
+/// 按分钟汇总吞吐量时保留的分钟数
+const THROUGHPUT_MINUTES: usize = 60;
+
+const MINUTE_MS: i64 = 60_000;
+
+/// 单次检测的耗时样本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSample {
+    pub timestamp_ms: i64, // Unix 毫秒时间戳
+    pub latency_ms: f64,
+    pub fps: f64, // 按本次耗时折算的帧率
+}
+
+/// 一分钟内完成的检测次数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinuteThroughput {
+    pub minute_start_ms: i64, // 该分钟起始的 Unix 毫秒时间戳
+    pub count: u64,
+}
+
+/// 性能曲线数据
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatsTimeseries {
+    pub samples: Vec<StatsSample>,         // 按时间顺序
+    pub throughput: Vec<MinuteThroughput>, // 按时间顺序，最后一项为当前（未满）分钟
+}
+
+/// 最近检测的耗时窗口
+#[derive(Debug, Default)]
+pub struct RollingStats {
+    samples: VecDeque<StatsSample>,
+    minutes: VecDeque<MinuteThroughput>,
+    last_minute: VecDeque<i64>, // 最近60秒内各次检测的时间戳
+}
+
+impl RollingStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次检测的总耗时
+    pub fn record(&mut self, latency_ms: f64) {
+        let timestamp_ms = chrono::Utc::now().timestamp_millis();
+        let fps = if latency_ms > 0.0 { 1000.0 / latency_ms } else { 0.0 };
+        if self.samples.len() >= WINDOW_SIZE {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(StatsSample {
+            timestamp_ms,
+            latency_ms,
+            fps,
+        });
+
+        self.last_minute.push_back(timestamp_ms);
+        while self.last_minute.front().is_some_and(|at| *at <= timestamp_ms - MINUTE_MS) {
+            self.last_minute.pop_front();
+        }
+
+        let minute_start_ms = timestamp_ms - timestamp_ms.rem_euclid(MINUTE_MS);
+        match self.minutes.back_mut() {
+            Some(bucket) if bucket.minute_start_ms == minute_start_ms => bucket.count += 1,
+            _ => {
+                if self.minutes.len() >= THROUGHPUT_MINUTES {
+                    self.minutes.pop_front();
+                }
+                self.minutes.push_back(MinuteThroughput {
+                    minute_start_ms,
+                    count: 1,
+                });
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.minutes.clear();
+        self.last_minute.clear();
+    }
+
+    /// 把窗口内的滚动统计写入 `ModelStats`
+    pub fn fill(&self, stats: &mut ModelStats) {
+        stats.window_size = self.samples.len();
+        if self.samples.is_empty() {
+            return;
+        }
+
+        let mut latencies: Vec<f64> = self.samples.iter().map(|sample| sample.latency_ms).collect();
+        latencies.sort_by(|a, b| a.total_cmp(b));
+        let mean = latencies.iter().sum::<f64>() / latencies.len() as f64;
+        stats.avg_fps = if mean > 0.0 { 1000.0 / mean } else { 0.0 };
+        stats.p50_latency_ms = percentile(&latencies, 50.0);
+        stats.p95_latency_ms = percentile(&latencies, 95.0);
+        stats.p99_latency_ms = percentile(&latencies, 99.0);
+
+        let since_ms = chrono::Utc::now().timestamp_millis() - MINUTE_MS;
+        stats.throughput_per_minute = self.last_minute.iter().filter(|at| **at > since_ms).count() as u64;
+    }
+
+    /// 最近 limit 个样本与按分钟的吞吐量
+    pub fn timeseries(&self, limit: usize) -> StatsTimeseries {
+        StatsTimeseries {
+            samples: self
+                .samples
+                .iter()
+                .skip(self.samples.len().saturating_sub(limit))
+                .cloned()
+                .collect(),
+            throughput: self.minutes.iter().cloned().collect(),
+        }
+    }
+}
+
+/// 最近秩法取百分位（输入已升序排列且非空）
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
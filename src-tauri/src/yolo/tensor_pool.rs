@@ -0,0 +1,156 @@
+/*!
+共享张量缓冲池
+按元素数量分级（2的幂）缓存 f32 缓冲区，在各次检测调用和各检测会话之间复用，
+减少持续30FPS运行时的内存分配抖动。
+检测器加载模型或修改输入尺寸时按输入/输出张量大小预留缓冲区（`reserve`），摄像头模式下每帧不再分配
+*/
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::{Deref, DerefMut};
+use std::sync::OnceLock;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// 每个尺寸级别最多保留的空闲缓冲区数量
+const MAX_FREE_PER_CLASS: usize = 4;
+
+/// 缓冲池统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TensorPoolStats {
+    pub allocations: u64,  // 新分配的缓冲区数量
+    pub reuses: u64,       // 从池中复用的次数
+    pub pooled_buffers: usize,
+    pub pooled_bytes: u64,
+    pub in_use_bytes: u64,
+    #[serde(default)]
+    pub free_by_size: BTreeMap<usize, usize>, // 容量级别（元素数）-> 空闲缓冲区数量
+}
+
+#[derive(Default)]
+struct PoolInner {
+    free: HashMap<usize, Vec<Vec<f32>>>, // 按容量级别存放的空闲缓冲区
+    stats: TensorPoolStats,
+}
+
+/// 尺寸分级的 f32 缓冲池
+#[derive(Default)]
+pub struct TensorPool {
+    inner: Mutex<PoolInner>,
+}
+
+/// 从池中借出的缓冲区，离开作用域时自动归还
+pub struct PooledBuffer<'a> {
+    pool: &'a TensorPool,
+    data: Vec<f32>,
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = Vec<f32>;
+
+    fn deref(&self) -> &Vec<f32> {
+        &self.data
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Vec<f32> {
+        &mut self.data
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        self.pool.release(std::mem::take(&mut self.data));
+    }
+}
+
+fn size_class(len: usize) -> usize {
+    len.max(1).next_power_of_two()
+}
+
+impl TensorPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 借出长度为 len、内容清零的缓冲区
+    pub fn acquire(&self, len: usize) -> PooledBuffer<'_> {
+        let class = size_class(len);
+        let reused = {
+            let mut inner = self.inner.lock();
+            let buffer = inner.free.get_mut(&class).and_then(|list| list.pop());
+            match buffer {
+                Some(_) => {
+                    inner.stats.reuses += 1;
+                    inner.stats.pooled_buffers -= 1;
+                    inner.stats.pooled_bytes -= (class * 4) as u64;
+                }
+                None => inner.stats.allocations += 1,
+            }
+            inner.stats.in_use_bytes += (class * 4) as u64;
+            buffer
+        };
+
+        let mut data = reused.unwrap_or_else(|| Vec::with_capacity(class));
+        data.clear();
+        data.resize(len, 0.0);
+        PooledBuffer { pool: self, data }
+    }
+
+    fn release(&self, data: Vec<f32>) {
+        let class = data.capacity();
+        let mut inner = self.inner.lock();
+        inner.stats.in_use_bytes = inner.stats.in_use_bytes.saturating_sub((class * 4) as u64);
+        // 容量不是2的幂说明缓冲区被外部重新分配过，不再回收
+        if !class.is_power_of_two() {
+            return;
+        }
+        let list = inner.free.entry(class).or_default();
+        if list.len() < MAX_FREE_PER_CLASS {
+            list.push(data);
+            inner.stats.pooled_buffers += 1;
+            inner.stats.pooled_bytes += (class * 4) as u64;
+        }
+    }
+
+    /// 预先分配 count 个可容纳 len 个元素的缓冲区（已有的空闲缓冲区计入，每级最多保留上限个）
+    pub fn reserve(&self, len: usize, count: usize) {
+        let class = size_class(len);
+        let mut inner = self.inner.lock();
+        let free = inner.free.get(&class).map_or(0, Vec::len);
+        let missing = count.min(MAX_FREE_PER_CLASS).saturating_sub(free);
+        for _ in 0..missing {
+            inner.free.entry(class).or_default().push(Vec::with_capacity(class));
+            inner.stats.allocations += 1;
+            inner.stats.pooled_buffers += 1;
+            inner.stats.pooled_bytes += (class * 4) as u64;
+        }
+    }
+
+    /// 释放全部空闲缓冲区（内存紧张时调用），返回释放的字节数
+    pub fn shrink(&self) -> u64 {
+        let mut inner = self.inner.lock();
+        inner.free.clear();
+        inner.stats.pooled_buffers = 0;
+        std::mem::take(&mut inner.stats.pooled_bytes)
+    }
+
+    pub fn stats(&self) -> TensorPoolStats {
+        let inner = self.inner.lock();
+        let mut stats = inner.stats.clone();
+        stats.free_by_size = inner
+            .free
+            .iter()
+            .filter(|(_, list)| !list.is_empty())
+            .map(|(class, list)| (*class, list.len()))
+            .collect();
+        stats
+    }
+}
+
+/// 进程内共享的缓冲池
+pub fn global() -> &'static TensorPool {
+    static POOL: OnceLock<TensorPool> = OnceLock::new();
+    POOL.get_or_init(TensorPool::new)
+}
@@ -0,0 +1,91 @@
+/*!
+切片推理（SAHI 式）
+线扫相机的图像宽度常在 8000 像素以上，整幅缩放到模型输入尺寸后细小缺陷只剩几个像素。
+切片模式把大图按固定尺寸、带重叠地切成若干块，每块单独检测（按批合并推理），
+检测框平移回原图坐标后与（可选的）整图检测结果合并，再统一做一次全局NMS去除重叠块中的重复框
+*/
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// 切片参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TilingConfig {
+    pub enabled: bool,
+    pub tile_size: (u32, u32),     // 切片尺寸 (width, height)
+    pub overlap: f32,              // 相邻切片的重叠比例（0-0.9）
+    pub min_image_size: u32,       // 长边不超过该像素数的图像不切片
+    pub include_full_image: bool,  // 同时检测整幅图像，保留切片放不下的大目标
+}
+
+impl Default for TilingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tile_size: (640, 640),
+            overlap: 0.2,
+            min_image_size: 1280,
+            include_full_image: true,
+        }
+    }
+}
+
+impl TilingConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.tile_size.0 < 32 || self.tile_size.1 < 32 {
+            return Err(anyhow!("切片尺寸不能小于32像素: {}x{}", self.tile_size.0, self.tile_size.1));
+        }
+        if !(0.0..=0.9).contains(&self.overlap) {
+            return Err(anyhow!("切片重叠比例必须在 0-0.9 之间: {}", self.overlap));
+        }
+        Ok(())
+    }
+
+    /// 该尺寸的图像是否按切片检测
+    pub fn applies_to(&self, image_size: (u32, u32)) -> bool {
+        self.enabled
+            && image_size.0.max(image_size.1) > self.min_image_size
+            && (image_size.0 > self.tile_size.0 || image_size.1 > self.tile_size.1)
+    }
+}
+
+/// 原图上的一个矩形区域（像素）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 一个方向上的切片起点：步长为切片尺寸扣除重叠部分，最后一块贴齐图像边缘
+fn tile_starts(length: u32, tile: u32, overlap: f32) -> Vec<u32> {
+    if length <= tile {
+        return vec![0];
+    }
+    let step = ((tile as f32 * (1.0 - overlap)).round() as u32).max(1);
+    let last = length - tile;
+    let mut starts: Vec<u32> = (0..last).step_by(step as usize).collect();
+    starts.push(last);
+    starts
+}
+
+/// 按切片参数划分原图，按行优先顺序返回；包含整图时整图排在最后
+pub fn plan(image_size: (u32, u32), config: &TilingConfig) -> Vec<Tile> {
+    let (width, height) = image_size;
+    let tile_width = config.tile_size.0.min(width);
+    let tile_height = config.tile_size.1.min(height);
+    let mut tiles: Vec<Tile> = tile_starts(height, tile_height, config.overlap)
+        .into_iter()
+        .flat_map(|y| {
+            tile_starts(width, tile_width, config.overlap)
+                .into_iter()
+                .map(move |x| Tile { x, y, width: tile_width, height: tile_height })
+        })
+        .collect();
+    if config.include_full_image {
+        tiles.push(Tile { x: 0, y: 0, width, height });
+    }
+    tiles
+}
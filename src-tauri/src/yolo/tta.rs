@@ -0,0 +1,213 @@
+/*!
+测试时增强（TTA）
+离线复核时对同一张图片做多次推理以换取更高的召回：原图与水平翻转图各按若干输入尺度推理，
+检测框换算回原图坐标后用加权框融合（WBF）合并——同一目标在各次推理中的框按置信度加权平均坐标，
+置信度取平均后按出现次数折减，只在少数增强中出现的框得分较低。
+耗时约为普通检测的（翻转 ×2）× 尺度数 倍，因此按每次运行单独开启；输入尺寸固定的模型只做翻转
+*/
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use super::nms::calculate_iou;
+use super::YoloDetection;
+
+/// TTA 参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TtaConfig {
+    pub flip: bool,        // 同时检测水平翻转图
+    pub scales: Vec<f32>,  // 输入尺寸的缩放倍数（按32取整）
+    pub fusion_iou: f32,   // WBF 中归为同一目标的 IoU 阈值
+}
+
+impl Default for TtaConfig {
+    fn default() -> Self {
+        Self {
+            flip: true,
+            scales: vec![1.0, 0.83, 0.67],
+            fusion_iou: 0.55,
+        }
+    }
+}
+
+impl TtaConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.scales.is_empty() {
+            return Err(anyhow!("TTA至少需要一个输入尺度"));
+        }
+        if let Some(scale) = self.scales.iter().find(|s| !(0.25..=2.0).contains(*s)) {
+            return Err(anyhow!("TTA输入尺度必须在 0.25-2 之间: {}", scale));
+        }
+        if !(0.0..=1.0).contains(&self.fusion_iou) {
+            return Err(anyhow!("WBF IoU阈值必须在 0-1 之间: {}", self.fusion_iou));
+        }
+        Ok(())
+    }
+}
+
+/// 一次增强推理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Augmentation {
+    pub flip: bool,
+    pub input_size: (u32, u32),
+}
+
+/// 按 TTA 参数列出各次推理，相同输入尺寸的增强相邻排列（便于合为一批）；
+/// fixed_input 为 true 时模型只接受 input_size，跳过其他尺度
+pub fn plan(config: &TtaConfig, input_size: (u32, u32), fixed_input: bool) -> Vec<Augmentation> {
+    let mut sizes: Vec<(u32, u32)> = if fixed_input {
+        vec![input_size]
+    } else {
+        config.scales.iter().map(|&scale| scale_size(input_size, scale)).collect()
+    };
+    sizes.dedup();
+    sizes
+        .into_iter()
+        .flat_map(|size| {
+            let flips: &[bool] = if config.flip { &[false, true] } else { &[false] };
+            flips.iter().map(move |&flip| Augmentation { flip, input_size: size })
+        })
+        .collect()
+}
+
+/// 缩放后的输入尺寸取最接近的32的倍数
+fn scale_size(size: (u32, u32), scale: f32) -> (u32, u32) {
+    let round = |value: u32| (((value as f32 * scale / 32.0).round() as u32).max(1)) * 32;
+    (round(size.0), round(size.1))
+}
+
+/// 翻转图上的检测框换算回原图坐标
+pub fn unflip(bbox: &mut [f32; 4], image_width: u32) {
+    bbox[0] = image_width as f32 - bbox[0] - bbox[2];
+}
+
+/// 融合中的一组检测框（按置信度从高到低加入）
+struct Cluster {
+    fused: YoloDetection,
+    members: Vec<YoloDetection>,
+}
+
+impl Cluster {
+    /// 按置信度加权平均各成员的角点坐标
+    fn refuse(&mut self) {
+        let total: f32 = self.members.iter().map(|d| d.confidence).sum();
+        if total <= 0.0 {
+            return;
+        }
+        let mut corners = [0.0f32; 4];
+        for member in &self.members {
+            let [x, y, width, height] = member.bbox;
+            for (corner, value) in corners.iter_mut().zip([x, y, x + width, y + height]) {
+                *corner += value * member.confidence;
+            }
+        }
+        let [x1, y1, x2, y2] = corners.map(|c| c / total);
+        self.fused.bbox = [x1, y1, x2 - x1, y2 - y1];
+        self.fused.confidence = total / self.members.len() as f32;
+    }
+}
+
+/// 加权框融合：各次推理的检测框按置信度降序依次归入 IoU 超过阈值的已有组（不跨类别时只与同类别比较），
+/// 组内坐标按置信度加权平均；出现次数少于推理次数的组置信度按比例折减，类别取组内置信度最高的框
+pub fn weighted_box_fusion(passes: Vec<Vec<YoloDetection>>, iou_threshold: f32, class_agnostic: bool) -> Vec<YoloDetection> {
+    let pass_count = passes.len().max(1);
+    let mut detections: Vec<YoloDetection> = passes.into_iter().flatten().collect();
+    detections.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    let mut clusters: Vec<Cluster> = Vec::new();
+    for detection in detections {
+        let matched = clusters
+            .iter_mut()
+            .filter(|cluster| class_agnostic || cluster.fused.class_id == detection.class_id)
+            .map(|cluster| (calculate_iou(&cluster.fused.bbox, &detection.bbox), cluster))
+            .filter(|(iou, _)| *iou > iou_threshold)
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+        match matched {
+            Some((_, cluster)) => {
+                cluster.members.push(detection);
+                cluster.refuse();
+            }
+            None => clusters.push(Cluster {
+                fused: detection.clone(),
+                members: vec![detection],
+            }),
+        }
+    }
+
+    clusters
+        .into_iter()
+        .map(|cluster| {
+            let mut fused = cluster.fused;
+            fused.confidence *= cluster.members.len().min(pass_count) as f32 / pass_count as f32;
+            fused
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detection(class_id: u32, confidence: f32, bbox: [f32; 4]) -> YoloDetection {
+        YoloDetection {
+            class_id,
+            class_name: format!("class_{}", class_id),
+            confidence,
+            bbox,
+            size_mm: None,
+        }
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn flipped_box_maps_back_to_original_coordinates() {
+        // 宽 640 的图上 [100, 50, 40, 30] 水平翻转后位于 x = 640 - 100 - 40 = 500
+        let mut bbox = [500.0, 50.0, 40.0, 30.0];
+        unflip(&mut bbox, 640);
+        assert_eq!(bbox, [100.0, 50.0, 40.0, 30.0]);
+
+        unflip(&mut bbox, 640);
+        assert_eq!(bbox, [500.0, 50.0, 40.0, 30.0]);
+    }
+
+    #[test]
+    fn wbf_merges_overlapping_boxes_with_score_weighted_coordinates() {
+        let passes = vec![
+            vec![detection(0, 0.9, [100.0, 100.0, 100.0, 100.0]), detection(0, 0.8, [400.0, 400.0, 50.0, 50.0])],
+            vec![detection(0, 0.6, [110.0, 100.0, 100.0, 100.0])],
+        ];
+        let fused = weighted_box_fusion(passes, 0.55, false);
+        assert_eq!(fused.len(), 2);
+
+        // 角点按置信度加权：x1 = (100 × 0.9 + 110 × 0.6) / 1.5 = 104，x2 = (200 × 0.9 + 210 × 0.6) / 1.5 = 204
+        assert_close(&fused[0].bbox, &[104.0, 100.0, 100.0, 100.0]);
+        // 平均置信度 0.75，两次推理均出现不折减
+        assert_close(&[fused[0].confidence], &[0.75]);
+
+        // 只在一次推理中出现的框置信度减半，坐标不变
+        assert_close(&fused[1].bbox, &[400.0, 400.0, 50.0, 50.0]);
+        assert_close(&[fused[1].confidence], &[0.4]);
+    }
+
+    #[test]
+    fn wbf_keeps_classes_apart_unless_class_agnostic() {
+        let passes = || {
+            vec![
+                vec![detection(0, 0.9, [100.0, 100.0, 100.0, 100.0])],
+                vec![detection(1, 0.6, [105.0, 100.0, 100.0, 100.0])],
+            ]
+        };
+        assert_eq!(weighted_box_fusion(passes(), 0.55, false).len(), 2);
+
+        let fused = weighted_box_fusion(passes(), 0.55, true);
+        assert_eq!(fused.len(), 1);
+        assert_eq!(fused[0].class_id, 0);
+    }
+}
@@ -0,0 +1,342 @@
+/*!
+检测结果统一数据结构
+所有推理后端、Tauri命令、HTTP接口、检测历史与无界面模式共用同一套检测结果类型，
+字段名即序列化后的JSON键名，前端与外部系统依赖这些键名，修改字段时只能新增（带默认值），不能改名或删除。
+
+`DetectionResult` 序列化后的JSON结构：
+
+```json
+{
+  "detections": [
+    {
+      "class_id": 0,
+      "class_name": "异常",
+      "confidence": 0.85,
+      "bbox": [120.0, 80.0, 64.0, 48.0],
+      "size_mm": { "width_mm": 12.8, "height_mm": 9.6, "area_mm2": 122.88 }
+    }
+  ],
+  "image_width": 1920,
+  "image_height": 1080,
+  "processing_time_ms": 35,
+  "model_input_size": [640, 640],
+  "thresholds": { "异常": 0.5 },
+  "track_ids": [3],
+  "timings": { "decode_ms": 2.1, "preprocess_ms": 3.4, "inference_ms": 25.0, "postprocess_ms": 1.2, "draw_ms": 0.0, "encode_ms": 0.0 },
+  "frame": { "source_id": "camera_0", "frame_index": 1024, "captured_at": "2024-05-01T08:00:00.123Z", "sequence": 5678 }
+}
+```
+
+- `bbox` 为原图像素坐标 `[x, y, width, height]`（左上角与宽高）
+- `size_mm` 仅在标定了像素/毫米比例时出现
+- `thresholds`、`track_ids`、`timings`、`frame` 缺省时按默认值解析，旧版本保存的结果仍可读取
+
+`InputSource` 按外部标签序列化：`{"Camera": 0}`、`{"Video": "a.mp4"}`、`{"Image": "a.jpg"}`、`{"Rtsp": "rtsp://..."}`
+*/
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::capture;
+
+/// YOLO检测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YoloDetection {
+    pub class_id: u32,
+    pub class_name: String,
+    pub confidence: f32,
+    pub bbox: [f32; 4], // [x, y, width, height] - 相对于原图的坐标
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_mm: Option<PhysicalSize>, // 物理尺寸，标定了像素/毫米比例时填写
+}
+
+/// 检测框的物理尺寸（毫米）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PhysicalSize {
+    pub width_mm: f32,
+    pub height_mm: f32,
+    pub area_mm2: f32,
+}
+
+impl PhysicalSize {
+    /// 按像素/毫米比例换算检测框 [x, y, width, height] 的尺寸
+    pub fn from_bbox(bbox: &[f32; 4], px_per_mm: f32) -> Self {
+        let width_mm = bbox[2] / px_per_mm;
+        let height_mm = bbox[3] / px_per_mm;
+        Self {
+            width_mm,
+            height_mm,
+            area_mm2: width_mm * height_mm,
+        }
+    }
+
+    /// 较长边的长度
+    pub fn max_side_mm(&self) -> f32 {
+        self.width_mm.max(self.height_mm)
+    }
+}
+
+/// 结果对应的输入帧，便于与外部日志、录像对齐
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FrameInfo {
+    pub source_id: Option<String>,   // 输入源标识（摄像头/视频流/视频文件的会话键，单张图片为路径）
+    pub frame_index: Option<u64>,    // 视频文件或实时输入中的帧序号
+    pub captured_at: Option<String>, // 采集时间（RFC3339，毫秒精度），单张图片为收到图片的时间
+    pub sequence: u64,               // 进程内单调递增的结果序号（从1开始）
+}
+
+impl FrameInfo {
+    /// 分配下一个结果序号
+    pub fn next() -> Self {
+        static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+        Self {
+            sequence: SEQUENCE.fetch_add(1, Ordering::Relaxed) + 1,
+            ..Default::default()
+        }
+    }
+}
+
+/// 检测结果包装
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionResult {
+    pub detections: Vec<YoloDetection>,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub processing_time_ms: u64,
+    pub model_input_size: (u32, u32),
+    #[serde(default)]
+    pub thresholds: HashMap<String, f32>, // 本次检测使用的各类别置信度阈值
+    #[serde(default)]
+    pub track_ids: Vec<Option<u64>>, // 与 detections 一一对应的跟踪ID，仅实时检测启用跟踪时填写
+    #[serde(default)]
+    pub timings: DetectionTimings, // 各阶段耗时，用于排查慢帧
+    #[serde(default)]
+    pub frame: FrameInfo, // 输入源与帧信息
+}
+
+impl DetectionResult {
+    /// 填写输入源与帧信息，未给出采集时间时取当前时间
+    pub fn set_frame(&mut self, source_id: &str, frame_index: Option<u64>, captured_at: Option<String>) {
+        self.frame.source_id = Some(source_id.to_string());
+        self.frame.frame_index = frame_index;
+        self.frame.captured_at = Some(captured_at.unwrap_or_else(|| {
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+        }));
+    }
+
+    /// 按像素/毫米比例填写各检测框的物理尺寸，未标定时清空
+    pub fn apply_scale(&mut self, px_per_mm: Option<f32>) {
+        for detection in &mut self.detections {
+            detection.size_mm = px_per_mm.map(|scale| PhysicalSize::from_bbox(&detection.bbox, scale));
+        }
+    }
+}
+
+/// 整图分类的一个类别得分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassPrediction {
+    pub class_id: u32,
+    pub class_name: String,
+    pub probability: f32,
+}
+
+/// 整图分类结果（按概率从高到低）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassificationResult {
+    pub predictions: Vec<ClassPrediction>,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub processing_time_ms: u64,
+    pub model_input_size: (u32, u32),
+    #[serde(default)]
+    pub timings: DetectionTimings,
+}
+
+/// 检测各阶段耗时（毫秒），未经过的阶段为0。
+/// 解码到后处理由检测器填写，绘制与编码由调用方在生成标注图时填写
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DetectionTimings {
+    pub decode_ms: f64,      // 图像解码
+    pub preprocess_ms: f64,  // 缩放与张量转换
+    pub inference_ms: f64,   // 模型推理
+    pub postprocess_ms: f64, // 解析输出、NMS与过滤
+    pub draw_ms: f64,        // 绘制检测框
+    pub encode_ms: f64,      // 图像编码（实时帧编码为JPEG、标注图编码）
+}
+
+impl DetectionTimings {
+    /// 从 start 到现在经过的毫秒数
+    pub fn since(start: std::time::Instant) -> f64 {
+        start.elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+/// 输入源类型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum InputSource {
+    Camera(i32),    // 摄像头设备ID
+    Video(String),  // 视频文件路径
+    Image(String),  // 图片文件路径
+    Rtsp(String),   // RTSP/HTTP网络摄像头地址
+}
+
+impl InputSource {
+    /// 输入源描述（日志、告警与监控窗口展示）
+    pub fn describe(&self) -> String {
+        match self {
+            InputSource::Camera(device_id) => format!("摄像头 {}", device_id),
+            InputSource::Video(path) | InputSource::Image(path) => path.clone(),
+            InputSource::Rtsp(url) => capture::redact_url(url),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_detection() -> YoloDetection {
+        YoloDetection {
+            class_id: 0,
+            class_name: "异常".to_string(),
+            confidence: 0.75,
+            bbox: [120.0, 80.0, 64.0, 48.0],
+            size_mm: None,
+        }
+    }
+
+    fn sample_result() -> DetectionResult {
+        DetectionResult {
+            detections: vec![YoloDetection {
+                size_mm: Some(PhysicalSize::from_bbox(&[120.0, 80.0, 64.0, 48.0], 4.0)),
+                ..sample_detection()
+            }],
+            image_width: 1920,
+            image_height: 1080,
+            processing_time_ms: 35,
+            model_input_size: (640, 640),
+            thresholds: HashMap::from([("异常".to_string(), 0.5)]),
+            track_ids: vec![Some(3)],
+            timings: DetectionTimings {
+                decode_ms: 2.0,
+                preprocess_ms: 3.5,
+                inference_ms: 25.0,
+                postprocess_ms: 1.25,
+                draw_ms: 0.0,
+                encode_ms: 0.0,
+            },
+            frame: FrameInfo {
+                source_id: Some("camera_0".to_string()),
+                frame_index: Some(1024),
+                captured_at: Some("2024-05-01T08:00:00.123Z".to_string()),
+                sequence: 5678,
+            },
+        }
+    }
+
+    #[test]
+    fn detection_omits_size_mm_when_not_calibrated() {
+        let value = serde_json::to_value(sample_detection()).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "class_id": 0,
+                "class_name": "异常",
+                "confidence": 0.75,
+                "bbox": [120.0, 80.0, 64.0, 48.0]
+            })
+        );
+        let parsed: YoloDetection = serde_json::from_value(value).unwrap();
+        assert!(parsed.size_mm.is_none());
+    }
+
+    #[test]
+    fn detection_result_matches_documented_schema() {
+        let value = serde_json::to_value(sample_result()).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "detections": [{
+                    "class_id": 0,
+                    "class_name": "异常",
+                    "confidence": 0.75,
+                    "bbox": [120.0, 80.0, 64.0, 48.0],
+                    "size_mm": { "width_mm": 16.0, "height_mm": 12.0, "area_mm2": 192.0 }
+                }],
+                "image_width": 1920,
+                "image_height": 1080,
+                "processing_time_ms": 35,
+                "model_input_size": [640, 640],
+                "thresholds": { "异常": 0.5 },
+                "track_ids": [3],
+                "timings": {
+                    "decode_ms": 2.0,
+                    "preprocess_ms": 3.5,
+                    "inference_ms": 25.0,
+                    "postprocess_ms": 1.25,
+                    "draw_ms": 0.0,
+                    "encode_ms": 0.0
+                },
+                "frame": {
+                    "source_id": "camera_0",
+                    "frame_index": 1024,
+                    "captured_at": "2024-05-01T08:00:00.123Z",
+                    "sequence": 5678
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn detection_result_round_trips() {
+        let json = serde_json::to_string(&sample_result()).unwrap();
+        let parsed: DetectionResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+    }
+
+    #[test]
+    fn detection_result_reads_results_saved_before_optional_fields() {
+        let parsed: DetectionResult = serde_json::from_value(json!({
+            "detections": [{
+                "class_id": 1,
+                "class_name": "正常",
+                "confidence": 0.5,
+                "bbox": [0.0, 0.0, 10.0, 10.0]
+            }],
+            "image_width": 640,
+            "image_height": 480,
+            "processing_time_ms": 12,
+            "model_input_size": [640, 640]
+        }))
+        .unwrap();
+        assert_eq!(parsed.detections.len(), 1);
+        assert!(parsed.detections[0].size_mm.is_none());
+        assert!(parsed.thresholds.is_empty());
+        assert!(parsed.track_ids.is_empty());
+        assert_eq!(parsed.timings.inference_ms, 0.0);
+        assert!(parsed.frame.source_id.is_none());
+        assert!(parsed.frame.frame_index.is_none());
+        assert!(parsed.frame.captured_at.is_none());
+        assert_eq!(parsed.frame.sequence, 0);
+    }
+
+    #[test]
+    fn input_source_is_externally_tagged() {
+        let cases = [
+            (InputSource::Camera(0), json!({ "Camera": 0 })),
+            (InputSource::Video("a.mp4".to_string()), json!({ "Video": "a.mp4" })),
+            (InputSource::Image("a.jpg".to_string()), json!({ "Image": "a.jpg" })),
+            (InputSource::Rtsp("rtsp://cam/stream".to_string()), json!({ "Rtsp": "rtsp://cam/stream" })),
+        ];
+        for (source, expected) in cases {
+            let value = serde_json::to_value(&source).unwrap();
+            assert_eq!(value, expected);
+            let parsed: InputSource = serde_json::from_value(value).unwrap();
+            assert_eq!(parsed.describe(), source.describe());
+        }
+    }
+}
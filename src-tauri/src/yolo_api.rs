@@ -3,26 +3,176 @@ YOLO检测系统API模块
 基于原PyQt5功能设计的完整API接口
 */
 
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use tauri::State;
-use crate::yolo::DetectionResult;
-use crate::{ApiResult, AppState};
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::adaptive_rate::AdaptiveRateController;
+use crate::alert_rules::AlertRules;
+use crate::alert_sinks::AlertSinks;
+use crate::alerts::{self, Alert};
+use crate::artifacts::{ArtifactImage, ArtifactSettings};
+use crate::blackbox::{self, BlackBoxRecorder};
+use crate::detection_config::{self, ConfigStore};
+use crate::error::DetectionError;
+use crate::event_recording::EventRecorder;
+use crate::frame_queue::FrameQueueConfig;
+use crate::history;
+use crate::industrial_io::IndustrialIo;
+use crate::inference_worker::InferenceWorker;
+use crate::label_render;
+use crate::models::{self, ModelReadiness};
+use crate::capture;
+use crate::profiles::{self, ProfileStore};
+use crate::profiling::{self, Profiler};
+use crate::realtime::{FrameSampling, RealtimePipeline, SeekTarget, VideoProgress};
+use crate::result_feed;
+use crate::sessions::SessionManager;
+use crate::source_lock::{self, SourceLocks};
+use crate::storage::Database;
+use crate::privacy::{self, PrivacyMasks};
+use crate::tasks::TaskManager;
+use crate::undistort::LensCalibration;
+use crate::viewer;
+use crate::yolo::device::DeviceSpec;
+use crate::yolo::nms::NmsConfig;
+use crate::yolo::preprocessing::ResizeConfig;
+use crate::yolo::tiling::TilingConfig;
+use crate::yolo::tta::TtaConfig;
+use crate::yolo::roi::RoiPolygon;
+use crate::yolo::{self, ClassificationResult, DetectionResult, DetectionTimings, Detector, InferenceBackend, InputSource, PhysicalSize};
+use crate::AppState;
 
-/// 输入源类型
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum InputSource {
-    Camera(i32),    // 摄像头设备ID
-    Video(String),  // 视频文件路径
-    Image(String),  // 图片文件路径
-}
+/// 检测区域轮廓颜色
+const ROI_COLOR: [u8; 3] = [255, 200, 0];
 
-/// 检测配置参数
+/// 检测配置参数（持久化见 `detection_config` 模块）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DetectionConfig {
     pub confidence_thresholds: HashMap<String, f32>,  // 各类别置信度阈值
     pub selected_classes: Vec<String>,                // 选中的检测类别
     pub input_source: Option<InputSource>,            // 输入源
+    #[serde(default)]
+    pub half_precision: bool,                         // 当前模型以FP16推理（设备不支持时回退FP32）
+    #[serde(default)]
+    pub device: Option<String>,                       // 推理设备（cpu、cuda:N、metal），为空时使用CPU
+    #[serde(default)]
+    pub input_size: Option<(u32, u32)>,               // 模型输入尺寸 (width, height)，为空时使用模型默认值
+    #[serde(default)]
+    pub nms: NmsConfig,                               // NMS参数
+    #[serde(default)]
+    pub class_display: HashMap<String, ClassDisplay>, // 各类别的绘制方式（按类别名称）
+    #[serde(default)]
+    pub roi: Vec<RoiPolygon>,                         // 检测区域，为空时检测整幅画面
+    #[serde(default = "default_alpha_background")]
+    pub alpha_background: [u8; 3],                    // 带透明通道的图片混合到的背景色 (R, G, B)
+    #[serde(default)]
+    pub tiling: TilingConfig,                         // 大图切片检测参数
+    #[serde(default)]
+    pub resize: ResizeConfig,                         // 缩放策略（letterbox、stretch、center-crop）与插值方式
+    #[serde(default)]
+    pub tta: TtaConfig,                               // 测试时增强参数（是否启用按每次运行指定）
+    #[serde(default)]
+    pub px_per_mm: Option<f32>,                       // 像素/毫米标定比例，为空时不换算物理尺寸
+    #[serde(default)]
+    pub lens_calibration: Option<LensCalibration>,    // 镜头内参与畸变系数，设置后视频帧先做畸变校正
+    #[serde(default)]
+    pub privacy: PrivacyMasks,                        // 隐私遮挡区域（只作用于输出画面与保存的产物）
+}
+
+fn default_alpha_background() -> [u8; 3] {
+    yolo::decode::DEFAULT_ALPHA_BACKGROUND
+}
+
+impl Default for DetectionConfig {
+    fn default() -> Self {
+        Self {
+            confidence_thresholds: HashMap::from([
+                ("异常".to_string(), 0.2),
+                ("正常".to_string(), 0.5),
+            ]),
+            selected_classes: vec!["正常".to_string(), "异常".to_string()],
+            input_source: None,
+            half_precision: false,
+            device: None,
+            input_size: None,
+            nms: NmsConfig::default(),
+            class_display: ["正常", "异常"]
+                .into_iter()
+                .map(|name| (name.to_string(), ClassDisplay::default_for(name)))
+                .collect(),
+            roi: Vec::new(),
+            alpha_background: default_alpha_background(),
+            tiling: TilingConfig::default(),
+            resize: ResizeConfig::default(),
+            tta: TtaConfig::default(),
+            px_per_mm: None,
+            lens_calibration: None,
+            privacy: PrivacyMasks::default(),
+        }
+    }
+}
+
+/// 类别绘制配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClassDisplay {
+    pub color: [u8; 3],    // 检测框颜色 (R, G, B)
+    pub show_label: bool,  // 是否绘制标签
+    pub min_box_size: u32, // 宽或高小于该像素数的检测框不绘制
+}
+
+impl Default for ClassDisplay {
+    fn default() -> Self {
+        Self {
+            color: [255, 165, 0], // 橙色 - 默认
+            show_label: true,
+            min_box_size: 0,
+        }
+    }
+}
+
+impl ClassDisplay {
+    /// 未单独配置时的绘制方式：正常为绿色，异常为红色，其余为橙色
+    pub fn default_for(class_name: &str) -> Self {
+        let color = match class_name {
+            "正常" => [0, 200, 0],
+            "异常" => [220, 0, 0],
+            _ => return Self::default(),
+        };
+        Self { color, ..Self::default() }
+    }
+}
+
+/// 前端随检测请求传入的类别配置
+/// 后端只应用置信度阈值；类别启用与颜色分别由 `update_selected_classes`、`set_class_display` 持久化，这里仅做校验
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassConfig {
+    pub name: String,
+    pub confidence: f32,
+    #[serde(default = "default_class_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub color: Option<[u8; 3]>, // 界面显示颜色 (R, G, B)
+}
+
+fn default_class_enabled() -> bool {
+    true
+}
+
+impl ClassConfig {
+    pub fn validate(&self) -> Result<(), DetectionError> {
+        if self.name.trim().is_empty() {
+            return Err(DetectionError::InvalidInput("类别名称不能为空".to_string()));
+        }
+        if !(self.confidence.is_finite() && (0.0..=1.0).contains(&self.confidence)) {
+            return Err(DetectionError::InvalidInput(format!(
+                "类别 {} 的置信度阈值须在 0-1 之间: {}",
+                self.name, self.confidence
+            )));
+        }
+        Ok(())
+    }
 }
 
 /// 实时检测状态
@@ -32,15 +182,81 @@ pub struct DetectionStatus {
     pub input_source: Option<InputSource>,
     pub frame_count: u64,
     pub detection_count: u64,
-    pub fps: f32,
+    pub fps: f32,  // 启动以来的平均处理帧率
+    #[serde(default)]
+    pub instant_fps: f32,  // 瞬时处理帧率
+    #[serde(default)]
+    pub rolling_fps: f32,  // 最近5秒的平均处理帧率
+    #[serde(default)]
+    pub throttled: bool,  // 是否因负载/温度自动降低了处理帧率
+    #[serde(default)]
+    pub camera_properties: Option<capture::CameraProperties>,  // 摄像头实际生效的采集参数
+    #[serde(default)]
+    pub video_progress: Option<VideoProgress>,  // 视频检测进度（当前帧/总帧数/剩余时间）
+    #[serde(default)]
+    pub push_dropped: u64,  // 前端处理不及时而丢弃的推送帧数
+    #[serde(default)]
+    pub queued_frames: usize,  // 帧队列中等待读取的帧数
+    #[serde(default)]
+    pub dropped_frames: u64,  // 帧队列按丢弃策略丢弃的帧数
+    #[serde(default)]
+    pub capture_dropped: u64,  // 推理繁忙或自适应降帧而未处理的采集帧数
 }
 
-/// 检测结果扩展（包含警告信息）
+/// 检测结果扩展（包含告警与处理过程中的警告信息）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtendedDetectionResult {
     pub result: DetectionResult,
-    pub warnings: Vec<String>,
+    pub warnings: Vec<String>, // 历史记录保存失败等处理警告
     pub processing_time_ms: u64,
+    pub alerts: Vec<Alert>,    // 本次检测按告警规则触发的告警
+    pub run_id: Option<i64>,   // 历史记录中的运行ID
+}
+
+/// 批量检测进度事件
+pub const EVENT_BATCH_PROGRESS: &str = "detection://batch-progress";
+
+/// 批量检测中单张图片的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchImageResult {
+    pub path: String,
+    pub result: DetectionResult,
+    pub run_id: Option<i64>,
+    pub alerts: Vec<Alert>,
+    pub duration_ms: u64, // 读取+推理耗时
+}
+
+/// 批量检测中处理失败的图片
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// 批量检测进度（每处理完一张图片推送一次）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub path: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub detection_count: usize,
+    pub duration_ms: u64,
+}
+
+/// 批量检测汇总结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchDetectionResult {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BatchImageResult>,
+    pub failures: Vec<BatchFailure>,
+    pub total_time_ms: u64,
+    pub avg_time_ms: f64, // 成功图片的平均耗时
+    #[serde(default)]
+    pub cancelled: bool, // 中途取消时只包含已完成的图片
 }
 
 /// 类别信息
@@ -49,6 +265,8 @@ pub struct ClassInfo {
     pub id: i32,
     pub name: String,
     pub default_confidence: f32,
+    #[serde(flatten)]
+    pub display: ClassDisplay,
 }
 
 // ==================== Tauri命令实现 ====================
@@ -57,12 +275,13 @@ pub struct ClassInfo {
 #[tauri::command]
 pub async fn initialize_yolo_model(
     state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    readiness: State<'_, ModelReadiness>,
     model_path: String
-) -> Result<Vec<String>, String> {
-    let mut yolo_manager = state.lock().await;
-    
-    match yolo_manager.init_model(&model_path).await {
-        Ok(()) => {
+) -> Result<Vec<String>, DetectionError> {
+    // 实时检测进行中也可加载，新模型就绪后在两帧之间替换
+    match models::load_model(&state, &readiness, &store.get(), &model_path).await {
+        Ok(_) => {
             // 异常检测系统只返回基本的状态类别
             let class_names = vec![
                 "正常".to_string(),
@@ -70,40 +289,150 @@ pub async fn initialize_yolo_model(
             ];
             Ok(class_names)
         },
-        Err(e) => Err(format!("模型初始化失败: {}", e)),
+        Err(e) => Err(DetectionError::from(e).context("模型初始化失败")),
     }
 }
 
 /// 获取所有可用的类别信息
 #[tauri::command]
 pub async fn get_class_names(
-    _state: State<'_, AppState>
-) -> Result<ApiResult<Vec<ClassInfo>>, String> {
-    // 异常检测系统的类别信息
-    let mock_classes = vec![
-        ClassInfo { id: 0, name: "正常".to_string(), default_confidence: 0.9 },
-        ClassInfo { id: 1, name: "异常".to_string(), default_confidence: 0.9 },
-    ];
-    Ok(ApiResult::success(mock_classes))
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>
+) -> Result<Vec<ClassInfo>, DetectionError> {
+    let config = store.get();
+    let detector = state.read().await;
+    let mut classes: Vec<ClassInfo> = detector
+        .get_class_names()
+        .iter()
+        .map(|(id, name)| ClassInfo {
+            id: *id as i32,
+            name: name.clone(),
+            default_confidence: config.confidence_thresholds.get(name).copied().unwrap_or(0.5),
+            display: detection_config::class_display(name),
+        })
+        .collect();
+    classes.sort_by_key(|class| class.id);
+    Ok(classes)
+}
+
+/// 设置类别的绘制方式（检测框颜色、标签显示、最小绘制尺寸）并保存
+#[tauri::command]
+pub async fn set_class_display(
+    store: State<'_, ConfigStore>,
+    class_name: String,
+    display: ClassDisplay
+) -> Result<ClassDisplay, DetectionError> {
+    match store.update(|config| {
+        config.class_display.insert(class_name.clone(), display.clone());
+    }) {
+        Ok(_) => Ok(display),
+        Err(e) => Err(DetectionError::from(e).context("保存类别显示配置失败")),
+    }
 }
 
 /// 启动摄像头检测 - React UI版本
 #[tauri::command]
 pub async fn start_camera_detection(
-    _state: State<'_, AppState>
-) -> Result<(), String> {
-    // TODO: 实现摄像头检测启动逻辑
-    Err("摄像头检测功能暂未实现".to_string())
+    app: AppHandle,
+    locks: State<'_, SourceLocks>,
+    pipeline: State<'_, RealtimePipeline>,
+    device_id: Option<i32>
+) -> Result<(), DetectionError> {
+    let device_id = device_id.unwrap_or(0);
+    let source = InputSource::Camera(device_id);
+
+    // 先确认摄像头未被其他实例占用，检测停止时释放
+    let key = source_lock::camera_key(device_id);
+    locks.hold(&key, &source.describe())?;
+
+    // 加载绑定到该摄像头的配置档案
+    if let Err(e) = profiles::apply_bound(&app, &profiles::source_key(&source)).await {
+        locks.release(&key);
+        return Err(DetectionError::from(e).context("摄像头检测启动失败"));
+    }
+    let started = capture::open_camera(device_id)
+        .and_then(|frame_source| pipeline.start(&app, source, key.clone(), frame_source));
+    if let Err(e) = started {
+        locks.release(&key);
+        return Err(DetectionError::from(e).context("摄像头检测启动失败"));
+    }
+    Ok(())
+}
+
+/// 启动RTSP/IP网络摄像头检测，断流后自动重连
+#[tauri::command]
+pub async fn start_stream_detection(
+    app: AppHandle,
+    locks: State<'_, SourceLocks>,
+    pipeline: State<'_, RealtimePipeline>,
+    url: String
+) -> Result<String, DetectionError> {
+    let source = InputSource::Rtsp(url.clone());
+    let description = source.describe();
+
+    let key = source_lock::stream_key(&url);
+    locks.hold(&key, &description)?;
+    if let Err(e) = profiles::apply_bound(&app, &profiles::source_key(&source)).await {
+        locks.release(&key);
+        return Err(DetectionError::from(e).context("网络摄像头检测启动失败"));
+    }
+
+    // 首次连接可能等待数秒，放到阻塞线程中执行
+    let opened = tokio::task::spawn_blocking(move || capture::open_stream(&url))
+        .await?;
+    let started = opened.and_then(|frame_source| pipeline.start(&app, source, key.clone(), frame_source));
+    match started {
+        Ok(()) => Ok(format!("{} 检测已启动", description)),
+        Err(e) => {
+            locks.release(&key);
+            Err(DetectionError::from(e).context("网络摄像头检测启动失败"))
+        }
+    }
+}
+
+/// 枚举可用的摄像头及其支持的分辨率与帧率
+#[tauri::command]
+pub async fn list_cameras(
+    locks: State<'_, SourceLocks>
+) -> Result<Vec<capture::CameraInfo>, DetectionError> {
+    let held: Vec<i32> = (0..capture::MAX_PROBE_DEVICES)
+        .filter(|&index| locks.is_held(&source_lock::camera_key(index)))
+        .collect();
+    // 打开设备较慢，放到阻塞线程中执行
+    let probed = tokio::task::spawn_blocking(move || capture::list_cameras(|index| held.contains(&index)))
+        .await?;
+    match probed {
+        Ok(cameras) => Ok(cameras),
+        Err(e) => Err(DetectionError::from(e).context("枚举摄像头失败")),
+    }
+}
+
+/// 设置摄像头采集参数（分辨率、帧率、曝光、增益、自动对焦）
+/// 未在采集时保存为启动默认值并返回 None，采集中立即生效并返回实际值
+#[tauri::command]
+pub async fn set_camera_properties(
+    pipeline: State<'_, RealtimePipeline>,
+    properties: capture::CameraProperties
+) -> Result<Option<capture::CameraProperties>, DetectionError> {
+    match pipeline.set_camera_properties(properties).await {
+        Ok(effective) => Ok(effective),
+        Err(e) => Err(DetectionError::from(e).context("设置摄像头参数失败")),
+    }
 }
 
 /// 选择摄像头作为输入源
 #[tauri::command]
 pub async fn select_camera_input(
     _state: State<'_, AppState>,
-    _device_id: i32
-) -> Result<ApiResult<String>, String> {
-    // TODO: 实现摄像头初始化逻辑
-    Ok(ApiResult::error("摄像头功能暂未实现".to_string()))
+    locks: State<'_, SourceLocks>,
+    device_id: i32
+) -> Result<String, DetectionError> {
+    // 选择摄像头即占用该设备，直到停止检测
+    let description = format!("摄像头 {}", device_id);
+    match locks.hold(&source_lock::camera_key(device_id), &description) {
+        Ok(()) => Ok(format!("{} 已锁定，等待启动检测", description)),
+        Err(e) => Err(e.into()),
+    }
 }
 
 /// 加载视频源 - React UI版本
@@ -111,14 +440,132 @@ pub async fn select_camera_input(
 pub async fn load_video_source(
     _state: State<'_, AppState>,
     path: String
-) -> Result<(), String> {
+) -> Result<(), DetectionError> {
     // TODO: 实现视频加载逻辑
     match validate_input_file(&path) {
         Ok(_) => {
-            println!("视频源已加载: {}", path);
+            tracing::info!("视频源已加载: {}", path);
             Ok(())
         },
-        Err(e) => Err(format!("视频加载失败: {}", e)),
+        Err(e) => Err(e.context("视频加载失败")),
+    }
+}
+
+/// 开始视频文件检测，逐帧结果与进度通过 `frame://annotated` 事件推送
+#[tauri::command]
+pub async fn start_video_detection(
+    app: AppHandle,
+    pipeline: State<'_, RealtimePipeline>,
+    video_path: String
+) -> Result<capture::VideoInfo, DetectionError> {
+    if let Err(e) = validate_input_file(&video_path) {
+        return Err(e.context("视频检测启动失败"));
+    }
+    if let Err(e) = profiles::apply_bound(&app, &video_path).await {
+        return Err(DetectionError::from(e).context("视频检测启动失败"));
+    }
+
+    let path = video_path.clone();
+    let opened = tokio::task::spawn_blocking(move || capture::open_video(&path))
+        .await?;
+    let frame_source = match opened {
+        Ok(frame_source) => frame_source,
+        Err(e) => return Err(DetectionError::from(e).context("视频检测启动失败")),
+    };
+    // 读不到视频信息时不启动管线，避免命令报错而检测仍在后台运行
+    let Some(info) = frame_source.video_info() else {
+        return Err(DetectionError::UnsupportedFormat("无法读取视频信息".to_string()));
+    };
+
+    let session = format!("video-{}", source_lock::sanitize_key(&video_path));
+    match pipeline.start(&app, InputSource::Video(video_path), session, frame_source) {
+        Ok(()) => Ok(info),
+        Err(e) => Err(DetectionError::from(e).context("视频检测启动失败")),
+    }
+}
+
+/// 设置摄像头/视频检测的抽帧策略
+#[tauri::command]
+pub async fn set_frame_sampling(
+    pipeline: State<'_, RealtimePipeline>,
+    sampling: FrameSampling
+) -> Result<FrameSampling, DetectionError> {
+    match pipeline.set_sampling(sampling) {
+        Ok(()) => Ok(pipeline.sampling()),
+        Err(e) => Err(DetectionError::from(e).context("设置抽帧策略失败")),
+    }
+}
+
+/// 设置标注帧队列的容量与队满丢弃策略（drop_oldest / drop_newest / block）
+#[tauri::command]
+pub async fn set_frame_queue_config(
+    pipeline: State<'_, RealtimePipeline>,
+    config: FrameQueueConfig
+) -> Result<FrameQueueConfig, DetectionError> {
+    match pipeline.set_queue_config(config) {
+        Ok(()) => Ok(pipeline.queue_config()),
+        Err(e) => Err(DetectionError::from(e).context("设置帧队列失败")),
+    }
+}
+
+/// 获取标注帧队列配置
+#[tauri::command]
+pub async fn get_frame_queue_config(
+    pipeline: State<'_, RealtimePipeline>
+) -> Result<FrameQueueConfig, DetectionError> {
+    Ok(pipeline.queue_config())
+}
+
+/// 获取当前抽帧策略
+#[tauri::command]
+pub async fn get_frame_sampling(
+    pipeline: State<'_, RealtimePipeline>
+) -> Result<FrameSampling, DetectionError> {
+    Ok(pipeline.sampling())
+}
+
+/// 暂停视频检测
+#[tauri::command]
+pub async fn pause_video(
+    pipeline: State<'_, RealtimePipeline>
+) -> Result<VideoProgress, DetectionError> {
+    match pipeline.set_video_paused(true) {
+        Ok(progress) => Ok(progress),
+        Err(e) => Err(DetectionError::from(e).context("暂停视频失败")),
+    }
+}
+
+/// 继续视频检测
+#[tauri::command]
+pub async fn resume_video(
+    pipeline: State<'_, RealtimePipeline>
+) -> Result<VideoProgress, DetectionError> {
+    match pipeline.set_video_paused(false) {
+        Ok(progress) => Ok(progress),
+        Err(e) => Err(DetectionError::from(e).context("继续视频失败")),
+    }
+}
+
+/// 跳转到指定帧（`{"frame": N}`）或时间点（`{"ms": N}`），暂停中会立即检测目标帧
+#[tauri::command]
+pub async fn seek_video(
+    pipeline: State<'_, RealtimePipeline>,
+    target: SeekTarget
+) -> Result<u64, DetectionError> {
+    match pipeline.seek_video(target) {
+        Ok(frame) => Ok(frame),
+        Err(e) => Err(DetectionError::from(e).context("视频跳转失败")),
+    }
+}
+
+/// 暂停并检测下一帧
+#[tauri::command]
+pub async fn step_frame(
+    pipeline: State<'_, RealtimePipeline>
+) -> Result<String, DetectionError> {
+    match pipeline.step_frame() {
+        Ok(()) => Ok("已单步到下一帧".to_string()),
+        Err(e) => Err(DetectionError::from(e).context("单步失败")),
     }
 }
 
@@ -127,9 +574,9 @@ pub async fn load_video_source(
 pub async fn select_video_input(
     _state: State<'_, AppState>,
     _file_path: String
-) -> Result<ApiResult<String>, String> {
+) -> Result<String, DetectionError> {
     // TODO: 实现视频文件验证和初始化逻辑
-    Ok(ApiResult::error("视频处理功能暂未实现".to_string()))
+    Err(DetectionError::FeatureDisabled("视频处理功能暂未实现".to_string()))
 }
 
 /// 处理单张图片 - React UI版本
@@ -138,6 +585,10 @@ pub struct ImageProcessResult {
     #[serde(rename = "imageData")]
     pub image_data: Option<String>,  // Base64编码的图片数据，前端期望 imageData
     pub detections: Vec<Detection>,
+    #[serde(rename = "runId")]
+    pub run_id: Option<i64>,         // 历史记录中的运行ID，用于后续修正
+    #[serde(default)]
+    pub timings: DetectionTimings,   // 各阶段耗时
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -145,56 +596,87 @@ pub struct Detection {
     pub class_name: String,
     pub confidence: f32,
     pub bbox: [f32; 4],
+    #[serde(default)]
+    pub track_id: Option<u64>, // 实时检测中的跟踪ID
+    #[serde(default)]
+    pub size_mm: Option<PhysicalSize>, // 物理尺寸，标定了像素/毫米比例时填写
 }
 
 #[tauri::command]
 pub async fn process_single_image(
+    app: AppHandle,
     state: State<'_, AppState>,
+    worker: State<'_, InferenceWorker>,
+    blackbox: State<'_, BlackBoxRecorder>,
+    recorder: State<'_, EventRecorder>,
+    rate: State<'_, AdaptiveRateController>,
+    profiler: State<'_, Profiler>,
     path: String,
-    class_configs: Vec<serde_json::Value>  // 类别配置
-) -> Result<ImageProcessResult, String> {
-    println!("Backend received image path: {}", path); // 调试日志
-    let mut yolo_manager = state.lock().await;
+    class_configs: Vec<ClassConfig>, // 类别配置
+    tta: Option<bool>                // 本次使用测试时增强（更慢，召回更高）
+) -> Result<ImageProcessResult, DetectionError> {
+    tracing::debug!("Backend received image path: {}", path);
     
     // 验证文件路径和格式
-    if let Err(e) = validate_image_file(&path) {
-        return Err(e);
-    }
+    validate_image_file(&path)?;
     
     match std::fs::read(&path) {
         Ok(data) => {
-            println!("[DEBUG] ==================== 开始图片处理 ====================");
-            println!("[DEBUG] 文件大小: {} 字节", data.len());
+            tracing::debug!("==================== 开始图片处理 ====================");
+            tracing::debug!("文件大小: {} 字节", data.len());
             
             // 首先尝试解码图片确保格式正确
-            let original_image = match image::load_from_memory(&data) {
+            let decode_start = std::time::Instant::now();
+            let original_image = match profiling::stage_sync("decode", || yolo::decode::decode(&data)) {
                 Ok(img) => {
-                    println!("[DEBUG] ✅ 图片解码成功");
-                    println!("[DEBUG] 图片尺寸: {}x{}", img.width(), img.height());
-                    println!("[DEBUG] 图片格式: {:?}", img.color());
+                    tracing::debug!("✅ 图片解码成功");
+                    tracing::debug!("图片尺寸: {}x{}", img.width(), img.height());
+                    tracing::debug!("图片格式: {:?}", img.color());
                     img
                 },
-                Err(e) => return Err(format!("图片格式错误: {}", e)),
+                Err(e) => return Err(DetectionError::UnsupportedFormat(format!("图片格式错误: {}", e))),
             };
+            let decode_ms = DetectionTimings::since(decode_start);
             
             // 应用前端的置信度配置
-            for config in &class_configs {
-                if let Ok(config_obj) = serde_json::from_value::<serde_json::Map<String, serde_json::Value>>(config.clone()) {
-                    if let (Some(name), Some(confidence)) = (config_obj.get("name"), config_obj.get("confidence")) {
-                        if let (Some(name_str), Some(conf_num)) = (name.as_str(), confidence.as_f64()) {
-                            let _ = yolo_manager.update_confidence_threshold(name_str, conf_num as f32).await;
+            apply_class_configs(state.read().await.as_ref(), &class_configs).await?;
+
+            match profiling::stage("detect", worker.detect_with(data, tta.unwrap_or(false))).await {
+                Ok(mut result) => {
+                    // 检测器内部再次解码，计入的是完整的解码耗时
+                    result.timings.decode_ms += decode_ms;
+                    result.set_frame(&path, None, None);
+                    tracing::debug!("✅ YOLO检测完成");
+                    tracing::debug!("检测到 {} 个对象", result.detections.len());
+                    rate.observe_latency(result.processing_time_ms);
+                    
+                    // 转换检测结果格式
+                    let detections: Vec<Detection> = result.detections.iter()
+                        .map(|d| Detection {
+                            class_name: d.class_name.clone(),
+                            confidence: d.confidence,
+                            bbox: d.bbox,
+                            track_id: None,
+                            size_mm: d.size_mm,
+                        })
+                        .collect();
+                    
+                    // 原始帧写入黑匣子与事件录像缓冲，告警时保存前后画面
+                    let session = blackbox::IMAGE_SESSION;
+                    if let Err(e) = blackbox.record_frame(session, &original_image, &detections) {
+                        tracing::error!("黑匣子写入失败: {}", e);
+                    }
+                    recorder.on_frame(session, &original_image, &detections);
+                    
+                    let stored = store_detection(&app, &path, ArtifactImage::Decoded(&original_image), &result);
+                    if let Some(alert) = stored.alerts.first() {
+                        if let Err(e) = recorder.start(&app, session, alert) {
+                            tracing::error!("事件录像启动失败: {}", e);
                         }
                     }
-                }
-            }
-
-            match yolo_manager.detect_image(&data).await {
-                Ok(result) => {
-                    println!("[DEBUG] ✅ YOLO检测完成");
-                    println!("[DEBUG] 检测到 {} 个对象", result.detections.len());
                     
                     for (i, detection) in result.detections.iter().enumerate() {
-                        println!("[DEBUG] 对象 {}: {} (置信度: {:.2}, 边界框: {:?})", 
+                        tracing::debug!("对象 {}: {} (置信度: {:.2}, 边界框: {:?})", 
                             i + 1, 
                             detection.class_name, 
                             detection.confidence,
@@ -203,78 +685,318 @@ pub async fn process_single_image(
                     }
                     
                     // 在原图上绘制检测结果
-                    println!("[DEBUG] 开始绘制检测结果...");
+                    tracing::debug!("开始绘制检测结果...");
+                    let draw_start = std::time::Instant::now();
                     let annotated_image = if result.detections.is_empty() {
-                        println!("[DEBUG] 无检测结果，返回原图");
+                        tracing::debug!("无检测结果，返回原图");
                         original_image.clone()
                     } else {
-                        draw_detections_on_image(&original_image, &result.detections)?
+                        profiling::stage_sync("draw", || draw_detections_on_image(&original_image, &result.detections))
+                            .map_err(DetectionError::Internal)?
                     };
-                    println!("[DEBUG] ✅ 检测结果绘制完成");
+                    result.timings.draw_ms = DetectionTimings::since(draw_start);
+                    tracing::debug!("✅ 检测结果绘制完成");
                     
                     // 转换为base64
-                    let image_base64 = image_to_base64(&annotated_image)?;
+                    let encode_start = std::time::Instant::now();
+                    let image_base64 = profiling::stage_sync("encode", || image_to_base64(&annotated_image))
+                        .map_err(DetectionError::Internal)?;
+                    result.timings.encode_ms = DetectionTimings::since(encode_start);
                     
-                    // 转换检测结果格式
-                    let detections: Vec<Detection> = result.detections.iter()
-                        .map(|d| Detection {
-                            class_name: d.class_name.clone(),
-                            confidence: d.confidence,
-                            bbox: d.bbox,
-                        })
-                        .collect();
+                    // 同步推送到只读监控窗口
+                    let stats = state.read().await.get_stats().await;
+                    viewer::publish_frame(
+                        &app,
+                        &path,
+                        Some(image_base64.clone()),
+                        detections.clone(),
+                        stats.total_inferences,
+                        stats.avg_fps,
+                    );
+                    
+                    profiler.frame_finished();
                     
                     Ok(ImageProcessResult {
                         image_data: Some(image_base64),
                         detections,
+                        run_id,
+                        timings: result.timings,
                     })
                 },
-                Err(e) => Err(format!("图片处理失败: {}", e)),
+                Err(e) => Err(DetectionError::from(e).context("图片处理失败")),
             }
         },
-        Err(e) => Err(format!("读取文件失败: {}", e)),
+        Err(e) => Err(DetectionError::IoError(e).context("读取文件失败")),
     }
 }
 
+/// 默认返回的分类结果数
+const DEFAULT_CLASSIFY_TOP_K: usize = 5;
+
+/// 整图分类（模型导出为分类器时使用），返回概率最高的 top_k 个类别
+#[tauri::command]
+pub async fn classify_image(
+    state: State<'_, AppState>,
+    path: String,
+    top_k: Option<usize>
+) -> Result<ClassificationResult, DetectionError> {
+    if let Err(e) = validate_image_file(&path) {
+        return Err(e);
+    }
+    let data = match std::fs::read(&path) {
+        Ok(data) => data,
+        Err(e) => return Err(DetectionError::IoError(e).context("读取文件失败")),
+    };
+    let top_k = top_k.unwrap_or(DEFAULT_CLASSIFY_TOP_K);
+    match profiling::stage("classify", async { state.read().await.classify_image(&data, top_k).await }).await {
+        Ok(result) => {
+            if let Some(top) = result.predictions.first() {
+                tracing::debug!("🏷️ 分类结果: {} ({:.3})", top.class_name, top.probability);
+            }
+            Ok(result)
+        }
+        Err(e) => Err(DetectionError::from(e).context("图像分类失败")),
+    }
+}
+
+/// 应用前端传入的类别配置中的置信度阈值，任一配置无效时整体不生效
+async fn apply_class_configs(detector: &dyn Detector, class_configs: &[ClassConfig]) -> Result<(), DetectionError> {
+    let mut names = HashSet::new();
+    for config in class_configs {
+        config.validate()?;
+        if !names.insert(config.name.as_str()) {
+            return Err(DetectionError::InvalidInput(format!("类别 {} 重复配置", config.name)));
+        }
+    }
+    for config in class_configs {
+        detector
+            .update_confidence_threshold(&config.name, config.confidence)
+            .await
+            .map_err(|e| DetectionError::from(e).context("更新置信度阈值失败"))?;
+    }
+    Ok(())
+}
+
+/// 检测结果写入历史后的收尾信息
+pub(crate) struct StoredRun {
+    pub run_id: Option<i64>,
+    pub alerts: Vec<Alert>,
+    pub warnings: Vec<String>, // 历史或告警写入失败，不影响检测结果本身
+}
+
+/// 检测完成后的统一收尾（单张、批量、目录监控与 HTTP 检测共用）：写入历史与产物、推送结果并按规则告警
+pub(crate) fn store_detection(app: &AppHandle, source: &str, image: ArtifactImage<'_>, result: &DetectionResult) -> StoredRun {
+    let mut warnings = Vec::new();
+    let run_id = match history::record_run(
+        &app.state::<Database>(),
+        source,
+        result,
+        app.state::<SessionManager>().current(),
+        app.state::<ProfileStore>().active().as_deref(),
+    ) {
+        Ok(id) => Some(id),
+        Err(e) => {
+            tracing::error!("历史记录保存失败: {}", e);
+            warnings.push(format!("历史记录保存失败: {}", e));
+            None
+        }
+    };
+    app.state::<ArtifactSettings>().save_in_background(source, run_id, image, &result.detections);
+    let alerts = match publish_and_alert(app, source, None, result) {
+        Ok(alerts) => alerts,
+        Err(e) => {
+            tracing::error!("告警记录失败: {}", e);
+            warnings.push(format!("告警记录失败: {}", e));
+            Vec::new()
+        }
+    };
+    StoredRun { run_id, alerts, warnings }
+}
+
+/// 推送检测结果并按规则告警，告警同时分发到外部通知与工业 IO
+pub(crate) fn publish_and_alert(
+    app: &AppHandle,
+    source: &str,
+    camera: Option<String>,
+    result: &DetectionResult,
+) -> anyhow::Result<Vec<Alert>> {
+    result_feed::publish(app, source, camera, result);
+    let alerts = alerts::raise_for_result(&app.state::<Database>(), &app.state::<AlertRules>(), source, result)?;
+    app.state::<AlertSinks>().dispatch(app, &alerts);
+    app.state::<IndustrialIo>().trigger(app, &alerts);
+    Ok(alerts)
+}
+
 /// 选择图片文件作为输入源并立即处理
 #[tauri::command]
 pub async fn select_image_input(
-    state: State<'_, AppState>,
+    app: AppHandle,
+    worker: State<'_, InferenceWorker>,
     file_path: String
-) -> Result<ApiResult<ExtendedDetectionResult>, String> {
-    let mut yolo_manager = state.lock().await;
-    
+) -> Result<ExtendedDetectionResult, DetectionError> {
     let start_time = std::time::Instant::now();
     
     match std::fs::read(&file_path) {
-        Ok(data) => match yolo_manager.detect_image(&data).await {
-            Ok(result) => {
+        Ok(data) => match worker.detect(data.clone()).await {
+            Ok(mut result) => {
+            result.set_frame(&file_path, None, None);
             let processing_time = start_time.elapsed().as_millis() as u64;
             
-            // TODO: 检查异常并生成警告
-            let warnings = check_for_abnormal_detections(&result);
+            let StoredRun { run_id, alerts, warnings } =
+                store_detection(&app, &file_path, ArtifactImage::Encoded(&data), &result);
             
             let extended_result = ExtendedDetectionResult {
                 result,
                 warnings,
                 processing_time_ms: processing_time,
+                alerts,
+                run_id,
             };
             
-            Ok(ApiResult::success(extended_result))
+            Ok(extended_result)
             },
-            Err(e) => Ok(ApiResult::error(format!("图片处理失败: {}", e))),
+            Err(e) => Err(DetectionError::from(e).context("图片处理失败")),
         },
-        Err(e) => Ok(ApiResult::error(format!("读取文件失败: {}", e))),
+        Err(e) => Err(DetectionError::IoError(e).context("读取文件失败")),
     }
 }
 
+/// 批量检测中的单张图片：文件读取并行，推理由推理线程并发执行（模型支持时合并为批）
+async fn detect_batch_item(
+    app: &AppHandle,
+    worker: &InferenceWorker,
+    tta: bool,
+    path: &str
+) -> Result<(DetectionResult, StoredRun), String> {
+    validate_image_file(path).map_err(|e| e.to_string())?;
+    let data = tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("读取文件失败: {}", e))?;
+    let mut result = worker
+        .detect_with(data.clone(), tta)
+        .await
+        .map_err(|e| format!("图片处理失败: {}", e))?;
+    result.set_frame(path, None, None);
+    let stored = store_detection(app, path, ArtifactImage::Encoded(&data), &result);
+    Ok((result, stored))
+}
+
+/// 批量检测图片，逐张推送进度事件并返回汇总结果
+#[tauri::command]
+pub async fn process_image_batch(
+    app: AppHandle,
+    worker: State<'_, InferenceWorker>,
+    tasks: State<'_, TaskManager>,
+    paths: Vec<String>,
+    concurrency: Option<usize>, // 同时提交的图片数，默认按推理线程的处理能力
+    task_id: Option<String>,    // 用于 cancel_task，未传入时自动分配
+    tta: Option<bool>           // 本次批量检测使用测试时增强（离线复核，更慢但召回更高）
+) -> Result<BatchDetectionResult, DetectionError> {
+    let task = tasks.begin(&app, "batch", task_id)?;
+    let total = paths.len();
+    let workers = concurrency.unwrap_or_else(|| worker.capacity()).max(1);
+    tracing::info!(
+        "📦 开始批量检测 [{}]: {} 张图片，并发 {}{}",
+        task.id(),
+        total,
+        workers,
+        if tta.unwrap_or(false) { "，测试时增强" } else { "" }
+    );
+    task.set_progress(0, Some(total as u64));
+
+    let batch_start = std::time::Instant::now();
+    let app: &AppHandle = &app;
+    let worker: &InferenceWorker = &worker;
+    let tta = tta.unwrap_or(false);
+    let mut items = futures::stream::iter(paths.into_iter().enumerate())
+        .map(|(index, path)| async move {
+            let start = std::time::Instant::now();
+            let outcome = detect_batch_item(app, worker, tta, &path).await;
+            (index, path, outcome, start.elapsed().as_millis() as u64)
+        })
+        .buffer_unordered(workers);
+
+    let mut completed = 0;
+    let mut results = Vec::new();
+    let mut failures = Vec::new();
+    let mut cancelled = false;
+    loop {
+        // 取消时丢弃尚未完成的图片
+        let next = tokio::select! {
+            next = items.next() => next,
+            _ = task.token().cancelled() => {
+                cancelled = true;
+                None
+            }
+        };
+        let Some((index, path, outcome, duration_ms)) = next else {
+            break;
+        };
+        completed += 1;
+        let progress = BatchProgress {
+            completed,
+            total,
+            path: path.clone(),
+            success: outcome.is_ok(),
+            error: outcome.as_ref().err().cloned(),
+            detection_count: outcome.as_ref().map_or(0, |(result, _)| result.detections.len()),
+            duration_ms,
+        };
+        let _ = app.emit(EVENT_BATCH_PROGRESS, progress);
+        task.set_progress(completed as u64, Some(total as u64));
+
+        match outcome {
+            Ok((result, stored)) => results.push((index, BatchImageResult {
+                path,
+                result,
+                run_id: stored.run_id,
+                alerts: stored.alerts,
+                duration_ms,
+            })),
+            Err(error) => failures.push((index, BatchFailure { path, error })),
+        }
+    }
+
+    // 按输入顺序返回
+    results.sort_by_key(|(index, _)| *index);
+    failures.sort_by_key(|(index, _)| *index);
+    let results: Vec<BatchImageResult> = results.into_iter().map(|(_, item)| item).collect();
+    let failures: Vec<BatchFailure> = failures.into_iter().map(|(_, item)| item).collect();
+
+    let avg_time_ms = if results.is_empty() {
+        0.0
+    } else {
+        results.iter().map(|r| r.duration_ms).sum::<u64>() as f64 / results.len() as f64
+    };
+    let summary = BatchDetectionResult {
+        total,
+        succeeded: results.len(),
+        failed: failures.len(),
+        results,
+        failures,
+        total_time_ms: batch_start.elapsed().as_millis() as u64,
+        avg_time_ms,
+        cancelled,
+    };
+    tracing::info!(
+        "📦 批量检测{}: 成功 {} / 失败 {}，用时 {} ms",
+        if cancelled { "已取消" } else { "完成" },
+        summary.succeeded, summary.failed, summary.total_time_ms
+    );
+    task.complete();
+    Ok(summary)
+}
+
 /// 停止检测 - React UI版本
 #[tauri::command]
 pub async fn stop_detection(
-    _state: State<'_, AppState>
-) -> Result<(), String> {
-    // TODO: 实现检测停止逻辑
-    println!("检测已停止");
+    _state: State<'_, AppState>,
+    locks: State<'_, SourceLocks>,
+    pipeline: State<'_, RealtimePipeline>
+) -> Result<(), DetectionError> {
+    pipeline.stop();
+    locks.release_all();
+    tracing::info!("检测已停止");
     Ok(())
 }
 
@@ -286,33 +1008,57 @@ pub struct FrameResult {
     pub detections: Option<Vec<Detection>>,
 }
 
+/// 轮询获取标注帧，新版前端改为监听 `frame://annotated` 事件
 #[tauri::command]
 pub async fn get_next_frame(
-    _state: State<'_, AppState>,
-    _class_configs: Vec<serde_json::Value>
-) -> Result<FrameResult, String> {
-    // TODO: 实现实时帧获取逻辑
-    // 目前返回模拟数据
-    Ok(FrameResult {
-        success: true,
-        image_data: Some("base64_encoded_frame_placeholder".to_string()),
-        detections: Some(vec![
-            Detection {
-                class_name: "正常".to_string(),
-                confidence: 0.92,
-                bbox: [50.0, 60.0, 150.0, 200.0],
-            }
-        ]),
-    })
+    state: State<'_, AppState>,
+    pipeline: State<'_, RealtimePipeline>,
+    class_configs: Vec<ClassConfig>
+) -> Result<FrameResult, DetectionError> {
+    // 阈值变化作用于之后推理的帧
+    apply_class_configs(state.read().await.as_ref(), &class_configs).await?;
+
+    match pipeline.next_frame() {
+        Some(frame) => Ok(FrameResult {
+            success: true,
+            image_data: frame.image_data,
+            detections: Some(frame.detections),
+        }),
+        None => Ok(FrameResult {
+            success: false,
+            image_data: None,
+            detections: None,
+        }),
+    }
+}
+
+/// 确认已显示推送的标注帧，允许后端继续推送
+#[tauri::command]
+pub async fn ack_frame(
+    app: AppHandle,
+    pipeline: State<'_, RealtimePipeline>,
+    seq: u64
+) -> Result<(), DetectionError> {
+    pipeline.ack_frame(&app, seq);
+    Ok(())
+}
+
+/// 恢复默认检测配置，写回配置文件并应用到检测器
+async fn reset_detection_config(state: &AppState, store: &ConfigStore) -> anyhow::Result<DetectionConfig> {
+    let config = store.reset()?;
+    detection_config::apply(state.write().await.as_mut(), &config).await
 }
 
 /// 重置配置 - React UI版本
 #[tauri::command]
 pub async fn reset_configuration(
-    _state: State<'_, AppState>
-) -> Result<(), String> {
-    // TODO: 实现配置重置逻辑
-    println!("配置已重置为默认值");
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>
+) -> Result<(), DetectionError> {
+    reset_detection_config(&state, &store)
+        .await
+        .map_err(|e| DetectionError::from(e).context("重置配置失败"))?;
+    tracing::info!("配置已重置为默认值");
     Ok(())
 }
 
@@ -320,166 +1066,398 @@ pub async fn reset_configuration(
 #[tauri::command]
 pub async fn start_realtime_detection(
     _state: State<'_, AppState>
-) -> Result<ApiResult<String>, String> {
+) -> Result<String, DetectionError> {
     // TODO: 实现实时检测启动逻辑
-    Ok(ApiResult::error("实时检测功能暂未实现".to_string()))
+    Err(DetectionError::FeatureDisabled("实时检测功能暂未实现".to_string()))
 }
 
 /// 停止实时检测
 #[tauri::command]
 pub async fn stop_realtime_detection(
-    _state: State<'_, AppState>
-) -> Result<ApiResult<String>, String> {
-    // TODO: 实现实时检测停止逻辑
-    Ok(ApiResult::error("实时检测停止功能暂未实现".to_string()))
+    _state: State<'_, AppState>,
+    locks: State<'_, SourceLocks>,
+    pipeline: State<'_, RealtimePipeline>
+) -> Result<String, DetectionError> {
+    let stopped = pipeline.stop();
+    locks.release_all();
+    if stopped {
+        Ok("实时检测已停止".to_string())
+    } else {
+        Err(DetectionError::NotFound("当前没有运行中的实时检测".to_string()))
+    }
 }
 
 /// 获取当前检测状态
 #[tauri::command]
 pub async fn get_realtime_status(
-    _state: State<'_, AppState>
-) -> Result<ApiResult<DetectionStatus>, String> {
-    // TODO: 实现状态获取逻辑
+    rate: State<'_, AdaptiveRateController>,
+    pipeline: State<'_, RealtimePipeline>
+) -> Result<DetectionStatus, DetectionError> {
+    let stats = pipeline.stats();
     let status = DetectionStatus {
-        is_running: false,
-        input_source: None,
-        frame_count: 0,
-        detection_count: 0,
-        fps: 0.0,
+        is_running: stats.is_running,
+        input_source: stats.input_source,
+        frame_count: stats.frame_count,
+        detection_count: stats.detection_count,
+        fps: stats.fps,
+        instant_fps: stats.instant_fps,
+        rolling_fps: stats.rolling_fps,
+        throttled: rate.status().throttled,
+        camera_properties: stats.camera_properties,
+        video_progress: stats.video_progress,
+        push_dropped: stats.push_dropped,
+        queued_frames: stats.queued_frames,
+        dropped_frames: stats.dropped_frames,
+        capture_dropped: stats.capture_dropped,
     };
-    Ok(ApiResult::success(status))
+    Ok(status)
 }
 
 /// 批量更新置信度阈值
 #[tauri::command]
 pub async fn update_confidence_thresholds(
-    _state: State<'_, AppState>,
-    _thresholds: HashMap<String, f32>
-) -> Result<ApiResult<String>, String> {
-    // TODO: 实现批量阈值更新逻辑
-    Ok(ApiResult::success("置信度阈值更新成功".to_string()))
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    thresholds: HashMap<String, f32>
+) -> Result<String, DetectionError> {
+    let detector = state.read().await;
+    for (class_name, threshold) in &thresholds {
+        if let Err(e) = detector.update_confidence_threshold(class_name, *threshold).await {
+            return Err(DetectionError::from(e).context("更新置信度阈值失败"));
+        }
+    }
+    match store.update(|config| config.confidence_thresholds.extend(thresholds)) {
+        Ok(_) => Ok("置信度阈值更新成功".to_string()),
+        Err(e) => Err(DetectionError::from(e).context("保存置信度阈值失败")),
+    }
+}
+
+/// 设置NMS参数（IoU阈值、最大检测数、跨类别/按类别抑制）
+#[tauri::command]
+pub async fn set_nms_config(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    config: NmsConfig
+) -> Result<NmsConfig, DetectionError> {
+    if let Err(e) = state.read().await.set_nms_config(config.clone()).await {
+        return Err(DetectionError::from(e).context("设置NMS参数失败"));
+    }
+    match store.update(|saved| saved.nms = config) {
+        Ok(saved) => Ok(saved.nms),
+        Err(e) => Err(DetectionError::from(e).context("保存NMS参数失败")),
+    }
+}
+
+/// 设置大图切片检测参数（切片尺寸、重叠比例、启用切片的最小图像尺寸、是否同时检测整图）
+#[tauri::command]
+pub async fn set_tiling_config(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    config: TilingConfig
+) -> Result<TilingConfig, DetectionError> {
+    if let Err(e) = state.read().await.set_tiling_config(config.clone()).await {
+        return Err(DetectionError::from(e).context("设置切片检测参数失败"));
+    }
+    match store.update(|saved| saved.tiling = config) {
+        Ok(saved) => Ok(saved.tiling),
+        Err(e) => Err(DetectionError::from(e).context("保存切片检测参数失败")),
+    }
+}
+
+/// 设置缩放策略与插值方式，预处理与检测框坐标换算同时生效
+#[tauri::command]
+pub async fn set_resize_config(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    config: ResizeConfig
+) -> Result<ResizeConfig, DetectionError> {
+    if let Err(e) = state.read().await.set_resize_config(config).await {
+        return Err(DetectionError::from(e).context("设置缩放策略失败"));
+    }
+    match store.update(|saved| saved.resize = config) {
+        Ok(saved) => Ok(saved.resize),
+        Err(e) => Err(DetectionError::from(e).context("保存缩放策略失败")),
+    }
+}
+
+/// 设置测试时增强参数（翻转、输入尺度、融合IoU阈值），单张与批量检测传入 tta 时使用
+#[tauri::command]
+pub async fn set_tta_config(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    config: TtaConfig
+) -> Result<TtaConfig, DetectionError> {
+    if let Err(e) = state.read().await.set_tta_config(config.clone()).await {
+        return Err(DetectionError::from(e).context("设置TTA参数失败"));
+    }
+    match store.update(|saved| saved.tta = config) {
+        Ok(saved) => Ok(saved.tta),
+        Err(e) => Err(DetectionError::from(e).context("保存TTA参数失败")),
+    }
+}
+
+/// 设置像素/毫米标定比例，检测结果中附带各检测框的物理宽高与面积；传空值取消标定
+#[tauri::command]
+pub async fn set_scale(
+    store: State<'_, ConfigStore>,
+    px_per_mm: Option<f32>
+) -> Result<Option<f32>, DetectionError> {
+    match store.update(|saved| saved.px_per_mm = px_per_mm) {
+        Ok(saved) => {
+            match saved.px_per_mm {
+                Some(scale) => tracing::info!("📏 像素/毫米标定比例: {:.4}", scale),
+                None => tracing::info!("📏 已取消像素/毫米标定"),
+            }
+            Ok(saved.px_per_mm)
+        }
+        Err(e) => Err(DetectionError::from(e).context("保存标定比例失败")),
+    }
+}
+
+/// 设置检测区域（多边形顶点为归一化坐标），区域外的检测框被忽略；传空列表恢复检测整幅画面
+#[tauri::command]
+pub async fn set_detection_roi(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    polygons: Vec<RoiPolygon>
+) -> Result<Vec<RoiPolygon>, DetectionError> {
+    if let Err(e) = state.read().await.set_roi(polygons.clone()).await {
+        return Err(DetectionError::from(e).context("设置检测区域失败"));
+    }
+    match store.update(|saved| saved.roi = polygons) {
+        Ok(saved) => Ok(saved.roi),
+        Err(e) => Err(DetectionError::from(e).context("保存检测区域失败")),
+    }
 }
 
 /// 更新选中的检测类别
 #[tauri::command]
 pub async fn update_selected_classes(
-    _state: State<'_, AppState>,
-    _class_names: Vec<String>
-) -> Result<ApiResult<String>, String> {
-    // TODO: 实现类别选择更新逻辑
-    Ok(ApiResult::success("检测类别更新成功".to_string()))
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    class_names: Vec<String>
+) -> Result<String, DetectionError> {
+    let detector = state.read().await;
+    let class_ids = detection_config::class_ids_for(detector.as_ref(), &class_names);
+    if let Err(e) = detector.set_enabled_classes(class_ids).await {
+        return Err(DetectionError::from(e).context("更新检测类别失败"));
+    }
+    match store.update(|config| config.selected_classes = class_names) {
+        Ok(_) => Ok("检测类别更新成功".to_string()),
+        Err(e) => Err(DetectionError::from(e).context("保存检测类别失败")),
+    }
 }
 
-/// 获取检测配置
+/// 获取检测配置（已加载模型时推理精度为实际生效值）
 #[tauri::command]
 pub async fn get_detection_config(
-    _state: State<'_, AppState>
-) -> Result<ApiResult<DetectionConfig>, String> {
-    // TODO: 从状态中获取当前配置
-    let config = DetectionConfig {
-        confidence_thresholds: HashMap::new(),
-        selected_classes: vec!["正常".to_string(), "异常".to_string()],
-        input_source: None,
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>
+) -> Result<DetectionConfig, DetectionError> {
+    let mut config = store.get();
+    let detector = state.read().await;
+    if detection_config::model_loaded(detector.as_ref()) {
+        config.half_precision = detector.is_half_precision();
+    }
+    Ok(config)
+}
+
+/// 应用并保存检测配置，返回实际生效的配置
+#[tauri::command]
+pub async fn set_detection_config(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    config: DetectionConfig
+) -> Result<DetectionConfig, DetectionError> {
+    if let Err(e) = detection_config::validate(&config) {
+        return Err(DetectionError::InvalidInput(format!("检测配置无效: {:#}", e)));
+    }
+    // 先应用再保存：应用失败的配置不写入文件，避免每次启动都重新应用失败
+    let effective = match detection_config::apply(state.write().await.as_mut(), &config).await {
+        Ok(effective) => effective,
+        Err(e) => return Err(DetectionError::from(e).context("应用检测配置失败")),
     };
-    Ok(ApiResult::success(config))
+    let saved = effective.clone();
+    match store.update(|current| *current = saved) {
+        Ok(_) => Ok(effective),
+        Err(e) => Err(DetectionError::from(e).context("保存检测配置失败")),
+    }
 }
 
 /// 重置所有配置到默认值
 #[tauri::command]
 pub async fn reset_to_defaults(
-    _state: State<'_, AppState>
-) -> Result<ApiResult<String>, String> {
-    // TODO: 实现配置重置逻辑
-    Ok(ApiResult::success("配置已重置为默认值".to_string()))
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>
+) -> Result<String, DetectionError> {
+    match reset_detection_config(&state, &store).await {
+        Ok(_) => Ok("配置已重置为默认值".to_string()),
+        Err(e) => Err(DetectionError::from(e).context("重置配置失败")),
+    }
+}
+
+/// 切换推理后端（已加载模型时用新后端重新加载同一模型，失败则保留原后端）
+#[tauri::command]
+pub async fn set_inference_backend(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    readiness: State<'_, ModelReadiness>,
+    backend: InferenceBackend
+) -> Result<HashMap<String, String>, DetectionError> {
+    let mut detector = state.write().await;
+    if detector.backend() == backend {
+        return Ok(detector.get_model_info());
+    }
+
+    let info = detector.get_model_info();
+    let model_path = match info.get("model_loaded").map(String::as_str) {
+        Some("true") => info.get("model_path").cloned(),
+        _ => None,
+    };
+
+    let mut next = yolo::create_detector(backend);
+    if let Some(path) = &model_path {
+        if let Err(e) = next.init_model(path).await {
+            return Err(DetectionError::from(e).context("切换推理后端失败"));
+        }
+    }
+
+    tracing::info!("🔀 推理后端已切换: {} -> {}", detector.backend().as_str(), backend.as_str());
+    *detector = next;
+    if let Err(e) = detection_config::apply(detector.as_mut(), &store.get()).await {
+        tracing::error!("检测配置应用失败: {}", e);
+    }
+    models::warm_up_and_mark(detector.as_ref(), &readiness, 1).await;
+    Ok(detector.get_model_info())
+}
+
+/// 选择推理设备（cpu、cuda:N、metal），不可用时自动回退到CPU
+#[tauri::command]
+pub async fn select_device(
+    state: State<'_, AppState>,
+    store: State<'_, ConfigStore>,
+    device: String
+) -> Result<HashMap<String, String>, DetectionError> {
+    let spec = match DeviceSpec::parse(&device) {
+        Ok(spec) => spec,
+        Err(e) => return Err(DetectionError::InvalidInput(format!("选择推理设备失败: {:#}", e))),
+    };
+    let mut detector = state.write().await;
+    if let Err(e) = detector.select_device(spec).await {
+        return Err(DetectionError::from(e).context("选择推理设备失败"));
+    }
+    if let Err(e) = store.update(|config| config.device = Some(spec.to_string())) {
+        tracing::error!("保存推理设备配置失败: {}", e);
+    }
+    Ok(detector.get_model_info())
 }
 
 // ==================== 图片处理辅助函数 ====================
 
 /// 验证图片文件格式
-fn validate_image_file(file_path: &str) -> Result<(), String> {
+fn validate_image_file(file_path: &str) -> Result<(), DetectionError> {
     use std::path::Path;
     
-    println!("[DEBUG] ==================== 文件路径验证开始 ====================");
-    println!("[DEBUG] 输入路径: {}", file_path);
-    println!("[DEBUG] 路径长度: {} 字符", file_path.len());
-    println!("[DEBUG] 是否包含中文: {}", file_path.chars().any(|c| '\u{4e00}' <= c && c <= '\u{9fff}'));
-    println!("[DEBUG] 路径编码: {:?}", file_path.as_bytes());
+    tracing::debug!("==================== 文件路径验证开始 ====================");
+    tracing::debug!("输入路径: {}", file_path);
+    tracing::debug!("路径长度: {} 字符", file_path.len());
+    tracing::debug!("是否包含中文: {}", file_path.chars().any(|c| '\u{4e00}' <= c && c <= '\u{9fff}'));
+    tracing::debug!("路径编码: {:?}", file_path.as_bytes());
     
     let path = Path::new(file_path);
     
     // 检查路径是否存在
-    println!("[DEBUG] 检查路径是否存在...");
+    tracing::debug!("检查路径是否存在...");
     if !path.exists() {
-        println!("[ERROR] 路径不存在: {}", file_path);
+        tracing::error!("路径不存在: {}", file_path);
         let absolute_path = match path.canonicalize() {
             Ok(abs_path) => format!("{:?}", abs_path),
             Err(e) => {
-                println!("[DEBUG] 无法规范化路径，错误: {:?}", e);
+                tracing::debug!("无法规范化路径，错误: {:?}", e);
                 "无法解析绝对路径".to_string()
             }
         };
         let error_msg = format!("图片文件不存在: {}\n尝试的绝对路径: {}\n请检查文件是否存在且路径正确", 
             file_path, absolute_path);
-        println!("[ERROR] {}", error_msg);
-        return Err(error_msg);
+        tracing::error!("{}", error_msg);
+        return Err(DetectionError::NotFound(error_msg));
     }
-    println!("[DEBUG] ✅ 路径存在");
+    tracing::debug!("✅ 路径存在");
     
     // 检查是否为文件
-    println!("[DEBUG] 检查是否为文件...");
+    tracing::debug!("检查是否为文件...");
     if !path.is_file() {
         let error_msg = format!("指定路径不是一个文件: {}", file_path);
-        println!("[ERROR] {}", error_msg);
-        return Err(error_msg);
+        tracing::error!("{}", error_msg);
+        return Err(DetectionError::InvalidInput(error_msg));
     }
-    println!("[DEBUG] ✅ 确认是文件类型");
+    tracing::debug!("✅ 确认是文件类型");
     
     // 检查文件扩展名
-    println!("[DEBUG] 检查文件扩展名...");
+    tracing::debug!("检查文件扩展名...");
     let extension = path.extension()
         .and_then(|ext| ext.to_str())
         .map(|s| s.to_lowercase())
         .ok_or_else(|| {
             let error_msg = format!("文件缺少扩展名: {}", file_path);
-            println!("[ERROR] {}", error_msg);
-            error_msg
+            tracing::error!("{}", error_msg);
+            DetectionError::UnsupportedFormat(error_msg)
         })?;
     
-    println!("[DEBUG] 文件扩展名: {}", extension);
+    tracing::debug!("文件扩展名: {}", extension);
     
-    match extension.as_str() {
-        "jpg" | "jpeg" | "png" | "bmp" | "gif" | "tiff" | "webp" => {
-            println!("[DEBUG] ✅ 文件格式验证通过: .{}", extension);
-            println!("[DEBUG] ==================== 文件路径验证完成 ====================");
-            Ok(())
-        },
-        _ => {
-            let error_msg = format!("不支持的图片格式: .{}\n支持的格式: jpg, jpeg, png, bmp, gif, tiff, webp", extension);
-            println!("[ERROR] {}", error_msg);
-            println!("[DEBUG] ==================== 文件路径验证失败 ====================");
-            Err(error_msg)
-        },
+    // 支持的格式以实际编译进来的解码器为准
+    if yolo::decode::is_supported_extension(&extension) {
+        tracing::debug!("✅ 文件格式验证通过: .{}", extension);
+        tracing::debug!("==================== 文件路径验证完成 ====================");
+        Ok(())
+    } else {
+        let error_msg = format!(
+            "不支持的图片格式: .{}\n支持的格式: {}",
+            extension,
+            yolo::decode::supported_extensions().join(", ")
+        );
+        tracing::error!("{}", error_msg);
+        tracing::debug!("==================== 文件路径验证失败 ====================");
+        Err(DetectionError::UnsupportedFormat(error_msg))
     }
 }
 
-/// 在图片上绘制检测结果
-fn draw_detections_on_image(
+/// 在图片上绘制检测结果（输出画面，先做隐私遮挡）
+pub(crate) fn draw_detections_on_image(
+    original_image: &image::DynamicImage,
+    detections: &[crate::yolo::YoloDetection]
+) -> Result<image::DynamicImage, String> {
+    let mut image = original_image.to_rgb8();
+    privacy::mask_output(&mut image);
+    draw_detections_on_rgb(image, detections)
+}
+
+/// 在图片上绘制检测结果，不做隐私遮挡（保存产物时按产物设置自行遮挡）
+pub(crate) fn draw_detections_unmasked(
     original_image: &image::DynamicImage,
     detections: &[crate::yolo::YoloDetection]
+) -> Result<image::DynamicImage, String> {
+    draw_detections_on_rgb(original_image.to_rgb8(), detections)
+}
+
+fn draw_detections_on_rgb(
+    mut image: image::RgbImage,
+    detections: &[crate::yolo::YoloDetection]
 ) -> Result<image::DynamicImage, String> {
     use imageproc::drawing::draw_hollow_rect_mut;
     use imageproc::rect::Rect;
     use image::Rgb;
     
-    let mut image = original_image.to_rgb8();
-    
-    // 定义颜色 - 使用更鲜明的配色方案
-    let normal_color = Rgb([0u8, 200u8, 0u8]);     // 明绿色 - 正常
-    let abnormal_color = Rgb([220u8, 0u8, 0u8]);   // 明红色 - 异常
-    let default_color = Rgb([255u8, 165u8, 0u8]);  // 橙色 - 默认
+    draw_roi(&mut image, &detection_config::detection_roi());
     
     for detection in detections {
         let [x, y, w, h] = detection.bbox;
+        // 颜色、标签与最小尺寸按类别配置
+        let display = detection_config::class_display(&detection.class_name);
+        if w < display.min_box_size as f32 || h < display.min_box_size as f32 {
+            continue;
+        }
         
         // 确保坐标在图片范围内
         let img_width = image.width() as f32;
@@ -490,12 +1468,7 @@ fn draw_detections_on_image(
         let w = w.max(1.0).min(img_width - x as f32) as u32;
         let h = h.max(1.0).min(img_height - y as f32) as u32;
         
-        // 选择颜色
-        let color = match detection.class_name.as_str() {
-            "正常" => normal_color,
-            "异常" => abnormal_color,
-            _ => default_color,
-        };
+        let color = Rgb(display.color);
         
         // 绘制矩形框（加粗效果）
         let _rect = Rect::at(x, y).of_size(w, h);
@@ -507,36 +1480,46 @@ fn draw_detections_on_image(
             }
         }
         
-        // 绘制标签文本（如果有足够空间）
-        if y >= 20 {
-            // 创建清晰的标签文本
+        // 绘制标签文本（类别与置信度）
+        if display.show_label {
             let confidence_percent = (detection.confidence * 100.0) as u8;
             let label = format!("{}: {}%", 
                 detection.class_name, 
                 confidence_percent
             );
-            println!("[DEBUG] 绘制检测标签: {} (位置: {}, {})", label, x, y);
-            
-            // 在检测框上方绘制标签背景
-            let label_height = 20;
-            let label_width = label.len() as u32 * 8; // 估算文本宽度
-            
-            // 绘制标签背景
-            for dy in 0..label_height {
-                for dx in 0..label_width.min(image.width() - x as u32) {
-                    if let Some(pixel) = image.get_pixel_mut_checked(x as u32 + dx, (y - label_height as i32 + dy as i32) as u32) {
-                        *pixel = Rgb([0, 0, 0]); // 黑色背景
-                    }
-                }
-            }
+            tracing::debug!("绘制检测标签: {} (位置: {}, {})", label, x, y);
+            label_render::draw_label(&mut image, x, y, &label, color);
         }
     }
     
     Ok(image::DynamicImage::ImageRgb8(image))
 }
 
+/// 绘制检测区域轮廓与名称
+fn draw_roi(image: &mut image::RgbImage, regions: &[RoiPolygon]) {
+    use imageproc::drawing::draw_line_segment_mut;
+    use image::Rgb;
+
+    let color = Rgb(ROI_COLOR);
+    let (width, height) = image.dimensions();
+    for region in regions {
+        let points = region.to_pixels(width, height);
+        for (index, start) in points.iter().enumerate() {
+            let end = points[(index + 1) % points.len()];
+            // 加粗效果
+            for offset in [-1.0, 0.0, 1.0] {
+                draw_line_segment_mut(image, (start.0 + offset, start.1), (end.0 + offset, end.1), color);
+                draw_line_segment_mut(image, (start.0, start.1 + offset), (end.0, end.1 + offset), color);
+            }
+        }
+        if let (false, Some(first)) = (region.name.is_empty(), points.first()) {
+            label_render::draw_label(image, first.0 as i32, first.1 as i32, &region.name, color);
+        }
+    }
+}
+
 /// 将图片转换为base64编码
-fn image_to_base64(image: &image::DynamicImage) -> Result<String, String> {
+pub(crate) fn image_to_base64(image: &image::DynamicImage) -> Result<String, String> {
     use std::io::Cursor;
     use image::ImageFormat;
     
@@ -556,31 +1539,14 @@ fn image_to_base64(image: &image::DynamicImage) -> Result<String, String> {
 
 // ==================== 原有辅助函数 ====================
 
-/// 检查检测结果中的异常情况（对应PyQt5中的check_abnormal）
-fn check_for_abnormal_detections(result: &DetectionResult) -> Vec<String> {
-    let mut warnings = Vec::new();
-    
-    // TODO: 实现异常检测逻辑
-    // 基于置信度、检测数量等生成警告信息
-    
-    // 示例逻辑（需要根据实际需求调整）
-    if result.detections.is_empty() {
-        warnings.push("未检测到任何目标".to_string());
-    } else if result.detections.len() > 10 {
-        warnings.push(format!("检测到大量目标: {} 个", result.detections.len()));
-    }
-    
-    warnings
-}
-
 /// 验证输入文件是否存在且格式正确
-fn validate_input_file(file_path: &str) -> Result<(), String> {
+fn validate_input_file(file_path: &str) -> Result<(), DetectionError> {
     use std::path::Path;
     
     let path = Path::new(file_path);
     
     if !path.exists() {
-        return Err("文件不存在".to_string());
+        return Err(DetectionError::NotFound("文件不存在".to_string()));
     }
     
     // TODO: 添加文件格式验证
@@ -0,0 +1,260 @@
+/*!
+区域停留时长分析模块
+结合目标跟踪（跟踪ID）与区域多边形，统计每个被跟踪目标在各区域内连续停留的时长，
+超过阈值时发出 `zone://dwell` 事件（如工件卡在产线上不动）。
+未单独配置区域时使用检测区域（ROI）；视频文件按视频时间计时，实时输入按实际时间计时
+*/
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::detection_config;
+use crate::error::{self, DetectionError};
+use crate::yolo::roi::{self, RoiPolygon};
+use crate::yolo::DetectionResult;
+
+/// 停留超时事件
+pub const EVENT_ZONE_DWELL: &str = "zone://dwell";
+
+/// 目标短暂未检出（或框中心抖出区域）不超过该时长时视为仍在区域内
+const EXIT_GRACE_SECS: f64 = 1.0;
+
+/// 停留分析配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ZoneDwellConfig {
+    pub enabled: bool,
+    pub threshold_secs: f64,     // 停留超过该时长发出事件
+    pub zones: Vec<RoiPolygon>,  // 为空时使用检测区域
+}
+
+impl Default for ZoneDwellConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_secs: 10.0,
+            zones: Vec::new(),
+        }
+    }
+}
+
+impl ZoneDwellConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.threshold_secs <= 0.0 {
+            return Err(anyhow!("停留时长阈值必须大于0: {}", self.threshold_secs));
+        }
+        roi::validate(&self.zones)
+    }
+}
+
+/// 停留超时事件内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneDwellEvent {
+    pub source: String,
+    pub zone: String,
+    pub track_id: u64,
+    pub class_name: String,
+    pub dwell_secs: f64,
+    pub threshold_secs: f64,
+    pub bbox: [f32; 4],
+    pub entered_at: String,
+    pub timestamp: String,
+}
+
+/// 当前在区域内的目标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneDwell {
+    pub zone: String,
+    pub track_id: u64,
+    pub class_name: String,
+    pub entered_at: String,
+    pub dwell_secs: f64,
+    pub exceeded: bool,
+}
+
+/// 停留分析状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneDwellStatus {
+    pub config: ZoneDwellConfig,
+    pub zones: Vec<String>, // 实际使用的区域
+    pub dwells: Vec<ZoneDwell>,
+    pub events_emitted: u64,
+}
+
+struct DwellEntry {
+    class_name: String,
+    entered_secs: f64,
+    entered_at: String,
+    last_seen_secs: f64,
+    notified: bool,
+}
+
+#[derive(Default)]
+struct MonitorInner {
+    config: ZoneDwellConfig,
+    entries: HashMap<(u64, usize), DwellEntry>, // (跟踪ID, 区域下标)
+    now_secs: f64,
+    events_emitted: u64,
+}
+
+fn zone_label(zones: &[RoiPolygon], index: usize) -> String {
+    match zones.get(index) {
+        Some(zone) if !zone.name.is_empty() => zone.name.clone(),
+        _ => format!("区域{}", index + 1),
+    }
+}
+
+/// 区域停留分析（Tauri托管状态），实时检测管线启动时重置
+#[derive(Default)]
+pub struct DwellMonitor {
+    inner: Mutex<MonitorInner>,
+}
+
+impl DwellMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn zones(config: &ZoneDwellConfig) -> Vec<RoiPolygon> {
+        if config.zones.is_empty() {
+            detection_config::detection_roi()
+        } else {
+            config.zones.clone()
+        }
+    }
+
+    /// 用一帧带跟踪ID的检测结果更新停留时长，`now_secs` 为检测开始以来的时间
+    pub fn update(&self, app: &AppHandle, source: &str, result: &DetectionResult, now_secs: f64) {
+        let mut inner = self.inner.lock();
+        if !inner.config.enabled || result.track_ids.is_empty() {
+            return;
+        }
+        let zones = Self::zones(&inner.config);
+        if zones.is_empty() {
+            return;
+        }
+        inner.now_secs = now_secs;
+        let threshold_secs = inner.config.threshold_secs;
+        let image_size = (result.image_width, result.image_height);
+        let now = crate::storage::now_rfc3339();
+
+        let mut events = Vec::new();
+        for (detection, track_id) in result.detections.iter().zip(&result.track_ids) {
+            let Some(track_id) = *track_id else {
+                continue;
+            };
+            for (index, zone) in zones.iter().enumerate() {
+                if !roi::contains_detection(std::slice::from_ref(zone), detection, image_size) {
+                    continue;
+                }
+                let entry = inner.entries.entry((track_id, index)).or_insert_with(|| DwellEntry {
+                    class_name: detection.class_name.clone(),
+                    entered_secs: now_secs,
+                    entered_at: now.clone(),
+                    last_seen_secs: now_secs,
+                    notified: false,
+                });
+                entry.last_seen_secs = now_secs;
+                entry.class_name = detection.class_name.clone();
+
+                let dwell_secs = now_secs - entry.entered_secs;
+                if !entry.notified && dwell_secs >= threshold_secs {
+                    entry.notified = true;
+                    events.push(ZoneDwellEvent {
+                        source: source.to_string(),
+                        zone: zone_label(&zones, index),
+                        track_id,
+                        class_name: detection.class_name.clone(),
+                        dwell_secs,
+                        threshold_secs,
+                        bbox: detection.bbox,
+                        entered_at: entry.entered_at.clone(),
+                        timestamp: now.clone(),
+                    });
+                }
+            }
+        }
+        // 离开区域（或跟踪结束）的目标停止计时
+        inner.entries.retain(|_, entry| now_secs - entry.last_seen_secs <= EXIT_GRACE_SECS);
+        inner.events_emitted += events.len() as u64;
+        drop(inner);
+
+        for event in events {
+            tracing::info!(
+                "⏱️ 目标 #{} ({}) 在 {} 停留 {:.1} 秒，超过阈值 {:.1} 秒",
+                event.track_id, event.class_name, event.zone, event.dwell_secs, event.threshold_secs
+            );
+            let _ = app.emit(EVENT_ZONE_DWELL, event);
+        }
+    }
+
+    pub fn reset(&self) {
+        let mut inner = self.inner.lock();
+        inner.entries.clear();
+        inner.now_secs = 0.0;
+    }
+
+    pub fn set_config(&self, config: ZoneDwellConfig) -> Result<()> {
+        config.validate().map_err(error::invalid_input)?;
+        let mut inner = self.inner.lock();
+        // 区域变化后下标不再对应，重新计时
+        if inner.config.zones != config.zones {
+            inner.entries.clear();
+        }
+        inner.config = config;
+        Ok(())
+    }
+
+    pub fn status(&self) -> ZoneDwellStatus {
+        let inner = self.inner.lock();
+        let zones = Self::zones(&inner.config);
+        let mut dwells: Vec<ZoneDwell> = inner
+            .entries
+            .iter()
+            .map(|((track_id, index), entry)| {
+                let dwell_secs = inner.now_secs - entry.entered_secs;
+                ZoneDwell {
+                    zone: zone_label(&zones, *index),
+                    track_id: *track_id,
+                    class_name: entry.class_name.clone(),
+                    entered_at: entry.entered_at.clone(),
+                    dwell_secs,
+                    exceeded: dwell_secs >= inner.config.threshold_secs,
+                }
+            })
+            .collect();
+        dwells.sort_by(|a, b| b.dwell_secs.total_cmp(&a.dwell_secs));
+        ZoneDwellStatus {
+            config: inner.config.clone(),
+            zones: (0..zones.len()).map(|index| zone_label(&zones, index)).collect(),
+            dwells,
+            events_emitted: inner.events_emitted,
+        }
+    }
+}
+
+// ==================== Tauri命令实现 ====================
+
+/// 设置停留时长阈值与统计区域（区域为空时使用检测区域）
+#[tauri::command]
+pub async fn set_zone_dwell_config(
+    monitor: State<'_, DwellMonitor>,
+    config: ZoneDwellConfig
+) -> Result<ZoneDwellStatus, DetectionError> {
+    match monitor.set_config(config) {
+        Ok(()) => Ok(monitor.status()),
+        Err(e) => Err(DetectionError::from(e).context("设置停留分析参数失败")),
+    }
+}
+
+/// 查询当前在各区域内的目标及其停留时长
+#[tauri::command]
+pub async fn get_zone_dwell_status(
+    monitor: State<'_, DwellMonitor>
+) -> Result<ZoneDwellStatus, DetectionError> {
+    Ok(monitor.status())
+}
@@ -16,6 +16,7 @@ import { ImagePreview, type DetectionBox } from '@/components/ui/image-preview'
 import { Spinner } from '@/components/ui/spinner'
 import { Toaster } from '@/components/ui/toaster'
 import { useToast } from '@/lib/use-toast'
+import { errorMessage } from '@/lib/errors'
 import { 
   Camera, 
   Video, 
@@ -144,7 +145,7 @@ const YoloApp: React.FC = () => {
     } catch (error) {
       setState(prev => ({ 
         ...prev, 
-        error: `初始化失败: ${error}`, 
+        error: `初始化失败: ${errorMessage(error)}`, 
         loading: false,
         progress: 0
       }))
@@ -185,7 +186,7 @@ const YoloApp: React.FC = () => {
     } catch (error) {
       setState(prev => ({ 
         ...prev, 
-        error: `摄像头启动失败: ${error}`, 
+        error: `摄像头启动失败: ${errorMessage(error)}`, 
         loading: false 
       }))
     }
@@ -225,7 +226,7 @@ const YoloApp: React.FC = () => {
     } catch (error) {
       setState(prev => ({ 
         ...prev, 
-        error: `视频加载失败: ${error}`, 
+        error: `视频加载失败: ${errorMessage(error)}`, 
         loading: false 
       }))
     }
@@ -318,7 +319,7 @@ absolutePath = await resolve(filePath)
       console.error('File selection error:', error)
       toast({
         title: "选择文件失败",
-        description: `无法打开文件选择器: ${error}`,
+        description: `无法打开文件选择器: ${errorMessage(error)}`,
         variant: "destructive"
       })
     }
@@ -410,12 +411,12 @@ try {
       setState(prev => ({ 
         ...prev, 
         processingImage: false,
-        error: `图片处理失败: ${error}` 
+        error: `图片处理失败: ${errorMessage(error)}` 
       }))
       
       toast({
         title: "处理失败",
-        description: `图片处理时出现错误: ${error}`,
+        description: `图片处理时出现错误: ${errorMessage(error)}`,
         variant: "destructive"
       })
     }
@@ -469,7 +470,7 @@ try {
     } catch (error) {
       setState(prev => ({ 
         ...prev, 
-        error: `停止检测失败: ${error}` 
+        error: `停止检测失败: ${errorMessage(error)}` 
       }))
     }
   }
@@ -567,7 +568,7 @@ try {
     } catch (error) {
       setState(prev => ({ 
         ...prev, 
-        error: `重置配置失败: ${error}` 
+        error: `重置配置失败: ${errorMessage(error)}` 
       }))
     }
   }
@@ -0,0 +1,42 @@
+// 后端命令错误：所有命令失败时都抛出 { code, message }，code 为稳定的错误码（见 src-tauri/src/error.rs）
+export type DetectionErrorCode =
+  | 'MODEL_NOT_LOADED'
+  | 'UNSUPPORTED_FORMAT'
+  | 'INVALID_INPUT'
+  | 'NOT_FOUND'
+  | 'DEVICE_ERROR'
+  | 'BUSY'
+  | 'FEATURE_DISABLED'
+  | 'CANCELLED'
+  | 'IO_ERROR'
+  | 'INTERNAL'
+
+export interface DetectionError {
+  code: DetectionErrorCode
+  message: string
+}
+
+export function isDetectionError(error: unknown): error is DetectionError {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    typeof (error as DetectionError).code === 'string' &&
+    typeof (error as DetectionError).message === 'string'
+  )
+}
+
+// 取错误信息用于显示（命令参数反序列化失败时 Tauri 直接抛出字符串，不经过后端错误类型）
+export function errorMessage(error: unknown): string {
+  if (isDetectionError(error)) {
+    return error.message
+  }
+  if (error instanceof Error) {
+    return error.message
+  }
+  return String(error)
+}
+
+// 取错误码，非后端错误时为 undefined
+export function errorCode(error: unknown): DetectionErrorCode | undefined {
+  return isDetectionError(error) ? error.code : undefined
+}